@@ -17,7 +17,7 @@ use linux_embedded_hal::{
     Delay, SPIError, SpidevDevice, SysfsPin,
 };
 
-fn main() -> Result<(), SPIError> {
+fn main() -> Result<(), EpdError<SPIError>> {
     // Set up the device
     let mut spi = SpidevDevice::open("/dev/spidev0.0").expect("spidev directory");
     let options = SpidevOptions::new()
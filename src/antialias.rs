@@ -0,0 +1,190 @@
+//! Anti-aliased text for the panels with gray levels
+//!
+//! The fonts of embedded-graphics are bitmaps, drawn with hard black and white edges. A
+//! [`Supersampled`] canvas is twice as large as the area it stands for: text drawn on it with a
+//! font twice the wanted size is then downsampled, every 2x2 block of pixels giving a gray level
+//! out of the number of pixels set. The result, drawn with [`Supersampled::draw_on`] on a gray
+//! display such as a `VarDisplay<Gray16>`, is then sent and refreshed with the grayscale
+//! waveform of the panel.
+//!
+//!```rust, no_run
+//!# use embedded_hal_mock::eh1::*;
+//!# fn main() -> Result<(), epd_waveshare::error::EpdError<embedded_hal::spi::ErrorKind>> {
+//!use embedded_graphics::{
+//!    mono_font::{ascii::FONT_10X20, MonoTextStyle},
+//!    pixelcolor::{BinaryColor, Gray4},
+//!    prelude::*,
+//!    primitives::Rectangle,
+//!    text::{Baseline, Text},
+//!};
+//!use epd_waveshare::{
+//!    antialias::Supersampled, color::Gray16, epd6in0::*, graphics::VarDisplay, it8951::WaveformMode,
+//!    rect::Rect,
+//!};
+//!#
+//!# let expectations = [];
+//!# let mut spi = spi::Mock::new(&expectations);
+//!# let expectations = [];
+//!# let busy_in = digital::Mock::new(&expectations);
+//!# let rst = digital::Mock::new(&expectations);
+//!# let mut delay = delay::NoopDelay::new();
+//!
+//!let mut epd = Epd6in0::new(&mut spi, busy_in, rst, &mut delay, 1500, None)?;
+//!
+//!// 5x10 pixels characters, rendered with the 10x20 font
+//!let mut coverage = [0; 80 * 10];
+//!let area = Rectangle::new(Point::zero(), Size::new(80, 10));
+//!let mut canvas = Supersampled::new(area, &mut coverage).unwrap();
+//!let style = MonoTextStyle::new(&FONT_10X20, BinaryColor::On);
+//!let _ = Text::with_baseline("Hello world", Point::zero(), style, Baseline::Top).draw(&mut canvas);
+//!
+//!let mut buffer = [0; 80 / 2 * 10];
+//!let mut display = VarDisplay::<Gray16>::new(80, 10, &mut buffer, false).unwrap();
+//!let _ = canvas.draw_on(&mut display, Gray4::BLACK, Gray4::WHITE);
+//!
+//!epd.update_partial_frame(&mut spi, &mut delay, display.buffer(), 0, 0, 80, 10)?;
+//!epd.display_partial_frame(&mut spi, &mut delay, Rect::new(0, 0, 80, 10), WaveformMode::Gc16)?;
+//!# Ok(())
+//!# }
+//!```
+
+use embedded_graphics_core::{
+    pixelcolor::{BinaryColor, Gray4, GrayColor},
+    prelude::*,
+    primitives::Rectangle,
+};
+
+/// Number of pixels of the canvas in each direction per pixel of the area
+const SCALE: u32 = 2;
+
+/// Canvas with twice the resolution of an area, downsampled to gray levels
+///
+/// Only the pixels drawn with [`BinaryColor::On`] count, each pixel of the area keeps in a byte of
+/// the coverage buffer how many of its 4 pixels are set.
+pub struct Supersampled<'a> {
+    area: Rectangle,
+    coverage: &'a mut [u8],
+}
+
+impl<'a> Supersampled<'a> {
+    /// Creates a canvas for `area`, with a coverage buffer of one byte per pixel of the area
+    ///
+    /// Returns `None` if the buffer is too small.
+    pub fn new(area: Rectangle, coverage: &'a mut [u8]) -> Option<Self> {
+        let len = area.size.width as usize * area.size.height as usize;
+        let coverage = coverage.get_mut(..len)?;
+        coverage.fill(0);
+        Some(Supersampled { area, coverage })
+    }
+
+    /// The area of the target the canvas stands for
+    pub fn area(&self) -> Rectangle {
+        self.area
+    }
+
+    /// Forgets everything drawn so far
+    pub fn clear_coverage(&mut self) {
+        self.coverage.fill(0);
+    }
+
+    /// Draws the downsampled pixels on the area of `target`
+    ///
+    /// Every pixel of the area is drawn, with a gray level between `background` (none of its
+    /// pixels set) and `foreground` (all of them set).
+    pub fn draw_on<D>(
+        &self,
+        target: &mut D,
+        foreground: Gray4,
+        background: Gray4,
+    ) -> Result<(), D::Error>
+    where
+        D: DrawTarget,
+        D::Color: From<Gray4>,
+    {
+        let samples = SCALE * SCALE;
+        let pixels = self
+            .area
+            .points()
+            .zip(self.coverage.iter())
+            .map(|(point, &count)| {
+                let count = u32::from(count);
+                let luma = (u32::from(background.luma()) * (samples - count)
+                    + u32::from(foreground.luma()) * count
+                    + samples / 2)
+                    / samples;
+                Pixel(point, Gray4::new(luma as u8).into())
+            });
+        target.draw_iter(pixels)
+    }
+}
+
+impl DrawTarget for Supersampled<'_> {
+    type Color = BinaryColor;
+    type Error = core::convert::Infallible;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        let size = self.size();
+        for Pixel(point, color) in pixels {
+            if color.is_off()
+                || point.x < 0
+                || point.y < 0
+                || point.x as u32 >= size.width
+                || point.y as u32 >= size.height
+            {
+                continue;
+            }
+            let index =
+                (point.y as u32 / SCALE * self.area.size.width + point.x as u32 / SCALE) as usize;
+            self.coverage[index] = self.coverage[index].saturating_add(1);
+        }
+        Ok(())
+    }
+}
+
+impl OriginDimensions for Supersampled<'_> {
+    fn size(&self) -> Size {
+        self.area.size * SCALE
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::Gray16;
+    use crate::graphics::VarDisplay;
+    use embedded_graphics::primitives::{Primitive, PrimitiveStyle};
+
+    #[test]
+    fn supersampled_coverage() {
+        let mut coverage = [0xff; 4 * 2];
+        let area = Rectangle::new(Point::zero(), Size::new(4, 2));
+        let mut canvas = Supersampled::new(area, &mut coverage).unwrap();
+        assert_eq!(canvas.size(), Size::new(8, 4));
+
+        // one pixel fully covered, one half covered and one with a single pixel set
+        Rectangle::new(Point::zero(), Size::new(3, 2))
+            .into_styled(PrimitiveStyle::with_fill(BinaryColor::On))
+            .draw(&mut canvas)
+            .unwrap();
+        Pixel(Point::new(7, 3), BinaryColor::On)
+            .draw(&mut canvas)
+            .unwrap();
+
+        let mut buffer = [0; 4];
+        let mut display = VarDisplay::<Gray16>::new(4, 2, &mut buffer, false).unwrap();
+        canvas
+            .draw_on(&mut display, Gray4::BLACK, Gray4::WHITE)
+            .unwrap();
+        assert_eq!(display.buffer(), [0x08, 0xff, 0xff, 0xfb]);
+    }
+
+    #[test]
+    fn supersampled_buffer_too_small() {
+        let mut coverage = [0; 7];
+        let area = Rectangle::new(Point::zero(), Size::new(4, 2));
+        assert!(Supersampled::new(area, &mut coverage).is_none());
+    }
+}
@@ -0,0 +1,410 @@
+//! A driver chosen at runtime
+//!
+//! An application supporting several panels has to be generic over the driver, or to know the
+//! panel when it's compiled. [`AnyEpd`] is one of the drivers, created from the [`Model`] of the
+//! panel, e.g. read from a configuration at boot, with the operations shared by all of them:
+//! the frames are the buffers of the driver of the model, of its
+//! [`width`](AnyEpd::width) and [`height`](AnyEpd::height).
+//!
+//! The models are the drivers enabled by the cargo features, except the ones which aren't created
+//! from an SPI device and the BUSY, DC and RST pins: the epd13in3e has a second chip select, the
+//! epd12in48b_v2 drives its four controllers through pins of its own, the panels of the
+//! [IT8951](crate::it8951) controller (epd6in0, epd7in8, epd9in7 and epd10in3) have no DC pin
+//! and the lilygo4in7 is driven through a parallel bus. The drivers of the [`inky`](crate::inky)
+//! boards, which aren't modules of the crate root, aren't models either.
+//!
+//!```rust, no_run
+//!# use embedded_hal_mock::eh1::*;
+//!# fn main() -> Result<(), epd_waveshare::error::EpdError<embedded_hal::spi::ErrorKind>> {
+//!use epd_waveshare::any_epd::{AnyEpd, Model};
+//!use epd_waveshare::{buffer_len, prelude::*};
+//!#
+//!# let expectations = [];
+//!# let mut spi = spi::Mock::new(&expectations);
+//!# let expectations = [];
+//!# let busy_in = digital::Mock::new(&expectations);
+//!# let dc = digital::Mock::new(&expectations);
+//!# let rst = digital::Mock::new(&expectations);
+//!# let mut delay = delay::NoopDelay::new();
+//!
+//!let model = Model::from_name("epd2in9_v2").unwrap_or(Model::Epd1in54);
+//!let mut epd = AnyEpd::new(model, &mut spi, busy_in, dc, rst, &mut delay, None)?;
+//!
+//!// large enough for both models
+//!let frame = [0xff; 200 / 8 * 296];
+//!let len = buffer_len(epd.width() as usize, epd.height() as usize);
+//!epd.update_and_display_frame(&mut spi, &frame[..len], &mut delay)?;
+//!epd.sleep(&mut spi, &mut delay)?;
+//!# Ok(())
+//!# }
+//!```
+
+use core::borrow::Borrow;
+use core::convert::Infallible;
+use core::marker::PhantomData;
+
+use crate::error::EpdError;
+use crate::pin::BusyPin;
+use crate::traits::{RefreshLut, ResetTiming};
+use embedded_hal::{delay::*, digital::*, spi::SpiDevice};
+
+macro_rules! any_epd {
+    ($(#[cfg($cfg:meta)] $model:ident = $name:literal => $epd:ty,)*) => {
+        /// A panel supported by [`AnyEpd`]
+        #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+        #[cfg_attr(feature = "defmt", derive(defmt::Format))]
+        pub enum Model {
+            $(
+                #[cfg($cfg)]
+                #[doc = concat!("[`", $name, "`](crate::", $name, ")")]
+                $model,
+            )*
+        }
+
+        impl Model {
+            /// All the models
+            pub const ALL: &'static [Model] = &[$(#[cfg($cfg)] Model::$model,)*];
+
+            /// Name of the module of the driver, e.g. `"epd2in9_v2"`
+            pub const fn name(self) -> &'static str {
+                match self {
+                    $(#[cfg($cfg)] Model::$model => $name,)*
+                }
+            }
+        }
+
+        /// One of the drivers, see the [module](self)
+        #[allow(clippy::large_enum_variant)]
+        pub enum AnyEpd<SPI, BUSY, DC, RST, DELAY> {
+            $(
+                #[cfg($cfg)]
+                #[doc = concat!("Driver of [`Model::", stringify!($model), "`]")]
+                $model($epd),
+            )*
+            #[doc(hidden)]
+            _Unused(Infallible, PhantomData<(SPI, BUSY, DC, RST, DELAY)>),
+        }
+
+        #[cfg_attr(not(any($($cfg),*)), allow(unused_variables, unreachable_code))]
+        impl<SPI, BUSY, DC, RST, DELAY> AnyEpd<SPI, BUSY, DC, RST, DELAY>
+        where
+            SPI: SpiDevice,
+            BUSY: BusyPin,
+            DC: OutputPin,
+            RST: OutputPin,
+            DELAY: DelayNs,
+        {
+            /// Creates the driver of `model`, see [`WaveshareDisplay::new`]
+            pub fn new(
+                model: Model,
+                spi: &mut SPI,
+                busy: BUSY,
+                dc: DC,
+                rst: RST,
+                delay: &mut DELAY,
+                delay_us: Option<u32>,
+            ) -> Result<Self, EpdError<SPI::Error>> {
+                Ok(match model {
+                    $(
+                        #[cfg($cfg)]
+                        Model::$model => AnyEpd::$model(<$epd>::new(
+                            spi, busy, dc, rst, delay, delay_us,
+                        )?),
+                    )*
+                })
+            }
+
+            /// Model of the driver
+            pub fn model(&self) -> Model {
+                match self {
+                    $(#[cfg($cfg)] AnyEpd::$model(_) => Model::$model,)*
+                    AnyEpd::_Unused(never, _) => absurd(never),
+                }
+            }
+        }
+
+        // without any model, the arguments of the methods aren't used
+        macro_rules! without_models {
+            ($item:item) => {
+                #[cfg_attr(not(any($($cfg),*)), allow(unused_imports, unused_variables))]
+                $item
+            };
+        }
+
+        // calls the method of the driver of any model
+        macro_rules! dispatch {
+            ($self:ident, $driver:ident => $call:expr) => {
+                match $self {
+                    $(#[cfg($cfg)] AnyEpd::$model($driver) => $call,)*
+                    AnyEpd::_Unused(never, _) => absurd(never),
+                }
+            };
+        }
+    };
+}
+
+/// Value of the variant which can't be created
+fn absurd<T>(never: impl Borrow<Infallible>) -> T {
+    match *never.borrow() {}
+}
+
+any_epd! {
+    #[cfg(feature = "epd1in02")]
+    Epd1in02 = "epd1in02" => crate::epd1in02::Epd1in02<SPI, BUSY, DC, RST, DELAY>,
+    #[cfg(feature = "epd1in54")]
+    Epd1in54 = "epd1in54" => crate::epd1in54::Epd1in54<SPI, BUSY, DC, RST, DELAY>,
+    #[cfg(feature = "epd1in54_v2")]
+    Epd1in54V2 = "epd1in54_v2" => crate::epd1in54_v2::Epd1in54<SPI, BUSY, DC, RST, DELAY>,
+    #[cfg(feature = "epd1in54_v3")]
+    Epd1in54V3 = "epd1in54_v3" => crate::epd1in54_v3::Epd1in54<SPI, BUSY, DC, RST, DELAY>,
+    #[cfg(feature = "epd1in54b")]
+    Epd1in54b = "epd1in54b" => crate::epd1in54b::Epd1in54b<SPI, BUSY, DC, RST, DELAY>,
+    #[cfg(feature = "epd1in54c")]
+    Epd1in54c = "epd1in54c" => crate::epd1in54c::Epd1in54c<SPI, BUSY, DC, RST, DELAY>,
+    #[cfg(any(feature = "epd2in13_v2", feature = "epd2in13_v3"))]
+    Epd2in13V2 = "epd2in13_v2" => crate::epd2in13_v2::Epd2in13<SPI, BUSY, DC, RST, DELAY>,
+    #[cfg(feature = "epd2in13_v4")]
+    Epd2in13V4 = "epd2in13_v4" => crate::epd2in13_v4::Epd2in13<SPI, BUSY, DC, RST, DELAY>,
+    #[cfg(feature = "epd2in13b_v4")]
+    Epd2in13bV4 = "epd2in13b_v4" => crate::epd2in13b_v4::Epd2in13b<SPI, BUSY, DC, RST, DELAY>,
+    #[cfg(feature = "epd2in13bc")]
+    Epd2in13bc = "epd2in13bc" => crate::epd2in13bc::Epd2in13bc<SPI, BUSY, DC, RST, DELAY>,
+    #[cfg(feature = "epd2in13d")]
+    Epd2in13d = "epd2in13d" => crate::epd2in13d::Epd2in13d<SPI, BUSY, DC, RST, DELAY>,
+    #[cfg(feature = "epd2in36g")]
+    Epd2in36g = "epd2in36g" => crate::epd2in36g::Epd2in36g<SPI, BUSY, DC, RST, DELAY>,
+    #[cfg(feature = "epd2in66")]
+    Epd2in66 = "epd2in66" => crate::epd2in66::Epd2in66<SPI, BUSY, DC, RST, DELAY>,
+    #[cfg(feature = "epd2in66b")]
+    Epd2in66b = "epd2in66b" => crate::epd2in66b::Epd2in66b<SPI, BUSY, DC, RST, DELAY>,
+    #[cfg(feature = "epd2in7")]
+    Epd2in7 = "epd2in7" => crate::epd2in7::Epd2in7<SPI, BUSY, DC, RST, DELAY>,
+    #[cfg(feature = "epd2in7_v2")]
+    Epd2in7V2 = "epd2in7_v2" => crate::epd2in7_v2::Epd2in7<SPI, BUSY, DC, RST, DELAY>,
+    #[cfg(feature = "epd2in7b")]
+    Epd2in7b = "epd2in7b" => crate::epd2in7b::Epd2in7b<SPI, BUSY, DC, RST, DELAY>,
+    #[cfg(feature = "epd2in7b_v2")]
+    Epd2in7bV2 = "epd2in7b_v2" => crate::epd2in7b_v2::Epd2in7b<SPI, BUSY, DC, RST, DELAY>,
+    #[cfg(feature = "epd2in9")]
+    Epd2in9 = "epd2in9" => crate::epd2in9::Epd2in9<SPI, BUSY, DC, RST, DELAY>,
+    #[cfg(feature = "epd2in9_v2")]
+    Epd2in9V2 = "epd2in9_v2" => crate::epd2in9_v2::Epd2in9<SPI, BUSY, DC, RST, DELAY>,
+    #[cfg(feature = "epd2in9b_v4")]
+    Epd2in9bV4 = "epd2in9b_v4" => crate::epd2in9b_v4::Epd2in9b<SPI, BUSY, DC, RST, DELAY>,
+    #[cfg(feature = "epd2in9bc")]
+    Epd2in9bc = "epd2in9bc" => crate::epd2in9bc::Epd2in9bc<SPI, BUSY, DC, RST, DELAY>,
+    #[cfg(feature = "epd2in9d")]
+    Epd2in9d = "epd2in9d" => crate::epd2in9d::Epd2in9d<'static, SPI, BUSY, DC, RST, DELAY>,
+    #[cfg(feature = "epd3in0g")]
+    Epd3in0g = "epd3in0g" => crate::epd3in0g::Epd3in0g<SPI, BUSY, DC, RST, DELAY>,
+    #[cfg(feature = "epd3in52")]
+    Epd3in52 = "epd3in52" => crate::epd3in52::Epd3in52<SPI, BUSY, DC, RST, DELAY>,
+    #[cfg(feature = "epd3in7")]
+    Epd3in7 = "epd3in7" => crate::epd3in7::EPD3in7<SPI, BUSY, DC, RST, DELAY>,
+    #[cfg(feature = "epd4in01f")]
+    Epd4in01f = "epd4in01f" => crate::epd4in01f::Epd4in01f<SPI, BUSY, DC, RST, DELAY>,
+    #[cfg(feature = "epd4in2")]
+    Epd4in2 = "epd4in2" => crate::epd4in2::Epd4in2<SPI, BUSY, DC, RST, DELAY>,
+    #[cfg(feature = "epd4in26")]
+    Epd4in26 = "epd4in26" => crate::epd4in26::Epd4in26<SPI, BUSY, DC, RST, DELAY>,
+    #[cfg(feature = "epd5in65f")]
+    Epd5in65f = "epd5in65f" => crate::epd5in65f::Epd5in65f<SPI, BUSY, DC, RST, DELAY>,
+    #[cfg(feature = "epd5in79")]
+    Epd5in79 = "epd5in79" => crate::epd5in79::Epd5in79<SPI, BUSY, DC, RST, DELAY>,
+    #[cfg(feature = "epd5in83_v2")]
+    Epd5in83V2 = "epd5in83_v2" => crate::epd5in83_v2::Epd5in83<SPI, BUSY, DC, RST, DELAY>,
+    #[cfg(feature = "epd5in83b_v2")]
+    Epd5in83bV2 = "epd5in83b_v2" => crate::epd5in83b_v2::Epd5in83<SPI, BUSY, DC, RST, DELAY>,
+    #[cfg(feature = "epd7in3f")]
+    Epd7in3f = "epd7in3f" => crate::epd7in3f::Epd7in3f<SPI, BUSY, DC, RST, DELAY>,
+    #[cfg(feature = "epd7in5")]
+    Epd7in5 = "epd7in5" => crate::epd7in5::Epd7in5<SPI, BUSY, DC, RST, DELAY>,
+    #[cfg(feature = "epd7in5_hd")]
+    Epd7in5Hd = "epd7in5_hd" => crate::epd7in5_hd::Epd7in5<SPI, BUSY, DC, RST, DELAY>,
+    #[cfg(feature = "epd7in5_v2")]
+    Epd7in5V2 = "epd7in5_v2" => crate::epd7in5_v2::Epd7in5<SPI, BUSY, DC, RST, DELAY>,
+    #[cfg(feature = "epd7in5b_v2")]
+    Epd7in5bV2 = "epd7in5b_v2" => crate::epd7in5b_v2::Epd7in5<SPI, BUSY, DC, RST, DELAY>,
+    #[cfg(feature = "epd10in2")]
+    Epd10in2 = "epd10in2" => crate::epd10in2::Epd10in2<SPI, BUSY, DC, RST, DELAY>,
+    #[cfg(feature = "epd13in3k")]
+    Epd13in3k = "epd13in3k" => crate::epd13in3k::Epd13in3k<SPI, BUSY, DC, RST, DELAY>,
+    #[cfg(feature = "weact2in9")]
+    WeAct2in9 = "weact2in9" => crate::weact2in9::WeAct2in9<SPI, BUSY, DC, RST, DELAY>,
+    #[cfg(feature = "weact4in2")]
+    WeAct4in2 = "weact4in2" => crate::weact4in2::WeAct4in2<SPI, BUSY, DC, RST, DELAY>,
+    #[cfg(feature = "magtag2in9")]
+    MagTag2in9 = "magtag2in9" => crate::magtag2in9::MagTag2in9<SPI, BUSY, DC, RST, DELAY>,
+    #[cfg(feature = "badger2040")]
+    Badger2040 = "badger2040" => crate::badger2040::Badger2040<SPI, BUSY, DC, RST, DELAY>,
+}
+
+without_models! {
+    use crate::traits::WaveshareDisplay;
+}
+
+impl Model {
+    /// Model of the driver of module `name`, e.g. `"epd2in9_v2"`
+    pub fn from_name(name: &str) -> Option<Model> {
+        Model::ALL
+            .iter()
+            .copied()
+            .find(|model| model.name() == name)
+    }
+}
+
+without_models! {
+    impl<SPI, BUSY, DC, RST, DELAY> AnyEpd<SPI, BUSY, DC, RST, DELAY>
+    where
+        SPI: SpiDevice,
+        BUSY: BusyPin,
+        DC: OutputPin,
+        RST: OutputPin,
+        DELAY: DelayNs,
+    {
+        /// Width of the panel, see [`WaveshareDisplay::width`]
+        pub fn width(&self) -> u32 {
+            dispatch!(self, epd => epd.width())
+        }
+
+        /// Height of the panel, see [`WaveshareDisplay::height`]
+        pub fn height(&self) -> u32 {
+            dispatch!(self, epd => epd.height())
+        }
+
+        /// See [`WaveshareDisplay::sleep`]
+        pub fn sleep(&mut self, spi: &mut SPI, delay: &mut DELAY) -> Result<(), EpdError<SPI::Error>> {
+            dispatch!(self, epd => epd.sleep(spi, delay))
+        }
+
+        /// See [`WaveshareDisplay::wake_up`]
+        pub fn wake_up(
+            &mut self,
+            spi: &mut SPI,
+            delay: &mut DELAY,
+        ) -> Result<(), EpdError<SPI::Error>> {
+            dispatch!(self, epd => epd.wake_up(spi, delay))
+        }
+
+        /// See [`WaveshareDisplay::update_frame`]
+        pub fn update_frame(
+            &mut self,
+            spi: &mut SPI,
+            buffer: &[u8],
+            delay: &mut DELAY,
+        ) -> Result<(), EpdError<SPI::Error>> {
+            dispatch!(self, epd => epd.update_frame(spi, buffer, delay))
+        }
+
+        /// See [`WaveshareDisplay::update_partial_frame`]
+        #[allow(clippy::too_many_arguments)]
+        pub fn update_partial_frame(
+            &mut self,
+            spi: &mut SPI,
+            delay: &mut DELAY,
+            buffer: &[u8],
+            x: u32,
+            y: u32,
+            width: u32,
+            height: u32,
+        ) -> Result<(), EpdError<SPI::Error>> {
+            dispatch!(self, epd => epd.update_partial_frame(spi, delay, buffer, x, y, width, height))
+        }
+
+        /// See [`WaveshareDisplay::display_frame`]
+        pub fn display_frame(
+            &mut self,
+            spi: &mut SPI,
+            delay: &mut DELAY,
+        ) -> Result<(), EpdError<SPI::Error>> {
+            dispatch!(self, epd => epd.display_frame(spi, delay))
+        }
+
+        /// See [`WaveshareDisplay::update_and_display_frame`]
+        pub fn update_and_display_frame(
+            &mut self,
+            spi: &mut SPI,
+            buffer: &[u8],
+            delay: &mut DELAY,
+        ) -> Result<(), EpdError<SPI::Error>> {
+            dispatch!(self, epd => epd.update_and_display_frame(spi, buffer, delay))
+        }
+
+        /// See [`WaveshareDisplay::clear_frame`]
+        pub fn clear_frame(
+            &mut self,
+            spi: &mut SPI,
+            delay: &mut DELAY,
+        ) -> Result<(), EpdError<SPI::Error>> {
+            dispatch!(self, epd => epd.clear_frame(spi, delay))
+        }
+
+        /// See [`WaveshareDisplay::set_lut`]
+        pub fn set_lut(
+            &mut self,
+            spi: &mut SPI,
+            delay: &mut DELAY,
+            refresh_rate: Option<RefreshLut>,
+        ) -> Result<(), EpdError<SPI::Error>> {
+            dispatch!(self, epd => epd.set_lut(spi, delay, refresh_rate))
+        }
+
+        /// See [`WaveshareDisplay::set_busy_timeout`]
+        pub fn set_busy_timeout(&mut self, timeout_us: Option<u32>) {
+            dispatch!(self, epd => epd.set_busy_timeout(timeout_us))
+        }
+
+        /// See [`WaveshareDisplay::set_busy_poll`]
+        pub fn set_busy_poll(&mut self, interval_us: u32) {
+            dispatch!(self, epd => epd.set_busy_poll(interval_us))
+        }
+
+        /// See [`WaveshareDisplay::set_on_busy_wait`]
+        pub fn set_on_busy_wait(&mut self, hook: Option<fn(u32)>) {
+            dispatch!(self, epd => epd.set_on_busy_wait(hook))
+        }
+
+        /// See [`WaveshareDisplay::set_reset_timing`]
+        pub fn set_reset_timing(&mut self, timing: Option<ResetTiming>) {
+            dispatch!(self, epd => epd.set_reset_timing(timing))
+        }
+
+        /// See [`WaveshareDisplay::set_max_transfer`]
+        pub fn set_max_transfer(&mut self, max_bytes: Option<usize>) {
+            dispatch!(self, epd => epd.set_max_transfer(max_bytes))
+        }
+
+        /// See [`WaveshareDisplay::set_single_byte_write`]
+        pub fn set_single_byte_write(&mut self, single_byte_write: bool) {
+            dispatch!(self, epd => epd.set_single_byte_write(single_byte_write))
+        }
+
+        /// See [`WaveshareDisplay::release`]
+        pub fn release(self) -> (BUSY, DC, RST) {
+            dispatch!(self, epd => epd.release())
+        }
+
+        /// See [`WaveshareDisplay::wait_until_idle`]
+        pub fn wait_until_idle(
+            &mut self,
+            spi: &mut SPI,
+            delay: &mut DELAY,
+        ) -> Result<(), EpdError<SPI::Error>> {
+            dispatch!(self, epd => epd.wait_until_idle(spi, delay))
+        }
+    }
+}
+
+#[cfg(all(test, feature = "all-displays"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn model_names() {
+        for model in Model::ALL {
+            assert_eq!(Model::from_name(model.name()), Some(*model));
+        }
+        assert_eq!(Model::from_name("epd2in9_v2"), Some(Model::Epd2in9V2));
+        assert_eq!(Model::from_name("epd2in9_v9"), None);
+    }
+}
@@ -0,0 +1,546 @@
+//! A simple Driver for the 2.9" E-Ink Display of the Pimoroni Badger 2040 via SPI
+//!
+//! The 296x128 panel (UC8151 controller) of the Badger 2040 and of the Pimoroni Pico Inky Pack
+//! is refreshed with the waveform of the controller OTP. Like the update speeds of
+//! PicoGraphics, [`Speed`] trades the quality of the refreshes for their duration: the faster
+//! levels raise the frame rate the waveform is played at, so it lasts less and leaves more
+//! ghosting. The custom waveforms of the Pimoroni library aren't included, a waveform of the
+//! application can be loaded in the LUT registers with
+//! [`set_custom_lut`](Badger2040::set_custom_lut).
+//!
+//! The panel is portrait, rotate the display to draw in the landscape orientation of the Badger.
+//!
+//! # Example for the Badger 2040
+//!
+//!```rust, no_run
+//!# use embedded_hal_mock::eh1::*;
+//!# fn main() -> Result<(), epd_waveshare::error::EpdError<embedded_hal::spi::ErrorKind>> {
+//!use embedded_graphics::{prelude::*, primitives::{PrimitiveStyle, Rectangle}};
+//!use epd_waveshare::{badger2040::*, graphics::DisplayRotation, prelude::*};
+//!#
+//!# let expectations = [];
+//!# let mut spi = spi::Mock::new(&expectations);
+//!# let expectations = [];
+//!# let busy_in = digital::Mock::new(&expectations);
+//!# let dc = digital::Mock::new(&expectations);
+//!# let rst = digital::Mock::new(&expectations);
+//!# let mut delay = delay::NoopDelay::new();
+//!
+//!let mut epd = Badger2040::new(&mut spi, busy_in, dc, rst, &mut delay, None)?;
+//!epd.set_speed(&mut spi, &mut delay, Speed::Fast)?;
+//!
+//!let mut display = DisplayBadger2040::default();
+//!display.set_rotation(DisplayRotation::Rotate270);
+//!display.clear(Color::White).ok();
+//!let _ = Rectangle::new(Point::new(8, 8), Size::new(100, 40))
+//!    .into_styled(PrimitiveStyle::with_fill(Color::Black))
+//!    .draw(&mut display);
+//!
+//!epd.update_and_display_frame(&mut spi, display.buffer(), &mut delay)?;
+//!epd.sleep(&mut spi, &mut delay)?;
+//!# Ok(())
+//!# }
+//!```
+
+use crate::error::EpdError;
+use crate::pin::BusyPin;
+use embedded_hal::{delay::*, digital::*, spi::SpiDevice};
+
+use crate::buffer_len;
+use crate::color::Color;
+use crate::epd2in9d::command::Command;
+use crate::interface::DisplayInterface;
+use crate::traits::{InterfaceConfig, InternalWiAdditions, RefreshLut, WaveshareDisplay};
+
+pub use crate::epd3in52::Lut;
+#[cfg(feature = "async")]
+use crate::interface::DisplayInterfaceAsync;
+#[cfg(feature = "async")]
+use crate::traits::{AsyncBusyPin, InternalWiAdditionsAsync, WaveshareDisplayAsync};
+#[cfg(feature = "async")]
+use embedded_hal_async::{delay::DelayNs as AsyncDelayNs, spi::SpiDevice as AsyncSpiDevice};
+
+/// Width of the display in pixels
+pub const WIDTH: u32 = 128;
+/// Height of the display in pixels
+pub const HEIGHT: u32 = 296;
+/// Default Background Color (white)
+pub const DEFAULT_BACKGROUND_COLOR: Color = Color::White;
+const IS_BUSY_LOW: bool = true;
+const SINGLE_BYTE_WRITE: bool = false;
+
+/// Display with Fullsize buffer for use with the Badger 2040
+#[cfg(feature = "graphics")]
+pub type DisplayBadger2040 = crate::graphics::Display<
+    WIDTH,
+    HEIGHT,
+    false,
+    { crate::graphics::display_len::<Color>(WIDTH, HEIGHT) },
+    Color,
+>;
+
+/// Panel setting with the LUT of the OTP, black and white mode
+const PANEL_SETTING_OTP: u8 = 0x1F;
+/// Panel setting with the LUT of the registers, black and white mode
+const PANEL_SETTING_REGISTERS: u8 = 0x3F;
+
+/// Update speeds, from the slowest and cleanest to the fastest
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Speed {
+    /// 50Hz, the frame rate the waveform is made for
+    #[default]
+    Default,
+    /// 100Hz
+    Medium,
+    /// 150Hz
+    Fast,
+    /// 200Hz, for menus and other content updated often
+    Turbo,
+}
+
+impl Speed {
+    /// Value of the PLL control register
+    fn pll(self) -> u8 {
+        match self {
+            Speed::Default => 0x3C,
+            Speed::Medium => 0x3A,
+            Speed::Fast => 0x29,
+            Speed::Turbo => 0x39,
+        }
+    }
+}
+
+#[maybe_async_cfg::maybe(
+    sync(keep_self),
+    async(
+        feature = "async",
+        idents(
+            Badger2040,
+            DisplayInterface,
+            WaveshareDisplay,
+            InternalWiAdditions,
+            SpiDevice(async = "AsyncSpiDevice"),
+            BusyPin(async = "AsyncBusyPin"),
+            DelayNs(async = "AsyncDelayNs")
+        )
+    )
+)]
+/// Badger 2040 driver
+pub struct Badger2040<SPI, BUSY, DC, RST, DELAY> {
+    /// Connection Interface
+    interface: DisplayInterface<SPI, BUSY, DC, RST, DELAY, SINGLE_BYTE_WRITE>,
+    /// Background Color
+    background_color: Color,
+    /// Update speed
+    speed: Speed,
+}
+
+#[maybe_async_cfg::maybe(
+    sync(keep_self),
+    async(
+        feature = "async",
+        idents(
+            Badger2040,
+            DisplayInterface,
+            WaveshareDisplay,
+            InternalWiAdditions,
+            SpiDevice(async = "AsyncSpiDevice"),
+            BusyPin(async = "AsyncBusyPin"),
+            DelayNs(async = "AsyncDelayNs")
+        )
+    )
+)]
+impl<SPI, BUSY, DC, RST, DELAY> InternalWiAdditions<SPI, BUSY, DC, RST, DELAY>
+    for Badger2040<SPI, BUSY, DC, RST, DELAY>
+where
+    SPI: SpiDevice,
+    BUSY: BusyPin,
+    DC: OutputPin,
+    RST: OutputPin,
+    DELAY: DelayNs,
+{
+    async fn init(&mut self, spi: &mut SPI, delay: &mut DELAY) -> Result<(), EpdError<SPI::Error>> {
+        self.interface.reset(delay, 10_000, 10_000).await?;
+
+        // internal power, VDH/VDL at +-11V, VDHR at 11V
+        self.interface
+            .cmd_with_data(spi, Command::PowerSetting, &[0x03, 0x00, 0x2B, 0x2B, 0x2B])
+            .await?;
+        self.interface
+            .cmd_with_data(spi, Command::BoosterSoftStart, &[0x17, 0x17, 0x17])
+            .await?;
+        self.interface.cmd(spi, Command::PowerOn).await?;
+        self.wait_until_idle(spi, delay).await?;
+
+        self.interface
+            .cmd_with_data(spi, Command::PanelSetting, &[PANEL_SETTING_OTP])
+            .await?;
+        self.interface
+            .cmd_with_data(spi, Command::PllControl, &[self.speed.pll()])
+            .await?;
+        self.interface
+            .cmd_with_data(spi, Command::VcomAndDataIntervalSetting, &[0x97])
+            .await?;
+        self.interface
+            .cmd_with_data(
+                spi,
+                Command::ResolutionSetting,
+                &[WIDTH as u8, (HEIGHT >> 8) as u8, HEIGHT as u8],
+            )
+            .await
+    }
+}
+
+#[maybe_async_cfg::maybe(
+    sync(keep_self),
+    async(
+        feature = "async",
+        idents(
+            Badger2040,
+            DisplayInterface,
+            WaveshareDisplay,
+            InternalWiAdditions,
+            SpiDevice(async = "AsyncSpiDevice"),
+            BusyPin(async = "AsyncBusyPin"),
+            DelayNs(async = "AsyncDelayNs")
+        )
+    )
+)]
+impl<SPI, BUSY, DC, RST, DELAY> WaveshareDisplay<SPI, BUSY, DC, RST, DELAY>
+    for Badger2040<SPI, BUSY, DC, RST, DELAY>
+where
+    SPI: SpiDevice,
+    BUSY: BusyPin,
+    DC: OutputPin,
+    RST: OutputPin,
+    DELAY: DelayNs,
+{
+    type DisplayColor = Color;
+    fn width(&self) -> u32 {
+        WIDTH
+    }
+
+    fn height(&self) -> u32 {
+        HEIGHT
+    }
+
+    async fn new(
+        spi: &mut SPI,
+        busy: BUSY,
+        dc: DC,
+        rst: RST,
+        delay: &mut DELAY,
+        delay_us: Option<u32>,
+    ) -> Result<Self, EpdError<SPI::Error>> {
+        let mut epd = Self::new_uninit(busy, dc, rst, delay_us);
+        epd.init(spi, delay).await?;
+        Ok(epd)
+    }
+
+    fn new_uninit(busy: BUSY, dc: DC, rst: RST, delay_us: Option<u32>) -> Self {
+        let interface = DisplayInterface::new(busy, dc, rst, delay_us);
+
+        Badger2040 {
+            interface,
+            background_color: DEFAULT_BACKGROUND_COLOR,
+            speed: Speed::Default,
+        }
+    }
+
+    async fn sleep(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.interface.cmd(spi, Command::PowerOff).await?;
+        self.wait_until_idle(spi, delay).await?;
+        self.interface
+            .cmd_with_data(spi, Command::DeepSleep, &[0xA5])
+            .await
+    }
+
+    /// Wakes the panel up, with the waveform of the OTP at the last speed set
+    async fn wake_up(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.init(spi, delay).await
+    }
+
+    async fn update_frame(
+        &mut self,
+        spi: &mut SPI,
+        buffer: &[u8],
+        delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        EpdError::check_buffer(buffer, buffer_len(WIDTH as usize, HEIGHT as usize))?;
+        self.wait_until_idle(spi, delay).await?;
+        // same old and new frame, the OTP waveform refreshes every pixel anyway
+        self.interface
+            .cmd_with_data(spi, Command::DataStartTransmission1, buffer)
+            .await?;
+        self.interface
+            .cmd_with_data(spi, Command::DataStartTransmission2, buffer)
+            .await
+    }
+
+    /// Writes a window of the frame, `x` and `width` must be multiples of 8
+    ///
+    /// [`display_partial_frame`](Badger2040::display_partial_frame) refreshes only this window.
+    async fn update_partial_frame(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+        buffer: &[u8],
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        EpdError::check_buffer(buffer, (width / 8 * height) as usize)?;
+        self.wait_until_idle(spi, delay).await?;
+
+        self.interface.cmd(spi, Command::PartialIn).await?;
+        self.set_partial_window(spi, x, y, width, height).await?;
+        self.interface
+            .cmd_with_data(spi, Command::DataStartTransmission1, buffer)
+            .await?;
+        self.interface
+            .cmd_with_data(spi, Command::DataStartTransmission2, buffer)
+            .await?;
+        self.interface.cmd(spi, Command::PartialOut).await
+    }
+
+    async fn display_frame(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.interface.cmd(spi, Command::DisplayRefresh).await?;
+        self.wait_until_idle(spi, delay).await
+    }
+
+    async fn update_and_display_frame(
+        &mut self,
+        spi: &mut SPI,
+        buffer: &[u8],
+        delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.update_frame(spi, buffer, delay).await?;
+        self.display_frame(spi, delay).await
+    }
+
+    async fn clear_frame(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.wait_until_idle(spi, delay).await?;
+
+        let color = self.background_color.get_byte_value();
+        let len = buffer_len(WIDTH as usize, HEIGHT as usize) as u32;
+        self.interface
+            .cmd(spi, Command::DataStartTransmission1)
+            .await?;
+        self.interface.data_x_times(spi, color, len).await?;
+        self.interface
+            .cmd(spi, Command::DataStartTransmission2)
+            .await?;
+        self.interface.data_x_times(spi, color, len).await
+    }
+
+    fn set_background_color(&mut self, background_color: Color) {
+        self.background_color = background_color;
+    }
+
+    fn background_color(&self) -> &Color {
+        &self.background_color
+    }
+
+    /// Selects [`Speed::Default`] for full refreshes and [`Speed::Turbo`] for quick ones
+    async fn set_lut(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+        refresh_rate: Option<RefreshLut>,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        let speed = match refresh_rate.map(|lut| lut.or_full(&[RefreshLut::Quick])) {
+            Some(RefreshLut::Quick) => Speed::Turbo,
+            Some(_) => Speed::Default,
+            None => self.speed,
+        };
+        self.set_speed(spi, delay, speed).await
+    }
+
+    fn interface_config_mut(&mut self) -> &mut InterfaceConfig {
+        self.interface.config_mut()
+    }
+
+    fn release(self) -> (BUSY, DC, RST) {
+        self.interface.release()
+    }
+
+    async fn wait_until_idle(
+        &mut self,
+        _spi: &mut SPI,
+        delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.interface.wait_until_idle(delay, IS_BUSY_LOW).await
+    }
+}
+
+#[maybe_async_cfg::maybe(
+    sync(keep_self),
+    async(
+        feature = "async",
+        idents(
+            Badger2040,
+            DisplayInterface,
+            WaveshareDisplay,
+            InternalWiAdditions,
+            SpiDevice(async = "AsyncSpiDevice"),
+            BusyPin(async = "AsyncBusyPin"),
+            DelayNs(async = "AsyncDelayNs")
+        )
+    )
+)]
+impl<SPI, BUSY, DC, RST, DELAY> Badger2040<SPI, BUSY, DC, RST, DELAY>
+where
+    SPI: SpiDevice,
+    BUSY: BusyPin,
+    DC: OutputPin,
+    RST: OutputPin,
+    DELAY: DelayNs,
+{
+    /// Sets the speed of the next refreshes
+    pub async fn set_speed(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+        speed: Speed,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.speed = speed;
+        self.wait_until_idle(spi, delay).await?;
+        self.interface
+            .cmd_with_data(spi, Command::PllControl, &[speed.pll()])
+            .await
+    }
+
+    /// Returns the speed of the refreshes
+    pub fn speed(&self) -> Speed {
+        self.speed
+    }
+
+    /// Refreshes the panel with the waveform `lut` written in the LUT registers, or with the
+    /// one of the OTP again for `None`
+    ///
+    /// The registers are kept until the next [`wake_up`](WaveshareDisplay::wake_up), which
+    /// goes back to the waveform of the OTP.
+    pub async fn set_custom_lut(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+        lut: Option<&Lut<'_>>,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.wait_until_idle(spi, delay).await?;
+        let lut = match lut {
+            Some(lut) => lut,
+            None => {
+                return self
+                    .interface
+                    .cmd_with_data(spi, Command::PanelSetting, &[PANEL_SETTING_OTP])
+                    .await
+            }
+        };
+        self.interface
+            .cmd_with_data(spi, Command::LutForVcom, lut.vcom)
+            .await?;
+        self.interface
+            .cmd_with_data(spi, Command::LutWhiteToWhite, lut.ww)
+            .await?;
+        self.interface
+            .cmd_with_data(spi, Command::LutBlackToWhite, lut.bw)
+            .await?;
+        self.interface
+            .cmd_with_data(spi, Command::LutWhiteToBlack, lut.wb)
+            .await?;
+        self.interface
+            .cmd_with_data(spi, Command::LutBlackToBlack, lut.bb)
+            .await?;
+        self.interface
+            .cmd_with_data(spi, Command::PanelSetting, &[PANEL_SETTING_REGISTERS])
+            .await
+    }
+
+    /// Refreshes only a window of the panel from the frame memory
+    ///
+    /// Unlike [`display_frame`](WaveshareDisplay::display_frame), the pixels outside of the
+    /// window aren't driven at all, so they don't flash. `x` and `width` must be multiples of 8.
+    pub async fn display_partial_frame(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.wait_until_idle(spi, delay).await?;
+        self.interface.cmd(spi, Command::PartialIn).await?;
+        self.set_partial_window(spi, x, y, width, height).await?;
+        self.interface.cmd(spi, Command::DisplayRefresh).await?;
+        self.wait_until_idle(spi, delay).await?;
+        self.interface.cmd(spi, Command::PartialOut).await
+    }
+
+    /// Selects the window written and refreshed while in partial mode
+    async fn set_partial_window(
+        &mut self,
+        spi: &mut SPI,
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        let x_start = x & 0xf8;
+        let y_end = y + height - 1;
+        self.interface
+            .cmd_with_data(
+                spi,
+                Command::PartialWindow,
+                &[
+                    x_start as u8,
+                    ((x_start + width - 1) | 0x07) as u8,
+                    (y >> 8) as u8,
+                    y as u8,
+                    (y_end >> 8) as u8,
+                    y_end as u8,
+                    // Gates scan both inside and outside of the partial window
+                    0x01,
+                ],
+            )
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn epd_size() {
+        assert_eq!(WIDTH, 128);
+        assert_eq!(HEIGHT, 296);
+        assert_eq!(DEFAULT_BACKGROUND_COLOR, Color::White);
+    }
+
+    #[test]
+    fn speed_frame_rates() {
+        assert_eq!(Speed::default(), Speed::Default);
+        assert_eq!(Speed::Default.pll(), 0x3C);
+        assert_eq!(Speed::Medium.pll(), 0x3A);
+        assert_eq!(Speed::Fast.pll(), 0x29);
+        assert_eq!(Speed::Turbo.pll(), 0x39);
+    }
+}
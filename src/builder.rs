@@ -0,0 +1,197 @@
+//! Creation of the drivers with their options
+//!
+//! [`WaveshareDisplay::new`] only takes the delay between the polls of BUSY, the other options
+//! being set on the created driver. [`EpdBuilder`] collects them and sets them when it creates
+//! the driver with [`WaveshareDisplay::new_uninit`], before it initialises the panel, so that the
+//! initialisation already uses them.
+//!
+//!```rust, no_run
+//!# use embedded_hal_mock::eh1::*;
+//!# fn main() -> Result<(), epd_waveshare::error::EpdError<embedded_hal::spi::ErrorKind>> {
+//!use epd_waveshare::{builder::EpdBuilder, epd2in9_v2::*, prelude::*};
+//!#
+//!# let expectations = [];
+//!# let mut spi = spi::Mock::new(&expectations);
+//!# let expectations = [];
+//!# let busy_in = digital::Mock::new(&expectations);
+//!# let dc = digital::Mock::new(&expectations);
+//!# let rst = digital::Mock::new(&expectations);
+//!# let mut delay = delay::NoopDelay::new();
+//!
+//!let mut epd: Epd2in9<_, _, _, _, _> = EpdBuilder::new()
+//!    .with_background_color(Color::Black)
+//!    .with_refresh(RefreshLut::Quick)
+//!    .with_busy_timeout(5_000_000)
+//!    .build(&mut spi, busy_in, dc, rst, &mut delay)?;
+//!epd.clear_frame(&mut spi, &mut delay)?;
+//!# Ok(())
+//!# }
+//!```
+//!
+//! The rotation isn't an option of the drivers but of the display buffers, see
+//! [`Display::set_rotation`](crate::graphics::Display::set_rotation).
+
+use crate::error::EpdError;
+use crate::pin::BusyPin;
+use crate::traits::{RefreshLut, ResetTiming, WaveshareDisplay};
+use embedded_hal::{delay::*, digital::*, spi::SpiDevice};
+
+/// Options of a driver, see the [module](self)
+///
+/// `COLOR` is the color type of the driver, the [`DisplayColor`](WaveshareDisplay::DisplayColor)
+/// of its [`WaveshareDisplay`] implementation.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct EpdBuilder<COLOR> {
+    delay_us: Option<u32>,
+    busy_timeout_us: Option<u32>,
+    reset_timing: Option<ResetTiming>,
+    skip_init: bool,
+    background_color: Option<COLOR>,
+    refresh: Option<RefreshLut>,
+}
+
+impl<COLOR> Default for EpdBuilder<COLOR> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<COLOR> EpdBuilder<COLOR> {
+    /// Options of the drivers by default: BUSY polled every 10ms without timeout, the reset
+    /// timing of the driver, the panel initialised, the default background color and full
+    /// refreshes
+    pub const fn new() -> Self {
+        EpdBuilder {
+            delay_us: None,
+            busy_timeout_us: None,
+            reset_timing: None,
+            skip_init: false,
+            background_color: None,
+            refresh: None,
+        }
+    }
+
+    /// Polls BUSY every `delay_us` instead of 10ms, `0` polling without delay
+    pub fn with_busy_poll_delay(mut self, delay_us: u32) -> Self {
+        self.delay_us = Some(delay_us);
+        self
+    }
+
+    /// Stops the waits for the controller after `timeout_us`, see
+    /// [`set_busy_timeout`](WaveshareDisplay::set_busy_timeout), the ones of the initialisation
+    /// included
+    pub fn with_busy_timeout(mut self, timeout_us: u32) -> Self {
+        self.busy_timeout_us = Some(timeout_us);
+        self
+    }
+
+    /// Resets the controller with `timing` instead of the timing of the driver, see
+    /// [`set_reset_timing`](WaveshareDisplay::set_reset_timing)
+    pub fn with_reset_timing(mut self, timing: ResetTiming) -> Self {
+        self.reset_timing = Some(timing);
+        self
+    }
+
+    /// Doesn't initialise the panel when creating the driver, e.g. when it's still initialised
+    /// after a reset of the MCU which kept it powered, see
+    /// [`new_uninit`](WaveshareDisplay::new_uninit)
+    pub fn with_skip_init(mut self) -> Self {
+        self.skip_init = true;
+        self
+    }
+
+    /// Clears the frames with `color` instead of the default background color of the driver
+    pub fn with_background_color(mut self, color: COLOR) -> Self {
+        self.background_color = Some(color);
+        self
+    }
+
+    /// Selects the LUT of the refreshes, see [`set_lut`](WaveshareDisplay::set_lut)
+    pub fn with_refresh(mut self, refresh: RefreshLut) -> Self {
+        self.refresh = Some(refresh);
+        self
+    }
+
+    /// Creates the driver, sets its options and initialises the panel
+    pub fn build<EPD, SPI, BUSY, DC, RST, DELAY>(
+        self,
+        spi: &mut SPI,
+        busy: BUSY,
+        dc: DC,
+        rst: RST,
+        delay: &mut DELAY,
+    ) -> Result<EPD, EpdError<SPI::Error>>
+    where
+        EPD: WaveshareDisplay<SPI, BUSY, DC, RST, DELAY, DisplayColor = COLOR>,
+        SPI: SpiDevice,
+        BUSY: BusyPin,
+        DC: OutputPin,
+        RST: OutputPin,
+        DELAY: DelayNs,
+    {
+        let mut epd = EPD::new_uninit(busy, dc, rst, self.delay_us);
+        epd.set_busy_timeout(self.busy_timeout_us);
+        epd.set_reset_timing(self.reset_timing);
+        if !self.skip_init {
+            epd.wake_up(spi, delay)?;
+        }
+        if let Some(color) = self.background_color {
+            epd.set_background_color(color);
+        }
+        if let Some(refresh) = self.refresh {
+            epd.set_lut(spi, delay, Some(refresh))?;
+        }
+        Ok(epd)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::Color;
+
+    #[test]
+    fn options() {
+        let builder = EpdBuilder::new()
+            .with_busy_poll_delay(0)
+            .with_background_color(Color::Black);
+        assert_eq!(builder.delay_us, Some(0));
+        assert_eq!(builder.busy_timeout_us, None);
+        assert_eq!(builder.reset_timing, None);
+        assert!(!builder.skip_init);
+        assert_eq!(builder.background_color, Some(Color::Black));
+        assert_eq!(builder.refresh, None);
+        assert_eq!(EpdBuilder::<Color>::default(), EpdBuilder::new());
+    }
+
+    #[test]
+    fn skip_init() {
+        use crate::controller::ssd16xx::{Panel, PanelConfig, Ssd16xx};
+        use embedded_hal_mock::eh1::{delay::NoopDelay, digital, spi};
+
+        struct TestPanel;
+
+        impl Panel for TestPanel {
+            const CONFIG: PanelConfig = PanelConfig::new(8, 8);
+        }
+
+        // neither the SPI device nor the pins are used
+        let mut spi = spi::Mock::new(&[]);
+        let epd: Ssd16xx<TestPanel, _, _, _, _, _> = EpdBuilder::new()
+            .with_skip_init()
+            .with_reset_timing(ResetTiming::NONE)
+            .build(
+                &mut spi,
+                digital::Mock::new(&[]),
+                digital::Mock::new(&[]),
+                digital::Mock::new(&[]),
+                &mut NoopDelay::new(),
+            )
+            .unwrap();
+        let (mut busy, mut dc, mut rst) = epd.release();
+        spi.done();
+        busy.done();
+        dc.done();
+        rst.done();
+    }
+}
@@ -68,6 +68,133 @@ pub enum OctColor {
     HiZ = 0x07,
 }
 
+/// For the 4 Color Displays (G)
+///
+/// The values are those of the controllers, the colors being packed by 4 in a byte with the
+/// leftmost pixel in the high bits.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum QuadColor {
+    /// Black Color
+    Black = 0x00,
+    /// White Color
+    #[default]
+    White = 0x01,
+    /// Yellow Color
+    Yellow = 0x02,
+    /// Red Color
+    Red = 0x03,
+}
+
+/// For the 6 Color Displays (E), with an E Ink Spectra 6 panel
+///
+/// The values are those of the controllers, the colors being packed by 2 in a byte with the
+/// leftmost pixel in the high nibble. The nibbles 0x4 and 0x7 and above aren't colors.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum SpectraColor {
+    /// Black Color
+    Black = 0x00,
+    /// White Color
+    #[default]
+    White = 0x01,
+    /// Yellow Color
+    Yellow = 0x02,
+    /// Red Color
+    Red = 0x03,
+    /// Blue Color
+    Blue = 0x05,
+    /// Green Color
+    Green = 0x06,
+}
+
+/// 16 gray levels, for the displays driven by an [IT8951](crate::it8951) controller
+///
+/// Level 0 is black and level 15 is white, as expected by the controller.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Gray16(u8);
+
+impl Gray16 {
+    /// Black
+    pub const BLACK: Gray16 = Gray16(0x0);
+    /// White
+    pub const WHITE: Gray16 = Gray16(0xf);
+
+    /// Gray level from 0 (black) to 15 (white), only the 4 lower bits are used
+    pub const fn new(luma: u8) -> Gray16 {
+        Gray16(luma & 0x0f)
+    }
+
+    /// Gray level from 0 (black) to 15 (white)
+    pub const fn luma(self) -> u8 {
+        self.0
+    }
+}
+
+impl Default for Gray16 {
+    fn default() -> Self {
+        Gray16::WHITE
+    }
+}
+
+/// 4 gray levels, for the displays with a 4 gray waveform
+///
+/// Level 0 is black and level 3 is white. The levels are packed by 4 in a byte, the leftmost pixel
+/// in the high bits; the controllers receive them as two planes of one bit per pixel.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Gray4(u8);
+
+impl Gray4 {
+    /// Black
+    pub const BLACK: Gray4 = Gray4(0x0);
+    /// Dark gray
+    pub const DARK_GRAY: Gray4 = Gray4(0x1);
+    /// Light gray
+    pub const LIGHT_GRAY: Gray4 = Gray4(0x2);
+    /// White
+    pub const WHITE: Gray4 = Gray4(0x3);
+
+    /// Gray level from 0 (black) to 3 (white), only the 2 lower bits are used
+    pub const fn new(luma: u8) -> Gray4 {
+        Gray4(luma & 0x03)
+    }
+
+    /// Gray level from 0 (black) to 3 (white)
+    pub const fn luma(self) -> u8 {
+        self.0
+    }
+}
+
+/// Bit `bit` of the 8 gray levels packed in two bytes, leftmost pixel first: one byte of the
+/// planes sent to the controllers
+#[cfg_attr(
+    not(any(feature = "epd2in9_v2", feature = "epd4in26", feature = "magtag2in9")),
+    allow(dead_code)
+)]
+pub(crate) fn gray4_plane_byte(pixels: &[u8], bit: u32) -> u8 {
+    let packed = pixels
+        .iter()
+        .fold(0u16, |packed, &byte| packed << 8 | u16::from(byte));
+    (0..8).fold(0, |byte, i| {
+        let level = packed >> (14 - 2 * i);
+        byte << 1 | ((level >> bit) & 1) as u8
+    })
+}
+
+impl Default for Gray4 {
+    fn default() -> Self {
+        Gray4::WHITE
+    }
+}
+
+/// Fills `frame`, made of `COLOR::BUFFER_COUNT` planes, with `color`
+pub(crate) fn fill_frame<COLOR: ColorType>(frame: &mut [u8], color: &COLOR) {
+    let bits = (0..8 / COLOR::BITS_PER_PIXEL_PER_BUFFER as u32)
+        .fold(0u16, |bits, pos| bits | color.bitmask(false, pos).1);
+    let plane_len = frame.len() / COLOR::BUFFER_COUNT;
+    for (i, plane) in frame.chunks_mut(plane_len.max(1)).enumerate() {
+        plane.fill((bits >> (8 * i)) as u8);
+    }
+}
+
 /// Color trait for use in `Display`s
 pub trait ColorType {
     /// Number of bit used to represent this color type in a single buffer.
@@ -87,8 +214,20 @@ pub trait ColorType {
     /// Return values are :
     /// * .0 is the mask used to exclude this pixel from the byte (eg: 0x7F in BiColor)
     /// * .1 are the bits used to set the color in the byte (eg: 0x80 in BiColor)
-    ///      this is u16 because we set 2 bytes in case of split buffer
+    ///   this is u16 because we set 2 bytes in case of split buffer
     fn bitmask(&self, bwrbit: bool, pos: u32) -> (u8, u16);
+
+    /// Return the color of a pixel from its bits in the buffer, used to read pixels back
+    ///
+    /// `bits` are the bits of the pixel moved to the lowest ones, the bits of the second buffer
+    /// of a split buffer above the ones of the first buffer. Returns `None` if they don't make a
+    /// valid color, which is always the case by default.
+    fn from_bits(_bits: u16, _bwrbit: bool) -> Option<Self>
+    where
+        Self: Sized,
+    {
+        None
+    }
 }
 
 impl ColorType for Color {
@@ -101,6 +240,14 @@ impl ColorType for Color {
             Color::White => (!bit, bit as u16),
         }
     }
+
+    fn from_bits(bits: u16, _bwrbit: bool) -> Option<Self> {
+        Some(if bits & 1 == 1 {
+            Color::White
+        } else {
+            Color::Black
+        })
+    }
 }
 
 impl ColorType for TriColor {
@@ -121,6 +268,15 @@ impl ColorType for TriColor {
             ),
         }
     }
+
+    fn from_bits(bits: u16, _bwrbit: bool) -> Option<Self> {
+        // the chromatic bit wins whatever the value of the B/W bit
+        Some(match bits & 0b11 {
+            0b00 => TriColor::Black,
+            0b01 => TriColor::White,
+            _ => TriColor::Chromatic,
+        })
+    }
 }
 
 impl ColorType for OctColor {
@@ -131,6 +287,247 @@ impl ColorType for OctColor {
         let bits = self.get_nibble() as u16;
         (mask, if pos % 2 == 1 { bits } else { bits << 4 })
     }
+
+    fn from_bits(bits: u16, _bwrbit: bool) -> Option<Self> {
+        OctColor::from_nibble(bits as u8).ok()
+    }
+}
+
+impl ColorType for Gray16 {
+    const BITS_PER_PIXEL_PER_BUFFER: usize = 4;
+    const BUFFER_COUNT: usize = 1;
+    fn bitmask(&self, _bwrbit: bool, pos: u32) -> (u8, u16) {
+        let mask = !(0xF0 >> ((pos % 2) * 4));
+        let bits = self.0 as u16;
+        (mask, if pos % 2 == 1 { bits } else { bits << 4 })
+    }
+
+    fn from_bits(bits: u16, _bwrbit: bool) -> Option<Self> {
+        Some(Gray16::new(bits as u8))
+    }
+}
+
+impl ColorType for Gray4 {
+    const BITS_PER_PIXEL_PER_BUFFER: usize = 2;
+    const BUFFER_COUNT: usize = 1;
+    fn bitmask(&self, _bwrbit: bool, pos: u32) -> (u8, u16) {
+        let shift = 6 - (pos % 4) * 2;
+        (!(0x03 << shift), (self.0 as u16) << shift)
+    }
+
+    fn from_bits(bits: u16, _bwrbit: bool) -> Option<Self> {
+        Some(Gray4::new(bits as u8))
+    }
+}
+
+impl ColorType for QuadColor {
+    const BITS_PER_PIXEL_PER_BUFFER: usize = 2;
+    const BUFFER_COUNT: usize = 1;
+    fn bitmask(&self, _bwrbit: bool, pos: u32) -> (u8, u16) {
+        let shift = 6 - (pos % 4) * 2;
+        (!(0x03 << shift), (self.get_bits() as u16) << shift)
+    }
+
+    fn from_bits(bits: u16, _bwrbit: bool) -> Option<Self> {
+        Some(QuadColor::from_bits(bits as u8))
+    }
+}
+
+impl ColorType for SpectraColor {
+    const BITS_PER_PIXEL_PER_BUFFER: usize = 4;
+    const BUFFER_COUNT: usize = 1;
+    fn bitmask(&self, _bwrbit: bool, pos: u32) -> (u8, u16) {
+        let mask = !(0xF0 >> ((pos % 2) * 4));
+        let bits = self.get_nibble() as u16;
+        (mask, if pos % 2 == 1 { bits } else { bits << 4 })
+    }
+
+    fn from_bits(bits: u16, _bwrbit: bool) -> Option<Self> {
+        SpectraColor::from_nibble(bits as u8).ok()
+    }
+}
+
+/// `BinaryColor::On` is black and `Off` white, as in the `From<BinaryColor>` implementations,
+/// unless `bwrbit` is set: it inverts the mapping (`On` is white) for the displays of
+/// [`BinaryDisplay`](crate::graphics::BinaryDisplay).
+#[cfg(feature = "graphics")]
+impl ColorType for BinaryColor {
+    const BITS_PER_PIXEL_PER_BUFFER: usize = 1;
+    const BUFFER_COUNT: usize = 1;
+    fn bitmask(&self, bwrbit: bool, pos: u32) -> (u8, u16) {
+        let bit = 0x80 >> (pos % 8);
+        // a set bit is white
+        if self.is_on() == bwrbit {
+            (!bit, bit as u16)
+        } else {
+            (!bit, 0u16)
+        }
+    }
+
+    fn from_bits(bits: u16, bwrbit: bool) -> Option<Self> {
+        Some(BinaryColor::from((bits & 1 == 1) == bwrbit))
+    }
+}
+
+#[cfg(feature = "graphics")]
+impl PixelColor for Gray16 {
+    type Raw = embedded_graphics_core::pixelcolor::raw::RawU4;
+}
+
+#[cfg(feature = "graphics")]
+impl embedded_graphics_core::pixelcolor::GrayColor for Gray16 {
+    fn luma(&self) -> u8 {
+        self.0
+    }
+
+    const BLACK: Self = Gray16::BLACK;
+    const WHITE: Self = Gray16::WHITE;
+}
+
+#[cfg(feature = "graphics")]
+impl From<embedded_graphics_core::pixelcolor::raw::RawU4> for Gray16 {
+    fn from(b: embedded_graphics_core::pixelcolor::raw::RawU4) -> Self {
+        use embedded_graphics_core::prelude::RawData;
+        Gray16::new(b.into_inner())
+    }
+}
+
+#[cfg(feature = "graphics")]
+impl From<Gray16> for embedded_graphics_core::pixelcolor::raw::RawU4 {
+    fn from(color: Gray16) -> Self {
+        Self::new(color.0)
+    }
+}
+
+#[cfg(feature = "graphics")]
+impl From<BinaryColor> for Gray16 {
+    fn from(b: BinaryColor) -> Gray16 {
+        match b {
+            BinaryColor::On => Gray16::BLACK,
+            BinaryColor::Off => Gray16::WHITE,
+        }
+    }
+}
+
+#[cfg(feature = "graphics")]
+impl From<embedded_graphics_core::pixelcolor::Gray4> for Gray16 {
+    fn from(gray: embedded_graphics_core::pixelcolor::Gray4) -> Gray16 {
+        use embedded_graphics_core::pixelcolor::GrayColor;
+        Gray16::new(gray.luma())
+    }
+}
+
+#[cfg(feature = "graphics")]
+impl From<embedded_graphics_core::pixelcolor::Gray8> for Gray16 {
+    fn from(gray: embedded_graphics_core::pixelcolor::Gray8) -> Gray16 {
+        use embedded_graphics_core::pixelcolor::GrayColor;
+        Gray16::new(gray.luma() >> 4)
+    }
+}
+
+#[cfg(feature = "graphics")]
+impl From<Gray16> for embedded_graphics_core::pixelcolor::Gray8 {
+    fn from(gray: Gray16) -> Self {
+        Self::new(gray.0 * 0x11)
+    }
+}
+
+#[cfg(feature = "graphics")]
+impl From<embedded_graphics_core::pixelcolor::Rgb888> for Gray16 {
+    fn from(rgb: embedded_graphics_core::pixelcolor::Rgb888) -> Self {
+        use embedded_graphics_core::pixelcolor::RgbColor;
+        // ITU-R BT.601 luma
+        let luma = (rgb.r() as u32 * 77 + rgb.g() as u32 * 150 + rgb.b() as u32 * 29) >> 8;
+        Gray16::new((luma >> 4) as u8)
+    }
+}
+
+#[cfg(feature = "graphics")]
+impl From<Gray16> for embedded_graphics_core::pixelcolor::Rgb888 {
+    fn from(gray: Gray16) -> Self {
+        let luma = gray.0 * 0x11;
+        Self::new(luma, luma, luma)
+    }
+}
+
+#[cfg(feature = "graphics")]
+impl PixelColor for Gray4 {
+    type Raw = embedded_graphics_core::pixelcolor::raw::RawU2;
+}
+
+#[cfg(feature = "graphics")]
+impl embedded_graphics_core::pixelcolor::GrayColor for Gray4 {
+    fn luma(&self) -> u8 {
+        self.0
+    }
+
+    const BLACK: Self = Gray4::BLACK;
+    const WHITE: Self = Gray4::WHITE;
+}
+
+#[cfg(feature = "graphics")]
+impl From<embedded_graphics_core::pixelcolor::raw::RawU2> for Gray4 {
+    fn from(b: embedded_graphics_core::pixelcolor::raw::RawU2) -> Self {
+        use embedded_graphics_core::prelude::RawData;
+        Gray4::new(b.into_inner())
+    }
+}
+
+#[cfg(feature = "graphics")]
+impl From<Gray4> for embedded_graphics_core::pixelcolor::raw::RawU2 {
+    fn from(color: Gray4) -> Self {
+        Self::new(color.0)
+    }
+}
+
+#[cfg(feature = "graphics")]
+impl From<BinaryColor> for Gray4 {
+    fn from(b: BinaryColor) -> Gray4 {
+        match b {
+            BinaryColor::On => Gray4::BLACK,
+            BinaryColor::Off => Gray4::WHITE,
+        }
+    }
+}
+
+#[cfg(feature = "graphics")]
+impl From<Gray4> for embedded_graphics_core::pixelcolor::Rgb888 {
+    fn from(gray: Gray4) -> Self {
+        let luma = gray.0 * 0x55;
+        Self::new(luma, luma, luma)
+    }
+}
+
+#[cfg(feature = "graphics")]
+impl From<embedded_graphics_core::pixelcolor::Gray2> for Gray4 {
+    fn from(gray: embedded_graphics_core::pixelcolor::Gray2) -> Gray4 {
+        use embedded_graphics_core::pixelcolor::GrayColor;
+        Gray4::new(gray.luma())
+    }
+}
+
+#[cfg(feature = "graphics")]
+impl From<embedded_graphics_core::pixelcolor::Gray8> for Gray4 {
+    fn from(gray: embedded_graphics_core::pixelcolor::Gray8) -> Gray4 {
+        use embedded_graphics_core::pixelcolor::GrayColor;
+        Gray4::new(gray.luma() >> 6)
+    }
+}
+
+#[cfg(feature = "graphics")]
+impl From<Gray4> for embedded_graphics_core::pixelcolor::Gray8 {
+    fn from(gray: Gray4) -> Self {
+        Self::new(gray.0 * 0x55)
+    }
+}
+
+impl From<Color> for OctColor {
+    fn from(color: Color) -> OctColor {
+        match color {
+            Color::Black => OctColor::Black,
+            Color::White => OctColor::White,
+        }
+    }
 }
 
 #[cfg(feature = "graphics")]
@@ -199,6 +596,230 @@ impl PixelColor for OctColor {
     type Raw = embedded_graphics_core::pixelcolor::raw::RawU4;
 }
 
+impl From<Color> for QuadColor {
+    fn from(color: Color) -> QuadColor {
+        match color {
+            Color::Black => QuadColor::Black,
+            Color::White => QuadColor::White,
+        }
+    }
+}
+
+#[cfg(feature = "graphics")]
+impl From<BinaryColor> for QuadColor {
+    fn from(b: BinaryColor) -> QuadColor {
+        match b {
+            BinaryColor::On => QuadColor::Black,
+            BinaryColor::Off => QuadColor::White,
+        }
+    }
+}
+
+#[cfg(feature = "graphics")]
+impl From<QuadColor> for embedded_graphics_core::pixelcolor::Rgb888 {
+    fn from(color: QuadColor) -> Self {
+        let (r, g, b) = color.rgb();
+        Self::new(r, g, b)
+    }
+}
+
+#[cfg(feature = "graphics")]
+impl From<embedded_graphics_core::pixelcolor::Rgb888> for QuadColor {
+    fn from(p: embedded_graphics_core::pixelcolor::Rgb888) -> QuadColor {
+        use embedded_graphics_core::prelude::RgbColor;
+        // the nearest color
+        [
+            QuadColor::Black,
+            QuadColor::White,
+            QuadColor::Yellow,
+            QuadColor::Red,
+        ]
+        .into_iter()
+        .min_by_key(|color| {
+            let (r, g, b) = color.rgb();
+            (i32::from(r) - i32::from(p.r())).pow(2)
+                + (i32::from(g) - i32::from(p.g())).pow(2)
+                + (i32::from(b) - i32::from(p.b())).pow(2)
+        })
+        .unwrap_or_default()
+    }
+}
+
+#[cfg(feature = "graphics")]
+impl From<embedded_graphics_core::pixelcolor::raw::RawU2> for QuadColor {
+    fn from(b: embedded_graphics_core::pixelcolor::raw::RawU2) -> Self {
+        use embedded_graphics_core::prelude::RawData;
+        QuadColor::from_bits(b.into_inner())
+    }
+}
+
+#[cfg(feature = "graphics")]
+impl From<QuadColor> for embedded_graphics_core::pixelcolor::raw::RawU2 {
+    fn from(color: QuadColor) -> Self {
+        Self::new(color.get_bits())
+    }
+}
+
+#[cfg(feature = "graphics")]
+impl PixelColor for QuadColor {
+    type Raw = embedded_graphics_core::pixelcolor::raw::RawU2;
+}
+
+impl QuadColor {
+    /// Gets the 2 bits representing the color for the display
+    pub fn get_bits(self) -> u8 {
+        self as u8
+    }
+
+    /// Converts four colors, from left to right, into a single byte for the display
+    pub fn colors_byte(colors: [QuadColor; 4]) -> u8 {
+        colors
+            .iter()
+            .fold(0, |byte, color| byte << 2 | color.get_bits())
+    }
+
+    /// Takes the 2 lower bits and converts them to a QuadColor
+    pub fn from_bits(bits: u8) -> QuadColor {
+        match bits & 0x03 {
+            0x00 => QuadColor::Black,
+            0x01 => QuadColor::White,
+            0x02 => QuadColor::Yellow,
+            _ => QuadColor::Red,
+        }
+    }
+
+    /// Splits a byte into its four colors, from left to right
+    pub fn split_byte(byte: u8) -> [QuadColor; 4] {
+        [6, 4, 2, 0].map(|shift| QuadColor::from_bits(byte >> shift))
+    }
+
+    /// Converts to limited range of RGB values.
+    pub fn rgb(self) -> (u8, u8, u8) {
+        match self {
+            QuadColor::Black => (0x00, 0x00, 0x00),
+            QuadColor::White => (0xff, 0xff, 0xff),
+            QuadColor::Yellow => (0xff, 0xff, 0x00),
+            QuadColor::Red => (0xff, 0x00, 0x00),
+        }
+    }
+}
+
+impl From<Color> for SpectraColor {
+    fn from(color: Color) -> SpectraColor {
+        match color {
+            Color::Black => SpectraColor::Black,
+            Color::White => SpectraColor::White,
+        }
+    }
+}
+
+#[cfg(feature = "graphics")]
+impl From<BinaryColor> for SpectraColor {
+    fn from(b: BinaryColor) -> SpectraColor {
+        match b {
+            BinaryColor::On => SpectraColor::Black,
+            BinaryColor::Off => SpectraColor::White,
+        }
+    }
+}
+
+#[cfg(feature = "graphics")]
+impl From<SpectraColor> for embedded_graphics_core::pixelcolor::Rgb888 {
+    fn from(color: SpectraColor) -> Self {
+        let (r, g, b) = color.rgb();
+        Self::new(r, g, b)
+    }
+}
+
+#[cfg(feature = "graphics")]
+impl From<embedded_graphics_core::pixelcolor::Rgb888> for SpectraColor {
+    fn from(p: embedded_graphics_core::pixelcolor::Rgb888) -> SpectraColor {
+        use embedded_graphics_core::prelude::RgbColor;
+        // the nearest color
+        [
+            SpectraColor::Black,
+            SpectraColor::White,
+            SpectraColor::Yellow,
+            SpectraColor::Red,
+            SpectraColor::Blue,
+            SpectraColor::Green,
+        ]
+        .into_iter()
+        .min_by_key(|color| {
+            let (r, g, b) = color.rgb();
+            (i32::from(r) - i32::from(p.r())).pow(2)
+                + (i32::from(g) - i32::from(p.g())).pow(2)
+                + (i32::from(b) - i32::from(p.b())).pow(2)
+        })
+        .unwrap_or_default()
+    }
+}
+
+/// The nibbles which aren't colors are read as white, the color the panel shows for them
+#[cfg(feature = "graphics")]
+impl From<embedded_graphics_core::pixelcolor::raw::RawU4> for SpectraColor {
+    fn from(b: embedded_graphics_core::pixelcolor::raw::RawU4) -> Self {
+        use embedded_graphics_core::prelude::RawData;
+        SpectraColor::from_nibble(b.into_inner()).unwrap_or_default()
+    }
+}
+
+#[cfg(feature = "graphics")]
+impl From<SpectraColor> for embedded_graphics_core::pixelcolor::raw::RawU4 {
+    fn from(color: SpectraColor) -> Self {
+        Self::new(color.get_nibble())
+    }
+}
+
+#[cfg(feature = "graphics")]
+impl PixelColor for SpectraColor {
+    type Raw = embedded_graphics_core::pixelcolor::raw::RawU4;
+}
+
+impl SpectraColor {
+    /// Gets the Nibble representation of the Color as needed by the display
+    pub fn get_nibble(self) -> u8 {
+        self as u8
+    }
+
+    /// Converts two colors, from left to right, into a single byte for the display
+    pub fn colors_byte(a: SpectraColor, b: SpectraColor) -> u8 {
+        a.get_nibble() << 4 | b.get_nibble()
+    }
+
+    /// Takes the nibble (lower 4 bits) and converts it to a SpectraColor if possible
+    pub fn from_nibble(nibble: u8) -> Result<SpectraColor, OutOfColorRangeParseError> {
+        match nibble & 0xf {
+            0x00 => Ok(SpectraColor::Black),
+            0x01 => Ok(SpectraColor::White),
+            0x02 => Ok(SpectraColor::Yellow),
+            0x03 => Ok(SpectraColor::Red),
+            0x05 => Ok(SpectraColor::Blue),
+            0x06 => Ok(SpectraColor::Green),
+            e => Err(OutOfColorRangeParseError(e)),
+        }
+    }
+
+    /// Splits a byte into its two colors, from left to right
+    pub fn split_byte(byte: u8) -> Result<(SpectraColor, SpectraColor), OutOfColorRangeParseError> {
+        let left = SpectraColor::from_nibble(byte >> 4)?;
+        let right = SpectraColor::from_nibble(byte)?;
+        Ok((left, right))
+    }
+
+    /// Converts to limited range of RGB values.
+    pub fn rgb(self) -> (u8, u8, u8) {
+        match self {
+            SpectraColor::Black => (0x00, 0x00, 0x00),
+            SpectraColor::White => (0xff, 0xff, 0xff),
+            SpectraColor::Yellow => (0xff, 0xff, 0x00),
+            SpectraColor::Red => (0xff, 0x00, 0x00),
+            SpectraColor::Blue => (0x00, 0x00, 0xff),
+            SpectraColor::Green => (0x00, 0xff, 0x00),
+        }
+    }
+}
+
 impl OctColor {
     /// Gets the Nibble representation of the Color as needed by the display
     pub fn get_nibble(self) -> u8 {
@@ -452,6 +1073,15 @@ impl PixelColor for TriColor {
     type Raw = embedded_graphics_core::pixelcolor::raw::RawU2;
 }
 
+impl From<Color> for TriColor {
+    fn from(color: Color) -> TriColor {
+        match color {
+            Color::Black => TriColor::Black,
+            Color::White => TriColor::White,
+        }
+    }
+}
+
 #[cfg(feature = "graphics")]
 impl From<BinaryColor> for TriColor {
     fn from(b: BinaryColor) -> TriColor {
@@ -492,6 +1122,64 @@ impl From<TriColor> for embedded_graphics_core::pixelcolor::Rgb888 {
 mod tests {
     use super::*;
 
+    #[test]
+    fn filled_frames() {
+        let mut frame = [0x55; 4];
+        fill_frame(&mut frame, &Color::White);
+        assert_eq!(frame, [0xff; 4]);
+        fill_frame(&mut frame, &TriColor::from(Color::Black));
+        assert_eq!(frame, [0x00; 4]);
+        fill_frame(&mut frame, &TriColor::White);
+        assert_eq!(frame, [0xff, 0xff, 0x00, 0x00]);
+        fill_frame(&mut frame, &OctColor::from(Color::White));
+        assert_eq!(frame, [0x11; 4]);
+    }
+
+    #[test]
+    fn gray4_planes() {
+        // white, light gray, dark gray, black, then the other way round
+        let pixels = [0b1110_0100, 0b0001_1011];
+        assert_eq!(gray4_plane_byte(&pixels, 1), 0b1100_0011);
+        assert_eq!(gray4_plane_byte(&pixels, 0), 0b1010_0101);
+    }
+
+    #[test]
+    fn gray16() {
+        assert_eq!(Gray16::default(), Gray16::WHITE);
+        assert_eq!(Gray16::new(0x1a).luma(), 0xa);
+        assert_eq!(Gray16::new(0x5).bitmask(false, 0), (0x0f, 0x50));
+        assert_eq!(Gray16::new(0x5).bitmask(false, 3), (0xf0, 0x05));
+    }
+
+    #[cfg(feature = "graphics")]
+    #[test]
+    fn gray16_conversions() {
+        use embedded_graphics_core::pixelcolor::{Gray8, Rgb888};
+        assert_eq!(Gray16::from(BinaryColor::On), Gray16::BLACK);
+        assert_eq!(Gray16::from(Gray8::new(0x7f)), Gray16::new(0x7));
+        assert_eq!(Gray8::from(Gray16::WHITE), Gray8::new(0xff));
+        assert_eq!(Gray16::from(Rgb888::new(255, 255, 255)), Gray16::WHITE);
+        assert_eq!(Gray16::from(Rgb888::new(0, 0, 0)), Gray16::BLACK);
+    }
+
+    #[test]
+    fn gray4() {
+        assert_eq!(Gray4::default(), Gray4::WHITE);
+        assert_eq!(Gray4::new(0x6).luma(), 0x2);
+        assert_eq!(Gray4::new(0x1).bitmask(false, 0), (0x3f, 0x40));
+        assert_eq!(Gray4::new(0x2).bitmask(false, 7), (0xfc, 0x02));
+    }
+
+    #[cfg(feature = "graphics")]
+    #[test]
+    fn gray4_conversions() {
+        use embedded_graphics_core::pixelcolor::{Gray2, Gray8};
+        assert_eq!(Gray4::from(BinaryColor::On), Gray4::BLACK);
+        assert_eq!(Gray4::from(Gray2::new(2)), Gray4::LIGHT_GRAY);
+        assert_eq!(Gray4::from(Gray8::new(0x7f)), Gray4::DARK_GRAY);
+        assert_eq!(Gray8::from(Gray4::WHITE), Gray8::new(0xff));
+    }
+
     #[test]
     fn from_u8() {
         assert_eq!(Color::Black, Color::from(0u8));
@@ -530,6 +1218,95 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_quad() {
+        let colors = [
+            QuadColor::Red,
+            QuadColor::White,
+            QuadColor::Black,
+            QuadColor::Yellow,
+        ];
+        assert_eq!(QuadColor::colors_byte(colors), 0b11_01_00_10);
+        assert_eq!(QuadColor::split_byte(0b11_01_00_10), colors);
+        assert_eq!(QuadColor::Yellow.bitmask(false, 1), (0xcf, 0x20));
+        let mut frame = [0; 2];
+        fill_frame(&mut frame, &QuadColor::from(Color::White));
+        assert_eq!(frame, [0x55; 2]);
+    }
+
+    #[cfg(feature = "graphics")]
+    #[test]
+    fn quad_conversions() {
+        use embedded_graphics_core::pixelcolor::Rgb888;
+        assert_eq!(QuadColor::from(BinaryColor::On), QuadColor::Black);
+        assert_eq!(
+            QuadColor::from(Rgb888::new(0xff, 0xc0, 0x10)),
+            QuadColor::Yellow
+        );
+        assert_eq!(
+            QuadColor::from(Rgb888::new(0xc0, 0x20, 0x20)),
+            QuadColor::Red
+        );
+        assert_eq!(
+            Rgb888::from(QuadColor::White),
+            Rgb888::new(0xff, 0xff, 0xff)
+        );
+    }
+
+    #[cfg(feature = "graphics")]
+    #[test]
+    fn quad_display_packing() {
+        use crate::graphics::{display_len, Display};
+        use embedded_graphics_core::{prelude::*, Pixel};
+        use QuadColor::*;
+
+        // 3 bytes per line, 4 pixels per byte, the leftmost in the highest bits
+        let mut display =
+            Display::<12, 2, false, { display_len::<QuadColor>(12, 2) }, QuadColor>::default();
+        let _ = display.clear(White);
+        display.set_pixel(Pixel(Point::new(1, 0), Red));
+        display.set_pixel(Pixel(Point::new(11, 1), Yellow));
+        assert_eq!(
+            display.buffer(),
+            [
+                QuadColor::colors_byte([White, Red, White, White]),
+                0x55,
+                0x55,
+                0x55,
+                0x55,
+                QuadColor::colors_byte([White, White, White, Yellow]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_spectra() {
+        let left = SpectraColor::Blue;
+        let right = SpectraColor::Yellow;
+        assert_eq!(SpectraColor::colors_byte(left, right), 0x52);
+        assert_eq!(SpectraColor::split_byte(0x52), Ok((left, right)));
+        assert!(SpectraColor::from_nibble(0x04).is_err());
+        assert_eq!(SpectraColor::Green.bitmask(false, 1), (0xf0, 0x06));
+        let mut frame = [0; 2];
+        fill_frame(&mut frame, &SpectraColor::from(Color::White));
+        assert_eq!(frame, [0x11; 2]);
+    }
+
+    #[cfg(feature = "graphics")]
+    #[test]
+    fn spectra_conversions() {
+        use embedded_graphics_core::pixelcolor::Rgb888;
+        assert_eq!(SpectraColor::from(BinaryColor::On), SpectraColor::Black);
+        assert_eq!(
+            SpectraColor::from(Rgb888::new(0x20, 0x30, 0xd0)),
+            SpectraColor::Blue
+        );
+        assert_eq!(
+            SpectraColor::from(Rgb888::new(0x30, 0xc0, 0x40)),
+            SpectraColor::Green
+        );
+    }
+
     #[test]
     fn test_tricolor_bitmask() {
         assert_eq!(
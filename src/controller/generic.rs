@@ -0,0 +1,567 @@
+//! Driver of the black and white panels described at runtime
+//!
+//! Panels without a driver in the crate, e.g. a GoodDisplay panel sharing its controller with a
+//! supported one, can be driven by [`GenericEpd`] from a [`PanelDescriptor`]: the controller
+//! family, the size of the panel and the registers written by the initialisation and to select
+//! the waveforms, as found in the example code of the panel vendor.
+//!
+//! The descriptor is a plain value, so it can be chosen at boot or read from a configuration.
+//!
+//!```rust, no_run
+//!# use embedded_hal_mock::eh1::*;
+//!# fn main() -> Result<(), epd_waveshare::error::EpdError<embedded_hal::spi::ErrorKind>> {
+//!use epd_waveshare::controller::generic::*;
+//!use epd_waveshare::prelude::*;
+//!#
+//!# let expectations = [];
+//!# let mut spi = spi::Mock::new(&expectations);
+//!# let expectations = [];
+//!# let busy_in = digital::Mock::new(&expectations);
+//!# let dc = digital::Mock::new(&expectations);
+//!# let rst = digital::Mock::new(&expectations);
+//!# let mut delay = delay::NoopDelay::new();
+//!
+//!// a 152x152 UC8151 panel with the waveforms of the controller OTP
+//!const PANEL: PanelDescriptor = PanelDescriptor {
+//!    init: &[
+//!        // panel setting: OTP LUT, black and white, scan up, shift right, booster on
+//!        Register::new(0x00, &[0x1f, 0x0d]),
+//!        // resolution: 152 x 152
+//!        Register::new(0x61, &[0x98, 0x00, 0x98]),
+//!        Register::new(0x50, &[0x97]),
+//!    ],
+//!    ..PanelDescriptor::new(ControllerFamily::Uc81xx, 152, 152)
+//!};
+//!
+//!let mut epd = GenericEpd::new(&mut spi, busy_in, dc, rst, &mut delay, None, PANEL)?;
+//!let frame = [0xff; 152 / 8 * 152];
+//!epd.update_and_display_frame(&mut spi, &frame, &mut delay)?;
+//!epd.sleep(&mut spi, &mut delay)?;
+//!# Ok(())
+//!# }
+//!```
+
+use crate::error::EpdError;
+use crate::pin::BusyPin;
+use embedded_hal::{delay::*, digital::*, spi::SpiDevice};
+
+use crate::buffer_len;
+use crate::color::Color;
+use crate::controller::ssd16xx::Addressing;
+use crate::interface::DisplayInterface;
+#[cfg(feature = "async")]
+use crate::interface::DisplayInterfaceAsync;
+use crate::ram_addressing::{Ssd1677, Ssd1680};
+#[cfg(feature = "async")]
+use crate::traits::AsyncBusyPin;
+use crate::traits::{self, RefreshLut, ResetTiming};
+use crate::type_a::command::Command as SsdCommand;
+#[cfg(feature = "async")]
+use embedded_hal_async::{delay::DelayNs as AsyncDelayNs, spi::SpiDevice as AsyncSpiDevice};
+
+const SINGLE_BYTE_WRITE: bool = false;
+
+/// Family of the controller of a panel, setting the commands of the driver
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ControllerFamily {
+    /// Solomon Systech SSD16xx (SSD1608, SSD1675, SSD1680, SSD1681, SSD1683, SSD1677, ...): BUSY
+    /// high while busy, the frame is written to a RAM window
+    Ssd16xx(Addressing),
+    /// UltraChip UC81xx and their clones (UC8151, UC8176, IL0373, ...): BUSY low while busy,
+    /// the frame is sent with the data start transmission commands
+    Uc81xx,
+}
+
+/// A command and its data
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Register<'a> {
+    /// Address of the command
+    pub command: u8,
+    /// Data following the command
+    pub data: &'a [u8],
+}
+
+impl<'a> Register<'a> {
+    /// Writes `data` with `command`
+    pub const fn new(command: u8, data: &'a [u8]) -> Self {
+        Register { command, data }
+    }
+}
+
+/// Description of a panel
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PanelDescriptor<'a> {
+    /// Controller of the panel
+    pub controller: ControllerFamily,
+    /// Width of the panel in pixels
+    pub width: u32,
+    /// Height of the panel in pixels
+    pub height: u32,
+    /// Registers written by the initialisation, after the reset
+    ///
+    /// The driver only sends the commands which don't depend on the panel: the software reset of
+    /// the SSD16xx, then their data entry mode and RAM window, the power on of the UC81xx. The
+    /// descriptor gives the others, e.g. the driver output control or the resolution.
+    pub init: &'a [Register<'a>],
+    /// Registers written after the initialisation for the full refreshes, e.g. their LUTs
+    ///
+    /// Empty for the waveform of the controller OTP.
+    pub lut_full: &'a [Register<'a>],
+    /// Registers written for the quick refreshes, after `set_lut(Some(RefreshLut::Quick))`
+    ///
+    /// Empty for the quick waveform of the OTP, only found in the SSD16xx.
+    pub lut_quick: &'a [Register<'a>],
+}
+
+impl<'a> PanelDescriptor<'a> {
+    /// Descriptor of a `width` x `height` panel without any register to write
+    pub const fn new(controller: ControllerFamily, width: u32, height: u32) -> Self {
+        PanelDescriptor {
+            controller,
+            width,
+            height,
+            init: &[],
+            lut_full: &[],
+            lut_quick: &[],
+        }
+    }
+
+    /// Length of the frames of the panel
+    pub const fn frame_len(&self) -> usize {
+        buffer_len(self.width as usize, self.height as usize)
+    }
+}
+
+/// Commands of the UC81xx used by the driver
+#[derive(Clone, Copy)]
+enum UcCommand {
+    PowerOff = 0x02,
+    PowerOn = 0x04,
+    DeepSleep = 0x07,
+    DataStartTransmission1 = 0x10,
+    DisplayRefresh = 0x12,
+    DataStartTransmission2 = 0x13,
+    GetStatus = 0x71,
+}
+
+impl traits::Command for UcCommand {
+    fn address(self) -> u8 {
+        self as u8
+    }
+}
+
+/// A command given by its address
+#[derive(Clone, Copy)]
+struct RawCommand(u8);
+
+impl traits::Command for RawCommand {
+    fn address(self) -> u8 {
+        self.0
+    }
+}
+
+#[maybe_async_cfg::maybe(
+    sync(keep_self),
+    async(
+        feature = "async",
+        idents(
+            GenericEpd,
+            DisplayInterface,
+            SpiDevice(async = "AsyncSpiDevice"),
+            BusyPin(async = "AsyncBusyPin"),
+            DelayNs(async = "AsyncDelayNs")
+        )
+    )
+)]
+/// Driver of a panel given by a [`PanelDescriptor`]
+///
+/// It has the methods of [`WaveshareDisplay`](crate::traits::WaveshareDisplay), whose `new`
+/// can't be given the descriptor.
+pub struct GenericEpd<'a, SPI, BUSY, DC, RST, DELAY> {
+    /// Connection Interface
+    interface: DisplayInterface<SPI, BUSY, DC, RST, DELAY, SINGLE_BYTE_WRITE>,
+    /// Description of the panel
+    panel: PanelDescriptor<'a>,
+    /// Background Color
+    background_color: Color,
+    /// Refresh LUT
+    refresh: RefreshLut,
+}
+
+#[maybe_async_cfg::maybe(
+    sync(keep_self),
+    async(
+        feature = "async",
+        idents(
+            GenericEpd,
+            DisplayInterface,
+            SpiDevice(async = "AsyncSpiDevice"),
+            BusyPin(async = "AsyncBusyPin"),
+            DelayNs(async = "AsyncDelayNs")
+        )
+    )
+)]
+impl<'a, SPI, BUSY, DC, RST, DELAY> GenericEpd<'a, SPI, BUSY, DC, RST, DELAY>
+where
+    SPI: SpiDevice,
+    BUSY: BusyPin,
+    DC: OutputPin,
+    RST: OutputPin,
+    DELAY: DelayNs,
+{
+    /// Creates the driver of `panel` and initialises the panel
+    ///
+    /// `delay_us` is the delay between the polls of BUSY, 10ms if `None`.
+    pub async fn new(
+        spi: &mut SPI,
+        busy: BUSY,
+        dc: DC,
+        rst: RST,
+        delay: &mut DELAY,
+        delay_us: Option<u32>,
+        panel: PanelDescriptor<'a>,
+    ) -> Result<Self, EpdError<SPI::Error>> {
+        let interface = DisplayInterface::new(busy, dc, rst, delay_us);
+
+        let mut epd = GenericEpd {
+            interface,
+            panel,
+            background_color: Color::White,
+            refresh: RefreshLut::Full,
+        };
+
+        epd.init(spi, delay).await?;
+
+        Ok(epd)
+    }
+
+    /// Description of the panel
+    pub fn panel(&self) -> &PanelDescriptor<'a> {
+        &self.panel
+    }
+
+    /// Width of the panel
+    pub fn width(&self) -> u32 {
+        self.panel.width
+    }
+
+    /// Height of the panel
+    pub fn height(&self) -> u32 {
+        self.panel.height
+    }
+
+    async fn init(&mut self, spi: &mut SPI, delay: &mut DELAY) -> Result<(), EpdError<SPI::Error>> {
+        self.interface.reset(delay, 10_000, 10_000).await?;
+        self.wait_until_idle(spi, delay).await?;
+
+        if let ControllerFamily::Ssd16xx(_) = self.panel.controller {
+            self.interface.cmd(spi, SsdCommand::SwReset).await?;
+            self.wait_until_idle(spi, delay).await?;
+        }
+
+        let init = self.panel.init;
+        self.write_registers(spi, init).await?;
+
+        match self.panel.controller {
+            ControllerFamily::Ssd16xx(_) => {
+                // x increment, y increment, address counter is updated in x direction
+                self.interface
+                    .cmd_with_data(spi, SsdCommand::DataEntryModeSetting, &[0x03])
+                    .await?;
+                self.use_full_frame(spi).await?;
+            }
+            ControllerFamily::Uc81xx => {
+                self.interface.cmd(spi, UcCommand::PowerOn).await?;
+            }
+        }
+        self.wait_until_idle(spi, delay).await?;
+
+        self.write_lut(spi).await
+    }
+
+    async fn write_registers(
+        &mut self,
+        spi: &mut SPI,
+        registers: &[Register<'_>],
+    ) -> Result<(), EpdError<SPI::Error>> {
+        for register in registers {
+            self.interface
+                .cmd_with_data(spi, RawCommand(register.command), register.data)
+                .await?;
+        }
+        Ok(())
+    }
+
+    async fn write_lut(&mut self, spi: &mut SPI) -> Result<(), EpdError<SPI::Error>> {
+        let lut = match self.refresh {
+            RefreshLut::Quick => self.panel.lut_quick,
+            _ => self.panel.lut_full,
+        };
+        self.write_registers(spi, lut).await
+    }
+
+    async fn use_full_frame(&mut self, spi: &mut SPI) -> Result<(), EpdError<SPI::Error>> {
+        let (end_x, end_y) = (self.panel.width - 1, self.panel.height - 1);
+        match self.panel.controller {
+            ControllerFamily::Ssd16xx(Addressing::Bytes) => {
+                self.interface
+                    .set_ram_area::<Ssd1680>(spi, 0, 0, end_x, end_y)
+                    .await?;
+                self.interface.set_ram_counter::<Ssd1680>(spi, 0, 0).await
+            }
+            ControllerFamily::Ssd16xx(Addressing::Pixels) => {
+                self.interface
+                    .set_ram_area::<Ssd1677>(spi, 0, 0, end_x, end_y)
+                    .await?;
+                self.interface.set_ram_counter::<Ssd1677>(spi, 0, 0).await
+            }
+            ControllerFamily::Uc81xx => Ok(()),
+        }
+    }
+
+    /// Puts the panel to deep sleep, [`wake_up`](Self::wake_up) initialises it again
+    pub async fn sleep(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.wait_until_idle(spi, delay).await?;
+        match self.panel.controller {
+            ControllerFamily::Ssd16xx(_) => {
+                self.interface
+                    .cmd_with_data(spi, SsdCommand::DeepSleepMode, &[0x01])
+                    .await
+            }
+            ControllerFamily::Uc81xx => {
+                self.interface.cmd(spi, UcCommand::PowerOff).await?;
+                self.wait_until_idle(spi, delay).await?;
+                // check code
+                self.interface
+                    .cmd_with_data(spi, UcCommand::DeepSleep, &[0xA5])
+                    .await
+            }
+        }
+    }
+
+    /// Wakes the panel up from deep sleep
+    pub async fn wake_up(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.init(spi, delay).await
+    }
+
+    /// Transmits a full frame to the panel
+    pub async fn update_frame(
+        &mut self,
+        spi: &mut SPI,
+        buffer: &[u8],
+        delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        EpdError::check_buffer(buffer, self.panel.frame_len())?;
+        self.wait_until_idle(spi, delay).await?;
+        match self.panel.controller {
+            ControllerFamily::Ssd16xx(_) => {
+                self.interface
+                    .cmd_with_data(spi, SsdCommand::WriteRam, buffer)
+                    .await
+            }
+            ControllerFamily::Uc81xx => {
+                self.interface
+                    .cmd_with_data(spi, UcCommand::DataStartTransmission2, buffer)
+                    .await
+            }
+        }
+    }
+
+    /// Keeps `buffer` as the old frame the quick refreshes compare the new one with
+    async fn update_old_frame(
+        &mut self,
+        spi: &mut SPI,
+        buffer: &[u8],
+    ) -> Result<(), EpdError<SPI::Error>> {
+        match self.panel.controller {
+            ControllerFamily::Ssd16xx(_) => {
+                self.interface
+                    .cmd_with_data(spi, SsdCommand::WriteRam2, buffer)
+                    .await
+            }
+            ControllerFamily::Uc81xx => {
+                self.interface
+                    .cmd_with_data(spi, UcCommand::DataStartTransmission1, buffer)
+                    .await
+            }
+        }
+    }
+
+    /// Refreshes the panel with the waveform selected by [`set_lut`](Self::set_lut)
+    pub async fn display_frame(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.wait_until_idle(spi, delay).await?;
+        match self.panel.controller {
+            ControllerFamily::Ssd16xx(_) => {
+                // the LUT is loaded from the OTP unless the descriptor wrote it, display mode 2
+                // only changes the pixels which differ from the old frame
+                let sequence = match (self.refresh, self.panel.lut_quick.is_empty()) {
+                    (RefreshLut::Quick, true) => 0xFF,
+                    (RefreshLut::Quick, false) => 0xCF,
+                    (_, _) if self.panel.lut_full.is_empty() => 0xF7,
+                    (_, _) => 0xC7,
+                };
+                self.interface
+                    .cmd_with_data(spi, SsdCommand::DisplayUpdateControl2, &[sequence])
+                    .await?;
+                self.interface
+                    .cmd(spi, SsdCommand::MasterActivation)
+                    .await?;
+            }
+            ControllerFamily::Uc81xx => {
+                self.interface.cmd(spi, UcCommand::DisplayRefresh).await?;
+                delay.delay_us(200).await;
+            }
+        }
+        self.wait_until_idle(spi, delay).await
+    }
+
+    /// Transmits and displays a full frame
+    ///
+    /// The frame is also kept as the old frame of the next quick refresh.
+    pub async fn update_and_display_frame(
+        &mut self,
+        spi: &mut SPI,
+        buffer: &[u8],
+        delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.update_frame(spi, buffer, delay).await?;
+        self.display_frame(spi, delay).await?;
+        self.update_old_frame(spi, buffer).await
+    }
+
+    /// Fills the old and the new frame with the background color, without refreshing
+    pub async fn clear_frame(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.wait_until_idle(spi, delay).await?;
+
+        let color = self.background_color.get_byte_value();
+        let len = self.panel.frame_len() as u32;
+        let (old, new) = match self.panel.controller {
+            ControllerFamily::Ssd16xx(_) => (
+                RawCommand(SsdCommand::WriteRam2 as u8),
+                RawCommand(SsdCommand::WriteRam as u8),
+            ),
+            ControllerFamily::Uc81xx => (
+                RawCommand(UcCommand::DataStartTransmission1 as u8),
+                RawCommand(UcCommand::DataStartTransmission2 as u8),
+            ),
+        };
+        self.interface.cmd(spi, old).await?;
+        self.interface.data_x_times(spi, color, len).await?;
+        self.interface.cmd(spi, new).await?;
+        self.interface.data_x_times(spi, color, len).await
+    }
+
+    /// Sets the color of [`clear_frame`](Self::clear_frame)
+    pub fn set_background_color(&mut self, background_color: Color) {
+        self.background_color = background_color;
+    }
+
+    /// Color of [`clear_frame`](Self::clear_frame)
+    pub fn background_color(&self) -> &Color {
+        &self.background_color
+    }
+
+    /// Selects the full or the quick refresh and writes the registers of its LUT
+    ///
+    /// The UC81xx only have the quick refresh if the descriptor has its LUT.
+    pub async fn set_lut(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+        refresh_rate: Option<RefreshLut>,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        if let Some(refresh_lut) = refresh_rate {
+            let quick = match self.panel.controller {
+                ControllerFamily::Uc81xx if self.panel.lut_quick.is_empty() => &[][..],
+                _ => &[RefreshLut::Quick][..],
+            };
+            self.refresh = refresh_lut.or_full(quick);
+        }
+        self.wait_until_idle(spi, delay).await?;
+        self.write_lut(spi).await
+    }
+
+    /// Sets the longest wait for the controller, `None` waiting forever
+    pub fn set_busy_timeout(&mut self, timeout_us: Option<u32>) {
+        self.interface.config_mut().busy_timeout_us = timeout_us;
+    }
+
+    /// Sets the delay between the polls of BUSY, `0` polling without delay
+    pub fn set_busy_poll(&mut self, interval_us: u32) {
+        self.interface.config_mut().busy_poll_us = interval_us;
+    }
+
+    /// Sets the function called before each poll of BUSY with the time waited in us
+    pub fn set_on_busy_wait(&mut self, hook: Option<fn(u32)>) {
+        self.interface.config_mut().on_busy_wait = hook;
+    }
+
+    /// Sets the timing of the resets done by [`wake_up`](Self::wake_up), `None` for the
+    /// default one
+    pub fn set_reset_timing(&mut self, timing: Option<ResetTiming>) {
+        self.interface.config_mut().reset_timing = timing;
+    }
+
+    /// Sets the longest SPI write in bytes, `None` not splitting the writes
+    pub fn set_max_transfer(&mut self, max_bytes: Option<usize>) {
+        self.interface.config_mut().max_transfer = max_bytes;
+    }
+
+    /// Sets whether the data is written one byte per SPI transaction, `false` by default
+    pub fn set_single_byte_write(&mut self, single_byte_write: bool) {
+        self.interface.config_mut().single_byte_write = single_byte_write;
+    }
+
+    /// Returns the BUSY, DC and RST pins
+    pub fn release(self) -> (BUSY, DC, RST) {
+        self.interface.release()
+    }
+
+    /// Waits until the controller isn't busy anymore
+    pub async fn wait_until_idle(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        match self.panel.controller {
+            ControllerFamily::Ssd16xx(_) => self.interface.wait_until_idle(delay, false).await,
+            ControllerFamily::Uc81xx => {
+                self.interface
+                    .wait_until_idle_with_cmd(spi, delay, true, UcCommand::GetStatus)
+                    .await
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn descriptor() {
+        const PANEL: PanelDescriptor = PanelDescriptor {
+            init: &[Register::new(0x00, &[0x1f, 0x0d])],
+            ..PanelDescriptor::new(ControllerFamily::Uc81xx, 152, 152)
+        };
+        assert_eq!(PANEL.frame_len(), 2888);
+        assert_eq!(PANEL.init[0].data, &[0x1f, 0x0d]);
+        assert!(PANEL.lut_full.is_empty() && PANEL.lut_quick.is_empty());
+    }
+}
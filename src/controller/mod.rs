@@ -0,0 +1,14 @@
+//! Drivers shared by the panels of a controller family
+//!
+//! The panels of a family differ by a few settings only: their resolution, how the controller
+//! addresses its RAM and how the refreshes are configured. A driver of this module is given
+//! these settings by a `Panel` type, see [`ssd16xx::Panel`] and [`uc81xx::Panel`], so a new panel
+//! is a configuration table instead of a whole driver, and panels which aren't supported by the
+//! crate can be described by the applications themselves.
+//!
+//! When the panel is only known at runtime, [`GenericEpd`](generic::GenericEpd) is given its
+//! description, with the registers to write, when it's created.
+
+pub mod generic;
+pub mod ssd16xx;
+pub mod uc81xx;
@@ -0,0 +1,1007 @@
+//! Black and white or tri-color panels driven by a Solomon Systech SSD16xx controller (SSD1608,
+//! SSD1680, SSD1683, SSD1677, ...)
+//!
+//! The frame is written to the first RAM, the second RAM keeps the old frame of the quick
+//! refreshes, which only change the pixels differing from it (display mode 2). The tri-color
+//! panels, an `Ssd16xx<P, SPI, BUSY, DC, RST, DELAY, TriColor>`, use the second RAM for the
+//! chromatic pixels instead and have no quick refresh.
+//!
+//! The waveforms are the ones of the controller OTP, or the ones of [`PanelConfig::luts`]
+//! written to the LUT register. The [`PanelConfig`] of a panel also gives its voltages, the
+//! polarity of its BUSY pin and the temperature loading the fast waveform of the OTP, if it has
+//! one.
+//!
+//! Clone glass wired the other way round is fixed with a [`ScanConfig`], see
+//! [`set_scan_config`](Ssd16xx::set_scan_config).
+//!
+//! # Describing a panel
+//!
+//!```rust, no_run
+//!# use embedded_hal_mock::eh1::*;
+//!# fn main() -> Result<(), epd_waveshare::error::EpdError<embedded_hal::spi::ErrorKind>> {
+//!use epd_waveshare::controller::ssd16xx::*;
+//!use epd_waveshare::prelude::*;
+//!#
+//!# let expectations = [];
+//!# let mut spi = spi::Mock::new(&expectations);
+//!# let expectations = [];
+//!# let busy_in = digital::Mock::new(&expectations);
+//!# let dc = digital::Mock::new(&expectations);
+//!# let rst = digital::Mock::new(&expectations);
+//!# let mut delay = delay::NoopDelay::new();
+//!
+//!/// A 200x200 panel on an SSD1681
+//!struct MyPanel;
+//!
+//!impl Panel for MyPanel {
+//!    const CONFIG: PanelConfig = PanelConfig::new(200, 200);
+//!}
+//!
+//!let mut epd = Ssd16xx::<MyPanel, _, _, _, _, _>::new(&mut spi, busy_in, dc, rst, &mut delay, None)?;
+//!epd.clear_frame(&mut spi, &mut delay)?;
+//!epd.display_frame(&mut spi, &mut delay)?;
+//!# Ok(())
+//!# }
+//!```
+
+use core::marker::PhantomData;
+
+use crate::error::EpdError;
+use crate::pin::BusyPin;
+use embedded_hal::{delay::*, digital::*, spi::SpiDevice};
+
+use crate::buffer_len;
+use crate::color::{Color, TriColor};
+#[cfg(feature = "async")]
+use crate::interface::DisplayInterfaceAsync;
+use crate::interface::{BusyTimings, DisplayInterface};
+use crate::ram_addressing::{Ssd1677, Ssd1680};
+use crate::rect::Rect;
+use crate::scan::ScanConfig;
+#[cfg(feature = "async")]
+use crate::traits::{
+    AsyncBusyPin, InternalWiAdditionsAsync, QuickRefreshAsync, WaveshareDisplayAsync,
+    WaveshareThreeColorDisplayAsync,
+};
+use crate::traits::{
+    InterfaceConfig, InternalWiAdditions, QuickRefresh, RefreshLut, WaveshareDisplay,
+    WaveshareThreeColorDisplay,
+};
+use crate::type_a::command::Command;
+#[cfg(feature = "async")]
+use embedded_hal_async::{delay::DelayNs as AsyncDelayNs, spi::SpiDevice as AsyncSpiDevice};
+
+const SINGLE_BYTE_WRITE: bool = false;
+
+/// Enable clock and analog, load temperature and the LUT, display mode 1, disable
+const FULL_REFRESH: u8 = 0xF7;
+/// Same in display mode 2, only changing the pixels which differ from the old frame
+const QUICK_REFRESH: u8 = 0xFF;
+/// Same as the full refresh with the LUT loaded by `load_waveform`
+const FAST_REFRESH: u8 = 0xC7;
+
+/// How the controller addresses the columns of its RAM
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Addressing {
+    /// In bytes of 8 pixels (SSD1608, SSD1675, SSD1680, SSD1681, SSD1683)
+    Bytes,
+    /// In pixels (SSD1677)
+    Pixels,
+}
+
+/// Voltages of a panel, the ones which are `None` or empty keep the defaults of the controller
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Voltages {
+    /// Booster soft start control, 3 bytes on the SSD1608 and SSD1680, 5 on the SSD1677
+    pub booster: &'static [u8],
+    /// Gate driving voltage (VGH)
+    pub gate: Option<u8>,
+    /// Source driving voltages (VSH1, VSH2, VSL)
+    pub source: Option<[u8; 3]>,
+    /// VCOM register
+    pub vcom: Option<u8>,
+}
+
+impl Voltages {
+    /// The voltages of the controller, or the ones of its OTP
+    pub const DEFAULT: Self = Voltages {
+        booster: &[],
+        gate: None,
+        source: None,
+        vcom: None,
+    };
+}
+
+/// Waveforms written to the LUT register instead of the ones of the controller OTP
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Luts {
+    /// LUT of the full refreshes
+    pub full: &'static [u8],
+    /// LUT of the quick refreshes, selected with `set_lut(Some(RefreshLut::Quick))`
+    pub quick: &'static [u8],
+    /// Display update control 2 of the refreshes, which mustn't load the LUT of the OTP
+    pub refresh: u8,
+    /// Dummy line period of the waveforms
+    pub dummy_line_period: u8,
+    /// Gate line width of the waveforms
+    pub gate_line_width: u8,
+}
+
+/// Settings of a panel
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PanelConfig {
+    /// Width of the panel in pixels, the number of sources
+    pub width: u32,
+    /// Height of the panel in pixels, the number of gates
+    pub height: u32,
+    /// Addresses of the RAM columns
+    pub addressing: Addressing,
+    /// Whether the BUSY pin is low while the controller is busy
+    pub is_busy_low: bool,
+    /// Whether the data is written byte by byte, see
+    /// [`set_single_byte_write`](WaveshareDisplay::set_single_byte_write)
+    pub single_byte_write: bool,
+    /// Hardware reset in us: time RST is high before the pulse and length of the pulse
+    pub reset_us: [u32; 2],
+    /// Whether the controller is reset by software after the hardware reset
+    pub sw_reset: bool,
+    /// Border waveform control, `0x05` makes the border follow the LUT of the white pixels,
+    /// `None` keeps the default of the controller
+    pub border_waveform: Option<u8>,
+    /// Temperature sensor selection, `None` for the controllers without one (SSD1608)
+    pub temperature_sensor: Option<u8>,
+    /// Voltages of the panel
+    pub voltages: Voltages,
+    /// Waveforms of the panel, `None` for the ones of the controller OTP
+    pub luts: Option<Luts>,
+    /// Temperature forced to load the fast waveform of the OTP selected with
+    /// `set_lut(Some(RefreshLut::Fast))`, `None` if the panel doesn't have one
+    pub fast_temperature: Option<u8>,
+    /// Display update control 1 of the full refreshes: RAM options and first source output,
+    /// `None` keeps the default of the controller
+    pub update_control_full: Option<[u8; 2]>,
+    /// Display update control 1 of the quick refreshes
+    pub update_control_quick: Option<[u8; 2]>,
+}
+
+impl PanelConfig {
+    /// Settings of a `width` x `height` panel with the defaults of the controllers: addressed
+    /// in bytes, BUSY high while busy, software reset, white border, internal temperature sensor,
+    /// voltages and waveforms of the OTP, both RAMs used as they are and the first source output
+    /// at S0
+    pub const fn new(width: u32, height: u32) -> Self {
+        PanelConfig {
+            width,
+            height,
+            addressing: Addressing::Bytes,
+            is_busy_low: false,
+            single_byte_write: false,
+            reset_us: [10_000, 10_000],
+            sw_reset: true,
+            border_waveform: Some(0x05),
+            temperature_sensor: Some(0x80),
+            voltages: Voltages::DEFAULT,
+            luts: None,
+            fast_temperature: None,
+            update_control_full: Some([0x00, 0x00]),
+            update_control_quick: Some([0x00, 0x00]),
+        }
+    }
+}
+
+/// A panel driven by [`Ssd16xx`]
+pub trait Panel {
+    /// Settings of the panel
+    const CONFIG: PanelConfig;
+}
+
+/// Colors of the panels driven by [`Ssd16xx`]: [`Color`] or [`TriColor`]
+pub trait RamColor: Copy + From<Color> {
+    /// Whether the second RAM holds the chromatic pixels instead of the old frame
+    const CHROMATIC: bool;
+
+    /// Bytes filling the first and the second RAM with the color
+    fn ram_bytes(self) -> (u8, u8);
+}
+
+impl RamColor for Color {
+    const CHROMATIC: bool = false;
+
+    fn ram_bytes(self) -> (u8, u8) {
+        let byte = self.get_byte_value();
+        (byte, byte)
+    }
+}
+
+impl RamColor for TriColor {
+    const CHROMATIC: bool = true;
+
+    fn ram_bytes(self) -> (u8, u8) {
+        let chromatic = match self {
+            TriColor::Chromatic => 0xff,
+            TriColor::White | TriColor::Black => 0x00,
+        };
+        (self.get_byte_value(), chromatic)
+    }
+}
+
+#[maybe_async_cfg::maybe(
+    sync(keep_self),
+    async(
+        feature = "async",
+        idents(
+            Ssd16xx,
+            DisplayInterface,
+            WaveshareDisplay,
+            InternalWiAdditions,
+            QuickRefresh,
+            WaveshareThreeColorDisplay,
+            SpiDevice(async = "AsyncSpiDevice"),
+            BusyPin(async = "AsyncBusyPin"),
+            DelayNs(async = "AsyncDelayNs")
+        )
+    )
+)]
+/// Driver of the panels of an SSD16xx controller, black and white unless `C` is [`TriColor`]
+pub struct Ssd16xx<P, SPI, BUSY, DC, RST, DELAY, C = Color> {
+    /// Connection Interface
+    interface: DisplayInterface<SPI, BUSY, DC, RST, DELAY, SINGLE_BYTE_WRITE>,
+    /// Background Color
+    background_color: C,
+    /// Refresh LUT
+    refresh: RefreshLut,
+    /// Scan directions
+    scan: ScanConfig,
+    _panel: PhantomData<P>,
+}
+
+#[maybe_async_cfg::maybe(
+    sync(keep_self),
+    async(
+        feature = "async",
+        idents(
+            Ssd16xx,
+            DisplayInterface,
+            WaveshareDisplay,
+            InternalWiAdditions,
+            QuickRefresh,
+            WaveshareThreeColorDisplay,
+            SpiDevice(async = "AsyncSpiDevice"),
+            BusyPin(async = "AsyncBusyPin"),
+            DelayNs(async = "AsyncDelayNs")
+        )
+    )
+)]
+impl<P, SPI, BUSY, DC, RST, DELAY, C> InternalWiAdditions<SPI, BUSY, DC, RST, DELAY>
+    for Ssd16xx<P, SPI, BUSY, DC, RST, DELAY, C>
+where
+    P: Panel,
+    C: RamColor,
+    SPI: SpiDevice,
+    BUSY: BusyPin,
+    DC: OutputPin,
+    RST: OutputPin,
+    DELAY: DelayNs,
+{
+    async fn init(&mut self, spi: &mut SPI, delay: &mut DELAY) -> Result<(), EpdError<SPI::Error>> {
+        let [high_us, low_us] = P::CONFIG.reset_us;
+        self.interface.reset(delay, high_us, low_us).await?;
+
+        self.wait_until_idle(spi, delay).await?;
+        if P::CONFIG.sw_reset {
+            self.interface.cmd(spi, Command::SwReset).await?;
+            self.wait_until_idle(spi, delay).await?;
+        }
+
+        self.set_scan(spi).await?;
+
+        if let Some(border) = P::CONFIG.border_waveform {
+            self.interface
+                .cmd_with_data(spi, Command::BorderWaveformControl, &[border])
+                .await?;
+        }
+
+        if let Some(sensor) = P::CONFIG.temperature_sensor {
+            self.interface
+                .cmd_with_data(spi, Command::TemperatureSensorSelection, &[sensor])
+                .await?;
+        }
+
+        let voltages = P::CONFIG.voltages;
+        if !voltages.booster.is_empty() {
+            self.interface
+                .cmd_with_data(spi, Command::BoosterSoftStartControl, voltages.booster)
+                .await?;
+        }
+        if let Some(gate) = voltages.gate {
+            self.interface
+                .cmd_with_data(spi, Command::GateDrivingVoltage, &[gate])
+                .await?;
+        }
+        if let Some(source) = voltages.source {
+            self.interface
+                .cmd_with_data(spi, Command::SourceDrivingVoltage, &source)
+                .await?;
+        }
+        if let Some(vcom) = voltages.vcom {
+            self.interface
+                .cmd_with_data(spi, Command::WriteVcomRegister, &[vcom])
+                .await?;
+        }
+
+        if let Some(luts) = P::CONFIG.luts {
+            self.interface
+                .cmd_with_data(spi, Command::SetDummyLinePeriod, &[luts.dummy_line_period])
+                .await?;
+            self.interface
+                .cmd_with_data(spi, Command::SetGateLineWidth, &[luts.gate_line_width])
+                .await?;
+        }
+
+        self.use_full_frame(spi, delay).await?;
+        self.load_waveform(spi, delay).await
+    }
+}
+
+#[maybe_async_cfg::maybe(
+    sync(keep_self),
+    async(
+        feature = "async",
+        idents(
+            Ssd16xx,
+            DisplayInterface,
+            WaveshareDisplay,
+            InternalWiAdditions,
+            QuickRefresh,
+            WaveshareThreeColorDisplay,
+            SpiDevice(async = "AsyncSpiDevice"),
+            BusyPin(async = "AsyncBusyPin"),
+            DelayNs(async = "AsyncDelayNs")
+        )
+    )
+)]
+impl<P, SPI, BUSY, DC, RST, DELAY, C> WaveshareDisplay<SPI, BUSY, DC, RST, DELAY>
+    for Ssd16xx<P, SPI, BUSY, DC, RST, DELAY, C>
+where
+    P: Panel,
+    C: RamColor,
+    SPI: SpiDevice,
+    BUSY: BusyPin,
+    DC: OutputPin,
+    RST: OutputPin,
+    DELAY: DelayNs,
+{
+    type DisplayColor = C;
+    fn width(&self) -> u32 {
+        P::CONFIG.width
+    }
+
+    fn height(&self) -> u32 {
+        P::CONFIG.height
+    }
+
+    async fn new(
+        spi: &mut SPI,
+        busy: BUSY,
+        dc: DC,
+        rst: RST,
+        delay: &mut DELAY,
+        delay_us: Option<u32>,
+    ) -> Result<Self, EpdError<SPI::Error>> {
+        let mut epd = Self::new_uninit(busy, dc, rst, delay_us);
+        epd.init(spi, delay).await?;
+        Ok(epd)
+    }
+
+    fn new_uninit(busy: BUSY, dc: DC, rst: RST, delay_us: Option<u32>) -> Self {
+        let mut interface = DisplayInterface::new(busy, dc, rst, delay_us);
+        if C::CHROMATIC {
+            interface = interface.with_busy_timings(BusyTimings::SSD16XX_COLOR);
+        }
+        interface.config_mut().single_byte_write = P::CONFIG.single_byte_write;
+
+        Ssd16xx {
+            interface,
+            background_color: Color::White.into(),
+            refresh: RefreshLut::Full,
+            scan: ScanConfig::default(),
+            _panel: PhantomData,
+        }
+    }
+
+    async fn sleep(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.wait_until_idle(spi, delay).await?;
+        // 0x00 for Normal mode (Power on Reset), 0x01 for Deep Sleep Mode
+        self.interface
+            .cmd_with_data(spi, Command::DeepSleepMode, &[0x01])
+            .await
+    }
+
+    async fn wake_up(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.init(spi, delay).await
+    }
+
+    async fn update_frame(
+        &mut self,
+        spi: &mut SPI,
+        buffer: &[u8],
+        delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        EpdError::check_buffer(buffer, Self::frame_len())?;
+        self.use_full_frame(spi, delay).await?;
+        self.interface
+            .cmd_with_data(spi, Command::WriteRam, buffer)
+            .await?;
+        if C::CHROMATIC {
+            // the frame is shown without chromatic pixels
+            self.interface.cmd(spi, Command::WriteRam2).await?;
+            self.interface
+                .data_x_times(spi, 0x00, Self::frame_len() as u32)
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// `x` and `width` must be multiples of 8
+    async fn update_partial_frame(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+        buffer: &[u8],
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.write_window(spi, delay, Command::WriteRam, buffer, x, y, width, height)
+            .await
+    }
+
+    /// Refreshes with the full waveform, the one of
+    /// [`display_new_frame`](QuickRefresh::display_new_frame) after
+    /// `set_lut(Some(RefreshLut::Quick))` or the fast one of the OTP after
+    /// `set_lut(Some(RefreshLut::Fast))`
+    async fn display_frame(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.turn_on_display(spi, delay, self.refresh).await
+    }
+
+    async fn update_and_display_frame(
+        &mut self,
+        spi: &mut SPI,
+        buffer: &[u8],
+        delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.update_frame(spi, buffer, delay).await?;
+        self.display_frame(spi, delay).await
+    }
+
+    async fn clear_frame(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.use_full_frame(spi, delay).await?;
+
+        // clear both the new and the old frame, or the chromatic pixels, with the background color
+        let (first, second) = self.background_color.ram_bytes();
+        let len = Self::frame_len() as u32;
+        self.interface.cmd(spi, Command::WriteRam).await?;
+        self.interface.data_x_times(spi, first, len).await?;
+        self.interface.cmd(spi, Command::WriteRam2).await?;
+        self.interface.data_x_times(spi, second, len).await
+    }
+
+    fn set_background_color(&mut self, background_color: C) {
+        self.background_color = background_color;
+    }
+
+    fn background_color(&self) -> &C {
+        &self.background_color
+    }
+
+    /// Selects the full, the quick or the fast waveform if the panel has one, see
+    /// [`PanelConfig::fast_temperature`]
+    ///
+    /// The waveforms of [`PanelConfig::luts`] are written to the LUT register. The tri-color
+    /// panels have no quick waveform.
+    async fn set_lut(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+        refresh_rate: Option<RefreshLut>,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        if let Some(refresh_lut) = refresh_rate {
+            self.refresh = match (C::CHROMATIC, P::CONFIG.fast_temperature) {
+                (false, Some(_)) => refresh_lut.or_full(&[RefreshLut::Quick, RefreshLut::Fast]),
+                (false, None) => refresh_lut.or_full(&[RefreshLut::Quick]),
+                (true, Some(_)) => refresh_lut.or_full(&[RefreshLut::Fast]),
+                (true, None) => RefreshLut::Full,
+            };
+        }
+        self.wait_until_idle(spi, delay).await?;
+        self.load_waveform(spi, delay).await
+    }
+
+    fn interface_config_mut(&mut self) -> &mut InterfaceConfig {
+        self.interface.config_mut()
+    }
+
+    fn release(self) -> (BUSY, DC, RST) {
+        self.interface.release()
+    }
+
+    async fn wait_until_idle(
+        &mut self,
+        _spi: &mut SPI,
+        delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.interface
+            .wait_until_idle(delay, P::CONFIG.is_busy_low)
+            .await
+    }
+}
+
+#[maybe_async_cfg::maybe(
+    sync(keep_self),
+    async(
+        feature = "async",
+        idents(
+            Ssd16xx,
+            DisplayInterface,
+            WaveshareDisplay,
+            InternalWiAdditions,
+            QuickRefresh,
+            WaveshareThreeColorDisplay,
+            SpiDevice(async = "AsyncSpiDevice"),
+            BusyPin(async = "AsyncBusyPin"),
+            DelayNs(async = "AsyncDelayNs")
+        )
+    )
+)]
+impl<P, SPI, BUSY, DC, RST, DELAY, C> Ssd16xx<P, SPI, BUSY, DC, RST, DELAY, C>
+where
+    P: Panel,
+    C: RamColor,
+    SPI: SpiDevice,
+    BUSY: BusyPin,
+    DC: OutputPin,
+    RST: OutputPin,
+    DELAY: DelayNs,
+{
+    /// Sets the scan directions of the panel, for clone glass showing the image mirrored or
+    /// upside down, see [`scan`](crate::scan)
+    ///
+    /// The next frames are written with the new directions.
+    pub async fn set_scan_config(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+        config: ScanConfig,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.scan = config;
+        self.wait_until_idle(spi, delay).await?;
+        self.set_scan(spi).await?;
+        self.use_full_frame(spi, delay).await
+    }
+
+    /// Returns the scan directions of the panel
+    pub fn scan_config(&self) -> ScanConfig {
+        self.scan
+    }
+
+    /// Sends a black and white frame band by band, rendered in `band` by `render`
+    ///
+    /// `band` holds as many lines of `width / 8` bytes as possible, at least one or
+    /// [`EpdError::BufferSize`] is returned. For each band, `render` is called with the area of the
+    /// frame it covers and the pixels to fill, 8 per byte as in a `Display`. The frame is shown
+    /// with [`display_frame`](WaveshareDisplay::display_frame).
+    pub async fn update_frame_banded<F>(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+        band: &mut [u8],
+        render: F,
+    ) -> Result<(), EpdError<SPI::Error>>
+    where
+        F: FnMut(Rect, &mut [u8]),
+    {
+        let (width, height) = (P::CONFIG.width, P::CONFIG.height);
+        self.use_full_frame(spi, delay).await?;
+        self.interface.cmd(spi, Command::WriteRam).await?;
+        self.interface
+            .data_banded(
+                spi,
+                Rect::new(0, 0, width, height),
+                buffer_len(width as usize, 1),
+                band,
+                render,
+            )
+            .await
+    }
+
+    fn frame_len() -> usize {
+        buffer_len(P::CONFIG.width as usize, P::CONFIG.height as usize)
+    }
+
+    /// All the gates and the direction of the scans
+    async fn set_scan(&mut self, spi: &mut SPI) -> Result<(), EpdError<SPI::Error>> {
+        let last_gate = P::CONFIG.height - 1;
+        self.interface
+            .cmd_with_data(
+                spi,
+                Command::DriverOutputControl,
+                &[
+                    last_gate as u8,
+                    (last_gate >> 8) as u8,
+                    self.scan.driver_output(),
+                ],
+            )
+            .await?;
+        self.interface
+            .cmd_with_data(
+                spi,
+                Command::DataEntryModeSetting,
+                &[self.scan.data_entry_mode()],
+            )
+            .await
+    }
+
+    async fn set_ram_area(
+        &mut self,
+        spi: &mut SPI,
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        let (panel_width, panel_height) = (P::CONFIG.width, P::CONFIG.height);
+        let scan = self.scan;
+        let (end_x, end_y) = (
+            scan.x(x + width - 1, panel_width),
+            scan.y(y + height - 1, panel_height),
+        );
+        let (x, y) = (scan.x(x, panel_width), scan.y(y, panel_height));
+        match P::CONFIG.addressing {
+            Addressing::Bytes => {
+                self.interface
+                    .set_ram_area::<Ssd1680>(spi, x, y, end_x, end_y)
+                    .await?;
+                self.interface.set_ram_counter::<Ssd1680>(spi, x, y).await
+            }
+            Addressing::Pixels => {
+                self.interface
+                    .set_ram_area::<Ssd1677>(spi, x, y, end_x, end_y)
+                    .await?;
+                self.interface.set_ram_counter::<Ssd1677>(spi, x, y).await
+            }
+        }
+    }
+
+    async fn use_full_frame(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.wait_until_idle(spi, delay).await?;
+        self.set_ram_area(spi, 0, 0, P::CONFIG.width, P::CONFIG.height)
+            .await
+    }
+
+    /// Writes `buffer` in a window of the RAM selected by `command`
+    #[allow(clippy::too_many_arguments)]
+    async fn write_window(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+        command: Command,
+        buffer: &[u8],
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        EpdError::check_buffer(buffer, buffer_len(width as usize, height as usize))?;
+        self.wait_until_idle(spi, delay).await?;
+        self.set_ram_area(spi, x, y, width, height).await?;
+        self.interface.cmd_with_data(spi, command, buffer).await?;
+        self.use_full_frame(spi, delay).await
+    }
+
+    /// Writes the LUT of the selected waveform, or loads the fast one of the OTP
+    async fn load_waveform(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        match (P::CONFIG.luts, P::CONFIG.fast_temperature) {
+            (Some(luts), _) => {
+                let lut = match self.refresh {
+                    RefreshLut::Quick => luts.quick,
+                    _ => luts.full,
+                };
+                self.interface
+                    .cmd_with_data(spi, Command::WriteLutRegister, lut)
+                    .await
+            }
+            // the full refreshes load the waveform of the measured temperature
+            (None, Some(temperature)) if self.refresh == RefreshLut::Fast => {
+                self.interface
+                    .cmd_with_data(spi, Command::TemperatureSensorControl, &[temperature, 0x00])
+                    .await?;
+                // load the LUT of this temperature
+                self.interface
+                    .cmd_with_data(spi, Command::DisplayUpdateControl2, &[0x91])
+                    .await?;
+                self.interface.cmd(spi, Command::MasterActivation).await?;
+                self.wait_until_idle(spi, delay).await
+            }
+            _ => Ok(()),
+        }
+    }
+
+    async fn turn_on_display(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+        refresh: RefreshLut,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.wait_until_idle(spi, delay).await?;
+        let (update_control, sequence) = match refresh {
+            RefreshLut::Quick => (P::CONFIG.update_control_quick, QUICK_REFRESH),
+            RefreshLut::Fast => (P::CONFIG.update_control_full, FAST_REFRESH),
+            _ => (P::CONFIG.update_control_full, FULL_REFRESH),
+        };
+        // the LUT register keeps the waveform selected by `set_lut`
+        let sequence = P::CONFIG.luts.map_or(sequence, |luts| luts.refresh);
+        if let Some(update_control) = update_control {
+            self.interface
+                .cmd_with_data(spi, Command::DisplayUpdateControl1, &update_control)
+                .await?;
+        }
+        self.interface
+            .cmd_with_data(spi, Command::DisplayUpdateControl2, &[sequence])
+            .await?;
+        self.interface.cmd(spi, Command::MasterActivation).await?;
+        self.wait_until_idle(spi, delay).await
+    }
+}
+
+#[maybe_async_cfg::maybe(
+    sync(keep_self),
+    async(
+        feature = "async",
+        idents(
+            Ssd16xx,
+            DisplayInterface,
+            WaveshareDisplay,
+            InternalWiAdditions,
+            QuickRefresh,
+            WaveshareThreeColorDisplay,
+            SpiDevice(async = "AsyncSpiDevice"),
+            BusyPin(async = "AsyncBusyPin"),
+            DelayNs(async = "AsyncDelayNs")
+        )
+    )
+)]
+impl<P, SPI, BUSY, DC, RST, DELAY> WaveshareThreeColorDisplay<SPI, BUSY, DC, RST, DELAY>
+    for Ssd16xx<P, SPI, BUSY, DC, RST, DELAY, TriColor>
+where
+    P: Panel,
+    SPI: SpiDevice,
+    BUSY: BusyPin,
+    DC: OutputPin,
+    RST: OutputPin,
+    DELAY: DelayNs,
+{
+    async fn update_color_frame(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+        black: &[u8],
+        chromatic: &[u8],
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.update_achromatic_frame(spi, delay, black).await?;
+        self.update_chromatic_frame(spi, delay, chromatic).await
+    }
+
+    async fn update_achromatic_frame(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+        black: &[u8],
+    ) -> Result<(), EpdError<SPI::Error>> {
+        EpdError::check_buffer(black, Self::frame_len())?;
+        self.use_full_frame(spi, delay).await?;
+        self.interface
+            .cmd_with_data(spi, Command::WriteRam, black)
+            .await
+    }
+
+    /// The chromatic pixels are the bits set in `chromatic`
+    async fn update_chromatic_frame(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+        chromatic: &[u8],
+    ) -> Result<(), EpdError<SPI::Error>> {
+        EpdError::check_buffer(chromatic, Self::frame_len())?;
+        self.use_full_frame(spi, delay).await?;
+        self.interface
+            .cmd_with_data(spi, Command::WriteRam2, chromatic)
+            .await
+    }
+}
+
+#[maybe_async_cfg::maybe(
+    sync(keep_self),
+    async(
+        feature = "async",
+        idents(
+            Ssd16xx,
+            DisplayInterface,
+            WaveshareDisplay,
+            InternalWiAdditions,
+            QuickRefresh,
+            WaveshareThreeColorDisplay,
+            SpiDevice(async = "AsyncSpiDevice"),
+            BusyPin(async = "AsyncBusyPin"),
+            DelayNs(async = "AsyncDelayNs")
+        )
+    )
+)]
+impl<P, SPI, BUSY, DC, RST, DELAY> QuickRefresh<SPI, BUSY, DC, RST, DELAY>
+    for Ssd16xx<P, SPI, BUSY, DC, RST, DELAY>
+where
+    P: Panel,
+    SPI: SpiDevice,
+    BUSY: BusyPin,
+    DC: OutputPin,
+    RST: OutputPin,
+    DELAY: DelayNs,
+{
+    /// Writes the frame shown by the panel, as the new and the old frame
+    async fn update_old_frame(
+        &mut self,
+        spi: &mut SPI,
+        buffer: &[u8],
+        delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.update_frame(spi, buffer, delay).await?;
+        self.interface
+            .cmd_with_data(spi, Command::WriteRam2, buffer)
+            .await
+    }
+
+    async fn update_new_frame(
+        &mut self,
+        spi: &mut SPI,
+        buffer: &[u8],
+        delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.update_frame(spi, buffer, delay).await
+    }
+
+    /// Refreshes the pixels which differ from the old frame
+    async fn display_new_frame(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.turn_on_display(spi, delay, RefreshLut::Quick).await
+    }
+
+    /// Updates and displays the new frame, then keeps it as the old frame of the next one
+    async fn update_and_display_new_frame(
+        &mut self,
+        spi: &mut SPI,
+        buffer: &[u8],
+        delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.update_new_frame(spi, buffer, delay).await?;
+        self.display_new_frame(spi, delay).await?;
+        self.interface
+            .cmd_with_data(spi, Command::WriteRam2, buffer)
+            .await
+    }
+
+    /// `x` and `width` must be multiples of 8
+    async fn update_partial_old_frame(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+        buffer: &[u8],
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.write_window(spi, delay, Command::WriteRam2, buffer, x, y, width, height)
+            .await
+    }
+
+    /// `x` and `width` must be multiples of 8
+    async fn update_partial_new_frame(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+        buffer: &[u8],
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.write_window(spi, delay, Command::WriteRam, buffer, x, y, width, height)
+            .await
+    }
+
+    /// Fills a window of the new frame with the background color, the old frame is kept
+    ///
+    /// `x` and `width` must be multiples of 8.
+    async fn clear_partial_frame(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.wait_until_idle(spi, delay).await?;
+        self.set_ram_area(spi, x, y, width, height).await?;
+
+        let color = self.background_color.get_byte_value();
+        self.interface.cmd(spi, Command::WriteRam).await?;
+        self.interface
+            .data_x_times(spi, color, width / 8 * height)
+            .await?;
+
+        self.use_full_frame(spi, delay).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_config() {
+        const CONFIG: PanelConfig = PanelConfig::new(200, 200);
+        assert_eq!(CONFIG.addressing, Addressing::Bytes);
+        assert_eq!(
+            (CONFIG.is_busy_low, CONFIG.single_byte_write),
+            (false, false)
+        );
+        assert_eq!(CONFIG.reset_us, [10_000, 10_000]);
+        assert_eq!(
+            (CONFIG.sw_reset, CONFIG.border_waveform),
+            (true, Some(0x05))
+        );
+        assert_eq!(CONFIG.temperature_sensor, Some(0x80));
+        assert_eq!(CONFIG.voltages, Voltages::DEFAULT);
+        assert_eq!(CONFIG.luts, None);
+        assert_eq!(CONFIG.fast_temperature, None);
+        assert_eq!(CONFIG.update_control_full, Some([0x00, 0x00]));
+        assert_eq!(CONFIG.update_control_quick, Some([0x00, 0x00]));
+    }
+
+    #[test]
+    fn ram_bytes() {
+        assert_eq!(Color::Black.ram_bytes(), (0x00, 0x00));
+        assert_eq!(Color::White.ram_bytes(), (0xff, 0xff));
+        assert_eq!(TriColor::Black.ram_bytes(), (0x00, 0x00));
+        assert_eq!(TriColor::White.ram_bytes(), (0xff, 0x00));
+        assert_eq!(TriColor::Chromatic.ram_bytes(), (0x00, 0xff));
+    }
+}
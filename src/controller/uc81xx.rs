@@ -0,0 +1,680 @@
+//! Black and white panels driven by an UltraChip UC8151, UC8176 or UC8179 controller, or one of
+//! their clones (IL0373, IL0398, GD7965, ...)
+//!
+//! The new frame is sent with the data start transmission 2, the old frame with the data start
+//! transmission 1: [`update_and_display_frame`](WaveshareDisplay::update_and_display_frame)
+//! keeps the frame it shows as the old frame of the next refresh, for the waveforms comparing
+//! them.
+//!
+//! The waveforms are the ones of the controller OTP, or the ones of [`PanelConfig::lut_full`]
+//! and [`PanelConfig::lut_quick`] written to the LUT registers. The [`PanelConfig`] of a panel
+//! also gives its panel setting, voltages and the polarity of its BUSY pin.
+//!
+//! # Describing a panel
+//!
+//!```rust, no_run
+//!# use embedded_hal_mock::eh1::*;
+//!# fn main() -> Result<(), epd_waveshare::error::EpdError<embedded_hal::spi::ErrorKind>> {
+//!use epd_waveshare::controller::uc81xx::*;
+//!use epd_waveshare::prelude::*;
+//!#
+//!# let expectations = [];
+//!# let mut spi = spi::Mock::new(&expectations);
+//!# let expectations = [];
+//!# let busy_in = digital::Mock::new(&expectations);
+//!# let dc = digital::Mock::new(&expectations);
+//!# let rst = digital::Mock::new(&expectations);
+//!# let mut delay = delay::NoopDelay::new();
+//!
+//!/// A 152x152 panel on an UC8151 with the waveforms of the OTP
+//!struct MyPanel;
+//!
+//!impl Panel for MyPanel {
+//!    const CONFIG: PanelConfig = PanelConfig {
+//!        // OTP LUT, black and white, scan up, shift right, booster on
+//!        panel_setting: &[0x1f, 0x0d],
+//!        vcom_and_data_interval: &[0x97],
+//!        ..PanelConfig::new(Controller::Uc8151, 152, 152)
+//!    };
+//!}
+//!
+//!let mut epd = Uc81xx::<MyPanel, _, _, _, _, _>::new(&mut spi, busy_in, dc, rst, &mut delay, None)?;
+//!epd.clear_frame(&mut spi, &mut delay)?;
+//!epd.display_frame(&mut spi, &mut delay)?;
+//!# Ok(())
+//!# }
+//!```
+
+use core::marker::PhantomData;
+
+use crate::error::EpdError;
+use crate::pin::BusyPin;
+use embedded_hal::{delay::*, digital::*, spi::SpiDevice};
+
+use crate::buffer_len;
+use crate::color::Color;
+use crate::controller::generic::Register;
+use crate::interface::DisplayInterface;
+#[cfg(feature = "async")]
+use crate::interface::DisplayInterfaceAsync;
+use crate::traits::{self, InterfaceConfig, InternalWiAdditions, RefreshLut, WaveshareDisplay};
+#[cfg(feature = "async")]
+use crate::traits::{AsyncBusyPin, InternalWiAdditionsAsync, WaveshareDisplayAsync};
+#[cfg(feature = "async")]
+use embedded_hal_async::{delay::DelayNs as AsyncDelayNs, spi::SpiDevice as AsyncSpiDevice};
+
+const SINGLE_BYTE_WRITE: bool = false;
+
+/// Commands of the UC81xx used by the driver
+#[derive(Clone, Copy)]
+enum Command {
+    PanelSetting = 0x00,
+    PowerSetting = 0x01,
+    PowerOff = 0x02,
+    PowerOn = 0x04,
+    BoosterSoftStart = 0x06,
+    DeepSleep = 0x07,
+    DataStartTransmission1 = 0x10,
+    DisplayRefresh = 0x12,
+    DataStartTransmission2 = 0x13,
+    LutForVcom = 0x20,
+    LutWhiteToWhite = 0x21,
+    LutBlackToWhite = 0x22,
+    LutWhiteToBlack = 0x23,
+    LutBlackToBlack = 0x24,
+    VcomAndDataIntervalSetting = 0x50,
+    ResolutionSetting = 0x61,
+    GetStatus = 0x71,
+    VcmDcSetting = 0x82,
+    PartialWindow = 0x90,
+    PartialIn = 0x91,
+    PartialOut = 0x92,
+}
+
+impl traits::Command for Command {
+    fn address(self) -> u8 {
+        self as u8
+    }
+}
+
+/// A command given by its address
+#[derive(Clone, Copy)]
+struct RawCommand(u8);
+
+impl traits::Command for RawCommand {
+    fn address(self) -> u8 {
+        self.0
+    }
+}
+
+/// Controller of a panel, setting the layout of its resolution and partial window
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Controller {
+    /// UC8151 (IL0373), up to 160 sources: the columns are given in a byte
+    Uc8151,
+    /// UC8176 (IL0398), up to 400 sources
+    Uc8176,
+    /// UC8179 (GD7965), up to 800 sources
+    Uc8179,
+}
+
+/// Voltages of a panel, the ones which are `None` or empty keep the defaults of the controller
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Voltages {
+    /// Power setting: the source and gate voltages
+    pub power: &'static [u8],
+    /// Booster soft start
+    pub booster: &'static [u8],
+    /// VCOM DC setting
+    pub vcom_dc: Option<u8>,
+}
+
+impl Voltages {
+    /// The voltages of the controller
+    pub const DEFAULT: Self = Voltages {
+        power: &[],
+        booster: &[],
+        vcom_dc: None,
+    };
+}
+
+/// Waveform written to the LUT registers, the LUT of the VCOM and the ones of the 4 transitions
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Luts {
+    /// LUT of the VCOM
+    pub vcom: &'static [u8],
+    /// LUT of the white pixels staying white
+    pub white_to_white: &'static [u8],
+    /// LUT of the black pixels turning white
+    pub black_to_white: &'static [u8],
+    /// LUT of the white pixels turning black
+    pub white_to_black: &'static [u8],
+    /// LUT of the black pixels staying black
+    pub black_to_black: &'static [u8],
+}
+
+/// Settings of a panel
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PanelConfig {
+    /// Controller of the panel
+    pub controller: Controller,
+    /// Width of the panel in pixels, the number of sources
+    pub width: u32,
+    /// Height of the panel in pixels, the number of gates
+    pub height: u32,
+    /// Whether the BUSY pin is low while the controller is busy
+    pub is_busy_low: bool,
+    /// Whether the data is written byte by byte, see
+    /// [`set_single_byte_write`](WaveshareDisplay::set_single_byte_write)
+    pub single_byte_write: bool,
+    /// Hardware reset in us: time RST is high before the pulse and length of the pulse
+    pub reset_us: [u32; 2],
+    /// Panel setting: LUT from the OTP or the registers, colors, scan directions
+    pub panel_setting: &'static [u8],
+    /// Voltages of the panel
+    pub voltages: Voltages,
+    /// VCOM and data interval setting: border and polarity of the data
+    pub vcom_and_data_interval: &'static [u8],
+    /// Other registers written by the initialisation, e.g. the PLL or the TCON setting
+    pub registers: &'static [Register<'static>],
+    /// Whether the panel shows the frames inverted, `0` being white
+    ///
+    /// The frames are sent as they are, [`clear_frame`](WaveshareDisplay::clear_frame) fills the
+    /// RAMs with the inverted background color.
+    pub inverted: bool,
+    /// Waveform of the full refreshes, `None` for the one of the OTP
+    pub lut_full: Option<Luts>,
+    /// Waveform of the quick refreshes, selected with `set_lut(Some(RefreshLut::Quick))`, `None`
+    /// if the panel doesn't have one
+    pub lut_quick: Option<Luts>,
+}
+
+impl PanelConfig {
+    /// Settings of a `width` x `height` panel with the defaults of the controllers: BUSY low
+    /// while busy, voltages of the controller, waveform of the OTP and no quick refresh
+    ///
+    /// The panel setting and the VCOM and data interval setting are left empty, they depend on
+    /// the panel.
+    pub const fn new(controller: Controller, width: u32, height: u32) -> Self {
+        PanelConfig {
+            controller,
+            width,
+            height,
+            is_busy_low: true,
+            single_byte_write: false,
+            reset_us: [10_000, 2_000],
+            panel_setting: &[],
+            voltages: Voltages::DEFAULT,
+            vcom_and_data_interval: &[],
+            registers: &[],
+            inverted: false,
+            lut_full: None,
+            lut_quick: None,
+        }
+    }
+}
+
+/// A panel driven by [`Uc81xx`]
+pub trait Panel {
+    /// Settings of the panel
+    const CONFIG: PanelConfig;
+}
+
+#[maybe_async_cfg::maybe(
+    sync(keep_self),
+    async(
+        feature = "async",
+        idents(
+            Uc81xx,
+            DisplayInterface,
+            WaveshareDisplay,
+            InternalWiAdditions,
+            SpiDevice(async = "AsyncSpiDevice"),
+            BusyPin(async = "AsyncBusyPin"),
+            DelayNs(async = "AsyncDelayNs")
+        )
+    )
+)]
+/// Driver of the panels of an UC81xx controller
+pub struct Uc81xx<P, SPI, BUSY, DC, RST, DELAY> {
+    /// Connection Interface
+    interface: DisplayInterface<SPI, BUSY, DC, RST, DELAY, SINGLE_BYTE_WRITE>,
+    /// Background Color
+    background_color: Color,
+    /// Refresh LUT
+    refresh: RefreshLut,
+    _panel: PhantomData<P>,
+}
+
+#[maybe_async_cfg::maybe(
+    sync(keep_self),
+    async(
+        feature = "async",
+        idents(
+            Uc81xx,
+            DisplayInterface,
+            WaveshareDisplay,
+            InternalWiAdditions,
+            SpiDevice(async = "AsyncSpiDevice"),
+            BusyPin(async = "AsyncBusyPin"),
+            DelayNs(async = "AsyncDelayNs")
+        )
+    )
+)]
+impl<P, SPI, BUSY, DC, RST, DELAY> InternalWiAdditions<SPI, BUSY, DC, RST, DELAY>
+    for Uc81xx<P, SPI, BUSY, DC, RST, DELAY>
+where
+    P: Panel,
+    SPI: SpiDevice,
+    BUSY: BusyPin,
+    DC: OutputPin,
+    RST: OutputPin,
+    DELAY: DelayNs,
+{
+    async fn init(&mut self, spi: &mut SPI, delay: &mut DELAY) -> Result<(), EpdError<SPI::Error>> {
+        let [high_us, low_us] = P::CONFIG.reset_us;
+        self.interface.reset(delay, high_us, low_us).await?;
+
+        let voltages = P::CONFIG.voltages;
+        self.cmd_with_optional_data(spi, Command::PowerSetting, voltages.power)
+            .await?;
+        self.cmd_with_optional_data(spi, Command::BoosterSoftStart, voltages.booster)
+            .await?;
+        self.interface.cmd(spi, Command::PowerOn).await?;
+        self.wait_until_idle(spi, delay).await?;
+
+        self.cmd_with_optional_data(spi, Command::PanelSetting, P::CONFIG.panel_setting)
+            .await?;
+        self.send_resolution(spi).await?;
+        if let Some(vcom_dc) = voltages.vcom_dc {
+            self.interface
+                .cmd_with_data(spi, Command::VcmDcSetting, &[vcom_dc])
+                .await?;
+        }
+        self.cmd_with_optional_data(
+            spi,
+            Command::VcomAndDataIntervalSetting,
+            P::CONFIG.vcom_and_data_interval,
+        )
+        .await?;
+        for register in P::CONFIG.registers {
+            self.interface
+                .cmd_with_data(spi, RawCommand(register.command), register.data)
+                .await?;
+        }
+
+        self.write_lut(spi).await
+    }
+}
+
+#[maybe_async_cfg::maybe(
+    sync(keep_self),
+    async(
+        feature = "async",
+        idents(
+            Uc81xx,
+            DisplayInterface,
+            WaveshareDisplay,
+            InternalWiAdditions,
+            SpiDevice(async = "AsyncSpiDevice"),
+            BusyPin(async = "AsyncBusyPin"),
+            DelayNs(async = "AsyncDelayNs")
+        )
+    )
+)]
+impl<P, SPI, BUSY, DC, RST, DELAY> WaveshareDisplay<SPI, BUSY, DC, RST, DELAY>
+    for Uc81xx<P, SPI, BUSY, DC, RST, DELAY>
+where
+    P: Panel,
+    SPI: SpiDevice,
+    BUSY: BusyPin,
+    DC: OutputPin,
+    RST: OutputPin,
+    DELAY: DelayNs,
+{
+    type DisplayColor = Color;
+    fn width(&self) -> u32 {
+        P::CONFIG.width
+    }
+
+    fn height(&self) -> u32 {
+        P::CONFIG.height
+    }
+
+    async fn new(
+        spi: &mut SPI,
+        busy: BUSY,
+        dc: DC,
+        rst: RST,
+        delay: &mut DELAY,
+        delay_us: Option<u32>,
+    ) -> Result<Self, EpdError<SPI::Error>> {
+        let mut epd = Self::new_uninit(busy, dc, rst, delay_us);
+        epd.init(spi, delay).await?;
+        Ok(epd)
+    }
+
+    fn new_uninit(busy: BUSY, dc: DC, rst: RST, delay_us: Option<u32>) -> Self {
+        let mut interface = DisplayInterface::new(busy, dc, rst, delay_us);
+        interface.config_mut().single_byte_write = P::CONFIG.single_byte_write;
+
+        Uc81xx {
+            interface,
+            background_color: Color::White,
+            refresh: RefreshLut::Full,
+            _panel: PhantomData,
+        }
+    }
+
+    async fn sleep(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.wait_until_idle(spi, delay).await?;
+        self.interface.cmd(spi, Command::PowerOff).await?;
+        self.wait_until_idle(spi, delay).await?;
+        // check code
+        self.interface
+            .cmd_with_data(spi, Command::DeepSleep, &[0xA5])
+            .await
+    }
+
+    async fn wake_up(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.init(spi, delay).await
+    }
+
+    async fn update_frame(
+        &mut self,
+        spi: &mut SPI,
+        buffer: &[u8],
+        delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        EpdError::check_buffer(buffer, Self::frame_len())?;
+        self.wait_until_idle(spi, delay).await?;
+        self.interface
+            .cmd_with_data(spi, Command::DataStartTransmission2, buffer)
+            .await
+    }
+
+    /// Writes `buffer` in a window of the new frame, `x` and `width` must be multiples of 8
+    async fn update_partial_frame(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+        buffer: &[u8],
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        EpdError::check_buffer(buffer, buffer_len(width as usize, height as usize))?;
+        self.wait_until_idle(spi, delay).await?;
+        self.interface.cmd(spi, Command::PartialIn).await?;
+        self.set_partial_window(spi, x, y, width, height).await?;
+        self.interface
+            .cmd_with_data(spi, Command::DataStartTransmission2, buffer)
+            .await?;
+        self.interface.cmd(spi, Command::PartialOut).await
+    }
+
+    /// Refreshes with the waveform selected by [`set_lut`](WaveshareDisplay::set_lut)
+    async fn display_frame(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.wait_until_idle(spi, delay).await?;
+        self.interface.cmd(spi, Command::DisplayRefresh).await?;
+        self.wait_until_idle(spi, delay).await
+    }
+
+    /// Updates and displays the frame, then keeps it as the old frame of the next refresh
+    async fn update_and_display_frame(
+        &mut self,
+        spi: &mut SPI,
+        buffer: &[u8],
+        delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.update_frame(spi, buffer, delay).await?;
+        self.display_frame(spi, delay).await?;
+        self.interface
+            .cmd_with_data(spi, Command::DataStartTransmission1, buffer)
+            .await
+    }
+
+    /// Fills the old and the new frame with the background color, without refreshing
+    async fn clear_frame(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.wait_until_idle(spi, delay).await?;
+
+        let mut color = self.background_color.get_byte_value();
+        if P::CONFIG.inverted {
+            color = !color;
+        }
+        let len = Self::frame_len() as u32;
+        self.interface
+            .cmd(spi, Command::DataStartTransmission1)
+            .await?;
+        self.interface.data_x_times(spi, color, len).await?;
+        self.interface
+            .cmd(spi, Command::DataStartTransmission2)
+            .await?;
+        self.interface.data_x_times(spi, color, len).await
+    }
+
+    fn set_background_color(&mut self, background_color: Color) {
+        self.background_color = background_color;
+    }
+
+    fn background_color(&self) -> &Color {
+        &self.background_color
+    }
+
+    /// Selects the full or the quick waveform, if the panel has one, and writes its LUTs
+    async fn set_lut(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+        refresh_rate: Option<RefreshLut>,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        if let Some(refresh_lut) = refresh_rate {
+            let quick = match P::CONFIG.lut_quick {
+                Some(_) => &[RefreshLut::Quick][..],
+                None => &[][..],
+            };
+            self.refresh = refresh_lut.or_full(quick);
+        }
+        self.wait_until_idle(spi, delay).await?;
+        self.write_lut(spi).await
+    }
+
+    fn interface_config_mut(&mut self) -> &mut InterfaceConfig {
+        self.interface.config_mut()
+    }
+
+    fn release(self) -> (BUSY, DC, RST) {
+        self.interface.release()
+    }
+
+    async fn wait_until_idle(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.interface
+            .wait_until_idle_with_cmd(spi, delay, P::CONFIG.is_busy_low, Command::GetStatus)
+            .await
+    }
+}
+
+#[maybe_async_cfg::maybe(
+    sync(keep_self),
+    async(
+        feature = "async",
+        idents(
+            Uc81xx,
+            DisplayInterface,
+            WaveshareDisplay,
+            InternalWiAdditions,
+            SpiDevice(async = "AsyncSpiDevice"),
+            BusyPin(async = "AsyncBusyPin"),
+            DelayNs(async = "AsyncDelayNs")
+        )
+    )
+)]
+impl<P, SPI, BUSY, DC, RST, DELAY> Uc81xx<P, SPI, BUSY, DC, RST, DELAY>
+where
+    P: Panel,
+    SPI: SpiDevice,
+    BUSY: BusyPin,
+    DC: OutputPin,
+    RST: OutputPin,
+    DELAY: DelayNs,
+{
+    fn frame_len() -> usize {
+        buffer_len(P::CONFIG.width as usize, P::CONFIG.height as usize)
+    }
+
+    /// Sends `command` unless `data` is empty, keeping the default of the controller
+    async fn cmd_with_optional_data(
+        &mut self,
+        spi: &mut SPI,
+        command: Command,
+        data: &[u8],
+    ) -> Result<(), EpdError<SPI::Error>> {
+        match data.is_empty() {
+            true => Ok(()),
+            false => self.interface.cmd_with_data(spi, command, data).await,
+        }
+    }
+
+    async fn send_resolution(&mut self, spi: &mut SPI) -> Result<(), EpdError<SPI::Error>> {
+        let (width, height) = (P::CONFIG.width, P::CONFIG.height);
+        match P::CONFIG.controller {
+            Controller::Uc8151 => {
+                self.interface
+                    .cmd_with_data(
+                        spi,
+                        Command::ResolutionSetting,
+                        &[width as u8, (height >> 8) as u8, height as u8],
+                    )
+                    .await
+            }
+            Controller::Uc8176 | Controller::Uc8179 => {
+                self.interface
+                    .cmd_with_data(
+                        spi,
+                        Command::ResolutionSetting,
+                        &[
+                            (width >> 8) as u8,
+                            width as u8,
+                            (height >> 8) as u8,
+                            height as u8,
+                        ],
+                    )
+                    .await
+            }
+        }
+    }
+
+    /// Sets the window of the partial updates, the gates scanning the whole panel
+    async fn set_partial_window(
+        &mut self,
+        spi: &mut SPI,
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        let (x, end_x) = (x & !0x07, (x + width - 1) | 0x07);
+        let end_y = y + height - 1;
+        match P::CONFIG.controller {
+            Controller::Uc8151 => {
+                self.interface
+                    .cmd_with_data(
+                        spi,
+                        Command::PartialWindow,
+                        &[
+                            x as u8,
+                            end_x as u8,
+                            (y >> 8) as u8,
+                            y as u8,
+                            (end_y >> 8) as u8,
+                            end_y as u8,
+                            0x01,
+                        ],
+                    )
+                    .await
+            }
+            Controller::Uc8176 | Controller::Uc8179 => {
+                self.interface
+                    .cmd_with_data(
+                        spi,
+                        Command::PartialWindow,
+                        &[
+                            (x >> 8) as u8,
+                            x as u8,
+                            (end_x >> 8) as u8,
+                            end_x as u8,
+                            (y >> 8) as u8,
+                            y as u8,
+                            (end_y >> 8) as u8,
+                            end_y as u8,
+                            0x01,
+                        ],
+                    )
+                    .await
+            }
+        }
+    }
+
+    /// Writes the LUTs of the selected waveform, nothing for the one of the OTP
+    async fn write_lut(&mut self, spi: &mut SPI) -> Result<(), EpdError<SPI::Error>> {
+        let luts = match self.refresh {
+            RefreshLut::Quick => P::CONFIG.lut_quick,
+            _ => P::CONFIG.lut_full,
+        };
+        let luts = match luts {
+            Some(luts) => luts,
+            None => return Ok(()),
+        };
+        self.interface
+            .cmd_with_data(spi, Command::LutForVcom, luts.vcom)
+            .await?;
+        self.interface
+            .cmd_with_data(spi, Command::LutWhiteToWhite, luts.white_to_white)
+            .await?;
+        self.interface
+            .cmd_with_data(spi, Command::LutBlackToWhite, luts.black_to_white)
+            .await?;
+        self.interface
+            .cmd_with_data(spi, Command::LutWhiteToBlack, luts.white_to_black)
+            .await?;
+        self.interface
+            .cmd_with_data(spi, Command::LutBlackToBlack, luts.black_to_black)
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_config() {
+        const CONFIG: PanelConfig = PanelConfig::new(Controller::Uc8179, 800, 480);
+        assert_eq!((CONFIG.is_busy_low, CONFIG.inverted), (true, false));
+        assert_eq!(CONFIG.reset_us, [10_000, 2_000]);
+        assert!(CONFIG.panel_setting.is_empty() && CONFIG.registers.is_empty());
+        assert_eq!(CONFIG.voltages, Voltages::DEFAULT);
+        assert_eq!((CONFIG.lut_full, CONFIG.lut_quick), (None, None));
+    }
+}
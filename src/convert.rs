@@ -0,0 +1,835 @@
+//! Color conversion for EPDs
+//!
+//! Most UI code and widget libraries draw with "rich" colors (`Rgb565`, `Gray8`, ...) while
+//! EPDs only know a few colors. [`ColorConverted`] sits between both: it is a `DrawTarget` for
+//! any color that can be converted into `Rgb888` and quantizes every pixel to black or white
+//! before drawing it on the wrapped display.
+//!
+//! How the luminance of a color is computed and compared can be tuned with [`Levels`].
+//!
+//! Tri-color displays are supported with [`ColorConverted::new_tricolor`], which draws colors
+//! close to the chromatic color of the display with it.
+//!
+//!```rust
+//!# use epd_waveshare::{convert::*, epd1in54::Display1in54};
+//!use embedded_graphics::{pixelcolor::Rgb565, prelude::*, primitives::*};
+//!
+//!let mut display = Display1in54::default();
+//!let mut errors = [0i16; error_buffer_len(200)];
+//!let mut rgb = ColorConverted::new(&mut display, Dithering::FloydSteinberg(&mut errors));
+//!
+//!let _ = Rectangle::new(Point::new(10, 10), Size::new(50, 50))
+//!    .into_styled(PrimitiveStyle::with_fill(Rgb565::CSS_STEEL_BLUE))
+//!    .draw(&mut rgb);
+//!```
+
+use crate::color::TriColor;
+use core::marker::PhantomData;
+use embedded_graphics_core::{
+    pixelcolor::{BinaryColor, Rgb888},
+    prelude::*,
+    primitives::Rectangle,
+};
+
+/// Number of pixels on each side of the error lines, for kernels spreading errors 2 pixels away
+const PADDING: usize = 2;
+
+/// Number of `i16` needed by the error diffusion buffer for lines of `width` pixels
+pub const fn error_buffer_len(width: u32) -> usize {
+    // the line being converted, followed by the errors for the current line and the two next
+    // lines, all padded on each side
+    width as usize + 3 * (width as usize + 2 * PADDING)
+}
+
+/// Error diffusion kernel
+struct Kernel {
+    /// (dx, dy, weight): part of the error given to the pixel at (x + dx, y + dy), dx being
+    /// in the scan direction
+    taps: &'static [(i8, u8, i16)],
+    /// Sum of all the weights for kernels spreading the whole error
+    divisor: i16,
+}
+
+const FLOYD_STEINBERG: Kernel = Kernel {
+    taps: &[(1, 0, 7), (-1, 1, 3), (0, 1, 5), (1, 1, 1)],
+    divisor: 16,
+};
+
+const STUCKI: Kernel = Kernel {
+    #[rustfmt::skip]
+    taps: &[
+                                      (1, 0, 8), (2, 0, 4),
+        (-2, 1, 2), (-1, 1, 4), (0, 1, 8), (1, 1, 4), (2, 1, 2),
+        (-2, 2, 1), (-1, 2, 2), (0, 2, 4), (1, 2, 2), (2, 2, 1),
+    ],
+    divisor: 42,
+};
+
+// only 6/8 of the error is spread
+const ATKINSON: Kernel = Kernel {
+    taps: &[
+        (1, 0, 1),
+        (2, 0, 1),
+        (-1, 1, 1),
+        (0, 1, 1),
+        (1, 1, 1),
+        (0, 2, 1),
+    ],
+    divisor: 8,
+};
+
+/// How colors between black and white are quantized
+#[derive(Debug)]
+pub enum Dithering<'a> {
+    /// Every pixel is compared to the threshold on its own
+    None,
+    /// Floyd-Steinberg error diffusion
+    ///
+    /// The quantization error of each pixel is spread over its neighbours, which keeps the
+    /// average brightness of photos and gradients. The slice is used to store errors between
+    /// lines and needs to be at least [`error_buffer_len`] long for the widest area drawn.
+    ///
+    /// Only contiguous fills (images, filled shapes) are diffused, pixels drawn one by one are
+    /// converted as with [`Dithering::None`]. So are areas too wide for the error buffer.
+    ///
+    /// Lines are scanned from left to right, unless serpentine scanning is enabled with
+    /// [`ColorConverted::set_serpentine`].
+    FloydSteinberg(&'a mut [i16]),
+    /// Stucki error diffusion
+    ///
+    /// Same as [`Dithering::FloydSteinberg`], but the error is spread over more pixels and the
+    /// two next lines, which gives cleaner results on big pictures at a higher cost.
+    Stucki(&'a mut [i16]),
+    /// Atkinson error diffusion
+    ///
+    /// Same as [`Dithering::FloydSteinberg`], but only 3/4 of the error is spread, which gives
+    /// more contrast and less noise in very dark or very light areas.
+    Atkinson(&'a mut [i16]),
+    /// Ordered dithering with a 4x4 Bayer matrix
+    ///
+    /// Ordered dithering compares each pixel to a threshold depending only on its position on
+    /// the display. It is cheaper than error diffusion, doesn't need any buffer and gives the
+    /// same result whatever the order pixels are drawn in, so it works the same for single
+    /// pixels and for images drawn in several bands.
+    Bayer4,
+    /// Ordered dithering with a 8x8 Bayer matrix, for smoother gradients than [`Dithering::Bayer4`]
+    Bayer8,
+    /// Ordered dithering with a 16x16 blue noise matrix, which avoids the visible cross-hatch
+    /// pattern of Bayer matrices
+    BlueNoise,
+}
+
+impl Dithering<'_> {
+    /// Value added to the luminance of the pixel at `point` before comparing it to the threshold
+    fn ordered_offset(&self, point: Point) -> i16 {
+        fn offset<const N: usize>(matrix: &[[u8; N]; N], point: Point) -> i16 {
+            // positions are wrapped around the matrix, rem_euclid keeps them positive
+            let x = point.x.rem_euclid(N as i32) as usize;
+            let y = point.y.rem_euclid(N as i32) as usize;
+            // spread the ranks of the matrix evenly between -127 and 127, so that black and
+            // white stay untouched
+            (127 - (2 * matrix[y][x] as i32 + 1) * 255 / (2 * N * N) as i32) as i16
+        }
+        match self {
+            Dithering::None
+            | Dithering::FloydSteinberg(_)
+            | Dithering::Stucki(_)
+            | Dithering::Atkinson(_) => 0,
+            Dithering::Bayer4 => offset(&BAYER_4, point),
+            Dithering::Bayer8 => offset(&BAYER_8, point),
+            Dithering::BlueNoise => offset(&BLUE_NOISE, point),
+        }
+    }
+}
+
+/// Curve applied to the luminance before quantization
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Gamma {
+    /// Luminance is used as is
+    Linear,
+    /// Luminance is considered sRGB encoded (as most images are) and converted to linear
+    /// light, which darkens midtones
+    Srgb,
+    /// Custom curve, indexed by the luminance
+    Custom(&'static [u8; 256]),
+}
+
+impl Gamma {
+    fn apply(self, luma: u8) -> u8 {
+        match self {
+            Gamma::Linear => luma,
+            Gamma::Srgb => SRGB_TO_LINEAR[luma as usize],
+            Gamma::Custom(curve) => curve[luma as usize],
+        }
+    }
+}
+
+/// Parameters used to turn a color into black or white
+///
+/// The luminance of a color is computed with `weights`, adjusted with the `gamma` curve and then
+/// compared to `threshold` (or used for dithering).
+///
+/// The defaults are a 50% threshold, no gamma correction and ITU-R BT.601 weights. As EPDs have
+/// a lower contrast than LCDs, lowering the threshold or using a curve that brightens midtones
+/// often keeps more details.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Levels {
+    /// Luminance from which (included) a pixel is white
+    pub threshold: u8,
+    /// Curve applied to the luminance
+    pub gamma: Gamma,
+    /// Relative weights of the red, green and blue channels, they must not all be 0
+    pub weights: [u8; 3],
+}
+
+impl Default for Levels {
+    fn default() -> Self {
+        Levels {
+            threshold: 128,
+            gamma: Gamma::Linear,
+            weights: [77, 150, 29],
+        }
+    }
+}
+
+impl Levels {
+    /// Luminance of a color, from 0 (black) to 255 (white)
+    pub fn luma<C: Into<Rgb888>>(&self, color: C) -> u8 {
+        let color = color.into();
+        let [r, g, b] = self.weights.map(u32::from);
+        let total = (r + g + b).max(1);
+        let luma = (color.r() as u32 * r + color.g() as u32 * g + color.b() as u32 * b) / total;
+        self.gamma.apply(luma as u8)
+    }
+
+    /// Quantize a single pixel, without any knowledge of its neighbours
+    fn quantize(&self, color: Rgb888, offset: i16) -> TriColor {
+        black_or_white(self.luma(color) as i16 + offset >= self.threshold as i16)
+    }
+}
+
+/// Which colors are drawn with the chromatic color of a tri-color display
+///
+/// A color is chromatic when it is saturated enough and its hue is close to the one of the
+/// chromatic color of the display, all other colors are converted to black or white.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Chromatic {
+    /// Hue of the chromatic color of the display, in degrees
+    pub hue: u16,
+    /// Maximum difference between the hue of a color and `hue`, in degrees
+    pub hue_tolerance: u16,
+    /// Minimum saturation of a color, as the difference between its highest and lowest channel
+    pub min_saturation: u8,
+}
+
+impl Chromatic {
+    /// For displays with red as chromatic color
+    pub const RED: Chromatic = Chromatic {
+        hue: 0,
+        hue_tolerance: 30,
+        min_saturation: 96,
+    };
+
+    /// For displays with yellow as chromatic color
+    pub const YELLOW: Chromatic = Chromatic {
+        hue: 60,
+        hue_tolerance: 30,
+        min_saturation: 96,
+    };
+
+    /// Whether a color should be drawn with the chromatic color
+    pub fn matches<C: Into<Rgb888>>(&self, color: C) -> bool {
+        let color = color.into();
+        let (r, g, b) = (color.r() as i32, color.g() as i32, color.b() as i32);
+        let max = r.max(g).max(b);
+        let saturation = max - r.min(g).min(b);
+        if saturation < self.min_saturation as i32 || saturation == 0 {
+            return false;
+        }
+        let hue = if max == r {
+            60 * (g - b) / saturation
+        } else if max == g {
+            120 + 60 * (b - r) / saturation
+        } else {
+            240 + 60 * (r - g) / saturation
+        };
+        let distance = (hue - self.hue as i32).rem_euclid(360);
+        distance.min(360 - distance) <= self.hue_tolerance as i32
+    }
+}
+
+fn black_or_white(white: bool) -> TriColor {
+    if white {
+        TriColor::White
+    } else {
+        TriColor::Black
+    }
+}
+
+/// Output of the conversion for black and white displays, which never gets a chromatic color
+fn to_binary<T: From<BinaryColor>>(color: TriColor) -> T {
+    // white is `BinaryColor::Off` as in all the `From<BinaryColor>` implementations of this crate
+    if color == TriColor::White {
+        BinaryColor::Off.into()
+    } else {
+        BinaryColor::On.into()
+    }
+}
+
+/// Quantize a pixel to its chromatic color if it has one, or with its threshold offset
+fn quantize(levels: &Levels, chromatic: Option<Chromatic>, color: Rgb888, offset: i16) -> TriColor {
+    match chromatic {
+        Some(chromatic) if chromatic.matches(color) => TriColor::Chromatic,
+        _ => levels.quantize(color, offset),
+    }
+}
+
+/// A `DrawTarget` converting colors on the fly to draw them on an EPD buffer
+///
+/// - D: the wrapped display, any `DrawTarget` whose color can be built from a `BinaryColor`
+///   (as all the colors of this crate can), or a tri-color display
+/// - C: the color accepted by this adapter, e.g. `Rgb565` or `Gray8`
+pub struct ColorConverted<'a, D: DrawTarget, C> {
+    display: &'a mut D,
+    dithering: Dithering<'a>,
+    levels: Levels,
+    serpentine: bool,
+    chromatic: Option<Chromatic>,
+    output: fn(TriColor) -> D::Color,
+    _color: PhantomData<C>,
+}
+
+impl<'a, D, C> ColorConverted<'a, D, C>
+where
+    D: DrawTarget,
+    D::Color: From<BinaryColor>,
+    C: PixelColor + Into<Rgb888>,
+{
+    /// Wrap a display to draw on it with colors of type `C`
+    pub fn new(display: &'a mut D, dithering: Dithering<'a>) -> Self {
+        ColorConverted {
+            display,
+            dithering,
+            levels: Levels::default(),
+            serpentine: false,
+            chromatic: None,
+            output: to_binary,
+            _color: PhantomData,
+        }
+    }
+}
+
+impl<'a, D, C> ColorConverted<'a, D, C>
+where
+    D: DrawTarget<Color = TriColor>,
+    C: PixelColor + Into<Rgb888>,
+{
+    /// Wrap a tri-color display to draw on it with colors of type `C`
+    ///
+    /// Colors matching `chromatic` are drawn with the chromatic color, the other ones are
+    /// converted to black and white (and dithered) as for black and white displays. Both the
+    /// black/white and the chromatic planes are written in a single pass over the pixels.
+    ///
+    /// Chromatic pixels don't spread any error when using error diffusion.
+    pub fn new_tricolor(
+        display: &'a mut D,
+        dithering: Dithering<'a>,
+        chromatic: Chromatic,
+    ) -> Self {
+        ColorConverted {
+            display,
+            dithering,
+            levels: Levels::default(),
+            serpentine: false,
+            chromatic: Some(chromatic),
+            output: |color| color,
+            _color: PhantomData,
+        }
+    }
+}
+
+impl<'a, D, C> ColorConverted<'a, D, C>
+where
+    D: DrawTarget,
+    C: PixelColor + Into<Rgb888>,
+{
+    /// Enable serpentine scanning for error diffusion
+    ///
+    /// Every other line is scanned from right to left, which avoids the directional artifacts
+    /// error diffusion produces when errors are always pushed to the right.
+    pub fn set_serpentine(&mut self, serpentine: bool) {
+        self.serpentine = serpentine;
+    }
+
+    /// Change the parameters used to convert colors
+    pub fn set_levels(&mut self, levels: Levels) {
+        self.levels = levels;
+    }
+
+    /// Get the parameters used to convert colors
+    pub fn levels(&self) -> Levels {
+        self.levels
+    }
+
+    /// Get back the wrapped display
+    pub fn into_inner(self) -> &'a mut D {
+        self.display
+    }
+}
+
+impl<D, C> Dimensions for ColorConverted<'_, D, C>
+where
+    D: DrawTarget,
+{
+    fn bounding_box(&self) -> Rectangle {
+        self.display.bounding_box()
+    }
+}
+
+impl<D, C> DrawTarget for ColorConverted<'_, D, C>
+where
+    D: DrawTarget,
+    C: PixelColor + Into<Rgb888>,
+{
+    type Color = C;
+    type Error = D::Error;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        let (levels, chromatic, output) = (self.levels, self.chromatic, self.output);
+        let dithering = &self.dithering;
+        self.display
+            .draw_iter(pixels.into_iter().map(|Pixel(point, color)| {
+                let offset = dithering.ordered_offset(point);
+                Pixel(
+                    point,
+                    output(quantize(&levels, chromatic, color.into(), offset)),
+                )
+            }))
+    }
+
+    fn fill_contiguous<I>(&mut self, area: &Rectangle, colors: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Self::Color>,
+    {
+        let width = area.size.width;
+        let (levels, chromatic, output) = (self.levels, self.chromatic, self.output);
+        let kernel = match self.dithering {
+            Dithering::Stucki(_) => &STUCKI,
+            Dithering::Atkinson(_) => &ATKINSON,
+            _ => &FLOYD_STEINBERG,
+        };
+        match &mut self.dithering {
+            Dithering::FloydSteinberg(errors)
+            | Dithering::Stucki(errors)
+            | Dithering::Atkinson(errors)
+                if errors.len() >= error_buffer_len(width) =>
+            {
+                let diffusion = Diffusion {
+                    levels,
+                    chromatic,
+                    output,
+                    kernel,
+                    serpentine: self.serpentine,
+                };
+                diffusion.draw(self.display, area, colors, errors)
+            }
+            dithering => {
+                let dithering = &*dithering;
+                let colors = area.points().zip(colors).map(|(point, color)| {
+                    let offset = dithering.ordered_offset(point);
+                    output(quantize(&levels, chromatic, color.into(), offset))
+                });
+                self.display.fill_contiguous(area, colors)
+            }
+        }
+    }
+}
+
+/// Error diffusion settings of a `ColorConverted`
+struct Diffusion<T> {
+    levels: Levels,
+    chromatic: Option<Chromatic>,
+    output: fn(TriColor) -> T,
+    kernel: &'static Kernel,
+    serpentine: bool,
+}
+
+impl<T> Diffusion<T> {
+    /// Convert `colors` with error diffusion and draw them in `area`, line by line
+    fn draw<D, C, I>(
+        &self,
+        display: &mut D,
+        area: &Rectangle,
+        colors: I,
+        errors: &mut [i16],
+    ) -> Result<(), D::Error>
+    where
+        D: DrawTarget<Color = T>,
+        C: PixelColor + Into<Rgb888>,
+        I: IntoIterator<Item = C>,
+    {
+        // values stored in the line buffer once converted
+        const BLACK: i16 = 0;
+        const WHITE: i16 = 1;
+        const CHROMATIC: i16 = -1;
+
+        let width = area.size.width as usize;
+        let line_len = width + 2 * PADDING;
+        let (line, errors) = errors.split_at_mut(width);
+        errors[..3 * line_len].fill(0);
+        // offsets of the error lines of the current line and of the two next lines
+        let mut offsets = [0, line_len, 2 * line_len];
+        let mut colors = colors.into_iter();
+
+        for y in 0..area.size.height {
+            let mut len = 0;
+            for (value, color) in line.iter_mut().zip(colors.by_ref()) {
+                let color = color.into();
+                *value = match self.chromatic {
+                    Some(chromatic) if chromatic.matches(color) => CHROMATIC,
+                    _ => self.levels.luma(color) as i16,
+                };
+                len += 1;
+            }
+
+            let reverse = self.serpentine && y % 2 == 1;
+            for i in 0..len {
+                let x = if reverse { len - 1 - i } else { i };
+                if line[x] == CHROMATIC {
+                    continue;
+                }
+                let value = line[x] + errors[offsets[0] + x + PADDING];
+                let white = value >= self.levels.threshold as i16;
+                let error = value - if white { 255 } else { 0 };
+                for &(dx, dy, weight) in self.kernel.taps {
+                    let dx = if reverse { -dx } else { dx };
+                    let index = (offsets[dy as usize] + x + PADDING) as isize + dx as isize;
+                    errors[index as usize] += error * weight / self.kernel.divisor;
+                }
+                line[x] = if white { WHITE } else { BLACK };
+            }
+
+            let line_area = Rectangle::new(
+                area.top_left + Point::new(0, y as i32),
+                Size::new(len as u32, 1),
+            );
+            display.fill_contiguous(
+                &line_area,
+                line[..len].iter().map(|&value| {
+                    (self.output)(match value {
+                        CHROMATIC => TriColor::Chromatic,
+                        value => black_or_white(value == WHITE),
+                    })
+                }),
+            )?;
+            if len < width {
+                break;
+            }
+
+            errors[offsets[0]..offsets[0] + line_len].fill(0);
+            offsets.rotate_left(1);
+        }
+        Ok(())
+    }
+}
+
+#[rustfmt::skip]
+const BAYER_4: [[u8; 4]; 4] = [
+    [0, 8, 2, 10],
+    [12, 4, 14, 6],
+    [3, 11, 1, 9],
+    [15, 7, 13, 5],
+];
+
+#[rustfmt::skip]
+const BAYER_8: [[u8; 8]; 8] = [
+    [0, 32, 8, 40, 2, 34, 10, 42],
+    [48, 16, 56, 24, 50, 18, 58, 26],
+    [12, 44, 4, 36, 14, 46, 6, 38],
+    [60, 28, 52, 20, 62, 30, 54, 22],
+    [3, 35, 11, 43, 1, 33, 9, 41],
+    [51, 19, 59, 27, 49, 17, 57, 25],
+    [15, 47, 7, 39, 13, 45, 5, 37],
+    [63, 31, 55, 23, 61, 29, 53, 21],
+];
+
+#[rustfmt::skip]
+// generated with the void-and-cluster method
+const BLUE_NOISE: [[u8; 16]; 16] = [
+    [120, 61, 134, 223, 84, 33, 168, 12, 113, 225, 63, 246, 185, 233, 88, 169],
+    [23, 206, 181, 17, 109, 214, 58, 140, 201, 24, 161, 93, 34, 133, 14, 221],
+    [144, 73, 250, 49, 158, 187, 81, 251, 100, 51, 142, 210, 172, 57, 191, 106],
+    [42, 167, 101, 126, 220, 3, 121, 40, 170, 231, 82, 8, 114, 254, 80, 232],
+    [212, 11, 195, 31, 72, 239, 152, 196, 16, 127, 188, 222, 45, 157, 26, 128],
+    [154, 87, 235, 143, 179, 94, 54, 108, 237, 65, 29, 105, 139, 207, 184, 66],
+    [248, 47, 115, 62, 209, 20, 164, 217, 79, 146, 178, 243, 69, 90, 1, 118],
+    [30, 190, 173, 6, 131, 255, 41, 136, 10, 204, 43, 159, 22, 229, 162, 218],
+    [77, 148, 99, 226, 74, 182, 117, 192, 86, 247, 119, 97, 197, 130, 53, 103],
+    [242, 19, 198, 44, 155, 96, 59, 230, 28, 165, 60, 5, 240, 39, 175, 202],
+    [137, 64, 122, 238, 25, 211, 0, 149, 104, 224, 135, 183, 151, 71, 112, 9],
+    [91, 213, 166, 85, 186, 111, 249, 174, 48, 75, 208, 32, 89, 205, 236, 160],
+    [37, 252, 18, 55, 138, 38, 78, 123, 194, 13, 107, 253, 124, 15, 56, 189],
+    [76, 145, 110, 228, 203, 163, 219, 21, 241, 141, 171, 50, 156, 227, 102, 129],
+    [2, 199, 176, 68, 7, 98, 52, 150, 92, 36, 215, 83, 200, 27, 177, 216],
+    [244, 95, 35, 153, 245, 125, 193, 234, 70, 180, 132, 4, 116, 67, 147, 46],
+];
+
+#[rustfmt::skip]
+// sRGB transfer function, scaled to 0..=255
+const SRGB_TO_LINEAR: [u8; 256] = [
+    0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 1, 1, 1, 1, 1,
+    1, 1, 2, 2, 2, 2, 2, 2, 2, 2, 3, 3, 3, 3, 3, 3,
+    4, 4, 4, 4, 4, 5, 5, 5, 5, 6, 6, 6, 6, 7, 7, 7,
+    8, 8, 8, 8, 9, 9, 9, 10, 10, 10, 11, 11, 12, 12, 12, 13,
+    13, 13, 14, 14, 15, 15, 16, 16, 17, 17, 17, 18, 18, 19, 19, 20,
+    20, 21, 22, 22, 23, 23, 24, 24, 25, 25, 26, 27, 27, 28, 29, 29,
+    30, 30, 31, 32, 32, 33, 34, 35, 35, 36, 37, 37, 38, 39, 40, 41,
+    41, 42, 43, 44, 45, 45, 46, 47, 48, 49, 50, 51, 51, 52, 53, 54,
+    55, 56, 57, 58, 59, 60, 61, 62, 63, 64, 65, 66, 67, 68, 69, 70,
+    71, 72, 73, 74, 76, 77, 78, 79, 80, 81, 82, 84, 85, 86, 87, 88,
+    90, 91, 92, 93, 95, 96, 97, 99, 100, 101, 103, 104, 105, 107, 108, 109,
+    111, 112, 114, 115, 116, 118, 119, 121, 122, 124, 125, 127, 128, 130, 131, 133,
+    134, 136, 138, 139, 141, 142, 144, 146, 147, 149, 151, 152, 154, 156, 157, 159,
+    161, 163, 164, 166, 168, 170, 171, 173, 175, 177, 179, 181, 183, 184, 186, 188,
+    190, 192, 194, 196, 198, 200, 202, 204, 206, 208, 210, 212, 214, 216, 218, 220,
+    222, 224, 226, 229, 231, 233, 235, 237, 239, 242, 244, 246, 248, 250, 253, 255,
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::Color;
+    use crate::graphics::Display;
+    use embedded_graphics::{
+        pixelcolor::{Gray8, Rgb565},
+        primitives::{PrimitiveStyle, StyledDrawable},
+    };
+
+    type TestDisplay = Display<16, 16, false, { 16 * 16 / 8 }, Color>;
+
+    fn white_pixels(display: &TestDisplay) -> u32 {
+        display.buffer().iter().map(|byte| byte.count_ones()).sum()
+    }
+
+    #[test]
+    fn convert_black_and_white() {
+        let mut display = TestDisplay::default();
+        let mut rgb = ColorConverted::new(&mut display, Dithering::None);
+        rgb.clear(Rgb565::WHITE).unwrap();
+        assert!(display.buffer().iter().all(|&byte| byte == 0xff));
+
+        let mut errors = [0; error_buffer_len(16)];
+        let mut gray = ColorConverted::new(&mut display, Dithering::FloydSteinberg(&mut errors));
+        gray.clear(Gray8::BLACK).unwrap();
+        assert!(display.buffer().iter().all(|&byte| byte == 0x00));
+    }
+
+    #[test]
+    fn convert_threshold() {
+        let mut display = TestDisplay::default();
+        let mut gray = ColorConverted::new(&mut display, Dithering::None);
+        gray.draw_iter([
+            Pixel(Point::new(0, 0), Gray8::new(127)),
+            Pixel(Point::new(1, 0), Gray8::new(128)),
+        ])
+        .unwrap();
+        assert_eq!(display.buffer()[0], 0b0100_0000);
+    }
+
+    #[test]
+    fn convert_floyd_steinberg() {
+        let mut display = TestDisplay::default();
+        let mut errors = [0; error_buffer_len(16)];
+        let mut gray = ColorConverted::new(&mut display, Dithering::FloydSteinberg(&mut errors));
+        // 50% gray is dithered to a checkerboard like pattern keeping the average brightness
+        gray.clear(Gray8::new(128)).unwrap();
+        assert!((120..=136).contains(&white_pixels(&display)));
+
+        let mut display = TestDisplay::default();
+        let mut gray = ColorConverted::new(&mut display, Dithering::None);
+        gray.clear(Gray8::new(128)).unwrap();
+        assert_eq!(white_pixels(&display), 256);
+    }
+
+    #[test]
+    fn convert_too_small_error_buffer() {
+        let mut display = TestDisplay::default();
+        let mut errors = [0; error_buffer_len(8)];
+        let mut gray = ColorConverted::new(&mut display, Dithering::FloydSteinberg(&mut errors));
+        // wider than the error buffer: no dithering
+        Rectangle::new(Point::zero(), Size::new(16, 2))
+            .draw_styled(&PrimitiveStyle::with_fill(Gray8::new(200)), &mut gray)
+            .unwrap();
+        assert_eq!(white_pixels(&display), 32);
+    }
+
+    #[test]
+    fn convert_levels() {
+        let mut display = TestDisplay::default();
+        let mut rgb = ColorConverted::new(&mut display, Dithering::None);
+        assert_eq!(rgb.levels().luma(Rgb888::WHITE), 255);
+        assert_eq!(rgb.levels().luma(Rgb888::new(128, 128, 128)), 128);
+
+        rgb.set_levels(Levels {
+            threshold: 100,
+            ..Default::default()
+        });
+        rgb.draw_iter([Pixel(Point::new(0, 0), Rgb888::new(110, 110, 110))])
+            .unwrap();
+
+        // only take the red channel into account
+        rgb.set_levels(Levels {
+            weights: [1, 0, 0],
+            ..Default::default()
+        });
+        rgb.draw_iter([
+            Pixel(Point::new(1, 0), Rgb888::RED),
+            Pixel(Point::new(2, 0), Rgb888::GREEN),
+        ])
+        .unwrap();
+
+        // sRGB 50% gray is much darker in linear light
+        rgb.set_levels(Levels {
+            gamma: Gamma::Srgb,
+            ..Default::default()
+        });
+        assert_eq!(rgb.levels().luma(Rgb888::new(128, 128, 128)), 55);
+        rgb.draw_iter([Pixel(Point::new(3, 0), Rgb888::new(128, 128, 128))])
+            .unwrap();
+
+        assert_eq!(display.buffer()[0], 0b1100_0000);
+    }
+
+    #[test]
+    fn convert_ordered() {
+        for dithering in [Dithering::Bayer4, Dithering::Bayer8, Dithering::BlueNoise] {
+            let mut display = TestDisplay::default();
+            let mut gray = ColorConverted::new(&mut display, dithering);
+            gray.clear(Gray8::new(128)).unwrap();
+            assert!((127..=129).contains(&white_pixels(&display)));
+
+            let mut gray = ColorConverted::new(&mut display, Dithering::BlueNoise);
+            gray.clear(Gray8::WHITE).unwrap();
+            assert_eq!(white_pixels(&display), 256);
+            let mut gray = ColorConverted::new(&mut display, Dithering::BlueNoise);
+            gray.clear(Gray8::BLACK).unwrap();
+            assert_eq!(white_pixels(&display), 0);
+        }
+    }
+
+    #[test]
+    fn convert_ordered_bands() {
+        // drawing pixel by pixel or in bands gives the same result as a single fill
+        let gray = Gray8::new(70);
+        let mut full = TestDisplay::default();
+        ColorConverted::new(&mut full, Dithering::Bayer8)
+            .clear(gray)
+            .unwrap();
+
+        let mut banded = TestDisplay::default();
+        let mut target = ColorConverted::new(&mut banded, Dithering::Bayer8);
+        for band in 0..4 {
+            Rectangle::new(Point::new(0, band * 4), Size::new(16, 4))
+                .draw_styled(&PrimitiveStyle::with_fill(gray), &mut target)
+                .unwrap();
+        }
+        assert_eq!(full.buffer(), banded.buffer());
+
+        let mut single = TestDisplay::default();
+        let mut target = ColorConverted::new(&mut single, Dithering::Bayer8);
+        for point in target.bounding_box().points() {
+            target.draw_iter([Pixel(point, gray)]).unwrap();
+        }
+        assert_eq!(full.buffer(), single.buffer());
+    }
+
+    #[test]
+    fn convert_error_diffusion_kernels() {
+        let mut errors = [0; error_buffer_len(16)];
+        for serpentine in [false, true] {
+            for kernel in 0..3 {
+                let dithering = match kernel {
+                    0 => Dithering::FloydSteinberg(&mut errors),
+                    1 => Dithering::Stucki(&mut errors),
+                    _ => Dithering::Atkinson(&mut errors),
+                };
+                let mut display = TestDisplay::default();
+                let mut gray = ColorConverted::new(&mut display, dithering);
+                gray.set_serpentine(serpentine);
+                gray.clear(Gray8::new(128)).unwrap();
+                // about half of the pixels are white
+                assert!((112..=144).contains(&white_pixels(&display)));
+
+                let mut gray = ColorConverted::<_, Gray8>::new(&mut display, Dithering::None);
+                gray.clear(Gray8::BLACK).unwrap();
+            }
+        }
+    }
+
+    #[test]
+    fn convert_serpentine() {
+        let mut errors = [0; error_buffer_len(16)];
+        let area = Rectangle::new(Point::zero(), Size::new(16, 2));
+        let style = PrimitiveStyle::with_fill(Gray8::new(200));
+
+        let mut display = TestDisplay::default();
+        let mut gray = ColorConverted::new(&mut display, Dithering::FloydSteinberg(&mut errors));
+        area.draw_styled(&style, &mut gray).unwrap();
+        assert_eq!(
+            display.buffer()[0..4],
+            [0xff, 0xff, 0b1010_1010, 0b1010_1011]
+        );
+
+        // the first line is scanned left to right, the second one right to left
+        let mut display = TestDisplay::default();
+        let mut gray = ColorConverted::new(&mut display, Dithering::FloydSteinberg(&mut errors));
+        gray.set_serpentine(true);
+        area.draw_styled(&style, &mut gray).unwrap();
+        assert_eq!(
+            display.buffer()[0..4],
+            [0xff, 0xff, 0b1011_0101, 0b0101_0101]
+        );
+    }
+
+    #[test]
+    fn convert_chromatic() {
+        assert!(Chromatic::RED.matches(Rgb888::RED));
+        assert!(Chromatic::RED.matches(Rgb888::new(200, 40, 60)));
+        assert!(!Chromatic::RED.matches(Rgb888::new(200, 180, 180)));
+        assert!(!Chromatic::RED.matches(Rgb888::YELLOW));
+        assert!(!Chromatic::RED.matches(Rgb888::WHITE));
+        assert!(Chromatic::YELLOW.matches(Rgb888::YELLOW));
+        assert!(Chromatic::YELLOW.matches(Rgb888::new(250, 200, 20)));
+        assert!(!Chromatic::YELLOW.matches(Rgb888::BLUE));
+    }
+
+    #[test]
+    fn convert_tricolor() {
+        let mut errors = [0; error_buffer_len(8)];
+        for dithering in [Dithering::None, Dithering::FloydSteinberg(&mut errors)] {
+            let mut display = Display::<8, 2, true, { 8 * 2 * 2 / 8 }, TriColor>::default();
+            let mut rgb = ColorConverted::new_tricolor(&mut display, dithering, Chromatic::RED);
+            rgb.fill_contiguous(
+                &Rectangle::new(Point::zero(), Size::new(8, 2)),
+                [
+                    Rgb888::RED,
+                    Rgb888::WHITE,
+                    Rgb888::BLACK,
+                    Rgb888::new(220, 30, 30),
+                    Rgb888::BLUE,
+                    Rgb888::YELLOW,
+                    Rgb888::WHITE,
+                    Rgb888::RED,
+                ]
+                .into_iter()
+                .chain([Rgb888::WHITE; 8]),
+            )
+            .unwrap();
+            assert_eq!(display.bw_buffer(), [0b0100_0110, 0xff]);
+            assert_eq!(display.chromatic_buffer(), [0b1001_0001, 0x00]);
+        }
+    }
+}
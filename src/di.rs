@@ -0,0 +1,222 @@
+//! Adapter to use the drivers over the `display-interface` traits
+//!
+//! The drivers send commands and data through an [`SpiDevice`] and a DC pin. With the
+//! `display-interface` feature, any [`WriteOnlyDataCommand`] (e.g. an `SPIInterface` of
+//! `display-interface-spi`, as used by `mipidsi` or `ssd1306`) can stand in for both:
+//! [`DiAdapter::split`] returns an SPI device and a DC pin which forward the bytes written while
+//! DC is low as commands and the others as data. The writes following the first one of a
+//! transaction are data, as with [`in_band_dc`](crate::traits::InterfaceConfig::in_band_dc).
+//!
+//! Reading isn't supported by `display-interface`, the few displays which read from the
+//! controller can't be used this way.
+//!
+//! With the `async` feature, [`DiSpi`] is also an async SPI device for the async drivers when the
+//! interface implements [`AsyncWriteOnlyDataCommand`].
+//!
+//!```rust, ignore
+//!use epd_waveshare::{di::DiAdapter, epd1in54::*, prelude::*};
+//!
+//!// interface is a display_interface_spi::SPIInterface, already owning the real DC pin
+//!let mut adapter = DiAdapter::new(interface);
+//!let (mut spi, dc) = adapter.split();
+//!let mut epd = Epd1in54::new(&mut spi, busy, dc, rst, &mut delay, None)?;
+//!```
+
+use core::cell::Cell;
+use core::convert::Infallible;
+#[cfg(feature = "async")]
+use display_interface::AsyncWriteOnlyDataCommand;
+use display_interface::{DataFormat, DisplayError, WriteOnlyDataCommand};
+use embedded_hal::{
+    digital::{self, OutputPin},
+    spi::{self, ErrorKind, Operation, SpiDevice},
+};
+#[cfg(feature = "async")]
+use embedded_hal_async::spi::SpiDevice as AsyncSpiDevice;
+
+/// Error of a [`DiSpi`]
+#[derive(Debug, Clone)]
+pub enum DiError {
+    /// Error of the wrapped interface
+    Interface(DisplayError),
+    /// The operation (a read) isn't supported by `display-interface`
+    Unsupported,
+}
+
+impl spi::Error for DiError {
+    fn kind(&self) -> ErrorKind {
+        ErrorKind::Other
+    }
+}
+
+/// Owner of a `display-interface` and of the DC state shared by [`DiSpi`] and [`DiDc`]
+pub struct DiAdapter<DI> {
+    interface: DI,
+    data: Cell<bool>,
+}
+
+impl<DI> DiAdapter<DI> {
+    /// Wrap a `display-interface`
+    pub fn new(interface: DI) -> Self {
+        DiAdapter {
+            interface,
+            data: Cell::new(false),
+        }
+    }
+
+    /// Get the SPI device and DC pin to give to a driver
+    pub fn split(&mut self) -> (DiSpi<'_, DI>, DiDc<'_>) {
+        let DiAdapter { interface, data } = self;
+        (DiSpi { interface, data }, DiDc { data })
+    }
+
+    /// Get back the `display-interface`
+    pub fn release(self) -> DI {
+        self.interface
+    }
+}
+
+/// SPI device sending commands or data through a `display-interface`, depending on [`DiDc`]
+pub struct DiSpi<'a, DI> {
+    interface: &'a mut DI,
+    data: &'a Cell<bool>,
+}
+
+impl<DI> spi::ErrorType for DiSpi<'_, DI> {
+    type Error = DiError;
+}
+
+#[maybe_async_cfg::maybe(
+    sync(keep_self),
+    async(
+        keep_self,
+        feature = "async",
+        idents(
+            SpiDevice(async = "AsyncSpiDevice"),
+            WriteOnlyDataCommand(async = "AsyncWriteOnlyDataCommand")
+        )
+    )
+)]
+impl<DI: WriteOnlyDataCommand> SpiDevice for DiSpi<'_, DI> {
+    async fn transaction(
+        &mut self,
+        operations: &mut [Operation<'_, u8>],
+    ) -> Result<(), Self::Error> {
+        let mut data = self.data.get();
+        for operation in operations {
+            let words = match operation {
+                Operation::Write(words) => words,
+                Operation::DelayNs(_) => continue,
+                _ => return Err(DiError::Unsupported),
+            };
+            let result = if data {
+                self.interface.send_data(DataFormat::U8(words)).await
+            } else {
+                self.interface.send_commands(DataFormat::U8(words)).await
+            };
+            result.map_err(DiError::Interface)?;
+            // the data of a command follows it
+            data = true;
+        }
+        Ok(())
+    }
+}
+
+/// DC pin selecting whether [`DiSpi`] sends commands (low) or data (high)
+pub struct DiDc<'a> {
+    data: &'a Cell<bool>,
+}
+
+impl digital::ErrorType for DiDc<'_> {
+    type Error = Infallible;
+}
+
+impl OutputPin for DiDc<'_> {
+    fn set_low(&mut self) -> Result<(), Self::Error> {
+        self.data.set(false);
+        Ok(())
+    }
+
+    fn set_high(&mut self) -> Result<(), Self::Error> {
+        self.data.set(true);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Default)]
+    struct Interface {
+        commands: [u8; 4],
+        data: [u8; 4],
+    }
+
+    impl WriteOnlyDataCommand for Interface {
+        fn send_commands(&mut self, cmd: DataFormat<'_>) -> Result<(), DisplayError> {
+            match cmd {
+                DataFormat::U8(cmd) => self.commands[..cmd.len()].copy_from_slice(cmd),
+                _ => return Err(DisplayError::DataFormatNotImplemented),
+            }
+            Ok(())
+        }
+
+        fn send_data(&mut self, buf: DataFormat<'_>) -> Result<(), DisplayError> {
+            match buf {
+                DataFormat::U8(buf) => self.data[..buf.len()].copy_from_slice(buf),
+                _ => return Err(DisplayError::DataFormatNotImplemented),
+            }
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn di_adapter() {
+        let mut adapter = DiAdapter::new(Interface::default());
+        let (mut spi, mut dc) = adapter.split();
+        dc.set_low().unwrap();
+        spi.write(&[0x12]).unwrap();
+        dc.set_high().unwrap();
+        spi.write(&[0x34, 0x56]).unwrap();
+        assert!(matches!(spi.read(&mut [0]), Err(DiError::Unsupported)));
+        // a command and its data in one transaction
+        dc.set_low().unwrap();
+        spi.transaction(&mut [Operation::Write(&[0x13]), Operation::Write(&[0x78])])
+            .unwrap();
+
+        let interface = adapter.release();
+        assert_eq!(interface.commands, [0x13, 0, 0, 0]);
+        assert_eq!(interface.data, [0x78, 0x56, 0, 0]);
+    }
+
+    #[cfg(feature = "async")]
+    #[test]
+    fn di_adapter_async() {
+        use crate::test_utils::block_on;
+
+        #[derive(Default)]
+        struct AsyncInterface(Interface);
+
+        impl AsyncWriteOnlyDataCommand for AsyncInterface {
+            async fn send_commands(&mut self, cmd: DataFormat<'_>) -> Result<(), DisplayError> {
+                self.0.send_commands(cmd)
+            }
+
+            async fn send_data(&mut self, buf: DataFormat<'_>) -> Result<(), DisplayError> {
+                self.0.send_data(buf)
+            }
+        }
+
+        let mut adapter = DiAdapter::new(AsyncInterface::default());
+        let (mut spi, mut dc) = adapter.split();
+        dc.set_low().unwrap();
+        block_on(spi.write(&[0x12])).unwrap();
+        dc.set_high().unwrap();
+        block_on(spi.write(&[0x34, 0x56])).unwrap();
+
+        let interface = adapter.release().0;
+        assert_eq!(interface.commands, [0x12, 0, 0, 0]);
+        assert_eq!(interface.data, [0x34, 0x56, 0, 0]);
+    }
+}
@@ -0,0 +1,637 @@
+//! Snapshots of the state of a panel, for bug reports
+//!
+//! The drivers don't keep much more state than what the controller needs, which makes an issue
+//! seen in the field hard to reproduce: was the panel asleep, which LUT was loaded, how long did
+//! the last refresh take? [`Tracked`] wraps a driver and records all of it on the way, and
+//! [`dump_state`](Tracked::dump_state) returns it as a [`PanelState`], printable with `{:?}` or,
+//! with the `defmt` feature, with `defmt`.
+//!
+//! E-paper panels are rated for a limited number of refreshes. [`Tracked`] also counts them over
+//! the whole life of the panel ([`LifetimeCounts`]), the counts being kept across reboots in a
+//! [`FrameStore`] with [`save_lifetime`](Tracked::save_lifetime) and
+//! [`restore_lifetime`](Tracked::restore_lifetime), so that a product can e.g. limit the rate of
+//! its refreshes or warn before the panel wears out.
+//!
+//!```rust, no_run
+//!# use embedded_hal_mock::eh1::*;
+//!# fn main() -> Result<(), epd_waveshare::error::EpdError<embedded_hal::spi::ErrorKind>> {
+//!use epd_waveshare::{diagnostics::Tracked, epd2in9_v2::*, prelude::*};
+//!#
+//!# let expectations = [];
+//!# let mut spi = spi::Mock::new(&expectations);
+//!# let expectations = [];
+//!# let busy_in = digital::Mock::new(&expectations);
+//!# let dc = digital::Mock::new(&expectations);
+//!# let rst = digital::Mock::new(&expectations);
+//!# let mut delay = delay::NoopDelay::new();
+//!# fn micros() -> u32 { 0 }
+//!
+//!let epd = Epd2in9::new(&mut spi, busy_in, dc, rst, &mut delay, None)?;
+//!// `micros` reads a timer of the MCU, to time the refreshes
+//!let mut epd = Tracked::wrap(epd).with_clock(micros);
+//!
+//!let frame = [0xff; WIDTH as usize / 8 * HEIGHT as usize];
+//!if epd.update_and_display_frame(&mut spi, &frame, &mut delay).is_err() {
+//!    // log it along the error
+//!    let state = epd.dump_state();
+//!    // defmt::error!("refresh failed: {}", state);
+//!}
+//!# Ok(())
+//!# }
+//!```
+
+use crate::error::EpdError;
+use crate::frame_store::FrameStore;
+use crate::pin::BusyPin;
+use crate::rect::Rect;
+use crate::traits::{InterfaceConfig, QuickRefresh, RefreshLut, WaveshareDisplay};
+use embedded_hal::{delay::*, digital::*, spi::SpiDevice};
+
+#[cfg(feature = "graphics")]
+use crate::graphics::DisplayRotation;
+
+/// Power state of the controller, as last requested by the driver
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum PowerState {
+    /// Not known yet, the driver was wrapped after its creation
+    #[default]
+    Unknown,
+    /// Initialised and ready to refresh
+    Awake,
+    /// In deep sleep, until the next [`wake_up`](WaveshareDisplay::wake_up)
+    Asleep,
+}
+
+/// Counts of the operations done through a [`Tracked`] driver
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct RefreshStats {
+    /// Refreshes with the full LUT
+    pub full: u32,
+    /// Refreshes with the quick LUT or through [`QuickRefresh`]
+    pub quick: u32,
+    /// Partial frames sent to the RAM of the controller
+    pub partial_updates: u32,
+    /// Calls which returned an error
+    pub errors: u32,
+}
+
+/// Refreshes done by a panel over its whole life
+///
+/// Unlike [`RefreshStats`], they are meant to be saved and restored across reboots, which
+/// [`to_bytes`](LifetimeCounts::to_bytes) and [`from_bytes`](LifetimeCounts::from_bytes) help with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct LifetimeCounts {
+    /// Full refreshes
+    pub full: u32,
+    /// Quick (partial) refreshes
+    pub partial: u32,
+}
+
+impl LifetimeCounts {
+    /// Length of the saved counts in bytes
+    pub const BYTES: usize = 8;
+
+    /// All the refreshes, full and partial
+    pub fn total(&self) -> u32 {
+        self.full.saturating_add(self.partial)
+    }
+
+    /// Encodes the counts, in little endian
+    pub fn to_bytes(&self) -> [u8; Self::BYTES] {
+        let mut bytes = [0; Self::BYTES];
+        bytes[..4].copy_from_slice(&self.full.to_le_bytes());
+        bytes[4..].copy_from_slice(&self.partial.to_le_bytes());
+        bytes
+    }
+
+    /// Decodes counts encoded by [`to_bytes`](LifetimeCounts::to_bytes)
+    pub fn from_bytes(bytes: [u8; Self::BYTES]) -> Self {
+        LifetimeCounts {
+            full: u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]),
+            partial: u32::from_le_bytes([bytes[4], bytes[5], bytes[6], bytes[7]]),
+        }
+    }
+}
+
+/// Snapshot of the state of a panel, returned by [`Tracked::dump_state`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct PanelState {
+    /// Power state of the controller
+    pub power: PowerState,
+    /// LUT last selected with [`set_lut`](WaveshareDisplay::set_lut), if any
+    pub lut: Option<RefreshLut>,
+    /// Rotation the application draws with, if it told it with
+    /// [`set_rotation`](Tracked::set_rotation)
+    #[cfg(feature = "graphics")]
+    pub rotation: Option<DisplayRotation>,
+    /// Area of the RAM last written, the full panel for a full frame
+    pub last_window: Option<Rect>,
+    /// Length of the last refresh in microseconds, if a clock was given
+    pub last_refresh_us: Option<u32>,
+    /// Counts of the operations so far
+    pub stats: RefreshStats,
+    /// Refreshes over the whole life of the panel
+    pub lifetime: LifetimeCounts,
+}
+
+/// Driver wrapper recording the state of the panel for [`dump_state`](Tracked::dump_state)
+///
+/// It implements [`WaveshareDisplay`] and [`QuickRefresh`] like the driver it wraps, which can
+/// still be reached with [`epd_mut`](Tracked::epd_mut) for the calls specific to it (these aren't
+/// recorded then).
+pub struct Tracked<EPD> {
+    epd: EPD,
+    state: PanelState,
+    clock: Option<fn() -> u32>,
+}
+
+impl<EPD> Tracked<EPD> {
+    /// Wraps `epd`, whose power state is unknown until the next call to it
+    pub fn wrap(epd: EPD) -> Self {
+        Tracked {
+            epd,
+            state: PanelState {
+                power: PowerState::Unknown,
+                lut: None,
+                #[cfg(feature = "graphics")]
+                rotation: None,
+                last_window: None,
+                last_refresh_us: None,
+                stats: RefreshStats::default(),
+                lifetime: LifetimeCounts::default(),
+            },
+            clock: None,
+        }
+    }
+
+    /// Times the refreshes with `clock`, a free running counter of microseconds
+    ///
+    /// The counter may wrap around.
+    pub fn with_clock(mut self, clock: fn() -> u32) -> Self {
+        self.clock = Some(clock);
+        self
+    }
+
+    /// Records the rotation the application draws with
+    #[cfg(feature = "graphics")]
+    pub fn set_rotation(&mut self, rotation: DisplayRotation) {
+        self.state.rotation = Some(rotation);
+    }
+
+    /// Returns the wrapped driver
+    pub fn epd(&self) -> &EPD {
+        &self.epd
+    }
+
+    /// Returns the wrapped driver, e.g. for the calls not part of the traits
+    pub fn epd_mut(&mut self) -> &mut EPD {
+        &mut self.epd
+    }
+
+    /// Unwraps the driver
+    pub fn into_inner(self) -> EPD {
+        self.epd
+    }
+
+    /// Returns the counts of the operations so far
+    pub fn stats(&self) -> RefreshStats {
+        self.state.stats
+    }
+
+    /// Returns the refreshes done over the whole life of the panel
+    ///
+    /// They only include the refreshes done before the last reboot once
+    /// [restored](Tracked::restore_lifetime).
+    pub fn lifetime(&self) -> LifetimeCounts {
+        self.state.lifetime
+    }
+
+    /// Sets the refreshes done over the whole life of the panel, e.g. read from a storage of the
+    /// application
+    pub fn set_lifetime(&mut self, lifetime: LifetimeCounts) {
+        self.state.lifetime = lifetime;
+    }
+
+    /// Saves the refreshes done over the whole life of the panel in `store`, as
+    /// [`LifetimeCounts::BYTES`] bytes
+    pub fn save_lifetime<STORE: FrameStore>(&self, store: &mut STORE) -> Result<(), STORE::Error> {
+        store.save(&self.state.lifetime.to_bytes())
+    }
+
+    /// Restores the refreshes done over the whole life of the panel from `store`
+    ///
+    /// The store must keep the bytes saved, not only their hash. Call it right after wrapping the
+    /// driver: the refreshes counted so far are replaced. Returns `false`, leaving the counts as
+    /// they are, if the store has nothing saved yet.
+    pub fn restore_lifetime<STORE: FrameStore>(
+        &mut self,
+        store: &mut STORE,
+    ) -> Result<bool, STORE::Error> {
+        let mut bytes = [0; LifetimeCounts::BYTES];
+        if !store.restore(&mut bytes)? {
+            return Ok(false);
+        }
+        self.state.lifetime = LifetimeCounts::from_bytes(bytes);
+        Ok(true)
+    }
+
+    /// Returns a snapshot of the state of the panel
+    pub fn dump_state(&self) -> PanelState {
+        self.state
+    }
+
+    fn now(&self) -> Option<u32> {
+        self.clock.map(|clock| clock())
+    }
+
+    /// Counts the error of a call, if any
+    fn record<E>(&mut self, result: Result<(), E>) -> Result<(), E> {
+        if result.is_err() {
+            self.state.stats.errors += 1;
+        }
+        result
+    }
+
+    /// Counts and times a refresh started at `start`
+    fn record_refresh<E>(
+        &mut self,
+        start: Option<u32>,
+        quick: bool,
+        result: Result<(), E>,
+    ) -> Result<(), E> {
+        if result.is_ok() {
+            let lifetime = &mut self.state.lifetime;
+            if quick {
+                self.state.stats.quick += 1;
+                lifetime.partial = lifetime.partial.saturating_add(1);
+            } else {
+                self.state.stats.full += 1;
+                lifetime.full = lifetime.full.saturating_add(1);
+            }
+        }
+        self.state.last_refresh_us = match (start, self.now()) {
+            (Some(start), Some(end)) => Some(end.wrapping_sub(start)),
+            _ => None,
+        };
+        self.record(result)
+    }
+
+    fn quick_lut(&self) -> bool {
+        self.state.lut == Some(RefreshLut::Quick)
+    }
+}
+
+impl<EPD, SPI, BUSY, DC, RST, DELAY> WaveshareDisplay<SPI, BUSY, DC, RST, DELAY> for Tracked<EPD>
+where
+    EPD: WaveshareDisplay<SPI, BUSY, DC, RST, DELAY>,
+    SPI: SpiDevice,
+    BUSY: BusyPin,
+    DC: OutputPin,
+    RST: OutputPin,
+    DELAY: DelayNs,
+{
+    type DisplayColor = EPD::DisplayColor;
+
+    fn new(
+        spi: &mut SPI,
+        busy: BUSY,
+        dc: DC,
+        rst: RST,
+        delay: &mut DELAY,
+        delay_us: Option<u32>,
+    ) -> Result<Self, EpdError<SPI::Error>> {
+        let epd = EPD::new(spi, busy, dc, rst, delay, delay_us)?;
+        let mut tracked = Tracked::wrap(epd);
+        tracked.state.power = PowerState::Awake;
+        Ok(tracked)
+    }
+
+    /// The power state is unknown, until the next [`wake_up`](WaveshareDisplay::wake_up)
+    fn new_uninit(busy: BUSY, dc: DC, rst: RST, delay_us: Option<u32>) -> Self {
+        Tracked::wrap(EPD::new_uninit(busy, dc, rst, delay_us))
+    }
+
+    fn sleep(&mut self, spi: &mut SPI, delay: &mut DELAY) -> Result<(), EpdError<SPI::Error>> {
+        let result = self.epd.sleep(spi, delay);
+        if result.is_ok() {
+            self.state.power = PowerState::Asleep;
+        }
+        self.record(result)
+    }
+
+    fn wake_up(&mut self, spi: &mut SPI, delay: &mut DELAY) -> Result<(), EpdError<SPI::Error>> {
+        let result = self.epd.wake_up(spi, delay);
+        self.state.power = if result.is_ok() {
+            PowerState::Awake
+        } else {
+            PowerState::Unknown
+        };
+        self.record(result)
+    }
+
+    fn set_background_color(&mut self, color: Self::DisplayColor) {
+        self.epd.set_background_color(color)
+    }
+
+    fn background_color(&self) -> &Self::DisplayColor {
+        self.epd.background_color()
+    }
+
+    fn width(&self) -> u32 {
+        self.epd.width()
+    }
+
+    fn height(&self) -> u32 {
+        self.epd.height()
+    }
+
+    fn update_frame(
+        &mut self,
+        spi: &mut SPI,
+        buffer: &[u8],
+        delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.state.last_window = Some(Rect::new(0, 0, self.epd.width(), self.epd.height()));
+        let result = self.epd.update_frame(spi, buffer, delay);
+        self.record(result)
+    }
+
+    fn update_partial_frame(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+        buffer: &[u8],
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.state.last_window = Some(Rect::new(x, y, width, height));
+        let result = self
+            .epd
+            .update_partial_frame(spi, delay, buffer, x, y, width, height);
+        if result.is_ok() {
+            self.state.stats.partial_updates += 1;
+        }
+        self.record(result)
+    }
+
+    fn display_frame(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        let start = self.now();
+        let result = self.epd.display_frame(spi, delay);
+        self.record_refresh(start, self.quick_lut(), result)
+    }
+
+    fn update_and_display_frame(
+        &mut self,
+        spi: &mut SPI,
+        buffer: &[u8],
+        delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.state.last_window = Some(Rect::new(0, 0, self.epd.width(), self.epd.height()));
+        let start = self.now();
+        let result = self.epd.update_and_display_frame(spi, buffer, delay);
+        self.record_refresh(start, self.quick_lut(), result)
+    }
+
+    fn clear_frame(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.state.last_window = Some(Rect::new(0, 0, self.epd.width(), self.epd.height()));
+        let result = self.epd.clear_frame(spi, delay);
+        self.record(result)
+    }
+
+    fn set_lut(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+        refresh_rate: Option<RefreshLut>,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        let result = self.epd.set_lut(spi, delay, refresh_rate);
+        if result.is_ok() && refresh_rate.is_some() {
+            self.state.lut = refresh_rate;
+        }
+        self.record(result)
+    }
+
+    fn wait_until_idle(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        let result = self.epd.wait_until_idle(spi, delay);
+        self.record(result)
+    }
+
+    fn interface_config_mut(&mut self) -> &mut InterfaceConfig {
+        self.epd.interface_config_mut()
+    }
+
+    fn release(self) -> (BUSY, DC, RST) {
+        self.epd.release()
+    }
+}
+
+impl<EPD, SPI, BUSY, DC, RST, DELAY> QuickRefresh<SPI, BUSY, DC, RST, DELAY> for Tracked<EPD>
+where
+    EPD: QuickRefresh<SPI, BUSY, DC, RST, DELAY> + WaveshareDisplay<SPI, BUSY, DC, RST, DELAY>,
+    SPI: SpiDevice,
+    BUSY: BusyPin,
+    DC: OutputPin,
+    RST: OutputPin,
+    DELAY: DelayNs,
+{
+    fn update_old_frame(
+        &mut self,
+        spi: &mut SPI,
+        buffer: &[u8],
+        delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        let result = self.epd.update_old_frame(spi, buffer, delay);
+        self.record(result)
+    }
+
+    fn update_new_frame(
+        &mut self,
+        spi: &mut SPI,
+        buffer: &[u8],
+        delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.state.last_window = Some(Rect::new(0, 0, self.epd.width(), self.epd.height()));
+        let result = self.epd.update_new_frame(spi, buffer, delay);
+        self.record(result)
+    }
+
+    fn display_new_frame(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        let start = self.now();
+        let result = self.epd.display_new_frame(spi, delay);
+        self.record_refresh(start, true, result)
+    }
+
+    fn update_and_display_new_frame(
+        &mut self,
+        spi: &mut SPI,
+        buffer: &[u8],
+        delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.state.last_window = Some(Rect::new(0, 0, self.epd.width(), self.epd.height()));
+        let start = self.now();
+        let result = self.epd.update_and_display_new_frame(spi, buffer, delay);
+        self.record_refresh(start, true, result)
+    }
+
+    fn update_partial_old_frame(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+        buffer: &[u8],
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        let result = self
+            .epd
+            .update_partial_old_frame(spi, delay, buffer, x, y, width, height);
+        self.record(result)
+    }
+
+    fn update_partial_new_frame(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+        buffer: &[u8],
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.state.last_window = Some(Rect::new(x, y, width, height));
+        let result = self
+            .epd
+            .update_partial_new_frame(spi, delay, buffer, x, y, width, height);
+        if result.is_ok() {
+            self.state.stats.partial_updates += 1;
+        }
+        self.record(result)
+    }
+
+    fn clear_partial_frame(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.state.last_window = Some(Rect::new(x, y, width, height));
+        let result = self
+            .epd
+            .clear_partial_frame(spi, delay, x, y, width, height);
+        self.record(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::sync::atomic::{AtomicU32, Ordering};
+
+    /// Counts 1000us per call, wrapping around after the first one
+    fn clock() -> u32 {
+        static NOW: AtomicU32 = AtomicU32::new(u32::MAX - 99);
+        NOW.fetch_add(1000, Ordering::Relaxed)
+    }
+
+    #[test]
+    fn refresh_stats() {
+        let mut tracked = Tracked::wrap(()).with_clock(clock);
+        let start = tracked.now();
+        assert_eq!(
+            tracked.record_refresh(start, false, Ok::<_, ()>(())),
+            Ok(())
+        );
+        assert_eq!(tracked.dump_state().last_refresh_us, Some(1000));
+        assert_eq!(tracked.record_refresh(None, true, Err(())), Err(()));
+        assert_eq!(tracked.dump_state().last_refresh_us, None);
+        let start = tracked.now();
+        assert_eq!(tracked.record_refresh(start, true, Ok::<_, ()>(())), Ok(()));
+
+        let state = tracked.dump_state();
+        assert_eq!(state.power, PowerState::Unknown);
+        assert_eq!(state.last_refresh_us, Some(1000));
+        assert_eq!(
+            state.stats,
+            RefreshStats {
+                full: 1,
+                quick: 1,
+                partial_updates: 0,
+                errors: 1,
+            }
+        );
+        assert_eq!(
+            state.lifetime,
+            LifetimeCounts {
+                full: 1,
+                partial: 1
+            }
+        );
+    }
+
+    /// Keeps the bytes saved, as an FRAM or EEPROM would
+    struct Bytes(Option<[u8; 8]>);
+
+    impl FrameStore for Bytes {
+        type Error = ();
+        fn save(&mut self, frame: &[u8]) -> Result<(), ()> {
+            self.0 = Some(frame.try_into().map_err(|_| ())?);
+            Ok(())
+        }
+        fn restore(&mut self, frame: &mut [u8]) -> Result<bool, ()> {
+            match self.0 {
+                Some(bytes) => {
+                    frame.copy_from_slice(&bytes);
+                    Ok(true)
+                }
+                None => Ok(false),
+            }
+        }
+    }
+
+    #[test]
+    fn lifetime_persistence() {
+        let counts = LifetimeCounts {
+            full: 0x0102_0304,
+            partial: 7,
+        };
+        assert_eq!(counts.to_bytes(), [4, 3, 2, 1, 7, 0, 0, 0]);
+        assert_eq!(LifetimeCounts::from_bytes(counts.to_bytes()), counts);
+        assert_eq!(counts.total(), 0x0102_030b);
+
+        let mut store = Bytes(None);
+        let mut tracked = Tracked::wrap(());
+        assert_eq!(tracked.restore_lifetime(&mut store), Ok(false));
+        tracked.set_lifetime(counts);
+        tracked.save_lifetime(&mut store).unwrap();
+
+        let mut tracked = Tracked::wrap(());
+        assert_eq!(tracked.restore_lifetime(&mut store), Ok(true));
+        assert_eq!(tracked.lifetime(), counts);
+    }
+}
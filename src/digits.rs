@@ -0,0 +1,333 @@
+//! Flicker-free numbers for clocks and sensor readouts
+//!
+//! [`Digits`] shows a fixed number of characters out of [`CHARSET`], pre-rendered once with a
+//! monospaced font. When the text changes, only the cells that differ are drawn and sent to the
+//! EPD with a quick refresh, and a full refresh is done after a given number of updates (or when
+//! requested) to remove the ghosting that quick refreshes leave behind.
+//!
+//!```rust, no_run
+//!# use embedded_hal_mock::eh1::*;
+//!# fn main() -> Result<(), epd_waveshare::error::EpdError<embedded_hal::spi::ErrorKind>> {
+//!use embedded_graphics::{mono_font::ascii::FONT_10X20, prelude::*};
+//!use epd_waveshare::{digits::Digits, epd2in9_v2::*, prelude::*};
+//!#
+//!# let expectations = [];
+//!# let mut spi = spi::Mock::new(&expectations);
+//!# let expectations = [];
+//!# let busy_in = digital::Mock::new(&expectations);
+//!# let dc = digital::Mock::new(&expectations);
+//!# let rst = digital::Mock::new(&expectations);
+//!# let mut delay = delay::NoopDelay::new();
+//!
+//!let mut epd = Epd2in9::new(&mut spi, busy_in, dc, rst, &mut delay, None)?;
+//!let mut display = Display2in9::default();
+//!let mut glyphs = [0; Digits::<5>::glyph_buffer_len(16, 20)];
+//!let mut clock = Digits::<5>::new(&FONT_10X20, Point::new(8, 40), &mut glyphs).unwrap();
+//!
+//!// once a minute, a full refresh a day
+//!clock.set("12:34");
+//!clock.flush(&mut display, &mut epd, &mut spi, &mut delay)?;
+//!# Ok(())
+//!# }
+//!```
+
+use crate::color::Color;
+use crate::error::EpdError;
+use crate::graphics::{DisplayBuffer, DisplayRotation, VarDisplay};
+use crate::pin::BusyPin;
+use crate::refresh_policy::{RefreshCounter, RefreshPolicy};
+use crate::traits::{RefreshLut, WaveshareDisplay};
+use embedded_graphics::{
+    mono_font::{MonoFont, MonoTextStyle},
+    prelude::*,
+    text::{Baseline, Text},
+};
+use embedded_hal::{delay::*, digital::*, spi::SpiDevice};
+
+/// Characters that can be shown by [`Digits`], any other character is shown as a space
+pub const CHARSET: &str = "0123456789 :.-+%";
+
+/// Number of updates after which a full refresh is done by default, one day with an update
+/// every minute
+const DEFAULT_FULL_REFRESH_INTERVAL: u32 = 24 * 60;
+
+/// Error of [`Digits::new`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DigitsError {
+    /// The glyph buffer is smaller than [`glyph_buffer_len`](Digits::glyph_buffer_len)
+    BufferTooSmall,
+}
+
+/// A row of pre-rendered characters updated with quick refreshes
+///
+/// Each character is shown in a cell as large as a character of the font, with its width rounded
+/// up to a multiple of 8 pixels. When the display isn't rotated and the cells start on a multiple
+/// of 8 pixels horizontally, every changed cell is sent to the EPD on its own, directly from the
+/// pre-rendered glyphs. Otherwise the whole buffer is sent.
+pub struct Digits<'a, const N: usize> {
+    glyphs: &'a [u8],
+    cell: Size,
+    position: Point,
+    /// Index in [`CHARSET`] of the shown characters, `None` when unknown
+    shown: [Option<u8>; N],
+    wanted: [u8; N],
+    counter: RefreshCounter,
+}
+
+impl<'a, const N: usize> Digits<'a, N> {
+    /// Size of the buffer needed for the glyphs of a font of the given character size
+    pub const fn glyph_buffer_len(char_width: u32, char_height: u32) -> usize {
+        CHARSET.len() * ((char_width as usize + 7) / 8) * char_height as usize
+    }
+
+    /// Pre-render all the characters of [`CHARSET`] with `font` in `glyphs`
+    ///
+    /// `position` is the top left corner of the first cell. Fails if `glyphs` is smaller than
+    /// [`glyph_buffer_len`](Digits::glyph_buffer_len).
+    pub fn new(
+        font: &MonoFont<'_>,
+        position: Point,
+        glyphs: &'a mut [u8],
+    ) -> Result<Self, DigitsError> {
+        let cell = Size::new(
+            (font.character_size.width + 7) / 8 * 8,
+            font.character_size.height,
+        );
+        let len = Self::glyph_buffer_len(cell.width, cell.height);
+        if glyphs.len() < len {
+            return Err(DigitsError::BufferTooSmall);
+        }
+
+        let glyph_len = len / CHARSET.len();
+        let style = MonoTextStyle::new(font, Color::Black);
+        for (c, glyph) in CHARSET.char_indices().zip(glyphs.chunks_mut(glyph_len)) {
+            glyph.fill(0xff);
+            let mut display = VarDisplay::<Color>::new(cell.width, cell.height, glyph, false)
+                .expect("glyph buffer length");
+            let mut utf8 = [0; 4];
+            let _ = Text::with_baseline(
+                c.1.encode_utf8(&mut utf8),
+                Point::zero(),
+                style,
+                Baseline::Top,
+            )
+            .draw(&mut display);
+        }
+
+        Ok(Digits {
+            glyphs: &glyphs[..len],
+            cell,
+            position,
+            shown: [None; N],
+            wanted: [Self::index(' '); N],
+            counter: RefreshCounter::new(RefreshPolicy {
+                full_every: Some(DEFAULT_FULL_REFRESH_INTERVAL),
+                full_after_s: None,
+            }),
+        })
+    }
+
+    fn index(c: char) -> u8 {
+        CHARSET
+            .chars()
+            .position(|charset| charset == c)
+            .or_else(|| CHARSET.chars().position(|charset| charset == ' '))
+            .unwrap_or_default() as u8
+    }
+
+    fn glyph(&self, index: u8) -> &[u8] {
+        let len = self.glyphs.len() / CHARSET.len();
+        &self.glyphs[index as usize * len..(index as usize + 1) * len]
+    }
+
+    fn cell_position(&self, cell: usize) -> Point {
+        self.position + Point::new((cell as u32 * self.cell.width) as i32, 0)
+    }
+
+    /// Set the text to show at the next flush
+    ///
+    /// Only the first `N` characters are used, shorter texts are padded with spaces.
+    pub fn set(&mut self, text: &str) {
+        let mut chars = text.chars();
+        for wanted in self.wanted.iter_mut() {
+            *wanted = Self::index(chars.next().unwrap_or(' '));
+        }
+    }
+
+    /// Set after how many updates a full refresh is done
+    ///
+    /// With one update a minute, the default of 1440 does a full refresh once a day. 0 means every
+    /// update uses a full refresh.
+    pub fn set_full_refresh_interval(&mut self, interval: u32) {
+        self.counter.set_policy(RefreshPolicy {
+            full_every: Some(interval),
+            full_after_s: None,
+        });
+    }
+
+    /// Force the next flush to do a full refresh, for example at night
+    pub fn request_full_refresh(&mut self) {
+        self.counter.force_full();
+    }
+
+    /// Forget what is shown, the next flush will draw all the cells
+    ///
+    /// This is needed when something else was drawn over the cells.
+    pub fn invalidate(&mut self) {
+        self.shown = [None; N];
+    }
+
+    /// Draw the changed cells on `display`
+    ///
+    /// This is done by [`flush`](Digits::flush), but can be used alone when the EPD is updated
+    /// in some other way. Returns whether any cell changed.
+    pub fn draw<D: DrawTarget<Color = Color>>(&mut self, display: &mut D) -> bool {
+        let mut changed = false;
+        for cell in 0..N {
+            if self.shown[cell] != Some(self.wanted[cell]) {
+                self.draw_cell(display, cell);
+                self.shown[cell] = Some(self.wanted[cell]);
+                changed = true;
+            }
+        }
+        changed
+    }
+
+    fn draw_cell<D: DrawTarget<Color = Color>>(&self, display: &mut D, cell: usize) {
+        let glyph = self.glyph(self.wanted[cell]);
+        let origin = self.cell_position(cell);
+        let line_bytes = self.cell.width as usize / 8;
+        let pixels = (0..self.cell.height).flat_map(move |y| {
+            (0..self.cell.width).map(move |x| {
+                let byte = glyph[y as usize * line_bytes + x as usize / 8];
+                let color = if byte & (0x80 >> (x % 8)) != 0 {
+                    Color::White
+                } else {
+                    Color::Black
+                };
+                Pixel(origin + Point::new(x as i32, y as i32), color)
+            })
+        });
+        let _ = display.draw_iter(pixels);
+    }
+
+    /// Draw the changed cells on `display` and show them on the EPD
+    ///
+    /// Nothing is sent when no cell changed, unless a full refresh is due.
+    pub fn flush<D, SPI, BUSY, DC, RST, DELAY, EPD>(
+        &mut self,
+        display: &mut D,
+        epd: &mut EPD,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>>
+    where
+        D: DisplayBuffer<Color = Color>,
+        SPI: SpiDevice,
+        BUSY: BusyPin,
+        DC: OutputPin,
+        RST: OutputPin,
+        DELAY: DelayNs,
+        EPD: WaveshareDisplay<SPI, BUSY, DC, RST, DELAY>,
+    {
+        let shown = self.shown;
+        let changed = self.draw(display);
+
+        if self.counter.next_lut(None) == RefreshLut::Full {
+            epd.set_lut(spi, delay, Some(RefreshLut::Full))?;
+            epd.update_and_display_frame(spi, display.buffer(), delay)?;
+            self.counter.refreshed(RefreshLut::Full, None);
+            return Ok(());
+        }
+        if !changed {
+            return Ok(());
+        }
+
+        epd.set_lut(spi, delay, Some(RefreshLut::Quick))?;
+        if display.rotation() == DisplayRotation::Rotate0 && self.position.x % 8 == 0 {
+            for (cell, (&shown, &wanted)) in shown.iter().zip(self.wanted.iter()).enumerate() {
+                if shown == Some(wanted) {
+                    continue;
+                }
+                let position = self.cell_position(cell);
+                epd.update_partial_frame(
+                    spi,
+                    delay,
+                    self.glyph(wanted),
+                    position.x as u32,
+                    position.y as u32,
+                    self.cell.width,
+                    self.cell.height,
+                )?;
+            }
+        } else {
+            epd.update_frame(spi, display.buffer(), delay)?;
+        }
+        epd.display_frame(spi, delay)?;
+        self.counter.refreshed(RefreshLut::Quick, None);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graphics::Display;
+    use embedded_graphics::mono_font::ascii::FONT_6X10;
+
+    type TestDisplay = Display<32, 16, false, { 32 * 16 / 8 }, Color>;
+
+    #[test]
+    fn digits_glyphs() {
+        let mut glyphs = [0; Digits::<2>::glyph_buffer_len(8, 10)];
+        let digits = Digits::<2>::new(&FONT_6X10, Point::zero(), &mut glyphs).unwrap();
+        assert_eq!(digits.cell, Size::new(8, 10));
+        // a space is all white, an 8 is not
+        assert!(digits
+            .glyph(Digits::<2>::index(' '))
+            .iter()
+            .all(|&b| b == 0xff));
+        assert!(digits
+            .glyph(Digits::<2>::index('8'))
+            .iter()
+            .any(|&b| b != 0xff));
+        // unknown characters are spaces
+        assert_eq!(Digits::<2>::index('a'), Digits::<2>::index(' '));
+    }
+
+    #[test]
+    fn digits_draw_changed_cells() {
+        let mut glyphs = [0; Digits::<3>::glyph_buffer_len(8, 10)];
+        let mut digits = Digits::<3>::new(&FONT_6X10, Point::new(8, 0), &mut glyphs).unwrap();
+        let mut display = TestDisplay::default();
+
+        digits.set("1");
+        assert!(digits.draw(&mut display));
+        assert!(!digits.draw(&mut display));
+        // the glyph is copied as is in the buffer
+        for y in 0..10 {
+            assert_eq!(display.buffer()[y * 4 + 1], digits.glyph(1)[y]);
+        }
+
+        // only the last cell changes
+        display.clear(Color::Black).unwrap();
+        digits.set("1 7");
+        assert!(digits.draw(&mut display));
+        assert!(display.buffer()[..10 * 4]
+            .chunks(4)
+            .all(|line| line[..3] == [0x00, 0x00, 0x00] && line[3] != 0x00));
+
+        digits.invalidate();
+        assert!(digits.draw(&mut display));
+        assert_eq!(display.buffer()[1], digits.glyph(1)[0]);
+    }
+
+    #[test]
+    fn digits_glyph_buffer_too_small() {
+        let mut glyphs = [0; Digits::<2>::glyph_buffer_len(8, 10) - 1];
+        assert!(matches!(
+            Digits::<2>::new(&FONT_6X10, Point::zero(), &mut glyphs),
+            Err(DigitsError::BufferTooSmall)
+        ));
+    }
+}
@@ -0,0 +1,309 @@
+//! Adapters for HALs still implementing embedded-hal 0.2
+//!
+//! The drivers of this crate use the embedded-hal 1.0 traits. With the `eh02` feature, the
+//! peripherals of a HAL which only implements the 0.2 traits can be wrapped to be used anyway:
+//!
+//! - [`Eh02Spi`] makes an [`SpiDevice`] out of a blocking SPI bus and its chip select pin
+//! - [`Eh02Input`] and [`Eh02Output`] wrap `digital::v2` pins
+//! - [`Eh02Delay`] wraps a `DelayUs<u32>` delay
+//!
+//!```rust, ignore
+//!use epd_waveshare::{eh02::*, epd1in54::*, prelude::*};
+//!
+//!// spi, cs, busy, dc, rst and delay come from an embedded-hal 0.2 HAL
+//!let mut spi = Eh02Spi::new(spi, cs);
+//!let mut delay = Eh02Delay::new(delay);
+//!let mut epd = Epd1in54::new(
+//!    &mut spi,
+//!    Eh02Input::new(busy),
+//!    Eh02Output::new(dc),
+//!    Eh02Output::new(rst),
+//!    &mut delay,
+//!    None,
+//!)?;
+//!```
+
+use core::fmt::Debug;
+use embedded_hal::{
+    delay::DelayNs,
+    digital::{self, ErrorKind as PinErrorKind},
+    spi::{self, ErrorKind as SpiErrorKind, Operation, SpiDevice},
+};
+use embedded_hal_02::{blocking, blocking::delay::DelayUs, digital::v2};
+
+/// Error of an adapted peripheral
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Eh02Error<E> {
+    /// Error of the wrapped peripheral
+    Inner(E),
+    /// Error setting the chip select pin of an SPI device
+    ChipSelect,
+}
+
+impl<E: Debug> spi::Error for Eh02Error<E> {
+    fn kind(&self) -> SpiErrorKind {
+        match self {
+            Eh02Error::Inner(_) => SpiErrorKind::Other,
+            Eh02Error::ChipSelect => SpiErrorKind::ChipSelectFault,
+        }
+    }
+}
+
+impl<E: Debug> digital::Error for Eh02Error<E> {
+    fn kind(&self) -> PinErrorKind {
+        PinErrorKind::Other
+    }
+}
+
+/// SPI device made of an embedded-hal 0.2 blocking SPI bus and a chip select pin
+///
+/// The chip select pin is active low, it is set low during each transaction. Delays inside
+/// transactions aren't supported and are skipped, none of the drivers use them.
+pub struct Eh02Spi<SPI, CS> {
+    spi: SPI,
+    cs: CS,
+}
+
+impl<SPI, CS> Eh02Spi<SPI, CS> {
+    /// Wrap an SPI bus and the chip select pin of the display
+    pub fn new(spi: SPI, cs: CS) -> Self {
+        Eh02Spi { spi, cs }
+    }
+
+    /// Get back the SPI bus and the chip select pin
+    pub fn release(self) -> (SPI, CS) {
+        (self.spi, self.cs)
+    }
+}
+
+impl<SPI, CS, E> spi::ErrorType for Eh02Spi<SPI, CS>
+where
+    SPI: blocking::spi::Write<u8, Error = E> + blocking::spi::Transfer<u8, Error = E>,
+    CS: v2::OutputPin,
+    E: Debug,
+{
+    type Error = Eh02Error<E>;
+}
+
+impl<SPI, CS, E> SpiDevice for Eh02Spi<SPI, CS>
+where
+    SPI: blocking::spi::Write<u8, Error = E> + blocking::spi::Transfer<u8, Error = E>,
+    CS: v2::OutputPin,
+    E: Debug,
+{
+    fn transaction(&mut self, operations: &mut [Operation<'_, u8>]) -> Result<(), Self::Error> {
+        self.cs.set_low().map_err(|_| Eh02Error::ChipSelect)?;
+        let result = operations
+            .iter_mut()
+            .try_for_each(|operation| match operation {
+                Operation::Write(words) => self.spi.write(words).map(|_| ()),
+                Operation::Read(words) => {
+                    words.fill(0);
+                    self.spi.transfer(words).map(|_| ())
+                }
+                Operation::Transfer(read, write) => {
+                    // 0.2 only has in place transfers, exchange the common part then pad
+                    let len = read.len().min(write.len());
+                    read[..len].copy_from_slice(&write[..len]);
+                    read[len..].fill(0);
+                    self.spi.transfer(read)?;
+                    write.get(len..).map_or(Ok(()), |rest| self.spi.write(rest))
+                }
+                Operation::TransferInPlace(words) => self.spi.transfer(words).map(|_| ()),
+                Operation::DelayNs(_) => Ok(()),
+            });
+        // release the chip select even after an error
+        let cs = self.cs.set_high().map_err(|_| Eh02Error::ChipSelect);
+        result.map_err(Eh02Error::Inner)?;
+        cs
+    }
+}
+
+/// Input pin implementing embedded-hal 0.2 `digital::v2::InputPin`
+pub struct Eh02Input<PIN>(PIN);
+
+impl<PIN> Eh02Input<PIN> {
+    /// Wrap an input pin
+    pub fn new(pin: PIN) -> Self {
+        Eh02Input(pin)
+    }
+
+    /// Get back the input pin
+    pub fn release(self) -> PIN {
+        self.0
+    }
+}
+
+impl<PIN> digital::ErrorType for Eh02Input<PIN>
+where
+    PIN: v2::InputPin,
+    PIN::Error: Debug,
+{
+    type Error = Eh02Error<PIN::Error>;
+}
+
+impl<PIN> digital::InputPin for Eh02Input<PIN>
+where
+    PIN: v2::InputPin,
+    PIN::Error: Debug,
+{
+    fn is_high(&mut self) -> Result<bool, Self::Error> {
+        self.0.is_high().map_err(Eh02Error::Inner)
+    }
+
+    fn is_low(&mut self) -> Result<bool, Self::Error> {
+        self.0.is_low().map_err(Eh02Error::Inner)
+    }
+}
+
+/// Output pin implementing embedded-hal 0.2 `digital::v2::OutputPin`
+pub struct Eh02Output<PIN>(PIN);
+
+impl<PIN> Eh02Output<PIN> {
+    /// Wrap an output pin
+    pub fn new(pin: PIN) -> Self {
+        Eh02Output(pin)
+    }
+
+    /// Get back the output pin
+    pub fn release(self) -> PIN {
+        self.0
+    }
+}
+
+impl<PIN> digital::ErrorType for Eh02Output<PIN>
+where
+    PIN: v2::OutputPin,
+    PIN::Error: Debug,
+{
+    type Error = Eh02Error<PIN::Error>;
+}
+
+impl<PIN> digital::OutputPin for Eh02Output<PIN>
+where
+    PIN: v2::OutputPin,
+    PIN::Error: Debug,
+{
+    fn set_low(&mut self) -> Result<(), Self::Error> {
+        self.0.set_low().map_err(Eh02Error::Inner)
+    }
+
+    fn set_high(&mut self) -> Result<(), Self::Error> {
+        self.0.set_high().map_err(Eh02Error::Inner)
+    }
+}
+
+/// Delay implementing embedded-hal 0.2 `DelayUs<u32>`
+///
+/// Delays are rounded up to whole microseconds.
+pub struct Eh02Delay<DELAY>(DELAY);
+
+impl<DELAY> Eh02Delay<DELAY> {
+    /// Wrap a delay
+    pub fn new(delay: DELAY) -> Self {
+        Eh02Delay(delay)
+    }
+
+    /// Get back the delay
+    pub fn release(self) -> DELAY {
+        self.0
+    }
+}
+
+impl<DELAY: DelayUs<u32>> DelayNs for Eh02Delay<DELAY> {
+    fn delay_ns(&mut self, ns: u32) {
+        self.0.delay_us((ns + 999) / 1000);
+    }
+
+    fn delay_us(&mut self, us: u32) {
+        self.0.delay_us(us);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use embedded_hal::digital::{InputPin, OutputPin};
+
+    #[derive(Default)]
+    struct Bus {
+        written: [u8; 8],
+        len: usize,
+    }
+
+    impl blocking::spi::Write<u8> for Bus {
+        type Error = ();
+        fn write(&mut self, words: &[u8]) -> Result<(), ()> {
+            self.written[self.len..self.len + words.len()].copy_from_slice(words);
+            self.len += words.len();
+            Ok(())
+        }
+    }
+
+    impl blocking::spi::Transfer<u8> for Bus {
+        type Error = ();
+        fn transfer<'w>(&mut self, words: &'w mut [u8]) -> Result<&'w [u8], ()> {
+            for word in words.iter_mut() {
+                *word = !*word;
+            }
+            Ok(words)
+        }
+    }
+
+    #[derive(Default)]
+    struct Pin {
+        high: bool,
+        changes: u32,
+    }
+
+    impl v2::OutputPin for Pin {
+        type Error = ();
+        fn set_low(&mut self) -> Result<(), ()> {
+            self.high = false;
+            self.changes += 1;
+            Ok(())
+        }
+        fn set_high(&mut self) -> Result<(), ()> {
+            self.high = true;
+            self.changes += 1;
+            Ok(())
+        }
+    }
+
+    impl v2::InputPin for Pin {
+        type Error = ();
+        fn is_high(&self) -> Result<bool, ()> {
+            Ok(self.high)
+        }
+        fn is_low(&self) -> Result<bool, ()> {
+            Ok(!self.high)
+        }
+    }
+
+    #[test]
+    fn eh02_spi() {
+        let mut spi = Eh02Spi::new(Bus::default(), Pin::default());
+        let mut read = [0; 2];
+        let mut transfer = [0; 3];
+        spi.transaction(&mut [
+            Operation::Write(&[1, 2]),
+            Operation::Read(&mut read),
+            Operation::Transfer(&mut transfer, &[4, 5]),
+        ])
+        .unwrap();
+        let (bus, cs) = spi.release();
+        assert_eq!(&bus.written[..bus.len], [1, 2]);
+        assert_eq!(read, [0xff, 0xff]);
+        assert_eq!(transfer, [!4, !5, 0xff]);
+        assert!(cs.high);
+        assert_eq!(cs.changes, 2);
+    }
+
+    #[test]
+    fn eh02_pins() {
+        let mut output = Eh02Output::new(Pin::default());
+        output.set_high().unwrap();
+        let mut input = Eh02Input::new(output.release());
+        assert!(input.is_high().unwrap());
+    }
+}
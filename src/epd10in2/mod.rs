@@ -0,0 +1,137 @@
+//! A Driver for the Waveshare 10.2" E-Ink Display via SPI
+//!
+//! The 960x640 black and white panel has a SSD1677 controller. Its waveforms are the ones of the
+//! controller OTP: the full refresh and a fast full refresh selected with
+//! [`RefreshLut::Fast`](crate::traits::RefreshLut::Fast). A part of the frame can be written in a
+//! window of the RAM with
+//! [`update_partial_frame`](crate::traits::WaveshareDisplay::update_partial_frame).
+//!
+//! The full frame takes 76.8KB: on microcontrollers, it can be rendered and sent band by band
+//! with [`update_frame_banded`](crate::controller::ssd16xx::Ssd16xx::update_frame_banded),
+//! drawing each band in a `VarDisplay` of a few lines instead of a [`Display10in2`].
+//!
+//! The driver is the one of the SSD16xx controllers, see [`crate::controller::ssd16xx`].
+//!
+//!```rust, no_run
+//!# use embedded_hal_mock::eh1::*;
+//!# fn main() -> Result<(), epd_waveshare::error::EpdError<embedded_hal::spi::ErrorKind>> {
+//!use embedded_graphics::{prelude::*, primitives::{Circle, PrimitiveStyle}};
+//!use epd_waveshare::{epd10in2::*, graphics::VarDisplay, prelude::*};
+//!#
+//!# let expectations = [];
+//!# let mut spi = spi::Mock::new(&expectations);
+//!# let expectations = [];
+//!# let busy_in = digital::Mock::new(&expectations);
+//!# let dc = digital::Mock::new(&expectations);
+//!# let rst = digital::Mock::new(&expectations);
+//!# let mut delay = delay::NoopDelay::new();
+//!
+//!let mut epd = Epd10in2::new(&mut spi, busy_in, dc, rst, &mut delay, None)?;
+//!
+//!// a circle drawn 32 lines at a time, in 3.75KB instead of 76.8KB
+//!let mut band = [0; 32 * LINE_BYTES];
+//!epd.update_frame_banded(&mut spi, &mut delay, &mut band, |area, pixels| {
+//!    let mut display = VarDisplay::<Color>::new(WIDTH, area.h, pixels, false).unwrap();
+//!    let _ = display.clear(Color::White);
+//!    let _ = Circle::new(Point::new(160, 0), 640)
+//!        .into_styled(PrimitiveStyle::with_stroke(Color::Black, 8))
+//!        .draw(&mut display.translated(Point::new(0, -(area.y as i32))));
+//!})?;
+//!epd.display_frame(&mut spi, &mut delay)?;
+//!
+//!// the following full refreshes are faster
+//!epd.set_lut(&mut spi, &mut delay, Some(RefreshLut::Fast))?;
+//!epd.clear_frame(&mut spi, &mut delay)?;
+//!epd.display_frame(&mut spi, &mut delay)?;
+//!
+//!epd.sleep(&mut spi, &mut delay)?;
+//!# Ok(())
+//!# }
+//!```
+
+use crate::color::Color;
+use crate::controller::ssd16xx::{Addressing, Panel, PanelConfig, Ssd16xx, Voltages};
+
+/// Width of the display in pixels
+pub const WIDTH: u32 = 960;
+/// Height of the display in pixels
+pub const HEIGHT: u32 = 640;
+/// Number of bytes of a line of pixels
+pub const LINE_BYTES: usize = WIDTH as usize / 8;
+/// Default Background Color (white)
+pub const DEFAULT_BACKGROUND_COLOR: Color = Color::White;
+
+/// Full size buffer for use with the 10in2 EPD, only for hosts with enough memory
+///
+/// On MCUs, a `VarDisplay<Color>` of a few lines can be used to render the bands of
+/// [`update_frame_banded`](crate::controller::ssd16xx::Ssd16xx::update_frame_banded) instead.
+#[cfg(feature = "graphics")]
+pub type Display10in2 = crate::graphics::Display<
+    WIDTH,
+    HEIGHT,
+    false,
+    { crate::graphics::display_len::<Color>(WIDTH, HEIGHT) },
+    Color,
+>;
+
+/// Settings of the 10in2 panel
+pub struct Epd10in2Panel;
+
+impl Panel for Epd10in2Panel {
+    const CONFIG: PanelConfig = PanelConfig {
+        addressing: Addressing::Pixels,
+        reset_us: [100_000, 2_000],
+        border_waveform: Some(0x01),
+        voltages: Voltages {
+            booster: &[0xAE, 0xC7, 0xC3, 0xC0, 0x80],
+            ..Voltages::DEFAULT
+        },
+        // 90°C
+        fast_temperature: Some(0x5A),
+        ..PanelConfig::new(WIDTH, HEIGHT)
+    };
+}
+
+/// Epd10in2 driver
+pub type Epd10in2<SPI, BUSY, DC, RST, DELAY> = Ssd16xx<Epd10in2Panel, SPI, BUSY, DC, RST, DELAY>;
+
+/// Epd10in2 driver for the async SPI and delay traits
+#[cfg(feature = "async")]
+pub type Epd10in2Async<SPI, BUSY, DC, RST, DELAY> =
+    crate::controller::ssd16xx::Ssd16xxAsync<Epd10in2Panel, SPI, BUSY, DC, RST, DELAY>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn epd_size() {
+        assert_eq!(WIDTH, 960);
+        assert_eq!(HEIGHT, 640);
+        assert_eq!(LINE_BYTES * HEIGHT as usize, 76_800);
+        assert_eq!(DEFAULT_BACKGROUND_COLOR, Color::White);
+    }
+
+    #[test]
+    fn panel_config() {
+        let config = Epd10in2Panel::CONFIG;
+        assert_eq!((config.width, config.height), (WIDTH, HEIGHT));
+        assert_eq!(config.addressing, Addressing::Pixels);
+        assert_eq!(config.fast_temperature, Some(0x5A));
+        assert_eq!(config.luts, None);
+    }
+
+    #[cfg(feature = "graphics")]
+    #[test]
+    fn band_display() {
+        use crate::graphics::VarDisplay;
+        use embedded_graphics_core::{prelude::*, Pixel};
+
+        let mut band = [0xff; LINE_BYTES * 2];
+        let mut display = VarDisplay::<Color>::new(WIDTH, 2, &mut band, false).unwrap();
+        display.set_pixel(Pixel(Point::new(1, 0), Color::Black));
+        display.set_pixel(Pixel(Point::new(WIDTH as i32 - 1, 1), Color::Black));
+        assert_eq!(display.buffer()[0], 0xbf);
+        assert_eq!(display.buffer()[2 * LINE_BYTES - 1], 0xfe);
+    }
+}
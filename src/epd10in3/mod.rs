@@ -0,0 +1,323 @@
+//! A driver for the Waveshare 10.3" e-Paper HAT (1872x1404, 16 gray levels)
+//!
+//! The panel is driven by an [IT8951](crate::it8951) controller which keeps the frame in its own
+//! memory. The full frame needs 1.3MiB at 4 bits per pixel, so it can be rendered and sent band
+//! by band with [`update_frame_banded`](Epd10in3::update_frame_banded) from a buffer of a few
+//! lines.
+//!
+//! Pixels are 4 bits gray levels, `0x0` for black up to `0xf` for white, the first pixel of a
+//! byte in its upper bits. Images with 8 bits gray levels can be converted to this format with
+//! [`pack_4bpp`](crate::it8951::pack_4bpp).
+//!
+//! Sending a full frame takes a while, the SPI device should be configured with a clock as high as
+//! the wiring allows, up to [`MAX_SPI_FREQUENCY`](crate::it8951::MAX_SPI_FREQUENCY).
+//!
+//! # References
+//!
+//! - [Wiki](https://www.waveshare.com/wiki/10.3inch_e-Paper_HAT)
+//! - [Waveshare C driver](https://github.com/waveshare/IT8951-ePaper)
+//!
+//!```rust, no_run
+//!# use embedded_hal_mock::eh1::*;
+//!# fn main() -> Result<(), epd_waveshare::error::EpdError<embedded_hal::spi::ErrorKind>> {
+//!use epd_waveshare::epd10in3::*;
+//!#
+//!# let expectations = [];
+//!# let mut spi = spi::Mock::new(&expectations);
+//!# let expectations = [];
+//!# let busy_in = digital::Mock::new(&expectations);
+//!# let rst = digital::Mock::new(&expectations);
+//!# let mut delay = delay::NoopDelay::new();
+//!
+//!// the VCOM voltage is printed on the ribbon cable of the panel
+//!let mut epd = Epd10in3::new(&mut spi, busy_in, rst, &mut delay, 1500, None)?;
+//!
+//!// 16 lines at a time: a horizontal gradient
+//!let mut band = [0; 16 * LINE_BYTES];
+//!epd.update_frame_banded(&mut spi, &mut delay, &mut band, |_area, pixels| {
+//!    for line in pixels.chunks_mut(LINE_BYTES) {
+//!        for (x, byte) in line.iter_mut().enumerate() {
+//!            let gray = (x * 16 / LINE_BYTES) as u8;
+//!            *byte = gray << 4 | gray;
+//!        }
+//!    }
+//!})?;
+//!epd.display_frame(&mut spi, &mut delay)?;
+//!
+//!// fast black and white updates
+//!epd.set_display_mode(DisplayMode::A2);
+//!# Ok(())
+//!# }
+//!```
+
+use crate::color::Gray16;
+use crate::error::EpdError;
+#[cfg(feature = "async")]
+use crate::it8951::It8951Async;
+use crate::it8951::{It8951, PixelFormat, WaveformMode};
+use crate::rect::Rect;
+use embedded_hal::{delay::*, digital::*, spi::SpiDevice};
+#[cfg(feature = "async")]
+use embedded_hal_async::{delay::DelayNs as AsyncDelayNs, spi::SpiDevice as AsyncSpiDevice};
+
+/// Width of the display
+pub const WIDTH: u32 = 1872;
+/// Height of the display
+pub const HEIGHT: u32 = 1404;
+/// Number of bytes of a line of pixels
+pub const LINE_BYTES: usize = WIDTH as usize / 2;
+/// Default gray level, white
+pub const DEFAULT_BACKGROUND_GRAY: u8 = 0xf;
+/// Default Background Color
+pub const DEFAULT_BACKGROUND_COLOR: Gray16 = Gray16::WHITE;
+
+/// Full size buffer for use with the 10in3 EPD, only for hosts with enough memory
+///
+/// On MCUs, a `VarDisplay<Gray16>` of a few lines can be used to render the bands of
+/// [`update_frame_banded`](Epd10in3::update_frame_banded) instead.
+#[cfg(feature = "graphics")]
+pub type Display10in3 = crate::graphics::Display<
+    WIDTH,
+    HEIGHT,
+    false,
+    { crate::graphics::display_len::<Gray16>(WIDTH, HEIGHT) },
+    Gray16,
+>;
+
+const FULL_RECT: Rect = Rect::new(0, 0, WIDTH, HEIGHT);
+
+/// Refresh mode used by [`display_frame`](Epd10in3::display_frame)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DisplayMode {
+    /// Clears the panel to white with a lot of flashing, whatever the frame memory holds
+    Init,
+    /// 16 gray levels with flashing, the best quality
+    #[default]
+    Gc16,
+    /// Fast black and white refresh without flashing, the frame must only contain black
+    /// (`0x0`) and white (`0xf`) pixels
+    A2,
+}
+
+impl From<DisplayMode> for WaveformMode {
+    fn from(mode: DisplayMode) -> Self {
+        match mode {
+            DisplayMode::Init => WaveformMode::Init,
+            DisplayMode::Gc16 => WaveformMode::Gc16,
+            DisplayMode::A2 => WaveformMode::A2,
+        }
+    }
+}
+
+#[maybe_async_cfg::maybe(
+    sync(keep_self),
+    async(
+        feature = "async",
+        idents(
+            Epd10in3,
+            It8951,
+            SpiDevice(async = "AsyncSpiDevice"),
+            DelayNs(async = "AsyncDelayNs")
+        )
+    )
+)]
+/// Epd10in3 driver
+pub struct Epd10in3<SPI, BUSY, RST, DELAY> {
+    it8951: It8951<SPI, BUSY, RST, DELAY>,
+    mode: DisplayMode,
+}
+
+#[maybe_async_cfg::maybe(
+    sync(keep_self),
+    async(
+        feature = "async",
+        idents(
+            Epd10in3,
+            It8951,
+            SpiDevice(async = "AsyncSpiDevice"),
+            DelayNs(async = "AsyncDelayNs")
+        )
+    )
+)]
+impl<SPI, BUSY, RST, DELAY> Epd10in3<SPI, BUSY, RST, DELAY>
+where
+    SPI: SpiDevice,
+    BUSY: InputPin,
+    RST: OutputPin,
+    DELAY: DelayNs,
+{
+    /// Initializes the controller and sets the VCOM voltage of the panel, in mV (1500 for
+    /// -1.50V)
+    pub async fn new(
+        spi: &mut SPI,
+        busy: BUSY,
+        rst: RST,
+        delay: &mut DELAY,
+        vcom: u16,
+        delay_us: Option<u32>,
+    ) -> Result<Self, EpdError<SPI::Error>> {
+        let mut it8951 = It8951::new(spi, busy, rst, delay, delay_us).await?;
+        it8951.set_vcom(spi, delay, vcom).await?;
+        Ok(Epd10in3 {
+            it8951,
+            mode: DisplayMode::default(),
+        })
+    }
+
+    /// Sets the refresh mode used from now on
+    pub fn set_display_mode(&mut self, mode: DisplayMode) {
+        self.mode = mode;
+    }
+
+    /// Current refresh mode
+    pub fn display_mode(&self) -> DisplayMode {
+        self.mode
+    }
+
+    /// The underlying controller, for the features not covered by this driver
+    pub fn controller(&mut self) -> &mut It8951<SPI, BUSY, RST, DELAY> {
+        &mut self.it8951
+    }
+
+    /// Wakes the controller up from sleep
+    pub async fn wake_up(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.it8951.wake_up(spi, delay).await
+    }
+
+    /// Puts the controller to sleep, the frame memory is kept
+    pub async fn sleep(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.it8951.wait_display_ready(spi, delay).await?;
+        self.it8951.sleep(spi, delay).await
+    }
+
+    /// Sends a whole frame of `HEIGHT` x `LINE_BYTES` bytes
+    pub async fn update_frame(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+        buffer: &[u8],
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.update_partial_frame(spi, delay, buffer, 0, 0, WIDTH, HEIGHT)
+            .await
+    }
+
+    /// Sends the frame band by band, rendered in `band` by `render`
+    ///
+    /// `band` holds as many lines of `LINE_BYTES` bytes as possible, at least one. For each band,
+    /// `render` is called with the area of the frame it covers and the pixels to fill.
+    pub async fn update_frame_banded<F>(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+        band: &mut [u8],
+        render: F,
+    ) -> Result<(), EpdError<SPI::Error>>
+    where
+        F: FnMut(Rect, &mut [u8]),
+    {
+        self.it8951
+            .load_image_banded(spi, delay, PixelFormat::Bpp4, FULL_RECT, band, render)
+            .await
+    }
+
+    /// Sends the pixels of an area of the frame
+    ///
+    /// `x` and `width` must be multiples of 4 so that every line is a whole number of 16 bits
+    /// words.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn update_partial_frame(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+        buffer: &[u8],
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        let area = Rect::new(x, y, width, height);
+        self.it8951
+            .load_image_area(spi, delay, PixelFormat::Bpp4, area, buffer)
+            .await
+    }
+
+    /// Sends a whole frame and refreshes the panel with the current [`DisplayMode`], GC16 (16
+    /// gray levels) by default
+    pub async fn update_and_display_frame(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+        buffer: &[u8],
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.update_frame(spi, delay, buffer).await?;
+        self.display_frame(spi, delay).await
+    }
+
+    /// Refreshes the whole panel with the current [`DisplayMode`]
+    pub async fn display_frame(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.display_partial_frame(spi, delay, FULL_RECT).await
+    }
+
+    /// Refreshes an area of the panel with the current [`DisplayMode`]
+    pub async fn display_partial_frame(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+        area: Rect,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.it8951
+            .display_area(spi, delay, area, self.mode.into())
+            .await
+    }
+
+    /// Clears the frame memory to white and the panel with the INIT waveform
+    pub async fn clear_frame(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        let white = [DEFAULT_BACKGROUND_GRAY << 4 | DEFAULT_BACKGROUND_GRAY; LINE_BYTES];
+        self.it8951
+            .load_image_start(spi, delay, PixelFormat::Bpp4, FULL_RECT)
+            .await?;
+        for _ in 0..HEIGHT {
+            self.it8951.load_image_data(spi, delay, &white).await?;
+        }
+        self.it8951.load_image_end(spi, delay).await?;
+        self.it8951
+            .display_area(spi, delay, FULL_RECT, WaveformMode::Init)
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn epd_size() {
+        assert_eq!(DEFAULT_BACKGROUND_COLOR.luma(), DEFAULT_BACKGROUND_GRAY);
+        assert_eq!(WIDTH, 1872);
+        assert_eq!(HEIGHT, 1404);
+        assert_eq!(LINE_BYTES * HEIGHT as usize, 1_314_144);
+    }
+
+    #[test]
+    fn display_mode() {
+        assert_eq!(DisplayMode::default(), DisplayMode::Gc16);
+        assert_eq!(WaveformMode::from(DisplayMode::A2), WaveformMode::A2);
+        assert_eq!(WaveformMode::from(DisplayMode::Init), WaveformMode::Init);
+    }
+}
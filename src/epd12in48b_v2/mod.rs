@@ -17,10 +17,25 @@ use embedded_hal::{
     spi::SpiBus,
 };
 
+use crate::error::EpdError;
 pub use crate::rect::Rect;
 use command::Command;
 pub use config::*;
-
+#[cfg(feature = "async")]
+use embedded_hal_async::{delay::DelayNs as AsyncDelayNs, spi::SpiBus as AsyncSpiBus};
+
+#[maybe_async_cfg::maybe(
+    sync(keep_self),
+    async(
+        feature = "async",
+        idents(
+            Peripherals,
+            EpdDriver,
+            SpiBus(async = "AsyncSpiBus"),
+            DelayNs(async = "AsyncDelayNs")
+        )
+    )
+)]
 /// A collection of peripherals controlling the EPD
 ///
 /// The display is composed of 4 sub-displays arranged like so:
@@ -35,6 +50,9 @@ pub use config::*;
 /// Resolution of `S2` and `M1` is 648 x 492,
 /// resolution of `S1` and `M2` is 656 x 492.
 ///
+/// The sub-displays are cascaded controllers with their own CS and BUSY lines: the driver
+/// sends each of them its quarter of the frame, so the frames given to it cover the whole panel.
+///
 pub struct Peripherals<INPUT, OUTPUT, SPI>
 where
     INPUT: InputPin,
@@ -120,6 +138,21 @@ const CS_S2: CS = 0b1000;
 const CS_ALL: CS = CS_M1 | CS_S1 | CS_M2 | CS_S2;
 const CS_DATA: CS = 0b10000;
 
+/// Delay between the polls of the BUSY lines
+const BUSY_POLL_MS: u32 = 200;
+
+#[maybe_async_cfg::maybe(
+    sync(keep_self),
+    async(
+        feature = "async",
+        idents(
+            Peripherals,
+            EpdDriver,
+            SpiBus(async = "AsyncSpiBus"),
+            DelayNs(async = "AsyncDelayNs")
+        )
+    )
+)]
 /// Waveshare 12.48"(B)
 pub struct EpdDriver<INPUT, OUTPUT, SPI, DELAY>
 where
@@ -131,16 +164,27 @@ where
     peris: Peripherals<INPUT, OUTPUT, SPI>,
     delay: DELAY,
     control_state: CS,
+    /// Longest wait for the sub-displays in us, forever if `None`
+    busy_timeout_us: Option<u32>,
 }
 
+#[maybe_async_cfg::maybe(
+    sync(keep_self),
+    async(
+        feature = "async",
+        idents(
+            Peripherals,
+            EpdDriver,
+            SpiBus(async = "AsyncSpiBus"),
+            DelayNs(async = "AsyncDelayNs")
+        )
+    )
+)]
 impl<INPUT, OUTPUT, SPI, DELAY> EpdDriver<INPUT, OUTPUT, SPI, DELAY>
 where
     INPUT: InputPin,
-    INPUT::Error: core::fmt::Debug,
     OUTPUT: OutputPin,
-    OUTPUT::Error: core::fmt::Debug,
     SPI: SpiBus<u8>,
-    SPI::Error: core::fmt::Debug,
     DELAY: DelayNs,
 {
     /// Constructs a new instance of the EpdDriver.  
@@ -151,6 +195,7 @@ where
             peris,
             delay,
             control_state: 0,
+            busy_timeout_us: None,
         }
     }
 
@@ -159,38 +204,47 @@ where
         self.peris
     }
 
+    /// Sets the longest wait for the sub-displays, `None` waiting forever (the default)
+    ///
+    /// The waits for the sub-displays, e.g. in [`refresh_display`](EpdDriver::refresh_display),
+    /// return [`EpdError::BusyTimeout`] after `timeout_us`.
+    pub fn set_busy_timeout(&mut self, timeout_us: Option<u32>) {
+        self.busy_timeout_us = timeout_us;
+    }
+
     /// Reset the display, potentially waking it up from deep sleep.
     /// Normally should be followed by a call to [`init()`](EpdDriver::init).
-    pub fn reset(&mut self) -> Result<(), OUTPUT::Error> {
-        drop(self.peris.m1_cs.set_high());
-        drop(self.peris.s1_cs.set_high());
-        drop(self.peris.m2_cs.set_high());
-        drop(self.peris.s2_cs.set_high());
-        drop(self.peris.m1s1_dc.set_low());
-        drop(self.peris.m2s2_dc.set_low());
+    pub async fn reset(&mut self) -> Result<(), EpdError<SPI::Error>> {
+        self.peris.m1_cs.set_high().map_err(EpdError::pin)?;
+        self.peris.s1_cs.set_high().map_err(EpdError::pin)?;
+        self.peris.m2_cs.set_high().map_err(EpdError::pin)?;
+        self.peris.s2_cs.set_high().map_err(EpdError::pin)?;
+        self.peris.m1s1_dc.set_low().map_err(EpdError::pin)?;
+        self.peris.m2s2_dc.set_low().map_err(EpdError::pin)?;
         self.control_state = 0;
 
-        self.peris.m1s1_rst.set_high()?;
-        self.peris.m2s2_rst.set_high()?;
-        self.delay.delay_ms(1);
+        self.peris.m1s1_rst.set_high().map_err(EpdError::pin)?;
+        self.peris.m2s2_rst.set_high().map_err(EpdError::pin)?;
+        self.delay.delay_ms(1).await;
 
-        self.peris.m1s1_rst.set_low()?;
-        self.delay.delay_us(100); // min RST low = 50us
-        self.peris.m1s1_rst.set_high()?;
-        self.delay.delay_ms(100); // min wait after RST = 10ms
+        self.peris.m1s1_rst.set_low().map_err(EpdError::pin)?;
+        self.delay.delay_us(100).await; // min RST low = 50us
+        self.peris.m1s1_rst.set_high().map_err(EpdError::pin)?;
+        self.delay.delay_ms(100).await; // min wait after RST = 10ms
 
-        self.peris.m2s2_rst.set_low()?;
-        self.delay.delay_us(100);
-        self.peris.m2s2_rst.set_high()?;
-        self.delay.delay_ms(100);
+        self.peris.m2s2_rst.set_low().map_err(EpdError::pin)?;
+        self.delay.delay_us(100).await;
+        self.peris.m2s2_rst.set_high().map_err(EpdError::pin)?;
+        self.delay.delay_ms(100).await;
 
         Ok(())
     }
 
     /// Initialize display registers.
-    pub fn init(&mut self, config: &Config) -> Result<(), SPI::Error> {
+    pub async fn init(&mut self, config: &Config) -> Result<(), EpdError<SPI::Error>> {
         // booster soft start
-        self.cmd_with_data(CS_ALL, Command::BoosterSoftStart, &[0x17, 0x17, 0x39, 0x17])?;
+        self.cmd_with_data(CS_ALL, Command::BoosterSoftStart, &[0x17, 0x17, 0x39, 0x17])
+            .await?;
 
         // resolution setting
         fn resolution_data(rect: Rect) -> [u8; 4] {
@@ -201,24 +255,33 @@ where
                 (rect.h % 256) as u8,
             ]
         }
-        self.cmd_with_data(CS_M1, Command::TconResolution, &resolution_data(M1_RECT))?;
-        self.cmd_with_data(CS_S1, Command::TconResolution, &resolution_data(S1_RECT))?;
-        self.cmd_with_data(CS_M2, Command::TconResolution, &resolution_data(M2_RECT))?;
-        self.cmd_with_data(CS_S2, Command::TconResolution, &resolution_data(S2_RECT))?;
-
-        self.cmd_with_data(CS_ALL, Command::DualSPI, &[0x20])?;
-        self.cmd_with_data(CS_ALL, Command::TconSetting, &[0x22])?;
-        self.cmd_with_data(CS_ALL, Command::PowerSaving, &[0x00])?;
-        self.cmd_with_data(CS_ALL, Command::CascadeSetting, &[0x03])?;
-        self.cmd_with_data(CS_ALL, Command::ForceTemperature, &[25])?;
-
-        self.set_mode(config)?;
-
-        self.flush()
+        self.cmd_with_data(CS_M1, Command::TconResolution, &resolution_data(M1_RECT))
+            .await?;
+        self.cmd_with_data(CS_S1, Command::TconResolution, &resolution_data(S1_RECT))
+            .await?;
+        self.cmd_with_data(CS_M2, Command::TconResolution, &resolution_data(M2_RECT))
+            .await?;
+        self.cmd_with_data(CS_S2, Command::TconResolution, &resolution_data(S2_RECT))
+            .await?;
+
+        self.cmd_with_data(CS_ALL, Command::DualSPI, &[0x20])
+            .await?;
+        self.cmd_with_data(CS_ALL, Command::TconSetting, &[0x22])
+            .await?;
+        self.cmd_with_data(CS_ALL, Command::PowerSaving, &[0x00])
+            .await?;
+        self.cmd_with_data(CS_ALL, Command::CascadeSetting, &[0x03])
+            .await?;
+        self.cmd_with_data(CS_ALL, Command::ForceTemperature, &[25])
+            .await?;
+
+        self.set_mode(config).await?;
+
+        self.flush().await
     }
 
     /// Set data "polarity", waveform lookup table mode, etc, without re-initializing anything else.
-    pub fn set_mode(&mut self, config: &Config) -> Result<(), SPI::Error> {
+    pub async fn set_mode(&mut self, config: &Config) -> Result<(), EpdError<SPI::Error>> {
         let ddx = match (config.inverted_r, config.inverted_kw) {
             (false, true) => 0b00,
             (false, false) => 0b01,
@@ -238,19 +301,24 @@ where
         };
 
         let reg = (config.external_lut as u8) << 5;
-        self.cmd_with_data(CS_M1, Command::PanelSetting, &[reg | 0x0F])?;
-        self.cmd_with_data(CS_S1, Command::PanelSetting, &[reg | 0x0F])?;
-        self.cmd_with_data(CS_M2, Command::PanelSetting, &[reg | 0x03])?;
-        self.cmd_with_data(CS_S2, Command::PanelSetting, &[reg | 0x03])?;
+        self.cmd_with_data(CS_M1, Command::PanelSetting, &[reg | 0x0F])
+            .await?;
+        self.cmd_with_data(CS_S1, Command::PanelSetting, &[reg | 0x0F])
+            .await?;
+        self.cmd_with_data(CS_M2, Command::PanelSetting, &[reg | 0x03])
+            .await?;
+        self.cmd_with_data(CS_S2, Command::PanelSetting, &[reg | 0x03])
+            .await?;
 
         let bdv = bdv << 4;
         self.cmd_with_data(
             CS_ALL,
             Command::VcomAndDataIntervalSetting,
             &[bdv | ddx, 0x07],
-        )?;
+        )
+        .await?;
 
-        self.flush()
+        self.flush().await
     }
 
     /// Fill data1 buffer with pixels:
@@ -259,30 +327,42 @@ where
     ///
     /// `pixels` may contain a lesser number of rows than the window being written,
     /// in which case it will be treated as circular.
-    pub fn write_data1(&mut self, pixels: &[u8]) -> Result<(), SPI::Error> {
-        self.write_window_data(Command::DataStartTransmission1, FULL_RECT, pixels)?;
-        self.flush()
+    pub async fn write_data1(&mut self, pixels: &[u8]) -> Result<(), EpdError<SPI::Error>> {
+        self.write_window_data(Command::DataStartTransmission1, FULL_RECT, pixels)
+            .await?;
+        self.flush().await
     }
 
     /// Fill data2 buffer with pixels.
     /// See also [`write_data1`](EpdDriver::write_data1).
-    pub fn write_data2(&mut self, pixels: &[u8]) -> Result<(), SPI::Error> {
-        self.write_window_data(Command::DataStartTransmission2, FULL_RECT, pixels)?;
-        self.flush()
+    pub async fn write_data2(&mut self, pixels: &[u8]) -> Result<(), EpdError<SPI::Error>> {
+        self.write_window_data(Command::DataStartTransmission2, FULL_RECT, pixels)
+            .await?;
+        self.flush().await
     }
 
     /// Fill a window in the data1 buffer with pixels.
     /// See also [`write_data1`](EpdDriver::write_data1).
-    pub fn write_data1_partial(&mut self, window: Rect, pixels: &[u8]) -> Result<(), SPI::Error> {
-        self.write_partial(Command::DataStartTransmission1, window, pixels)?;
-        self.flush()
+    pub async fn write_data1_partial(
+        &mut self,
+        window: Rect,
+        pixels: &[u8],
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.write_partial(Command::DataStartTransmission1, window, pixels)
+            .await?;
+        self.flush().await
     }
 
     /// Fill a window in the data2 buffer with pixels.
     /// See also [`write_data1`](EpdDriver::write_data1).
-    pub fn write_data2_partial(&mut self, window: Rect, pixels: &[u8]) -> Result<(), SPI::Error> {
-        self.write_partial(Command::DataStartTransmission2, window, pixels)?;
-        self.flush()
+    pub async fn write_data2_partial(
+        &mut self,
+        window: Rect,
+        pixels: &[u8],
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.write_partial(Command::DataStartTransmission2, window, pixels)
+            .await?;
+        self.flush().await
     }
 
     /// Store VCOM Look-Up Table.
@@ -290,116 +370,129 @@ where
     /// If LUT data is shorter than expected, the rest is filled with zeroes.<br>
     /// Note that stored lookup tables need to be activated by setting
     /// [`Config::external_lut`](config::Config::external_lut)`=true`.
-    pub fn set_lutc(&mut self, data: &[u8]) -> Result<(), SPI::Error> {
-        self.set_lut(Command::LutC, data, 60)
+    pub async fn set_lutc(&mut self, data: &[u8]) -> Result<(), EpdError<SPI::Error>> {
+        self.set_lut(Command::LutC, data, 60).await
     }
 
     /// Store White-to-White Look-Up Table.
     /// See also [`write_data1`](EpdDriver::set_lutc).
-    pub fn set_lutww(&mut self, data: &[u8]) -> Result<(), SPI::Error> {
-        self.set_lut(Command::LutWW, data, 42)
+    pub async fn set_lutww(&mut self, data: &[u8]) -> Result<(), EpdError<SPI::Error>> {
+        self.set_lut(Command::LutWW, data, 42).await
     }
 
     /// Store Black-to-White (KW mode) / Red (KWR mode) Look-Up Table.
     /// See also [`write_data1`](EpdDriver::set_lutc).
-    pub fn set_lutkw_lutr(&mut self, data: &[u8]) -> Result<(), SPI::Error> {
-        self.set_lut(Command::LutKW_LutR, data, 60)
+    pub async fn set_lutkw_lutr(&mut self, data: &[u8]) -> Result<(), EpdError<SPI::Error>> {
+        self.set_lut(Command::LutKW_LutR, data, 60).await
     }
 
     /// Store White-to-Black (KW mode) / White (KWR mode) Look-Up Table.
     /// See also [`write_data1`](EpdDriver::set_lutc).
-    pub fn set_lutwk_lutw(&mut self, data: &[u8]) -> Result<(), SPI::Error> {
-        self.set_lut(Command::LutWK_LutW, data, 60)
+    pub async fn set_lutwk_lutw(&mut self, data: &[u8]) -> Result<(), EpdError<SPI::Error>> {
+        self.set_lut(Command::LutWK_LutW, data, 60).await
     }
 
     /// Store Black-to-Black (KW mode) / Black (KWR mode) Look-Up Table.
     /// See also [`write_data1`](EpdDriver::set_lutc).
-    pub fn set_lutkk_lutk(&mut self, data: &[u8]) -> Result<(), SPI::Error> {
-        self.set_lut(Command::LutKK_LutK, data, 60)
+    pub async fn set_lutkk_lutk(&mut self, data: &[u8]) -> Result<(), EpdError<SPI::Error>> {
+        self.set_lut(Command::LutKK_LutK, data, 60).await
     }
 
     /// Store Border Look-Up Table.
     /// See also [`write_data1`](EpdDriver::set_lutc).
-    pub fn set_lutbd(&mut self, data: &[u8]) -> Result<(), SPI::Error> {
-        self.set_lut(Command::LutBD, data, 42)
+    pub async fn set_lutbd(&mut self, data: &[u8]) -> Result<(), EpdError<SPI::Error>> {
+        self.set_lut(Command::LutBD, data, 42).await
     }
 
-    fn set_lut(&mut self, cmd: Command, data: &[u8], reqd_len: usize) -> Result<(), SPI::Error> {
-        self.cmd_with_data(CS_ALL, cmd, data)?;
+    async fn set_lut(
+        &mut self,
+        cmd: Command,
+        data: &[u8],
+        reqd_len: usize,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.cmd_with_data(CS_ALL, cmd, data).await?;
         if data.len() < reqd_len {
             let zeroes = [0; 60];
-            self.spi_write(CS_ALL | CS_DATA, &zeroes[..reqd_len - data.len()])?;
+            self.spi_write(CS_ALL | CS_DATA, &zeroes[..reqd_len - data.len()])
+                .await?;
         }
-        self.flush()
+        self.flush().await
     }
 
     /// Refresh the entire display.
-    pub fn refresh_display(&mut self) -> Result<(), SPI::Error> {
-        self.begin_refresh_display()?;
-        drop(self.wait_ready(CS_ALL));
+    pub async fn refresh_display(&mut self) -> Result<(), EpdError<SPI::Error>> {
+        self.begin_refresh_display().await?;
+        self.wait_ready(CS_ALL).await?;
         Ok(())
     }
 
     /// Asynchronous version of [`refresh_display`](EpdDriver::refresh_display).
     /// Use [`is_busy`](EpdDriver::is_busy) to poll for completion.
-    pub fn begin_refresh_display(&mut self) -> Result<(), SPI::Error> {
-        self.cmd(CS_ALL, Command::PowerOn)?;
-        drop(self.wait_ready(CS_ALL));
+    pub async fn begin_refresh_display(&mut self) -> Result<(), EpdError<SPI::Error>> {
+        self.cmd(CS_ALL, Command::PowerOn).await?;
+        self.wait_ready(CS_ALL).await?;
         // Appears to be required to reliably trigger display refresh after a power-on.
-        self.delay.delay_ms(100);
+        self.delay.delay_ms(100).await;
 
-        self.cmd(CS_ALL, Command::DisplayRefresh)?;
+        self.cmd(CS_ALL, Command::DisplayRefresh).await?;
 
-        self.flush()
+        self.flush().await
     }
 
     /// Refresh the specified sub-window of the display.  
     ///
     /// Technically, this works, however, after 2+ partial updates, the rest of the displayed image becomes visibly degraded.
-    pub fn refresh_display_partial(&mut self, window: Rect) -> Result<(), SPI::Error> {
-        self.begin_refresh_display_partial(window)?;
+    pub async fn refresh_display_partial(
+        &mut self,
+        window: Rect,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.begin_refresh_display_partial(window).await?;
 
-        drop(self.wait_ready(CS_ALL));
+        self.wait_ready(CS_ALL).await?;
         Ok(())
     }
 
     /// Asynchronous version of [`refresh_display_partial`](EpdDriver::refresh_display_partial).
     /// Use [`is_busy`](EpdDriver::is_busy) to poll for completion.
-    pub fn begin_refresh_display_partial(&mut self, window: Rect) -> Result<(), SPI::Error> {
-        self.setup_partial_windows(window)?;
+    pub async fn begin_refresh_display_partial(
+        &mut self,
+        window: Rect,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.setup_partial_windows(window).await?;
 
-        self.cmd(CS_ALL, Command::PowerOn)?;
-        drop(self.wait_ready(CS_ALL));
-        self.delay.delay_ms(100);
+        self.cmd(CS_ALL, Command::PowerOn).await?;
+        self.wait_ready(CS_ALL).await?;
+        self.delay.delay_ms(100).await;
 
-        self.cmd(CS_ALL, Command::PartialIn)?;
-        self.cmd(CS_ALL, Command::DisplayRefresh)?;
-        self.cmd(CS_ALL, Command::PartialOut)?;
+        self.cmd(CS_ALL, Command::PartialIn).await?;
+        self.cmd(CS_ALL, Command::DisplayRefresh).await?;
+        self.cmd(CS_ALL, Command::PartialOut).await?;
 
-        self.flush()
+        self.flush().await
     }
 
     /// Turn off booster, controller, source driver, gate driver, VCOM, and temperature sensor.
     /// However, the contents of the data memory buffers will be retained.
-    pub fn power_off(&mut self) -> Result<(), SPI::Error> {
-        self.cmd(CS_ALL, Command::PowerOff)?;
-        drop(self.wait_ready(CS_ALL));
+    pub async fn power_off(&mut self) -> Result<(), EpdError<SPI::Error>> {
+        self.cmd(CS_ALL, Command::PowerOff).await?;
+        self.wait_ready(CS_ALL).await?;
 
-        self.flush()
+        self.flush().await
     }
 
     /// Put display into deep sleep.  Only [`reset()`](EpdDriver::reset) can bring it out of this state.
     /// The contents of the data memory buffers will be lost.
-    pub fn hibernate(&mut self) -> Result<(), SPI::Error> {
-        self.cmd(CS_ALL, Command::PowerOff)?;
-        drop(self.wait_ready(CS_ALL));
+    pub async fn hibernate(&mut self) -> Result<(), EpdError<SPI::Error>> {
+        self.cmd(CS_ALL, Command::PowerOff).await?;
+        self.wait_ready(CS_ALL).await?;
 
-        self.cmd_with_data(CS_ALL, Command::DeepSleep, &[0xA5])?;
+        self.cmd_with_data(CS_ALL, Command::DeepSleep, &[0xA5])
+            .await?;
 
-        self.flush()
+        self.flush().await
     }
 
-    fn setup_partial_windows(&mut self, window: Rect) -> Result<(), SPI::Error> {
+    async fn setup_partial_windows(&mut self, window: Rect) -> Result<(), EpdError<SPI::Error>> {
         let s2_part = window.intersect(S2_RECT).sub_offset(S2_RECT.x, S2_RECT.y);
         let m2_part = window.intersect(M2_RECT).sub_offset(M2_RECT.x, M2_RECT.y);
         let m1_part = window.intersect(M1_RECT).sub_offset(M1_RECT.x, M1_RECT.y);
@@ -434,51 +527,56 @@ where
             CS_S2,
             Command::PartialWindow,
             &partial_window_data(s2_part, Some(S2_RECT.w)),
-        )?;
+        )
+        .await?;
         self.cmd_with_data(
             CS_M2,
             Command::PartialWindow,
             &partial_window_data(m2_part, Some(M2_RECT.w)),
-        )?;
+        )
+        .await?;
         self.cmd_with_data(
             CS_M1,
             Command::PartialWindow,
             &partial_window_data(m1_part, None),
-        )?;
+        )
+        .await?;
         self.cmd_with_data(
             CS_S1,
             Command::PartialWindow,
             &partial_window_data(s1_part, None),
-        )?;
+        )
+        .await?;
 
         Ok(())
     }
 
-    fn write_partial(
+    async fn write_partial(
         &mut self,
         transmission_cmd: Command,
         window: Rect,
         pixels: &[u8],
-    ) -> Result<(), SPI::Error> {
+    ) -> Result<(), EpdError<SPI::Error>> {
         if window.x % 8 != 0 || window.w % 8 != 0 {
             panic!("Window is not 8-aligned horizontally");
         }
 
-        self.cmd(CS_ALL, Command::PartialIn)?;
+        self.cmd(CS_ALL, Command::PartialIn).await?;
 
-        self.setup_partial_windows(window)?;
-        self.write_window_data(transmission_cmd, window, pixels)?;
+        self.setup_partial_windows(window).await?;
+        self.write_window_data(transmission_cmd, window, pixels)
+            .await?;
 
-        self.cmd(CS_ALL, Command::PartialOut)
+        self.cmd(CS_ALL, Command::PartialOut).await
     }
 
     // Send data to each sub-display for the window area that overlaps with it.
-    fn write_window_data(
+    async fn write_window_data(
         &mut self,
         transmission_cmd: Command,
         window: Rect,
         pixels: &[u8],
-    ) -> Result<(), SPI::Error> {
+    ) -> Result<(), EpdError<SPI::Error>> {
         assert!(!pixels.is_empty());
 
         let s2_part = window.intersect(S2_RECT);
@@ -501,40 +599,40 @@ where
 
         if top_rows > 0 {
             if left_bytes > 0 {
-                self.cmd(CS_S2, transmission_cmd)?;
+                self.cmd(CS_S2, transmission_cmd).await?;
                 for y in 0..top_rows {
                     let begin = row_offset(y);
                     let end = begin + left_bytes;
-                    self.spi_write(CS_S2 | CS_DATA, &pixels[begin..end])?;
+                    self.spi_write(CS_S2 | CS_DATA, &pixels[begin..end]).await?;
                 }
             }
 
             if right_bytes > 0 {
-                self.cmd(CS_M2, transmission_cmd)?;
+                self.cmd(CS_M2, transmission_cmd).await?;
                 for y in 0..top_rows {
                     let begin = row_offset(y) + left_bytes;
                     let end = begin + right_bytes;
-                    self.spi_write(CS_M2 | CS_DATA, &pixels[begin..end])?;
+                    self.spi_write(CS_M2 | CS_DATA, &pixels[begin..end]).await?;
                 }
             }
         }
 
         if bottom_rows > 0 {
             if left_bytes > 0 {
-                self.cmd(CS_M1, transmission_cmd)?;
+                self.cmd(CS_M1, transmission_cmd).await?;
                 for y in 0..bottom_rows {
                     let begin = row_offset(top_rows + y);
                     let end = begin + left_bytes;
-                    self.spi_write(CS_M1 | CS_DATA, &pixels[begin..end])?;
+                    self.spi_write(CS_M1 | CS_DATA, &pixels[begin..end]).await?;
                 }
             }
 
             if right_bytes > 0 {
-                self.cmd(CS_S1, transmission_cmd)?;
+                self.cmd(CS_S1, transmission_cmd).await?;
                 for y in 0..bottom_rows {
                     let begin = row_offset(top_rows + y) + left_bytes;
                     let end = begin + right_bytes;
-                    self.spi_write(CS_S1 | CS_DATA, &pixels[begin..end])?;
+                    self.spi_write(CS_S1 | CS_DATA, &pixels[begin..end]).await?;
                 }
             }
         }
@@ -542,22 +640,22 @@ where
         Ok(())
     }
 
-    fn cmd(&mut self, chips: CS, command: Command) -> Result<(), SPI::Error> {
-        self.spi_write(chips, &[command as u8])
+    async fn cmd(&mut self, chips: CS, command: Command) -> Result<(), EpdError<SPI::Error>> {
+        self.spi_write(chips, &[command as u8]).await
     }
 
-    fn cmd_with_data(
+    async fn cmd_with_data(
         &mut self,
         chips: CS,
         command: Command,
         data: &[u8],
-    ) -> Result<(), SPI::Error> {
-        self.spi_write(chips, &[command as u8])?;
-        self.spi_write(chips | CS_DATA, data)
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.spi_write(chips, &[command as u8]).await?;
+        self.spi_write(chips | CS_DATA, data).await
     }
 
     // Set control pins to the specified state, then send data via SPI.
-    fn spi_write(&mut self, control: CS, data: &[u8]) -> Result<(), SPI::Error> {
+    async fn spi_write(&mut self, control: CS, data: &[u8]) -> Result<(), EpdError<SPI::Error>> {
         if self.control_state != control {
             fn pin_state(high: bool) -> PinState {
                 if high {
@@ -567,77 +665,97 @@ where
                 }
             }
 
-            self.peris.spi.flush()?;
-            self.delay.delay_ns(100); // Tscc = 20ns, Tchw = 40ns
+            self.peris.spi.flush().await.map_err(EpdError::Spi)?;
+            self.delay.delay_ns(100).await; // Tscc = 20ns, Tchw = 40ns
 
             // CS is active low
-            drop(self.peris.m1_cs.set_state(pin_state(control & CS_M1 == 0)));
-            drop(self.peris.s1_cs.set_state(pin_state(control & CS_S1 == 0)));
-            drop(self.peris.m2_cs.set_state(pin_state(control & CS_M2 == 0)));
-            drop(self.peris.s2_cs.set_state(pin_state(control & CS_S2 == 0)));
+            self.peris
+                .m1_cs
+                .set_state(pin_state(control & CS_M1 == 0))
+                .map_err(EpdError::pin)?;
+            self.peris
+                .s1_cs
+                .set_state(pin_state(control & CS_S1 == 0))
+                .map_err(EpdError::pin)?;
+            self.peris
+                .m2_cs
+                .set_state(pin_state(control & CS_M2 == 0))
+                .map_err(EpdError::pin)?;
+            self.peris
+                .s2_cs
+                .set_state(pin_state(control & CS_S2 == 0))
+                .map_err(EpdError::pin)?;
 
             // DC is active high
             let dc = pin_state(control & CS_DATA != 0);
-            drop(self.peris.m1s1_dc.set_state(dc));
-            drop(self.peris.m2s2_dc.set_state(dc));
+            self.peris.m1s1_dc.set_state(dc).map_err(EpdError::pin)?;
+            self.peris.m2s2_dc.set_state(dc).map_err(EpdError::pin)?;
 
-            self.delay.delay_ns(100); // Tcss = 60ns, Tsds = 30ns
+            self.delay.delay_ns(100).await; // Tcss = 60ns, Tsds = 30ns
             self.control_state = control;
         }
 
-        self.peris.spi.write(data)
+        self.peris.spi.write(data).await.map_err(EpdError::Spi)
     }
 
     // Flush SPI, reset control pins to the default state.
-    fn flush(&mut self) -> Result<(), SPI::Error> {
-        self.peris.spi.flush()?;
-        drop(self.peris.m1_cs.set_high());
-        drop(self.peris.s1_cs.set_high());
-        drop(self.peris.m2_cs.set_high());
-        drop(self.peris.s2_cs.set_high());
-        drop(self.peris.m1s1_dc.set_low());
-        drop(self.peris.m2s2_dc.set_low());
+    async fn flush(&mut self) -> Result<(), EpdError<SPI::Error>> {
+        self.peris.spi.flush().await.map_err(EpdError::Spi)?;
+        self.peris.m1_cs.set_high().map_err(EpdError::pin)?;
+        self.peris.s1_cs.set_high().map_err(EpdError::pin)?;
+        self.peris.m2_cs.set_high().map_err(EpdError::pin)?;
+        self.peris.s2_cs.set_high().map_err(EpdError::pin)?;
+        self.peris.m1s1_dc.set_low().map_err(EpdError::pin)?;
+        self.peris.m2s2_dc.set_low().map_err(EpdError::pin)?;
         self.control_state = 0;
         Ok(())
     }
 
-    fn wait_ready(&mut self, chips: CS) -> Result<(), INPUT::Error> {
+    async fn wait_ready(&mut self, chips: CS) -> Result<(), EpdError<SPI::Error>> {
+        let mut waited_us = 0u32;
         while self.busy_chips(chips)? != 0 {
-            self.delay.delay_ms(200);
+            if self
+                .busy_timeout_us
+                .is_some_and(|timeout_us| waited_us >= timeout_us)
+            {
+                return Err(EpdError::BusyTimeout);
+            }
+            self.delay.delay_ms(BUSY_POLL_MS).await;
+            waited_us = waited_us.saturating_add(BUSY_POLL_MS * 1000);
         }
         Ok(())
     }
 
-    fn busy_chips(&mut self, chips: CS) -> Result<CS, INPUT::Error> {
+    fn busy_chips(&mut self, chips: CS) -> Result<CS, EpdError<SPI::Error>> {
         let mut busy = 0;
-        if chips & CS_M1 != 0 && self.peris.m1_busy.is_low()? {
+        if chips & CS_M1 != 0 && self.peris.m1_busy.is_low().map_err(EpdError::pin)? {
             busy |= CS_M1;
         }
-        if chips & CS_S1 != 0 && self.peris.s1_busy.is_low()? {
+        if chips & CS_S1 != 0 && self.peris.s1_busy.is_low().map_err(EpdError::pin)? {
             busy |= CS_S1;
         }
-        if chips & CS_M2 != 0 && self.peris.m2_busy.is_low()? {
+        if chips & CS_M2 != 0 && self.peris.m2_busy.is_low().map_err(EpdError::pin)? {
             busy |= CS_M2;
         }
-        if chips & CS_S2 != 0 && self.peris.s2_busy.is_low()? {
+        if chips & CS_S2 != 0 && self.peris.s2_busy.is_low().map_err(EpdError::pin)? {
             busy |= CS_S2;
         }
         Ok(busy)
     }
 
     /// Poll readiness status of all sub-displays and return a bit mask of the busy ones.
-    pub fn get_busy(&mut self) -> u8 {
-        self.busy_chips(CS_ALL).unwrap()
+    pub fn get_busy(&mut self) -> Result<u8, EpdError<SPI::Error>> {
+        self.busy_chips(CS_ALL)
     }
 
     /// Check if any of the sub-displays is busy.
-    pub fn is_busy(&mut self) -> bool {
-        self.busy_chips(CS_ALL).unwrap() != 0
+    pub fn is_busy(&mut self) -> Result<bool, EpdError<SPI::Error>> {
+        Ok(self.get_busy()? != 0)
     }
 
     /// Query and return the status byte of each sub-display.
     /// Order: \[M1, S1, M2, S2\].
-    pub fn get_status(&mut self) -> Result<[u8; 4], SPI::Error> {
+    pub async fn get_status(&mut self) -> Result<[u8; 4], EpdError<SPI::Error>> {
         self.control_state = 0xFF;
         let mut status = [0u8; 4];
         for i in 0..4 {
@@ -648,24 +766,91 @@ where
                 _ => (&mut self.peris.s2_cs, &mut self.peris.m2s2_dc),
             };
             // Request status
-            drop(cs.set_low());
-            drop(dc.set_low());
-            self.delay.delay_ns(100); // Tcss = 60ns
-            self.peris.spi.write(&[Command::GetStatus as u8])?;
-            self.peris.spi.flush()?;
-            self.delay.delay_ns(100); // Tsds = 30ns
+            cs.set_low().map_err(EpdError::pin)?;
+            dc.set_low().map_err(EpdError::pin)?;
+            self.delay.delay_ns(100).await; // Tcss = 60ns
+            self.peris
+                .spi
+                .write(&[Command::GetStatus as u8])
+                .await
+                .map_err(EpdError::Spi)?;
+            self.peris.spi.flush().await.map_err(EpdError::Spi)?;
+            self.delay.delay_ns(100).await; // Tsds = 30ns
 
             // Read status
-            drop(dc.set_high());
-            self.delay.delay_ns(100); // Tsdh = 30ns
-            self.peris.spi.read(&mut status[i..i + 1])?;
-            self.delay.delay_ns(100); // Tscc = 20ns
-            drop(dc.set_low());
-
-            drop(cs.set_high());
-            self.delay.delay_ns(100); // Tchw = 40ns
+            dc.set_high().map_err(EpdError::pin)?;
+            self.delay.delay_ns(100).await; // Tsdh = 30ns
+            self.peris
+                .spi
+                .read(&mut status[i..i + 1])
+                .await
+                .map_err(EpdError::Spi)?;
+            self.delay.delay_ns(100).await; // Tscc = 20ns
+            dc.set_low().map_err(EpdError::pin)?;
+
+            cs.set_high().map_err(EpdError::pin)?;
+            self.delay.delay_ns(100).await; // Tchw = 40ns
         }
         self.control_state = 0;
         Ok(status)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use embedded_hal_mock::eh1::{
+        delay::NoopDelay,
+        digital::{Mock, State, Transaction},
+        spi::Mock as SpiMock,
+    };
+
+    #[test]
+    fn busy_timeout() {
+        // polled after 0, 200 and 400ms
+        let busy = || {
+            let low = || Transaction::get(State::Low);
+            Mock::new(&[low(), low(), low()])
+        };
+        let pin = || Mock::new(&[]);
+        let mut epd = EpdDriver::new(
+            Peripherals {
+                spi: SpiMock::new(&[]),
+                m1_cs: pin(),
+                s1_cs: pin(),
+                m2_cs: pin(),
+                s2_cs: pin(),
+                m1s1_dc: pin(),
+                m2s2_dc: pin(),
+                m1s1_rst: pin(),
+                m2s2_rst: pin(),
+                m1_busy: busy(),
+                s1_busy: busy(),
+                m2_busy: busy(),
+                s2_busy: busy(),
+            },
+            NoopDelay::new(),
+        );
+        epd.set_busy_timeout(Some(300_000));
+        assert_eq!(epd.wait_ready(CS_ALL), Err(EpdError::BusyTimeout));
+
+        let mut peris = epd.into_peripherals();
+        peris.spi.done();
+        for pin in [
+            &mut peris.m1_cs,
+            &mut peris.s1_cs,
+            &mut peris.m2_cs,
+            &mut peris.s2_cs,
+            &mut peris.m1s1_dc,
+            &mut peris.m2s2_dc,
+            &mut peris.m1s1_rst,
+            &mut peris.m2s2_rst,
+            &mut peris.m1_busy,
+            &mut peris.s1_busy,
+            &mut peris.m2_busy,
+            &mut peris.s2_busy,
+        ] {
+            pin.done();
+        }
+    }
+}
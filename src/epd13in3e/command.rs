@@ -0,0 +1,47 @@
+//! SPI Commands for the Waveshare 13.3" (E) E-Ink Display
+
+use crate::traits;
+
+/// Epd13in3e commands
+///
+/// The commands without a documented name are named after their address, as in the Waveshare
+/// code.
+#[allow(dead_code)]
+#[derive(Clone, Copy, Debug)]
+pub(crate) enum Command {
+    PanelSetting = 0x00,
+    PowerSetting = 0x01,
+    PowerOff = 0x02,
+    PowerOn = 0x04,
+    /// Booster of the negative voltages
+    BoosterSoftStartN = 0x05,
+    /// Booster of the positive voltages
+    BoosterSoftStartP = 0x06,
+    DeepSleep = 0x07,
+
+    DataStartTransmission = 0x10,
+    DisplayRefresh = 0x12,
+
+    VcomAndDataIntervalSetting = 0x50,
+    TconSetting = 0x60,
+    ResolutionSetting = 0x61,
+    AnalogTiming = 0x74,
+    GateInterval = 0x86,
+
+    BuckBoostVddn = 0xB0,
+    TftVcomPower = 0xB1,
+    EnableBuffer = 0xB6,
+    BoostVddpEnable = 0xB7,
+
+    CascadeSetting = 0xE0,
+    PowerSaving = 0xE3,
+
+    OxF0 = 0xF0,
+}
+
+impl traits::Command for Command {
+    /// Returns the address of the command
+    fn address(self) -> u8 {
+        self as u8
+    }
+}
@@ -0,0 +1,479 @@
+//! A Driver for the Waveshare 13.3" (E) E Ink Spectra 6 Display via SPI
+//!
+//! The 1200x1600 panel shows 6 colors: black, white, yellow, red, blue and green. Its pixels are
+//! sent at 4 bits per pixel, 2 in a byte with the leftmost one in the high nibble, as packed by a
+//! [`SpectraColor`] display.
+//!
+//! Two cascaded controllers drive it, each with its own chip select: the master the columns 0 to
+//! 599, the slave the columns 600 to 1199. The driver selects them with the `cs_m` and `cs_s`
+//! pins given to [`new`](Epd13in3e::new), so the SPI device mustn't drive a chip select of its
+//! own (e.g. an `ExclusiveDevice` with `NoCs`). The frames are those of the whole panel: each
+//! line is split between both controllers, following the Waveshare C driver (`EPD_13in3e.c`).
+//!
+//! The full frame takes 960KB, more than the RAM of most microcontrollers: it can be rendered and
+//! sent band by band with [`update_frame_banded`](Epd13in3e::update_frame_banded) from a buffer
+//! of a few half lines instead of a [`Display13in3e`].
+//!
+//!```rust, no_run
+//!# use embedded_hal_mock::eh1::*;
+//!# fn main() -> Result<(), epd_waveshare::error::EpdError<embedded_hal::spi::ErrorKind>> {
+//!use epd_waveshare::{epd13in3e::*, prelude::*};
+//!#
+//!# let expectations = [];
+//!# let mut spi = spi::Mock::new(&expectations);
+//!# let expectations = [];
+//!# let busy_in = digital::Mock::new(&expectations);
+//!# let dc = digital::Mock::new(&expectations);
+//!# let rst = digital::Mock::new(&expectations);
+//!# let cs_m = digital::Mock::new(&expectations);
+//!# let cs_s = digital::Mock::new(&expectations);
+//!# let mut delay = delay::NoopDelay::new();
+//!
+//!let mut epd = Epd13in3e::new(&mut spi, cs_m, cs_s, busy_in, dc, rst, &mut delay, None)?;
+//!
+//!// 16 half lines at a time: a band of each color from the top to the bottom
+//!let colors = [
+//!    SpectraColor::Black,
+//!    SpectraColor::White,
+//!    SpectraColor::Yellow,
+//!    SpectraColor::Red,
+//!    SpectraColor::Blue,
+//!    SpectraColor::Green,
+//!];
+//!let mut band = [0; 16 * HALF_LINE_BYTES];
+//!epd.update_frame_banded(&mut spi, &mut delay, &mut band, |area, pixels| {
+//!    let color = colors[(area.y * 6 / HEIGHT) as usize];
+//!    pixels.fill(SpectraColor::colors_byte(color, color));
+//!})?;
+//!epd.display_frame(&mut spi, &mut delay)?;
+//!
+//!epd.sleep(&mut spi, &mut delay)?;
+//!# Ok(())
+//!# }
+//!```
+
+use crate::error::EpdError;
+use crate::pin::BusyPin;
+use embedded_hal::{
+    delay::DelayNs,
+    digital::{OutputPin, PinState},
+    spi::SpiDevice,
+};
+
+use crate::color::SpectraColor;
+use crate::interface::{BusyTimings, DisplayInterface};
+use crate::rect::Rect;
+
+use self::command::Command;
+#[cfg(feature = "async")]
+use crate::interface::DisplayInterfaceAsync;
+#[cfg(feature = "async")]
+use crate::traits::AsyncBusyPin;
+#[cfg(feature = "async")]
+use embedded_hal_async::{delay::DelayNs as AsyncDelayNs, spi::SpiDevice as AsyncSpiDevice};
+
+mod command;
+
+/// Full size buffer for use with the 13in3e EPD
+#[cfg(feature = "graphics")]
+pub type Display13in3e = crate::graphics::Display<
+    WIDTH,
+    HEIGHT,
+    false,
+    { crate::graphics::display_len::<SpectraColor>(WIDTH, HEIGHT) },
+    SpectraColor,
+>;
+
+/// Width of the display
+pub const WIDTH: u32 = 1200;
+/// Height of the display
+pub const HEIGHT: u32 = 1600;
+/// Number of bytes of a line of pixels
+pub const LINE_BYTES: usize = WIDTH as usize / 2;
+/// Number of bytes of the half of a line driven by one controller
+pub const HALF_LINE_BYTES: usize = LINE_BYTES / 2;
+/// Default Background Color
+pub const DEFAULT_BACKGROUND_COLOR: SpectraColor = SpectraColor::White;
+const IS_BUSY_LOW: bool = true;
+const SINGLE_BYTE_WRITE: bool = false;
+
+/// Controllers receiving a command
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Controllers {
+    Master,
+    Slave,
+    Both,
+}
+
+#[maybe_async_cfg::maybe(
+    sync(keep_self),
+    async(
+        feature = "async",
+        idents(
+            Epd13in3e,
+            DisplayInterface,
+            SpiDevice(async = "AsyncSpiDevice"),
+            BusyPin(async = "AsyncBusyPin"),
+            DelayNs(async = "AsyncDelayNs")
+        )
+    )
+)]
+/// Epd13in3e driver
+pub struct Epd13in3e<SPI, CS, BUSY, DC, RST, DELAY> {
+    /// Connection Interface
+    interface: DisplayInterface<SPI, BUSY, DC, RST, DELAY, SINGLE_BYTE_WRITE>,
+    /// Chip select of the master controller
+    cs_m: CS,
+    /// Chip select of the slave controller
+    cs_s: CS,
+    /// Background Color
+    color: SpectraColor,
+}
+
+#[maybe_async_cfg::maybe(
+    sync(keep_self),
+    async(
+        feature = "async",
+        idents(
+            Epd13in3e,
+            DisplayInterface,
+            SpiDevice(async = "AsyncSpiDevice"),
+            BusyPin(async = "AsyncBusyPin"),
+            DelayNs(async = "AsyncDelayNs")
+        )
+    )
+)]
+impl<SPI, CS, BUSY, DC, RST, DELAY> Epd13in3e<SPI, CS, BUSY, DC, RST, DELAY>
+where
+    SPI: SpiDevice,
+    CS: OutputPin,
+    BUSY: BusyPin,
+    DC: OutputPin,
+    RST: OutputPin,
+    DELAY: DelayNs,
+{
+    /// Creates the driver and initializes the panel
+    ///
+    /// `cs_m` and `cs_s` select the master and the slave controller, `delay_us` is the
+    /// interval of the polling of the BUSY pin as for the other drivers.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn new(
+        spi: &mut SPI,
+        cs_m: CS,
+        cs_s: CS,
+        busy: BUSY,
+        dc: DC,
+        rst: RST,
+        delay: &mut DELAY,
+        delay_us: Option<u32>,
+    ) -> Result<Self, EpdError<SPI::Error>> {
+        let interface =
+            DisplayInterface::new(busy, dc, rst, delay_us).with_busy_timings(BusyTimings::ACEP);
+        let mut epd = Epd13in3e {
+            interface,
+            cs_m,
+            cs_s,
+            color: DEFAULT_BACKGROUND_COLOR,
+        };
+
+        epd.init(spi, delay).await?;
+
+        Ok(epd)
+    }
+
+    async fn init(&mut self, spi: &mut SPI, delay: &mut DELAY) -> Result<(), EpdError<SPI::Error>> {
+        self.select(None)?;
+        self.interface.reset(delay, 30_000, 30_000).await?;
+        self.wait_until_idle(spi, delay).await?;
+
+        use Controllers::*;
+        self.cmd_with_data(
+            spi,
+            Both,
+            Command::AnalogTiming,
+            &[0xC0, 0x1C, 0x1C, 0xCC, 0xCC, 0xCC, 0x15, 0x15, 0x55],
+        )
+        .await?;
+        self.cmd_with_data(
+            spi,
+            Both,
+            Command::OxF0,
+            &[0x49, 0x55, 0x13, 0x5D, 0x05, 0x10],
+        )
+        .await?;
+        self.cmd_with_data(spi, Both, Command::PanelSetting, &[0xDF, 0x69])
+            .await?;
+        self.cmd_with_data(spi, Both, Command::VcomAndDataIntervalSetting, &[0xF7])
+            .await?;
+        self.cmd_with_data(spi, Both, Command::TconSetting, &[0x03, 0x03])
+            .await?;
+        self.cmd_with_data(spi, Both, Command::GateInterval, &[0x10])
+            .await?;
+        self.cmd_with_data(spi, Both, Command::PowerSaving, &[0x22])
+            .await?;
+        self.cmd_with_data(spi, Both, Command::CascadeSetting, &[0x01])
+            .await?;
+        self.cmd_with_data(
+            spi,
+            Both,
+            Command::ResolutionSetting,
+            &[0x04, 0xB0, 0x03, 0x20],
+        )
+        .await?;
+
+        // the master generates the voltages of both
+        self.cmd_with_data(
+            spi,
+            Master,
+            Command::PowerSetting,
+            &[0x0F, 0x00, 0x28, 0x2C, 0x28, 0x38],
+        )
+        .await?;
+        self.cmd_with_data(spi, Master, Command::EnableBuffer, &[0x07])
+            .await?;
+        self.cmd_with_data(spi, Master, Command::BoosterSoftStartP, &[0xE8, 0x28])
+            .await?;
+        self.cmd_with_data(spi, Master, Command::BoostVddpEnable, &[0x01])
+            .await?;
+        self.cmd_with_data(spi, Master, Command::BoosterSoftStartN, &[0xE8, 0x28])
+            .await?;
+        self.cmd_with_data(spi, Master, Command::BuckBoostVddn, &[0x01])
+            .await?;
+        self.cmd_with_data(spi, Master, Command::TftVcomPower, &[0x02])
+            .await
+    }
+
+    /// Wakes the panel up from the deep sleep, with a reset
+    pub async fn wake_up(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.init(spi, delay).await
+    }
+
+    /// Puts the panel in deep sleep, [`wake_up`](Epd13in3e::wake_up) resets it
+    pub async fn sleep(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.wait_until_idle(spi, delay).await?;
+        self.cmd_with_data(spi, Controllers::Both, Command::DeepSleep, &[0xA5])
+            .await
+    }
+
+    /// Sends a full frame, 2 pixels per byte as in [`Display13in3e`]
+    pub async fn update_frame(
+        &mut self,
+        spi: &mut SPI,
+        buffer: &[u8],
+        delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        EpdError::check_buffer(buffer, LINE_BYTES * HEIGHT as usize)?;
+        self.wait_until_idle(spi, delay).await?;
+        for (controller, half) in [(Controllers::Master, 0), (Controllers::Slave, 1)] {
+            self.select(Some(controller))?;
+            let sent = self.send_half(spi, buffer, half).await;
+            self.select(None)?;
+            sent?;
+        }
+        Ok(())
+    }
+
+    /// Sends a frame band by band, rendered in `band` by `render`
+    ///
+    /// `band` holds as many half lines of `HALF_LINE_BYTES` bytes as possible, at least one or
+    /// [`EpdError::BufferSize`] is returned. For each band, `render` is called with the area of the
+    /// frame it covers and the pixels to fill, 2 per byte as in [`Display13in3e`]: the bands of the
+    /// left half of the panel come first, from the top to the bottom, then those of the right half.
+    /// The frame is shown with [`display_frame`](Epd13in3e::display_frame).
+    pub async fn update_frame_banded<F>(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+        band: &mut [u8],
+        mut render: F,
+    ) -> Result<(), EpdError<SPI::Error>>
+    where
+        F: FnMut(Rect, &mut [u8]),
+    {
+        self.wait_until_idle(spi, delay).await?;
+        for (controller, x) in [(Controllers::Master, 0), (Controllers::Slave, WIDTH / 2)] {
+            self.select(Some(controller))?;
+            let mut sent = self
+                .interface
+                .cmd(spi, Command::DataStartTransmission)
+                .await;
+            if sent.is_ok() {
+                sent = self
+                    .interface
+                    .data_banded(
+                        spi,
+                        Rect::new(x, 0, WIDTH / 2, HEIGHT),
+                        HALF_LINE_BYTES,
+                        band,
+                        &mut render,
+                    )
+                    .await;
+            }
+            self.select(None)?;
+            sent?;
+        }
+        Ok(())
+    }
+
+    /// Powers the panel on, refreshes it and powers it off
+    pub async fn display_frame(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        use Controllers::Both;
+        self.wait_until_idle(spi, delay).await?;
+        self.cmd_with_data(spi, Both, Command::PowerOn, &[]).await?;
+        self.wait_until_idle(spi, delay).await?;
+        self.cmd_with_data(spi, Both, Command::DisplayRefresh, &[0x00])
+            .await?;
+        self.wait_until_idle(spi, delay).await?;
+        self.cmd_with_data(spi, Both, Command::PowerOff, &[0x00])
+            .await?;
+        self.wait_until_idle(spi, delay).await
+    }
+
+    /// Sends a full frame and displays it
+    pub async fn update_and_display_frame(
+        &mut self,
+        spi: &mut SPI,
+        buffer: &[u8],
+        delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.update_frame(spi, buffer, delay).await?;
+        self.display_frame(spi, delay).await
+    }
+
+    /// Fills the frame with the background color and displays it
+    pub async fn clear_frame(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        let bg = SpectraColor::colors_byte(self.color, self.color);
+
+        self.wait_until_idle(spi, delay).await?;
+        for controller in [Controllers::Master, Controllers::Slave] {
+            self.select(Some(controller))?;
+            let mut sent = self
+                .interface
+                .cmd(spi, Command::DataStartTransmission)
+                .await;
+            if sent.is_ok() {
+                sent = self
+                    .interface
+                    .data_x_times(spi, bg, HALF_LINE_BYTES as u32 * HEIGHT)
+                    .await;
+            }
+            self.select(None)?;
+            sent?;
+        }
+        self.display_frame(spi, delay).await
+    }
+
+    /// Sets the background color of [`clear_frame`](Epd13in3e::clear_frame)
+    pub fn set_background_color(&mut self, color: SpectraColor) {
+        self.color = color;
+    }
+
+    /// Returns the background color
+    pub fn background_color(&self) -> &SpectraColor {
+        &self.color
+    }
+
+    /// Width of the panel
+    pub fn width(&self) -> u32 {
+        WIDTH
+    }
+
+    /// Height of the panel
+    pub fn height(&self) -> u32 {
+        HEIGHT
+    }
+
+    /// Sets the longest time to wait for the BUSY pin, `None` waiting forever
+    pub fn set_busy_timeout(&mut self, timeout_us: Option<u32>) {
+        self.interface.config_mut().busy_timeout_us = timeout_us;
+    }
+
+    /// Waits until the controllers are idle
+    pub async fn wait_until_idle(
+        &mut self,
+        _spi: &mut SPI,
+        delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.interface.wait_until_idle(delay, IS_BUSY_LOW).await
+    }
+
+    /// Selects one or both controllers, or none
+    fn select(&mut self, controllers: Option<Controllers>) -> Result<(), EpdError<SPI::Error>> {
+        let (master, slave) = match controllers {
+            Some(Controllers::Master) => (true, false),
+            Some(Controllers::Slave) => (false, true),
+            Some(Controllers::Both) => (true, true),
+            None => (false, false),
+        };
+        // the chip selects are active low
+        self.cs_m
+            .set_state(PinState::from(!master))
+            .map_err(EpdError::pin)?;
+        self.cs_s
+            .set_state(PinState::from(!slave))
+            .map_err(EpdError::pin)
+    }
+
+    async fn cmd_with_data(
+        &mut self,
+        spi: &mut SPI,
+        controllers: Controllers,
+        command: Command,
+        data: &[u8],
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.select(Some(controllers))?;
+        let sent = self.interface.cmd_with_data(spi, command, data).await;
+        self.select(None)?;
+        sent
+    }
+
+    /// Sends the left (`half` 0) or the right (`half` 1) half of the lines of `buffer` to the
+    /// selected controller
+    async fn send_half(
+        &mut self,
+        spi: &mut SPI,
+        buffer: &[u8],
+        half: usize,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.interface
+            .cmd(spi, Command::DataStartTransmission)
+            .await?;
+        for line in buffer.chunks_exact(LINE_BYTES) {
+            let start = half * HALF_LINE_BYTES;
+            self.interface
+                .data(spi, &line[start..start + HALF_LINE_BYTES])
+                .await?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn epd_size() {
+        assert_eq!(WIDTH, 1200);
+        assert_eq!(HEIGHT, 1600);
+        assert_eq!(HALF_LINE_BYTES, 300);
+        assert_eq!(LINE_BYTES * HEIGHT as usize, 960_000);
+        assert_eq!(DEFAULT_BACKGROUND_COLOR, SpectraColor::White);
+    }
+}
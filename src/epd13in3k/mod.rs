@@ -0,0 +1,119 @@
+//! A Driver for the Waveshare 13.3" (K) E-Ink Display via SPI
+//!
+//! The 960x680 black and white panel has a SSD1677 controller. Its waveforms are the ones of the
+//! controller OTP: the full refresh and a fast full refresh selected with
+//! [`RefreshLut::Fast`](crate::traits::RefreshLut::Fast), as in the Waveshare C driver
+//! (`EPD_13in3k.c`).
+//!
+//! The full frame takes about 81KB, more than the RAM of many microcontrollers: it can be
+//! rendered and sent band by band with
+//! [`update_frame_banded`](crate::controller::ssd16xx::Ssd16xx::update_frame_banded) from a
+//! buffer of a few lines instead of a [`Display13in3k`].
+//!
+//! The driver is the one of the SSD16xx controllers, see [`crate::controller::ssd16xx`].
+//!
+//!```rust, no_run
+//!# use embedded_hal_mock::eh1::*;
+//!# fn main() -> Result<(), epd_waveshare::error::EpdError<embedded_hal::spi::ErrorKind>> {
+//!use epd_waveshare::{epd13in3k::*, prelude::*};
+//!#
+//!# let expectations = [];
+//!# let mut spi = spi::Mock::new(&expectations);
+//!# let expectations = [];
+//!# let busy_in = digital::Mock::new(&expectations);
+//!# let dc = digital::Mock::new(&expectations);
+//!# let rst = digital::Mock::new(&expectations);
+//!# let mut delay = delay::NoopDelay::new();
+//!
+//!let mut epd = Epd13in3k::new(&mut spi, busy_in, dc, rst, &mut delay, None)?;
+//!
+//!// 8 lines at a time: black on the left half, white on the right one
+//!let mut band = [0; 8 * LINE_BYTES];
+//!epd.update_frame_banded(&mut spi, &mut delay, &mut band, |_area, pixels| {
+//!    for line in pixels.chunks_mut(LINE_BYTES) {
+//!        let (left, right) = line.split_at_mut(LINE_BYTES / 2);
+//!        left.fill(Color::Black.get_byte_value());
+//!        right.fill(Color::White.get_byte_value());
+//!    }
+//!})?;
+//!epd.display_frame(&mut spi, &mut delay)?;
+//!
+//!// the following full refreshes are faster
+//!epd.set_lut(&mut spi, &mut delay, Some(RefreshLut::Fast))?;
+//!epd.clear_frame(&mut spi, &mut delay)?;
+//!epd.display_frame(&mut spi, &mut delay)?;
+//!
+//!epd.sleep(&mut spi, &mut delay)?;
+//!# Ok(())
+//!# }
+//!```
+
+use crate::color::Color;
+use crate::controller::ssd16xx::{Addressing, Panel, PanelConfig, Ssd16xx, Voltages};
+
+/// Width of the display in pixels
+pub const WIDTH: u32 = 960;
+/// Height of the display in pixels
+pub const HEIGHT: u32 = 680;
+/// Number of bytes of a line of pixels
+pub const LINE_BYTES: usize = WIDTH as usize / 8;
+/// Default Background Color (white)
+pub const DEFAULT_BACKGROUND_COLOR: Color = Color::White;
+
+/// Full size buffer for use with the 13in3k EPD
+#[cfg(feature = "graphics")]
+pub type Display13in3k = crate::graphics::Display<
+    WIDTH,
+    HEIGHT,
+    false,
+    { crate::graphics::display_len::<Color>(WIDTH, HEIGHT) },
+    Color,
+>;
+
+/// Settings of the 13in3k panel
+pub struct Epd13in3kPanel;
+
+impl Panel for Epd13in3kPanel {
+    const CONFIG: PanelConfig = PanelConfig {
+        addressing: Addressing::Pixels,
+        reset_us: [100_000, 2_000],
+        border_waveform: Some(0x01),
+        voltages: Voltages {
+            booster: &[0xAE, 0xC7, 0xC3, 0xC0, 0x80],
+            ..Voltages::DEFAULT
+        },
+        // 90°C
+        fast_temperature: Some(0x5A),
+        ..PanelConfig::new(WIDTH, HEIGHT)
+    };
+}
+
+/// Epd13in3k driver
+pub type Epd13in3k<SPI, BUSY, DC, RST, DELAY> = Ssd16xx<Epd13in3kPanel, SPI, BUSY, DC, RST, DELAY>;
+
+/// Epd13in3k driver for the async SPI and delay traits
+#[cfg(feature = "async")]
+pub type Epd13in3kAsync<SPI, BUSY, DC, RST, DELAY> =
+    crate::controller::ssd16xx::Ssd16xxAsync<Epd13in3kPanel, SPI, BUSY, DC, RST, DELAY>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn epd_size() {
+        assert_eq!(WIDTH, 960);
+        assert_eq!(HEIGHT, 680);
+        assert_eq!(LINE_BYTES * HEIGHT as usize, 81_600);
+        assert_eq!(DEFAULT_BACKGROUND_COLOR, Color::White);
+    }
+
+    #[test]
+    fn panel_config() {
+        let config = Epd13in3kPanel::CONFIG;
+        assert_eq!((config.width, config.height), (WIDTH, HEIGHT));
+        assert_eq!(config.addressing, Addressing::Pixels);
+        assert_eq!(config.fast_temperature, Some(0x5A));
+        assert_eq!(config.luts, None);
+    }
+}
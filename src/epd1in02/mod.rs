@@ -2,18 +2,50 @@
 //!
 //! - [Datasheet](https://www.waveshare.com/product/1.02inch-e-paper.htm)
 //!
-//! The display controller IC is UC8175
+//! The display controller IC is UC8175. Besides the full refreshes, the LUTs of the partial
+//! refreshes of the Waveshare code give quick refreshes of a frame following an old one, see
+//! [`QuickRefresh`].
+//!
+//!```rust, no_run
+//!# use embedded_hal_mock::eh1::*;
+//!# fn main() -> Result<(), epd_waveshare::error::EpdError<embedded_hal::spi::ErrorKind>> {
+//!use embedded_graphics::{prelude::*, primitives::{Line, PrimitiveStyle}};
+//!use epd_waveshare::{epd1in02::*, prelude::*};
+//!#
+//!# let expectations = [];
+//!# let mut spi = spi::Mock::new(&expectations);
+//!# let expectations = [];
+//!# let busy_in = digital::Mock::new(&expectations);
+//!# let dc = digital::Mock::new(&expectations);
+//!# let rst = digital::Mock::new(&expectations);
+//!# let mut delay = delay::NoopDelay::new();
+//!
+//!let mut epd = Epd1in02::new(&mut spi, busy_in, dc, rst, &mut delay, None)?;
+//!
+//!let mut display = Display1in02::default();
+//!let _ = display.clear(Color::White);
+//!epd.update_and_display_frame(&mut spi, display.buffer(), &mut delay)?;
+//!
+//!// quick refresh of the pixels drawn since
+//!epd.update_old_frame(&mut spi, display.buffer(), &mut delay)?;
+//!let _ = Line::new(Point::new(0, 20), Point::new(79, 20))
+//!    .into_styled(PrimitiveStyle::with_stroke(Color::Black, 2))
+//!    .draw(&mut display);
+//!epd.update_and_display_new_frame(&mut spi, display.buffer(), &mut delay)?;
+//!
+//!epd.sleep(&mut spi, &mut delay)?;
+//!# Ok(())
+//!# }
+//!```
 
-use embedded_hal::{
-    delay::DelayNs,
-    digital::{InputPin, OutputPin},
-    spi::SpiDevice,
-};
+use crate::error::EpdError;
+use crate::pin::BusyPin;
+use embedded_hal::{delay::DelayNs, digital::OutputPin, spi::SpiDevice};
 
 use crate::color::Color;
 use crate::interface::DisplayInterface;
 use crate::prelude::WaveshareDisplay;
-use crate::traits::{InternalWiAdditions, QuickRefresh, RefreshLut};
+use crate::traits::{InterfaceConfig, InternalWiAdditions, QuickRefresh, RefreshLut};
 
 pub(crate) mod command;
 use self::command::Command;
@@ -24,6 +56,14 @@ use self::constants::{
     LUT_FULL_UPDATE_BLACK, LUT_FULL_UPDATE_WHITE, LUT_PARTIAL_UPDATE_BLACK,
     LUT_PARTIAL_UPDATE_WHITE,
 };
+#[cfg(feature = "async")]
+use crate::interface::DisplayInterfaceAsync;
+#[cfg(feature = "async")]
+use crate::traits::{
+    AsyncBusyPin, InternalWiAdditionsAsync, QuickRefreshAsync, WaveshareDisplayAsync,
+};
+#[cfg(feature = "async")]
+use embedded_hal_async::{delay::DelayNs as AsyncDelayNs, spi::SpiDevice as AsyncSpiDevice};
 
 /// Full size buffer for use with the 1in02 EPD
 #[cfg(feature = "graphics")]
@@ -31,7 +71,7 @@ pub type Display1in02 = crate::graphics::Display<
     WIDTH,
     HEIGHT,
     false,
-    { buffer_len(WIDTH as usize, HEIGHT as usize) },
+    { crate::graphics::display_len::<Color>(WIDTH, HEIGHT) },
     Color,
 >;
 
@@ -47,6 +87,22 @@ const IS_BUSY_LOW: bool = true;
 const NUMBER_OF_BYTES: u32 = WIDTH * HEIGHT / 8;
 const SINGLE_BYTE_WRITE: bool = true;
 
+#[maybe_async_cfg::maybe(
+    sync(keep_self),
+    async(
+        feature = "async",
+        idents(
+            Epd1in02,
+            DisplayInterface,
+            WaveshareDisplay,
+            InternalWiAdditions,
+            QuickRefresh,
+            SpiDevice(async = "AsyncSpiDevice"),
+            BusyPin(async = "AsyncBusyPin"),
+            DelayNs(async = "AsyncDelayNs")
+        )
+    )
+)]
 /// Epd1in02 driver
 ///
 pub struct Epd1in02<SPI, BUSY, DC, RST, DELAY> {
@@ -58,44 +114,66 @@ pub struct Epd1in02<SPI, BUSY, DC, RST, DELAY> {
     refresh_mode: RefreshLut,
 }
 
+#[maybe_async_cfg::maybe(
+    sync(keep_self),
+    async(
+        feature = "async",
+        idents(
+            Epd1in02,
+            DisplayInterface,
+            WaveshareDisplay,
+            InternalWiAdditions,
+            QuickRefresh,
+            SpiDevice(async = "AsyncSpiDevice"),
+            BusyPin(async = "AsyncBusyPin"),
+            DelayNs(async = "AsyncDelayNs")
+        )
+    )
+)]
 impl<SPI, BUSY, DC, RST, DELAY> WaveshareDisplay<SPI, BUSY, DC, RST, DELAY>
     for Epd1in02<SPI, BUSY, DC, RST, DELAY>
 where
     SPI: SpiDevice,
-    BUSY: InputPin,
+    BUSY: BusyPin,
     DC: OutputPin,
     RST: OutputPin,
     DELAY: DelayNs,
 {
     type DisplayColor = Color;
 
-    fn new(
+    async fn new(
         spi: &mut SPI,
         busy: BUSY,
         dc: DC,
         rst: RST,
         delay: &mut DELAY,
         delay_us: Option<u32>,
-    ) -> Result<Self, SPI::Error> {
+    ) -> Result<Self, EpdError<SPI::Error>> {
+        let mut epd = Self::new_uninit(busy, dc, rst, delay_us);
+        epd.init(spi, delay).await?;
+        Ok(epd)
+    }
+
+    fn new_uninit(busy: BUSY, dc: DC, rst: RST, delay_us: Option<u32>) -> Self {
         let interface = DisplayInterface::new(busy, dc, rst, delay_us);
         let color = DEFAULT_BACKGROUND_COLOR;
 
-        let mut epd = Epd1in02 {
+        Epd1in02 {
             interface,
             color,
             is_turned_on: false,
             refresh_mode: RefreshLut::Full,
-        };
-
-        epd.init(spi, delay)?;
-
-        Ok(epd)
+        }
     }
 
-    fn sleep(&mut self, spi: &mut SPI, delay: &mut DELAY) -> Result<(), SPI::Error> {
-        self.wait_until_idle(spi, delay)?;
-        self.turn_off(spi, delay)?;
-        self.cmd_with_data(spi, Command::DeepSleep, &[0xA5])?;
+    async fn sleep(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.wait_until_idle(spi, delay).await?;
+        self.turn_off(spi, delay).await?;
+        self.cmd_with_data(spi, Command::DeepSleep, &[0xA5]).await?;
 
         // display registers are set to default value
         self.refresh_mode = RefreshLut::Full;
@@ -103,8 +181,12 @@ where
         Ok(())
     }
 
-    fn wake_up(&mut self, spi: &mut SPI, delay: &mut DELAY) -> Result<(), SPI::Error> {
-        self.init(spi, delay)
+    async fn wake_up(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.init(spi, delay).await
     }
 
     fn set_background_color(&mut self, color: Color) {
@@ -123,29 +205,32 @@ where
         HEIGHT
     }
 
-    fn update_frame(
+    async fn update_frame(
         &mut self,
         spi: &mut SPI,
         buffer: &[u8],
         delay: &mut DELAY,
-    ) -> Result<(), SPI::Error> {
-        self.wait_until_idle(spi, delay)?;
+    ) -> Result<(), EpdError<SPI::Error>> {
+        EpdError::check_buffer(buffer, NUMBER_OF_BYTES as usize)?;
+        self.wait_until_idle(spi, delay).await?;
 
-        self.set_full_mode(spi, delay)?;
+        self.set_full_mode(spi, delay).await?;
 
         let color_value = self.background_color().get_byte_value();
 
-        self.command(spi, Command::DataStartTransmission1)?;
+        self.command(spi, Command::DataStartTransmission1).await?;
         self.interface
-            .data_x_times(spi, color_value, NUMBER_OF_BYTES)?;
+            .data_x_times(spi, color_value, NUMBER_OF_BYTES)
+            .await?;
 
-        self.cmd_with_data(spi, Command::DataStartTransmission2, buffer)?;
+        self.cmd_with_data(spi, Command::DataStartTransmission2, buffer)
+            .await?;
         Ok(())
     }
 
     // Implemented as quick partial update
     // as it requires old frame update
-    fn update_partial_frame(
+    async fn update_partial_frame(
         &mut self,
         _spi: &mut SPI,
         _delay: &mut DELAY,
@@ -154,120 +239,171 @@ where
         _y: u32,
         _width: u32,
         _height: u32,
-    ) -> Result<(), SPI::Error> {
+    ) -> Result<(), EpdError<SPI::Error>> {
         unimplemented!()
     }
 
-    fn display_frame(&mut self, spi: &mut SPI, delay: &mut DELAY) -> Result<(), SPI::Error> {
-        self.wait_until_idle(spi, delay)?;
-        self.turn_on_if_turned_off(spi, delay)?;
+    async fn display_frame(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.wait_until_idle(spi, delay).await?;
+        self.turn_on_if_turned_off(spi, delay).await?;
 
-        self.command(spi, Command::DisplayRefresh)?;
-        self.wait_until_idle(spi, delay)?;
+        self.command(spi, Command::DisplayRefresh).await?;
+        self.wait_until_idle(spi, delay).await?;
         Ok(())
     }
 
-    fn update_and_display_frame(
+    async fn update_and_display_frame(
         &mut self,
         spi: &mut SPI,
         buffer: &[u8],
         delay: &mut DELAY,
-    ) -> Result<(), SPI::Error> {
-        self.update_frame(spi, buffer, delay)?;
-        self.display_frame(spi, delay)?;
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.update_frame(spi, buffer, delay).await?;
+        self.display_frame(spi, delay).await?;
         Ok(())
     }
 
-    fn clear_frame(&mut self, spi: &mut SPI, delay: &mut DELAY) -> Result<(), SPI::Error> {
-        self.wait_until_idle(spi, delay)?;
-        self.set_full_mode(spi, delay)?;
+    async fn clear_frame(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.wait_until_idle(spi, delay).await?;
+        self.set_full_mode(spi, delay).await?;
 
         let color_value = self.background_color().get_byte_value();
 
-        self.command(spi, Command::DataStartTransmission1)?;
+        self.command(spi, Command::DataStartTransmission1).await?;
         self.interface
-            .data_x_times(spi, !color_value, NUMBER_OF_BYTES)?;
+            .data_x_times(spi, !color_value, NUMBER_OF_BYTES)
+            .await?;
 
-        self.command(spi, Command::DataStartTransmission2)?;
+        self.command(spi, Command::DataStartTransmission2).await?;
         self.interface
-            .data_x_times(spi, color_value, NUMBER_OF_BYTES)?;
+            .data_x_times(spi, color_value, NUMBER_OF_BYTES)
+            .await?;
 
         Ok(())
     }
 
-    fn set_lut(
+    async fn set_lut(
         &mut self,
         spi: &mut SPI,
         _delay: &mut DELAY,
         refresh_rate: Option<RefreshLut>,
-    ) -> Result<(), SPI::Error> {
+    ) -> Result<(), EpdError<SPI::Error>> {
         let (white_lut, black_lut) = match refresh_rate {
-            Some(RefreshLut::Full) => (&LUT_FULL_UPDATE_WHITE, &LUT_FULL_UPDATE_BLACK),
             Some(RefreshLut::Quick) => (&LUT_PARTIAL_UPDATE_WHITE, &LUT_PARTIAL_UPDATE_BLACK),
+            Some(_) => (&LUT_FULL_UPDATE_WHITE, &LUT_FULL_UPDATE_BLACK),
             None => return Ok(()),
         };
 
-        self.cmd_with_data(spi, Command::SetWhiteLut, white_lut)?;
-        self.cmd_with_data(spi, Command::SetBlackLut, black_lut)?;
+        self.cmd_with_data(spi, Command::SetWhiteLut, white_lut)
+            .await?;
+        self.cmd_with_data(spi, Command::SetBlackLut, black_lut)
+            .await?;
         Ok(())
     }
 
-    fn wait_until_idle(&mut self, _spi: &mut SPI, delay: &mut DELAY) -> Result<(), SPI::Error> {
-        self.interface.wait_until_idle(delay, IS_BUSY_LOW);
-        Ok(())
+    fn interface_config_mut(&mut self) -> &mut InterfaceConfig {
+        self.interface.config_mut()
+    }
+
+    fn release(self) -> (BUSY, DC, RST) {
+        self.interface.release()
+    }
+
+    async fn wait_until_idle(
+        &mut self,
+        _spi: &mut SPI,
+        delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.interface.wait_until_idle(delay, IS_BUSY_LOW).await
     }
 }
 
+#[maybe_async_cfg::maybe(
+    sync(keep_self),
+    async(
+        feature = "async",
+        idents(
+            Epd1in02,
+            DisplayInterface,
+            WaveshareDisplay,
+            InternalWiAdditions,
+            QuickRefresh,
+            SpiDevice(async = "AsyncSpiDevice"),
+            BusyPin(async = "AsyncBusyPin"),
+            DelayNs(async = "AsyncDelayNs")
+        )
+    )
+)]
 impl<SPI, BUSY, DC, RST, DELAY> QuickRefresh<SPI, BUSY, DC, RST, DELAY>
     for Epd1in02<SPI, BUSY, DC, RST, DELAY>
 where
     SPI: SpiDevice,
-    BUSY: InputPin,
+    BUSY: BusyPin,
     DC: OutputPin,
     RST: OutputPin,
     DELAY: DelayNs,
 {
     /// To be followed immediately by update_new_frame
-    fn update_old_frame(
+    async fn update_old_frame(
         &mut self,
         spi: &mut SPI,
         buffer: &[u8],
         delay: &mut DELAY,
-    ) -> Result<(), SPI::Error> {
-        self.set_partial_mode(spi, delay)?;
-        self.set_partial_window(spi, delay, 0, 0, WIDTH, HEIGHT)?;
-
-        self.cmd_with_data(spi, Command::DataStartTransmission1, buffer)?;
+    ) -> Result<(), EpdError<SPI::Error>> {
+        EpdError::check_buffer(buffer, NUMBER_OF_BYTES as usize)?;
+        self.set_partial_mode(spi, delay).await?;
+        self.set_partial_window(spi, delay, 0, 0, WIDTH, HEIGHT)
+            .await?;
+
+        self.cmd_with_data(spi, Command::DataStartTransmission1, buffer)
+            .await?;
         Ok(())
     }
 
     /// To be used immediately after update_old_frame
-    fn update_new_frame(
+    async fn update_new_frame(
         &mut self,
         spi: &mut SPI,
         buffer: &[u8],
         _delay: &mut DELAY,
-    ) -> Result<(), SPI::Error> {
-        self.cmd_with_data(spi, Command::DataStartTransmission2, buffer)?;
+    ) -> Result<(), EpdError<SPI::Error>> {
+        EpdError::check_buffer(buffer, NUMBER_OF_BYTES as usize)?;
+        self.cmd_with_data(spi, Command::DataStartTransmission2, buffer)
+            .await?;
         Ok(())
     }
 
-    fn display_new_frame(&mut self, _spi: &mut SPI, _delay: &mut DELAY) -> Result<(), SPI::Error> {
-        unimplemented!()
+    /// Refreshes the panel with the partial LUTs selected by `update_old_frame`
+    async fn display_new_frame(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.display_frame(spi, delay).await
     }
 
-    fn update_and_display_new_frame(
+    /// To be used immediately after update_old_frame
+    async fn update_and_display_new_frame(
         &mut self,
-        _spi: &mut SPI,
-        _buffer: &[u8],
-        _delay: &mut DELAY,
-    ) -> Result<(), SPI::Error> {
-        unimplemented!()
+        spi: &mut SPI,
+        buffer: &[u8],
+        delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.update_new_frame(spi, buffer, delay).await?;
+        self.display_new_frame(spi, delay).await
     }
 
     /// To be followed immediately by update_partial_new_frame
     /// isn't faster then full update
-    fn update_partial_old_frame(
+    async fn update_partial_old_frame(
         &mut self,
         spi: &mut SPI,
         delay: &mut DELAY,
@@ -276,20 +412,25 @@ where
         y: u32,
         width: u32,
         height: u32,
-    ) -> Result<(), SPI::Error> {
+    ) -> Result<(), EpdError<SPI::Error>> {
         if !is_buffer_size_ok(buffer, width, height) {
-            panic!("Image buffer size is not correct")
+            return Err(EpdError::BufferSize {
+                expected: buffer_len(width as usize, height as usize),
+                actual: buffer.len(),
+            });
         }
 
-        self.set_partial_mode(spi, delay)?;
-        self.set_partial_window(spi, delay, x, y, width, height)?;
+        self.set_partial_mode(spi, delay).await?;
+        self.set_partial_window(spi, delay, x, y, width, height)
+            .await?;
 
-        self.cmd_with_data(spi, Command::DataStartTransmission1, buffer)?;
+        self.cmd_with_data(spi, Command::DataStartTransmission1, buffer)
+            .await?;
         Ok(())
     }
 
     /// To be used immediately after update_partial_old_frame
-    fn update_partial_new_frame(
+    async fn update_partial_new_frame(
         &mut self,
         spi: &mut SPI,
         _delay: &mut DELAY,
@@ -298,17 +439,21 @@ where
         _y: u32,
         width: u32,
         height: u32,
-    ) -> Result<(), SPI::Error> {
+    ) -> Result<(), EpdError<SPI::Error>> {
         if !is_buffer_size_ok(buffer, width, height) {
-            panic!("Image buffer size is not correct")
+            return Err(EpdError::BufferSize {
+                expected: buffer_len(width as usize, height as usize),
+                actual: buffer.len(),
+            });
         }
 
-        self.cmd_with_data(spi, Command::DataStartTransmission2, buffer)?;
+        self.cmd_with_data(spi, Command::DataStartTransmission2, buffer)
+            .await?;
         Ok(())
     }
 
     /// Isn't faster then full clear
-    fn clear_partial_frame(
+    async fn clear_partial_frame(
         &mut self,
         spi: &mut SPI,
         delay: &mut DELAY,
@@ -316,57 +461,81 @@ where
         y: u32,
         width: u32,
         height: u32,
-    ) -> Result<(), SPI::Error> {
-        self.wait_until_idle(spi, delay)?;
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.wait_until_idle(spi, delay).await?;
         // set full LUT as quick LUT requires old image
-        self.set_full_mode(spi, delay)?;
-        self.command(spi, Command::PartialIn)?;
-        self.set_partial_window(spi, delay, x, y, width, height)?;
+        self.set_full_mode(spi, delay).await?;
+        self.command(spi, Command::PartialIn).await?;
+        self.set_partial_window(spi, delay, x, y, width, height)
+            .await?;
 
         let color_value = self.background_color().get_byte_value();
         let number_of_bytes = buffer_len(width as usize, height as usize) as u32;
 
-        self.command(spi, Command::DataStartTransmission1)?;
+        self.command(spi, Command::DataStartTransmission1).await?;
         self.interface
-            .data_x_times(spi, !color_value, number_of_bytes)?;
+            .data_x_times(spi, !color_value, number_of_bytes)
+            .await?;
 
-        self.command(spi, Command::DataStartTransmission2)?;
+        self.command(spi, Command::DataStartTransmission2).await?;
         self.interface
-            .data_x_times(spi, color_value, number_of_bytes)?;
+            .data_x_times(spi, color_value, number_of_bytes)
+            .await?;
 
-        self.command(spi, Command::PartialOut)?;
+        self.command(spi, Command::PartialOut).await?;
 
         Ok(())
     }
 }
 
+#[maybe_async_cfg::maybe(
+    sync(keep_self),
+    async(
+        feature = "async",
+        idents(
+            Epd1in02,
+            DisplayInterface,
+            WaveshareDisplay,
+            InternalWiAdditions,
+            QuickRefresh,
+            SpiDevice(async = "AsyncSpiDevice"),
+            BusyPin(async = "AsyncBusyPin"),
+            DelayNs(async = "AsyncDelayNs")
+        )
+    )
+)]
 impl<SPI, BUSY, DC, RST, DELAY> InternalWiAdditions<SPI, BUSY, DC, RST, DELAY>
     for Epd1in02<SPI, BUSY, DC, RST, DELAY>
 where
     SPI: SpiDevice,
-    BUSY: InputPin,
+    BUSY: BusyPin,
     DC: OutputPin,
     RST: OutputPin,
     DELAY: DelayNs,
 {
-    fn init(&mut self, spi: &mut SPI, delay: &mut DELAY) -> Result<(), SPI::Error> {
+    async fn init(&mut self, spi: &mut SPI, delay: &mut DELAY) -> Result<(), EpdError<SPI::Error>> {
         // Reset the device
-        self.interface.reset(delay, 20_000, 2000);
+        self.interface.reset(delay, 20_000, 2000).await?;
 
         // Set the panel settings: LUT from register
-        self.cmd_with_data(spi, Command::PanelSetting, &[0x6F])?;
+        self.cmd_with_data(spi, Command::PanelSetting, &[0x6F])
+            .await?;
 
         // Set the power settings: VGH=16V, VGL=-16V, VDH=11V, VDL=-11V
-        self.cmd_with_data(spi, Command::PowerSetting, &[0x03, 0x00, 0x2b, 0x2b])?;
+        self.cmd_with_data(spi, Command::PowerSetting, &[0x03, 0x00, 0x2b, 0x2b])
+            .await?;
 
         // Set the charge pump settings: 50ms, Strength 4, 8kHz
-        self.cmd_with_data(spi, Command::ChargePumpSetting, &[0x3F])?;
+        self.cmd_with_data(spi, Command::ChargePumpSetting, &[0x3F])
+            .await?;
 
         // Set LUT option: no All-Gate-ON
-        self.cmd_with_data(spi, Command::LutOption, &[0x00, 0x00])?;
+        self.cmd_with_data(spi, Command::LutOption, &[0x00, 0x00])
+            .await?;
 
         // Set the clock frequency: 50 Hz
-        self.cmd_with_data(spi, Command::PllControl, &[0x17])?;
+        self.cmd_with_data(spi, Command::PllControl, &[0x17])
+            .await?;
 
         // Set Vcom and data interval: default
         // set the border color the same as background color
@@ -374,101 +543,137 @@ where
             Color::Black => 0x57,
             Color::White => 0x97,
         };
-        self.cmd_with_data(spi, Command::VcomAndDataIntervalSetting, &[value])?;
+        self.cmd_with_data(spi, Command::VcomAndDataIntervalSetting, &[value])
+            .await?;
 
         // Set the non-overlapping period of Gate and Source: 24us
-        self.cmd_with_data(spi, Command::TconSetting, &[0x22])?;
+        self.cmd_with_data(spi, Command::TconSetting, &[0x22])
+            .await?;
 
         // Set the real resolution
-        self.send_resolution(spi)?;
+        self.send_resolution(spi).await?;
 
         // Set Vcom DC value: -1 V
-        self.cmd_with_data(spi, Command::VcomDcSetting, &[0x12])?;
+        self.cmd_with_data(spi, Command::VcomDcSetting, &[0x12])
+            .await?;
 
         // Set pover saving settings
-        self.cmd_with_data(spi, Command::PowerSaving, &[0x33])?;
+        self.cmd_with_data(spi, Command::PowerSaving, &[0x33])
+            .await?;
 
-        self.set_lut(spi, delay, Some(self.refresh_mode))?;
+        self.set_lut(spi, delay, Some(self.refresh_mode)).await?;
 
-        self.wait_until_idle(spi, delay)?;
+        self.wait_until_idle(spi, delay).await?;
         Ok(())
     }
 }
 
+#[maybe_async_cfg::maybe(
+    sync(keep_self),
+    async(
+        feature = "async",
+        idents(
+            Epd1in02,
+            DisplayInterface,
+            WaveshareDisplay,
+            InternalWiAdditions,
+            QuickRefresh,
+            SpiDevice(async = "AsyncSpiDevice"),
+            BusyPin(async = "AsyncBusyPin"),
+            DelayNs(async = "AsyncDelayNs")
+        )
+    )
+)]
 impl<SPI, BUSY, DC, RST, DELAY> Epd1in02<SPI, BUSY, DC, RST, DELAY>
 where
     SPI: SpiDevice,
-    BUSY: InputPin,
+    BUSY: BusyPin,
     DC: OutputPin,
     RST: OutputPin,
     DELAY: DelayNs,
 {
-    fn command(&mut self, spi: &mut SPI, command: Command) -> Result<(), SPI::Error> {
-        self.interface.cmd(spi, command)
+    async fn command(
+        &mut self,
+        spi: &mut SPI,
+        command: Command,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.interface.cmd(spi, command).await
     }
 
-    fn send_data(&mut self, spi: &mut SPI, data: &[u8]) -> Result<(), SPI::Error> {
-        self.interface.data(spi, data)
+    async fn send_data(&mut self, spi: &mut SPI, data: &[u8]) -> Result<(), EpdError<SPI::Error>> {
+        self.interface.data(spi, data).await
     }
 
-    fn cmd_with_data(
+    async fn cmd_with_data(
         &mut self,
         spi: &mut SPI,
         command: Command,
         data: &[u8],
-    ) -> Result<(), SPI::Error> {
-        self.interface.cmd_with_data(spi, command, data)
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.interface.cmd_with_data(spi, command, data).await
     }
 
-    fn send_resolution(&mut self, spi: &mut SPI) -> Result<(), SPI::Error> {
+    async fn send_resolution(&mut self, spi: &mut SPI) -> Result<(), EpdError<SPI::Error>> {
         let w = self.width();
         let h = self.height();
 
-        self.command(spi, Command::TconResolution)?;
-        self.send_data(spi, &[h as u8])?;
-        self.send_data(spi, &[w as u8])
+        self.command(spi, Command::TconResolution).await?;
+        self.send_data(spi, &[h as u8]).await?;
+        self.send_data(spi, &[w as u8]).await
     }
 
     /// PowerOn command
-    fn turn_on_if_turned_off(
+    async fn turn_on_if_turned_off(
         &mut self,
         spi: &mut SPI,
         delay: &mut DELAY,
-    ) -> Result<(), SPI::Error> {
+    ) -> Result<(), EpdError<SPI::Error>> {
         if !self.is_turned_on {
-            self.command(spi, Command::PowerOn)?;
-            self.wait_until_idle(spi, delay)?;
+            self.command(spi, Command::PowerOn).await?;
+            self.wait_until_idle(spi, delay).await?;
             self.is_turned_on = true;
         }
         Ok(())
     }
 
-    fn turn_off(&mut self, spi: &mut SPI, delay: &mut DELAY) -> Result<(), SPI::Error> {
-        self.command(spi, Command::PowerOff)?;
-        self.wait_until_idle(spi, delay)?;
+    async fn turn_off(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.command(spi, Command::PowerOff).await?;
+        self.wait_until_idle(spi, delay).await?;
         self.is_turned_on = false;
         Ok(())
     }
 
-    fn set_full_mode(&mut self, spi: &mut SPI, delay: &mut DELAY) -> Result<(), SPI::Error> {
+    async fn set_full_mode(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>> {
         if self.refresh_mode != RefreshLut::Full {
-            self.command(spi, Command::PartialOut)?;
-            self.set_lut(spi, delay, Some(RefreshLut::Full))?;
+            self.command(spi, Command::PartialOut).await?;
+            self.set_lut(spi, delay, Some(RefreshLut::Full)).await?;
             self.refresh_mode = RefreshLut::Full;
         }
         Ok(())
     }
 
-    fn set_partial_mode(&mut self, spi: &mut SPI, delay: &mut DELAY) -> Result<(), SPI::Error> {
+    async fn set_partial_mode(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>> {
         if self.refresh_mode != RefreshLut::Quick {
-            self.command(spi, Command::PartialIn)?;
-            self.set_lut(spi, delay, Some(RefreshLut::Quick))?;
+            self.command(spi, Command::PartialIn).await?;
+            self.set_lut(spi, delay, Some(RefreshLut::Quick)).await?;
             self.refresh_mode = RefreshLut::Quick;
         }
         Ok(())
     }
 
-    fn set_partial_window(
+    async fn set_partial_window(
         &mut self,
         spi: &mut SPI,
         _delay: &mut DELAY,
@@ -476,7 +681,7 @@ where
         y: u32,
         width: u32,
         height: u32,
-    ) -> Result<(), SPI::Error> {
+    ) -> Result<(), EpdError<SPI::Error>> {
         if !is_window_size_ok(x, y, width, height) {
             panic!("Partial update window size is not correct")
         }
@@ -491,7 +696,8 @@ where
                 (y + height - 1) as u8,
                 0x00,
             ],
-        )?;
+        )
+        .await?;
 
         Ok(())
     }
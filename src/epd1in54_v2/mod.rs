@@ -1,6 +1,9 @@
 //! A simple Driver for the Waveshare 1.54" E-Ink Display via SPI
 //!
 //! GDEH0154D67
+//!
+//! Besides the quick partial refresh, the full refresh has a [fast mode](DisplayMode::Fast) taking
+//! about 1.5s instead of 3s.
 
 /// Width of the display
 pub const WIDTH: u32 = 200;
@@ -8,10 +11,17 @@ pub const WIDTH: u32 = 200;
 pub const HEIGHT: u32 = 200;
 /// Default Background Color
 pub const DEFAULT_BACKGROUND_COLOR: Color = Color::White;
+pub use crate::waveform::LUT_LENGTH;
 const IS_BUSY_LOW: bool = false;
 const SINGLE_BYTE_WRITE: bool = true;
 
+use crate::error::EpdError;
+use crate::pin::BusyPin;
+#[cfg(feature = "async")]
+use crate::traits::AsyncBusyPin;
 use embedded_hal::{delay::*, digital::*, spi::SpiDevice};
+#[cfg(feature = "async")]
+use embedded_hal_async::{delay::DelayNs as AsyncDelayNs, spi::SpiDevice as AsyncSpiDevice};
 
 use crate::type_a::command::Command;
 
@@ -20,14 +30,49 @@ use crate::epd1in54_v2::constants::{LUT_FULL_UPDATE, LUT_PARTIAL_UPDATE};
 
 use crate::color::Color;
 
-use crate::traits::{RefreshLut, WaveshareDisplay};
+use crate::traits::{InterfaceConfig, NonBlockingRefresh, RefreshLut, WaveshareDisplay};
+#[cfg(feature = "async")]
+use crate::traits::{NonBlockingRefreshAsync, WaveshareDisplayAsync};
 
 use crate::interface::DisplayInterface;
+#[cfg(feature = "async")]
+use crate::interface::DisplayInterfaceAsync;
+use crate::ram_addressing::Ssd1680;
+use crate::scan::ScanConfig;
+use crate::waveform::Waveform;
 
 #[cfg(feature = "graphics")]
 pub use crate::epd1in54::Display1in54;
 
+/// Waveform used by the full refreshes, see [`Epd1in54::set_display_mode`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DisplayMode {
+    /// The full refresh LUT of the driver, about 3s
+    #[default]
+    Full,
+    /// The waveform of the controller for a high temperature, about 1.5s but with a slightly
+    /// less clean result
+    Fast,
+}
+
+#[maybe_async_cfg::maybe(
+    sync(keep_self),
+    async(
+        feature = "async",
+        idents(
+            Epd1in54,
+            DisplayInterface,
+            WaveshareDisplay,
+            SpiDevice(async = "AsyncSpiDevice"),
+            BusyPin(async = "AsyncBusyPin"),
+            DelayNs(async = "AsyncDelayNs")
+        )
+    )
+)]
 /// Epd1in54 driver
+///
+/// With the `async` feature, `Epd1in54Async` is the same driver for the async SPI and delay
+/// traits, generated from the same code.
 pub struct Epd1in54<SPI, BUSY, DC, RST, DELAY> {
     /// SPI
     interface: DisplayInterface<SPI, BUSY, DC, RST, DELAY, SINGLE_BYTE_WRITE>,
@@ -36,62 +81,203 @@ pub struct Epd1in54<SPI, BUSY, DC, RST, DELAY> {
 
     /// Refresh LUT
     refresh: RefreshLut,
+    /// Waveform of the full refreshes
+    mode: DisplayMode,
+    /// Scan directions of the panel
+    scan: ScanConfig,
 }
 
+#[maybe_async_cfg::maybe(
+    sync(keep_self),
+    async(
+        feature = "async",
+        idents(
+            Epd1in54,
+            DisplayInterface,
+            WaveshareDisplay,
+            SpiDevice(async = "AsyncSpiDevice"),
+            BusyPin(async = "AsyncBusyPin"),
+            DelayNs(async = "AsyncDelayNs")
+        )
+    )
+)]
 impl<SPI, BUSY, DC, RST, DELAY> Epd1in54<SPI, BUSY, DC, RST, DELAY>
 where
     SPI: SpiDevice,
-    BUSY: InputPin,
+    BUSY: BusyPin,
     DC: OutputPin,
     RST: OutputPin,
     DELAY: DelayNs,
 {
-    fn init(&mut self, spi: &mut SPI, delay: &mut DELAY) -> Result<(), SPI::Error> {
-        self.interface.reset(delay, 10_000, 10_000);
-        self.wait_until_idle(spi, delay)?;
-        self.interface.cmd(spi, Command::SwReset)?;
-        self.wait_until_idle(spi, delay)?;
+    async fn init(&mut self, spi: &mut SPI, delay: &mut DELAY) -> Result<(), EpdError<SPI::Error>> {
+        self.interface.reset(delay, 10_000, 10_000).await?;
+        self.wait_until_idle(spi, delay).await?;
+        self.interface.cmd(spi, Command::SwReset).await?;
+        self.wait_until_idle(spi, delay).await?;
 
         // 3 Databytes:
         // A[7:0]
         // 0.. A[8]
         // 0.. B[2:0]
         // Default Values: A = Height of Screen (0x127), B = 0x00 (GD, SM and TB=0?)
-        self.interface.cmd_with_data(
-            spi,
-            Command::DriverOutputControl,
-            &[(HEIGHT - 1) as u8, 0x0, 0x00],
-        )?;
+        self.interface
+            .cmd_with_data(
+                spi,
+                Command::DriverOutputControl,
+                &[(HEIGHT - 1) as u8, 0x0, self.scan.driver_output()],
+            )
+            .await?;
 
         self.interface
-            .cmd_with_data(spi, Command::DataEntryModeSetting, &[0x3])?;
+            .cmd_with_data(
+                spi,
+                Command::DataEntryModeSetting,
+                &[self.scan.data_entry_mode()],
+            )
+            .await?;
 
-        self.set_ram_area(spi, delay, 0, 0, WIDTH - 1, HEIGHT - 1)?;
+        self.set_ram_area(spi, delay, 0, 0, WIDTH - 1, HEIGHT - 1)
+            .await?;
 
-        self.interface.cmd_with_data(
-            spi,
-            Command::TemperatureSensorSelection,
-            &[0x80], // 0x80: internal temperature sensor
-        )?;
+        self.interface
+            .cmd_with_data(
+                spi,
+                Command::TemperatureSensorSelection,
+                &[0x80], // 0x80: internal temperature sensor
+            )
+            .await?;
 
         self.interface
-            .cmd_with_data(spi, Command::TemperatureSensorControl, &[0xB1, 0x20])?;
+            .cmd_with_data(spi, Command::TemperatureSensorControl, &[0xB1, 0x20])
+            .await?;
 
-        self.set_ram_counter(spi, delay, 0, 0)?;
+        self.set_ram_counter(spi, delay, 0, 0).await?;
 
         //Initialize the lookup table with a refresh waveform
-        self.set_lut(spi, delay, None)?;
+        self.set_lut(spi, delay, None).await?;
+
+        self.wait_until_idle(spi, delay).await?;
+        Ok(())
+    }
 
-        self.wait_until_idle(spi, delay)?;
+    /// Sets the waveform of the full refreshes, loaded right away if the full LUT is in use
+    pub async fn set_display_mode(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+        mode: DisplayMode,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.mode = mode;
+        if self.refresh == RefreshLut::Full {
+            self.set_lut(spi, delay, None).await?;
+        }
         Ok(())
     }
+
+    /// Current waveform of the full refreshes
+    pub fn display_mode(&self) -> DisplayMode {
+        self.mode
+    }
+
+    /// Sets the scan directions of the panel, for clone glass showing the image mirrored or
+    /// upside down, see [`scan`](crate::scan)
+    ///
+    /// The next frames are written with the new directions.
+    pub async fn set_scan_config(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+        config: ScanConfig,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.scan = config;
+        self.wait_until_idle(spi, delay).await?;
+        self.interface
+            .cmd_with_data(
+                spi,
+                Command::DriverOutputControl,
+                &[(HEIGHT - 1) as u8, 0x0, config.driver_output()],
+            )
+            .await?;
+        self.interface
+            .cmd_with_data(
+                spi,
+                Command::DataEntryModeSetting,
+                &[config.data_entry_mode()],
+            )
+            .await?;
+        self.use_full_frame(spi, delay).await
+    }
+
+    /// Returns the scan directions of the panel
+    pub fn scan_config(&self) -> ScanConfig {
+        self.scan
+    }
+
+    /// Loads `waveform` for the next refreshes, in place of the LUT selected with
+    /// [`set_lut`](WaveshareDisplay::set_lut), see [`waveform`](crate::waveform)
+    ///
+    /// The selected LUT is loaded again by `set_lut` and by the wake-up.
+    pub async fn set_waveform(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+        waveform: &Waveform,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.set_lut_helper(spi, delay, &waveform.to_bytes()).await
+    }
+
+    /// Updates and refreshes a region with a custom LUT of [`LUT_LENGTH`] bytes, e.g. a gentler
+    /// waveform for a clock updated every minute
+    ///
+    /// The LUT in use before is restored afterwards, even if the refresh failed.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn update_and_display_partial_frame_with_lut(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+        lut: &[u8; LUT_LENGTH],
+        buffer: &[u8],
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        let mut result = self.set_lut_helper(spi, delay, lut).await;
+        if result.is_ok() {
+            result = self
+                .update_partial_frame(spi, delay, buffer, x, y, width, height)
+                .await;
+        }
+        if result.is_ok() {
+            result = self.display_frame(spi, delay).await;
+        }
+        if result.is_ok() {
+            result = self.wait_until_idle(spi, delay).await;
+        }
+        let restored = self.set_lut(spi, delay, None).await;
+        result.and(restored)
+    }
 }
 
+#[maybe_async_cfg::maybe(
+    sync(keep_self),
+    async(
+        feature = "async",
+        idents(
+            Epd1in54,
+            DisplayInterface,
+            WaveshareDisplay,
+            SpiDevice(async = "AsyncSpiDevice"),
+            BusyPin(async = "AsyncBusyPin"),
+            DelayNs(async = "AsyncDelayNs")
+        )
+    )
+)]
 impl<SPI, BUSY, DC, RST, DELAY> WaveshareDisplay<SPI, BUSY, DC, RST, DELAY>
     for Epd1in54<SPI, BUSY, DC, RST, DELAY>
 where
     SPI: SpiDevice,
-    BUSY: InputPin,
+    BUSY: BusyPin,
     DC: OutputPin,
     RST: OutputPin,
     DELAY: DelayNs,
@@ -105,53 +291,67 @@ where
         HEIGHT
     }
 
-    fn new(
+    async fn new(
         spi: &mut SPI,
         busy: BUSY,
         dc: DC,
         rst: RST,
         delay: &mut DELAY,
         delay_us: Option<u32>,
-    ) -> Result<Self, SPI::Error> {
+    ) -> Result<Self, EpdError<SPI::Error>> {
+        let mut epd = Self::new_uninit(busy, dc, rst, delay_us);
+        epd.init(spi, delay).await?;
+        Ok(epd)
+    }
+
+    fn new_uninit(busy: BUSY, dc: DC, rst: RST, delay_us: Option<u32>) -> Self {
         let interface = DisplayInterface::new(busy, dc, rst, delay_us);
 
-        let mut epd = Epd1in54 {
+        Epd1in54 {
             interface,
             background_color: DEFAULT_BACKGROUND_COLOR,
             refresh: RefreshLut::Full,
-        };
-
-        epd.init(spi, delay)?;
-
-        Ok(epd)
+            mode: DisplayMode::default(),
+            scan: ScanConfig::default(),
+        }
     }
 
-    fn wake_up(&mut self, spi: &mut SPI, delay: &mut DELAY) -> Result<(), SPI::Error> {
-        self.init(spi, delay)
+    async fn wake_up(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.init(spi, delay).await
     }
 
-    fn sleep(&mut self, spi: &mut SPI, delay: &mut DELAY) -> Result<(), SPI::Error> {
-        self.wait_until_idle(spi, delay)?;
+    async fn sleep(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.wait_until_idle(spi, delay).await?;
         self.interface
-            .cmd_with_data(spi, Command::DeepSleepMode, &[0x01])?;
+            .cmd_with_data(spi, Command::DeepSleepMode, &[0x01])
+            .await?;
         Ok(())
     }
 
-    fn update_frame(
+    async fn update_frame(
         &mut self,
         spi: &mut SPI,
         buffer: &[u8],
         delay: &mut DELAY,
-    ) -> Result<(), SPI::Error> {
-        self.wait_until_idle(spi, delay)?;
-        self.use_full_frame(spi, delay)?;
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.wait_until_idle(spi, delay).await?;
+        self.use_full_frame(spi, delay).await?;
         self.interface
-            .cmd_with_data(spi, Command::WriteRam, buffer)?;
+            .cmd_with_data(spi, Command::WriteRam, buffer)
+            .await?;
         Ok(())
     }
 
     //TODO: update description: last 3 bits will be ignored for width and x_pos
-    fn update_partial_frame(
+    async fn update_partial_frame(
         &mut self,
         spi: &mut SPI,
         delay: &mut DELAY,
@@ -160,57 +360,69 @@ where
         y: u32,
         width: u32,
         height: u32,
-    ) -> Result<(), SPI::Error> {
-        self.wait_until_idle(spi, delay)?;
-        self.set_ram_area(spi, delay, x, y, x + width, y + height)?;
-        self.set_ram_counter(spi, delay, x, y)?;
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.wait_until_idle(spi, delay).await?;
+        self.set_ram_area(spi, delay, x, y, x + width, y + height)
+            .await?;
+        self.set_ram_counter(spi, delay, x, y).await?;
 
         self.interface
-            .cmd_with_data(spi, Command::WriteRam, buffer)?;
+            .cmd_with_data(spi, Command::WriteRam, buffer)
+            .await?;
         Ok(())
     }
 
-    fn display_frame(&mut self, spi: &mut SPI, delay: &mut DELAY) -> Result<(), SPI::Error> {
-        self.wait_until_idle(spi, delay)?;
-        if self.refresh == RefreshLut::Full {
-            self.interface
-                .cmd_with_data(spi, Command::DisplayUpdateControl2, &[0xC7])?;
-        } else if self.refresh == RefreshLut::Quick {
-            self.interface
-                .cmd_with_data(spi, Command::DisplayUpdateControl2, &[0xCF])?;
-        }
+    async fn display_frame(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.wait_until_idle(spi, delay).await?;
+        let sequence = match self.refresh {
+            RefreshLut::Quick => 0xCF,
+            _ => 0xC7,
+        };
+        self.interface
+            .cmd_with_data(spi, Command::DisplayUpdateControl2, &[sequence])
+            .await?;
 
-        self.interface.cmd(spi, Command::MasterActivation)?;
+        self.interface.cmd(spi, Command::MasterActivation).await?;
         // MASTER Activation should not be interupted to avoid currption of panel images
         // therefore a terminate command is send
-        self.interface.cmd(spi, Command::Nop)?;
+        self.interface.cmd(spi, Command::Nop).await?;
         Ok(())
     }
 
-    fn update_and_display_frame(
+    async fn update_and_display_frame(
         &mut self,
         spi: &mut SPI,
         buffer: &[u8],
         delay: &mut DELAY,
-    ) -> Result<(), SPI::Error> {
-        self.update_frame(spi, buffer, delay)?;
-        self.display_frame(spi, delay)?;
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.update_frame(spi, buffer, delay).await?;
+        self.display_frame(spi, delay).await?;
         Ok(())
     }
 
-    fn clear_frame(&mut self, spi: &mut SPI, delay: &mut DELAY) -> Result<(), SPI::Error> {
-        self.wait_until_idle(spi, delay)?;
-        self.use_full_frame(spi, delay)?;
+    async fn clear_frame(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.wait_until_idle(spi, delay).await?;
+        self.use_full_frame(spi, delay).await?;
 
         // clear the ram with the background color
         let color = self.background_color.get_byte_value();
 
-        self.interface.cmd(spi, Command::WriteRam)?;
+        self.interface.cmd(spi, Command::WriteRam).await?;
         self.interface
-            .data_x_times(spi, color, WIDTH / 8 * HEIGHT)?;
-        self.interface.cmd(spi, Command::WriteRam2)?;
+            .data_x_times(spi, color, WIDTH / 8 * HEIGHT)
+            .await?;
+        self.interface.cmd(spi, Command::WriteRam2).await?;
         self.interface
-            .data_x_times(spi, color, WIDTH / 8 * HEIGHT)?;
+            .data_x_times(spi, color, WIDTH / 8 * HEIGHT)
+            .await?;
         Ok(())
     }
 
@@ -222,66 +434,133 @@ where
         &self.background_color
     }
 
-    fn set_lut(
+    async fn set_lut(
         &mut self,
         spi: &mut SPI,
         delay: &mut DELAY,
         refresh_rate: Option<RefreshLut>,
-    ) -> Result<(), SPI::Error> {
+    ) -> Result<(), EpdError<SPI::Error>> {
         if let Some(refresh_lut) = refresh_rate {
-            self.refresh = refresh_lut;
+            self.refresh = refresh_lut.or_full(&[RefreshLut::Quick, RefreshLut::Fast]);
         }
-        match self.refresh {
-            RefreshLut::Full => self.set_lut_helper(spi, delay, &LUT_FULL_UPDATE),
-            RefreshLut::Quick => self.set_lut_helper(spi, delay, &LUT_PARTIAL_UPDATE),
+        match (self.refresh, self.mode) {
+            (RefreshLut::Quick, _) => self.set_lut_helper(spi, delay, &LUT_PARTIAL_UPDATE).await,
+            (RefreshLut::Fast, _) | (_, DisplayMode::Fast) => self.load_fast_lut(spi, delay).await,
+            _ => self.set_lut_helper(spi, delay, &LUT_FULL_UPDATE).await,
         }?;
 
         // Additional configuration required only for partial updates
         if self.refresh == RefreshLut::Quick {
-            self.interface.cmd_with_data(
-                spi,
-                Command::WriteOtpSelection,
-                &[0x0, 0x0, 0x0, 0x0, 0x0, 0x40, 0x0, 0x0, 0x0, 0x0],
-            )?;
             self.interface
-                .cmd_with_data(spi, Command::BorderWaveformControl, &[0x80])?;
+                .cmd_with_data(
+                    spi,
+                    Command::WriteOtpSelection,
+                    &[0x0, 0x0, 0x0, 0x0, 0x0, 0x40, 0x0, 0x0, 0x0, 0x0],
+                )
+                .await?;
             self.interface
-                .cmd_with_data(spi, Command::DisplayUpdateControl2, &[0xc0])?;
-            self.interface.cmd(spi, Command::MasterActivation)?;
+                .cmd_with_data(spi, Command::BorderWaveformControl, &[0x80])
+                .await?;
+            self.interface
+                .cmd_with_data(spi, Command::DisplayUpdateControl2, &[0xc0])
+                .await?;
+            self.interface.cmd(spi, Command::MasterActivation).await?;
             // MASTER Activation should not be interupted to avoid currption of panel images
             // therefore a terminate command is send
-            self.interface.cmd(spi, Command::Nop)?;
+            self.interface.cmd(spi, Command::Nop).await?;
         }
         Ok(())
     }
 
-    fn wait_until_idle(&mut self, _spi: &mut SPI, delay: &mut DELAY) -> Result<(), SPI::Error> {
-        self.interface.wait_until_idle(delay, IS_BUSY_LOW);
-        Ok(())
+    fn interface_config_mut(&mut self) -> &mut InterfaceConfig {
+        self.interface.config_mut()
+    }
+
+    fn release(self) -> (BUSY, DC, RST) {
+        self.interface.release()
+    }
+
+    async fn wait_until_idle(
+        &mut self,
+        _spi: &mut SPI,
+        delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.interface.wait_until_idle(delay, IS_BUSY_LOW).await
+    }
+}
+
+#[maybe_async_cfg::maybe(
+    sync(keep_self),
+    async(
+        feature = "async",
+        idents(
+            Epd1in54,
+            NonBlockingRefresh,
+            SpiDevice(async = "AsyncSpiDevice"),
+            BusyPin(async = "AsyncBusyPin"),
+            DelayNs(async = "AsyncDelayNs")
+        )
+    )
+)]
+impl<SPI, BUSY, DC, RST, DELAY> NonBlockingRefresh<SPI, BUSY, DC, RST, DELAY>
+    for Epd1in54<SPI, BUSY, DC, RST, DELAY>
+where
+    SPI: SpiDevice,
+    BUSY: BusyPin,
+    DC: OutputPin,
+    RST: OutputPin,
+    DELAY: DelayNs,
+{
+    async fn display_frame_nonblocking(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        // the refresh doesn't wait for its end already
+        self.display_frame(spi, delay).await
+    }
+
+    fn is_refresh_complete(&mut self) -> Result<bool, EpdError<SPI::Error>> {
+        Ok(!self.interface.is_busy(IS_BUSY_LOW)?)
     }
 }
 
+#[maybe_async_cfg::maybe(
+    sync(keep_self),
+    async(
+        feature = "async",
+        idents(
+            Epd1in54,
+            DisplayInterface,
+            WaveshareDisplay,
+            SpiDevice(async = "AsyncSpiDevice"),
+            BusyPin(async = "AsyncBusyPin"),
+            DelayNs(async = "AsyncDelayNs")
+        )
+    )
+)]
 impl<SPI, BUSY, DC, RST, DELAY> Epd1in54<SPI, BUSY, DC, RST, DELAY>
 where
     SPI: SpiDevice,
-    BUSY: InputPin,
+    BUSY: BusyPin,
     DC: OutputPin,
     RST: OutputPin,
     DELAY: DelayNs,
 {
-    pub(crate) fn use_full_frame(
+    pub(crate) async fn use_full_frame(
         &mut self,
         spi: &mut SPI,
         delay: &mut DELAY,
-    ) -> Result<(), SPI::Error> {
+    ) -> Result<(), EpdError<SPI::Error>> {
         // choose full frame/ram
-        self.set_ram_area(spi, delay, 0, 0, WIDTH - 1, HEIGHT - 1)?;
+        self.set_ram_area(spi, delay, 0, 0, WIDTH - 1, HEIGHT - 1)
+            .await?;
 
         // start from the beginning
-        self.set_ram_counter(spi, delay, 0, 0)
+        self.set_ram_counter(spi, delay, 0, 0).await
     }
 
-    pub(crate) fn set_ram_area(
+    pub(crate) async fn set_ram_area(
         &mut self,
         spi: &mut SPI,
         delay: &mut DELAY,
@@ -289,82 +568,92 @@ where
         start_y: u32,
         end_x: u32,
         end_y: u32,
-    ) -> Result<(), SPI::Error> {
-        self.wait_until_idle(spi, delay)?;
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.wait_until_idle(spi, delay).await?;
         assert!(start_x < end_x);
         assert!(start_y < end_y);
 
-        // x is positioned in bytes, so the last 3 bits which show the position inside a byte in the ram
-        // aren't relevant
-        self.interface.cmd_with_data(
-            spi,
-            Command::SetRamXAddressStartEndPosition,
-            &[(start_x >> 3) as u8, (end_x >> 3) as u8],
-        )?;
-
-        // 2 Databytes: A[7:0] & 0..A[8] for each - start and end
-        self.interface.cmd_with_data(
-            spi,
-            Command::SetRamYAddressStartEndPosition,
-            &[
-                start_y as u8,
-                (start_y >> 8) as u8,
-                end_y as u8,
-                (end_y >> 8) as u8,
-            ],
-        )?;
-        Ok(())
+        let scan = self.scan;
+        self.interface
+            .set_ram_area::<Ssd1680>(
+                spi,
+                scan.x(start_x, WIDTH),
+                scan.y(start_y, HEIGHT),
+                scan.x(end_x, WIDTH),
+                scan.y(end_y, HEIGHT),
+            )
+            .await
     }
 
-    pub(crate) fn set_ram_counter(
+    pub(crate) async fn set_ram_counter(
         &mut self,
         spi: &mut SPI,
         delay: &mut DELAY,
         x: u32,
         y: u32,
-    ) -> Result<(), SPI::Error> {
-        self.wait_until_idle(spi, delay)?;
-        // x is positioned in bytes, so the last 3 bits which show the position inside a byte in the ram
-        // aren't relevant
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.wait_until_idle(spi, delay).await?;
+        let (x, y) = (self.scan.x(x, WIDTH), self.scan.y(y, HEIGHT));
+        self.interface.set_ram_counter::<Ssd1680>(spi, x, y).await
+    }
+
+    /// Loads the waveform of the controller for 100°C, which is faster than the others
+    async fn load_fast_lut(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.wait_until_idle(spi, delay).await?;
+        // load the waveform for the measured temperature first
         self.interface
-            .cmd_with_data(spi, Command::SetRamXAddressCounter, &[(x >> 3) as u8])?;
-
-        // 2 Databytes: A[7:0] & 0..A[8]
-        self.interface.cmd_with_data(
-            spi,
-            Command::SetRamYAddressCounter,
-            &[y as u8, (y >> 8) as u8],
-        )?;
-        Ok(())
+            .cmd_with_data(spi, Command::DisplayUpdateControl2, &[0xB1])
+            .await?;
+        self.interface.cmd(spi, Command::MasterActivation).await?;
+        self.wait_until_idle(spi, delay).await?;
+
+        // then overwrite the temperature and load the matching waveform
+        self.interface
+            .cmd_with_data(spi, Command::TemperatureSensorControl, &[0x64, 0x00])
+            .await?;
+        self.interface
+            .cmd_with_data(spi, Command::DisplayUpdateControl2, &[0x91])
+            .await?;
+        self.interface.cmd(spi, Command::MasterActivation).await?;
+        self.wait_until_idle(spi, delay).await
     }
 
-    fn set_lut_helper(
+    async fn set_lut_helper(
         &mut self,
         spi: &mut SPI,
         delay: &mut DELAY,
-        buffer: &[u8],
-    ) -> Result<(), SPI::Error> {
-        self.wait_until_idle(spi, delay)?;
-        assert!(buffer.len() == 159);
+        buffer: &[u8; LUT_LENGTH],
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.wait_until_idle(spi, delay).await?;
 
         self.interface
-            .cmd_with_data(spi, Command::WriteLutRegister, &buffer[0..153])?;
+            .cmd_with_data(spi, Command::WriteLutRegister, &buffer[0..153])
+            .await?;
 
         self.interface
-            .cmd_with_data(spi, Command::WriteLutRegisterEnd, &[buffer[153]])?;
+            .cmd_with_data(spi, Command::WriteLutRegisterEnd, &[buffer[153]])
+            .await?;
 
-        self.wait_until_idle(spi, delay)?;
+        self.wait_until_idle(spi, delay).await?;
 
         self.interface
-            .cmd_with_data(spi, Command::GateDrivingVoltage, &[buffer[154]])?;
+            .cmd_with_data(spi, Command::GateDrivingVoltage, &[buffer[154]])
+            .await?;
 
-        self.interface.cmd_with_data(
-            spi,
-            Command::SourceDrivingVoltage,
-            &[buffer[155], buffer[156], buffer[157]],
-        )?;
         self.interface
-            .cmd_with_data(spi, Command::WriteVcomRegister, &[buffer[158]])?;
+            .cmd_with_data(
+                spi,
+                Command::SourceDrivingVoltage,
+                &[buffer[155], buffer[156], buffer[157]],
+            )
+            .await?;
+        self.interface
+            .cmd_with_data(spi, Command::WriteVcomRegister, &[buffer[158]])
+            .await?;
 
         Ok(())
     }
@@ -379,5 +668,119 @@ mod tests {
         assert_eq!(WIDTH, 200);
         assert_eq!(HEIGHT, 200);
         assert_eq!(DEFAULT_BACKGROUND_COLOR, Color::White);
+        assert_eq!(DisplayMode::default(), DisplayMode::Full);
+    }
+
+    #[cfg(feature = "async")]
+    #[test]
+    fn async_variant_sends_the_same_bytes() {
+        use crate::test_utils::block_on;
+        use core::convert::Infallible;
+        use embedded_hal::spi::{ErrorType, Operation};
+
+        // number of bytes written and a checksum of them
+        #[derive(Default, PartialEq, Debug)]
+        struct Spi(usize, u32);
+
+        impl ErrorType for Spi {
+            type Error = Infallible;
+        }
+
+        impl SpiDevice for Spi {
+            fn transaction(
+                &mut self,
+                operations: &mut [Operation<'_, u8>],
+            ) -> Result<(), Infallible> {
+                for operation in operations {
+                    if let Operation::Write(words) = operation {
+                        for word in words.iter() {
+                            self.0 += 1;
+                            self.1 = self.1.rotate_left(5) ^ *word as u32;
+                        }
+                    }
+                }
+                Ok(())
+            }
+        }
+
+        impl AsyncSpiDevice for Spi {
+            async fn transaction(
+                &mut self,
+                operations: &mut [Operation<'_, u8>],
+            ) -> Result<(), Infallible> {
+                SpiDevice::transaction(self, operations)
+            }
+        }
+
+        struct Pin;
+
+        impl embedded_hal::digital::ErrorType for Pin {
+            type Error = Infallible;
+        }
+
+        impl InputPin for Pin {
+            fn is_high(&mut self) -> Result<bool, Infallible> {
+                Ok(false)
+            }
+            fn is_low(&mut self) -> Result<bool, Infallible> {
+                Ok(true)
+            }
+        }
+
+        impl OutputPin for Pin {
+            fn set_low(&mut self) -> Result<(), Infallible> {
+                Ok(())
+            }
+            fn set_high(&mut self) -> Result<(), Infallible> {
+                Ok(())
+            }
+        }
+
+        impl embedded_hal_async::digital::Wait for Pin {
+            async fn wait_for_high(&mut self) -> Result<(), Infallible> {
+                unreachable!("never busy")
+            }
+            async fn wait_for_low(&mut self) -> Result<(), Infallible> {
+                Ok(())
+            }
+            async fn wait_for_rising_edge(&mut self) -> Result<(), Infallible> {
+                unreachable!("never busy")
+            }
+            async fn wait_for_falling_edge(&mut self) -> Result<(), Infallible> {
+                unreachable!("never busy")
+            }
+            async fn wait_for_any_edge(&mut self) -> Result<(), Infallible> {
+                unreachable!("never busy")
+            }
+        }
+
+        struct Delay;
+
+        impl DelayNs for Delay {
+            fn delay_ns(&mut self, _ns: u32) {}
+        }
+
+        impl AsyncDelayNs for Delay {
+            async fn delay_ns(&mut self, _ns: u32) {}
+        }
+
+        let mut spi = Spi::default();
+        let mut epd = Epd1in54::new(&mut spi, Pin, Pin, Pin, &mut Delay, None).unwrap();
+        epd.clear_frame(&mut spi, &mut Delay).unwrap();
+
+        let mut async_spi = Spi::default();
+        let mut epd = block_on(Epd1in54Async::new(
+            &mut async_spi,
+            Pin,
+            Pin,
+            Pin,
+            &mut Delay,
+            None,
+        ))
+        .unwrap();
+        block_on(epd.clear_frame(&mut async_spi, &mut Delay)).unwrap();
+
+        assert!(spi.0 > WIDTH as usize / 8 * HEIGHT as usize * 2);
+        assert_eq!(spi, async_spi);
     }
 }
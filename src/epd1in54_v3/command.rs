@@ -19,16 +19,15 @@ pub(crate) enum Command {
     TconSetting = 0x60,
     ResolutionSetting = 0x61,
 
+    PartialWindow = 0x90,
+    PartialIn = 0x91,
+    PartialOut = 0x92,
+
     InternalAA = 0xAA,
     InternalB6 = 0xB6,
     InternalE3 = 0xE3,
     InternalE9 = 0xE9,
     InternalF3 = 0xF3,
-
-    SetRamXAddressStartEndPosition = 0x44,
-    SetRamYAddressStartEndPosition = 0x45,
-    SetRamXAddressCounter = 0x4E,
-    SetRamYAddressCounter = 0x4F,
 }
 
 impl traits::Command for Command {
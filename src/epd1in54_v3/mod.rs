@@ -1,12 +1,29 @@
 //! A Driver for the Waveshare 1.54" v3 E-Ink Display
 //!
 //! GDEW0154M09
+//!
+//! The controller refreshes from the old image (DataStartTransmission1) to the new one
+//! (DataStartTransmission2). By default the driver doesn't remember the frames and sends the
+//! background color as old image, which gives wrong waveforms for the pixels which don't change
+//! color. With the `epd1in54_v3_old_frame` feature, the driver keeps a copy of the last frame
+//! (5000 bytes) and sends it as old image instead.
+//!
+//! The old image can also be given explicitly with [`QuickRefresh`], as in the partial refresh
+//! loops of the other UC81xx panels.
+//!
+//! The partial updates put the controller in partial mode (PartialIn, then PartialWindow) until
+//! the next refresh: [`display_frame`](WaveshareDisplay::display_frame) then only refreshes the
+//! window of the last partial update, and leaves the partial mode (PartialOut) once done.
 
+use crate::error::EpdError;
+use crate::pin::BusyPin;
 use embedded_hal::{delay::*, digital::*, spi::SpiDevice};
 
 use crate::color::Color;
 use crate::interface::DisplayInterface;
-use crate::traits::{InternalWiAdditions, RefreshLut, WaveshareDisplay};
+use crate::traits::{
+    InterfaceConfig, InternalWiAdditions, QuickRefresh, RefreshLut, WaveshareDisplay,
+};
 
 pub(crate) mod command;
 use self::command::Command;
@@ -19,111 +36,202 @@ pub const HEIGHT: u32 = 200;
 pub const DEFAULT_BACKGROUND_COLOR: Color = Color::White;
 const IS_BUSY_LOW: bool = true;
 const SINGLE_BYTE_WRITE: bool = true;
+/// Bytes of a full frame
+const FRAME_LEN: usize = WIDTH as usize / 8 * HEIGHT as usize;
 
 #[cfg(feature = "graphics")]
 pub use crate::epd1in54::Display1in54;
-
+#[cfg(feature = "async")]
+use crate::interface::DisplayInterfaceAsync;
+#[cfg(feature = "async")]
+use crate::traits::{
+    AsyncBusyPin, InternalWiAdditionsAsync, QuickRefreshAsync, WaveshareDisplayAsync,
+};
+#[cfg(feature = "async")]
+use embedded_hal_async::{delay::DelayNs as AsyncDelayNs, spi::SpiDevice as AsyncSpiDevice};
+
+#[maybe_async_cfg::maybe(
+    sync(keep_self),
+    async(
+        feature = "async",
+        idents(
+            Epd1in54,
+            DisplayInterface,
+            WaveshareDisplay,
+            InternalWiAdditions,
+            QuickRefresh,
+            SpiDevice(async = "AsyncSpiDevice"),
+            BusyPin(async = "AsyncBusyPin"),
+            DelayNs(async = "AsyncDelayNs")
+        )
+    )
+)]
 /// Epd1in54 v3 driver
 pub struct Epd1in54<SPI, BUSY, DC, RST, DELAY> {
     /// Connection Interface
     interface: DisplayInterface<SPI, BUSY, DC, RST, DELAY, SINGLE_BYTE_WRITE>,
     /// Background Color
     color: Color,
+    /// Last frame sent, the old image of the next refresh
+    #[cfg(feature = "epd1in54_v3_old_frame")]
+    old_frame: [u8; FRAME_LEN],
+    /// Whether the controller is in partial mode, until the next refresh
+    partial: bool,
 }
 
+#[maybe_async_cfg::maybe(
+    sync(keep_self),
+    async(
+        feature = "async",
+        idents(
+            Epd1in54,
+            DisplayInterface,
+            WaveshareDisplay,
+            InternalWiAdditions,
+            QuickRefresh,
+            SpiDevice(async = "AsyncSpiDevice"),
+            BusyPin(async = "AsyncBusyPin"),
+            DelayNs(async = "AsyncDelayNs")
+        )
+    )
+)]
 impl<SPI, BUSY, DC, RST, DELAY> InternalWiAdditions<SPI, BUSY, DC, RST, DELAY>
     for Epd1in54<SPI, BUSY, DC, RST, DELAY>
 where
     SPI: SpiDevice,
-    BUSY: InputPin,
+    BUSY: BusyPin,
     DC: OutputPin,
     RST: OutputPin,
     DELAY: DelayNs,
 {
-    fn init(&mut self, spi: &mut SPI, delay: &mut DELAY) -> Result<(), SPI::Error> {
-        // Reset the device
-        self.interface.reset(delay, 10_000, 10_000);
+    async fn init(&mut self, spi: &mut SPI, delay: &mut DELAY) -> Result<(), EpdError<SPI::Error>> {
+        // Reset the device, which also leaves the partial mode
+        self.interface.reset(delay, 10_000, 10_000).await?;
+        self.partial = false;
 
         // Panel Setting
         self.interface
-            .cmd_with_data(spi, Command::PanelSetting, &[0xDf, 0x0e])?;
+            .cmd_with_data(spi, Command::PanelSetting, &[0xdf, 0x0e])
+            .await?;
 
         // Internal codes (Magic numbers from Arduino driver)
         self.interface
-            .cmd_with_data(spi, Command::Internal4D, &[0x55])?;
+            .cmd_with_data(spi, Command::Internal4D, &[0x55])
+            .await?;
         self.interface
-            .cmd_with_data(spi, Command::InternalAA, &[0x0f])?;
+            .cmd_with_data(spi, Command::InternalAA, &[0x0f])
+            .await?;
         self.interface
-            .cmd_with_data(spi, Command::InternalE9, &[0x02])?;
+            .cmd_with_data(spi, Command::InternalE9, &[0x02])
+            .await?;
         self.interface
-            .cmd_with_data(spi, Command::InternalB6, &[0x11])?;
+            .cmd_with_data(spi, Command::InternalB6, &[0x11])
+            .await?;
         self.interface
-            .cmd_with_data(spi, Command::InternalF3, &[0x0a])?;
+            .cmd_with_data(spi, Command::InternalF3, &[0x0a])
+            .await?;
 
         // Resolution setting
         self.interface
-            .cmd_with_data(spi, Command::ResolutionSetting, &[0xc8, 0x00, 0xc8])?;
+            .cmd_with_data(spi, Command::ResolutionSetting, &[0xc8, 0x00, 0xc8])
+            .await?;
 
         // Tcon setting
         self.interface
-            .cmd_with_data(spi, Command::TconSetting, &[0x00])?;
+            .cmd_with_data(spi, Command::TconSetting, &[0x00])
+            .await?;
 
         // VCOM
         self.interface
-            .cmd_with_data(spi, Command::VcomAndDataIntervalSetting, &[0x97])?;
+            .cmd_with_data(spi, Command::VcomAndDataIntervalSetting, &[0x97])
+            .await?;
 
         // Internal code
         self.interface
-            .cmd_with_data(spi, Command::InternalE3, &[0x00])?;
+            .cmd_with_data(spi, Command::InternalE3, &[0x00])
+            .await?;
 
         // Power on
-        self.interface.cmd(spi, Command::PowerOn)?;
-        delay.delay_ms(100);
-        self.wait_until_idle(spi, delay)?;
+        self.interface.cmd(spi, Command::PowerOn).await?;
+        delay.delay_ms(100).await;
+        self.wait_until_idle(spi, delay).await?;
 
         Ok(())
     }
 }
 
+#[maybe_async_cfg::maybe(
+    sync(keep_self),
+    async(
+        feature = "async",
+        idents(
+            Epd1in54,
+            DisplayInterface,
+            WaveshareDisplay,
+            InternalWiAdditions,
+            QuickRefresh,
+            SpiDevice(async = "AsyncSpiDevice"),
+            BusyPin(async = "AsyncBusyPin"),
+            DelayNs(async = "AsyncDelayNs")
+        )
+    )
+)]
 impl<SPI, BUSY, DC, RST, DELAY> WaveshareDisplay<SPI, BUSY, DC, RST, DELAY>
     for Epd1in54<SPI, BUSY, DC, RST, DELAY>
 where
     SPI: SpiDevice,
-    BUSY: InputPin,
+    BUSY: BusyPin,
     DC: OutputPin,
     RST: OutputPin,
     DELAY: DelayNs,
 {
     type DisplayColor = Color;
-    fn new(
+    async fn new(
         spi: &mut SPI,
         busy: BUSY,
         dc: DC,
         rst: RST,
         delay: &mut DELAY,
         delay_us: Option<u32>,
-    ) -> Result<Self, SPI::Error> {
+    ) -> Result<Self, EpdError<SPI::Error>> {
+        let mut epd = Self::new_uninit(busy, dc, rst, delay_us);
+        epd.init(spi, delay).await?;
+        Ok(epd)
+    }
+
+    fn new_uninit(busy: BUSY, dc: DC, rst: RST, delay_us: Option<u32>) -> Self {
         let interface = DisplayInterface::new(busy, dc, rst, delay_us);
         let color = DEFAULT_BACKGROUND_COLOR;
 
-        let mut epd = Epd1in54 { interface, color };
-
-        epd.init(spi, delay)?;
-
-        Ok(epd)
+        Epd1in54 {
+            interface,
+            color,
+            #[cfg(feature = "epd1in54_v3_old_frame")]
+            old_frame: [color.get_byte_value(); FRAME_LEN],
+            partial: false,
+        }
     }
 
-    fn sleep(&mut self, spi: &mut SPI, delay: &mut DELAY) -> Result<(), SPI::Error> {
-        self.interface.cmd(spi, Command::PowerOff)?;
-        self.wait_until_idle(spi, delay)?;
-        delay.delay_ms(1000); // Wait for discharge?
+    async fn sleep(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.interface.cmd(spi, Command::PowerOff).await?;
+        self.wait_until_idle(spi, delay).await?;
+        delay.delay_ms(1000).await; // Wait for discharge?
         self.interface
-            .cmd_with_data(spi, Command::DeepSleep, &[0xA5])?;
+            .cmd_with_data(spi, Command::DeepSleep, &[0xA5])
+            .await?;
         Ok(())
     }
 
-    fn wake_up(&mut self, spi: &mut SPI, delay: &mut DELAY) -> Result<(), SPI::Error> {
-        self.init(spi, delay)
+    async fn wake_up(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.init(spi, delay).await
     }
 
     fn set_background_color(&mut self, color: Color) {
@@ -142,30 +250,30 @@ where
         HEIGHT
     }
 
-    fn update_frame(
+    async fn update_frame(
         &mut self,
         spi: &mut SPI,
         buffer: &[u8],
         delay: &mut DELAY,
-    ) -> Result<(), SPI::Error> {
-        self.use_full_frame(spi, delay)?;
-        self.wait_until_idle(spi, delay)?;
+    ) -> Result<(), EpdError<SPI::Error>> {
+        EpdError::check_buffer(buffer, FRAME_LEN)?;
+        self.wait_until_idle(spi, delay).await?;
+        self.leave_partial_mode(spi).await?;
 
         // Based on Arduino:
         // 0x10 -> Old Data (0xFF/White for "Clear" to "Image", or "OldImage" for "Image" to "Image")
-        // Since we don't track old frame, we write background color (Old state assumption)
-        self.interface.cmd(spi, Command::DataStartTransmission1)?;
-        self.interface
-            .data_x_times(spi, self.color.get_byte_value(), WIDTH / 8 * HEIGHT)?;
+        self.send_old_frame(spi, 0, 0, WIDTH, HEIGHT).await?;
 
         // 0x13 -> New Data
         self.interface
-            .cmd_with_data(spi, Command::DataStartTransmission2, buffer)?;
+            .cmd_with_data(spi, Command::DataStartTransmission2, buffer)
+            .await?;
+        self.keep_old_frame(buffer, 0, 0, WIDTH, HEIGHT);
 
         Ok(())
     }
 
-    fn update_partial_frame(
+    async fn update_partial_frame(
         &mut self,
         spi: &mut SPI,
         delay: &mut DELAY,
@@ -174,43 +282,52 @@ where
         y: u32,
         width: u32,
         height: u32,
-    ) -> Result<(), SPI::Error> {
-        self.wait_until_idle(spi, delay)?;
-        self.set_ram_area(spi, delay, x, y, x + width, y + height)?;
-        self.set_ram_counter(spi, delay, x, y)?;
+    ) -> Result<(), EpdError<SPI::Error>> {
+        EpdError::check_buffer(buffer, (width / 8 * height) as usize)?;
+        self.wait_until_idle(spi, delay).await?;
 
-        self.interface.cmd(spi, Command::DataStartTransmission1)?;
-        self.interface
-            .data_x_times(spi, self.color.get_byte_value(), width / 8 * height)?;
+        self.enter_partial_mode(spi).await?;
+        self.set_partial_window(spi, x, y, width, height).await?;
+
+        self.send_old_frame(spi, x, y, width, height).await?;
 
         self.interface
-            .cmd_with_data(spi, Command::DataStartTransmission2, buffer)?;
+            .cmd_with_data(spi, Command::DataStartTransmission2, buffer)
+            .await?;
+        self.keep_old_frame(buffer, x, y, width, height);
         Ok(())
     }
 
-    fn display_frame(&mut self, spi: &mut SPI, delay: &mut DELAY) -> Result<(), SPI::Error> {
-        self.wait_until_idle(spi, delay)?;
-        self.interface.cmd(spi, Command::DisplayRefresh)?;
+    async fn display_frame(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.wait_until_idle(spi, delay).await?;
+        self.interface.cmd(spi, Command::DisplayRefresh).await?;
         // The delay is necessary, 200uS at least!!!
-        delay.delay_ms(10);
-        self.wait_until_idle(spi, delay)?;
-        Ok(())
+        delay.delay_ms(10).await;
+        self.wait_until_idle(spi, delay).await?;
+        self.leave_partial_mode(spi).await
     }
 
-    fn update_and_display_frame(
+    async fn update_and_display_frame(
         &mut self,
         spi: &mut SPI,
         buffer: &[u8],
         delay: &mut DELAY,
-    ) -> Result<(), SPI::Error> {
-        self.update_frame(spi, buffer, delay)?;
-        self.display_frame(spi, delay)?;
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.update_frame(spi, buffer, delay).await?;
+        self.display_frame(spi, delay).await?;
         Ok(())
     }
 
-    fn clear_frame(&mut self, spi: &mut SPI, delay: &mut DELAY) -> Result<(), SPI::Error> {
-        self.wait_until_idle(spi, delay)?;
-        self.use_full_frame(spi, delay)?;
+    async fn clear_frame(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.wait_until_idle(spi, delay).await?;
 
         // Clear is sending 0x00 to 0x10 and 0xFF to 0x13 in Arduino 'PIC_display_Clean' ?
         // Wait, PIC_display_Clean: 0x10 -> 0x00. 0x13 -> 0xFF.
@@ -220,110 +337,443 @@ where
 
         // If we want to clear to Background Color (usually white).
         let color_value = self.color.get_byte_value();
+        self.leave_partial_mode(spi).await?;
 
         // However, standard Clear often implies removing everything.
         // Lets follow Arduino Clean procedure but using our Background color for New Data
 
-        self.interface.cmd(spi, Command::DataStartTransmission1)?;
-        self.interface.data_x_times(spi, 0x00, WIDTH / 8 * HEIGHT)?; // 0x00 in 0x10 (from Arduino)
+        self.interface
+            .cmd(spi, Command::DataStartTransmission1)
+            .await?;
+        self.interface
+            .data_x_times(spi, 0x00, WIDTH / 8 * HEIGHT)
+            .await?; // 0x00 in 0x10 (from Arduino)
 
-        self.interface.cmd(spi, Command::DataStartTransmission2)?;
+        self.interface
+            .cmd(spi, Command::DataStartTransmission2)
+            .await?;
         // New data is 0xFF (White) typically. Or self.color.
         self.interface
-            .data_x_times(spi, color_value, WIDTH / 8 * HEIGHT)?;
+            .data_x_times(spi, color_value, WIDTH / 8 * HEIGHT)
+            .await?;
+        #[cfg(feature = "epd1in54_v3_old_frame")]
+        self.old_frame.fill(color_value);
 
-        self.display_frame(spi, delay)?;
+        self.display_frame(spi, delay).await?;
 
         Ok(())
     }
 
-    fn set_lut(
+    async fn set_lut(
         &mut self,
         _spi: &mut SPI,
         _delay: &mut DELAY,
         _refresh_rate: Option<RefreshLut>,
-    ) -> Result<(), SPI::Error> {
+    ) -> Result<(), EpdError<SPI::Error>> {
         // GDEW0154M09 uses internal LUTs and doesn't seem to support custom LUT downloads via SPI
         // in the example code.
         Ok(())
     }
 
-    fn wait_until_idle(&mut self, _spi: &mut SPI, delay: &mut DELAY) -> Result<(), SPI::Error> {
-        self.interface.wait_until_idle(delay, IS_BUSY_LOW);
-        Ok(())
+    fn interface_config_mut(&mut self) -> &mut InterfaceConfig {
+        self.interface.config_mut()
+    }
+
+    fn release(self) -> (BUSY, DC, RST) {
+        self.interface.release()
+    }
+
+    async fn wait_until_idle(
+        &mut self,
+        _spi: &mut SPI,
+        delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.interface.wait_until_idle(delay, IS_BUSY_LOW).await
     }
 }
 
-impl<SPI, BUSY, DC, RST, DELAY> Epd1in54<SPI, BUSY, DC, RST, DELAY>
+#[maybe_async_cfg::maybe(
+    sync(keep_self),
+    async(
+        feature = "async",
+        idents(
+            Epd1in54,
+            DisplayInterface,
+            WaveshareDisplay,
+            InternalWiAdditions,
+            QuickRefresh,
+            SpiDevice(async = "AsyncSpiDevice"),
+            BusyPin(async = "AsyncBusyPin"),
+            DelayNs(async = "AsyncDelayNs")
+        )
+    )
+)]
+impl<SPI, BUSY, DC, RST, DELAY> QuickRefresh<SPI, BUSY, DC, RST, DELAY>
+    for Epd1in54<SPI, BUSY, DC, RST, DELAY>
 where
     SPI: SpiDevice,
-    BUSY: InputPin,
+    BUSY: BusyPin,
     DC: OutputPin,
     RST: OutputPin,
     DELAY: DelayNs,
 {
-    fn use_full_frame(&mut self, spi: &mut SPI, delay: &mut DELAY) -> Result<(), SPI::Error> {
-        // choose full frame/ram
-        self.set_ram_area(spi, delay, 0, 0, WIDTH - 1, HEIGHT - 1)?;
+    /// To be followed by `update_new_frame`.
+    async fn update_old_frame(
+        &mut self,
+        spi: &mut SPI,
+        buffer: &[u8],
+        delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        EpdError::check_buffer(buffer, FRAME_LEN)?;
+        self.wait_until_idle(spi, delay).await?;
+        self.leave_partial_mode(spi).await?;
+        self.interface
+            .cmd_with_data(spi, Command::DataStartTransmission1, buffer)
+            .await
+    }
+
+    /// To be used after `update_old_frame`.
+    async fn update_new_frame(
+        &mut self,
+        spi: &mut SPI,
+        buffer: &[u8],
+        delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        EpdError::check_buffer(buffer, FRAME_LEN)?;
+        self.wait_until_idle(spi, delay).await?;
+        self.leave_partial_mode(spi).await?;
+        self.interface
+            .cmd_with_data(spi, Command::DataStartTransmission2, buffer)
+            .await?;
+        self.keep_old_frame(buffer, 0, 0, WIDTH, HEIGHT);
+        Ok(())
+    }
 
-        // start from the beginning
-        self.set_ram_counter(spi, delay, 0, 0)
+    /// This is a wrapper around `display_frame` for using this device as a true
+    /// `QuickRefresh` device.
+    async fn display_new_frame(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.display_frame(spi, delay).await
     }
 
-    fn set_ram_area(
+    /// To be used after `update_old_frame`.
+    async fn update_and_display_new_frame(
         &mut self,
         spi: &mut SPI,
+        buffer: &[u8],
         delay: &mut DELAY,
-        start_x: u32,
-        start_y: u32,
-        end_x: u32,
-        end_y: u32,
-    ) -> Result<(), SPI::Error> {
-        self.wait_until_idle(spi, delay)?;
-        assert!(start_x < end_x);
-        assert!(start_y < end_y);
-
-        // x is positioned in bytes, so the last 3 bits which show the position inside a byte in the ram
-        // aren't relevant
-        self.interface.cmd_with_data(
-            spi,
-            Command::SetRamXAddressStartEndPosition,
-            &[(start_x >> 3) as u8, (end_x >> 3) as u8],
-        )?;
-
-        // 2 Databytes: A[7:0] & 0..A[8] for each - start and end
-        self.interface.cmd_with_data(
-            spi,
-            Command::SetRamYAddressStartEndPosition,
-            &[
-                start_y as u8,
-                (start_y >> 8) as u8,
-                end_y as u8,
-                (end_y >> 8) as u8,
-            ],
-        )?;
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.update_new_frame(spi, buffer, delay).await?;
+        self.display_frame(spi, delay).await
+    }
+
+    /// Enters the partial mode, to be followed by `update_partial_new_frame` with the same
+    /// window.
+    async fn update_partial_old_frame(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+        buffer: &[u8],
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        EpdError::check_buffer(buffer, (width / 8 * height) as usize)?;
+        self.wait_until_idle(spi, delay).await?;
+
+        self.enter_partial_mode(spi).await?;
+        self.set_partial_window(spi, x, y, width, height).await?;
+        self.interface
+            .cmd_with_data(spi, Command::DataStartTransmission1, buffer)
+            .await
+    }
+
+    /// Always call `update_partial_old_frame` before this, with buffer-updating code
+    /// between the calls.
+    async fn update_partial_new_frame(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+        buffer: &[u8],
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        EpdError::check_buffer(buffer, (width / 8 * height) as usize)?;
+        self.wait_until_idle(spi, delay).await?;
+
+        self.enter_partial_mode(spi).await?;
+        self.set_partial_window(spi, x, y, width, height).await?;
+        self.interface
+            .cmd_with_data(spi, Command::DataStartTransmission2, buffer)
+            .await?;
+        self.keep_old_frame(buffer, x, y, width, height);
         Ok(())
     }
 
-    fn set_ram_counter(
+    async fn clear_partial_frame(
         &mut self,
         spi: &mut SPI,
         delay: &mut DELAY,
         x: u32,
         y: u32,
-    ) -> Result<(), SPI::Error> {
-        self.wait_until_idle(spi, delay)?;
-        // x is positioned in bytes, so the last 3 bits which show the position inside a byte in the ram
-        // aren't relevant
+        width: u32,
+        height: u32,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.wait_until_idle(spi, delay).await?;
+        let color_value = self.color.get_byte_value();
+
+        self.enter_partial_mode(spi).await?;
+        self.set_partial_window(spi, x, y, width, height).await?;
+
+        self.interface
+            .cmd(spi, Command::DataStartTransmission1)
+            .await?;
+        self.interface
+            .data_x_times(spi, color_value, width / 8 * height)
+            .await?;
         self.interface
-            .cmd_with_data(spi, Command::SetRamXAddressCounter, &[(x >> 3) as u8])?;
-
-        // 2 Databytes: A[7:0] & 0..A[8]
-        self.interface.cmd_with_data(
-            spi,
-            Command::SetRamYAddressCounter,
-            &[y as u8, (y >> 8) as u8],
-        )?;
+            .cmd(spi, Command::DataStartTransmission2)
+            .await?;
+        self.interface
+            .data_x_times(spi, color_value, width / 8 * height)
+            .await?;
+
+        #[cfg(feature = "epd1in54_v3_old_frame")]
+        for row in window_rows(x, y, width, height) {
+            self.old_frame[row].fill(color_value);
+        }
         Ok(())
     }
 }
+
+#[maybe_async_cfg::maybe(
+    sync(keep_self),
+    async(
+        feature = "async",
+        idents(
+            Epd1in54,
+            DisplayInterface,
+            WaveshareDisplay,
+            InternalWiAdditions,
+            QuickRefresh,
+            SpiDevice(async = "AsyncSpiDevice"),
+            BusyPin(async = "AsyncBusyPin"),
+            DelayNs(async = "AsyncDelayNs")
+        )
+    )
+)]
+impl<SPI, BUSY, DC, RST, DELAY> Epd1in54<SPI, BUSY, DC, RST, DELAY>
+where
+    SPI: SpiDevice,
+    BUSY: BusyPin,
+    DC: OutputPin,
+    RST: OutputPin,
+    DELAY: DelayNs,
+{
+    /// Sends the old image of the window to DataStartTransmission1: the last frame sent with the
+    /// `epd1in54_v3_old_frame` feature, the background color without
+    async fn send_old_frame(
+        &mut self,
+        spi: &mut SPI,
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.interface
+            .cmd(spi, Command::DataStartTransmission1)
+            .await?;
+        #[cfg(feature = "epd1in54_v3_old_frame")]
+        for row in window_rows(x, y, width, height) {
+            self.interface.data(spi, &self.old_frame[row]).await?;
+        }
+        #[cfg(not(feature = "epd1in54_v3_old_frame"))]
+        {
+            let _ = (x, y);
+            self.interface
+                .data_x_times(spi, self.color.get_byte_value(), width / 8 * height)
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// Keeps `buffer`, the new image of the window, as old image of the next refresh
+    fn keep_old_frame(&mut self, buffer: &[u8], x: u32, y: u32, width: u32, height: u32) {
+        #[cfg(feature = "epd1in54_v3_old_frame")]
+        for (row, line) in window_rows(x, y, width, height).zip(buffer.chunks(width as usize / 8)) {
+            self.old_frame[row].copy_from_slice(line);
+        }
+        #[cfg(not(feature = "epd1in54_v3_old_frame"))]
+        let _ = (buffer, x, y, width, height);
+    }
+
+    /// Enters the partial mode, kept until the next refresh
+    async fn enter_partial_mode(&mut self, spi: &mut SPI) -> Result<(), EpdError<SPI::Error>> {
+        if !self.partial {
+            self.interface.cmd(spi, Command::PartialIn).await?;
+            self.partial = true;
+        }
+        Ok(())
+    }
+
+    /// Leaves the partial mode, for the full updates and once a partial refresh is done
+    async fn leave_partial_mode(&mut self, spi: &mut SPI) -> Result<(), EpdError<SPI::Error>> {
+        if self.partial {
+            self.interface.cmd(spi, Command::PartialOut).await?;
+            self.partial = false;
+        }
+        Ok(())
+    }
+
+    /// Sets the window of the partial mode, `x` and `width` being multiples of 8
+    async fn set_partial_window(
+        &mut self,
+        spi: &mut SPI,
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        let x_end = x + width - 1;
+        let y_end = y + height - 1;
+        self.interface
+            .cmd_with_data(
+                spi,
+                Command::PartialWindow,
+                &[
+                    // the 3 less significant bits are ignored
+                    (x & 0xf8) as u8,
+                    (x_end | 0x07) as u8,
+                    (y >> 8) as u8,
+                    y as u8,
+                    (y_end >> 8) as u8,
+                    y_end as u8,
+                    // gates scan both inside and outside of the window
+                    0x01,
+                ],
+            )
+            .await
+    }
+}
+
+/// Byte ranges of the rows of a window in a full frame
+#[cfg(feature = "epd1in54_v3_old_frame")]
+fn window_rows(
+    x: u32,
+    y: u32,
+    width: u32,
+    height: u32,
+) -> impl Iterator<Item = core::ops::Range<usize>> {
+    let (start, len) = (x as usize / 8, width as usize / 8);
+    (y as usize..(y + height) as usize).map(move |row| {
+        let row_start = row * WIDTH as usize / 8 + start;
+        row_start..row_start + len
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn epd_size() {
+        assert_eq!(WIDTH, 200);
+        assert_eq!(HEIGHT, 200);
+        assert_eq!(FRAME_LEN, 5000);
+        assert_eq!(DEFAULT_BACKGROUND_COLOR, Color::White);
+    }
+
+    #[test]
+    fn partial_mode_until_refresh() {
+        extern crate std;
+        use crate::pin::NoPin;
+        use core::cell::{Cell, RefCell};
+        use core::convert::Infallible;
+        use embedded_hal::spi::{ErrorType, Operation};
+        use embedded_hal_mock::eh1::delay::NoopDelay;
+        use std::vec::Vec;
+
+        /// Records the commands, the bytes written while DC is low
+        struct Spi<'a> {
+            data: &'a Cell<bool>,
+            commands: &'a RefCell<Vec<u8>>,
+        }
+
+        impl ErrorType for Spi<'_> {
+            type Error = Infallible;
+        }
+
+        impl SpiDevice for Spi<'_> {
+            fn transaction(
+                &mut self,
+                operations: &mut [Operation<'_, u8>],
+            ) -> Result<(), Infallible> {
+                for operation in operations {
+                    if let (Operation::Write(words), false) = (operation, self.data.get()) {
+                        self.commands.borrow_mut().extend_from_slice(words);
+                    }
+                }
+                Ok(())
+            }
+        }
+
+        struct Dc<'a>(&'a Cell<bool>);
+
+        impl embedded_hal::digital::ErrorType for Dc<'_> {
+            type Error = Infallible;
+        }
+
+        impl OutputPin for Dc<'_> {
+            fn set_low(&mut self) -> Result<(), Infallible> {
+                self.0.set(false);
+                Ok(())
+            }
+            fn set_high(&mut self) -> Result<(), Infallible> {
+                self.0.set(true);
+                Ok(())
+            }
+        }
+
+        let data = Cell::new(false);
+        let commands = RefCell::new(Vec::new());
+        let mut spi = Spi {
+            data: &data,
+            commands: &commands,
+        };
+        let mut delay = NoopDelay::new();
+        let mut epd = Epd1in54::new_uninit(NoPin, Dc(&data), NoPin, None);
+
+        epd.update_partial_frame(&mut spi, &mut delay, &[0; 8], 8, 16, 8, 8)
+            .unwrap();
+        epd.display_frame(&mut spi, &mut delay).unwrap();
+        // PartialIn, PartialWindow, the old and new images, the refresh and PartialOut
+        assert_eq!(*commands.borrow(), [0x91, 0x90, 0x10, 0x13, 0x12, 0x92]);
+
+        // a full update after a partial one leaves the partial mode first
+        commands.borrow_mut().clear();
+        epd.update_partial_old_frame(&mut spi, &mut delay, &[0; 8], 8, 16, 8, 8)
+            .unwrap();
+        epd.update_frame(&mut spi, &[0; FRAME_LEN], &mut delay)
+            .unwrap();
+        epd.display_frame(&mut spi, &mut delay).unwrap();
+        assert_eq!(
+            *commands.borrow(),
+            [0x91, 0x90, 0x10, 0x92, 0x10, 0x13, 0x12]
+        );
+    }
+
+    #[cfg(feature = "epd1in54_v3_old_frame")]
+    #[test]
+    fn old_frame_window() {
+        let mut rows = window_rows(16, 2, 24, 2);
+        assert_eq!(rows.next(), Some(52..55));
+        assert_eq!(rows.next(), Some(77..80));
+        assert_eq!(rows.next(), None);
+    }
+}
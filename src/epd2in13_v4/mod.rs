@@ -0,0 +1,660 @@
+//! A Driver for the Waveshare 2.13" E-Ink Display (V4) via SPI
+//!
+//! The V4 is the current revision of the 2.13" panel, with a SSD1680 controller. The waveforms
+//! are the ones of the controller OTP: besides the full refresh, it has a fast full refresh (see
+//! [`set_fast_refresh`](Epd2in13::set_fast_refresh) and [`RefreshLut::Fast`]) and a quick refresh of the pixels which
+//! differ from a base image (see [`QuickRefresh`]).
+//!
+//! The panel is 122 pixels wide: the rows of the frames are padded to 128 pixels (16 bytes), as
+//! in the [`Display2in13`] buffer.
+//!
+//! # References
+//!
+//! - [Waveshare product page](https://www.waveshare.com/wiki/2.13inch_e-Paper_HAT)
+//! - [Waveshare C driver](https://github.com/waveshare/e-Paper/blob/master/RaspberryPi_JetsonNano/c/lib/e-Paper/EPD_2in13_V4.c)
+//! - [Waveshare Python driver](https://github.com/waveshare/e-Paper/blob/master/RaspberryPi_JetsonNano/python/lib/waveshare_epd/epd2in13_V4.py)
+//!
+//! # Example for the 2.13" V4 E-Ink Display
+//!
+//!```rust, no_run
+//!# use embedded_hal_mock::eh1::*;
+//!# fn main() -> Result<(), epd_waveshare::error::EpdError<embedded_hal::spi::ErrorKind>> {
+//!use embedded_graphics::{prelude::*, primitives::{Line, PrimitiveStyle}};
+//!use epd_waveshare::{epd2in13_v4::*, prelude::*};
+//!#
+//!# let expectations = [];
+//!# let mut spi = spi::Mock::new(&expectations);
+//!# let expectations = [];
+//!# let busy_in = digital::Mock::new(&expectations);
+//!# let dc = digital::Mock::new(&expectations);
+//!# let rst = digital::Mock::new(&expectations);
+//!# let mut delay = delay::NoopDelay::new();
+//!
+//!// Setup EPD
+//!let mut epd = Epd2in13::new(&mut spi, busy_in, dc, rst, &mut delay, None)?;
+//!
+//!// Use display graphics from embedded-graphics
+//!let mut display = Display2in13::default();
+//!
+//!// Use embedded graphics for drawing a line
+//!let _ = Line::new(Point::new(0, 120), Point::new(0, 249))
+//!    .into_styled(PrimitiveStyle::with_stroke(Color::Black, 1))
+//!    .draw(&mut display);
+//!
+//!// Display the frame, also as the base image of quick refreshes
+//!epd.update_old_frame(&mut spi, display.buffer(), &mut delay)?;
+//!epd.display_frame(&mut spi, &mut delay)?;
+//!
+//!// Update image here
+//!
+//!// quick refresh of updated pixels
+//!epd.update_and_display_new_frame(&mut spi, display.buffer(), &mut delay)?;
+//!
+//!// Set the EPD to sleep
+//!epd.sleep(&mut spi, &mut delay)?;
+//!# Ok(())
+//!# }
+//!```
+
+use crate::error::EpdError;
+use crate::pin::BusyPin;
+use embedded_hal::{delay::*, digital::*, spi::SpiDevice};
+
+use crate::buffer_len;
+use crate::color::Color;
+use crate::interface::DisplayInterface;
+#[cfg(feature = "async")]
+use crate::interface::DisplayInterfaceAsync;
+use crate::ram_addressing::Ssd1680;
+#[cfg(feature = "async")]
+use crate::traits::{
+    AsyncBusyPin, InternalWiAdditionsAsync, QuickRefreshAsync, WaveshareDisplayAsync,
+};
+use crate::traits::{
+    InterfaceConfig, InternalWiAdditions, QuickRefresh, RefreshLut, WaveshareDisplay,
+};
+use crate::type_a::command::Command;
+#[cfg(feature = "async")]
+use embedded_hal_async::{delay::DelayNs as AsyncDelayNs, spi::SpiDevice as AsyncSpiDevice};
+
+/// Width of the display in pixels
+pub const WIDTH: u32 = 122;
+/// Height of the display in pixels
+pub const HEIGHT: u32 = 250;
+/// Default Background Color (white)
+pub const DEFAULT_BACKGROUND_COLOR: Color = Color::White;
+const IS_BUSY_LOW: bool = false;
+const SINGLE_BYTE_WRITE: bool = false;
+
+/// Enable clock and analog, load temperature and the LUT, display mode 1, disable
+const FULL_REFRESH: u8 = 0xF7;
+/// Same with the LUT loaded for the temperature forced by the fast init
+const FAST_REFRESH: u8 = 0xC7;
+/// Same in display mode 2, only changing the pixels which differ from the old frame
+const QUICK_REFRESH: u8 = 0xFF;
+
+/// Full size buffer for use with the 2in13 v4 EPD
+#[cfg(feature = "graphics")]
+pub type Display2in13 = crate::graphics::Display<
+    WIDTH,
+    HEIGHT,
+    false,
+    { crate::graphics::display_len::<Color>(WIDTH, HEIGHT) },
+    Color,
+>;
+
+#[maybe_async_cfg::maybe(
+    sync(keep_self),
+    async(
+        feature = "async",
+        idents(
+            Epd2in13,
+            DisplayInterface,
+            WaveshareDisplay,
+            InternalWiAdditions,
+            QuickRefresh,
+            SpiDevice(async = "AsyncSpiDevice"),
+            BusyPin(async = "AsyncBusyPin"),
+            DelayNs(async = "AsyncDelayNs")
+        )
+    )
+)]
+/// Epd2in13 (V4) driver
+pub struct Epd2in13<SPI, BUSY, DC, RST, DELAY> {
+    /// Connection Interface
+    interface: DisplayInterface<SPI, BUSY, DC, RST, DELAY, SINGLE_BYTE_WRITE>,
+    /// Background Color
+    background_color: Color,
+    /// Refresh LUT
+    refresh: RefreshLut,
+    /// Whether the full refreshes use the fast waveform
+    fast: bool,
+}
+
+#[maybe_async_cfg::maybe(
+    sync(keep_self),
+    async(
+        feature = "async",
+        idents(
+            Epd2in13,
+            DisplayInterface,
+            WaveshareDisplay,
+            InternalWiAdditions,
+            QuickRefresh,
+            SpiDevice(async = "AsyncSpiDevice"),
+            BusyPin(async = "AsyncBusyPin"),
+            DelayNs(async = "AsyncDelayNs")
+        )
+    )
+)]
+impl<SPI, BUSY, DC, RST, DELAY> InternalWiAdditions<SPI, BUSY, DC, RST, DELAY>
+    for Epd2in13<SPI, BUSY, DC, RST, DELAY>
+where
+    SPI: SpiDevice,
+    BUSY: BusyPin,
+    DC: OutputPin,
+    RST: OutputPin,
+    DELAY: DelayNs,
+{
+    async fn init(&mut self, spi: &mut SPI, delay: &mut DELAY) -> Result<(), EpdError<SPI::Error>> {
+        self.interface.reset(delay, 10_000, 10_000).await?;
+
+        self.wait_until_idle(spi, delay).await?;
+        self.interface.cmd(spi, Command::SwReset).await?;
+        self.wait_until_idle(spi, delay).await?;
+
+        // all 250 gates, scanned from G0
+        self.interface
+            .cmd_with_data(
+                spi,
+                Command::DriverOutputControl,
+                &[(HEIGHT - 1) as u8, ((HEIGHT - 1) >> 8) as u8, 0x00],
+            )
+            .await?;
+
+        // x increment, y increment, address counter is updated in x direction
+        self.interface
+            .cmd_with_data(spi, Command::DataEntryModeSetting, &[0x03])
+            .await?;
+
+        // follow the LUT for the border, like the white pixels
+        self.interface
+            .cmd_with_data(spi, Command::BorderWaveformControl, &[0x05])
+            .await?;
+
+        // normal RAM content, the panel is connected to the sources S8 to S167
+        self.interface
+            .cmd_with_data(spi, Command::DisplayUpdateControl1, &[0x00, 0x80])
+            .await?;
+
+        // internal temperature sensor
+        self.interface
+            .cmd_with_data(spi, Command::TemperatureSensorSelection, &[0x80])
+            .await?;
+
+        self.use_full_frame(spi, delay).await?;
+        self.wait_until_idle(spi, delay).await?;
+
+        if self.fast || self.refresh == RefreshLut::Fast {
+            self.load_fast_waveform(spi, delay).await?;
+        }
+        Ok(())
+    }
+}
+
+#[maybe_async_cfg::maybe(
+    sync(keep_self),
+    async(
+        feature = "async",
+        idents(
+            Epd2in13,
+            DisplayInterface,
+            WaveshareDisplay,
+            InternalWiAdditions,
+            QuickRefresh,
+            SpiDevice(async = "AsyncSpiDevice"),
+            BusyPin(async = "AsyncBusyPin"),
+            DelayNs(async = "AsyncDelayNs")
+        )
+    )
+)]
+impl<SPI, BUSY, DC, RST, DELAY> WaveshareDisplay<SPI, BUSY, DC, RST, DELAY>
+    for Epd2in13<SPI, BUSY, DC, RST, DELAY>
+where
+    SPI: SpiDevice,
+    BUSY: BusyPin,
+    DC: OutputPin,
+    RST: OutputPin,
+    DELAY: DelayNs,
+{
+    type DisplayColor = Color;
+    fn width(&self) -> u32 {
+        WIDTH
+    }
+
+    fn height(&self) -> u32 {
+        HEIGHT
+    }
+
+    async fn new(
+        spi: &mut SPI,
+        busy: BUSY,
+        dc: DC,
+        rst: RST,
+        delay: &mut DELAY,
+        delay_us: Option<u32>,
+    ) -> Result<Self, EpdError<SPI::Error>> {
+        let mut epd = Self::new_uninit(busy, dc, rst, delay_us);
+        epd.init(spi, delay).await?;
+        Ok(epd)
+    }
+
+    fn new_uninit(busy: BUSY, dc: DC, rst: RST, delay_us: Option<u32>) -> Self {
+        let interface = DisplayInterface::new(busy, dc, rst, delay_us);
+
+        Epd2in13 {
+            interface,
+            background_color: DEFAULT_BACKGROUND_COLOR,
+            refresh: RefreshLut::Full,
+            fast: false,
+        }
+    }
+
+    async fn sleep(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.wait_until_idle(spi, delay).await?;
+        // 0x00 for Normal mode (Power on Reset), 0x01 for Deep Sleep Mode
+        self.interface
+            .cmd_with_data(spi, Command::DeepSleepMode, &[0x01])
+            .await
+    }
+
+    async fn wake_up(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.init(spi, delay).await
+    }
+
+    async fn update_frame(
+        &mut self,
+        spi: &mut SPI,
+        buffer: &[u8],
+        delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        EpdError::check_buffer(buffer, buffer_len(WIDTH as usize, HEIGHT as usize))?;
+        self.wait_until_idle(spi, delay).await?;
+        self.interface
+            .cmd_with_data(spi, Command::WriteRam, buffer)
+            .await
+    }
+
+    /// `x` and `width` must be multiples of 8
+    async fn update_partial_frame(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+        buffer: &[u8],
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.write_window(spi, delay, Command::WriteRam, buffer, x, y, width, height)
+            .await
+    }
+
+    /// Refreshes with the full waveform (the fast one after
+    /// [`set_fast_refresh`](Epd2in13::set_fast_refresh) or `set_lut(Some(RefreshLut::Fast))`),
+    /// or the quick one of [`display_new_frame`](QuickRefresh::display_new_frame) after
+    /// `set_lut(Some(RefreshLut::Quick))`
+    async fn display_frame(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        let sequence = match self.refresh {
+            RefreshLut::Quick => QUICK_REFRESH,
+            RefreshLut::Fast => FAST_REFRESH,
+            _ if self.fast => FAST_REFRESH,
+            _ => FULL_REFRESH,
+        };
+        self.turn_on_display(spi, delay, sequence).await
+    }
+
+    async fn update_and_display_frame(
+        &mut self,
+        spi: &mut SPI,
+        buffer: &[u8],
+        delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.update_frame(spi, buffer, delay).await?;
+        self.display_frame(spi, delay).await
+    }
+
+    async fn clear_frame(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.wait_until_idle(spi, delay).await?;
+
+        // clear both the new and the old frame with the background color
+        let color = self.background_color.get_byte_value();
+        let len = buffer_len(WIDTH as usize, HEIGHT as usize) as u32;
+        self.interface.cmd(spi, Command::WriteRam).await?;
+        self.interface.data_x_times(spi, color, len).await?;
+        self.interface.cmd(spi, Command::WriteRam2).await?;
+        self.interface.data_x_times(spi, color, len).await
+    }
+
+    fn set_background_color(&mut self, background_color: Color) {
+        self.background_color = background_color;
+    }
+
+    fn background_color(&self) -> &Color {
+        &self.background_color
+    }
+
+    /// Selects the waveform of [`display_frame`](WaveshareDisplay::display_frame), the quick
+    /// refreshes also keeping the border as it is
+    async fn set_lut(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+        refresh_rate: Option<RefreshLut>,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        if let Some(refresh_lut) = refresh_rate {
+            self.refresh = refresh_lut.or_full(&[RefreshLut::Quick, RefreshLut::Fast]);
+        }
+        let border = match self.refresh {
+            // keep the border at VSS
+            RefreshLut::Quick => 0x80,
+            // follow the LUT, like the white pixels
+            _ => 0x05,
+        };
+        self.wait_until_idle(spi, delay).await?;
+        // the other refreshes load the waveform of the measured temperature
+        if self.refresh == RefreshLut::Fast {
+            self.load_fast_waveform(spi, delay).await?;
+        }
+        self.interface
+            .cmd_with_data(spi, Command::BorderWaveformControl, &[border])
+            .await
+    }
+
+    fn interface_config_mut(&mut self) -> &mut InterfaceConfig {
+        self.interface.config_mut()
+    }
+
+    fn release(self) -> (BUSY, DC, RST) {
+        self.interface.release()
+    }
+
+    async fn wait_until_idle(
+        &mut self,
+        _spi: &mut SPI,
+        delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.interface.wait_until_idle(delay, IS_BUSY_LOW).await
+    }
+}
+
+#[maybe_async_cfg::maybe(
+    sync(keep_self),
+    async(
+        feature = "async",
+        idents(
+            Epd2in13,
+            DisplayInterface,
+            WaveshareDisplay,
+            InternalWiAdditions,
+            QuickRefresh,
+            SpiDevice(async = "AsyncSpiDevice"),
+            BusyPin(async = "AsyncBusyPin"),
+            DelayNs(async = "AsyncDelayNs")
+        )
+    )
+)]
+impl<SPI, BUSY, DC, RST, DELAY> Epd2in13<SPI, BUSY, DC, RST, DELAY>
+where
+    SPI: SpiDevice,
+    BUSY: BusyPin,
+    DC: OutputPin,
+    RST: OutputPin,
+    DELAY: DelayNs,
+{
+    /// Switches the full refreshes to the fast waveform, about twice as fast but leaving more
+    /// ghosting, or back to the normal one
+    ///
+    /// The controller is initialized again.
+    pub async fn set_fast_refresh(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+        fast: bool,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.fast = fast;
+        self.init(spi, delay).await?;
+        self.set_lut(spi, delay, None).await
+    }
+
+    /// Loads the shorter waveform of a high temperature, used by the fast refreshes
+    async fn load_fast_waveform(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        // load the temperature and the LUT
+        self.interface
+            .cmd_with_data(spi, Command::DisplayUpdateControl2, &[0xB1])
+            .await?;
+        self.interface.cmd(spi, Command::MasterActivation).await?;
+        self.wait_until_idle(spi, delay).await?;
+
+        // force a temperature of 100°C, whose waveform is shorter
+        self.interface
+            .cmd_with_data(spi, Command::TemperatureSensorControl, &[0x64, 0x00])
+            .await?;
+        // load the LUT of this temperature
+        self.interface
+            .cmd_with_data(spi, Command::DisplayUpdateControl2, &[0x91])
+            .await?;
+        self.interface.cmd(spi, Command::MasterActivation).await?;
+        self.wait_until_idle(spi, delay).await
+    }
+
+    async fn use_full_frame(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.interface
+            .set_ram_area::<Ssd1680>(spi, 0, 0, WIDTH - 1, HEIGHT - 1)
+            .await?;
+        self.wait_until_idle(spi, delay).await?;
+        self.interface.set_ram_counter::<Ssd1680>(spi, 0, 0).await
+    }
+
+    /// Writes `buffer` in a window of the RAM selected by `command`
+    #[allow(clippy::too_many_arguments)]
+    async fn write_window(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+        command: Command,
+        buffer: &[u8],
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        EpdError::check_buffer(buffer, buffer_len(width as usize, height as usize))?;
+        self.wait_until_idle(spi, delay).await?;
+        self.interface
+            .set_ram_area::<Ssd1680>(spi, x, y, x + width - 1, y + height - 1)
+            .await?;
+        self.interface.set_ram_counter::<Ssd1680>(spi, x, y).await?;
+        self.interface.cmd_with_data(spi, command, buffer).await?;
+        self.use_full_frame(spi, delay).await
+    }
+
+    async fn turn_on_display(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+        sequence: u8,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.wait_until_idle(spi, delay).await?;
+        self.interface
+            .cmd_with_data(spi, Command::DisplayUpdateControl2, &[sequence])
+            .await?;
+        self.interface.cmd(spi, Command::MasterActivation).await?;
+        self.wait_until_idle(spi, delay).await
+    }
+}
+
+#[maybe_async_cfg::maybe(
+    sync(keep_self),
+    async(
+        feature = "async",
+        idents(
+            Epd2in13,
+            DisplayInterface,
+            WaveshareDisplay,
+            InternalWiAdditions,
+            QuickRefresh,
+            SpiDevice(async = "AsyncSpiDevice"),
+            BusyPin(async = "AsyncBusyPin"),
+            DelayNs(async = "AsyncDelayNs")
+        )
+    )
+)]
+impl<SPI, BUSY, DC, RST, DELAY> QuickRefresh<SPI, BUSY, DC, RST, DELAY>
+    for Epd2in13<SPI, BUSY, DC, RST, DELAY>
+where
+    SPI: SpiDevice,
+    BUSY: BusyPin,
+    DC: OutputPin,
+    RST: OutputPin,
+    DELAY: DelayNs,
+{
+    /// Writes the frame shown by the panel, as the new and the old frame
+    async fn update_old_frame(
+        &mut self,
+        spi: &mut SPI,
+        buffer: &[u8],
+        delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.update_frame(spi, buffer, delay).await?;
+        self.interface
+            .cmd_with_data(spi, Command::WriteRam2, buffer)
+            .await
+    }
+
+    async fn update_new_frame(
+        &mut self,
+        spi: &mut SPI,
+        buffer: &[u8],
+        delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.update_frame(spi, buffer, delay).await
+    }
+
+    /// Refreshes the pixels which differ from the old frame
+    async fn display_new_frame(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.turn_on_display(spi, delay, QUICK_REFRESH).await
+    }
+
+    /// Updates and displays the new frame, then keeps it as the old frame of the next one
+    async fn update_and_display_new_frame(
+        &mut self,
+        spi: &mut SPI,
+        buffer: &[u8],
+        delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.update_new_frame(spi, buffer, delay).await?;
+        self.display_new_frame(spi, delay).await?;
+        self.interface
+            .cmd_with_data(spi, Command::WriteRam2, buffer)
+            .await
+    }
+
+    /// `x` and `width` must be multiples of 8
+    async fn update_partial_old_frame(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+        buffer: &[u8],
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.write_window(spi, delay, Command::WriteRam2, buffer, x, y, width, height)
+            .await
+    }
+
+    /// `x` and `width` must be multiples of 8
+    async fn update_partial_new_frame(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+        buffer: &[u8],
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.write_window(spi, delay, Command::WriteRam, buffer, x, y, width, height)
+            .await
+    }
+
+    /// Fills a window of the new frame with the background color, the old frame is kept
+    ///
+    /// `x` and `width` must be multiples of 8.
+    async fn clear_partial_frame(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.wait_until_idle(spi, delay).await?;
+        self.interface
+            .set_ram_area::<Ssd1680>(spi, x, y, x + width - 1, y + height - 1)
+            .await?;
+        self.interface.set_ram_counter::<Ssd1680>(spi, x, y).await?;
+
+        let color = self.background_color.get_byte_value();
+        self.interface.cmd(spi, Command::WriteRam).await?;
+        self.interface
+            .data_x_times(spi, color, width / 8 * height)
+            .await?;
+
+        self.use_full_frame(spi, delay).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn epd_size() {
+        assert_eq!(WIDTH, 122);
+        assert_eq!(HEIGHT, 250);
+        assert_eq!(buffer_len(WIDTH as usize, HEIGHT as usize), 16 * 250);
+        assert_eq!(DEFAULT_BACKGROUND_COLOR, Color::White);
+    }
+}
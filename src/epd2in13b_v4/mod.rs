@@ -51,17 +51,16 @@
 //!# }
 //!```
 // Original Waveforms from Waveshare
-use embedded_hal::{
-    delay::DelayNs,
-    digital::{InputPin, OutputPin},
-    spi::SpiDevice,
-};
+use crate::error::EpdError;
+use crate::pin::BusyPin;
+use embedded_hal::{delay::DelayNs, digital::OutputPin, spi::SpiDevice};
 
 use crate::buffer_len;
 use crate::color::TriColor;
-use crate::interface::DisplayInterface;
+use crate::interface::{BusyTimings, DisplayInterface};
+use crate::ram_addressing::Ssd1680;
 use crate::traits::{
-    InternalWiAdditions, RefreshLut, WaveshareDisplay, WaveshareThreeColorDisplay,
+    InterfaceConfig, InternalWiAdditions, RefreshLut, WaveshareDisplay, WaveshareThreeColorDisplay,
 };
 
 pub(crate) mod command;
@@ -70,6 +69,14 @@ use self::command::{
     DataEntryModeDir, DataEntryModeIncr, DeepSleepMode, DisplayUpdateControl, DriverOutput,
     RamOption,
 };
+#[cfg(feature = "async")]
+use crate::interface::DisplayInterfaceAsync;
+#[cfg(feature = "async")]
+use crate::traits::{
+    AsyncBusyPin, InternalWiAdditionsAsync, WaveshareDisplayAsync, WaveshareThreeColorDisplayAsync,
+};
+#[cfg(feature = "async")]
+use embedded_hal_async::{delay::DelayNs as AsyncDelayNs, spi::SpiDevice as AsyncSpiDevice};
 
 const SINGLE_BYTE_WRITE: bool = true;
 
@@ -79,7 +86,7 @@ pub type Display2in13b = crate::graphics::Display<
     WIDTH,
     HEIGHT,
     false,
-    { buffer_len(WIDTH as usize, HEIGHT as usize) * 2 },
+    { crate::graphics::display_len::<TriColor>(WIDTH, HEIGHT) },
     TriColor,
 >;
 
@@ -93,6 +100,22 @@ pub const HEIGHT: u32 = 250;
 pub const DEFAULT_BACKGROUND_COLOR: TriColor = TriColor::White;
 const IS_BUSY_LOW: bool = false;
 
+#[maybe_async_cfg::maybe(
+    sync(keep_self),
+    async(
+        feature = "async",
+        idents(
+            Epd2in13b,
+            DisplayInterface,
+            WaveshareDisplay,
+            InternalWiAdditions,
+            WaveshareThreeColorDisplay,
+            SpiDevice(async = "AsyncSpiDevice"),
+            BusyPin(async = "AsyncBusyPin"),
+            DelayNs(async = "AsyncDelayNs")
+        )
+    )
+)]
 /// Epd2in13b (V4) driver
 pub struct Epd2in13b<SPI, BUSY, DC, RST, DELAY> {
     /// Connection Interface
@@ -102,22 +125,38 @@ pub struct Epd2in13b<SPI, BUSY, DC, RST, DELAY> {
     background_color: TriColor,
 }
 
+#[maybe_async_cfg::maybe(
+    sync(keep_self),
+    async(
+        feature = "async",
+        idents(
+            Epd2in13b,
+            DisplayInterface,
+            WaveshareDisplay,
+            InternalWiAdditions,
+            WaveshareThreeColorDisplay,
+            SpiDevice(async = "AsyncSpiDevice"),
+            BusyPin(async = "AsyncBusyPin"),
+            DelayNs(async = "AsyncDelayNs")
+        )
+    )
+)]
 impl<SPI, BUSY, DC, RST, DELAY> InternalWiAdditions<SPI, BUSY, DC, RST, DELAY>
     for Epd2in13b<SPI, BUSY, DC, RST, DELAY>
 where
     SPI: SpiDevice,
-    BUSY: InputPin,
+    BUSY: BusyPin,
     DC: OutputPin,
     RST: OutputPin,
     DELAY: DelayNs,
 {
-    fn init(&mut self, spi: &mut SPI, delay: &mut DELAY) -> Result<(), SPI::Error> {
+    async fn init(&mut self, spi: &mut SPI, delay: &mut DELAY) -> Result<(), EpdError<SPI::Error>> {
         // HW reset
-        self.interface.reset(delay, 10_000, 10_000);
+        self.interface.reset(delay, 10_000, 10_000).await?;
 
-        self.wait_until_idle(spi, delay)?;
-        self.interface.cmd(spi, Command::SwReset)?;
-        self.wait_until_idle(spi, delay)?;
+        self.wait_until_idle(spi, delay).await?;
+        self.interface.cmd(spi, Command::SwReset).await?;
+        self.wait_until_idle(spi, delay).await?;
 
         self.set_driver_output(
             spi,
@@ -127,13 +166,15 @@ where
                 scan_dir_incr: true,
                 width: (HEIGHT - 1) as u16,
             },
-        )?;
+        )
+        .await?;
 
-        self.set_data_entry_mode(spi, DataEntryModeIncr::XIncrYIncr, DataEntryModeDir::XDir)?;
+        self.set_data_entry_mode(spi, DataEntryModeIncr::XIncrYIncr, DataEntryModeDir::XDir)
+            .await?;
 
         // Use simple X/Y auto increase
-        self.set_ram_area(spi, 0, 0, WIDTH - 1, HEIGHT - 1)?;
-        self.set_ram_address_counters(spi, delay, 0, 0)?;
+        self.set_ram_area(spi, 0, 0, WIDTH - 1, HEIGHT - 1).await?;
+        self.set_ram_address_counters(spi, delay, 0, 0).await?;
 
         self.set_border_waveform(
             spi,
@@ -142,11 +183,15 @@ where
                 fix_level: BorderWaveFormFixLevel::Vss,
                 gs_trans: BorderWaveFormGs::Lut3,
             },
-        )?;
+        )
+        .await?;
 
-        self.cmd_with_data(spi, Command::WriteVcomRegister, &[0x36])?;
-        self.cmd_with_data(spi, Command::GateDrivingVoltageCtrl, &[0x17])?;
-        self.cmd_with_data(spi, Command::SourceDrivingVoltageCtrl, &[0x41, 0x00, 0x32])?;
+        self.cmd_with_data(spi, Command::WriteVcomRegister, &[0x36])
+            .await?;
+        self.cmd_with_data(spi, Command::GateDrivingVoltageCtrl, &[0x17])
+            .await?;
+        self.cmd_with_data(spi, Command::SourceDrivingVoltageCtrl, &[0x41, 0x00, 0x32])
+            .await?;
 
         self.set_display_update_control(
             spi,
@@ -155,112 +200,161 @@ where
                 bw_ram_option: RamOption::Normal,
                 source_output_mode: true,
             },
-        )?;
+        )
+        .await?;
 
-        self.wait_until_idle(spi, delay)?;
+        self.wait_until_idle(spi, delay).await?;
 
         Ok(())
     }
 }
 
+#[maybe_async_cfg::maybe(
+    sync(keep_self),
+    async(
+        feature = "async",
+        idents(
+            Epd2in13b,
+            DisplayInterface,
+            WaveshareDisplay,
+            InternalWiAdditions,
+            WaveshareThreeColorDisplay,
+            SpiDevice(async = "AsyncSpiDevice"),
+            BusyPin(async = "AsyncBusyPin"),
+            DelayNs(async = "AsyncDelayNs")
+        )
+    )
+)]
 impl<SPI, BUSY, DC, RST, DELAY> WaveshareThreeColorDisplay<SPI, BUSY, DC, RST, DELAY>
     for Epd2in13b<SPI, BUSY, DC, RST, DELAY>
 where
     SPI: SpiDevice,
-    BUSY: InputPin,
+    BUSY: BusyPin,
     DC: OutputPin,
     RST: OutputPin,
     DELAY: DelayNs,
 {
-    fn update_color_frame(
+    async fn update_color_frame(
         &mut self,
         spi: &mut SPI,
         delay: &mut DELAY,
         black: &[u8],
         chromatic: &[u8],
-    ) -> Result<(), SPI::Error> {
-        self.update_achromatic_frame(spi, delay, black)?;
-        self.update_chromatic_frame(spi, delay, chromatic)
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.update_achromatic_frame(spi, delay, black).await?;
+        self.update_chromatic_frame(spi, delay, chromatic).await
     }
 
-    fn update_achromatic_frame(
+    async fn update_achromatic_frame(
         &mut self,
         spi: &mut SPI,
         _delay: &mut DELAY,
         black: &[u8],
-    ) -> Result<(), SPI::Error> {
-        self.interface.cmd(spi, Command::WriteRam)?;
-        self.interface.data(spi, black)?;
+    ) -> Result<(), EpdError<SPI::Error>> {
+        EpdError::check_buffer(black, buffer_len(WIDTH as usize, HEIGHT as usize))?;
+        self.interface.cmd(spi, Command::WriteRam).await?;
+        self.interface.data(spi, black).await?;
         Ok(())
     }
 
-    fn update_chromatic_frame(
+    async fn update_chromatic_frame(
         &mut self,
         spi: &mut SPI,
         _delay: &mut DELAY,
         chromatic: &[u8],
-    ) -> Result<(), SPI::Error> {
-        self.interface.cmd(spi, Command::WriteRamRed)?;
-        self.interface.data(spi, chromatic)?;
+    ) -> Result<(), EpdError<SPI::Error>> {
+        EpdError::check_buffer(chromatic, buffer_len(WIDTH as usize, HEIGHT as usize))?;
+        self.interface.cmd(spi, Command::WriteRamRed).await?;
+        self.interface.data(spi, chromatic).await?;
         Ok(())
     }
 }
 
+#[maybe_async_cfg::maybe(
+    sync(keep_self),
+    async(
+        feature = "async",
+        idents(
+            Epd2in13b,
+            DisplayInterface,
+            WaveshareDisplay,
+            InternalWiAdditions,
+            WaveshareThreeColorDisplay,
+            SpiDevice(async = "AsyncSpiDevice"),
+            BusyPin(async = "AsyncBusyPin"),
+            DelayNs(async = "AsyncDelayNs")
+        )
+    )
+)]
 impl<SPI, BUSY, DC, RST, DELAY> WaveshareDisplay<SPI, BUSY, DC, RST, DELAY>
     for Epd2in13b<SPI, BUSY, DC, RST, DELAY>
 where
     SPI: SpiDevice,
-    BUSY: InputPin,
+    BUSY: BusyPin,
     DC: OutputPin,
     RST: OutputPin,
     DELAY: DelayNs,
 {
     type DisplayColor = TriColor;
-    fn new(
+    async fn new(
         spi: &mut SPI,
         busy: BUSY,
         dc: DC,
         rst: RST,
         delay: &mut DELAY,
         delay_us: Option<u32>,
-    ) -> Result<Self, SPI::Error> {
-        let mut epd = Epd2in13b {
-            interface: DisplayInterface::new(busy, dc, rst, delay_us),
-            background_color: DEFAULT_BACKGROUND_COLOR,
-        };
-
-        epd.init(spi, delay)?;
+    ) -> Result<Self, EpdError<SPI::Error>> {
+        let mut epd = Self::new_uninit(busy, dc, rst, delay_us);
+        epd.init(spi, delay).await?;
         Ok(epd)
     }
 
-    fn wake_up(&mut self, spi: &mut SPI, delay: &mut DELAY) -> Result<(), SPI::Error> {
-        self.init(spi, delay)
+    fn new_uninit(busy: BUSY, dc: DC, rst: RST, delay_us: Option<u32>) -> Self {
+        Epd2in13b {
+            interface: DisplayInterface::new(busy, dc, rst, delay_us)
+                .with_busy_timings(BusyTimings::SSD16XX_COLOR),
+            background_color: DEFAULT_BACKGROUND_COLOR,
+        }
+    }
+
+    async fn wake_up(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.init(spi, delay).await
     }
 
-    fn sleep(&mut self, spi: &mut SPI, _delay: &mut DELAY) -> Result<(), SPI::Error> {
-        self.set_sleep_mode(spi, DeepSleepMode::Normal)?;
+    async fn sleep(
+        &mut self,
+        spi: &mut SPI,
+        _delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.set_sleep_mode(spi, DeepSleepMode::Normal).await?;
         Ok(())
     }
 
-    fn update_frame(
+    async fn update_frame(
         &mut self,
         spi: &mut SPI,
         buffer: &[u8],
         _delay: &mut DELAY,
-    ) -> Result<(), SPI::Error> {
-        assert!(buffer.len() == buffer_len(WIDTH as usize, HEIGHT as usize));
-        self.cmd_with_data(spi, Command::WriteRam, buffer)?;
-
-        self.command(spi, Command::WriteRamRed)?;
-        self.interface.data_x_times(
-            spi,
-            TriColor::Black.get_byte_value(),
-            buffer_len(WIDTH as usize, HEIGHT as usize) as u32,
-        )?;
+    ) -> Result<(), EpdError<SPI::Error>> {
+        EpdError::check_buffer(buffer, buffer_len(WIDTH as usize, HEIGHT as usize))?;
+        self.cmd_with_data(spi, Command::WriteRam, buffer).await?;
+
+        self.command(spi, Command::WriteRamRed).await?;
+        self.interface
+            .data_x_times(
+                spi,
+                TriColor::Black.get_byte_value(),
+                buffer_len(WIDTH as usize, HEIGHT as usize) as u32,
+            )
+            .await?;
         Ok(())
     }
 
-    fn update_partial_frame(
+    async fn update_partial_frame(
         &mut self,
         _spi: &mut SPI,
         _delay: &mut DELAY,
@@ -269,31 +363,39 @@ where
         _y: u32,
         _width: u32,
         _height: u32,
-    ) -> Result<(), SPI::Error> {
+    ) -> Result<(), EpdError<SPI::Error>> {
         unimplemented!();
     }
 
-    fn display_frame(&mut self, spi: &mut SPI, delay: &mut DELAY) -> Result<(), SPI::Error> {
-        self.command(spi, Command::MasterActivation)?;
-        self.wait_until_idle(spi, delay)?;
+    async fn display_frame(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.command(spi, Command::MasterActivation).await?;
+        self.wait_until_idle(spi, delay).await?;
 
         Ok(())
     }
 
-    fn update_and_display_frame(
+    async fn update_and_display_frame(
         &mut self,
         spi: &mut SPI,
         buffer: &[u8],
         delay: &mut DELAY,
-    ) -> Result<(), SPI::Error> {
-        self.update_frame(spi, buffer, delay)?;
-        self.display_frame(spi, delay)?;
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.update_frame(spi, buffer, delay).await?;
+        self.display_frame(spi, delay).await?;
         Ok(())
     }
 
-    fn clear_frame(&mut self, spi: &mut SPI, _delay: &mut DELAY) -> Result<(), SPI::Error> {
-        self.clear_achromatic_frame(spi)?;
-        self.clear_chromatic_frame(spi)
+    async fn clear_frame(
+        &mut self,
+        spi: &mut SPI,
+        _delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.clear_achromatic_frame(spi).await?;
+        self.clear_chromatic_frame(spi).await
     }
 
     fn set_background_color(&mut self, background_color: TriColor) {
@@ -312,189 +414,225 @@ where
         HEIGHT
     }
 
-    fn set_lut(
+    async fn set_lut(
         &mut self,
         _spi: &mut SPI,
         _delay: &mut DELAY,
         _refresh_rate: Option<RefreshLut>,
-    ) -> Result<(), SPI::Error> {
+    ) -> Result<(), EpdError<SPI::Error>> {
         unimplemented!()
     }
 
-    fn wait_until_idle(&mut self, _spi: &mut SPI, delay: &mut DELAY) -> Result<(), SPI::Error> {
-        self.interface.wait_until_idle(delay, IS_BUSY_LOW);
-        Ok(())
+    fn interface_config_mut(&mut self) -> &mut InterfaceConfig {
+        self.interface.config_mut()
+    }
+
+    fn release(self) -> (BUSY, DC, RST) {
+        self.interface.release()
+    }
+
+    async fn wait_until_idle(
+        &mut self,
+        _spi: &mut SPI,
+        delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.interface.wait_until_idle(delay, IS_BUSY_LOW).await
     }
 }
 
+#[maybe_async_cfg::maybe(
+    sync(keep_self),
+    async(
+        feature = "async",
+        idents(
+            Epd2in13b,
+            DisplayInterface,
+            WaveshareDisplay,
+            InternalWiAdditions,
+            WaveshareThreeColorDisplay,
+            SpiDevice(async = "AsyncSpiDevice"),
+            BusyPin(async = "AsyncBusyPin"),
+            DelayNs(async = "AsyncDelayNs")
+        )
+    )
+)]
 impl<SPI, BUSY, DC, RST, DELAY> Epd2in13b<SPI, BUSY, DC, RST, DELAY>
 where
     SPI: SpiDevice,
-    BUSY: InputPin,
+    BUSY: BusyPin,
     DC: OutputPin,
     RST: OutputPin,
     DELAY: DelayNs,
 {
-    fn set_display_update_control(
+    async fn set_display_update_control(
         &mut self,
         spi: &mut SPI,
         display_update_control: DisplayUpdateControl,
-    ) -> Result<(), SPI::Error> {
+    ) -> Result<(), EpdError<SPI::Error>> {
         self.cmd_with_data(
             spi,
             Command::DisplayUpdateControl1,
             &display_update_control.to_bytes(),
         )
+        .await
     }
 
-    fn set_border_waveform(
+    async fn set_border_waveform(
         &mut self,
         spi: &mut SPI,
         borderwaveform: BorderWaveForm,
-    ) -> Result<(), SPI::Error> {
+    ) -> Result<(), EpdError<SPI::Error>> {
         self.cmd_with_data(
             spi,
             Command::BorderWaveformControl,
             &[borderwaveform.to_u8()],
         )
+        .await
     }
 
     /// Triggers the deep sleep mode
-    fn set_sleep_mode(&mut self, spi: &mut SPI, mode: DeepSleepMode) -> Result<(), SPI::Error> {
+    async fn set_sleep_mode(
+        &mut self,
+        spi: &mut SPI,
+        mode: DeepSleepMode,
+    ) -> Result<(), EpdError<SPI::Error>> {
         self.cmd_with_data(spi, Command::DeepSleepMode, &[mode as u8])
+            .await
     }
 
-    fn set_driver_output(&mut self, spi: &mut SPI, output: DriverOutput) -> Result<(), SPI::Error> {
+    async fn set_driver_output(
+        &mut self,
+        spi: &mut SPI,
+        output: DriverOutput,
+    ) -> Result<(), EpdError<SPI::Error>> {
         self.cmd_with_data(spi, Command::DriverOutputControl, &output.to_bytes())
+            .await
     }
 
     /// Sets the data entry mode (ie. how X and Y positions changes when writing
     /// data to RAM)
-    fn set_data_entry_mode(
+    async fn set_data_entry_mode(
         &mut self,
         spi: &mut SPI,
         counter_incr_mode: DataEntryModeIncr,
         counter_direction: DataEntryModeDir,
-    ) -> Result<(), SPI::Error> {
+    ) -> Result<(), EpdError<SPI::Error>> {
         let mode = counter_incr_mode as u8 | counter_direction as u8;
         self.cmd_with_data(spi, Command::DataEntryModeSetting, &[mode])
+            .await
     }
 
     /// Sets both X and Y pixels ranges
-    fn set_ram_area(
+    async fn set_ram_area(
         &mut self,
         spi: &mut SPI,
         start_x: u32,
         start_y: u32,
         end_x: u32,
         end_y: u32,
-    ) -> Result<(), SPI::Error> {
-        self.cmd_with_data(
-            spi,
-            Command::SetRamXAddressStartEndPosition,
-            &[(start_x >> 3) as u8, (end_x >> 3) as u8],
-        )?;
-
-        self.cmd_with_data(
-            spi,
-            Command::SetRamYAddressStartEndPosition,
-            &[
-                start_y as u8,
-                (start_y >> 8) as u8,
-                end_y as u8,
-                (end_y >> 8) as u8,
-            ],
-        )
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.interface
+            .set_ram_area::<Ssd1680>(spi, start_x, start_y, end_x, end_y)
+            .await
     }
 
     /// Sets both X and Y pixels counters when writing data to RAM
-    fn set_ram_address_counters(
+    async fn set_ram_address_counters(
         &mut self,
         spi: &mut SPI,
         delay: &mut DELAY,
         x: u32,
         y: u32,
-    ) -> Result<(), SPI::Error> {
-        self.wait_until_idle(spi, delay)?;
-        self.cmd_with_data(spi, Command::SetRamXAddressCounter, &[(x >> 3) as u8])?;
-
-        self.cmd_with_data(
-            spi,
-            Command::SetRamYAddressCounter,
-            &[y as u8, (y >> 8) as u8],
-        )?;
-        Ok(())
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.wait_until_idle(spi, delay).await?;
+        self.interface.set_ram_counter::<Ssd1680>(spi, x, y).await
     }
 
-    fn command(&mut self, spi: &mut SPI, command: Command) -> Result<(), SPI::Error> {
-        self.interface.cmd(spi, command)
+    async fn command(
+        &mut self,
+        spi: &mut SPI,
+        command: Command,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.interface.cmd(spi, command).await
     }
 
-    fn cmd_with_data(
+    async fn cmd_with_data(
         &mut self,
         spi: &mut SPI,
         command: Command,
         data: &[u8],
-    ) -> Result<(), SPI::Error> {
-        self.interface.cmd_with_data(spi, command, data)
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.interface.cmd_with_data(spi, command, data).await
     }
 
-    fn clear_achromatic_frame(&mut self, spi: &mut SPI) -> Result<(), SPI::Error> {
+    async fn clear_achromatic_frame(&mut self, spi: &mut SPI) -> Result<(), EpdError<SPI::Error>> {
         match self.background_color {
             TriColor::White => {
-                self.command(spi, Command::WriteRam)?;
-                self.interface.data_x_times(
-                    spi,
-                    0xFF,
-                    buffer_len(WIDTH as usize, HEIGHT as usize) as u32,
-                )?;
+                self.command(spi, Command::WriteRam).await?;
+                self.interface
+                    .data_x_times(
+                        spi,
+                        0xFF,
+                        buffer_len(WIDTH as usize, HEIGHT as usize) as u32,
+                    )
+                    .await?;
             }
             TriColor::Chromatic => {
-                self.command(spi, Command::WriteRam)?;
-                self.interface.data_x_times(
-                    spi,
-                    0xFF,
-                    buffer_len(WIDTH as usize, HEIGHT as usize) as u32,
-                )?;
+                self.command(spi, Command::WriteRam).await?;
+                self.interface
+                    .data_x_times(
+                        spi,
+                        0xFF,
+                        buffer_len(WIDTH as usize, HEIGHT as usize) as u32,
+                    )
+                    .await?;
             }
             TriColor::Black => {
-                self.command(spi, Command::WriteRam)?;
-                self.interface.data_x_times(
-                    spi,
-                    0x00,
-                    buffer_len(WIDTH as usize, HEIGHT as usize) as u32,
-                )?;
+                self.command(spi, Command::WriteRam).await?;
+                self.interface
+                    .data_x_times(
+                        spi,
+                        0x00,
+                        buffer_len(WIDTH as usize, HEIGHT as usize) as u32,
+                    )
+                    .await?;
             }
         }
 
         Ok(())
     }
 
-    fn clear_chromatic_frame(&mut self, spi: &mut SPI) -> Result<(), SPI::Error> {
+    async fn clear_chromatic_frame(&mut self, spi: &mut SPI) -> Result<(), EpdError<SPI::Error>> {
         match self.background_color {
             TriColor::White => {
-                self.command(spi, Command::WriteRam)?;
-                self.interface.data_x_times(
-                    spi,
-                    0x00,
-                    buffer_len(WIDTH as usize, HEIGHT as usize) as u32,
-                )?;
+                self.command(spi, Command::WriteRamRed).await?;
+                self.interface
+                    .data_x_times(
+                        spi,
+                        0x00,
+                        buffer_len(WIDTH as usize, HEIGHT as usize) as u32,
+                    )
+                    .await?;
             }
             TriColor::Chromatic => {
-                self.command(spi, Command::WriteRam)?;
-                self.interface.data_x_times(
-                    spi,
-                    0xFF,
-                    buffer_len(WIDTH as usize, HEIGHT as usize) as u32,
-                )?;
+                self.command(spi, Command::WriteRamRed).await?;
+                self.interface
+                    .data_x_times(
+                        spi,
+                        0xFF,
+                        buffer_len(WIDTH as usize, HEIGHT as usize) as u32,
+                    )
+                    .await?;
             }
             TriColor::Black => {
-                self.command(spi, Command::WriteRam)?;
-                self.interface.data_x_times(
-                    spi,
-                    0x00,
-                    buffer_len(WIDTH as usize, HEIGHT as usize) as u32,
-                )?;
+                self.command(spi, Command::WriteRamRed).await?;
+                self.interface
+                    .data_x_times(
+                        spi,
+                        0x00,
+                        buffer_len(WIDTH as usize, HEIGHT as usize) as u32,
+                    )
+                    .await?;
             }
         }
 
@@ -0,0 +1,644 @@
+//! A simple Driver for the Waveshare 2.13" D flexible E-Ink Display via SPI
+//!
+//! The panel is driven by an UC8151D, like the 2.9" D: it shares its commands and the LUTs of
+//! its quick refreshes with [`epd2in9d`](crate::epd2in9d). Full refreshes use the waveform
+//! stored in the OTP of the panel, quick refreshes load a difference based waveform into the
+//! LUT registers, which only moves the pixels changed between the old frame (DTM1) and the new
+//! one (DTM2). The controller doesn't copy the new frame to the old one after a refresh, so the
+//! old frame has to be sent again with [`QuickRefresh`] before each quick refresh.
+//!
+//! # Example for the 2.13" D E-Ink Display
+//!
+//!```rust, no_run
+//!# use embedded_hal_mock::eh1::*;
+//!# fn main() -> Result<(), epd_waveshare::error::EpdError<embedded_hal::spi::ErrorKind>> {
+//!use embedded_graphics::{prelude::*, primitives::{Line, PrimitiveStyle}};
+//!use epd_waveshare::{epd2in13d::*, prelude::*};
+//!#
+//!# let expectations = [];
+//!# let mut spi = spi::Mock::new(&expectations);
+//!# let expectations = [];
+//!# let cs_pin = digital::Mock::new(&expectations);
+//!# let busy_in = digital::Mock::new(&expectations);
+//!# let dc = digital::Mock::new(&expectations);
+//!# let rst = digital::Mock::new(&expectations);
+//!# let mut delay = delay::NoopDelay::new();
+//!
+//!// Setup EPD
+//!let mut epd = Epd2in13d::new(&mut spi, busy_in, dc, rst, &mut delay, None)?;
+//!
+//!// Draw and show a first frame with a full refresh
+//!let mut display = Display2in13d::default();
+//!display.clear(Color::White).ok();
+//!epd.update_and_display_frame(&mut spi, display.buffer(), &mut delay)?;
+//!
+//!// Move on to quick refreshes, giving the frame on the panel as the old one
+//!epd.set_lut(&mut spi, &mut delay, Some(RefreshLut::Quick))?;
+//!epd.update_old_frame(&mut spi, display.buffer(), &mut delay)?;
+//!let _ = Line::new(Point::new(0, 120), Point::new(0, 200))
+//!    .into_styled(PrimitiveStyle::with_stroke(Color::Black, 1))
+//!    .draw(&mut display);
+//!epd.update_and_display_new_frame(&mut spi, display.buffer(), &mut delay)?;
+//!
+//!// Set the EPD to sleep
+//!epd.sleep(&mut spi, &mut delay)?;
+//!# Ok(())
+//!# }
+//!```
+
+use embedded_hal::{delay::DelayNs, digital::OutputPin, spi::SpiDevice};
+
+use crate::color::Color;
+use crate::epd2in9d::command::Command;
+use crate::epd2in9d::constants::*;
+use crate::error::EpdError;
+use crate::interface::DisplayInterface;
+#[cfg(feature = "async")]
+use crate::interface::DisplayInterfaceAsync;
+use crate::pin::BusyPin;
+#[cfg(feature = "async")]
+use crate::traits::{
+    AsyncBusyPin, InternalWiAdditionsAsync, QuickRefreshAsync, WaveshareDisplayAsync,
+};
+use crate::traits::{
+    InterfaceConfig, InternalWiAdditions, QuickRefresh, RefreshLut, WaveshareDisplay,
+};
+#[cfg(feature = "async")]
+use embedded_hal_async::{delay::DelayNs as AsyncDelayNs, spi::SpiDevice as AsyncSpiDevice};
+
+/// Width of Epd2in13d in pixels
+pub const WIDTH: u32 = 104;
+/// Height of Epd2in13d in pixels
+pub const HEIGHT: u32 = 212;
+/// Default Background Color (white)
+pub const DEFAULT_BACKGROUND_COLOR: Color = Color::White;
+const IS_BUSY_LOW: bool = true;
+const SINGLE_BYTE_WRITE: bool = true;
+
+/// Number of bytes of a full frame
+const FRAME_BYTES: u32 = WIDTH / 8 * HEIGHT;
+
+/// Display with Fullsize buffer for use with the 2in13 EPD D
+#[cfg(feature = "graphics")]
+pub type Display2in13d = crate::graphics::Display<
+    WIDTH,
+    HEIGHT,
+    false,
+    { crate::graphics::display_len::<Color>(WIDTH, HEIGHT) },
+    Color,
+>;
+
+#[maybe_async_cfg::maybe(
+    sync(keep_self),
+    async(
+        feature = "async",
+        idents(
+            Epd2in13d,
+            DisplayInterface,
+            WaveshareDisplay,
+            InternalWiAdditions,
+            QuickRefresh,
+            SpiDevice(async = "AsyncSpiDevice"),
+            BusyPin(async = "AsyncBusyPin"),
+            DelayNs(async = "AsyncDelayNs")
+        )
+    )
+)]
+/// Epd2in13d driver
+pub struct Epd2in13d<SPI, BUSY, DC, RST, DELAY> {
+    /// SPI
+    interface: DisplayInterface<SPI, BUSY, DC, RST, DELAY, SINGLE_BYTE_WRITE>,
+    /// Background Color
+    color: Color,
+    /// Refresh LUT
+    refresh: RefreshLut,
+}
+
+#[maybe_async_cfg::maybe(
+    sync(keep_self),
+    async(
+        feature = "async",
+        idents(
+            Epd2in13d,
+            DisplayInterface,
+            WaveshareDisplay,
+            InternalWiAdditions,
+            QuickRefresh,
+            SpiDevice(async = "AsyncSpiDevice"),
+            BusyPin(async = "AsyncBusyPin"),
+            DelayNs(async = "AsyncDelayNs")
+        )
+    )
+)]
+impl<SPI, BUSY, DC, RST, DELAY> InternalWiAdditions<SPI, BUSY, DC, RST, DELAY>
+    for Epd2in13d<SPI, BUSY, DC, RST, DELAY>
+where
+    SPI: SpiDevice,
+    BUSY: BusyPin,
+    DC: OutputPin,
+    RST: OutputPin,
+    DELAY: DelayNs,
+{
+    async fn init(&mut self, spi: &mut SPI, delay: &mut DELAY) -> Result<(), EpdError<SPI::Error>> {
+        self.interface.reset(delay, 10_000, 2_000).await?;
+
+        match self.refresh {
+            RefreshLut::Quick => {
+                self.interface
+                    .cmd_with_data(spi, Command::PowerSetting, &[0x03, 0x00, 0x2b, 0x2b, 0x03])
+                    .await?;
+                self.interface
+                    .cmd_with_data(spi, Command::BoosterSoftStart, &[0x17, 0x17, 0x17])
+                    .await?;
+
+                // LUT from the registers
+                self.interface
+                    .cmd_with_data(spi, Command::PanelSetting, &[0xbf, 0x0D])
+                    .await?;
+                self.interface
+                    .cmd_with_data(spi, Command::PllControl, &[0x3C])
+                    .await?;
+                self.send_resolution(spi).await?;
+                self.interface
+                    .cmd_with_data(spi, Command::VcmDcSetting, &[0x12])
+                    .await?;
+
+                self.interface
+                    .cmd_with_data(spi, Command::LutForVcom, &LUT_VCOM1)
+                    .await?;
+                self.interface
+                    .cmd_with_data(spi, Command::LutWhiteToWhite, &LUT_WW1)
+                    .await?;
+                self.interface
+                    .cmd_with_data(spi, Command::LutBlackToWhite, &LUT_BW1)
+                    .await?;
+                self.interface
+                    .cmd_with_data(spi, Command::LutWhiteToBlack, &LUT_WB1)
+                    .await?;
+                self.interface
+                    .cmd_with_data(spi, Command::LutBlackToBlack, &LUT_BB1)
+                    .await?;
+
+                self.interface.cmd(spi, Command::PowerOn).await?;
+                self.wait_until_idle(spi, delay).await?;
+            }
+            _ => {
+                // LUT from the OTP
+                self.interface
+                    .cmd_with_data(spi, Command::PanelSetting, &[0x1f, 0x0D])
+                    .await?;
+                self.send_resolution(spi).await?;
+
+                self.interface.cmd(spi, Command::PowerOn).await?;
+                self.wait_until_idle(spi, delay).await?;
+
+                self.interface
+                    .cmd_with_data(spi, Command::VcomAndDataIntervalSetting, &[0x97])
+                    .await?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[maybe_async_cfg::maybe(
+    sync(keep_self),
+    async(
+        feature = "async",
+        idents(
+            Epd2in13d,
+            DisplayInterface,
+            WaveshareDisplay,
+            InternalWiAdditions,
+            QuickRefresh,
+            SpiDevice(async = "AsyncSpiDevice"),
+            BusyPin(async = "AsyncBusyPin"),
+            DelayNs(async = "AsyncDelayNs")
+        )
+    )
+)]
+impl<SPI, BUSY, DC, RST, DELAY> WaveshareDisplay<SPI, BUSY, DC, RST, DELAY>
+    for Epd2in13d<SPI, BUSY, DC, RST, DELAY>
+where
+    SPI: SpiDevice,
+    BUSY: BusyPin,
+    DC: OutputPin,
+    RST: OutputPin,
+    DELAY: DelayNs,
+{
+    type DisplayColor = Color;
+    async fn new(
+        spi: &mut SPI,
+        busy: BUSY,
+        dc: DC,
+        rst: RST,
+        delay: &mut DELAY,
+        delay_us: Option<u32>,
+    ) -> Result<Self, EpdError<SPI::Error>> {
+        let mut epd = Self::new_uninit(busy, dc, rst, delay_us);
+        epd.init(spi, delay).await?;
+        Ok(epd)
+    }
+
+    fn new_uninit(busy: BUSY, dc: DC, rst: RST, delay_us: Option<u32>) -> Self {
+        let interface = DisplayInterface::new(busy, dc, rst, delay_us);
+
+        Epd2in13d {
+            interface,
+            color: DEFAULT_BACKGROUND_COLOR,
+            refresh: RefreshLut::Full,
+        }
+    }
+
+    async fn sleep(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.interface
+            .cmd_with_data(spi, Command::VcomAndDataIntervalSetting, &[0xf7])
+            .await?;
+        self.interface.cmd(spi, Command::PowerOff).await?;
+        self.wait_until_idle(spi, delay).await?;
+        self.interface
+            .cmd_with_data(spi, Command::DeepSleep, &[0xA5])
+            .await?;
+
+        Ok(())
+    }
+
+    async fn wake_up(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.init(spi, delay).await
+    }
+
+    fn set_background_color(&mut self, background_color: Color) {
+        self.color = background_color;
+    }
+
+    fn background_color(&self) -> &Color {
+        &self.color
+    }
+
+    fn width(&self) -> u32 {
+        WIDTH
+    }
+
+    fn height(&self) -> u32 {
+        HEIGHT
+    }
+
+    async fn update_frame(
+        &mut self,
+        spi: &mut SPI,
+        buffer: &[u8],
+        delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.wait_until_idle(spi, delay).await?;
+        EpdError::check_buffer(buffer, FRAME_BYTES as usize)?;
+
+        self.interface
+            .cmd(spi, Command::DataStartTransmission1)
+            .await?;
+        self.interface
+            .data_x_times(spi, self.color.get_byte_value(), FRAME_BYTES)
+            .await?;
+
+        self.interface
+            .cmd_with_data(spi, Command::DataStartTransmission2, buffer)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Only writes the new content of the window (DTM2)
+    ///
+    /// The quick refreshes compare it to the old content, which has to be sent with
+    /// [`QuickRefresh::update_partial_old_frame`] first. `x` and `width` must be multiples of 8.
+    async fn update_partial_frame(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+        buffer: &[u8],
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.wait_until_idle(spi, delay).await?;
+        EpdError::check_buffer(buffer, (width / 8 * height) as usize)?;
+
+        self.interface.cmd(spi, Command::PartialIn).await?;
+        self.set_partial_window(spi, x, y, width, height).await?;
+        self.interface
+            .cmd_with_data(spi, Command::DataStartTransmission2, buffer)
+            .await?;
+        self.interface.cmd(spi, Command::PartialOut).await?;
+
+        Ok(())
+    }
+
+    async fn display_frame(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.interface.cmd(spi, Command::DisplayRefresh).await?;
+        delay.delay_us(1_000).await;
+        self.wait_until_idle(spi, delay).await?;
+        Ok(())
+    }
+
+    async fn update_and_display_frame(
+        &mut self,
+        spi: &mut SPI,
+        buffer: &[u8],
+        delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.update_frame(spi, buffer, delay).await?;
+        self.display_frame(spi, delay).await?;
+        Ok(())
+    }
+
+    async fn clear_frame(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.wait_until_idle(spi, delay).await?;
+        let color_value = self.color.get_byte_value();
+
+        self.interface
+            .cmd(spi, Command::DataStartTransmission1)
+            .await?;
+        self.interface
+            .data_x_times(spi, color_value, FRAME_BYTES)
+            .await?;
+
+        self.interface
+            .cmd(spi, Command::DataStartTransmission2)
+            .await?;
+        self.interface
+            .data_x_times(spi, color_value, FRAME_BYTES)
+            .await?;
+
+        self.display_frame(spi, delay).await
+    }
+
+    /// Switches between the full refresh of the OTP and the quick refresh
+    ///
+    /// The panel is initialised again, so the frame memory has to be written after this.
+    async fn set_lut(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+        refresh_rate: Option<RefreshLut>,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        if let Some(refresh_lut) = refresh_rate {
+            self.refresh = refresh_lut.or_full(&[RefreshLut::Quick]);
+        }
+        self.init(spi, delay).await
+    }
+
+    fn interface_config_mut(&mut self) -> &mut InterfaceConfig {
+        self.interface.config_mut()
+    }
+
+    fn release(self) -> (BUSY, DC, RST) {
+        self.interface.release()
+    }
+
+    async fn wait_until_idle(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.interface
+            .wait_until_idle_with_cmd(spi, delay, IS_BUSY_LOW, Command::GetStatus)
+            .await
+    }
+}
+
+#[maybe_async_cfg::maybe(
+    sync(keep_self),
+    async(
+        feature = "async",
+        idents(
+            Epd2in13d,
+            DisplayInterface,
+            WaveshareDisplay,
+            InternalWiAdditions,
+            QuickRefresh,
+            SpiDevice(async = "AsyncSpiDevice"),
+            BusyPin(async = "AsyncBusyPin"),
+            DelayNs(async = "AsyncDelayNs")
+        )
+    )
+)]
+impl<SPI, BUSY, DC, RST, DELAY> QuickRefresh<SPI, BUSY, DC, RST, DELAY>
+    for Epd2in13d<SPI, BUSY, DC, RST, DELAY>
+where
+    SPI: SpiDevice,
+    BUSY: BusyPin,
+    DC: OutputPin,
+    RST: OutputPin,
+    DELAY: DelayNs,
+{
+    /// To be followed by `update_new_frame`.
+    async fn update_old_frame(
+        &mut self,
+        spi: &mut SPI,
+        buffer: &[u8],
+        delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.wait_until_idle(spi, delay).await?;
+        EpdError::check_buffer(buffer, FRAME_BYTES as usize)?;
+
+        self.interface
+            .cmd_with_data(spi, Command::DataStartTransmission1, buffer)
+            .await
+    }
+
+    /// To be used after `update_old_frame`.
+    async fn update_new_frame(
+        &mut self,
+        spi: &mut SPI,
+        buffer: &[u8],
+        delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.wait_until_idle(spi, delay).await?;
+        EpdError::check_buffer(buffer, FRAME_BYTES as usize)?;
+
+        self.interface
+            .cmd_with_data(spi, Command::DataStartTransmission2, buffer)
+            .await
+    }
+
+    async fn display_new_frame(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.display_frame(spi, delay).await
+    }
+
+    async fn update_and_display_new_frame(
+        &mut self,
+        spi: &mut SPI,
+        buffer: &[u8],
+        delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.update_new_frame(spi, buffer, delay).await?;
+        self.display_frame(spi, delay).await
+    }
+
+    async fn update_partial_old_frame(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+        buffer: &[u8],
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.wait_until_idle(spi, delay).await?;
+        EpdError::check_buffer(buffer, (width / 8 * height) as usize)?;
+
+        self.interface.cmd(spi, Command::PartialIn).await?;
+        self.set_partial_window(spi, x, y, width, height).await?;
+        self.interface
+            .cmd_with_data(spi, Command::DataStartTransmission1, buffer)
+            .await?;
+        self.interface.cmd(spi, Command::PartialOut).await
+    }
+
+    /// Always call `update_partial_old_frame` before this, with buffer-updating code
+    /// between the calls.
+    async fn update_partial_new_frame(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+        buffer: &[u8],
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.update_partial_frame(spi, delay, buffer, x, y, width, height)
+            .await
+    }
+
+    async fn clear_partial_frame(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.wait_until_idle(spi, delay).await?;
+        let color_value = self.color.get_byte_value();
+
+        self.interface.cmd(spi, Command::PartialIn).await?;
+        self.set_partial_window(spi, x, y, width, height).await?;
+
+        self.interface
+            .cmd(spi, Command::DataStartTransmission1)
+            .await?;
+        self.interface
+            .data_x_times(spi, color_value, width / 8 * height)
+            .await?;
+
+        self.interface
+            .cmd(spi, Command::DataStartTransmission2)
+            .await?;
+        self.interface
+            .data_x_times(spi, color_value, width / 8 * height)
+            .await?;
+
+        self.interface.cmd(spi, Command::PartialOut).await
+    }
+}
+
+#[maybe_async_cfg::maybe(
+    sync(keep_self),
+    async(
+        feature = "async",
+        idents(
+            Epd2in13d,
+            DisplayInterface,
+            WaveshareDisplay,
+            InternalWiAdditions,
+            QuickRefresh,
+            SpiDevice(async = "AsyncSpiDevice"),
+            BusyPin(async = "AsyncBusyPin"),
+            DelayNs(async = "AsyncDelayNs")
+        )
+    )
+)]
+impl<SPI, BUSY, DC, RST, DELAY> Epd2in13d<SPI, BUSY, DC, RST, DELAY>
+where
+    SPI: SpiDevice,
+    BUSY: BusyPin,
+    DC: OutputPin,
+    RST: OutputPin,
+    DELAY: DelayNs,
+{
+    async fn send_resolution(&mut self, spi: &mut SPI) -> Result<(), EpdError<SPI::Error>> {
+        self.interface
+            .cmd_with_data(
+                spi,
+                Command::ResolutionSetting,
+                &[WIDTH as u8, (HEIGHT >> 8) as u8, HEIGHT as u8],
+            )
+            .await
+    }
+
+    /// Selects the window written and refreshed while in partial mode
+    async fn set_partial_window(
+        &mut self,
+        spi: &mut SPI,
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        let x_start = x & 0xf8;
+        let y_end = y + height - 1;
+        self.interface
+            .cmd_with_data(
+                spi,
+                Command::PartialWindow,
+                &[
+                    x_start as u8,
+                    ((x_start + width - 1) | 0x07) as u8,
+                    (y >> 8) as u8,
+                    y as u8,
+                    (y_end >> 8) as u8,
+                    y_end as u8,
+                    // Gates scan both inside and outside of the partial window
+                    0x01,
+                ],
+            )
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn epd_size() {
+        assert_eq!(WIDTH, 104);
+        assert_eq!(HEIGHT, 212);
+        assert_eq!(FRAME_BYTES, 2756);
+        assert_eq!(DEFAULT_BACKGROUND_COLOR, Color::White);
+    }
+}
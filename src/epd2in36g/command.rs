@@ -0,0 +1,41 @@
+//! SPI Commands for the Waveshare 2.36" (G) E-Ink Display
+
+use crate::traits;
+
+/// Epd2in36g commands
+///
+/// The commands without a documented name are named after their address, as in the Waveshare
+/// code.
+#[allow(dead_code, clippy::upper_case_acronyms)]
+#[derive(Clone, Copy, Debug)]
+pub(crate) enum Command {
+    PanelSetting = 0x00,
+
+    PowerOff = 0x02,
+    PowerOffSequenceSetting = 0x03,
+    PowerOn = 0x04,
+    BoosterSoftStart = 0x06,
+    DeepSleep = 0x07,
+
+    DataStartTransmission = 0x10,
+    DisplayRefresh = 0x12,
+
+    TemperatureSensorCalibration = 0x41,
+
+    VcomAndDataIntervalSetting = 0x50,
+    TconSetting = 0x60,
+    ResolutionSetting = 0x61,
+
+    Ox66 = 0x66,
+    Ox68 = 0x68,
+    Ox84 = 0x84,
+    OxB0 = 0xB0,
+    OxF0 = 0xF0,
+}
+
+impl traits::Command for Command {
+    /// Returns the address of the command
+    fn address(self) -> u8 {
+        self as u8
+    }
+}
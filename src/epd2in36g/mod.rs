@@ -0,0 +1,400 @@
+//! A simple Driver for the Waveshare 2.36" (G) E-Ink Display via SPI
+//!
+//! The 168x296 panel shows 4 colors: black, white, yellow and red. Its pixels are sent at 2 bits
+//! per pixel, 4 in a byte with the leftmost one in the high bits, as packed by a
+//! [`QuadColor`] display.
+//!
+//! A refresh takes about 20 seconds: [`display_frame`](WaveshareDisplay::display_frame) waits
+//! for it and powers the panel off afterwards.
+//!
+//! The commands follow the Waveshare C driver (`EPD_2in36g.c`).
+//!
+//!```rust, no_run
+//!# use embedded_hal_mock::eh1::*;
+//!# fn main() -> Result<(), epd_waveshare::error::EpdError<embedded_hal::spi::ErrorKind>> {
+//!use embedded_graphics::{prelude::*, primitives::{PrimitiveStyle, Rectangle}};
+//!use epd_waveshare::{epd2in36g::*, prelude::*};
+//!#
+//!# let expectations = [];
+//!# let mut spi = spi::Mock::new(&expectations);
+//!# let expectations = [];
+//!# let busy_in = digital::Mock::new(&expectations);
+//!# let dc = digital::Mock::new(&expectations);
+//!# let rst = digital::Mock::new(&expectations);
+//!# let mut delay = delay::NoopDelay::new();
+//!
+//!let mut epd = Epd2in36g::new(&mut spi, busy_in, dc, rst, &mut delay, None)?;
+//!
+//!let mut display = Display2in36g::default();
+//!for (i, color) in [QuadColor::Black, QuadColor::Yellow, QuadColor::Red].into_iter().enumerate() {
+//!    let _ = Rectangle::new(Point::new(0, i as i32 * 60), Size::new(168, 60))
+//!        .into_styled(PrimitiveStyle::with_fill(color))
+//!        .draw(&mut display);
+//!}
+//!
+//!epd.update_and_display_frame(&mut spi, display.buffer(), &mut delay)?;
+//!epd.sleep(&mut spi, &mut delay)?;
+//!# Ok(())
+//!# }
+//!```
+
+use crate::error::EpdError;
+use crate::pin::BusyPin;
+use embedded_hal::{delay::*, digital::*, spi::SpiDevice};
+
+use crate::color::QuadColor;
+use crate::interface::{BusyTimings, DisplayInterface};
+use crate::traits::{InterfaceConfig, InternalWiAdditions, RefreshLut, WaveshareDisplay};
+
+use self::command::Command;
+#[cfg(feature = "async")]
+use crate::interface::DisplayInterfaceAsync;
+#[cfg(feature = "async")]
+use crate::traits::{AsyncBusyPin, InternalWiAdditionsAsync, WaveshareDisplayAsync};
+#[cfg(feature = "async")]
+use embedded_hal_async::{delay::DelayNs as AsyncDelayNs, spi::SpiDevice as AsyncSpiDevice};
+
+mod command;
+
+/// Full size buffer for use with the 2in36g EPD
+#[cfg(feature = "graphics")]
+pub type Display2in36g = crate::graphics::Display<
+    WIDTH,
+    HEIGHT,
+    false,
+    { crate::graphics::display_len::<QuadColor>(WIDTH, HEIGHT) },
+    QuadColor,
+>;
+
+/// Width of the display
+pub const WIDTH: u32 = 168;
+/// Height of the display
+pub const HEIGHT: u32 = 296;
+/// Number of bytes of a line of pixels
+pub const LINE_BYTES: usize = WIDTH as usize / 4;
+/// Default Background Color
+pub const DEFAULT_BACKGROUND_COLOR: QuadColor = QuadColor::White;
+const IS_BUSY_LOW: bool = true;
+const SINGLE_BYTE_WRITE: bool = true;
+
+#[maybe_async_cfg::maybe(
+    sync(keep_self),
+    async(
+        feature = "async",
+        idents(
+            Epd2in36g,
+            DisplayInterface,
+            WaveshareDisplay,
+            InternalWiAdditions,
+            SpiDevice(async = "AsyncSpiDevice"),
+            BusyPin(async = "AsyncBusyPin"),
+            DelayNs(async = "AsyncDelayNs")
+        )
+    )
+)]
+/// Epd2in36g driver
+pub struct Epd2in36g<SPI, BUSY, DC, RST, DELAY> {
+    /// Connection Interface
+    interface: DisplayInterface<SPI, BUSY, DC, RST, DELAY, SINGLE_BYTE_WRITE>,
+    /// Background Color
+    color: QuadColor,
+}
+
+#[maybe_async_cfg::maybe(
+    sync(keep_self),
+    async(
+        feature = "async",
+        idents(
+            Epd2in36g,
+            DisplayInterface,
+            WaveshareDisplay,
+            InternalWiAdditions,
+            SpiDevice(async = "AsyncSpiDevice"),
+            BusyPin(async = "AsyncBusyPin"),
+            DelayNs(async = "AsyncDelayNs")
+        )
+    )
+)]
+impl<SPI, BUSY, DC, RST, DELAY> InternalWiAdditions<SPI, BUSY, DC, RST, DELAY>
+    for Epd2in36g<SPI, BUSY, DC, RST, DELAY>
+where
+    SPI: SpiDevice,
+    BUSY: BusyPin,
+    DC: OutputPin,
+    RST: OutputPin,
+    DELAY: DelayNs,
+{
+    async fn init(&mut self, spi: &mut SPI, delay: &mut DELAY) -> Result<(), EpdError<SPI::Error>> {
+        self.interface.reset(delay, 20_000, 2_000).await?;
+
+        self.cmd_with_data(spi, Command::Ox66, &[0x49, 0x55, 0x13, 0x5D])
+            .await?;
+        self.cmd_with_data(spi, Command::Ox66, &[0x49, 0x55])
+            .await?;
+        self.cmd_with_data(spi, Command::OxB0, &[0x03]).await?;
+        self.cmd_with_data(spi, Command::PanelSetting, &[0x4F, 0x6B])
+            .await?;
+        self.cmd_with_data(spi, Command::PowerOffSequenceSetting, &[0x00])
+            .await?;
+        self.cmd_with_data(spi, Command::OxF0, &[0xF6, 0x0D, 0x00, 0x00, 0x00])
+            .await?;
+        self.cmd_with_data(spi, Command::BoosterSoftStart, &[0xCF, 0xDF, 0x0F])
+            .await?;
+        self.cmd_with_data(spi, Command::TemperatureSensorCalibration, &[0x00])
+            .await?;
+        self.cmd_with_data(spi, Command::VcomAndDataIntervalSetting, &[0x30])
+            .await?;
+        self.cmd_with_data(spi, Command::TconSetting, &[0x0C, 0x05])
+            .await?;
+        self.cmd_with_data(
+            spi,
+            Command::ResolutionSetting,
+            &[WIDTH as u8, (HEIGHT >> 8) as u8, HEIGHT as u8],
+        )
+        .await?;
+        self.cmd_with_data(spi, Command::Ox84, &[0x01]).await
+    }
+}
+
+#[maybe_async_cfg::maybe(
+    sync(keep_self),
+    async(
+        feature = "async",
+        idents(
+            Epd2in36g,
+            DisplayInterface,
+            WaveshareDisplay,
+            InternalWiAdditions,
+            SpiDevice(async = "AsyncSpiDevice"),
+            BusyPin(async = "AsyncBusyPin"),
+            DelayNs(async = "AsyncDelayNs")
+        )
+    )
+)]
+impl<SPI, BUSY, DC, RST, DELAY> WaveshareDisplay<SPI, BUSY, DC, RST, DELAY>
+    for Epd2in36g<SPI, BUSY, DC, RST, DELAY>
+where
+    SPI: SpiDevice,
+    BUSY: BusyPin,
+    DC: OutputPin,
+    RST: OutputPin,
+    DELAY: DelayNs,
+{
+    type DisplayColor = QuadColor;
+
+    async fn new(
+        spi: &mut SPI,
+        busy: BUSY,
+        dc: DC,
+        rst: RST,
+        delay: &mut DELAY,
+        delay_us: Option<u32>,
+    ) -> Result<Self, EpdError<SPI::Error>> {
+        let mut epd = Self::new_uninit(busy, dc, rst, delay_us);
+        epd.init(spi, delay).await?;
+        Ok(epd)
+    }
+
+    fn new_uninit(busy: BUSY, dc: DC, rst: RST, delay_us: Option<u32>) -> Self {
+        let interface = DisplayInterface::new(busy, dc, rst, delay_us)
+            .with_busy_timings(BusyTimings::UC81XX_COLOR);
+        let color = DEFAULT_BACKGROUND_COLOR;
+
+        Epd2in36g { interface, color }
+    }
+
+    async fn sleep(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.wait_until_idle(spi, delay).await?;
+        self.cmd_with_data(spi, Command::PowerOff, &[0x00]).await?;
+        self.cmd_with_data(spi, Command::DeepSleep, &[0xA5]).await
+    }
+
+    async fn wake_up(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.init(spi, delay).await
+    }
+
+    fn set_background_color(&mut self, color: QuadColor) {
+        self.color = color;
+    }
+
+    fn background_color(&self) -> &QuadColor {
+        &self.color
+    }
+
+    fn width(&self) -> u32 {
+        WIDTH
+    }
+
+    fn height(&self) -> u32 {
+        HEIGHT
+    }
+
+    /// Powers the panel on and sends the frame, 4 pixels per byte as in [`Display2in36g`]
+    async fn update_frame(
+        &mut self,
+        spi: &mut SPI,
+        buffer: &[u8],
+        delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        EpdError::check_buffer(buffer, LINE_BYTES * HEIGHT as usize)?;
+        self.start_transmission(spi, delay).await?;
+        self.interface.data(spi, buffer).await?;
+        self.cmd_with_data(spi, Command::Ox68, &[0x00]).await
+    }
+
+    /// Partial updates aren't supported: returns [`EpdError::Unsupported`]
+    async fn update_partial_frame(
+        &mut self,
+        _spi: &mut SPI,
+        _delay: &mut DELAY,
+        _buffer: &[u8],
+        _x: u32,
+        _y: u32,
+        _width: u32,
+        _height: u32,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        Err(EpdError::Unsupported)
+    }
+
+    /// Refreshes the panel, then powers it off
+    async fn display_frame(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.cmd_with_data(spi, Command::DisplayRefresh, &[0x01])
+            .await?;
+        self.wait_until_idle(spi, delay).await?;
+
+        self.cmd_with_data(spi, Command::PowerOff, &[0x00]).await?;
+        self.wait_until_idle(spi, delay).await
+    }
+
+    async fn update_and_display_frame(
+        &mut self,
+        spi: &mut SPI,
+        buffer: &[u8],
+        delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.update_frame(spi, buffer, delay).await?;
+        self.display_frame(spi, delay).await
+    }
+
+    async fn clear_frame(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        let bg = QuadColor::colors_byte([self.color; 4]);
+
+        self.start_transmission(spi, delay).await?;
+        self.interface
+            .data_x_times(spi, bg, LINE_BYTES as u32 * HEIGHT)
+            .await?;
+        self.cmd_with_data(spi, Command::Ox68, &[0x00]).await?;
+
+        self.display_frame(spi, delay).await
+    }
+
+    async fn set_lut(
+        &mut self,
+        _spi: &mut SPI,
+        _delay: &mut DELAY,
+        _refresh_rate: Option<RefreshLut>,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        Ok(())
+    }
+
+    fn interface_config_mut(&mut self) -> &mut InterfaceConfig {
+        self.interface.config_mut()
+    }
+
+    fn release(self) -> (BUSY, DC, RST) {
+        self.interface.release()
+    }
+
+    async fn wait_until_idle(
+        &mut self,
+        _spi: &mut SPI,
+        delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.interface.wait_until_idle(delay, IS_BUSY_LOW).await
+    }
+}
+
+#[maybe_async_cfg::maybe(
+    sync(keep_self),
+    async(
+        feature = "async",
+        idents(
+            Epd2in36g,
+            DisplayInterface,
+            WaveshareDisplay,
+            InternalWiAdditions,
+            SpiDevice(async = "AsyncSpiDevice"),
+            BusyPin(async = "AsyncBusyPin"),
+            DelayNs(async = "AsyncDelayNs")
+        )
+    )
+)]
+impl<SPI, BUSY, DC, RST, DELAY> Epd2in36g<SPI, BUSY, DC, RST, DELAY>
+where
+    SPI: SpiDevice,
+    BUSY: BusyPin,
+    DC: OutputPin,
+    RST: OutputPin,
+    DELAY: DelayNs,
+{
+    async fn cmd_with_data(
+        &mut self,
+        spi: &mut SPI,
+        command: Command,
+        data: &[u8],
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.interface.cmd_with_data(spi, command, data).await
+    }
+
+    /// Powers the panel on and starts the transmission of a frame
+    async fn start_transmission(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.wait_until_idle(spi, delay).await?;
+        self.cmd_with_data(spi, Command::Ox68, &[0x01]).await?;
+        self.interface.cmd(spi, Command::PowerOn).await?;
+        self.wait_until_idle(spi, delay).await?;
+        self.interface
+            .cmd(spi, Command::DataStartTransmission)
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn epd_size() {
+        assert_eq!(WIDTH, 168);
+        assert_eq!(HEIGHT, 296);
+        assert_eq!(LINE_BYTES * HEIGHT as usize, 12_432);
+        assert_eq!(DEFAULT_BACKGROUND_COLOR, QuadColor::White);
+    }
+
+    #[cfg(feature = "graphics")]
+    #[test]
+    fn display_size() {
+        // the buffer is sent as is, line by line
+        let display = Display2in36g::default();
+        assert_eq!(display.buffer().len(), LINE_BYTES * HEIGHT as usize);
+    }
+}
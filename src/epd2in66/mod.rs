@@ -0,0 +1,110 @@
+//! A driver for the Waveshare 2.66" black and white E-Ink Display, as on the 'Pico-ePaper-2.66'
+//! hat
+//!
+//! The 152x296 panel has the SSD1675B controller of the three-color
+//! [`epd2in66b`](crate::epd2in66b). Being black and white, it takes the frames of a
+//! [`Display2in66`] and can write a part of the frame in a window of the RAM with
+//! [`update_partial_frame`](crate::traits::WaveshareDisplay::update_partial_frame), before a
+//! full refresh of the panel.
+//!
+//! The driver is the one of the SSD16xx controllers, see [`crate::controller::ssd16xx`], with the
+//! settings of the Waveshare C driver (`EPD_2in66.c`).
+//!
+//!```rust, no_run
+//!# use embedded_hal_mock::eh1::*;
+//!# fn main() -> Result<(), epd_waveshare::error::EpdError<embedded_hal::spi::ErrorKind>> {
+//!use embedded_graphics::{prelude::*, primitives::{Line, PrimitiveStyle}};
+//!use epd_waveshare::{epd2in66::*, prelude::*};
+//!#
+//!# let expectations = [];
+//!# let mut spi = spi::Mock::new(&expectations);
+//!# let expectations = [];
+//!# let busy_in = digital::Mock::new(&expectations);
+//!# let dc = digital::Mock::new(&expectations);
+//!# let rst = digital::Mock::new(&expectations);
+//!# let mut delay = delay::NoopDelay::new();
+//!
+//!let mut epd = Epd2in66::new(&mut spi, busy_in, dc, rst, &mut delay, None)?;
+//!
+//!let mut display = Display2in66::default();
+//!let _ = display.clear(Color::White);
+//!let _ = Line::new(Point::new(0, 120), Point::new(0, 295))
+//!    .into_styled(PrimitiveStyle::with_stroke(Color::Black, 1))
+//!    .draw(&mut display);
+//!epd.update_and_display_frame(&mut spi, display.buffer(), &mut delay)?;
+//!
+//!// only rewrite the rows 100 to 149
+//!let band = &display.buffer()[100 * LINE_BYTES..150 * LINE_BYTES];
+//!epd.update_partial_frame(&mut spi, &mut delay, band, 0, 100, WIDTH, 50)?;
+//!epd.display_frame(&mut spi, &mut delay)?;
+//!
+//!epd.sleep(&mut spi, &mut delay)?;
+//!# Ok(())
+//!# }
+//!```
+
+use crate::color::Color;
+use crate::controller::ssd16xx::{Panel, PanelConfig, Ssd16xx};
+
+/// Width of the display in pixels
+pub const WIDTH: u32 = crate::epd2in66b::WIDTH;
+/// Height of the display in pixels
+pub const HEIGHT: u32 = crate::epd2in66b::HEIGHT;
+/// Number of bytes of a line of pixels
+pub const LINE_BYTES: usize = WIDTH as usize / 8;
+/// Default Background Color (white)
+pub const DEFAULT_BACKGROUND_COLOR: Color = Color::White;
+
+/// Full size buffer for use with the 2in66 EPD
+#[cfg(feature = "graphics")]
+pub type Display2in66 = crate::graphics::Display<
+    WIDTH,
+    HEIGHT,
+    false,
+    { crate::graphics::display_len::<Color>(WIDTH, HEIGHT) },
+    Color,
+>;
+
+/// Settings of the 2in66 panel
+pub struct Epd2in66Panel;
+
+impl Panel for Epd2in66Panel {
+    const CONFIG: PanelConfig = PanelConfig {
+        single_byte_write: true,
+        // the 2 ms come from the SSD1675B datasheet
+        reset_us: [20_000, 2_000],
+        // normal RAM content, the panel is connected to the sources S8 to S167
+        update_control_full: Some([0x00, 0x80]),
+        update_control_quick: Some([0x00, 0x80]),
+        ..PanelConfig::new(WIDTH, HEIGHT)
+    };
+}
+
+/// Epd2in66 driver
+pub type Epd2in66<SPI, BUSY, DC, RST, DELAY> = Ssd16xx<Epd2in66Panel, SPI, BUSY, DC, RST, DELAY>;
+
+/// Epd2in66 driver for the async SPI and delay traits
+#[cfg(feature = "async")]
+pub type Epd2in66Async<SPI, BUSY, DC, RST, DELAY> =
+    crate::controller::ssd16xx::Ssd16xxAsync<Epd2in66Panel, SPI, BUSY, DC, RST, DELAY>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn epd_size() {
+        assert_eq!(WIDTH, 152);
+        assert_eq!(HEIGHT, 296);
+        assert_eq!(LINE_BYTES * HEIGHT as usize, 5_624);
+        assert_eq!(DEFAULT_BACKGROUND_COLOR, Color::White);
+    }
+
+    #[test]
+    fn panel_config() {
+        let config = Epd2in66Panel::CONFIG;
+        assert_eq!((config.width, config.height), (WIDTH, HEIGHT));
+        assert_eq!(config.update_control_full, Some([0x00, 0x80]));
+        assert!(config.single_byte_write);
+    }
+}
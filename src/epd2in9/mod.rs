@@ -1,11 +1,14 @@
 //! A simple Driver for the Waveshare 2.9" E-Ink Display via SPI
 //!
+//! The panel has an SSD1608 controller, with the waveforms of the Waveshare driver written to
+//! its LUT register. The driver is the one of the SSD16xx controllers, see
+//! [`crate::controller::ssd16xx`].
 //!
 //! # Example for the 2.9 in E-Ink Display
 //!
 //!```rust, no_run
 //!# use embedded_hal_mock::eh1::*;
-//!# fn main() -> Result<(), embedded_hal::spi::ErrorKind> {
+//!# fn main() -> Result<(), epd_waveshare::error::EpdError<embedded_hal::spi::ErrorKind>> {
 //!use embedded_graphics::{
 //!    pixelcolor::BinaryColor::On as Black, prelude::*, primitives::{Line, PrimitiveStyle},
 //!};
@@ -41,28 +44,16 @@
 //!# }
 //!```
 
+use crate::color::Color;
+use crate::controller::ssd16xx::{Luts, Panel, PanelConfig, Ssd16xx, Voltages};
+use crate::type_a::constants::{LUT_FULL_UPDATE, LUT_PARTIAL_UPDATE};
+
 /// Width of epd2in9 in pixels
 pub const WIDTH: u32 = 128;
 /// Height of epd2in9 in pixels
 pub const HEIGHT: u32 = 296;
 /// Default Background Color (white)
 pub const DEFAULT_BACKGROUND_COLOR: Color = Color::White;
-const IS_BUSY_LOW: bool = false;
-const SINGLE_BYTE_WRITE: bool = true;
-
-use embedded_hal::{delay::*, digital::*, spi::SpiDevice};
-
-use crate::type_a::{
-    command::Command,
-    constants::{LUT_FULL_UPDATE, LUT_PARTIAL_UPDATE},
-};
-
-use crate::color::Color;
-
-use crate::traits::*;
-
-use crate::buffer_len;
-use crate::interface::DisplayInterface;
 
 /// Display with Fullsize buffer for use with the 2in9 EPD
 #[cfg(feature = "graphics")]
@@ -70,310 +61,51 @@ pub type Display2in9 = crate::graphics::Display<
     WIDTH,
     HEIGHT,
     false,
-    { buffer_len(WIDTH as usize, HEIGHT as usize) },
+    { crate::graphics::display_len::<Color>(WIDTH, HEIGHT) },
     Color,
 >;
 
-/// Epd2in9 driver
-///
-pub struct Epd2in9<SPI, BUSY, DC, RST, DELAY> {
-    /// SPI
-    interface: DisplayInterface<SPI, BUSY, DC, RST, DELAY, SINGLE_BYTE_WRITE>,
-    /// Color
-    background_color: Color,
-    /// Refresh LUT
-    refresh: RefreshLut,
-}
-
-impl<SPI, BUSY, DC, RST, DELAY> Epd2in9<SPI, BUSY, DC, RST, DELAY>
-where
-    SPI: SpiDevice,
-    BUSY: InputPin,
-    DC: OutputPin,
-    RST: OutputPin,
-    DELAY: DelayNs,
-{
-    fn init(&mut self, spi: &mut SPI, delay: &mut DELAY) -> Result<(), SPI::Error> {
-        self.interface.reset(delay, 10_000, 10_000);
-
-        self.wait_until_idle(spi, delay)?;
-
-        // 3 Databytes:
-        // A[7:0]
-        // 0.. A[8]
-        // 0.. B[2:0]
-        // Default Values: A = Height of Screen (0x127), B = 0x00 (GD, SM and TB=0?)
-        self.interface
-            .cmd_with_data(spi, Command::DriverOutputControl, &[0x27, 0x01, 0x00])?;
-
-        // 3 Databytes: (and default values from datasheet and arduino)
-        // 1 .. A[6:0]  = 0xCF | 0xD7
-        // 1 .. B[6:0]  = 0xCE | 0xD6
-        // 1 .. C[6:0]  = 0x8D | 0x9D
-        //TODO: test
-        self.interface
-            .cmd_with_data(spi, Command::BoosterSoftStartControl, &[0xD7, 0xD6, 0x9D])?;
-
-        // One Databyte with value 0xA8 for 7V VCOM
-        self.interface
-            .cmd_with_data(spi, Command::WriteVcomRegister, &[0xA8])?;
-
-        // One Databyte with default value 0x1A for 4 dummy lines per gate
-        self.interface
-            .cmd_with_data(spi, Command::SetDummyLinePeriod, &[0x1A])?;
-
-        // One Databyte with default value 0x08 for 2us per line
-        self.interface
-            .cmd_with_data(spi, Command::SetGateLineWidth, &[0x08])?;
-
-        // One Databyte with default value 0x03
-        //  -> address: x increment, y increment, address counter is updated in x direction
-        self.interface
-            .cmd_with_data(spi, Command::DataEntryModeSetting, &[0x03])?;
-
-        self.set_lut(spi, delay, None)
-    }
+/// Settings of the 2in9 panel
+pub struct Epd2in9Panel;
+
+impl Panel for Epd2in9Panel {
+    const CONFIG: PanelConfig = PanelConfig {
+        single_byte_write: true,
+        // the sequences of the Waveshare driver, without software reset, border waveform and
+        // display update control 1
+        sw_reset: false,
+        border_waveform: None,
+        update_control_full: None,
+        update_control_quick: None,
+        // the SSD1608 doesn't have a temperature sensor
+        temperature_sensor: None,
+        voltages: Voltages {
+            booster: &[0xD7, 0xD6, 0x9D],
+            // 7V
+            vcom: Some(0xA8),
+            ..Voltages::DEFAULT
+        },
+        luts: Some(Luts {
+            full: &LUT_FULL_UPDATE,
+            quick: &LUT_PARTIAL_UPDATE,
+            // enable clock signal, enable cp, display pattern (tested with the arduino version)
+            refresh: 0xC4,
+            // 4 dummy lines per gate
+            dummy_line_period: 0x1A,
+            // 2us per line
+            gate_line_width: 0x08,
+        }),
+        ..PanelConfig::new(WIDTH, HEIGHT)
+    };
 }
 
-impl<SPI, BUSY, DC, RST, DELAY> WaveshareDisplay<SPI, BUSY, DC, RST, DELAY>
-    for Epd2in9<SPI, BUSY, DC, RST, DELAY>
-where
-    SPI: SpiDevice,
-    BUSY: InputPin,
-    DC: OutputPin,
-    RST: OutputPin,
-    DELAY: DelayNs,
-{
-    type DisplayColor = Color;
-    fn width(&self) -> u32 {
-        WIDTH
-    }
-
-    fn height(&self) -> u32 {
-        HEIGHT
-    }
-
-    fn new(
-        spi: &mut SPI,
-        busy: BUSY,
-        dc: DC,
-        rst: RST,
-        delay: &mut DELAY,
-        delay_us: Option<u32>,
-    ) -> Result<Self, SPI::Error> {
-        let interface = DisplayInterface::new(busy, dc, rst, delay_us);
-
-        let mut epd = Epd2in9 {
-            interface,
-            background_color: DEFAULT_BACKGROUND_COLOR,
-            refresh: RefreshLut::Full,
-        };
-
-        epd.init(spi, delay)?;
-
-        Ok(epd)
-    }
-
-    fn sleep(&mut self, spi: &mut SPI, delay: &mut DELAY) -> Result<(), SPI::Error> {
-        self.wait_until_idle(spi, delay)?;
-        // 0x00 for Normal mode (Power on Reset), 0x01 for Deep Sleep Mode
-        //TODO: is 0x00 needed here? (see also epd1in54)
-        self.interface
-            .cmd_with_data(spi, Command::DeepSleepMode, &[0x00])?;
-        Ok(())
-    }
-
-    fn wake_up(&mut self, spi: &mut SPI, delay: &mut DELAY) -> Result<(), SPI::Error> {
-        self.wait_until_idle(spi, delay)?;
-        self.init(spi, delay)?;
-        Ok(())
-    }
-
-    fn update_frame(
-        &mut self,
-        spi: &mut SPI,
-        buffer: &[u8],
-        delay: &mut DELAY,
-    ) -> Result<(), SPI::Error> {
-        self.wait_until_idle(spi, delay)?;
-        self.use_full_frame(spi, delay)?;
-
-        self.interface
-            .cmd_with_data(spi, Command::WriteRam, buffer)?;
-        Ok(())
-    }
-
-    //TODO: update description: last 3 bits will be ignored for width and x_pos
-    fn update_partial_frame(
-        &mut self,
-        spi: &mut SPI,
-        delay: &mut DELAY,
-        buffer: &[u8],
-        x: u32,
-        y: u32,
-        width: u32,
-        height: u32,
-    ) -> Result<(), SPI::Error> {
-        self.wait_until_idle(spi, delay)?;
-        self.set_ram_area(spi, x, y, x + width, y + height)?;
-        self.set_ram_counter(spi, delay, x, y)?;
-
-        self.interface
-            .cmd_with_data(spi, Command::WriteRam, buffer)?;
-        Ok(())
-    }
-
-    fn display_frame(&mut self, spi: &mut SPI, delay: &mut DELAY) -> Result<(), SPI::Error> {
-        self.wait_until_idle(spi, delay)?;
-        // enable clock signal, enable cp, display pattern -> 0xC4 (tested with the arduino version)
-        //TODO: test control_1 or control_2 with default value 0xFF (from the datasheet)
-        self.interface
-            .cmd_with_data(spi, Command::DisplayUpdateControl2, &[0xC4])?;
-
-        self.interface.cmd(spi, Command::MasterActivation)?;
-        // MASTER Activation should not be interupted to avoid currption of panel images
-        // therefore a terminate command is send
-        self.interface.cmd(spi, Command::Nop)?;
-        Ok(())
-    }
-
-    fn update_and_display_frame(
-        &mut self,
-        spi: &mut SPI,
-        buffer: &[u8],
-        delay: &mut DELAY,
-    ) -> Result<(), SPI::Error> {
-        self.update_frame(spi, buffer, delay)?;
-        self.display_frame(spi, delay)?;
-        Ok(())
-    }
-
-    fn clear_frame(&mut self, spi: &mut SPI, delay: &mut DELAY) -> Result<(), SPI::Error> {
-        self.wait_until_idle(spi, delay)?;
-        self.use_full_frame(spi, delay)?;
-
-        // clear the ram with the background color
-        let color = self.background_color.get_byte_value();
-
-        self.interface.cmd(spi, Command::WriteRam)?;
-        self.interface
-            .data_x_times(spi, color, WIDTH / 8 * HEIGHT)?;
-        Ok(())
-    }
-
-    fn set_background_color(&mut self, background_color: Color) {
-        self.background_color = background_color;
-    }
-
-    fn background_color(&self) -> &Color {
-        &self.background_color
-    }
-
-    fn set_lut(
-        &mut self,
-        spi: &mut SPI,
-        delay: &mut DELAY,
-        refresh_rate: Option<RefreshLut>,
-    ) -> Result<(), SPI::Error> {
-        if let Some(refresh_lut) = refresh_rate {
-            self.refresh = refresh_lut;
-        }
-        match self.refresh {
-            RefreshLut::Full => self.set_lut_helper(spi, delay, &LUT_FULL_UPDATE),
-            RefreshLut::Quick => self.set_lut_helper(spi, delay, &LUT_PARTIAL_UPDATE),
-        }
-    }
-
-    fn wait_until_idle(&mut self, _spi: &mut SPI, delay: &mut DELAY) -> Result<(), SPI::Error> {
-        self.interface.wait_until_idle(delay, IS_BUSY_LOW);
-        Ok(())
-    }
-}
-
-impl<SPI, BUSY, DC, RST, DELAY> Epd2in9<SPI, BUSY, DC, RST, DELAY>
-where
-    SPI: SpiDevice,
-    BUSY: InputPin,
-    DC: OutputPin,
-    RST: OutputPin,
-    DELAY: DelayNs,
-{
-    fn use_full_frame(&mut self, spi: &mut SPI, delay: &mut DELAY) -> Result<(), SPI::Error> {
-        // choose full frame/ram
-        self.set_ram_area(spi, 0, 0, WIDTH - 1, HEIGHT - 1)?;
-
-        // start from the beginning
-        self.set_ram_counter(spi, delay, 0, 0)
-    }
-
-    fn set_ram_area(
-        &mut self,
-        spi: &mut SPI,
-        start_x: u32,
-        start_y: u32,
-        end_x: u32,
-        end_y: u32,
-    ) -> Result<(), SPI::Error> {
-        assert!(start_x < end_x);
-        assert!(start_y < end_y);
-
-        // x is positioned in bytes, so the last 3 bits which show the position inside a byte in the ram
-        // aren't relevant
-        self.interface.cmd_with_data(
-            spi,
-            Command::SetRamXAddressStartEndPosition,
-            &[(start_x >> 3) as u8, (end_x >> 3) as u8],
-        )?;
-
-        // 2 Databytes: A[7:0] & 0..A[8] for each - start and end
-        self.interface.cmd_with_data(
-            spi,
-            Command::SetRamYAddressStartEndPosition,
-            &[
-                start_y as u8,
-                (start_y >> 8) as u8,
-                end_y as u8,
-                (end_y >> 8) as u8,
-            ],
-        )
-    }
-
-    fn set_ram_counter(
-        &mut self,
-        spi: &mut SPI,
-        delay: &mut DELAY,
-        x: u32,
-        y: u32,
-    ) -> Result<(), SPI::Error> {
-        self.wait_until_idle(spi, delay)?;
-        // x is positioned in bytes, so the last 3 bits which show the position inside a byte in the ram
-        // aren't relevant
-        self.interface
-            .cmd_with_data(spi, Command::SetRamXAddressCounter, &[(x >> 3) as u8])?;
-
-        // 2 Databytes: A[7:0] & 0..A[8]
-        self.interface.cmd_with_data(
-            spi,
-            Command::SetRamYAddressCounter,
-            &[y as u8, (y >> 8) as u8],
-        )?;
-        Ok(())
-    }
+/// Epd2in9 driver
+pub type Epd2in9<SPI, BUSY, DC, RST, DELAY> = Ssd16xx<Epd2in9Panel, SPI, BUSY, DC, RST, DELAY>;
 
-    /// Set your own LUT, this function is also used internally for set_lut
-    fn set_lut_helper(
-        &mut self,
-        spi: &mut SPI,
-        delay: &mut DELAY,
-        buffer: &[u8],
-    ) -> Result<(), SPI::Error> {
-        self.wait_until_idle(spi, delay)?;
-        assert!(buffer.len() == 30);
-        self.interface
-            .cmd_with_data(spi, Command::WriteLutRegister, buffer)?;
-        Ok(())
-    }
-}
+/// Epd2in9 driver for the async SPI and delay traits
+#[cfg(feature = "async")]
+pub type Epd2in9Async<SPI, BUSY, DC, RST, DELAY> =
+    crate::controller::ssd16xx::Ssd16xxAsync<Epd2in9Panel, SPI, BUSY, DC, RST, DELAY>;
 
 #[cfg(test)]
 mod tests {
@@ -385,4 +117,47 @@ mod tests {
         assert_eq!(HEIGHT, 296);
         assert_eq!(DEFAULT_BACKGROUND_COLOR, Color::White);
     }
+
+    #[test]
+    fn panel_config() {
+        let config = Epd2in9Panel::CONFIG;
+        assert_eq!((config.width, config.height), (WIDTH, HEIGHT));
+        assert_eq!(config.temperature_sensor, None);
+        assert_eq!(config.luts.map(|luts| luts.full.len()), Some(30));
+        assert_eq!(config.luts.map(|luts| luts.quick.len()), Some(30));
+    }
+
+    #[test]
+    fn command_stream() {
+        use crate::pin::NoPin;
+        use crate::test_utils::{commands, Recorder};
+        use crate::traits::WaveshareDisplay;
+        use embedded_hal_mock::eh1::delay::NoopDelay;
+
+        let recorder = Recorder::default();
+        let mut spi = recorder.clone();
+        let mut delay = NoopDelay::new();
+        let mut epd =
+            Epd2in9::new(&mut spi, NoPin, recorder.clone(), NoPin, &mut delay, None).unwrap();
+        let init = recorder.take();
+        // no software reset, border waveform or temperature sensor
+        assert_eq!(
+            commands(&init),
+            [0x01, 0x11, 0x0C, 0x2C, 0x3A, 0x3B, 0x44, 0x45, 0x4E, 0x4F, 0x32]
+        );
+        // all the gates
+        assert_eq!(init[0].1, [0x27, 0x01, 0x00]);
+        assert_eq!(init[10].1, LUT_FULL_UPDATE);
+
+        // the RAM address counter is reset before each frame
+        let frame = [0xff; WIDTH as usize / 8 * HEIGHT as usize];
+        epd.update_frame(&mut spi, &frame, &mut delay).unwrap();
+        epd.display_frame(&mut spi, &mut delay).unwrap();
+        let update = recorder.take();
+        assert_eq!(
+            commands(&update),
+            [0x44, 0x45, 0x4E, 0x4F, 0x24, 0x22, 0x20]
+        );
+        assert_eq!(update[5].1, [0xC4]);
+    }
 }
@@ -3,21 +3,33 @@
 //! [Documentation](https://www.waveshare.com/wiki/2.9inch_e-Paper_Module_(B)_Manual)
 //!
 //! [Reference code](https://github.com/waveshareteam/e-Paper/blob/master/RaspberryPi_JetsonNano/c/lib/e-Paper/EPD_2in9b_V4.c)
+//!
+//! [`RefreshLut::Quick`] selects the fast waveform of the controller, which refreshes the three
+//! colors with less flashing in a fraction of the time of the full one.
 
+use crate::error::EpdError;
+use crate::pin::BusyPin;
 use crate::{
-    buffer_len,
     color::TriColor,
-    interface::DisplayInterface,
-    traits::{InternalWiAdditions, WaveshareDisplay, WaveshareThreeColorDisplay},
-};
-use embedded_hal::{
-    delay::DelayNs,
-    digital::{InputPin, OutputPin},
-    spi::SpiDevice,
+    interface::{BusyTimings, DisplayInterface},
+    ram_addressing::Ssd1680,
+    traits::{
+        InterfaceConfig, InternalWiAdditions, RefreshLut, WaveshareDisplay,
+        WaveshareThreeColorDisplay,
+    },
 };
+use embedded_hal::{delay::DelayNs, digital::OutputPin, spi::SpiDevice};
 
 pub(crate) mod command;
 use self::command::Command;
+#[cfg(feature = "async")]
+use crate::interface::DisplayInterfaceAsync;
+#[cfg(feature = "async")]
+use crate::traits::{
+    AsyncBusyPin, InternalWiAdditionsAsync, WaveshareDisplayAsync, WaveshareThreeColorDisplayAsync,
+};
+#[cfg(feature = "async")]
+use embedded_hal_async::{delay::DelayNs as AsyncDelayNs, spi::SpiDevice as AsyncSpiDevice};
 
 const SINGLE_BYTE_WRITE: bool = false;
 
@@ -29,6 +41,8 @@ pub const WIDTH: u32 = 128;
 pub const HEIGHT: u32 = 296;
 
 const IS_BUSY_LOW: bool = false;
+/// Number of bytes of each of the two planes of a full frame
+const PLANE_BYTES: usize = WIDTH as usize / 8 * HEIGHT as usize;
 
 #[cfg(feature = "graphics")]
 /// Full size buffer for use with the 2.9" black/red EPD
@@ -36,30 +50,63 @@ pub type Display2in9b = crate::graphics::Display<
     WIDTH,
     HEIGHT,
     true,
-    { buffer_len(WIDTH as usize, HEIGHT as usize * 2) },
+    { crate::graphics::display_len::<TriColor>(WIDTH, HEIGHT) },
     TriColor,
 >;
 
+#[maybe_async_cfg::maybe(
+    sync(keep_self),
+    async(
+        feature = "async",
+        idents(
+            Epd2in9b,
+            DisplayInterface,
+            WaveshareDisplay,
+            InternalWiAdditions,
+            WaveshareThreeColorDisplay,
+            SpiDevice(async = "AsyncSpiDevice"),
+            BusyPin(async = "AsyncBusyPin"),
+            DelayNs(async = "AsyncDelayNs")
+        )
+    )
+)]
 /// Epd2in9b (v4) driver
 pub struct Epd2in9b<SPI, BUSY, DC, RST, DELAY> {
     /// SPI
     interface: DisplayInterface<SPI, BUSY, DC, RST, DELAY, SINGLE_BYTE_WRITE>,
     /// Color
     background_color: TriColor,
+    /// Refresh LUT
+    refresh: RefreshLut,
 }
 
-#[allow(dead_code)]
 enum DisplayMode {
     Default,
     Partial,
-    Fast, // TODO: Add support in future
+    Fast,
     Base,
 }
 
+#[maybe_async_cfg::maybe(
+    sync(keep_self),
+    async(
+        feature = "async",
+        idents(
+            Epd2in9b,
+            DisplayInterface,
+            WaveshareDisplay,
+            InternalWiAdditions,
+            WaveshareThreeColorDisplay,
+            SpiDevice(async = "AsyncSpiDevice"),
+            BusyPin(async = "AsyncBusyPin"),
+            DelayNs(async = "AsyncDelayNs")
+        )
+    )
+)]
 impl<SPI, BUSY, DC, RST, DELAY> Epd2in9b<SPI, BUSY, DC, RST, DELAY>
 where
     SPI: SpiDevice,
-    BUSY: InputPin,
+    BUSY: BusyPin,
     DC: OutputPin,
     RST: OutputPin,
     DELAY: DelayNs,
@@ -67,22 +114,22 @@ where
     /// set the base image before partially update
     ///
     /// <https://github.com/waveshareteam/e-Paper/blob/bc23f8ee814486edb6a364c802847224e079e523/RaspberryPi_JetsonNano/c/examples/EPD_2in9b_V4_test.c#L130>
-    pub fn update_and_display_frame_base(
+    pub async fn update_and_display_frame_base(
         &mut self,
         spi: &mut SPI,
         black: &[u8],
         chromatic: Option<&[u8]>,
         delay: &mut DELAY,
-    ) -> Result<(), <SPI>::Error> {
-        self.update_frame(spi, black, delay)?;
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.update_frame(spi, black, delay).await?;
         if let Some(chromatic) = chromatic {
-            self.update_chromatic_frame(spi, delay, chromatic)?;
+            self.update_chromatic_frame(spi, delay, chromatic).await?;
         }
 
-        self.turn_on_display(spi, delay, DisplayMode::Base)?;
+        self.turn_on_display(spi, delay, DisplayMode::Base).await?;
 
-        self.command(spi, Command::WriteRedData)?;
-        self.send_data(spi, black)?;
+        self.command(spi, Command::WriteRedData).await?;
+        self.send_data(spi, black).await?;
 
         Ok(())
     }
@@ -91,30 +138,35 @@ where
     ///
     /// To perform partial update, it need to call update_and_display_frame_base
     /// than call update_partial_frame before call display_frame_partial
-    pub fn display_frame_partial(
+    pub async fn display_frame_partial(
         &mut self,
         spi: &mut SPI,
         delay: &mut DELAY,
-    ) -> Result<(), <SPI>::Error> {
-        self.turn_on_display(spi, delay, DisplayMode::Partial)?;
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.turn_on_display(spi, delay, DisplayMode::Partial)
+            .await?;
         Ok(())
     }
 
-    fn command(&mut self, spi: &mut SPI, command: Command) -> Result<(), SPI::Error> {
-        self.interface.cmd(spi, command)
+    async fn command(
+        &mut self,
+        spi: &mut SPI,
+        command: Command,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.interface.cmd(spi, command).await
     }
 
-    fn send_data(&mut self, spi: &mut SPI, data: &[u8]) -> Result<(), SPI::Error> {
-        self.interface.data(spi, data)
+    async fn send_data(&mut self, spi: &mut SPI, data: &[u8]) -> Result<(), EpdError<SPI::Error>> {
+        self.interface.data(spi, data).await
     }
 
-    fn turn_on_display(
+    async fn turn_on_display(
         &mut self,
         spi: &mut SPI,
         delay: &mut DELAY,
         mode: DisplayMode,
-    ) -> Result<(), SPI::Error> {
-        self.command(spi, Command::TurnOnDisplay)?;
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.command(spi, Command::TurnOnDisplay).await?;
 
         let data = match mode {
             DisplayMode::Default => 0xf7,
@@ -123,161 +175,254 @@ where
             DisplayMode::Base => 0xf4,
         };
 
-        self.send_data(spi, &[data])?;
-        self.command(spi, Command::ActivateDisplayUpdateSequence)?;
-        self.wait_until_idle(spi, delay)?;
+        self.send_data(spi, &[data]).await?;
+        self.command(spi, Command::ActivateDisplayUpdateSequence)
+            .await?;
+        self.wait_until_idle(spi, delay).await?;
         Ok(())
     }
+
+    /// Loads the waveform the controller uses at 90°C, faster than the others
+    ///
+    /// It is kept until the next reset and used by the refreshes in [`DisplayMode::Fast`], which
+    /// don't load the waveform again.
+    async fn load_fast_waveform(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        // load the waveform for the measured temperature first
+        self.command(spi, Command::TurnOnDisplay).await?;
+        self.send_data(spi, &[0xb1]).await?;
+        self.command(spi, Command::ActivateDisplayUpdateSequence)
+            .await?;
+        self.wait_until_idle(spi, delay).await?;
+
+        self.command(spi, Command::WriteTemperatureRegister).await?;
+        self.send_data(spi, &[0x5a, 0x00]).await?;
+        self.command(spi, Command::TurnOnDisplay).await?;
+        self.send_data(spi, &[0x91]).await?;
+        self.command(spi, Command::ActivateDisplayUpdateSequence)
+            .await?;
+        self.wait_until_idle(spi, delay).await
+    }
 }
 
+#[maybe_async_cfg::maybe(
+    sync(keep_self),
+    async(
+        feature = "async",
+        idents(
+            Epd2in9b,
+            DisplayInterface,
+            WaveshareDisplay,
+            InternalWiAdditions,
+            WaveshareThreeColorDisplay,
+            SpiDevice(async = "AsyncSpiDevice"),
+            BusyPin(async = "AsyncBusyPin"),
+            DelayNs(async = "AsyncDelayNs")
+        )
+    )
+)]
 impl<SPI, BUSY, DC, RST, DELAY> InternalWiAdditions<SPI, BUSY, DC, RST, DELAY>
     for Epd2in9b<SPI, BUSY, DC, RST, DELAY>
 where
     SPI: SpiDevice,
-    BUSY: InputPin,
+    BUSY: BusyPin,
     DC: OutputPin,
     RST: OutputPin,
     DELAY: DelayNs,
 {
-    fn init(&mut self, spi: &mut SPI, delay: &mut DELAY) -> Result<(), SPI::Error> {
+    async fn init(&mut self, spi: &mut SPI, delay: &mut DELAY) -> Result<(), EpdError<SPI::Error>> {
         let w = self.width();
         let h = self.height();
 
-        self.interface.reset(delay, 200_000, 2_000);
+        self.interface.reset(delay, 200_000, 2_000).await?;
 
-        self.wait_until_idle(spi, delay)?;
-        self.command(spi, Command::SwReset)?;
-        self.wait_until_idle(spi, delay)?;
+        self.wait_until_idle(spi, delay).await?;
+        self.command(spi, Command::SwReset).await?;
+        self.wait_until_idle(spi, delay).await?;
 
-        self.command(spi, Command::DriverOutputControl)?;
-        self.send_data(spi, &[((h - 1) % 256) as u8])?;
-        self.send_data(spi, &[((h - 1) / 256) as u8])?;
-        self.send_data(spi, &[0])?;
+        self.command(spi, Command::DriverOutputControl).await?;
+        self.send_data(spi, &[((h - 1) % 256) as u8]).await?;
+        self.send_data(spi, &[((h - 1) / 256) as u8]).await?;
+        self.send_data(spi, &[0]).await?;
 
-        self.command(spi, Command::DataEntryMode)?;
-        self.send_data(spi, &[0x03])?;
+        self.command(spi, Command::DataEntryMode).await?;
+        self.send_data(spi, &[0x03]).await?;
 
-        self.command(spi, Command::RamXPosition)?;
-        self.send_data(spi, &[0])?;
-        self.send_data(spi, &[(w / 8 - 1) as u8])?;
+        self.command(spi, Command::RamXPosition).await?;
+        self.send_data(spi, &[0]).await?;
+        self.send_data(spi, &[(w / 8 - 1) as u8]).await?;
 
-        self.command(spi, Command::RamYPosition)?;
-        self.send_data(spi, &[0])?;
-        self.send_data(spi, &[0])?;
-        self.send_data(spi, &[((h - 1) % 256) as u8])?;
-        self.send_data(spi, &[((h - 1) / 256) as u8])?;
+        self.command(spi, Command::RamYPosition).await?;
+        self.send_data(spi, &[0]).await?;
+        self.send_data(spi, &[0]).await?;
+        self.send_data(spi, &[((h - 1) % 256) as u8]).await?;
+        self.send_data(spi, &[((h - 1) / 256) as u8]).await?;
 
-        self.command(spi, Command::BorderWavefrom)?;
-        self.send_data(spi, &[0x05])?;
+        self.command(spi, Command::BorderWavefrom).await?;
+        self.send_data(spi, &[0x05]).await?;
 
-        self.command(spi, Command::DisplayUpdateControl)?;
-        self.send_data(spi, &[0x00])?;
-        self.send_data(spi, &[0x80])?;
+        self.command(spi, Command::DisplayUpdateControl).await?;
+        self.send_data(spi, &[0x00]).await?;
+        self.send_data(spi, &[0x80]).await?;
 
-        self.command(spi, Command::ReadBuiltInTemperatureSensor)?;
-        self.send_data(spi, &[0x80])?;
+        self.command(spi, Command::ReadBuiltInTemperatureSensor)
+            .await?;
+        self.send_data(spi, &[0x80]).await?;
 
-        self.command(spi, Command::RamXAddressCount)?;
-        self.send_data(spi, &[0x00])?; // set RAM x address count to 0
-        self.command(spi, Command::RamYAddressCount)?; // set RAM y address count to 0X199
-        self.send_data(spi, &[0x00])?;
-        self.send_data(spi, &[0x00])?;
+        self.command(spi, Command::RamXAddressCount).await?;
+        self.send_data(spi, &[0x00]).await?; // set RAM x address count to 0
+        self.command(spi, Command::RamYAddressCount).await?; // set RAM y address count to 0X199
+        self.send_data(spi, &[0x00]).await?;
+        self.send_data(spi, &[0x00]).await?;
 
-        self.wait_until_idle(spi, delay)?;
+        self.wait_until_idle(spi, delay).await?;
+
+        if self.refresh == RefreshLut::Quick {
+            self.load_fast_waveform(spi, delay).await?;
+        }
 
         Ok(())
     }
 }
 
+#[maybe_async_cfg::maybe(
+    sync(keep_self),
+    async(
+        feature = "async",
+        idents(
+            Epd2in9b,
+            DisplayInterface,
+            WaveshareDisplay,
+            InternalWiAdditions,
+            WaveshareThreeColorDisplay,
+            SpiDevice(async = "AsyncSpiDevice"),
+            BusyPin(async = "AsyncBusyPin"),
+            DelayNs(async = "AsyncDelayNs")
+        )
+    )
+)]
 impl<SPI, BUSY, DC, RST, DELAY> WaveshareThreeColorDisplay<SPI, BUSY, DC, RST, DELAY>
     for Epd2in9b<SPI, BUSY, DC, RST, DELAY>
 where
     SPI: SpiDevice,
-    BUSY: InputPin,
+    BUSY: BusyPin,
     DC: OutputPin,
     RST: OutputPin,
     DELAY: DelayNs,
 {
-    fn update_color_frame(
+    async fn update_color_frame(
         &mut self,
         spi: &mut SPI,
         delay: &mut DELAY,
         black: &[u8],
         chromatic: &[u8],
-    ) -> Result<(), <SPI>::Error> {
-        self.update_achromatic_frame(spi, delay, black)?;
-        self.update_chromatic_frame(spi, delay, chromatic)?;
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.update_achromatic_frame(spi, delay, black).await?;
+        self.update_chromatic_frame(spi, delay, chromatic).await?;
         Ok(())
     }
 
-    fn update_achromatic_frame(
+    async fn update_achromatic_frame(
         &mut self,
         spi: &mut SPI,
         _delay: &mut DELAY,
         black: &[u8],
-    ) -> Result<(), <SPI>::Error> {
-        self.command(spi, Command::WriteBlackData)?;
-        self.send_data(spi, black)?;
+    ) -> Result<(), EpdError<SPI::Error>> {
+        EpdError::check_buffer(black, PLANE_BYTES)?;
+        self.command(spi, Command::WriteBlackData).await?;
+        self.send_data(spi, black).await?;
         Ok(())
     }
 
-    fn update_chromatic_frame(
+    async fn update_chromatic_frame(
         &mut self,
         spi: &mut SPI,
         _delay: &mut DELAY,
         chromatic: &[u8],
-    ) -> Result<(), <SPI>::Error> {
-        self.command(spi, Command::WriteRedData)?;
-        self.send_data(spi, chromatic)?;
+    ) -> Result<(), EpdError<SPI::Error>> {
+        EpdError::check_buffer(chromatic, PLANE_BYTES)?;
+        self.command(spi, Command::WriteRedData).await?;
+        self.send_data(spi, chromatic).await?;
         Ok(())
     }
 }
 
+#[maybe_async_cfg::maybe(
+    sync(keep_self),
+    async(
+        feature = "async",
+        idents(
+            Epd2in9b,
+            DisplayInterface,
+            WaveshareDisplay,
+            InternalWiAdditions,
+            WaveshareThreeColorDisplay,
+            SpiDevice(async = "AsyncSpiDevice"),
+            BusyPin(async = "AsyncBusyPin"),
+            DelayNs(async = "AsyncDelayNs")
+        )
+    )
+)]
 impl<SPI, BUSY, DC, RST, DELAY> WaveshareDisplay<SPI, BUSY, DC, RST, DELAY>
     for Epd2in9b<SPI, BUSY, DC, RST, DELAY>
 where
     SPI: SpiDevice,
-    BUSY: InputPin,
+    BUSY: BusyPin,
     DC: OutputPin,
     RST: OutputPin,
     DELAY: DelayNs,
 {
     type DisplayColor = TriColor;
 
-    fn new(
+    async fn new(
         spi: &mut SPI,
         busy: BUSY,
         dc: DC,
         rst: RST,
         delay: &mut DELAY,
         delay_us: Option<u32>,
-    ) -> Result<Self, <SPI>::Error>
+    ) -> Result<Self, EpdError<SPI::Error>>
     where
         Self: Sized,
     {
-        let interface = DisplayInterface::new(busy, dc, rst, delay_us);
+        let mut epd = Self::new_uninit(busy, dc, rst, delay_us);
+        epd.init(spi, delay).await?;
+        Ok(epd)
+    }
+
+    fn new_uninit(busy: BUSY, dc: DC, rst: RST, delay_us: Option<u32>) -> Self {
+        let interface = DisplayInterface::new(busy, dc, rst, delay_us)
+            .with_busy_timings(BusyTimings::SSD16XX_COLOR);
         let background_color = DEFAULT_BACKGROUND_COLOR;
 
-        let mut epd = Epd2in9b {
+        Epd2in9b {
             interface,
             background_color,
-        };
-
-        epd.init(spi, delay)?;
-
-        Ok(epd)
+            refresh: RefreshLut::Full,
+        }
     }
 
-    fn sleep(&mut self, spi: &mut SPI, delay: &mut DELAY) -> Result<(), <SPI>::Error> {
-        self.command(spi, Command::DeepSleep)?;
-        self.send_data(spi, &[1])?;
-        delay.delay_ms(100);
+    async fn sleep(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.command(spi, Command::DeepSleep).await?;
+        self.send_data(spi, &[1]).await?;
+        delay.delay_ms(100).await;
 
         Ok(())
     }
 
-    fn wake_up(&mut self, spi: &mut SPI, delay: &mut DELAY) -> Result<(), <SPI>::Error> {
-        self.init(spi, delay)?;
+    async fn wake_up(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.init(spi, delay).await?;
         Ok(())
     }
 
@@ -297,21 +442,24 @@ where
         HEIGHT
     }
 
-    fn update_frame(
+    async fn update_frame(
         &mut self,
         spi: &mut SPI,
         buffer: &[u8],
         _delay: &mut DELAY,
-    ) -> Result<(), <SPI>::Error> {
-        self.command(spi, Command::WriteBlackData)?;
-        self.send_data(spi, buffer)?;
-
-        self.command(spi, Command::WriteRedData)?;
-        self.interface.data_x_times(spi, 0x00, WIDTH / 8 * HEIGHT)?;
+    ) -> Result<(), EpdError<SPI::Error>> {
+        EpdError::check_buffer(buffer, PLANE_BYTES)?;
+        self.command(spi, Command::WriteBlackData).await?;
+        self.send_data(spi, buffer).await?;
+
+        self.command(spi, Command::WriteRedData).await?;
+        self.interface
+            .data_x_times(spi, 0x00, PLANE_BYTES as u32)
+            .await?;
         Ok(())
     }
 
-    fn update_partial_frame(
+    async fn update_partial_frame(
         &mut self,
         spi: &mut SPI,
         _delay: &mut DELAY,
@@ -320,100 +468,100 @@ where
         y: u32,
         width: u32,
         height: u32,
-    ) -> Result<(), <SPI>::Error> {
+    ) -> Result<(), EpdError<SPI::Error>> {
         assert!(width % 8 == 0, "width must multiple of 8");
-        let mut x_start = x;
-        let mut x_end = x + width;
-
-        let y_start = y;
-        let mut y_end = y + height;
-
-        if (x_start % 8 + x_end % 8 == 8 && x_start % 8 > x_end % 8)
-            || x_start % 8 + x_end % 8 == 0
-            || (x_end - x_start) % 8 == 0
-        {
-            x_start /= 8;
-            x_end /= 8;
-        } else {
-            x_start /= 8;
-            x_end = if x_end % 8 == 0 {
-                x_end / 8
-            } else {
-                x_end / 8 + 1
-            };
-        }
-
-        x_end -= 1;
-        y_end -= 1;
-
-        let x_start = x_start as u8;
-        let x_end = x_end as u8;
+        EpdError::check_buffer(buffer, (width / 8 * height) as usize)?;
 
-        let y_start_1 = y_start as u8;
-        let y_start_2 = (y_start >> 8) as u8;
+        self.interface
+            .set_ram_area::<Ssd1680>(spi, x, y, x + width - 1, y + height - 1)
+            .await?;
+        self.interface.set_ram_counter::<Ssd1680>(spi, x, y).await?;
 
-        let y_end_1 = y_end as u8;
-        let y_end_2 = (y_end >> 8) as u8;
+        self.command(spi, Command::WriteBlackData).await?;
+        self.send_data(spi, buffer).await?;
 
-        self.command(spi, Command::RamXPosition)?;
-        self.send_data(spi, &[x_start, x_end])?;
-        self.command(spi, Command::RamYPosition)?;
-        self.send_data(spi, &[y_start_1, y_start_2])?;
-        self.send_data(spi, &[y_end_1, y_end_2])?;
-
-        self.command(spi, Command::RamXAddressCount)?;
-        self.send_data(spi, &[x_start])?;
-        self.command(spi, Command::RamYAddressCount)?;
-        self.send_data(spi, &[y_start_1, y_start_2])?;
-
-        self.command(spi, Command::WriteBlackData)?;
-        self.send_data(spi, buffer)?;
+        // the next full frames are written to the whole RAM again
+        self.interface
+            .set_ram_area::<Ssd1680>(spi, 0, 0, WIDTH - 1, HEIGHT - 1)
+            .await?;
+        self.interface.set_ram_counter::<Ssd1680>(spi, 0, 0).await?;
 
         Ok(())
     }
 
-    fn display_frame(&mut self, spi: &mut SPI, delay: &mut DELAY) -> Result<(), <SPI>::Error> {
-        self.turn_on_display(spi, delay, DisplayMode::Default)?;
+    async fn display_frame(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        let mode = match self.refresh {
+            RefreshLut::Quick => DisplayMode::Fast,
+            _ => DisplayMode::Default,
+        };
+        self.turn_on_display(spi, delay, mode).await?;
 
         Ok(())
     }
 
-    fn update_and_display_frame(
+    async fn update_and_display_frame(
         &mut self,
         spi: &mut SPI,
         buffer: &[u8],
         delay: &mut DELAY,
-    ) -> Result<(), <SPI>::Error> {
-        self.update_frame(spi, buffer, delay)?;
-        self.display_frame(spi, delay)?;
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.update_frame(spi, buffer, delay).await?;
+        self.display_frame(spi, delay).await?;
 
         Ok(())
     }
 
-    fn clear_frame(&mut self, spi: &mut SPI, delay: &mut DELAY) -> Result<(), <SPI>::Error> {
-        const SIZE: u32 = WIDTH / 8 * HEIGHT;
+    async fn clear_frame(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        const SIZE: u32 = PLANE_BYTES as u32;
 
-        self.command(spi, Command::WriteBlackData)?;
-        self.interface.data_x_times(spi, 0xff, SIZE)?;
+        self.command(spi, Command::WriteBlackData).await?;
+        self.interface.data_x_times(spi, 0xff, SIZE).await?;
 
-        self.command(spi, Command::WriteRedData)?;
-        self.interface.data_x_times(spi, 0, SIZE)?;
+        self.command(spi, Command::WriteRedData).await?;
+        self.interface.data_x_times(spi, 0, SIZE).await?;
 
-        self.display_frame(spi, delay)?;
+        self.display_frame(spi, delay).await?;
         Ok(())
     }
 
-    fn set_lut(
+    /// Selects the full (default) or the fast waveform, for all three colors
+    async fn set_lut(
         &mut self,
-        _spi: &mut SPI,
-        _delay: &mut DELAY,
-        _refresh_rate: Option<crate::traits::RefreshLut>,
-    ) -> Result<(), <SPI>::Error> {
+        spi: &mut SPI,
+        delay: &mut DELAY,
+        refresh_rate: Option<RefreshLut>,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        if let Some(refresh_lut) = refresh_rate {
+            self.refresh = refresh_lut.or_full(&[RefreshLut::Quick]);
+        }
+        // the full refreshes load the waveform themselves
+        if self.refresh == RefreshLut::Quick {
+            self.load_fast_waveform(spi, delay).await?;
+        }
         Ok(())
     }
 
-    fn wait_until_idle(&mut self, _spi: &mut SPI, delay: &mut DELAY) -> Result<(), <SPI>::Error> {
-        self.interface.wait_until_idle(delay, IS_BUSY_LOW);
-        Ok(())
+    fn interface_config_mut(&mut self) -> &mut InterfaceConfig {
+        self.interface.config_mut()
+    }
+
+    fn release(self) -> (BUSY, DC, RST) {
+        self.interface.release()
+    }
+
+    async fn wait_until_idle(
+        &mut self,
+        _spi: &mut SPI,
+        delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.interface.wait_until_idle(delay, IS_BUSY_LOW).await
     }
 }
@@ -0,0 +1,39 @@
+//! SPI Commands for the Waveshare 3.0" (G) E-Ink Display
+
+use crate::traits;
+
+/// Epd3in0g commands
+///
+/// The UC8253-style controller shares most of its commands with the other UC81xx controllers.
+/// The commands without a documented name are named after their address, as in the Waveshare
+/// code.
+#[allow(dead_code, clippy::upper_case_acronyms)]
+#[derive(Clone, Copy, Debug)]
+pub(crate) enum Command {
+    PanelSetting = 0x00,
+    PowerSetting = 0x01,
+    PowerOff = 0x02,
+    PowerOn = 0x04,
+    BoosterSoftStart = 0x06,
+    DeepSleep = 0x07,
+
+    DataStartTransmission = 0x10,
+    DisplayRefresh = 0x12,
+
+    VcomAndDataIntervalSetting = 0x50,
+    TconSetting = 0x60,
+    ResolutionSetting = 0x61,
+
+    PowerSaving = 0xE3,
+
+    Ox66 = 0x66,
+    Ox84 = 0x84,
+    OxB0 = 0xB0,
+}
+
+impl traits::Command for Command {
+    /// Returns the address of the command
+    fn address(self) -> u8 {
+        self as u8
+    }
+}
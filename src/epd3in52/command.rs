@@ -0,0 +1,34 @@
+//! SPI Commands for the Waveshare 3.52" E-Ink Display
+
+use crate::traits;
+
+/// Epd3in52 commands
+///
+/// The UC8252 controller shares its commands with the other UC81xx controllers.
+#[derive(Clone, Copy, Debug)]
+pub(crate) enum Command {
+    PanelSetting = 0x00,
+    PowerOff = 0x02,
+    PowerOn = 0x04,
+    DeepSleep = 0x07,
+
+    DisplayRefresh = 0x12,
+    /// New frame in black and white mode
+    DataStartTransmission2 = 0x13,
+
+    LutForVcom = 0x20,
+    LutWhiteToWhite = 0x21,
+    LutBlackToWhite = 0x22,
+    LutWhiteToBlack = 0x23,
+    LutBlackToBlack = 0x24,
+
+    VcomAndDataIntervalSetting = 0x50,
+    ResolutionSetting = 0x61,
+}
+
+impl traits::Command for Command {
+    /// Returns the address of the command
+    fn address(self) -> u8 {
+        self as u8
+    }
+}
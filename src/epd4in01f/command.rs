@@ -2,7 +2,7 @@
 
 use crate::traits;
 
-/// Epd7in5 commands
+/// EPD6in65f commands
 ///
 /// Should rarely (never?) be needed directly.
 ///
@@ -63,11 +63,8 @@ pub(crate) enum Command {
     /// update is finished.
     DisplayRefresh = 0x12,
 
-    /// RED or NEW_DATA
-    DataStartTransmission2 = 0x13,
-
-    /// Dual SPI - what for?
-    DualSpi = 0x15,
+    /// Image Process Command
+    ImageProcess = 0x13,
 
     /// This command builds the VCOM Look-Up Table (LUTC).
     LutForVcom = 0x20,
@@ -114,23 +111,23 @@ pub(crate) enum Command {
     /// This command defines alternative resolution and this setting is of higher priority
     /// than the RES\[1:0\] in R00H (PSR).
     TconResolution = 0x61,
-    /// This command defines MCU host direct access external memory mode.
-    SpiFlashControl = 0x65,
+    // /// This command defines MCU host direct access external memory mode.
+    //SpiFlashControl = 0x65,
 
-    /// The LUT_REV / Chip Revision is read from OTP address = 25001 and 25000.
-    Revision = 0x70,
+    // /// The LUT_REV / Chip Revision is read from OTP address = 25001 and 25000.
+    //Revision = 0x70,
     /// This command reads the IC status.
     GetStatus = 0x71,
 
     /// This command implements related VCOM sensing setting.
-    AutoMeasurementVcom = 0x80,
+    //AutoMeasurementVcom = 0x80,
     /// This command gets the VCOM value.
     ReadVcomValue = 0x81,
     /// This command sets `VCOM_DC` value.
     VcmDcSetting = 0x82,
-    // /// This is in all the Waveshare controllers for Epd7in5, but it's not documented
+    // /// This is in all the Waveshare controllers for EPD6in65f, but it's not documented
     // /// anywhere in the datasheet `¯\_(ツ)_/¯`
-    // FlashMode = 0xE5,
+    FlashMode = 0xE3,
 }
 
 impl traits::Command for Command {
@@ -0,0 +1,568 @@
+//! A Driver for the Waveshare 4.26" E-Ink Display via SPI
+//!
+//! The 800x480 black and white panel has a SSD1677 controller. Its waveforms are the ones of the
+//! controller OTP: the full refresh, a fast full refresh selected with [`RefreshLut::Fast`] and 4
+//! gray levels selected with [`RefreshLut::Gray4`] or for a single frame by
+//! [`update_and_display_gray4_frame`](Gray4Refresh::update_and_display_gray4_frame), as in the
+//! Waveshare C driver (`EPD_4in26.c`).
+//!
+//! The full frame takes 48KB, so it can also be rendered and sent band by band with
+//! [`update_frame_banded`](Epd4in26::update_frame_banded) from a buffer of a few lines.
+//!
+//!```rust, no_run
+//!# use embedded_hal_mock::eh1::*;
+//!# fn main() -> Result<(), epd_waveshare::error::EpdError<embedded_hal::spi::ErrorKind>> {
+//!use epd_waveshare::{epd4in26::*, prelude::*};
+//!#
+//!# let expectations = [];
+//!# let mut spi = spi::Mock::new(&expectations);
+//!# let expectations = [];
+//!# let busy_in = digital::Mock::new(&expectations);
+//!# let dc = digital::Mock::new(&expectations);
+//!# let rst = digital::Mock::new(&expectations);
+//!# let mut delay = delay::NoopDelay::new();
+//!
+//!let mut epd = Epd4in26::new(&mut spi, busy_in, dc, rst, &mut delay, None)?;
+//!
+//!// 16 lines at a time: black on the top half, white on the bottom one
+//!let mut band = [0; 16 * LINE_BYTES];
+//!epd.update_frame_banded(&mut spi, &mut delay, &mut band, |area, pixels| {
+//!    let color = if area.y < HEIGHT / 2 { Color::Black } else { Color::White };
+//!    pixels.fill(color.get_byte_value());
+//!})?;
+//!epd.display_frame(&mut spi, &mut delay)?;
+//!
+//!// the following full refreshes are faster
+//!epd.set_lut(&mut spi, &mut delay, Some(RefreshLut::Fast))?;
+//!epd.clear_frame(&mut spi, &mut delay)?;
+//!epd.display_frame(&mut spi, &mut delay)?;
+//!
+//!epd.sleep(&mut spi, &mut delay)?;
+//!# Ok(())
+//!# }
+//!```
+
+use crate::error::EpdError;
+use crate::pin::BusyPin;
+use embedded_hal::{delay::*, digital::*, spi::SpiDevice};
+
+use crate::buffer_len;
+use crate::color::Color;
+use crate::interface::DisplayInterface;
+#[cfg(feature = "async")]
+use crate::interface::DisplayInterfaceAsync;
+use crate::ram_addressing::Ssd1677;
+use crate::rect::Rect;
+#[cfg(feature = "async")]
+use crate::traits::{
+    AsyncBusyPin, Gray4RefreshAsync, InternalWiAdditionsAsync, WaveshareDisplayAsync,
+};
+use crate::traits::{
+    Gray4Refresh, InterfaceConfig, InternalWiAdditions, RefreshLut, WaveshareDisplay,
+};
+use crate::type_a::command::Command;
+#[cfg(feature = "async")]
+use embedded_hal_async::{delay::DelayNs as AsyncDelayNs, spi::SpiDevice as AsyncSpiDevice};
+
+/// Width of the display in pixels
+pub const WIDTH: u32 = 800;
+/// Height of the display in pixels
+pub const HEIGHT: u32 = 480;
+/// Number of bytes of a line of pixels
+pub const LINE_BYTES: usize = WIDTH as usize / 8;
+/// Default Background Color (white)
+pub const DEFAULT_BACKGROUND_COLOR: Color = Color::White;
+const IS_BUSY_LOW: bool = false;
+const SINGLE_BYTE_WRITE: bool = false;
+
+/// Enable clock and analog, load temperature and the LUT, display mode 1, disable
+const FULL_REFRESH: u8 = 0xF7;
+/// Same with the LUT loaded for the temperature forced by `load_forced_waveform`
+const FAST_REFRESH: u8 = 0xC7;
+/// Same in display mode 2, both RAMs giving the 4 gray levels
+const GRAY4_REFRESH: u8 = 0xCF;
+/// Temperature forced for the fast and gray levels waveforms, 90°C
+const FORCED_TEMPERATURE: u8 = 0x5A;
+
+/// Full size buffer for use with the 4in26 EPD
+#[cfg(feature = "graphics")]
+pub type Display4in26 = crate::graphics::Display<
+    WIDTH,
+    HEIGHT,
+    false,
+    { crate::graphics::display_len::<Color>(WIDTH, HEIGHT) },
+    Color,
+>;
+
+/// Full size buffer for the 4 gray levels refreshes of the 4in26 EPD
+#[cfg(feature = "graphics")]
+pub type Display4in26Gray4 = crate::graphics::Display<
+    WIDTH,
+    HEIGHT,
+    false,
+    { crate::graphics::display_len::<crate::color::Gray4>(WIDTH, HEIGHT) },
+    crate::color::Gray4,
+>;
+
+#[maybe_async_cfg::maybe(
+    sync(keep_self),
+    async(
+        feature = "async",
+        idents(
+            Epd4in26,
+            DisplayInterface,
+            WaveshareDisplay,
+            InternalWiAdditions,
+            Gray4Refresh,
+            SpiDevice(async = "AsyncSpiDevice"),
+            BusyPin(async = "AsyncBusyPin"),
+            DelayNs(async = "AsyncDelayNs")
+        )
+    )
+)]
+/// Epd4in26 driver
+pub struct Epd4in26<SPI, BUSY, DC, RST, DELAY> {
+    /// Connection Interface
+    interface: DisplayInterface<SPI, BUSY, DC, RST, DELAY, SINGLE_BYTE_WRITE>,
+    /// Background Color
+    background_color: Color,
+    /// Refresh LUT
+    refresh: RefreshLut,
+}
+
+#[maybe_async_cfg::maybe(
+    sync(keep_self),
+    async(
+        feature = "async",
+        idents(
+            Epd4in26,
+            DisplayInterface,
+            WaveshareDisplay,
+            InternalWiAdditions,
+            Gray4Refresh,
+            SpiDevice(async = "AsyncSpiDevice"),
+            BusyPin(async = "AsyncBusyPin"),
+            DelayNs(async = "AsyncDelayNs")
+        )
+    )
+)]
+impl<SPI, BUSY, DC, RST, DELAY> InternalWiAdditions<SPI, BUSY, DC, RST, DELAY>
+    for Epd4in26<SPI, BUSY, DC, RST, DELAY>
+where
+    SPI: SpiDevice,
+    BUSY: BusyPin,
+    DC: OutputPin,
+    RST: OutputPin,
+    DELAY: DelayNs,
+{
+    async fn init(&mut self, spi: &mut SPI, delay: &mut DELAY) -> Result<(), EpdError<SPI::Error>> {
+        self.interface.reset(delay, 100_000, 2_000).await?;
+
+        self.wait_until_idle(spi, delay).await?;
+        self.interface.cmd(spi, Command::SwReset).await?;
+        self.wait_until_idle(spi, delay).await?;
+
+        // internal temperature sensor
+        self.interface
+            .cmd_with_data(spi, Command::TemperatureSensorSelection, &[0x80])
+            .await?;
+
+        self.interface
+            .cmd_with_data(
+                spi,
+                Command::BoosterSoftStartControl,
+                &[0xAE, 0xC7, 0xC3, 0xC0, 0x80],
+            )
+            .await?;
+
+        // all 480 gates, interlaced
+        self.interface
+            .cmd_with_data(
+                spi,
+                Command::DriverOutputControl,
+                &[(HEIGHT - 1) as u8, ((HEIGHT - 1) >> 8) as u8, 0x02],
+            )
+            .await?;
+
+        self.interface
+            .cmd_with_data(spi, Command::BorderWaveformControl, &[0x01])
+            .await?;
+
+        // x increment, y increment, address counter is updated in x direction
+        self.interface
+            .cmd_with_data(spi, Command::DataEntryModeSetting, &[0x03])
+            .await?;
+
+        self.use_full_frame(spi, delay).await?;
+
+        if self.refresh != RefreshLut::Full {
+            self.load_forced_waveform(spi, delay).await?;
+        }
+        Ok(())
+    }
+}
+
+#[maybe_async_cfg::maybe(
+    sync(keep_self),
+    async(
+        feature = "async",
+        idents(
+            Epd4in26,
+            DisplayInterface,
+            WaveshareDisplay,
+            InternalWiAdditions,
+            Gray4Refresh,
+            SpiDevice(async = "AsyncSpiDevice"),
+            BusyPin(async = "AsyncBusyPin"),
+            DelayNs(async = "AsyncDelayNs")
+        )
+    )
+)]
+impl<SPI, BUSY, DC, RST, DELAY> WaveshareDisplay<SPI, BUSY, DC, RST, DELAY>
+    for Epd4in26<SPI, BUSY, DC, RST, DELAY>
+where
+    SPI: SpiDevice,
+    BUSY: BusyPin,
+    DC: OutputPin,
+    RST: OutputPin,
+    DELAY: DelayNs,
+{
+    type DisplayColor = Color;
+
+    fn width(&self) -> u32 {
+        WIDTH
+    }
+
+    fn height(&self) -> u32 {
+        HEIGHT
+    }
+
+    async fn new(
+        spi: &mut SPI,
+        busy: BUSY,
+        dc: DC,
+        rst: RST,
+        delay: &mut DELAY,
+        delay_us: Option<u32>,
+    ) -> Result<Self, EpdError<SPI::Error>> {
+        let mut epd = Self::new_uninit(busy, dc, rst, delay_us);
+        epd.init(spi, delay).await?;
+        Ok(epd)
+    }
+
+    fn new_uninit(busy: BUSY, dc: DC, rst: RST, delay_us: Option<u32>) -> Self {
+        let interface = DisplayInterface::new(busy, dc, rst, delay_us);
+
+        Epd4in26 {
+            interface,
+            background_color: DEFAULT_BACKGROUND_COLOR,
+            refresh: RefreshLut::Full,
+        }
+    }
+
+    async fn sleep(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.wait_until_idle(spi, delay).await?;
+        // 0x00 for Normal mode (Power on Reset), 0x01 for Deep Sleep Mode
+        self.interface
+            .cmd_with_data(spi, Command::DeepSleepMode, &[0x01])
+            .await
+    }
+
+    async fn wake_up(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.init(spi, delay).await
+    }
+
+    /// Sends a frame, of 4 gray levels as in a [`Display4in26Gray4`] with
+    /// [`RefreshLut::Gray4`]
+    async fn update_frame(
+        &mut self,
+        spi: &mut SPI,
+        buffer: &[u8],
+        delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.wait_until_idle(spi, delay).await?;
+        if self.refresh != RefreshLut::Gray4 {
+            EpdError::check_buffer(buffer, buffer_len(WIDTH as usize, HEIGHT as usize))?;
+            return self
+                .interface
+                .cmd_with_data(spi, Command::WriteRam, buffer)
+                .await;
+        }
+        EpdError::check_buffer(buffer, WIDTH as usize * HEIGHT as usize / 4)?;
+        self.interface.cmd(spi, Command::WriteRam).await?;
+        self.interface.data_gray4_plane(spi, buffer, 1).await?;
+        self.use_full_frame(spi, delay).await?;
+        self.interface.cmd(spi, Command::WriteRam2).await?;
+        self.interface.data_gray4_plane(spi, buffer, 0).await?;
+        self.use_full_frame(spi, delay).await
+    }
+
+    /// `x` and `width` must be multiples of 8
+    async fn update_partial_frame(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+        buffer: &[u8],
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        EpdError::check_buffer(buffer, buffer_len(width as usize, height as usize))?;
+        self.wait_until_idle(spi, delay).await?;
+        self.interface
+            .set_ram_area::<Ssd1677>(spi, x, y, x + width - 1, y + height - 1)
+            .await?;
+        self.interface.set_ram_counter::<Ssd1677>(spi, x, y).await?;
+        self.interface
+            .cmd_with_data(spi, Command::WriteRam, buffer)
+            .await?;
+        self.use_full_frame(spi, delay).await
+    }
+
+    /// Refreshes with the waveform of the selected LUT
+    async fn display_frame(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        let sequence = match self.refresh {
+            RefreshLut::Fast => FAST_REFRESH,
+            RefreshLut::Gray4 => GRAY4_REFRESH,
+            _ => FULL_REFRESH,
+        };
+        self.wait_until_idle(spi, delay).await?;
+        self.interface
+            .cmd_with_data(spi, Command::DisplayUpdateControl2, &[sequence])
+            .await?;
+        self.interface.cmd(spi, Command::MasterActivation).await?;
+        self.wait_until_idle(spi, delay).await
+    }
+
+    async fn update_and_display_frame(
+        &mut self,
+        spi: &mut SPI,
+        buffer: &[u8],
+        delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.update_frame(spi, buffer, delay).await?;
+        self.display_frame(spi, delay).await
+    }
+
+    async fn clear_frame(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.wait_until_idle(spi, delay).await?;
+
+        // both RAMs, the gray levels being made of both
+        let color = self.background_color.get_byte_value();
+        self.interface.cmd(spi, Command::WriteRam).await?;
+        self.interface
+            .data_x_times(spi, color, WIDTH / 8 * HEIGHT)
+            .await?;
+        self.use_full_frame(spi, delay).await?;
+        self.interface.cmd(spi, Command::WriteRam2).await?;
+        self.interface
+            .data_x_times(spi, color, WIDTH / 8 * HEIGHT)
+            .await?;
+        self.use_full_frame(spi, delay).await
+    }
+
+    fn set_background_color(&mut self, background_color: Color) {
+        self.background_color = background_color;
+    }
+
+    fn background_color(&self) -> &Color {
+        &self.background_color
+    }
+
+    /// Selects the full, fast or 4 gray levels waveform, the latter changing the frames sent, see
+    /// [`update_frame`](WaveshareDisplay::update_frame)
+    async fn set_lut(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+        refresh_rate: Option<RefreshLut>,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        if let Some(refresh_lut) = refresh_rate {
+            self.refresh = refresh_lut.or_full(&[RefreshLut::Fast, RefreshLut::Gray4]);
+        }
+        self.wait_until_idle(spi, delay).await?;
+        // the full refreshes load the waveform of the measured temperature
+        if self.refresh != RefreshLut::Full {
+            self.load_forced_waveform(spi, delay).await?;
+        }
+        Ok(())
+    }
+
+    fn interface_config_mut(&mut self) -> &mut InterfaceConfig {
+        self.interface.config_mut()
+    }
+
+    fn release(self) -> (BUSY, DC, RST) {
+        self.interface.release()
+    }
+
+    async fn wait_until_idle(
+        &mut self,
+        _spi: &mut SPI,
+        delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.interface.wait_until_idle(delay, IS_BUSY_LOW).await
+    }
+}
+
+#[maybe_async_cfg::maybe(
+    sync(keep_self),
+    async(
+        feature = "async",
+        idents(
+            Epd4in26,
+            DisplayInterface,
+            WaveshareDisplay,
+            InternalWiAdditions,
+            Gray4Refresh,
+            SpiDevice(async = "AsyncSpiDevice"),
+            BusyPin(async = "AsyncBusyPin"),
+            DelayNs(async = "AsyncDelayNs")
+        )
+    )
+)]
+impl<SPI, BUSY, DC, RST, DELAY> Epd4in26<SPI, BUSY, DC, RST, DELAY>
+where
+    SPI: SpiDevice,
+    BUSY: BusyPin,
+    DC: OutputPin,
+    RST: OutputPin,
+    DELAY: DelayNs,
+{
+    /// Sends a black and white frame band by band, rendered in `band` by `render`
+    ///
+    /// `band` holds as many lines of `LINE_BYTES` bytes as possible, at least one or
+    /// [`EpdError::BufferSize`] is returned. For each band, `render` is called with the area of the
+    /// frame it covers and the pixels to fill, 8 per byte as in [`Display4in26`]. The frame is
+    /// shown with [`display_frame`](WaveshareDisplay::display_frame).
+    pub async fn update_frame_banded<F>(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+        band: &mut [u8],
+        render: F,
+    ) -> Result<(), EpdError<SPI::Error>>
+    where
+        F: FnMut(Rect, &mut [u8]),
+    {
+        self.wait_until_idle(spi, delay).await?;
+        self.interface.cmd(spi, Command::WriteRam).await?;
+        self.interface
+            .data_banded(
+                spi,
+                Rect::new(0, 0, WIDTH, HEIGHT),
+                LINE_BYTES,
+                band,
+                render,
+            )
+            .await
+    }
+
+    /// Loads the waveform of a forced high temperature, the fast and 4 gray levels ones of the
+    /// OTP
+    async fn load_forced_waveform(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.interface
+            .cmd_with_data(
+                spi,
+                Command::TemperatureSensorControl,
+                &[FORCED_TEMPERATURE, 0x00],
+            )
+            .await?;
+        // load the LUT of this temperature
+        self.interface
+            .cmd_with_data(spi, Command::DisplayUpdateControl2, &[0x91])
+            .await?;
+        self.interface.cmd(spi, Command::MasterActivation).await?;
+        self.wait_until_idle(spi, delay).await
+    }
+
+    async fn use_full_frame(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.interface
+            .set_ram_area::<Ssd1677>(spi, 0, 0, WIDTH - 1, HEIGHT - 1)
+            .await?;
+        self.wait_until_idle(spi, delay).await?;
+        self.interface.set_ram_counter::<Ssd1677>(spi, 0, 0).await
+    }
+}
+
+#[maybe_async_cfg::maybe(
+    sync(keep_self),
+    async(
+        feature = "async",
+        idents(
+            Epd4in26,
+            DisplayInterface,
+            WaveshareDisplay,
+            InternalWiAdditions,
+            Gray4Refresh,
+            SpiDevice(async = "AsyncSpiDevice"),
+            BusyPin(async = "AsyncBusyPin"),
+            DelayNs(async = "AsyncDelayNs")
+        )
+    )
+)]
+impl<SPI, BUSY, DC, RST, DELAY> Gray4Refresh<SPI, BUSY, DC, RST, DELAY>
+    for Epd4in26<SPI, BUSY, DC, RST, DELAY>
+where
+    SPI: SpiDevice,
+    BUSY: BusyPin,
+    DC: OutputPin,
+    RST: OutputPin,
+    DELAY: DelayNs,
+{
+    /// Sends a frame of 4 gray levels, packed by 4 pixels in a byte as in a
+    /// [`Display4in26Gray4`], and refreshes it with the grayscale waveform
+    ///
+    /// The following refreshes use the LUT selected before.
+    async fn update_and_display_gray4_frame(
+        &mut self,
+        spi: &mut SPI,
+        buffer: &[u8],
+        delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        EpdError::check_buffer(buffer, WIDTH as usize * HEIGHT as usize / 4)?;
+        let previous = self.refresh;
+        self.set_lut(spi, delay, Some(RefreshLut::Gray4)).await?;
+        let result = self.update_and_display_frame(spi, buffer, delay).await;
+        let restored = self.set_lut(spi, delay, Some(previous)).await;
+        result.and(restored)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn epd_size() {
+        assert_eq!(WIDTH, 800);
+        assert_eq!(HEIGHT, 480);
+        assert_eq!(LINE_BYTES * HEIGHT as usize, 48_000);
+        assert_eq!(DEFAULT_BACKGROUND_COLOR, Color::White);
+    }
+}
@@ -0,0 +1,42 @@
+//! SPI Commands for the Waveshare 5.79" E-Ink Display
+
+use crate::traits;
+
+/// Epd5in79 commands
+///
+/// The panel is driven by two SSD1683 controllers behind the same chip select: the commands with
+/// bit 7 set are those of the slave one, driving the right half.
+#[allow(dead_code)]
+#[derive(Copy, Clone)]
+pub(crate) enum Command {
+    DriverOutputControl = 0x01,
+    BoosterSoftStartControl = 0x0C,
+    DeepSleepMode = 0x10,
+    DataEntryModeSetting = 0x11,
+    SwReset = 0x12,
+    TemperatureSensorSelection = 0x18,
+    MasterActivation = 0x20,
+    DisplayUpdateControl2 = 0x22,
+    WriteRam = 0x24,
+    WriteRam2 = 0x26,
+    BorderWaveformControl = 0x3C,
+    SetRamXAddressStartEndPosition = 0x44,
+    SetRamYAddressStartEndPosition = 0x45,
+    SetRamXAddressCounter = 0x4E,
+    SetRamYAddressCounter = 0x4F,
+
+    SlaveDataEntryModeSetting = 0x91,
+    SlaveWriteRam = 0xA4,
+    SlaveWriteRam2 = 0xA6,
+    SlaveSetRamXAddressStartEndPosition = 0xC4,
+    SlaveSetRamYAddressStartEndPosition = 0xC5,
+    SlaveSetRamXAddressCounter = 0xCE,
+    SlaveSetRamYAddressCounter = 0xCF,
+}
+
+impl traits::Command for Command {
+    /// Returns the address of the command
+    fn address(self) -> u8 {
+        self as u8
+    }
+}
@@ -0,0 +1,460 @@
+//! A Driver for the Waveshare 5.79" E-Ink Display via SPI
+//!
+//! The 792x272 black and white panel is driven by two SSD1683 controllers behind the same chip
+//! select, each scanning one half of it: the master the columns 0 to 399, the slave the columns
+//! 392 to 791, both driving the 8 columns in between. The driver hides the split: the frames
+//! are those of the whole panel, as in a [`Display5in79`], and each line is sent in two parts
+//! to the RAMs of both controllers, following the Waveshare C driver (`EPD_5in79.c`).
+//!
+//!```rust, no_run
+//!# use embedded_hal_mock::eh1::*;
+//!# fn main() -> Result<(), epd_waveshare::error::EpdError<embedded_hal::spi::ErrorKind>> {
+//!use embedded_graphics::{prelude::*, primitives::{Line, PrimitiveStyle}};
+//!use epd_waveshare::{epd5in79::*, prelude::*};
+//!#
+//!# let expectations = [];
+//!# let mut spi = spi::Mock::new(&expectations);
+//!# let expectations = [];
+//!# let busy_in = digital::Mock::new(&expectations);
+//!# let dc = digital::Mock::new(&expectations);
+//!# let rst = digital::Mock::new(&expectations);
+//!# let mut delay = delay::NoopDelay::new();
+//!
+//!let mut epd = Epd5in79::new(&mut spi, busy_in, dc, rst, &mut delay, None)?;
+//!
+//!let mut display = Display5in79::default();
+//!let _ = display.clear(Color::White);
+//!// a line across both halves
+//!let _ = Line::new(Point::new(0, 136), Point::new(791, 136))
+//!    .into_styled(PrimitiveStyle::with_stroke(Color::Black, 4))
+//!    .draw(&mut display);
+//!
+//!epd.update_and_display_frame(&mut spi, display.buffer(), &mut delay)?;
+//!epd.sleep(&mut spi, &mut delay)?;
+//!# Ok(())
+//!# }
+//!```
+
+use crate::error::EpdError;
+use crate::pin::BusyPin;
+use embedded_hal::{delay::*, digital::*, spi::SpiDevice};
+
+use crate::buffer_len;
+use crate::color::Color;
+use crate::interface::DisplayInterface;
+use crate::ram_addressing::{x_window, y_window, RamAddressing, Ssd1680};
+use crate::traits::{InterfaceConfig, InternalWiAdditions, RefreshLut, WaveshareDisplay};
+
+use self::command::Command;
+#[cfg(feature = "async")]
+use crate::interface::DisplayInterfaceAsync;
+#[cfg(feature = "async")]
+use crate::traits::{AsyncBusyPin, InternalWiAdditionsAsync, WaveshareDisplayAsync};
+#[cfg(feature = "async")]
+use embedded_hal_async::{delay::DelayNs as AsyncDelayNs, spi::SpiDevice as AsyncSpiDevice};
+
+mod command;
+
+/// Width of the display in pixels
+pub const WIDTH: u32 = 792;
+/// Height of the display in pixels
+pub const HEIGHT: u32 = 272;
+/// Number of bytes of a line of pixels
+pub const LINE_BYTES: usize = WIDTH as usize / 8;
+/// Default Background Color (white)
+pub const DEFAULT_BACKGROUND_COLOR: Color = Color::White;
+const IS_BUSY_LOW: bool = false;
+const SINGLE_BYTE_WRITE: bool = false;
+
+/// Number of bytes of a line in the RAM of each controller, 400 columns
+const HALF_BYTES: usize = 50;
+
+/// Full size buffer for use with the 5in79 EPD
+#[cfg(feature = "graphics")]
+pub type Display5in79 = crate::graphics::Display<
+    WIDTH,
+    HEIGHT,
+    false,
+    { crate::graphics::display_len::<Color>(WIDTH, HEIGHT) },
+    Color,
+>;
+
+#[maybe_async_cfg::maybe(
+    sync(keep_self),
+    async(
+        feature = "async",
+        idents(
+            Epd5in79,
+            DisplayInterface,
+            WaveshareDisplay,
+            InternalWiAdditions,
+            SpiDevice(async = "AsyncSpiDevice"),
+            BusyPin(async = "AsyncBusyPin"),
+            DelayNs(async = "AsyncDelayNs")
+        )
+    )
+)]
+/// Epd5in79 driver
+pub struct Epd5in79<SPI, BUSY, DC, RST, DELAY> {
+    /// Connection Interface
+    interface: DisplayInterface<SPI, BUSY, DC, RST, DELAY, SINGLE_BYTE_WRITE>,
+    /// Background Color
+    background_color: Color,
+}
+
+#[maybe_async_cfg::maybe(
+    sync(keep_self),
+    async(
+        feature = "async",
+        idents(
+            Epd5in79,
+            DisplayInterface,
+            WaveshareDisplay,
+            InternalWiAdditions,
+            SpiDevice(async = "AsyncSpiDevice"),
+            BusyPin(async = "AsyncBusyPin"),
+            DelayNs(async = "AsyncDelayNs")
+        )
+    )
+)]
+impl<SPI, BUSY, DC, RST, DELAY> InternalWiAdditions<SPI, BUSY, DC, RST, DELAY>
+    for Epd5in79<SPI, BUSY, DC, RST, DELAY>
+where
+    SPI: SpiDevice,
+    BUSY: BusyPin,
+    DC: OutputPin,
+    RST: OutputPin,
+    DELAY: DelayNs,
+{
+    async fn init(&mut self, spi: &mut SPI, delay: &mut DELAY) -> Result<(), EpdError<SPI::Error>> {
+        self.interface.reset(delay, 100_000, 2_000).await?;
+
+        self.wait_until_idle(spi, delay).await?;
+        self.interface.cmd(spi, Command::SwReset).await?;
+        self.wait_until_idle(spi, delay).await?;
+
+        // internal temperature sensor
+        self.interface
+            .cmd_with_data(spi, Command::TemperatureSensorSelection, &[0x80])
+            .await?;
+
+        self.interface
+            .cmd_with_data(
+                spi,
+                Command::BoosterSoftStartControl,
+                &[0xAE, 0xC7, 0xC3, 0xC0, 0x80],
+            )
+            .await?;
+
+        // all 272 gates
+        self.interface
+            .cmd_with_data(
+                spi,
+                Command::DriverOutputControl,
+                &[(HEIGHT - 1) as u8, ((HEIGHT - 1) >> 8) as u8, 0x02],
+            )
+            .await?;
+
+        self.interface
+            .cmd_with_data(spi, Command::BorderWaveformControl, &[0x01])
+            .await?;
+
+        // master: x increment, y decrement
+        self.interface
+            .cmd_with_data(spi, Command::DataEntryModeSetting, &[0x01])
+            .await?;
+        // slave, whose sources are mirrored: x decrement, y decrement
+        self.interface
+            .cmd_with_data(spi, Command::SlaveDataEntryModeSetting, &[0x00])
+            .await?;
+
+        self.use_full_frame(spi, delay).await
+    }
+}
+
+#[maybe_async_cfg::maybe(
+    sync(keep_self),
+    async(
+        feature = "async",
+        idents(
+            Epd5in79,
+            DisplayInterface,
+            WaveshareDisplay,
+            InternalWiAdditions,
+            SpiDevice(async = "AsyncSpiDevice"),
+            BusyPin(async = "AsyncBusyPin"),
+            DelayNs(async = "AsyncDelayNs")
+        )
+    )
+)]
+impl<SPI, BUSY, DC, RST, DELAY> WaveshareDisplay<SPI, BUSY, DC, RST, DELAY>
+    for Epd5in79<SPI, BUSY, DC, RST, DELAY>
+where
+    SPI: SpiDevice,
+    BUSY: BusyPin,
+    DC: OutputPin,
+    RST: OutputPin,
+    DELAY: DelayNs,
+{
+    type DisplayColor = Color;
+
+    fn width(&self) -> u32 {
+        WIDTH
+    }
+
+    fn height(&self) -> u32 {
+        HEIGHT
+    }
+
+    async fn new(
+        spi: &mut SPI,
+        busy: BUSY,
+        dc: DC,
+        rst: RST,
+        delay: &mut DELAY,
+        delay_us: Option<u32>,
+    ) -> Result<Self, EpdError<SPI::Error>> {
+        let mut epd = Self::new_uninit(busy, dc, rst, delay_us);
+        epd.init(spi, delay).await?;
+        Ok(epd)
+    }
+
+    fn new_uninit(busy: BUSY, dc: DC, rst: RST, delay_us: Option<u32>) -> Self {
+        let interface = DisplayInterface::new(busy, dc, rst, delay_us);
+
+        Epd5in79 {
+            interface,
+            background_color: DEFAULT_BACKGROUND_COLOR,
+        }
+    }
+
+    async fn sleep(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.wait_until_idle(spi, delay).await?;
+        // 0x00 for Normal mode (Power on Reset), 0x01 for Deep Sleep Mode
+        self.interface
+            .cmd_with_data(spi, Command::DeepSleepMode, &[0x01])
+            .await
+    }
+
+    async fn wake_up(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.init(spi, delay).await
+    }
+
+    /// Sends a frame of the whole panel, each line being split between both controllers
+    async fn update_frame(
+        &mut self,
+        spi: &mut SPI,
+        buffer: &[u8],
+        delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        EpdError::check_buffer(buffer, buffer_len(WIDTH as usize, HEIGHT as usize))?;
+        self.wait_until_idle(spi, delay).await?;
+        self.use_full_frame(spi, delay).await?;
+
+        self.interface.cmd(spi, Command::WriteRam).await?;
+        for line in buffer.chunks(LINE_BYTES) {
+            self.interface.data(spi, split_line(line).0).await?;
+        }
+        self.interface.cmd(spi, Command::SlaveWriteRam).await?;
+        for line in buffer.chunks(LINE_BYTES) {
+            self.interface.data(spi, split_line(line).1).await?;
+        }
+        Ok(())
+    }
+
+    /// Partial updates aren't supported: returns [`EpdError::Unsupported`]
+    async fn update_partial_frame(
+        &mut self,
+        _spi: &mut SPI,
+        _delay: &mut DELAY,
+        _buffer: &[u8],
+        _x: u32,
+        _y: u32,
+        _width: u32,
+        _height: u32,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        Err(EpdError::Unsupported)
+    }
+
+    async fn display_frame(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.wait_until_idle(spi, delay).await?;
+        // Enable clock and analog, load temperature and the LUT, display mode 1, disable
+        self.interface
+            .cmd_with_data(spi, Command::DisplayUpdateControl2, &[0xF7])
+            .await?;
+        self.interface.cmd(spi, Command::MasterActivation).await?;
+        self.wait_until_idle(spi, delay).await
+    }
+
+    async fn update_and_display_frame(
+        &mut self,
+        spi: &mut SPI,
+        buffer: &[u8],
+        delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.update_frame(spi, buffer, delay).await?;
+        self.display_frame(spi, delay).await
+    }
+
+    async fn clear_frame(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.wait_until_idle(spi, delay).await?;
+        self.use_full_frame(spi, delay).await?;
+
+        let color = self.background_color.get_byte_value();
+        let half_frame = HALF_BYTES as u32 * HEIGHT;
+        self.interface.cmd(spi, Command::WriteRam).await?;
+        self.interface.data_x_times(spi, color, half_frame).await?;
+        self.interface.cmd(spi, Command::SlaveWriteRam).await?;
+        self.interface.data_x_times(spi, color, half_frame).await
+    }
+
+    fn set_background_color(&mut self, background_color: Color) {
+        self.background_color = background_color;
+    }
+
+    fn background_color(&self) -> &Color {
+        &self.background_color
+    }
+
+    async fn set_lut(
+        &mut self,
+        _spi: &mut SPI,
+        _delay: &mut DELAY,
+        _refresh_rate: Option<RefreshLut>,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        Ok(())
+    }
+
+    fn interface_config_mut(&mut self) -> &mut InterfaceConfig {
+        self.interface.config_mut()
+    }
+
+    fn release(self) -> (BUSY, DC, RST) {
+        self.interface.release()
+    }
+
+    async fn wait_until_idle(
+        &mut self,
+        _spi: &mut SPI,
+        delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.interface.wait_until_idle(delay, IS_BUSY_LOW).await
+    }
+}
+
+#[maybe_async_cfg::maybe(
+    sync(keep_self),
+    async(
+        feature = "async",
+        idents(
+            Epd5in79,
+            DisplayInterface,
+            WaveshareDisplay,
+            InternalWiAdditions,
+            SpiDevice(async = "AsyncSpiDevice"),
+            BusyPin(async = "AsyncBusyPin"),
+            DelayNs(async = "AsyncDelayNs")
+        )
+    )
+)]
+impl<SPI, BUSY, DC, RST, DELAY> Epd5in79<SPI, BUSY, DC, RST, DELAY>
+where
+    SPI: SpiDevice,
+    BUSY: BusyPin,
+    DC: OutputPin,
+    RST: OutputPin,
+    DELAY: DelayNs,
+{
+    /// Sets the RAM windows of both controllers to their whole half, from the last line up, and
+    /// moves their address counters to its start
+    async fn use_full_frame(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        let last_x = HALF_BYTES as u32 * 8 - 1;
+        let (x, len) = x_window::<Ssd1680>(0, last_x);
+        let (slave_x, _) = x_window::<Ssd1680>(last_x, 0);
+        let y = y_window::<Ssd1680>(HEIGHT - 1, 0);
+
+        self.interface
+            .cmd_with_data(spi, Command::SetRamXAddressStartEndPosition, &x[..len])
+            .await?;
+        self.interface
+            .cmd_with_data(spi, Command::SetRamYAddressStartEndPosition, &y)
+            .await?;
+        self.interface
+            .cmd_with_data(
+                spi,
+                Command::SlaveSetRamXAddressStartEndPosition,
+                &slave_x[..len],
+            )
+            .await?;
+        self.interface
+            .cmd_with_data(spi, Command::SlaveSetRamYAddressStartEndPosition, &y)
+            .await?;
+        self.wait_until_idle(spi, delay).await?;
+
+        let (x, len) = Ssd1680::x_address(0);
+        let (slave_x, _) = Ssd1680::x_address(last_x);
+        let y = Ssd1680::y_address(HEIGHT - 1);
+        self.interface
+            .cmd_with_data(spi, Command::SetRamXAddressCounter, &x[..len])
+            .await?;
+        self.interface
+            .cmd_with_data(spi, Command::SetRamYAddressCounter, &y)
+            .await?;
+        self.interface
+            .cmd_with_data(spi, Command::SlaveSetRamXAddressCounter, &slave_x[..len])
+            .await?;
+        self.interface
+            .cmd_with_data(spi, Command::SlaveSetRamYAddressCounter, &y)
+            .await
+    }
+}
+
+/// Parts of `line` sent to the master and the slave controllers, both holding the 8 columns in
+/// the middle
+fn split_line(line: &[u8]) -> (&[u8], &[u8]) {
+    (&line[..HALF_BYTES], &line[LINE_BYTES - HALF_BYTES..])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn epd_size() {
+        assert_eq!(WIDTH, 792);
+        assert_eq!(HEIGHT, 272);
+        assert_eq!(LINE_BYTES, 99);
+        assert_eq!(DEFAULT_BACKGROUND_COLOR, Color::White);
+    }
+
+    #[test]
+    fn halves() {
+        let line: [u8; LINE_BYTES] = core::array::from_fn(|i| i as u8);
+        let (master, slave) = split_line(&line);
+        assert_eq!(master.len(), HALF_BYTES);
+        assert_eq!(slave.len(), HALF_BYTES);
+        // columns 0 to 399, and 392 to 791
+        assert_eq!((master[0], master[HALF_BYTES - 1]), (0, 49));
+        assert_eq!((slave[0], slave[HALF_BYTES - 1]), (49, 98));
+    }
+}
@@ -1,35 +1,75 @@
 //! A simple Driver for the Waveshare 7.3inch e-Paper HAT (F) Display via SPI
 //!
+//! The full frame needs 188KiB at 4 bits per pixel, more than the RAM of many MCUs, so it can
+//! also be rendered and sent band by band with
+//! [`update_frame_banded`](Epd7in3f::update_frame_banded) from a buffer of a few lines.
+//!
+//! A refresh powers the panel on, refreshes it and powers it off again, which takes about 30
+//! seconds: [`display_frame`](WaveshareDisplay::display_frame) waits for each of these steps.
+//!
 //! # References
 //!
 //! - [Datasheet](https://www.waveshare.com/wiki/7.3inch_e-Paper_HAT_(F))
 //! - [Waveshare C driver](https://github.com/waveshareteam/e-Paper/blob/8be47b27f1a6808fd82ea9ceeac04c172e4ee9a8/RaspberryPi_JetsonNano/c/lib/e-Paper/EPD_7in3f.c)
 //! - [Waveshare Python driver](https://github.com/waveshareteam/e-Paper/blob/8be47b27f1a6808fd82ea9ceeac04c172e4ee9a8/RaspberryPi_JetsonNano/python/lib/waveshare_epd/epd7in3f.py)
-
-use embedded_hal::{
-    delay::DelayNs,
-    digital::{InputPin, OutputPin},
-    spi::SpiDevice,
-};
+//!
+//!```rust, no_run
+//!# use embedded_hal_mock::eh1::*;
+//!# fn main() -> Result<(), epd_waveshare::error::EpdError<embedded_hal::spi::ErrorKind>> {
+//!use epd_waveshare::{color::OctColor, epd7in3f::*, prelude::*};
+//!#
+//!# let expectations = [];
+//!# let mut spi = spi::Mock::new(&expectations);
+//!# let expectations = [];
+//!# let busy_in = digital::Mock::new(&expectations);
+//!# let dc = digital::Mock::new(&expectations);
+//!# let rst = digital::Mock::new(&expectations);
+//!# let mut delay = delay::NoopDelay::new();
+//!
+//!let mut epd = Epd7in3f::new(&mut spi, busy_in, dc, rst, &mut delay, None)?;
+//!
+//!// 16 lines at a time: red on the top half, blue on the bottom one
+//!let mut band = [0; 16 * LINE_BYTES];
+//!epd.update_frame_banded(&mut spi, &mut delay, &mut band, |area, pixels| {
+//!    let color = if area.y < HEIGHT / 2 { OctColor::Red } else { OctColor::Blue };
+//!    pixels.fill(OctColor::colors_byte(color, color));
+//!})?;
+//!epd.display_frame(&mut spi, &mut delay)?;
+//!# Ok(())
+//!# }
+//!```
+
+use crate::error::EpdError;
+use crate::pin::BusyPin;
+use embedded_hal::{delay::DelayNs, digital::OutputPin, spi::SpiDevice};
 
 use crate::{
-    buffer_len,
     color::OctColor,
-    interface::DisplayInterface,
-    traits::{InternalWiAdditions, WaveshareDisplay},
+    interface::{BusyTimings, DisplayInterface},
+    rect::Rect,
+    traits::{InterfaceConfig, InternalWiAdditions, WaveshareDisplay},
 };
 
 use self::command::Command;
+#[cfg(feature = "async")]
+use crate::interface::DisplayInterfaceAsync;
+#[cfg(feature = "async")]
+use crate::traits::{AsyncBusyPin, InternalWiAdditionsAsync, WaveshareDisplayAsync};
+#[cfg(feature = "async")]
+use embedded_hal_async::{delay::DelayNs as AsyncDelayNs, spi::SpiDevice as AsyncSpiDevice};
 
 mod command;
 
 /// Full size buffer for use with the 7in3f EPD
+///
+/// On MCUs with less RAM, a `VarDisplay<OctColor>` of a few lines can be used to render the
+/// bands of [`update_frame_banded`](Epd7in3f::update_frame_banded) instead.
 #[cfg(feature = "graphics")]
 pub type Display7in3f = crate::graphics::Display<
     WIDTH,
     HEIGHT,
     false,
-    { buffer_len(WIDTH as usize, HEIGHT as usize * 4) },
+    { crate::graphics::display_len::<OctColor>(WIDTH, HEIGHT) },
     OctColor,
 >;
 
@@ -37,12 +77,29 @@ pub type Display7in3f = crate::graphics::Display<
 pub const WIDTH: u32 = 800;
 /// Height of the display
 pub const HEIGHT: u32 = 480;
+/// Number of bytes of a line of pixels
+pub const LINE_BYTES: usize = WIDTH as usize / 2;
 /// Default Background Color
 pub const DEFAULT_BACKGROUND_COLOR: OctColor = OctColor::White;
 /// Default mode of writing data (single byte vs blockwise)
 const SINGLE_BYTE_WRITE: bool = true;
 
-/// Epd57n3f driver
+#[maybe_async_cfg::maybe(
+    sync(keep_self),
+    async(
+        feature = "async",
+        idents(
+            Epd7in3f,
+            DisplayInterface,
+            WaveshareDisplay,
+            InternalWiAdditions,
+            SpiDevice(async = "AsyncSpiDevice"),
+            BusyPin(async = "AsyncBusyPin"),
+            DelayNs(async = "AsyncDelayNs")
+        )
+    )
+)]
+/// Epd7in3f driver
 pub struct Epd7in3f<SPI, BUSY, DC, RST, DELAY> {
     /// Connection Interface
     interface: DisplayInterface<SPI, BUSY, DC, RST, DELAY, SINGLE_BYTE_WRITE>,
@@ -50,80 +107,130 @@ pub struct Epd7in3f<SPI, BUSY, DC, RST, DELAY> {
     color: OctColor,
 }
 
+#[maybe_async_cfg::maybe(
+    sync(keep_self),
+    async(
+        feature = "async",
+        idents(
+            Epd7in3f,
+            DisplayInterface,
+            WaveshareDisplay,
+            InternalWiAdditions,
+            SpiDevice(async = "AsyncSpiDevice"),
+            BusyPin(async = "AsyncBusyPin"),
+            DelayNs(async = "AsyncDelayNs")
+        )
+    )
+)]
 impl<SPI, BUSY, DC, RST, DELAY> InternalWiAdditions<SPI, BUSY, DC, RST, DELAY>
     for Epd7in3f<SPI, BUSY, DC, RST, DELAY>
 where
     SPI: SpiDevice,
-    BUSY: InputPin,
+    BUSY: BusyPin,
     DC: OutputPin,
     RST: OutputPin,
     DELAY: DelayNs,
 {
-    fn init(&mut self, spi: &mut SPI, delay: &mut DELAY) -> Result<(), <SPI>::Error> {
-        self.interface.reset(delay, 20_000, 2_000);
-        self.wait_busy_low(delay);
-        delay.delay_ms(30);
-
-        self.cmd_with_data(spi, Command::CMDH, &[0x49, 0x55, 0x20, 0x08, 0x09, 0x18])?;
-        self.cmd_with_data(spi, Command::Ox01, &[0x3F, 0x00, 0x32, 0x2A, 0x0E, 0x2A])?;
-        self.cmd_with_data(spi, Command::Ox00, &[0x5F, 0x69])?;
-        self.cmd_with_data(spi, Command::Ox03, &[0x00, 0x54, 0x00, 0x44])?;
-        self.cmd_with_data(spi, Command::Ox05, &[0x40, 0x1F, 0x1F, 0x2C])?;
-        self.cmd_with_data(spi, Command::Ox06, &[0x6F, 0x1F, 0x1F, 0x22])?;
-        self.cmd_with_data(spi, Command::Ox08, &[0x6F, 0x1F, 0x1F, 0x22])?;
-        self.cmd_with_data(spi, Command::IPC, &[0x00, 0x04])?;
-        self.cmd_with_data(spi, Command::Ox30, &[0x3C])?;
-        self.cmd_with_data(spi, Command::TSE, &[0x00])?;
-        self.cmd_with_data(spi, Command::Ox50, &[0x3F])?;
-        self.cmd_with_data(spi, Command::Ox60, &[0x02, 0x00])?;
-        self.cmd_with_data(spi, Command::Ox61, &[0x03, 0x20, 0x01, 0xE0])?;
-        self.cmd_with_data(spi, Command::Ox82, &[0x1E])?;
-        self.cmd_with_data(spi, Command::Ox84, &[0x00])?;
-        self.cmd_with_data(spi, Command::AGID, &[0x00])?;
-        self.cmd_with_data(spi, Command::OxE3, &[0x2F])?;
-        self.cmd_with_data(spi, Command::CCSET, &[0x00])?;
-        self.cmd_with_data(spi, Command::TSSET, &[0x00])
+    async fn init(&mut self, spi: &mut SPI, delay: &mut DELAY) -> Result<(), EpdError<SPI::Error>> {
+        self.interface.reset(delay, 20_000, 2_000).await?;
+        self.wait_busy_low(delay).await?;
+        delay.delay_ms(30).await;
+
+        self.cmd_with_data(spi, Command::CMDH, &[0x49, 0x55, 0x20, 0x08, 0x09, 0x18])
+            .await?;
+        self.cmd_with_data(spi, Command::Ox01, &[0x3F, 0x00, 0x32, 0x2A, 0x0E, 0x2A])
+            .await?;
+        self.cmd_with_data(spi, Command::Ox00, &[0x5F, 0x69])
+            .await?;
+        self.cmd_with_data(spi, Command::Ox03, &[0x00, 0x54, 0x00, 0x44])
+            .await?;
+        self.cmd_with_data(spi, Command::Ox05, &[0x40, 0x1F, 0x1F, 0x2C])
+            .await?;
+        self.cmd_with_data(spi, Command::Ox06, &[0x6F, 0x1F, 0x1F, 0x22])
+            .await?;
+        self.cmd_with_data(spi, Command::Ox08, &[0x6F, 0x1F, 0x1F, 0x22])
+            .await?;
+        self.cmd_with_data(spi, Command::IPC, &[0x00, 0x04]).await?;
+        self.cmd_with_data(spi, Command::Ox30, &[0x3C]).await?;
+        self.cmd_with_data(spi, Command::TSE, &[0x00]).await?;
+        self.cmd_with_data(spi, Command::Ox50, &[0x3F]).await?;
+        self.cmd_with_data(spi, Command::Ox60, &[0x02, 0x00])
+            .await?;
+        self.cmd_with_data(spi, Command::Ox61, &[0x03, 0x20, 0x01, 0xE0])
+            .await?;
+        self.cmd_with_data(spi, Command::Ox82, &[0x1E]).await?;
+        self.cmd_with_data(spi, Command::Ox84, &[0x00]).await?;
+        self.cmd_with_data(spi, Command::AGID, &[0x00]).await?;
+        self.cmd_with_data(spi, Command::OxE3, &[0x2F]).await?;
+        self.cmd_with_data(spi, Command::CCSET, &[0x00]).await?;
+        self.cmd_with_data(spi, Command::TSSET, &[0x00]).await
     }
 }
 
+#[maybe_async_cfg::maybe(
+    sync(keep_self),
+    async(
+        feature = "async",
+        idents(
+            Epd7in3f,
+            DisplayInterface,
+            WaveshareDisplay,
+            InternalWiAdditions,
+            SpiDevice(async = "AsyncSpiDevice"),
+            BusyPin(async = "AsyncBusyPin"),
+            DelayNs(async = "AsyncDelayNs")
+        )
+    )
+)]
 impl<SPI, BUSY, DC, RST, DELAY> WaveshareDisplay<SPI, BUSY, DC, RST, DELAY>
     for Epd7in3f<SPI, BUSY, DC, RST, DELAY>
 where
     SPI: SpiDevice,
-    BUSY: InputPin,
+    BUSY: BusyPin,
     DC: OutputPin,
     RST: OutputPin,
     DELAY: DelayNs,
 {
     type DisplayColor = OctColor;
 
-    fn new(
+    async fn new(
         spi: &mut SPI,
         busy: BUSY,
         dc: DC,
         rst: RST,
         delay: &mut DELAY,
         delay_us: Option<u32>,
-    ) -> Result<Self, <SPI>::Error>
+    ) -> Result<Self, EpdError<SPI::Error>>
     where
         Self: Sized,
     {
-        let interface = DisplayInterface::new(busy, dc, rst, delay_us);
-        let color = DEFAULT_BACKGROUND_COLOR;
-
-        let mut epd = Epd7in3f { interface, color };
+        let mut epd = Self::new_uninit(busy, dc, rst, delay_us);
+        epd.init(spi, delay).await?;
+        Ok(epd)
+    }
 
-        epd.init(spi, delay)?;
+    fn new_uninit(busy: BUSY, dc: DC, rst: RST, delay_us: Option<u32>) -> Self {
+        let interface =
+            DisplayInterface::new(busy, dc, rst, delay_us).with_busy_timings(BusyTimings::ACEP);
+        let color = DEFAULT_BACKGROUND_COLOR;
 
-        Ok(epd)
+        Epd7in3f { interface, color }
     }
 
-    fn sleep(&mut self, spi: &mut SPI, _delay: &mut DELAY) -> Result<(), <SPI>::Error> {
-        self.cmd_with_data(spi, Command::DeepSleep, &[0xA5])
+    async fn sleep(
+        &mut self,
+        spi: &mut SPI,
+        _delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.cmd_with_data(spi, Command::DeepSleep, &[0xA5]).await
     }
 
-    fn wake_up(&mut self, spi: &mut SPI, delay: &mut DELAY) -> Result<(), <SPI>::Error> {
-        self.init(spi, delay)
+    async fn wake_up(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.init(spi, delay).await
     }
 
     fn set_background_color(&mut self, color: Self::DisplayColor) {
@@ -142,17 +249,19 @@ where
         HEIGHT
     }
 
-    fn update_frame(
+    async fn update_frame(
         &mut self,
         spi: &mut SPI,
         buffer: &[u8],
         delay: &mut DELAY,
-    ) -> Result<(), <SPI>::Error> {
-        self.wait_until_idle(spi, delay)?;
+    ) -> Result<(), EpdError<SPI::Error>> {
+        EpdError::check_buffer(buffer, LINE_BYTES * HEIGHT as usize)?;
+        self.wait_until_idle(spi, delay).await?;
         self.cmd_with_data(spi, Command::DataStartTransmission, buffer)
+            .await
     }
 
-    fn update_partial_frame(
+    async fn update_partial_frame(
         &mut self,
         _spi: &mut SPI,
         _delay: &mut DELAY,
@@ -161,85 +270,156 @@ where
         _y: u32,
         _width: u32,
         _height: u32,
-    ) -> Result<(), <SPI>::Error> {
+    ) -> Result<(), EpdError<SPI::Error>> {
         unimplemented!()
     }
 
-    fn display_frame(&mut self, spi: &mut SPI, delay: &mut DELAY) -> Result<(), <SPI>::Error> {
-        self.command(spi, Command::PowerOn)?;
-        self.wait_busy_low(delay);
-
-        self.cmd_with_data(spi, Command::DataFresh, &[0x00])?;
-        self.wait_busy_low(delay);
+    async fn display_frame(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.command(spi, Command::PowerOn).await?;
+        self.wait_busy_low(delay).await?;
 
-        self.cmd_with_data(spi, Command::PowerOff, &[0x00])?;
-        self.wait_busy_low(delay);
+        self.cmd_with_data(spi, Command::DataFresh, &[0x00]).await?;
+        self.wait_busy_low(delay).await?;
 
-        Ok(())
+        self.cmd_with_data(spi, Command::PowerOff, &[0x00]).await?;
+        self.wait_busy_low(delay).await
     }
 
-    fn update_and_display_frame(
+    async fn update_and_display_frame(
         &mut self,
         spi: &mut SPI,
         buffer: &[u8],
         delay: &mut DELAY,
-    ) -> Result<(), <SPI>::Error> {
-        self.update_frame(spi, buffer, delay)?;
-        self.display_frame(spi, delay)
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.update_frame(spi, buffer, delay).await?;
+        self.display_frame(spi, delay).await
     }
 
-    fn clear_frame(&mut self, spi: &mut SPI, delay: &mut DELAY) -> Result<(), <SPI>::Error> {
+    async fn clear_frame(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>> {
         let bg = OctColor::colors_byte(self.color, self.color);
 
-        self.wait_busy_low(delay);
-        self.command(spi, Command::DataStartTransmission)?;
-        self.interface.data_x_times(spi, bg, WIDTH * HEIGHT / 2)?;
+        self.wait_busy_low(delay).await?;
+        self.command(spi, Command::DataStartTransmission).await?;
+        self.interface
+            .data_x_times(spi, bg, WIDTH * HEIGHT / 2)
+            .await?;
 
-        self.display_frame(spi, delay)
+        self.display_frame(spi, delay).await
     }
 
-    fn set_lut(
+    async fn set_lut(
         &mut self,
         _spi: &mut SPI,
         _delay: &mut DELAY,
         _refresh_rate: Option<crate::traits::RefreshLut>,
-    ) -> Result<(), <SPI>::Error> {
+    ) -> Result<(), EpdError<SPI::Error>> {
         unimplemented!()
     }
 
-    fn wait_until_idle(&mut self, _spi: &mut SPI, delay: &mut DELAY) -> Result<(), <SPI>::Error> {
-        self.wait_busy_low(delay);
-        Ok(())
+    fn interface_config_mut(&mut self) -> &mut InterfaceConfig {
+        self.interface.config_mut()
+    }
+
+    fn release(self) -> (BUSY, DC, RST) {
+        self.interface.release()
+    }
+
+    async fn wait_until_idle(
+        &mut self,
+        _spi: &mut SPI,
+        delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.wait_busy_low(delay).await
     }
 }
 
+#[maybe_async_cfg::maybe(
+    sync(keep_self),
+    async(
+        feature = "async",
+        idents(
+            Epd7in3f,
+            DisplayInterface,
+            WaveshareDisplay,
+            InternalWiAdditions,
+            SpiDevice(async = "AsyncSpiDevice"),
+            BusyPin(async = "AsyncBusyPin"),
+            DelayNs(async = "AsyncDelayNs")
+        )
+    )
+)]
 impl<SPI, BUSY, DC, RST, DELAY> Epd7in3f<SPI, BUSY, DC, RST, DELAY>
 where
     SPI: SpiDevice,
-    BUSY: InputPin,
+    BUSY: BusyPin,
     DC: OutputPin,
     RST: OutputPin,
     DELAY: DelayNs,
 {
-    fn command(&mut self, spi: &mut SPI, command: Command) -> Result<(), SPI::Error> {
-        self.interface.cmd(spi, command)
+    async fn command(
+        &mut self,
+        spi: &mut SPI,
+        command: Command,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.interface.cmd(spi, command).await
     }
 
-    fn cmd_with_data(
+    async fn cmd_with_data(
         &mut self,
         spi: &mut SPI,
         command: Command,
         data: &[u8],
-    ) -> Result<(), SPI::Error> {
-        self.interface.cmd_with_data(spi, command, data)
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.interface.cmd_with_data(spi, command, data).await
     }
 
-    fn wait_busy_low(&mut self, delay: &mut DELAY) {
-        self.interface.wait_until_idle(delay, true);
+    async fn wait_busy_low(&mut self, delay: &mut DELAY) -> Result<(), EpdError<SPI::Error>> {
+        self.interface.wait_until_idle(delay, true).await
+    }
+
+    /// Sends the frame band by band, rendered in `band` by `render`
+    ///
+    /// `band` holds as many lines of `LINE_BYTES` bytes as possible, at least one or
+    /// [`EpdError::BufferSize`] is returned. For each band, `render` is called with the area of the
+    /// frame it covers and the pixels to fill, two per byte as in [`OctColor::colors_byte`]. The
+    /// frame is shown with [`display_frame`](WaveshareDisplay::display_frame).
+    pub async fn update_frame_banded<F>(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+        band: &mut [u8],
+        render: F,
+    ) -> Result<(), EpdError<SPI::Error>>
+    where
+        F: FnMut(Rect, &mut [u8]),
+    {
+        self.wait_until_idle(spi, delay).await?;
+        self.command(spi, Command::DataStartTransmission).await?;
+        self.interface
+            .data_banded(
+                spi,
+                Rect::new(0, 0, WIDTH, HEIGHT),
+                LINE_BYTES,
+                band,
+                render,
+            )
+            .await
     }
 
     /// Show 7 blocks of color, used for quick testing
-    pub fn show_7block(&mut self, spi: &mut SPI, delay: &mut DELAY) -> Result<(), SPI::Error> {
+    pub async fn show_7block(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>> {
         let color_7 = [
             OctColor::Black,
             OctColor::White,
@@ -251,12 +431,13 @@ where
             OctColor::White,
         ];
 
-        self.command(spi, Command::DataStartTransmission)?;
+        self.command(spi, Command::DataStartTransmission).await?;
         for _ in 0..240 {
             for color in color_7.iter().take(4) {
                 for _ in 0..100 {
                     self.interface
-                        .data(spi, &[OctColor::colors_byte(*color, *color)])?;
+                        .data(spi, &[OctColor::colors_byte(*color, *color)])
+                        .await?;
                 }
             }
         }
@@ -265,11 +446,45 @@ where
             for color in color_7.iter().skip(4) {
                 for _ in 0..100 {
                     self.interface
-                        .data(spi, &[OctColor::colors_byte(*color, *color)])?;
+                        .data(spi, &[OctColor::colors_byte(*color, *color)])
+                        .await?;
                 }
             }
         }
 
-        self.display_frame(spi, delay)
+        self.display_frame(spi, delay).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn epd_size() {
+        assert_eq!(WIDTH, 800);
+        assert_eq!(HEIGHT, 480);
+        assert_eq!(LINE_BYTES * HEIGHT as usize, 192_000);
+        assert_eq!(DEFAULT_BACKGROUND_COLOR, OctColor::White);
+    }
+
+    #[cfg(feature = "graphics")]
+    #[test]
+    fn band_display() {
+        use crate::graphics::VarDisplay;
+        use embedded_graphics_core::{prelude::*, Pixel};
+
+        let mut band = [0x11; LINE_BYTES * 2];
+        let mut display = VarDisplay::<OctColor>::new(WIDTH, 2, &mut band, false).unwrap();
+        display.set_pixel(Pixel(Point::new(1, 0), OctColor::Red));
+        display.set_pixel(Pixel(Point::new(WIDTH as i32 - 2, 1), OctColor::Black));
+        assert_eq!(
+            display.buffer()[0],
+            OctColor::colors_byte(OctColor::White, OctColor::Red)
+        );
+        assert_eq!(
+            display.buffer()[2 * LINE_BYTES - 1],
+            OctColor::colors_byte(OctColor::Black, OctColor::White)
+        );
     }
 }
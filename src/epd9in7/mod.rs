@@ -0,0 +1,297 @@
+//! A driver for the Waveshare 9.7" e-Paper HAT (1200x825, 16 gray levels)
+//!
+//! The panel is driven by an [IT8951](crate::it8951) controller which keeps the frame in its own
+//! memory. The full frame needs 483KiB at 4 bits per pixel, so it can be rendered and sent band
+//! by band with [`update_frame_banded`](Epd9in7::update_frame_banded) from a buffer of a few
+//! lines.
+//!
+//! Refreshing the whole panel takes a while, small regions (a clock, a status line) are faster
+//! to update with [`update_partial_frame_banded`](Epd9in7::update_partial_frame_banded) or
+//! [`update_partial_frame`](Epd9in7::update_partial_frame) followed by
+//! [`display_partial_frame`](Epd9in7::display_partial_frame): only the region is sent and
+//! refreshed.
+//!
+//! Pixels are 4 bits gray levels, `0x0` for black up to `0xf` for white, the first pixel of a
+//! byte in its upper bits.
+//!
+//! # References
+//!
+//! - [Wiki](https://www.waveshare.com/wiki/9.7inch_e-Paper_HAT)
+//! - [Waveshare C driver](https://github.com/waveshare/IT8951-ePaper)
+//!
+//!```rust, no_run
+//!# use embedded_hal_mock::eh1::*;
+//!# fn main() -> Result<(), epd_waveshare::error::EpdError<embedded_hal::spi::ErrorKind>> {
+//!use epd_waveshare::{epd9in7::*, it8951::WaveformMode, rect::Rect};
+//!#
+//!# let expectations = [];
+//!# let mut spi = spi::Mock::new(&expectations);
+//!# let expectations = [];
+//!# let busy_in = digital::Mock::new(&expectations);
+//!# let rst = digital::Mock::new(&expectations);
+//!# let mut delay = delay::NoopDelay::new();
+//!
+//!// the VCOM voltage is printed on the ribbon cable of the panel
+//!let mut epd = Epd9in7::new(&mut spi, busy_in, rst, &mut delay, 1500, None)?;
+//!
+//!// 16 lines at a time: a horizontal gradient
+//!let mut band = [0; 16 * LINE_BYTES];
+//!epd.update_frame_banded(&mut spi, &mut delay, &mut band, |_area, pixels| {
+//!    for line in pixels.chunks_mut(LINE_BYTES) {
+//!        for (x, byte) in line.iter_mut().enumerate() {
+//!            let gray = (x * 16 / LINE_BYTES) as u8;
+//!            *byte = gray << 4 | gray;
+//!        }
+//!    }
+//!})?;
+//!epd.display_frame(&mut spi, &mut delay, WaveformMode::Gc16)?;
+//!
+//!// a fast update of a black rectangle
+//!let area = Rect::new(100, 100, 64, 32);
+//!let pixels = [0; 64 * 32 / 2];
+//!epd.update_partial_frame(&mut spi, &mut delay, &pixels, area)?;
+//!epd.display_partial_frame(&mut spi, &mut delay, area, WaveformMode::Du)?;
+//!# Ok(())
+//!# }
+//!```
+
+use crate::color::Gray16;
+use crate::error::EpdError;
+#[cfg(feature = "async")]
+use crate::it8951::It8951Async;
+use crate::it8951::{It8951, PixelFormat, WaveformMode};
+use crate::rect::Rect;
+use embedded_hal::{delay::*, digital::*, spi::SpiDevice};
+#[cfg(feature = "async")]
+use embedded_hal_async::{delay::DelayNs as AsyncDelayNs, spi::SpiDevice as AsyncSpiDevice};
+
+/// Width of the display
+pub const WIDTH: u32 = 1200;
+/// Height of the display
+pub const HEIGHT: u32 = 825;
+/// Number of bytes of a line of pixels
+pub const LINE_BYTES: usize = WIDTH as usize / 2;
+/// Default gray level, white
+pub const DEFAULT_BACKGROUND_GRAY: u8 = 0xf;
+/// Default Background Color
+pub const DEFAULT_BACKGROUND_COLOR: Gray16 = Gray16::WHITE;
+
+/// Full size buffer for use with the 9in7 EPD, only for hosts with enough memory
+///
+/// On MCUs, a `VarDisplay<Gray16>` of a few lines can be used to render the bands of
+/// [`update_frame_banded`](Epd9in7::update_frame_banded) instead.
+#[cfg(feature = "graphics")]
+pub type Display9in7 = crate::graphics::Display<
+    WIDTH,
+    HEIGHT,
+    false,
+    { crate::graphics::display_len::<Gray16>(WIDTH, HEIGHT) },
+    Gray16,
+>;
+
+const FULL_RECT: Rect = Rect::new(0, 0, WIDTH, HEIGHT);
+
+#[maybe_async_cfg::maybe(
+    sync(keep_self),
+    async(
+        feature = "async",
+        idents(
+            Epd9in7,
+            It8951,
+            SpiDevice(async = "AsyncSpiDevice"),
+            DelayNs(async = "AsyncDelayNs")
+        )
+    )
+)]
+/// Epd9in7 driver
+pub struct Epd9in7<SPI, BUSY, RST, DELAY> {
+    it8951: It8951<SPI, BUSY, RST, DELAY>,
+}
+
+#[maybe_async_cfg::maybe(
+    sync(keep_self),
+    async(
+        feature = "async",
+        idents(
+            Epd9in7,
+            It8951,
+            SpiDevice(async = "AsyncSpiDevice"),
+            DelayNs(async = "AsyncDelayNs")
+        )
+    )
+)]
+impl<SPI, BUSY, RST, DELAY> Epd9in7<SPI, BUSY, RST, DELAY>
+where
+    SPI: SpiDevice,
+    BUSY: InputPin,
+    RST: OutputPin,
+    DELAY: DelayNs,
+{
+    /// Initializes the controller and sets the VCOM voltage of the panel, in mV (1500 for
+    /// -1.50V)
+    pub async fn new(
+        spi: &mut SPI,
+        busy: BUSY,
+        rst: RST,
+        delay: &mut DELAY,
+        vcom: u16,
+        delay_us: Option<u32>,
+    ) -> Result<Self, EpdError<SPI::Error>> {
+        let mut it8951 = It8951::new(spi, busy, rst, delay, delay_us).await?;
+        it8951.set_vcom(spi, delay, vcom).await?;
+        Ok(Epd9in7 { it8951 })
+    }
+
+    /// The underlying controller, for the features not covered by this driver
+    pub fn controller(&mut self) -> &mut It8951<SPI, BUSY, RST, DELAY> {
+        &mut self.it8951
+    }
+
+    /// Wakes the controller up from sleep
+    pub async fn wake_up(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.it8951.wake_up(spi, delay).await
+    }
+
+    /// Puts the controller to sleep, the frame memory is kept
+    pub async fn sleep(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.it8951.wait_display_ready(spi, delay).await?;
+        self.it8951.sleep(spi, delay).await
+    }
+
+    /// Sends a whole frame of `HEIGHT` x `LINE_BYTES` bytes
+    pub async fn update_frame(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+        buffer: &[u8],
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.update_partial_frame(spi, delay, buffer, FULL_RECT)
+            .await
+    }
+
+    /// Sends the frame band by band, rendered in `band` by `render`
+    ///
+    /// `band` holds as many lines of `LINE_BYTES` bytes as possible, at least one. For each band,
+    /// `render` is called with the area of the frame it covers and the pixels to fill.
+    pub async fn update_frame_banded<F>(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+        band: &mut [u8],
+        render: F,
+    ) -> Result<(), EpdError<SPI::Error>>
+    where
+        F: FnMut(Rect, &mut [u8]),
+    {
+        self.it8951
+            .load_image_banded(spi, delay, PixelFormat::Bpp4, FULL_RECT, band, render)
+            .await
+    }
+
+    /// Sends the pixels of an area of the frame
+    ///
+    /// The lines of `area` must be whole 16 bits words, i.e. its `x` and `w` must be multiples
+    /// of 4, see [`aligned_area`](PixelFormat::aligned_area).
+    pub async fn update_partial_frame(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+        buffer: &[u8],
+        area: Rect,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.it8951
+            .load_image_area(spi, delay, PixelFormat::Bpp4, area, buffer)
+            .await
+    }
+
+    /// Sends an area of the frame band by band, rendered in `band` by `render`
+    ///
+    /// `area` is grown to whole 16 bits words, `render` is called with the parts of the grown
+    /// area and must fill all their pixels.
+    pub async fn update_partial_frame_banded<F>(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+        area: Rect,
+        band: &mut [u8],
+        render: F,
+    ) -> Result<(), EpdError<SPI::Error>>
+    where
+        F: FnMut(Rect, &mut [u8]),
+    {
+        let area = PixelFormat::Bpp4.aligned_area(area.intersect(FULL_RECT));
+        self.it8951
+            .load_image_banded(spi, delay, PixelFormat::Bpp4, area, band, render)
+            .await
+    }
+
+    /// Sends a whole frame and refreshes the panel with the GC16 waveform (16 gray levels)
+    pub async fn update_and_display_frame(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+        buffer: &[u8],
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.update_frame(spi, delay, buffer).await?;
+        self.display_frame(spi, delay, WaveformMode::Gc16).await
+    }
+
+    /// Refreshes the whole panel with a waveform `mode`
+    pub async fn display_frame(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+        mode: WaveformMode,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.it8951.display_area(spi, delay, FULL_RECT, mode).await
+    }
+
+    /// Refreshes an area of the panel with a waveform `mode`
+    pub async fn display_partial_frame(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+        area: Rect,
+        mode: WaveformMode,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.it8951.display_area(spi, delay, area, mode).await
+    }
+
+    /// Clears the frame memory to white and the panel with the INIT waveform
+    pub async fn clear_frame(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        let white = [DEFAULT_BACKGROUND_GRAY << 4 | DEFAULT_BACKGROUND_GRAY; LINE_BYTES];
+        self.it8951
+            .load_image_start(spi, delay, PixelFormat::Bpp4, FULL_RECT)
+            .await?;
+        for _ in 0..HEIGHT {
+            self.it8951.load_image_data(spi, delay, &white).await?;
+        }
+        self.it8951.load_image_end(spi, delay).await?;
+        self.display_frame(spi, delay, WaveformMode::Init).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn epd_size() {
+        assert_eq!(DEFAULT_BACKGROUND_COLOR.luma(), DEFAULT_BACKGROUND_GRAY);
+        assert_eq!(WIDTH, 1200);
+        assert_eq!(HEIGHT, 825);
+        assert_eq!(LINE_BYTES * HEIGHT as usize, 495_000);
+    }
+}
@@ -0,0 +1,329 @@
+//! A driver bundled with its display buffer
+//!
+//! Most applications keep a driver and the matching display buffer side by side and write the
+//! same glue to send the buffer to the driver. [`EpdDisplay`] owns both: it can be drawn on like
+//! the display buffer and shows what was drawn with [`flush`](EpdDisplay::flush), or only a part
+//! of it with [`flush_partial`](EpdDisplay::flush_partial). It also keeps the bounding box of
+//! what was drawn since the last flush, sent alone by [`flush_dirty`](EpdDisplay::flush_dirty):
+//! a clock or a sensor readout only sends the rows which changed.
+//!
+//!```rust, no_run
+//!# use embedded_hal_mock::eh1::*;
+//!# fn main() -> Result<(), epd_waveshare::error::EpdError<embedded_hal::spi::ErrorKind>> {
+//!use embedded_graphics::{prelude::*, primitives::{Line, PrimitiveStyle, Rectangle}};
+//!use epd_waveshare::{epd1in54::*, epd_display::EpdDisplay, prelude::*};
+//!#
+//!# let expectations = [];
+//!# let mut spi = spi::Mock::new(&expectations);
+//!# let expectations = [];
+//!# let busy_in = digital::Mock::new(&expectations);
+//!# let dc = digital::Mock::new(&expectations);
+//!# let rst = digital::Mock::new(&expectations);
+//!# let mut delay = delay::NoopDelay::new();
+//!
+//!let epd = Epd1in54::new(&mut spi, busy_in, dc, rst, &mut delay, None)?;
+//!let mut display = EpdDisplay::new(epd, Display1in54::default());
+//!
+//!let _ = Line::new(Point::new(0, 120), Point::new(0, 199))
+//!    .into_styled(PrimitiveStyle::with_stroke(Color::Black, 1))
+//!    .draw(&mut display);
+//!display.flush(&mut spi, &mut delay)?;
+//!
+//!// only the rows 100 to 149 are sent
+//!display.epd_mut().set_lut(&mut spi, &mut delay, Some(RefreshLut::Quick))?;
+//!let _ = Line::new(Point::new(10, 120), Point::new(10, 149))
+//!    .into_styled(PrimitiveStyle::with_stroke(Color::Black, 1))
+//!    .draw(&mut display);
+//!let area = Rectangle::new(Point::new(0, 100), Size::new(200, 50));
+//!display.flush_partial(&mut spi, &mut delay, area)?;
+//!
+//!// only the rows 160 to 169 are sent
+//!let _ = Line::new(Point::new(20, 160), Point::new(40, 169))
+//!    .into_styled(PrimitiveStyle::with_stroke(Color::Black, 1))
+//!    .draw(&mut display);
+//!display.flush_dirty(&mut spi, &mut delay)?;
+//!# Ok(())
+//!# }
+//!```
+
+use crate::color::ColorType;
+use crate::error::EpdError;
+use crate::graphics::{buffer_rows, DisplayBuffer};
+use crate::pin::BusyPin;
+use crate::traits::WaveshareDisplay;
+use embedded_graphics_core::{prelude::*, primitives::Rectangle};
+use embedded_hal::{delay::*, digital::*, spi::SpiDevice};
+
+/// A driver and its display buffer
+///
+/// `D` is a [`Display`](crate::graphics::Display) or [`VarDisplay`](crate::graphics::VarDisplay)
+/// of the size of the panel driven by `EPD`.
+pub struct EpdDisplay<EPD, D> {
+    epd: EPD,
+    display: D,
+    /// Bounding box of the pixels drawn since the last flush
+    dirty: Option<Rectangle>,
+}
+
+impl<EPD, D> EpdDisplay<EPD, D>
+where
+    D: DisplayBuffer,
+    D::Color: ColorType,
+{
+    /// Bundles `epd` with `display`
+    ///
+    /// The whole display counts as drawn, so that the first [`flush_dirty`](Self::flush_dirty)
+    /// sends all of it.
+    pub fn new(epd: EPD, display: D) -> Self {
+        let dirty = Some(display.bounding_box());
+        EpdDisplay {
+            epd,
+            display,
+            dirty,
+        }
+    }
+
+    /// Returns the driver
+    pub fn epd(&self) -> &EPD {
+        &self.epd
+    }
+
+    /// Returns the driver, e.g. to change the refresh LUT or to let it sleep
+    pub fn epd_mut(&mut self) -> &mut EPD {
+        &mut self.epd
+    }
+
+    /// Returns the display buffer
+    pub fn display(&self) -> &D {
+        &self.display
+    }
+
+    /// Returns the display buffer, e.g. to change its rotation
+    ///
+    /// What is drawn through it isn't tracked, so the whole display counts as drawn.
+    pub fn display_mut(&mut self) -> &mut D {
+        self.dirty = Some(self.display.bounding_box());
+        &mut self.display
+    }
+
+    /// Bounding box of the pixels drawn since the last flush, in the coordinates used for
+    /// drawing
+    pub fn dirty_area(&self) -> Option<Rectangle> {
+        self.dirty
+    }
+
+    /// Adds `area` to the pixels drawn since the last flush
+    fn mark_dirty(&mut self, area: Rectangle) {
+        self.dirty = envelope(self.dirty, area.intersection(&self.display.bounding_box()));
+    }
+
+    /// Splits the bundle in the driver and the display buffer
+    pub fn into_parts(self) -> (EPD, D) {
+        (self.epd, self.display)
+    }
+
+    /// Sends the whole buffer and refreshes the panel with the selected LUT
+    pub fn flush<SPI, BUSY, DC, RST, DELAY>(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>>
+    where
+        SPI: SpiDevice,
+        BUSY: BusyPin,
+        DC: OutputPin,
+        RST: OutputPin,
+        DELAY: DelayNs,
+        EPD: WaveshareDisplay<SPI, BUSY, DC, RST, DELAY>,
+    {
+        self.epd
+            .update_and_display_frame(spi, self.display.buffer(), delay)?;
+        self.dirty = None;
+        Ok(())
+    }
+
+    /// Sends the rows of the buffer covered by `area` and refreshes the panel with the selected
+    /// LUT
+    ///
+    /// `area` is given in the coordinates used for drawing, with the rotation of the display.
+    /// Whole rows of the buffer are sent; when the display is rotated by 90 or 270 degrees, the
+    /// area isn't made of contiguous rows and the whole buffer is sent instead, as with split
+    /// buffers (e.g. of `TriColor`).
+    pub fn flush_partial<SPI, BUSY, DC, RST, DELAY>(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+        area: Rectangle,
+    ) -> Result<(), EpdError<SPI::Error>>
+    where
+        SPI: SpiDevice,
+        BUSY: BusyPin,
+        DC: OutputPin,
+        RST: OutputPin,
+        DELAY: DelayNs,
+        EPD: WaveshareDisplay<SPI, BUSY, DC, RST, DELAY>,
+    {
+        let area = area.intersection(&self.display.bounding_box());
+        let (top, bottom) = match area.bottom_right() {
+            Some(bottom_right) => (area.top_left.y as u32, bottom_right.y as u32 + 1),
+            None => return Ok(()),
+        };
+        let width = self.display.buffer_width();
+        let height = self.display.buffer_height();
+        let rows = match D::Color::BUFFER_COUNT {
+            1 => buffer_rows(self.display.rotation(), height, top, bottom),
+            _ => None,
+        };
+        match rows {
+            Some((top, bottom)) => {
+                let line_bytes = self.display.buffer().len() / height as usize;
+                let band =
+                    &self.display.buffer()[top as usize * line_bytes..bottom as usize * line_bytes];
+                self.epd
+                    .update_partial_frame(spi, delay, band, 0, top, width, bottom - top)?;
+                self.epd.display_frame(spi, delay)?;
+                // what was drawn elsewhere is still to be sent
+                if self.dirty.is_some_and(|dirty| contains(&area, &dirty)) {
+                    self.dirty = None;
+                }
+                Ok(())
+            }
+            None => self.flush(spi, delay),
+        }
+    }
+
+    /// Sends the rows of the buffer drawn since the last flush and refreshes the panel with the
+    /// selected LUT, see [`flush_partial`](Self::flush_partial)
+    ///
+    /// Nothing is sent when nothing was drawn.
+    pub fn flush_dirty<SPI, BUSY, DC, RST, DELAY>(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>>
+    where
+        SPI: SpiDevice,
+        BUSY: BusyPin,
+        DC: OutputPin,
+        RST: OutputPin,
+        DELAY: DelayNs,
+        EPD: WaveshareDisplay<SPI, BUSY, DC, RST, DELAY>,
+    {
+        match self.dirty {
+            Some(area) => self.flush_partial(spi, delay, area),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Smallest rectangle containing `dirty` and `area`
+fn envelope(dirty: Option<Rectangle>, area: Rectangle) -> Option<Rectangle> {
+    let bottom_right = match area.bottom_right() {
+        Some(bottom_right) => bottom_right,
+        None => return dirty,
+    };
+    match dirty.and_then(|dirty| Some((dirty.top_left, dirty.bottom_right()?))) {
+        Some((top_left, dirty_bottom_right)) => Some(Rectangle::with_corners(
+            top_left.component_min(area.top_left),
+            dirty_bottom_right.component_max(bottom_right),
+        )),
+        None => Some(area),
+    }
+}
+
+/// Whether `outer` covers all of `inner`
+fn contains(outer: &Rectangle, inner: &Rectangle) -> bool {
+    outer.intersection(inner) == *inner
+}
+
+impl<EPD, D: DisplayBuffer> Dimensions for EpdDisplay<EPD, D> {
+    fn bounding_box(&self) -> Rectangle {
+        self.display.bounding_box()
+    }
+}
+
+/// For use with embedded_grahics
+impl<EPD, D> DrawTarget for EpdDisplay<EPD, D>
+where
+    D: DisplayBuffer,
+    D::Color: ColorType,
+{
+    type Color = D::Color;
+    type Error = D::Error;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        let mut corners: Option<(Point, Point)> = None;
+        let result = self
+            .display
+            .draw_iter(pixels.into_iter().inspect(|Pixel(point, _)| {
+                corners = Some(match corners {
+                    Some((min, max)) => (min.component_min(*point), max.component_max(*point)),
+                    None => (*point, *point),
+                });
+            }));
+        if let Some((min, max)) = corners {
+            self.mark_dirty(Rectangle::with_corners(min, max));
+        }
+        result
+    }
+
+    fn fill_contiguous<I>(&mut self, area: &Rectangle, colors: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Self::Color>,
+    {
+        self.mark_dirty(*area);
+        self.display.fill_contiguous(area, colors)
+    }
+
+    fn fill_solid(&mut self, area: &Rectangle, color: Self::Color) -> Result<(), Self::Error> {
+        self.mark_dirty(*area);
+        self.display.fill_solid(area, color)
+    }
+
+    fn clear(&mut self, color: Self::Color) -> Result<(), Self::Error> {
+        self.dirty = Some(self.display.bounding_box());
+        self.display.clear(color)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::Color;
+    use crate::graphics::Display;
+
+    #[test]
+    fn dirty_area() {
+        let mut display = EpdDisplay::new((), Display::<16, 8, false, 16, Color>::default());
+        display.dirty = None;
+
+        let _ = Pixel(Point::new(3, 2), Color::Black).draw(&mut display);
+        let _ = Pixel(Point::new(1, 5), Color::Black).draw(&mut display);
+        assert_eq!(
+            display.dirty_area(),
+            Some(Rectangle::with_corners(Point::new(1, 2), Point::new(3, 5)))
+        );
+
+        // clipped to the display
+        let _ = display.fill_solid(
+            &Rectangle::new(Point::new(10, 6), Size::new(10, 10)),
+            Color::Black,
+        );
+        assert_eq!(
+            display.dirty_area(),
+            Some(Rectangle::with_corners(Point::new(1, 2), Point::new(15, 7)))
+        );
+
+        display.dirty = None;
+        let _ = display.fill_solid(
+            &Rectangle::new(Point::new(20, 0), Size::new(4, 4)),
+            Color::Black,
+        );
+        assert_eq!(display.dirty_area(), None);
+        let _ = display.display_mut();
+        assert_eq!(display.dirty_area(), Some(display.bounding_box()));
+    }
+}
@@ -0,0 +1,63 @@
+//! Errors of the drivers
+
+use embedded_hal::digital;
+
+/// Error of a driver
+///
+/// The errors of the DC, RST and BUSY pins, whose types differ, are reported by their
+/// [`ErrorKind`](digital::ErrorKind).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum EpdError<SpiError, PinError = digital::ErrorKind> {
+    /// Error of the SPI bus, or of the [`ParallelBus`](crate::parallel::ParallelBus) of the panels
+    /// without controller
+    Spi(SpiError),
+    /// Error of the DC, RST or BUSY pin
+    Pin(PinError),
+    /// The frame doesn't have the length expected for the panel or the window it is sent to, or
+    /// the buffer of a banded update doesn't hold a line
+    BufferSize {
+        /// Length in bytes the frame should have
+        expected: usize,
+        /// Length in bytes of the frame given
+        actual: usize,
+    },
+    /// The controller stayed busy longer than the busy timeout (see
+    /// [`set_busy_timeout`](crate::traits::WaveshareDisplay::set_busy_timeout)): it may be
+    /// disconnected or wedged
+    BusyTimeout,
+    /// The operation isn't supported by the panel or its controller
+    Unsupported,
+}
+
+impl<SpiError> EpdError<SpiError> {
+    /// Wraps the error of a pin
+    pub(crate) fn pin<E: digital::Error>(error: E) -> Self {
+        EpdError::Pin(error.kind())
+    }
+
+    /// Checks that `buffer` is `expected` bytes long
+    pub(crate) fn check_buffer(buffer: &[u8], expected: usize) -> Result<(), Self> {
+        match buffer.len() {
+            actual if actual == expected => Ok(()),
+            actual => Err(EpdError::BufferSize { expected, actual }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn buffer_size() {
+        assert_eq!(EpdError::<()>::check_buffer(&[0; 4], 4), Ok(()));
+        assert_eq!(
+            EpdError::<()>::check_buffer(&[0; 3], 4),
+            Err(EpdError::BufferSize {
+                expected: 4,
+                actual: 3
+            })
+        );
+    }
+}
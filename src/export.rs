@@ -0,0 +1,222 @@
+//! Export of the display buffers as images, for screenshot-based tests and documentation
+//!
+//! [`write_pbm`] writes a black and white PBM image, the pixels of the buffer being split as in
+//! the [`preview`](crate::preview). [`write_png`] writes a PNG image with the colors of the
+//! buffer, e.g. the red of a `TriColor` display or the 7 colors of an `OctColor` one. The images
+//! are in the drawing coordinates, with the rotation of the display.
+//!
+//!```rust, no_run
+//!use embedded_graphics::{prelude::*, primitives::{Circle, PrimitiveStyle}};
+//!use epd_waveshare::{color::TriColor, epd2in9b_v4::Display2in9b, export};
+//!
+//!let mut display = Display2in9b::default();
+//!display.clear(TriColor::White).ok();
+//!let _ = Circle::new(Point::new(10, 10), 60)
+//!    .into_styled(PrimitiveStyle::with_fill(TriColor::Chromatic))
+//!    .draw(&mut display);
+//!
+//!export::save_png(&display, "circle.png")?;
+//!# Ok::<(), std::io::Error>(())
+//!```
+
+use crate::preview::PreviewColor;
+use embedded_graphics_core::{
+    image::GetPixel,
+    pixelcolor::{Rgb888, RgbColor},
+    prelude::*,
+};
+use std::{
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::Path,
+    vec::Vec,
+};
+
+/// Writes `display` as a binary PBM image, dark pixels in black
+pub fn write_pbm<D, W>(display: &D, out: &mut W) -> io::Result<()>
+where
+    D: GetPixel + Dimensions,
+    D::Color: PreviewColor,
+    W: Write,
+{
+    let area = display.bounding_box();
+    write!(out, "P4\n{} {}\n", area.size.width, area.size.height)?;
+    let mut row = Vec::with_capacity((area.size.width as usize + 7) / 8);
+    for y in 0..area.size.height as i32 {
+        row.clear();
+        row.resize((area.size.width as usize + 7) / 8, 0u8);
+        for x in 0..area.size.width as i32 {
+            let dark = display
+                .pixel(area.top_left + Point::new(x, y))
+                .is_some_and(|color| color.is_dark());
+            if dark {
+                row[x as usize / 8] |= 0x80 >> (x % 8);
+            }
+        }
+        out.write_all(&row)?;
+    }
+    Ok(())
+}
+
+/// Writes `display` as a RGB PNG image
+///
+/// The image data is stored without compression: the files are larger than those of an image
+/// editor, but need no compression library.
+pub fn write_png<D, W>(display: &D, out: &mut W) -> io::Result<()>
+where
+    D: GetPixel + Dimensions,
+    D::Color: Into<Rgb888>,
+    W: Write,
+{
+    let area = display.bounding_box();
+    let (width, height) = (area.size.width, area.size.height);
+
+    // every row starts with its filter type, 0 for none
+    let mut pixels = Vec::with_capacity(height as usize * (1 + 3 * width as usize));
+    for y in 0..height as i32 {
+        pixels.push(0);
+        for x in 0..width as i32 {
+            let rgb = display
+                .pixel(area.top_left + Point::new(x, y))
+                .map_or(Rgb888::WHITE, Into::into);
+            pixels.extend_from_slice(&[rgb.r(), rgb.g(), rgb.b()]);
+        }
+    }
+
+    out.write_all(b"\x89PNG\r\n\x1a\n")?;
+    let mut header = [0; 13];
+    header[0..4].copy_from_slice(&width.to_be_bytes());
+    header[4..8].copy_from_slice(&height.to_be_bytes());
+    // 8 bits per channel, RGB, deflate, no filter, no interlace
+    header[8..].copy_from_slice(&[8, 2, 0, 0, 0]);
+    write_chunk(out, b"IHDR", &header)?;
+    write_chunk(out, b"IDAT", &zlib_stored(&pixels))?;
+    write_chunk(out, b"IEND", &[])
+}
+
+/// Saves `display` as a PBM image in the file at `path`
+pub fn save_pbm<D>(display: &D, path: impl AsRef<Path>) -> io::Result<()>
+where
+    D: GetPixel + Dimensions,
+    D::Color: PreviewColor,
+{
+    let mut out = BufWriter::new(File::create(path)?);
+    write_pbm(display, &mut out)?;
+    out.flush()
+}
+
+/// Saves `display` as a PNG image in the file at `path`
+pub fn save_png<D>(display: &D, path: impl AsRef<Path>) -> io::Result<()>
+where
+    D: GetPixel + Dimensions,
+    D::Color: Into<Rgb888>,
+{
+    let mut out = BufWriter::new(File::create(path)?);
+    write_png(display, &mut out)?;
+    out.flush()
+}
+
+/// Writes a PNG chunk: its length, type, data and CRC
+fn write_chunk<W: Write>(out: &mut W, kind: &[u8; 4], data: &[u8]) -> io::Result<()> {
+    out.write_all(&(data.len() as u32).to_be_bytes())?;
+    out.write_all(kind)?;
+    out.write_all(data)?;
+    let crc = crc32(crc32(!0, kind), data);
+    out.write_all(&(!crc).to_be_bytes())
+}
+
+/// Updates the CRC-32 of the PNG chunks with `data`
+fn crc32(mut crc: u32, data: &[u8]) -> u32 {
+    for &byte in data {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 {
+                0xEDB8_8320 ^ (crc >> 1)
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    crc
+}
+
+/// Adler-32 checksum of a zlib stream
+fn adler32(data: &[u8]) -> u32 {
+    let (a, b) = data.iter().fold((1u32, 0u32), |(a, b), &byte| {
+        let a = (a + byte as u32) % 65521;
+        (a, (b + a) % 65521)
+    });
+    b << 16 | a
+}
+
+/// zlib stream of `data` in stored (uncompressed) deflate blocks
+fn zlib_stored(data: &[u8]) -> Vec<u8> {
+    const BLOCK: usize = 0xFFFF;
+    let mut stream = Vec::with_capacity(data.len() + 5 * (data.len() / BLOCK + 1) + 6);
+    // deflate with a 32K window, no dictionary
+    stream.extend_from_slice(&[0x78, 0x01]);
+    let mut blocks = data.chunks(BLOCK).peekable();
+    if blocks.peek().is_none() {
+        stream.extend_from_slice(&[0x01, 0x00, 0x00, 0xFF, 0xFF]);
+    }
+    while let Some(block) = blocks.next() {
+        let last = blocks.peek().is_none();
+        let len = block.len() as u16;
+        stream.push(last as u8);
+        stream.extend_from_slice(&len.to_le_bytes());
+        stream.extend_from_slice(&(!len).to_le_bytes());
+        stream.extend_from_slice(block);
+    }
+    stream.extend_from_slice(&adler32(data).to_be_bytes());
+    stream
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::{Color, TriColor};
+    use crate::graphics::{Display, VarDisplay};
+
+    #[test]
+    fn export_pbm() {
+        let mut display = Display::<10, 2, false, 4, Color>::default();
+        display.clear(Color::White).unwrap();
+        display.set_pixel(Pixel(Point::new(0, 0), Color::Black));
+        display.set_pixel(Pixel(Point::new(9, 1), Color::Black));
+
+        let mut image = Vec::new();
+        write_pbm(&display, &mut image).unwrap();
+        assert_eq!(image, b"P4\n10 2\n\x80\x00\x00\x40");
+    }
+
+    #[test]
+    fn export_png() {
+        let mut buffer = [0u8; 4];
+        let mut display = VarDisplay::<TriColor>::new(2, 1, &mut buffer, false).unwrap();
+        display.clear(TriColor::White).unwrap();
+        display.set_pixel(Pixel(Point::new(1, 0), TriColor::Chromatic));
+
+        let mut image = Vec::new();
+        write_png(&display, &mut image).unwrap();
+        assert_eq!(&image[..8], b"\x89PNG\r\n\x1a\n");
+        assert_eq!(&image[8..16], b"\x00\x00\x00\x0dIHDR");
+        assert_eq!(&image[16..29], &[0, 0, 0, 2, 0, 0, 0, 1, 8, 2, 0, 0, 0]);
+        let idat = &image[33..];
+        assert_eq!(&idat[..8], b"\x00\x00\x00\x12IDAT");
+        // zlib header, a single stored block of 7 bytes, the row, then the checksum
+        assert_eq!(
+            &idat[8..22],
+            &[0x78, 0x01, 0x01, 7, 0, !7, 0xFF, 0, 255, 255, 255, 255, 0, 0]
+        );
+        assert_eq!(
+            &image[image.len() - 12..],
+            b"\x00\x00\x00\x00IEND\xae\x42\x60\x82"
+        );
+    }
+
+    #[test]
+    fn checksums() {
+        assert_eq!(!crc32(!0, b"IEND"), 0xAE42_6082);
+        assert_eq!(adler32(b"Wikipedia"), 0x11E6_0398);
+    }
+}
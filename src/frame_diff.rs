@@ -0,0 +1,161 @@
+//! Sending only the rows of a frame which changed
+//!
+//! Most refreshes of a dashboard or a clock change a few rows of the frame. [`FrameDiff`] keeps
+//! a copy of the frame last sent, in a buffer given by the application, compares the next frame
+//! with it and only sends the band of rows in between the first and the last changed ones with
+//! [`update_partial_frame`](WaveshareDisplay::update_partial_frame). This spares SPI traffic on
+//! the large panels and, with a quick refresh LUT, the artifacts of the rows refreshed for
+//! nothing.
+//!
+//!```rust, no_run
+//!# use embedded_hal_mock::eh1::*;
+//!# fn main() -> Result<(), epd_waveshare::error::EpdError<embedded_hal::spi::ErrorKind>> {
+//!use epd_waveshare::{epd2in9_v2::*, frame_diff::FrameDiff, prelude::*};
+//!#
+//!# let expectations = [];
+//!# let mut spi = spi::Mock::new(&expectations);
+//!# let expectations = [];
+//!# let busy_in = digital::Mock::new(&expectations);
+//!# let dc = digital::Mock::new(&expectations);
+//!# let rst = digital::Mock::new(&expectations);
+//!# let mut delay = delay::NoopDelay::new();
+//!
+//!let mut epd = Epd2in9::new(&mut spi, busy_in, dc, rst, &mut delay, None)?;
+//!let mut previous = [0; WIDTH as usize / 8 * HEIGHT as usize];
+//!let mut diff = FrameDiff::new(&mut previous);
+//!
+//!let mut frame = [0xff; WIDTH as usize / 8 * HEIGHT as usize];
+//!// sent whole, nothing was sent before
+//!diff.update_and_display_frame(&mut epd, &mut spi, &frame, &mut delay)?;
+//!
+//!epd.set_lut(&mut spi, &mut delay, Some(RefreshLut::Quick))?;
+//!frame[20 * 16..30 * 16].fill(0x00);
+//!// only the rows 20 to 29 are sent
+//!diff.update_and_display_frame(&mut epd, &mut spi, &frame, &mut delay)?;
+//!# Ok(())
+//!# }
+//!```
+
+use crate::color::ColorType;
+use crate::error::EpdError;
+use crate::pin::BusyPin;
+use crate::traits::WaveshareDisplay;
+use embedded_hal::{delay::*, digital::*, spi::SpiDevice};
+
+/// Copy of the frame last sent, to send the rows of the next one which changed
+///
+/// The frames are those of the whole panel. Those of tricolor panels, with two planes, are sent
+/// whole when they changed.
+pub struct FrameDiff<'a> {
+    previous: &'a mut [u8],
+    /// Whether `previous` holds the frame in the RAM of the controller
+    valid: bool,
+}
+
+impl<'a> FrameDiff<'a> {
+    /// Compares the frames with a copy kept in `previous`, as large as a full frame
+    ///
+    /// The first frame is sent whole.
+    pub fn new(previous: &'a mut [u8]) -> Self {
+        FrameDiff {
+            previous,
+            valid: false,
+        }
+    }
+
+    /// Forgets the frame last sent, e.g. after the panel was cleared or the controller woke up
+    /// from deep sleep, so that the next one is sent whole
+    pub fn invalidate(&mut self) {
+        self.valid = false;
+    }
+
+    /// Sends the rows of `buffer` which changed since the last frame, and refreshes the panel
+    /// with the selected LUT
+    ///
+    /// Returns whether the panel was refreshed: nothing is sent when no row changed.
+    pub fn update_and_display_frame<EPD, SPI, BUSY, DC, RST, DELAY>(
+        &mut self,
+        epd: &mut EPD,
+        spi: &mut SPI,
+        buffer: &[u8],
+        delay: &mut DELAY,
+    ) -> Result<bool, EpdError<SPI::Error>>
+    where
+        EPD: WaveshareDisplay<SPI, BUSY, DC, RST, DELAY>,
+        EPD::DisplayColor: ColorType,
+        SPI: SpiDevice,
+        BUSY: BusyPin,
+        DC: OutputPin,
+        RST: OutputPin,
+        DELAY: DelayNs,
+    {
+        EpdError::check_buffer(buffer, self.previous.len())?;
+        let height = epd.height() as usize;
+        let rows = match (self.valid, EPD::DisplayColor::BUFFER_COUNT) {
+            (true, 1) => changed_rows(self.previous, buffer, buffer.len() / height),
+            _ => Some((0, height)),
+        };
+        let (top, bottom) = match rows {
+            Some(rows) => rows,
+            None => return Ok(false),
+        };
+
+        // the controller holds something unknown if the update fails
+        self.valid = false;
+        if (top, bottom) == (0, height) {
+            epd.update_and_display_frame(spi, buffer, delay)?;
+        } else {
+            let line_bytes = buffer.len() / height;
+            let band = &buffer[top * line_bytes..bottom * line_bytes];
+            epd.update_partial_frame(
+                spi,
+                delay,
+                band,
+                0,
+                top as u32,
+                epd.width(),
+                (bottom - top) as u32,
+            )?;
+            epd.display_frame(spi, delay)?;
+        }
+        self.previous.copy_from_slice(buffer);
+        self.valid = true;
+        Ok(true)
+    }
+}
+
+/// First row and row past the last one which differ between `previous` and `frame`
+fn changed_rows(previous: &[u8], frame: &[u8], line_bytes: usize) -> Option<(usize, usize)> {
+    let changed = |(old, new): &(&[u8], &[u8])| old != new;
+    let mut rows = previous.chunks(line_bytes).zip(frame.chunks(line_bytes));
+    let top = rows.position(|rows| changed(&rows))?;
+    let unchanged_below = rows.rev().position(|rows| changed(&rows));
+    let bottom = match unchanged_below {
+        Some(unchanged) => frame.len() / line_bytes - unchanged,
+        None => top + 1,
+    };
+    Some((top, bottom))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rows_of_the_changes() {
+        let previous = [0u8; 8];
+        assert_eq!(changed_rows(&previous, &previous, 2), None);
+        assert_eq!(
+            changed_rows(&previous, &[0, 0, 1, 0, 0, 0, 0, 0], 2),
+            Some((1, 2))
+        );
+        assert_eq!(
+            changed_rows(&previous, &[0, 1, 0, 0, 0, 0, 1, 0], 2),
+            Some((0, 4))
+        );
+        assert_eq!(
+            changed_rows(&previous, &[0, 0, 0, 1, 0, 0, 0, 1], 2),
+            Some((1, 4))
+        );
+    }
+}
@@ -0,0 +1,268 @@
+//! Keeping the frame shown by the panel across deep sleep and reboots
+//!
+//! Quick refreshes need the frame currently shown by the panel (the "old" frame), which the
+//! controller forgets in deep sleep and the MCU on reboot. Without it, the first refresh after
+//! waking up has to be a full one, with all its flashing.
+//!
+//! A [`FrameStore`] keeps that frame in a backend of the application's choice (internal flash,
+//! FRAM, SD card, RTC memory), or only its [hash](frame_hash) when space is scarce: the
+//! application then renders the frame again and the store checks that it is still the one
+//! shown. [`restore_old_frame`] gives it back to a driver after waking up.
+//!
+//! The same hash lets [`SkipUnchanged`] skip the refreshes of a frame identical to the one
+//! already shown, e.g. when a dashboard is rendered again with the same readings.
+//!
+//!```rust, no_run
+//!# use embedded_hal_mock::eh1::*;
+//!# fn main() -> Result<(), epd_waveshare::frame_store::FrameStoreError<epd_waveshare::error::EpdError<embedded_hal::spi::ErrorKind>, ()>> {
+//!use epd_waveshare::{epd2in9_v2::*, frame_store::*, prelude::*};
+//!#
+//!# let expectations = [];
+//!# let mut spi = spi::Mock::new(&expectations);
+//!# let expectations = [];
+//!# let busy_in = digital::Mock::new(&expectations);
+//!# let dc = digital::Mock::new(&expectations);
+//!# let rst = digital::Mock::new(&expectations);
+//!# let mut delay = delay::NoopDelay::new();
+//!
+//!/// Keeps the hash of the frame, e.g. in the RTC memory of the MCU
+//!struct Hash(Option<u32>);
+//!
+//!impl FrameStore for Hash {
+//!    type Error = ();
+//!    fn save(&mut self, frame: &[u8]) -> Result<(), ()> {
+//!        self.0 = Some(frame_hash(frame));
+//!        Ok(())
+//!    }
+//!    fn restore(&mut self, frame: &mut [u8]) -> Result<bool, ()> {
+//!        Ok(self.0 == Some(frame_hash(frame)))
+//!    }
+//!}
+//!# let mut store = Hash(None);
+//!
+//!let mut epd = Epd2in9::new(&mut spi, busy_in, dc, rst, &mut delay, None).map_err(FrameStoreError::Spi)?;
+//!// render what was shown before sleeping
+//!let mut frame = [0xff; WIDTH as usize / 8 * HEIGHT as usize];
+//!if !restore_old_frame(&mut epd, &mut spi, &mut delay, &mut store, &mut frame)? {
+//!    // nothing to start from
+//!    epd.clear_frame(&mut spi, &mut delay).map_err(FrameStoreError::Spi)?;
+//!}
+//!// render the new frame, then
+//!epd.update_and_display_new_frame(&mut spi, &frame, &mut delay)
+//!    .map_err(FrameStoreError::Spi)?;
+//!store.save(&frame).map_err(FrameStoreError::Store)?;
+//!# Ok(())
+//!# }
+//!```
+
+use crate::error::EpdError;
+use crate::pin::BusyPin;
+use crate::traits::{QuickRefresh, WaveshareDisplay};
+use embedded_hal::{delay::*, digital::*, spi::SpiDevice};
+
+/// Persistent storage of the frame shown by the panel
+pub trait FrameStore {
+    /// Error of the backend
+    type Error;
+
+    /// Saves the frame now shown by the panel
+    fn save(&mut self, frame: &[u8]) -> Result<(), Self::Error>;
+
+    /// Restores the saved frame in `frame`
+    ///
+    /// Stores keeping only a hash of the frame compare it with `frame`, rendered again by the
+    /// application, instead. Returns whether `frame` holds the frame shown by the panel.
+    fn restore(&mut self, frame: &mut [u8]) -> Result<bool, Self::Error>;
+}
+
+/// Error while restoring the old frame
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FrameStoreError<SpiError, StoreError> {
+    /// Error sending the frame to the EPD
+    Spi(SpiError),
+    /// Error of the frame store
+    Store(StoreError),
+}
+
+/// FNV-1a hash of a frame, for the stores keeping only a hash
+pub fn frame_hash(frame: &[u8]) -> u32 {
+    frame.iter().fold(0x811c_9dc5, |hash, &byte| {
+        (hash ^ u32::from(byte)).wrapping_mul(0x0100_0193)
+    })
+}
+
+/// Sends the frame restored by `store` to `epd` as the old frame of the next quick refresh
+///
+/// Returns `false`, without sending anything, if the store has no frame or if `frame` isn't the
+/// one it saved: the next refresh must then be a full one.
+pub fn restore_old_frame<EPD, SPI, BUSY, DC, RST, DELAY, STORE>(
+    epd: &mut EPD,
+    spi: &mut SPI,
+    delay: &mut DELAY,
+    store: &mut STORE,
+    frame: &mut [u8],
+) -> Result<bool, FrameStoreError<EpdError<SPI::Error>, STORE::Error>>
+where
+    EPD: QuickRefresh<SPI, BUSY, DC, RST, DELAY>,
+    SPI: SpiDevice,
+    BUSY: BusyPin,
+    DC: OutputPin,
+    RST: OutputPin,
+    DELAY: DelayNs,
+    STORE: FrameStore,
+{
+    if !store.restore(frame).map_err(FrameStoreError::Store)? {
+        return Ok(false);
+    }
+    epd.update_old_frame(spi, frame, delay)
+        .map_err(FrameStoreError::Spi)?;
+    Ok(true)
+}
+
+/// Skips the refreshes of frames identical to the last one displayed
+///
+/// Only the 32-bit [`frame_hash`] of the last frame is kept, not a copy: two different frames
+/// with the same hash, unlikely but possible, would skip a refresh which was needed. Compare a
+/// copy of the frame, as [`FrameDiff`](crate::frame_diff::FrameDiff) does, when that matters.
+#[derive(Debug, Clone, Default)]
+pub struct SkipUnchanged {
+    last: Option<u32>,
+}
+
+impl SkipUnchanged {
+    /// Creates a filter which doesn't know the frame shown yet
+    pub fn new() -> Self {
+        SkipUnchanged { last: None }
+    }
+
+    /// Calls [`update_and_display_frame`](WaveshareDisplay::update_and_display_frame) unless
+    /// `buffer` is the frame displayed last
+    ///
+    /// Returns whether the display was refreshed.
+    pub fn update_and_display_frame<EPD, SPI, BUSY, DC, RST, DELAY>(
+        &mut self,
+        epd: &mut EPD,
+        spi: &mut SPI,
+        buffer: &[u8],
+        delay: &mut DELAY,
+    ) -> Result<bool, EpdError<SPI::Error>>
+    where
+        EPD: WaveshareDisplay<SPI, BUSY, DC, RST, DELAY>,
+        SPI: SpiDevice,
+        BUSY: BusyPin,
+        DC: OutputPin,
+        RST: OutputPin,
+        DELAY: DelayNs,
+    {
+        let hash = frame_hash(buffer);
+        if self.last == Some(hash) {
+            return Ok(false);
+        }
+        // the panel shows something unknown if the refresh fails
+        self.last = None;
+        epd.update_and_display_frame(spi, buffer, delay)?;
+        self.last = Some(hash);
+        Ok(true)
+    }
+
+    /// Forgets the last frame, e.g. after the panel was cleared, so that the next one is
+    /// displayed in any case
+    pub fn invalidate(&mut self) {
+        self.last = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fnv_hash() {
+        assert_eq!(frame_hash(&[]), 0x811c_9dc5);
+        assert_eq!(frame_hash(b"a"), 0xe40c_292c);
+        assert_eq!(frame_hash(b"foobar"), 0xbf9c_f968);
+    }
+
+    #[cfg(feature = "epd2in9")]
+    mod skip_unchanged {
+        use super::*;
+        use crate::epd2in9::{Epd2in9, HEIGHT, WIDTH};
+        use crate::pin::NoPin;
+        use embedded_hal::spi::{ErrorKind, ErrorType, Operation};
+        use embedded_hal_mock::eh1::delay::NoopDelay;
+
+        const FRAME_LEN: usize = WIDTH as usize / 8 * HEIGHT as usize;
+
+        /// Counts the transactions, failing them all once `fail` is set
+        #[derive(Default)]
+        struct Spi {
+            transactions: usize,
+            fail: bool,
+        }
+
+        impl ErrorType for Spi {
+            type Error = ErrorKind;
+        }
+
+        impl SpiDevice for Spi {
+            fn transaction(&mut self, _: &mut [Operation<'_, u8>]) -> Result<(), ErrorKind> {
+                if self.fail {
+                    return Err(ErrorKind::Other);
+                }
+                self.transactions += 1;
+                Ok(())
+            }
+        }
+
+        #[test]
+        fn identical_frame_skipped() {
+            let mut spi = Spi::default();
+            let mut delay = NoopDelay::new();
+            let mut epd = Epd2in9::new_uninit(NoPin, NoPin, NoPin, None);
+            let mut skip = SkipUnchanged::new();
+            let mut frame = [0xff; FRAME_LEN];
+
+            let refresh = skip.update_and_display_frame(&mut epd, &mut spi, &frame, &mut delay);
+            assert_eq!(refresh, Ok(true));
+            let sent = spi.transactions;
+            assert_ne!(sent, 0);
+
+            // nothing is sent for the same frame again
+            let refresh = skip.update_and_display_frame(&mut epd, &mut spi, &frame, &mut delay);
+            assert_eq!(refresh, Ok(false));
+            assert_eq!(spi.transactions, sent);
+
+            frame[0] = 0;
+            let refresh = skip.update_and_display_frame(&mut epd, &mut spi, &frame, &mut delay);
+            assert_eq!(refresh, Ok(true));
+            assert_eq!(spi.transactions, 2 * sent);
+
+            skip.invalidate();
+            let refresh = skip.update_and_display_frame(&mut epd, &mut spi, &frame, &mut delay);
+            assert_eq!(refresh, Ok(true));
+        }
+
+        #[test]
+        fn failed_update_invalidates() {
+            let mut spi = Spi::default();
+            let mut delay = NoopDelay::new();
+            let mut epd = Epd2in9::new_uninit(NoPin, NoPin, NoPin, None);
+            let mut skip = SkipUnchanged::new();
+            let frame = [0xff; FRAME_LEN];
+            let other = [0; FRAME_LEN];
+
+            let refresh = skip.update_and_display_frame(&mut epd, &mut spi, &frame, &mut delay);
+            assert_eq!(refresh, Ok(true));
+
+            // the panel may show part of the other frame after the failure
+            spi.fail = true;
+            let refresh = skip.update_and_display_frame(&mut epd, &mut spi, &other, &mut delay);
+            assert_eq!(refresh, Err(EpdError::Spi(ErrorKind::Other)));
+
+            // so the first frame is sent again
+            spi.fail = false;
+            let refresh = skip.update_and_display_frame(&mut epd, &mut spi, &frame, &mut delay);
+            assert_eq!(refresh, Ok(true));
+        }
+    }
+}
@@ -2,10 +2,11 @@
 
 use crate::color::{ColorType, TriColor};
 use core::marker::PhantomData;
-use embedded_graphics_core::prelude::*;
+use embedded_graphics_core::{image::GetPixel, pixelcolor::BinaryColor, prelude::*};
 
 /// Display rotation, only 90° increments supported
-#[derive(Clone, Copy, Default)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum DisplayRotation {
     /// No rotation
     #[default]
@@ -18,22 +19,55 @@ pub enum DisplayRotation {
     Rotate270,
 }
 
+/// Order of the pixels within a byte of the buffer
+///
+/// Most controllers take the leftmost pixel of a byte in its most significant bits, some clone
+/// controllers take it in the least significant ones. Drawing in the order of the controller
+/// saves reversing every byte before sending the buffer.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum BitOrder {
+    /// Leftmost pixel in the most significant bits
+    #[default]
+    MsbFirst,
+    /// Leftmost pixel in the least significant bits
+    LsbFirst,
+}
+
 /// count the number of bytes per line knowing that it may contains padding bits
 const fn line_bytes(width: u32, bits_per_pixel: usize) -> usize {
     // round to upper 8 bit count
     (width as usize * bits_per_pixel + 7) / 8
 }
 
+/// Number of bytes of the buffer of a `width` x `height` display of `COLOR`
+///
+/// This is the `BYTECOUNT` of a [`Display`], e.g. to define the buffer of a panel without alias
+/// in this crate:
+///
+///```rust
+///use epd_waveshare::{color::TriColor, graphics::{display_len, Display}};
+///
+///type Display250x122 = Display<250, 122, false, { display_len::<TriColor>(250, 122) }, TriColor>;
+///
+///let display = Display250x122::default();
+///assert_eq!(display.buffer().len(), 2 * 32 * 122);
+///```
+pub const fn display_len<COLOR: ColorType>(width: u32, height: u32) -> usize {
+    // each plane of a split buffer has its own padded lines
+    height as usize * line_bytes(width, COLOR::BITS_PER_PIXEL_PER_BUFFER) * COLOR::BUFFER_COUNT
+}
+
 /// Display buffer used for drawing with embedded graphics
 /// This can be rendered on EPD using ...
 ///
 /// - WIDTH: width in pixel when display is not rotated
 /// - HEIGHT: height in pixel when display is not rotated
 /// - BWRBIT: mandatory value of the B/W when chromatic bit is set, can be any value for non
-///           tricolor epd
+///   tricolor epd (with `BinaryColor`, it makes `On` white, see [`BinaryDisplay`])
 /// - COLOR: color type used by the target display
 /// - BYTECOUNT: This is redundant with previous data and should be removed when const generic
-///              expressions are stabilized
+///   expressions are stabilized, given by [`display_len`] meanwhile
 ///
 /// More on BWRBIT:
 ///
@@ -55,6 +89,7 @@ pub struct Display<
 > {
     buffer: [u8; BYTECOUNT],
     rotation: DisplayRotation,
+    bit_order: BitOrder,
     _color: PhantomData<COLOR>,
 }
 
@@ -66,22 +101,11 @@ impl<
         COLOR: ColorType + PixelColor,
     > Default for Display<WIDTH, HEIGHT, BWRBIT, BYTECOUNT, COLOR>
 {
-    /// Initialize display with the color '0', which may not be the same on all device.
-    /// Many devices have a bit parameter polarity that should be changed if this is not the right
-    /// one.
-    /// However, every device driver should implement a DEFAULT_COLOR constant to indicate which
-    /// color this represents (TODO)
-    ///
-    /// If you want a specific default color, you can still call clear() to set one.
+    /// Initialize display with the color '0', see [`new`](Display::new)
     // inline is necessary here to allow heap allocation via Box on stack limited programs
     #[inline(always)]
     fn default() -> Self {
-        Self {
-            // default color must be 0 for every bit in a pixel to make this work everywere
-            buffer: [0u8; BYTECOUNT],
-            rotation: DisplayRotation::default(),
-            _color: PhantomData,
-        }
+        Self::new()
     }
 }
 
@@ -125,6 +149,30 @@ impl<
     }
 }
 
+/// For use with embedded_grahics, to read the pixels back
+impl<
+        const WIDTH: u32,
+        const HEIGHT: u32,
+        const BWRBIT: bool,
+        const BYTECOUNT: usize,
+        COLOR: ColorType + PixelColor,
+    > GetPixel for Display<WIDTH, HEIGHT, BWRBIT, BYTECOUNT, COLOR>
+{
+    type Color = COLOR;
+
+    fn pixel(&self, point: Point) -> Option<COLOR> {
+        get_pixel(
+            &self.buffer,
+            WIDTH,
+            HEIGHT,
+            self.rotation,
+            self.bit_order,
+            BWRBIT,
+            point,
+        )
+    }
+}
+
 impl<
         const WIDTH: u32,
         const HEIGHT: u32,
@@ -133,6 +181,40 @@ impl<
         COLOR: ColorType + PixelColor,
     > Display<WIDTH, HEIGHT, BWRBIT, BYTECOUNT, COLOR>
 {
+    /// Fails to compile when BYTECOUNT isn't the size of a WIDTH x HEIGHT buffer of COLOR
+    const BYTECOUNT_CHECK: () = SizeCheck::<WIDTH, HEIGHT, BYTECOUNT, COLOR>::EXACT;
+
+    /// Initialize display with the color '0', which may not be the same on all device.
+    /// Many devices have a bit parameter polarity that should be changed if this is not the right
+    /// one.
+    /// However, every device driver should implement a DEFAULT_COLOR constant to indicate which
+    /// color this represents (TODO)
+    ///
+    /// If you want a specific default color, you can still call clear() to set one.
+    ///
+    /// Being `const`, it can initialize a `static` display, e.g. placed in a specific memory
+    /// region with `#[link_section]`. To draw in a buffer owned elsewhere, use a [`VarDisplay`].
+    ///
+    ///```rust
+    ///use epd_waveshare::epd2in9_v2::Display2in9;
+    ///
+    ///static DISPLAY: Display2in9 = Display2in9::new();
+    ///assert!(DISPLAY.buffer().iter().all(|&byte| byte == 0));
+    ///```
+    // inline is necessary here to allow heap allocation via Box on stack limited programs
+    #[inline(always)]
+    pub const fn new() -> Self {
+        #[allow(clippy::let_unit_value)]
+        let () = Self::BYTECOUNT_CHECK;
+        Self {
+            // default color must be 0 for every bit in a pixel to make this work everywere
+            buffer: [0u8; BYTECOUNT],
+            rotation: DisplayRotation::Rotate0,
+            bit_order: BitOrder::MsbFirst,
+            _color: PhantomData,
+        }
+    }
+
     /// get internal buffer to use it (to draw in epd)
     pub fn buffer(&self) -> &[u8] {
         &self.buffer
@@ -141,16 +223,49 @@ impl<
     /// Set the display rotation.
     ///
     /// This only concerns future drawing made to it. Anything aready drawn
-    /// stays as it is in the buffer.
+    /// stays as it is in the buffer, use [`set_rotation_repack`](Self::set_rotation_repack)
+    /// to keep it.
     pub fn set_rotation(&mut self, rotation: DisplayRotation) {
         self.rotation = rotation;
     }
 
+    /// Set the display rotation and move what has already been drawn accordingly.
+    ///
+    /// Contrary to [`set_rotation`](Self::set_rotation), the content of the buffer looks the
+    /// same after the call when using the new rotation as it looked before with the old one.
+    /// When the width and height of the display differ, only the top left square of the
+    /// drawing can be kept when switching between portrait and landscape, the rest of the
+    /// buffer is filled with the `background` color.
+    pub fn set_rotation_repack(&mut self, rotation: DisplayRotation, background: COLOR) {
+        repack_rotation::<COLOR>(
+            &mut self.buffer,
+            WIDTH,
+            HEIGHT,
+            self.bit_order,
+            self.rotation,
+            rotation,
+            raw_color(&background, BWRBIT),
+        );
+        self.rotation = rotation;
+    }
+
     /// Get current rotation
     pub fn rotation(&self) -> DisplayRotation {
         self.rotation
     }
 
+    /// Set the order of the pixels within the bytes of the buffer
+    ///
+    /// Like [`set_rotation`](Self::set_rotation), this only concerns future drawing made to it.
+    pub fn set_bit_order(&mut self, bit_order: BitOrder) {
+        self.bit_order = bit_order;
+    }
+
+    /// Get the order of the pixels within the bytes of the buffer
+    pub fn bit_order(&self) -> BitOrder {
+        self.bit_order
+    }
+
     /// Set a specific pixel color on this display
     pub fn set_pixel(&mut self, pixel: Pixel<COLOR>) {
         set_pixel(
@@ -158,12 +273,57 @@ impl<
             WIDTH,
             HEIGHT,
             self.rotation,
+            self.bit_order,
             BWRBIT,
             pixel,
         );
     }
+
+    /// Copy an icon of `atlas` with its top left corner at `point`
+    ///
+    /// See [`IconAtlas`] for the details, nothing is drawn if `index` is out of range.
+    pub fn blit_icon(&mut self, atlas: &IconAtlas<'_, COLOR>, index: usize, point: Point) {
+        blit_icon(
+            &mut self.buffer,
+            WIDTH,
+            HEIGHT,
+            self.rotation,
+            self.bit_order,
+            atlas,
+            index,
+            point,
+        );
+    }
 }
 
+/// Display buffer drawn with `BinaryColor`, for the black and white displays
+///
+/// The code written for `BinaryColor` draws on it without converting its colors.
+/// `BinaryColor::On` is black by default, as with the `From<BinaryColor>` implementations of the
+/// colors of this crate; set `ON_IS_WHITE` to draw white on black instead.
+///
+/// The buffer is the same as the one of a display of [`Color`](crate::color::Color), e.g. for the
+/// 1.54" panel:
+///
+///```rust
+///use embedded_graphics::{pixelcolor::BinaryColor, prelude::*, primitives::{PrimitiveStyle, Rectangle}};
+///use epd_waveshare::{buffer_len, epd1in54::{HEIGHT, WIDTH}, graphics::BinaryDisplay};
+///
+///let mut display =
+///    BinaryDisplay::<WIDTH, HEIGHT, { buffer_len(WIDTH as usize, HEIGHT as usize) }>::default();
+///let _ = Rectangle::new(Point::new(10, 10), Size::new(8, 8))
+///    .into_styled(PrimitiveStyle::with_fill(BinaryColor::On))
+///    .draw(&mut display);
+///// black pixels
+///assert_eq!(display.buffer()[10 * WIDTH as usize / 8 + 1], 0x00);
+///```
+pub type BinaryDisplay<
+    const WIDTH: u32,
+    const HEIGHT: u32,
+    const BYTECOUNT: usize,
+    const ON_IS_WHITE: bool = false,
+> = Display<WIDTH, HEIGHT, ON_IS_WHITE, BYTECOUNT, BinaryColor>;
+
 /// Some Tricolor specifics
 impl<const WIDTH: u32, const HEIGHT: u32, const BWRBIT: bool, const BYTECOUNT: usize>
     Display<WIDTH, HEIGHT, BWRBIT, BYTECOUNT, TriColor>
@@ -179,15 +339,62 @@ impl<const WIDTH: u32, const HEIGHT: u32, const BWRBIT: bool, const BYTECOUNT: u
     }
 }
 
+/// Read access to the packed buffer of a display, shared by `Display` and `VarDisplay`
+///
+/// This allows helpers like [`Terminal`](crate::terminal::Terminal) to work with both.
+pub trait DisplayBuffer: DrawTarget {
+    /// get internal buffer to use it (to draw in epd)
+    fn buffer(&self) -> &[u8];
+
+    /// Width of the buffer in pixel when display is not rotated
+    fn buffer_width(&self) -> u32;
+
+    /// Height of the buffer in pixel when display is not rotated
+    fn buffer_height(&self) -> u32;
+
+    /// Get current rotation
+    fn rotation(&self) -> DisplayRotation;
+}
+
+impl<
+        const WIDTH: u32,
+        const HEIGHT: u32,
+        const BWRBIT: bool,
+        const BYTECOUNT: usize,
+        COLOR: ColorType + PixelColor,
+    > DisplayBuffer for Display<WIDTH, HEIGHT, BWRBIT, BYTECOUNT, COLOR>
+{
+    fn buffer(&self) -> &[u8] {
+        &self.buffer
+    }
+
+    fn buffer_width(&self) -> u32 {
+        WIDTH
+    }
+
+    fn buffer_height(&self) -> u32 {
+        HEIGHT
+    }
+
+    fn rotation(&self) -> DisplayRotation {
+        self.rotation
+    }
+}
+
 /// Same as `Display`, except that its characteristics are defined at runtime.
 /// See display for documentation as everything is the same except that default
 /// is replaced by a `new` method.
+///
+/// The buffer is borrowed rather than owned, so it can be allocated wherever the application
+/// needs it: in a `static`, in a specific memory region (CCM RAM, external PSRAM, ...), or as a
+/// band of a larger frame.
 pub struct VarDisplay<'a, COLOR: ColorType + PixelColor> {
     width: u32,
     height: u32,
     bwrbit: bool,
     buffer: &'a mut [u8],
     rotation: DisplayRotation,
+    bit_order: BitOrder,
     _color: PhantomData<COLOR>,
 }
 
@@ -221,6 +428,39 @@ impl<COLOR: ColorType + PixelColor> OriginDimensions for VarDisplay<'_, COLOR> {
     }
 }
 
+/// For use with embedded_grahics, to read the pixels back
+impl<COLOR: ColorType + PixelColor> GetPixel for VarDisplay<'_, COLOR> {
+    type Color = COLOR;
+
+    fn pixel(&self, point: Point) -> Option<COLOR> {
+        get_pixel(
+            self.buffer(),
+            self.width,
+            self.height,
+            self.rotation,
+            self.bit_order,
+            self.bwrbit,
+            point,
+        )
+    }
+}
+
+/// Compile time checks of the buffer size of a WIDTH x HEIGHT display of COLOR
+struct SizeCheck<const WIDTH: u32, const HEIGHT: u32, const N: usize, COLOR>(PhantomData<COLOR>);
+
+impl<const WIDTH: u32, const HEIGHT: u32, const N: usize, COLOR: ColorType>
+    SizeCheck<WIDTH, HEIGHT, N, COLOR>
+{
+    const BYTES: usize = display_len::<COLOR>(WIDTH, HEIGHT);
+
+    const EXACT: () = assert!(
+        N == Self::BYTES,
+        "the buffer size doesn't match the display"
+    );
+
+    const LARGE_ENOUGH: () = assert!(N >= Self::BYTES, "the buffer is too small for the display");
+}
+
 /// Error found during usage of VarDisplay
 #[derive(Debug)]
 pub enum VarDisplayError {
@@ -245,6 +485,7 @@ impl<'a, COLOR: ColorType + PixelColor> VarDisplay<'a, COLOR> {
             bwrbit,
             buffer,
             rotation: DisplayRotation::default(),
+            bit_order: BitOrder::default(),
             _color: PhantomData,
         };
         // enfore some constraints dynamicly
@@ -254,13 +495,30 @@ impl<'a, COLOR: ColorType + PixelColor> VarDisplay<'a, COLOR> {
         Ok(myself)
     }
 
+    /// Same as [`new`](VarDisplay::new) for a buffer whose size is known at compile time
+    ///
+    /// Fails to compile instead of returning an error when `buffer` is too small for a `WIDTH`
+    /// x `HEIGHT` display of `COLOR`.
+    pub fn new_sized<const WIDTH: u32, const HEIGHT: u32, const N: usize>(
+        buffer: &'a mut [u8; N],
+        bwrbit: bool,
+    ) -> Self {
+        #[allow(clippy::let_unit_value)]
+        let () = SizeCheck::<WIDTH, HEIGHT, N, COLOR>::LARGE_ENOUGH;
+        Self {
+            width: WIDTH,
+            height: HEIGHT,
+            bwrbit,
+            buffer,
+            rotation: DisplayRotation::default(),
+            bit_order: BitOrder::default(),
+            _color: PhantomData,
+        }
+    }
+
     /// get the number of used bytes in the buffer
     fn buffer_size(&self) -> usize {
-        self.height as usize
-            * line_bytes(
-                self.width,
-                COLOR::BITS_PER_PIXEL_PER_BUFFER * COLOR::BUFFER_COUNT,
-            )
+        display_len::<COLOR>(self.width, self.height)
     }
 
     /// get internal buffer to use it (to draw in epd)
@@ -276,11 +534,41 @@ impl<'a, COLOR: ColorType + PixelColor> VarDisplay<'a, COLOR> {
         self.rotation = rotation;
     }
 
+    /// Set the display rotation and move what has already been drawn accordingly.
+    ///
+    /// See [`Display::set_rotation_repack`] for details.
+    pub fn set_rotation_repack(&mut self, rotation: DisplayRotation, background: COLOR) {
+        let size = self.buffer_size();
+        let background = raw_color(&background, self.bwrbit);
+        repack_rotation::<COLOR>(
+            &mut self.buffer[..size],
+            self.width,
+            self.height,
+            self.bit_order,
+            self.rotation,
+            rotation,
+            background,
+        );
+        self.rotation = rotation;
+    }
+
     /// Get current rotation
     pub fn rotation(&self) -> DisplayRotation {
         self.rotation
     }
 
+    /// Set the order of the pixels within the bytes of the buffer
+    ///
+    /// See [`Display::set_bit_order`] for details.
+    pub fn set_bit_order(&mut self, bit_order: BitOrder) {
+        self.bit_order = bit_order;
+    }
+
+    /// Get the order of the pixels within the bytes of the buffer
+    pub fn bit_order(&self) -> BitOrder {
+        self.bit_order
+    }
+
     /// Set a specific pixel color on this display
     pub fn set_pixel(&mut self, pixel: Pixel<COLOR>) {
         let size = self.buffer_size();
@@ -289,10 +577,28 @@ impl<'a, COLOR: ColorType + PixelColor> VarDisplay<'a, COLOR> {
             self.width,
             self.height,
             self.rotation,
+            self.bit_order,
             self.bwrbit,
             pixel,
         );
     }
+
+    /// Copy an icon of `atlas` with its top left corner at `point`
+    ///
+    /// See [`IconAtlas`] for the details, nothing is drawn if `index` is out of range.
+    pub fn blit_icon(&mut self, atlas: &IconAtlas<'_, COLOR>, index: usize, point: Point) {
+        let size = self.buffer_size();
+        blit_icon(
+            &mut self.buffer[..size],
+            self.width,
+            self.height,
+            self.rotation,
+            self.bit_order,
+            atlas,
+            index,
+            point,
+        );
+    }
 }
 
 /// Some Tricolor specifics
@@ -308,6 +614,355 @@ impl VarDisplay<'_, TriColor> {
     }
 }
 
+impl<COLOR: ColorType + PixelColor> DisplayBuffer for VarDisplay<'_, COLOR> {
+    fn buffer(&self) -> &[u8] {
+        &self.buffer[..self.buffer_size()]
+    }
+
+    fn buffer_width(&self) -> u32 {
+        self.width
+    }
+
+    fn buffer_height(&self) -> u32 {
+        self.height
+    }
+
+    fn rotation(&self) -> DisplayRotation {
+        self.rotation
+    }
+}
+
+/// Rows of the buffer holding the rows `top..bottom` drawn with `rotation`
+///
+/// Returns `None` when they aren't rows of the buffer, with a rotation of 90 or 270 degrees.
+pub(crate) fn buffer_rows(
+    rotation: DisplayRotation,
+    height: u32,
+    top: u32,
+    bottom: u32,
+) -> Option<(u32, u32)> {
+    match rotation {
+        DisplayRotation::Rotate0 => Some((top, bottom)),
+        DisplayRotation::Rotate180 => Some((height - bottom, height - top)),
+        DisplayRotation::Rotate90 | DisplayRotation::Rotate270 => None,
+    }
+}
+
+/// Icons (sprite sheet) packed in the native buffer format of a display
+///
+/// The icons all have the same size and are stored one after the other, each one laid out
+/// exactly like the buffer of a `VarDisplay` of the icon size: unrotated, lines padded to a
+/// whole byte and, for tricolor displays, the black/white plane followed by the chromatic one.
+/// The easiest way to build an atlas is to draw the icons on such a `VarDisplay` once and to
+/// store its buffer.
+///
+/// When the display isn't rotated and the icon starts on a byte boundary of the display buffer,
+/// [`Display::blit_icon`] copies the icon lines byte by byte instead of drawing every pixel.
+pub struct IconAtlas<'a, COLOR: ColorType> {
+    data: &'a [u8],
+    icon_width: u32,
+    icon_height: u32,
+    _color: PhantomData<COLOR>,
+}
+
+impl<'a, COLOR: ColorType> IconAtlas<'a, COLOR> {
+    /// Create an atlas of `icon_width` x `icon_height` icons stored in `data`
+    pub const fn new(data: &'a [u8], icon_width: u32, icon_height: u32) -> Self {
+        IconAtlas {
+            data,
+            icon_width,
+            icon_height,
+            _color: PhantomData,
+        }
+    }
+
+    /// Number of bytes used by one icon
+    pub const fn icon_bytes(icon_width: u32, icon_height: u32) -> usize {
+        line_bytes(icon_width, COLOR::BITS_PER_PIXEL_PER_BUFFER)
+            * icon_height as usize
+            * COLOR::BUFFER_COUNT
+    }
+
+    /// Number of icons in the atlas
+    pub fn len(&self) -> usize {
+        match Self::icon_bytes(self.icon_width, self.icon_height) {
+            0 => 0,
+            bytes => self.data.len() / bytes,
+        }
+    }
+
+    /// Whether the atlas has no icon
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Size of the icons
+    pub fn icon_size(&self) -> Size {
+        Size::new(self.icon_width, self.icon_height)
+    }
+
+    /// Raw data of an icon
+    pub fn icon(&self, index: usize) -> Option<&'a [u8]> {
+        let bytes = Self::icon_bytes(self.icon_width, self.icon_height);
+        self.data.get(index * bytes..(index + 1) * bytes)
+    }
+}
+
+/// Maps a point given in rotated coordinates to its position in the unrotated buffer
+fn find_position(x: i32, y: i32, width: u32, height: u32, rotation: DisplayRotation) -> (i32, i32) {
+    match rotation {
+        // as i32 = never use more than 2 billion pixel per line or per column
+        DisplayRotation::Rotate0 => (x, y),
+        DisplayRotation::Rotate90 => (width as i32 - 1 - y, x),
+        DisplayRotation::Rotate180 => (width as i32 - 1 - x, height as i32 - 1 - y),
+        DisplayRotation::Rotate270 => (y, height as i32 - 1 - x),
+    }
+}
+
+/// Inverse of `find_position`: maps a position in the unrotated buffer to rotated coordinates
+fn find_rotated_position(
+    x: i32,
+    y: i32,
+    width: u32,
+    height: u32,
+    rotation: DisplayRotation,
+) -> (i32, i32) {
+    match rotation {
+        DisplayRotation::Rotate0 => (x, y),
+        DisplayRotation::Rotate90 => (y, width as i32 - 1 - x),
+        DisplayRotation::Rotate180 => (width as i32 - 1 - x, height as i32 - 1 - y),
+        DisplayRotation::Rotate270 => (height as i32 - 1 - y, x),
+    }
+}
+
+/// Size of the drawable area for a given rotation
+fn rotated_size(width: u32, height: u32, rotation: DisplayRotation) -> (u32, u32) {
+    match rotation {
+        DisplayRotation::Rotate0 | DisplayRotation::Rotate180 => (width, height),
+        DisplayRotation::Rotate90 | DisplayRotation::Rotate270 => (height, width),
+    }
+}
+
+// Position of a pixel within its byte for `ColorType::bitmask`: pixels within a byte are
+// reversed when the leftmost pixel is in the least significant bits
+fn bit_position<COLOR: ColorType>(x: u32, order: BitOrder) -> u32 {
+    match order {
+        BitOrder::MsbFirst => x,
+        BitOrder::LsbFirst => {
+            let per_byte = (8 / COLOR::BITS_PER_PIXEL_PER_BUFFER) as u32;
+            x - x % per_byte + (per_byte - 1 - x % per_byte)
+        }
+    }
+}
+
+// Byte index and bit shift of an unrotated pixel inside a single color plane
+fn raw_position<COLOR: ColorType>(width: u32, order: BitOrder, x: u32, y: u32) -> (usize, usize) {
+    let bits = COLOR::BITS_PER_PIXEL_PER_BUFFER;
+    let index = x as usize * bits / 8 + y as usize * line_bytes(width, bits);
+    let shift = 8 - bits - (bit_position::<COLOR>(x, order) as usize * bits) % 8;
+    (index, shift)
+}
+
+// Reads the raw bits of an unrotated pixel, the bits of the second plane of a split buffer
+// are placed above the ones of the first plane (same layout as `ColorType::bitmask`)
+fn get_raw_pixel<COLOR: ColorType>(
+    buffer: &[u8],
+    width: u32,
+    order: BitOrder,
+    x: u32,
+    y: u32,
+) -> u16 {
+    let bits = COLOR::BITS_PER_PIXEL_PER_BUFFER;
+    let plane_len = buffer.len() / COLOR::BUFFER_COUNT;
+    let mask = (1u16 << bits) - 1;
+    let (index, shift) = raw_position::<COLOR>(width, order, x, y);
+    (0..COLOR::BUFFER_COUNT).fold(0, |raw, plane| {
+        let value = (buffer[index + plane * plane_len] as u16 >> shift) & mask;
+        raw | value << (plane * bits)
+    })
+}
+
+// Raw bits of a color, in the layout of `get_raw_pixel`
+fn raw_color<COLOR: ColorType>(color: &COLOR, bwrbit: bool) -> u16 {
+    let bits = COLOR::BITS_PER_PIXEL_PER_BUFFER;
+    let mask = (1u16 << bits) - 1;
+    // the first pixel of a byte is in its high bits
+    let (_, value) = color.bitmask(bwrbit, 0);
+    (0..COLOR::BUFFER_COUNT).fold(0, |raw, plane| {
+        let byte = (value >> (8 * plane)) & 0xff;
+        raw | ((byte >> (8 - bits)) & mask) << (plane * bits)
+    })
+}
+
+// Writes raw bits read by `get_raw_pixel` back to an unrotated pixel
+fn set_raw_pixel<COLOR: ColorType>(
+    buffer: &mut [u8],
+    width: u32,
+    order: BitOrder,
+    x: u32,
+    y: u32,
+    raw: u16,
+) {
+    let bits = COLOR::BITS_PER_PIXEL_PER_BUFFER;
+    let plane_len = buffer.len() / COLOR::BUFFER_COUNT;
+    let mask = (1u16 << bits) - 1;
+    let (index, shift) = raw_position::<COLOR>(width, order, x, y);
+    for plane in 0..COLOR::BUFFER_COUNT {
+        let value = (raw >> (plane * bits)) & mask;
+        let byte = &mut buffer[index + plane * plane_len];
+        *byte = *byte & !((mask << shift) as u8) | (value << shift) as u8;
+    }
+}
+
+// This is a function to share code between `Display` and `VarDisplay`
+// It moves every pixel of the buffer from where it was drawn with the `from` rotation to
+// where it would have been drawn with the `to` rotation.
+//
+// This is done in place, without any temporary buffer: every pixel receives the value of
+// its source pixel. Following sources forms either chains (starting at a pixel whose value
+// is not needed anymore) or cycles of at most 4 pixels (rotations by 90° repeat after 4 steps).
+// Pixels without a source (when the drawable area isn't a square) are set to `background`.
+fn repack_rotation<COLOR: ColorType>(
+    buffer: &mut [u8],
+    width: u32,
+    height: u32,
+    order: BitOrder,
+    from: DisplayRotation,
+    to: DisplayRotation,
+    background: u16,
+) {
+    let (from_width, from_height) = rotated_size(width, height, from);
+    let (to_width, to_height) = rotated_size(width, height, to);
+
+    // where the value of a pixel comes from
+    let source = |x: u32, y: u32| -> Option<(u32, u32)> {
+        let (rx, ry) = find_rotated_position(x as i32, y as i32, width, height, to);
+        if rx as u32 >= from_width || ry as u32 >= from_height {
+            return None;
+        }
+        let (sx, sy) = find_position(rx, ry, width, height, from);
+        Some((sx as u32, sy as u32))
+    };
+    // whether the value of a pixel is needed somewhere else
+    let is_needed = |x: u32, y: u32| -> bool {
+        let (rx, ry) = find_rotated_position(x as i32, y as i32, width, height, from);
+        (rx as u32) < to_width && (ry as u32) < to_height
+    };
+
+    // chains: start at the end and walk back to the beginning
+    for y in 0..height {
+        for x in 0..width {
+            if is_needed(x, y) {
+                continue;
+            }
+            let (mut cx, mut cy) = (x, y);
+            while let Some((sx, sy)) = source(cx, cy) {
+                let raw = get_raw_pixel::<COLOR>(buffer, width, order, sx, sy);
+                set_raw_pixel::<COLOR>(buffer, width, order, cx, cy, raw);
+                (cx, cy) = (sx, sy);
+            }
+            set_raw_pixel::<COLOR>(buffer, width, order, cx, cy, background);
+        }
+    }
+
+    // cycles: only rotate them from their first pixel
+    let index = |x: u32, y: u32| y as usize * width as usize + x as usize;
+    for y in 0..height {
+        for x in 0..width {
+            let mut cycle = false;
+            let (mut cx, mut cy) = (x, y);
+            for _ in 0..4 {
+                match source(cx, cy) {
+                    Some((sx, sy)) if index(sx, sy) >= index(x, y) => (cx, cy) = (sx, sy),
+                    _ => break,
+                }
+                if (cx, cy) == (x, y) {
+                    cycle = true;
+                    break;
+                }
+            }
+            if !cycle || source(x, y) == Some((x, y)) {
+                continue;
+            }
+            let first = get_raw_pixel::<COLOR>(buffer, width, order, x, y);
+            let (mut cx, mut cy) = (x, y);
+            while let Some((sx, sy)) = source(cx, cy).filter(|&source| source != (x, y)) {
+                let raw = get_raw_pixel::<COLOR>(buffer, width, order, sx, sy);
+                set_raw_pixel::<COLOR>(buffer, width, order, cx, cy, raw);
+                (cx, cy) = (sx, sy);
+            }
+            set_raw_pixel::<COLOR>(buffer, width, order, cx, cy, first);
+        }
+    }
+}
+
+// This is a function to share code between `Display` and `VarDisplay`
+// It copies an icon to the buffer, line by line when the destination is aligned on bytes and
+// pixel by pixel otherwise.
+#[allow(clippy::too_many_arguments)]
+fn blit_icon<COLOR: ColorType>(
+    buffer: &mut [u8],
+    width: u32,
+    height: u32,
+    rotation: DisplayRotation,
+    order: BitOrder,
+    atlas: &IconAtlas<'_, COLOR>,
+    index: usize,
+    point: Point,
+) {
+    let icon = match atlas.icon(index) {
+        Some(icon) if !icon.is_empty() => icon,
+        _ => return,
+    };
+    let (icon_width, icon_height) = (atlas.icon_width, atlas.icon_height);
+    let bits = COLOR::BITS_PER_PIXEL_PER_BUFFER;
+
+    let fits = point.x >= 0
+        && point.y >= 0
+        && point.x as u32 + icon_width <= width
+        && point.y as u32 + icon_height <= height;
+    if rotation == DisplayRotation::Rotate0 && fits && (point.x as usize * bits) % 8 == 0 {
+        let icon_line = line_bytes(icon_width, bits);
+        // padding bits of the last byte of a line must not overwrite the next pixels
+        let last_mask = match ((icon_width as usize * bits) % 8, order) {
+            (0, _) => 0xff,
+            (used, BitOrder::MsbFirst) => 0xffu8 << (8 - used),
+            (used, BitOrder::LsbFirst) => 0xffu8 >> (8 - used),
+        };
+        let plane_len = buffer.len() / COLOR::BUFFER_COUNT;
+        let icon_plane_len = icon.len() / COLOR::BUFFER_COUNT;
+        for plane in 0..COLOR::BUFFER_COUNT {
+            for y in 0..icon_height as usize {
+                let (start, _) =
+                    raw_position::<COLOR>(width, order, point.x as u32, point.y as u32 + y as u32);
+                let dst = &mut buffer[plane * plane_len + start..][..icon_line];
+                let src = &icon[plane * icon_plane_len + y * icon_line..][..icon_line];
+                dst[..icon_line - 1].copy_from_slice(&src[..icon_line - 1]);
+                dst[icon_line - 1] =
+                    dst[icon_line - 1] & !last_mask | src[icon_line - 1] & last_mask;
+            }
+        }
+        return;
+    }
+
+    for iy in 0..icon_height {
+        for ix in 0..icon_width {
+            let (x, y) = find_position(
+                point.x + ix as i32,
+                point.y + iy as i32,
+                width,
+                height,
+                rotation,
+            );
+            if (x < 0) || (x >= width as i32) || (y < 0) || (y >= height as i32) {
+                continue;
+            }
+            let raw = get_raw_pixel::<COLOR>(icon, icon_width, order, ix, iy);
+            set_raw_pixel::<COLOR>(buffer, width, order, x as u32, y as u32, raw);
+        }
+    }
+}
+
 // This is a function to share code between `Display` and `VarDisplay`
 // It sets a specific pixel in a buffer to a given color.
 // The big number of parameters is due to the fact that it is an internal function to both
@@ -317,19 +972,14 @@ fn set_pixel<COLOR: ColorType + PixelColor>(
     width: u32,
     height: u32,
     rotation: DisplayRotation,
+    order: BitOrder,
     bwrbit: bool,
     pixel: Pixel<COLOR>,
 ) {
     let Pixel(point, color) = pixel;
 
     // final coordinates
-    let (x, y) = match rotation {
-        // as i32 = never use more than 2 billion pixel per line or per column
-        DisplayRotation::Rotate0 => (point.x, point.y),
-        DisplayRotation::Rotate90 => (width as i32 - 1 - point.y, point.x),
-        DisplayRotation::Rotate180 => (width as i32 - 1 - point.x, height as i32 - 1 - point.y),
-        DisplayRotation::Rotate270 => (point.y, height as i32 - 1 - point.x),
-    };
+    let (x, y) = find_position(point.x, point.y, width, height, rotation);
 
     // Out of range check
     if (x < 0) || (x >= width as i32) || (y < 0) || (y >= height as i32) {
@@ -339,7 +989,7 @@ fn set_pixel<COLOR: ColorType + PixelColor>(
 
     let index = x as usize * COLOR::BITS_PER_PIXEL_PER_BUFFER / 8
         + y as usize * line_bytes(width, COLOR::BITS_PER_PIXEL_PER_BUFFER);
-    let (mask, bits) = color.bitmask(bwrbit, x as u32);
+    let (mask, bits) = color.bitmask(bwrbit, bit_position::<COLOR>(x as u32, order));
 
     if COLOR::BUFFER_COUNT == 2 {
         // split buffer is for tricolor displays that use 2 buffer for 2 bits per pixel
@@ -351,13 +1001,32 @@ fn set_pixel<COLOR: ColorType + PixelColor>(
     }
 }
 
+// This is a function to share code between `Display` and `VarDisplay`
+// It reads back the color of a pixel drawn at `point`, `None` outside of the display.
+fn get_pixel<COLOR: ColorType + PixelColor>(
+    buffer: &[u8],
+    width: u32,
+    height: u32,
+    rotation: DisplayRotation,
+    order: BitOrder,
+    bwrbit: bool,
+    point: Point,
+) -> Option<COLOR> {
+    let (x, y) = find_position(point.x, point.y, width, height, rotation);
+    if (x < 0) || (x >= width as i32) || (y < 0) || (y >= height as i32) {
+        return None;
+    }
+    let raw = get_raw_pixel::<COLOR>(buffer, width, order, x as u32, y as u32);
+    COLOR::from_bits(raw, bwrbit)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::color::*;
     use embedded_graphics::{
         prelude::*,
-        primitives::{Line, PrimitiveStyle},
+        primitives::{Line, PrimitiveStyle, Rectangle},
     };
 
     // test buffer length
@@ -452,7 +1121,7 @@ mod tests {
 
     #[test]
     fn graphics_set_pixel_tricolor_false() {
-        let mut display = Display::<4, 4, false, { 4 * 4 * 2 / 8 }, TriColor>::default();
+        let mut display = Display::<4, 4, false, { 4 * 2 }, TriColor>::default();
         display.set_pixel(Pixel(Point::new(0, 0), TriColor::White));
         display.set_pixel(Pixel(Point::new(1, 0), TriColor::Chromatic));
         display.set_pixel(Pixel(Point::new(2, 0), TriColor::Black));
@@ -464,13 +1133,13 @@ mod tests {
         std::println!("{:?}", bw_buffer);
         std::println!("{:?}", chromatic_buffer);
 
-        assert_eq!(bw_buffer, [192, 0]);
-        assert_eq!(chromatic_buffer, [64, 0]);
+        assert_eq!(bw_buffer, [192, 0, 0, 0]);
+        assert_eq!(chromatic_buffer, [64, 0, 0, 0]);
     }
 
     #[test]
     fn graphics_set_pixel_tricolor_true() {
-        let mut display = Display::<4, 4, true, { 4 * 4 * 2 / 8 }, TriColor>::default();
+        let mut display = Display::<4, 4, true, { 4 * 2 }, TriColor>::default();
         display.set_pixel(Pixel(Point::new(0, 0), TriColor::White));
         display.set_pixel(Pixel(Point::new(1, 0), TriColor::Chromatic));
         display.set_pixel(Pixel(Point::new(2, 0), TriColor::Black));
@@ -482,7 +1151,413 @@ mod tests {
         std::println!("{:?}", bw_buffer);
         std::println!("{:?}", chromatic_buffer);
 
-        assert_eq!(bw_buffer, [128, 0]);
-        assert_eq!(chromatic_buffer, [64, 0]);
+        assert_eq!(bw_buffer, [128, 0, 0, 0]);
+        assert_eq!(chromatic_buffer, [64, 0, 0, 0]);
+    }
+
+    #[test]
+    #[allow(clippy::let_unit_value)]
+    fn display_bytecounts() {
+        // fails to compile if a display of a driver has a wrong BYTECOUNT
+        #[cfg(feature = "badger2040")]
+        let () = crate::badger2040::DisplayBadger2040::BYTECOUNT_CHECK;
+        #[cfg(feature = "epd10in2")]
+        let () = crate::epd10in2::Display10in2::BYTECOUNT_CHECK;
+        #[cfg(feature = "epd10in3")]
+        let () = crate::epd10in3::Display10in3::BYTECOUNT_CHECK;
+        #[cfg(feature = "epd13in3e")]
+        let () = crate::epd13in3e::Display13in3e::BYTECOUNT_CHECK;
+        #[cfg(feature = "epd13in3k")]
+        let () = crate::epd13in3k::Display13in3k::BYTECOUNT_CHECK;
+        #[cfg(feature = "epd1in02")]
+        let () = crate::epd1in02::Display1in02::BYTECOUNT_CHECK;
+        #[cfg(feature = "epd1in54")]
+        let () = crate::epd1in54::Display1in54::BYTECOUNT_CHECK;
+        #[cfg(feature = "epd1in54b")]
+        let () = crate::epd1in54b::Display1in54b::BYTECOUNT_CHECK;
+        #[cfg(feature = "epd1in54c")]
+        let () = crate::epd1in54c::Display1in54c::BYTECOUNT_CHECK;
+        #[cfg(any(feature = "epd2in13_v2", feature = "epd2in13_v3"))]
+        let () = crate::epd2in13_v2::Display2in13::BYTECOUNT_CHECK;
+        #[cfg(feature = "epd2in13_v4")]
+        let () = crate::epd2in13_v4::Display2in13::BYTECOUNT_CHECK;
+        #[cfg(feature = "epd2in13b_v4")]
+        let () = crate::epd2in13b_v4::Display2in13b::BYTECOUNT_CHECK;
+        #[cfg(feature = "epd2in13bc")]
+        let () = crate::epd2in13bc::Display2in13bc::BYTECOUNT_CHECK;
+        #[cfg(feature = "epd2in13d")]
+        let () = crate::epd2in13d::Display2in13d::BYTECOUNT_CHECK;
+        #[cfg(feature = "epd2in36g")]
+        let () = crate::epd2in36g::Display2in36g::BYTECOUNT_CHECK;
+        #[cfg(feature = "epd2in66")]
+        let () = crate::epd2in66::Display2in66::BYTECOUNT_CHECK;
+        #[cfg(feature = "epd2in66b")]
+        let () = crate::epd2in66b::Display2in66b::BYTECOUNT_CHECK;
+        #[cfg(feature = "epd2in7")]
+        let () = crate::epd2in7::Display2in7::BYTECOUNT_CHECK;
+        #[cfg(feature = "epd2in7_v2")]
+        let () = crate::epd2in7_v2::Display2in7::BYTECOUNT_CHECK;
+        #[cfg(feature = "epd2in7b")]
+        let () = crate::epd2in7b::Display2in7b::BYTECOUNT_CHECK;
+        #[cfg(feature = "epd2in7b_v2")]
+        let () = crate::epd2in7b_v2::Display2in7b::BYTECOUNT_CHECK;
+        #[cfg(feature = "epd2in9")]
+        let () = crate::epd2in9::Display2in9::BYTECOUNT_CHECK;
+        #[cfg(feature = "epd2in9_v2")]
+        let () = crate::epd2in9_v2::Display2in9::BYTECOUNT_CHECK;
+        #[cfg(feature = "epd2in9_v2")]
+        let () = crate::epd2in9_v2::Display2in9Gray4::BYTECOUNT_CHECK;
+        #[cfg(feature = "epd2in9b_v4")]
+        let () = crate::epd2in9b_v4::Display2in9b::BYTECOUNT_CHECK;
+        #[cfg(feature = "epd2in9bc")]
+        let () = crate::epd2in9bc::Display2in9bc::BYTECOUNT_CHECK;
+        #[cfg(feature = "epd2in9d")]
+        let () = crate::epd2in9d::Display2in9d::BYTECOUNT_CHECK;
+        #[cfg(feature = "epd3in0g")]
+        let () = crate::epd3in0g::Display3in0g::BYTECOUNT_CHECK;
+        #[cfg(feature = "epd3in52")]
+        let () = crate::epd3in52::Display3in52::BYTECOUNT_CHECK;
+        #[cfg(feature = "epd3in7")]
+        let () = crate::epd3in7::Display3in7::BYTECOUNT_CHECK;
+        #[cfg(feature = "epd4in01f")]
+        let () = crate::epd4in01f::Display4in01f::BYTECOUNT_CHECK;
+        #[cfg(feature = "epd4in2")]
+        let () = crate::epd4in2::Display4in2::BYTECOUNT_CHECK;
+        #[cfg(feature = "epd4in26")]
+        let () = crate::epd4in26::Display4in26::BYTECOUNT_CHECK;
+        #[cfg(feature = "epd4in26")]
+        let () = crate::epd4in26::Display4in26Gray4::BYTECOUNT_CHECK;
+        #[cfg(feature = "epd5in65f")]
+        let () = crate::epd5in65f::Display5in65f::BYTECOUNT_CHECK;
+        #[cfg(feature = "epd5in79")]
+        let () = crate::epd5in79::Display5in79::BYTECOUNT_CHECK;
+        #[cfg(feature = "epd5in83_v2")]
+        let () = crate::epd5in83_v2::Display5in83::BYTECOUNT_CHECK;
+        #[cfg(feature = "epd5in83b_v2")]
+        let () = crate::epd5in83b_v2::Display5in83::BYTECOUNT_CHECK;
+        #[cfg(feature = "epd6in0")]
+        let () = crate::epd6in0::Display6in0::BYTECOUNT_CHECK;
+        #[cfg(feature = "epd7in3f")]
+        let () = crate::epd7in3f::Display7in3f::BYTECOUNT_CHECK;
+        #[cfg(feature = "epd7in5")]
+        let () = crate::epd7in5::Display7in5::BYTECOUNT_CHECK;
+        #[cfg(feature = "epd7in5_hd")]
+        let () = crate::epd7in5_hd::Display7in5::BYTECOUNT_CHECK;
+        #[cfg(feature = "epd7in5_v2")]
+        let () = crate::epd7in5_v2::Display7in5::BYTECOUNT_CHECK;
+        #[cfg(feature = "epd7in5b_v2")]
+        let () = crate::epd7in5b_v2::Display7in5::BYTECOUNT_CHECK;
+        #[cfg(feature = "epd7in8")]
+        let () = crate::epd7in8::Display7in8::BYTECOUNT_CHECK;
+        #[cfg(feature = "epd9in7")]
+        let () = crate::epd9in7::Display9in7::BYTECOUNT_CHECK;
+        #[cfg(feature = "inky_phat_ssd1608")]
+        let () = crate::inky::phat_ssd1608::DisplayInkyPhat::BYTECOUNT_CHECK;
+        #[cfg(feature = "inky_phat_ssd1608")]
+        let () = crate::inky::phat_ssd1608::DisplayInkyPhatColor::BYTECOUNT_CHECK;
+        #[cfg(feature = "inky_what")]
+        let () = crate::inky::what::DisplayInkyWhat::BYTECOUNT_CHECK;
+        #[cfg(feature = "inky_what")]
+        let () = crate::inky::what::DisplayInkyWhatColor::BYTECOUNT_CHECK;
+        #[cfg(feature = "lilygo4in7")]
+        let () = crate::lilygo4in7::DisplayLilyGo4in7::BYTECOUNT_CHECK;
+        #[cfg(feature = "magtag2in9")]
+        let () = crate::magtag2in9::DisplayMagTag2in9::BYTECOUNT_CHECK;
+        #[cfg(feature = "magtag2in9")]
+        let () = crate::magtag2in9::DisplayMagTag2in9Gray4::BYTECOUNT_CHECK;
+        #[cfg(feature = "weact2in9")]
+        let () = crate::weact2in9::DisplayWeAct2in9::BYTECOUNT_CHECK;
+        #[cfg(feature = "weact4in2")]
+        let () = crate::weact4in2::DisplayWeAct4in2::BYTECOUNT_CHECK;
+    }
+
+    #[test]
+    fn var_display_new_sized() {
+        let mut buffer = [0; 6 * 2];
+        let mut display = VarDisplay::<Color>::new_sized::<10, 6, 12>(&mut buffer, false);
+        assert_eq!(display.size(), Size::new(10, 6));
+        display.set_pixel(Pixel(Point::new(9, 5), Color::White));
+        assert_eq!(display.buffer()[11], 0x40);
+    }
+
+    #[test]
+    fn rows_of_the_buffer() {
+        assert_eq!(
+            buffer_rows(DisplayRotation::Rotate0, 20, 2, 5),
+            Some((2, 5))
+        );
+        assert_eq!(
+            buffer_rows(DisplayRotation::Rotate180, 20, 2, 5),
+            Some((15, 18))
+        );
+        assert_eq!(buffer_rows(DisplayRotation::Rotate90, 20, 2, 5), None);
+    }
+
+    #[test]
+    fn graphics_get_pixel() {
+        let mut display = Display::<10, 4, false, { 2 * 4 }, Color>::default();
+        display.set_rotation(DisplayRotation::Rotate90);
+        display.set_pixel(Pixel(Point::new(1, 9), Color::White));
+        assert_eq!(display.pixel(Point::new(1, 9)), Some(Color::White));
+        assert_eq!(display.pixel(Point::new(0, 9)), Some(Color::Black));
+        assert_eq!(display.pixel(Point::new(4, 0)), None);
+
+        let mut buffer = [0; 4 * 2];
+        let mut display = VarDisplay::<TriColor>::new(4, 4, &mut buffer, true).unwrap();
+        for (x, color) in [TriColor::White, TriColor::Chromatic]
+            .into_iter()
+            .enumerate()
+        {
+            display.set_pixel(Pixel(Point::new(x as i32, 3), color));
+            assert_eq!(display.pixel(Point::new(x as i32, 3)), Some(color));
+        }
+
+        let mut display = Display::<3, 1, false, 2, OctColor>::default();
+        display.set_pixel(Pixel(Point::new(2, 0), OctColor::Orange));
+        assert_eq!(display.pixel(Point::new(2, 0)), Some(OctColor::Orange));
+        assert_eq!(display.pixel(Point::new(1, 0)), Some(OctColor::Black));
+    }
+
+    #[test]
+    fn graphics_binary_color() {
+        let mut display = BinaryDisplay::<16, 1, 2>::default();
+        display.clear(BinaryColor::Off).unwrap();
+        display.set_pixel(Pixel(Point::new(1, 0), BinaryColor::On));
+        assert_eq!(display.buffer(), [0xbf, 0xff]);
+        assert_eq!(display.pixel(Point::new(1, 0)), Some(BinaryColor::On));
+        assert_eq!(display.pixel(Point::new(2, 0)), Some(BinaryColor::Off));
+
+        let mut display = BinaryDisplay::<16, 1, 2, true>::default();
+        display.set_pixel(Pixel(Point::new(1, 0), BinaryColor::On));
+        assert_eq!(display.buffer(), [0x40, 0x00]);
+        assert_eq!(display.pixel(Point::new(1, 0)), Some(BinaryColor::On));
+        assert_eq!(display.pixel(Point::new(2, 0)), Some(BinaryColor::Off));
+    }
+
+    #[test]
+    fn graphics_bit_order() {
+        let mut display = Display::<16, 1, false, 2, Color>::default();
+        display.set_bit_order(BitOrder::LsbFirst);
+        display.set_pixel(Pixel(Point::new(1, 0), Color::White));
+        display.set_pixel(Pixel(Point::new(8, 0), Color::White));
+        assert_eq!(display.buffer(), [0x02, 0x01]);
+        assert_eq!(display.pixel(Point::new(1, 0)), Some(Color::White));
+        assert_eq!(display.pixel(Point::new(6, 0)), Some(Color::Black));
+
+        let mut display = Display::<4, 2, false, 2, Gray4>::default();
+        display.set_bit_order(BitOrder::LsbFirst);
+        display.set_pixel(Pixel(Point::new(0, 0), Gray4::DARK_GRAY));
+        display.set_pixel(Pixel(Point::new(3, 0), Gray4::WHITE));
+        assert_eq!(display.buffer(), [0b11_00_00_01, 0]);
+        display.set_rotation_repack(DisplayRotation::Rotate180, Gray4::BLACK);
+        assert_eq!(display.buffer(), [0, 0b01_00_00_11]);
+        assert_eq!(display.pixel(Point::new(0, 0)), Some(Gray4::DARK_GRAY));
+    }
+
+    const ROTATIONS: [DisplayRotation; 4] = [
+        DisplayRotation::Rotate0,
+        DisplayRotation::Rotate90,
+        DisplayRotation::Rotate180,
+        DisplayRotation::Rotate270,
+    ];
+
+    #[test]
+    fn graphics_rotation_repack() {
+        let pixels = [
+            Pixel(Point::new(0, 0), Color::White),
+            Pixel(Point::new(3, 1), Color::White),
+            Pixel(Point::new(15, 2), Color::White),
+            Pixel(Point::new(7, 14), Color::White),
+        ];
+        for from in ROTATIONS {
+            for to in ROTATIONS {
+                let mut display = Display::<16, 16, false, { 16 * 16 / 8 }, Color>::default();
+                display.set_rotation(from);
+                display.draw_iter(pixels).unwrap();
+                display.set_rotation_repack(to, Color::Black);
+
+                let mut expected = Display::<16, 16, false, { 16 * 16 / 8 }, Color>::default();
+                expected.set_rotation(to);
+                expected.draw_iter(pixels).unwrap();
+
+                assert_eq!(display.buffer(), expected.buffer());
+                assert_eq!(display.rotation(), to);
+            }
+        }
+    }
+
+    #[test]
+    fn graphics_rotation_repack_split_and_nibble_buffers() {
+        for from in ROTATIONS {
+            for to in ROTATIONS {
+                let tri_pixels = [
+                    Pixel(Point::new(1, 0), TriColor::Chromatic),
+                    Pixel(Point::new(2, 5), TriColor::White),
+                    Pixel(Point::new(7, 6), TriColor::Chromatic),
+                ];
+                let mut display = Display::<8, 8, true, { 8 * 8 * 2 / 8 }, TriColor>::default();
+                display.set_rotation(from);
+                display.draw_iter(tri_pixels).unwrap();
+                display.set_rotation_repack(to, TriColor::Black);
+                let mut expected = Display::<8, 8, true, { 8 * 8 * 2 / 8 }, TriColor>::default();
+                expected.set_rotation(to);
+                expected.draw_iter(tri_pixels).unwrap();
+                assert_eq!(display.buffer(), expected.buffer());
+
+                let oct_pixels = [
+                    Pixel(Point::new(0, 1), OctColor::Red),
+                    Pixel(Point::new(3, 0), OctColor::Green),
+                    Pixel(Point::new(5, 4), OctColor::HiZ),
+                ];
+                let mut buffer = [0u8; 6 * 6 / 2];
+                let mut display = VarDisplay::<OctColor>::new(6, 6, &mut buffer, false).unwrap();
+                display.set_rotation(from);
+                display.draw_iter(oct_pixels).unwrap();
+                display.set_rotation_repack(to, OctColor::Black);
+                let mut expected_buffer = [0u8; 6 * 6 / 2];
+                let mut expected =
+                    VarDisplay::<OctColor>::new(6, 6, &mut expected_buffer, false).unwrap();
+                expected.set_rotation(to);
+                expected.draw_iter(oct_pixels).unwrap();
+                assert_eq!(display.buffer(), expected.buffer());
+            }
+        }
+    }
+
+    #[test]
+    fn graphics_rotation_repack_not_square() {
+        // only the top left 8x8 square can survive when switching between 8x16 and 16x8, the
+        // rest being filled with the background
+        let kept = [
+            Pixel(Point::new(0, 0), Color::Black),
+            Pixel(Point::new(7, 7), Color::Black),
+            Pixel(Point::new(2, 5), Color::Black),
+        ];
+        // out of range (and therefore not drawn) in landscape
+        let portrait_only = [
+            Pixel(Point::new(0, 8), Color::Black),
+            Pixel(Point::new(7, 15), Color::Black),
+        ];
+        for from in ROTATIONS {
+            for to in ROTATIONS {
+                let mut display = Display::<8, 16, false, { 8 * 16 / 8 }, Color>::default();
+                display.set_rotation(from);
+                let portrait = display.size() == Size::new(8, 16);
+                display.clear(Color::White).unwrap();
+                display.draw_iter(kept).unwrap();
+                display.draw_iter(portrait_only).unwrap();
+                display.set_rotation_repack(to, Color::White);
+
+                let mut expected = Display::<8, 16, false, { 8 * 16 / 8 }, Color>::default();
+                expected.set_rotation(to);
+                expected.clear(Color::White).unwrap();
+                expected.draw_iter(kept).unwrap();
+                if portrait {
+                    expected.draw_iter(portrait_only).unwrap();
+                }
+                assert_eq!(display.buffer(), expected.buffer());
+            }
+        }
+    }
+
+    // draws the icon pixel by pixel to compare with blit_icon
+    fn draw_icon<COLOR: ColorType + PixelColor>(
+        display: &mut VarDisplay<'_, COLOR>,
+        icon: &[u8],
+        size: Size,
+        point: Point,
+    ) {
+        let (width, height) = (display.width, display.height);
+        let buffer_size = display.buffer_size();
+        for y in 0..size.height {
+            for x in 0..size.width {
+                let (dx, dy) = find_position(
+                    point.x + x as i32,
+                    point.y + y as i32,
+                    width,
+                    height,
+                    display.rotation,
+                );
+                if dx >= 0 && dy >= 0 && dx < width as i32 && dy < height as i32 {
+                    let order = display.bit_order;
+                    let raw = get_raw_pixel::<COLOR>(icon, size.width, order, x, y);
+                    let buffer = &mut display.buffer[..buffer_size];
+                    set_raw_pixel::<COLOR>(buffer, width, order, dx as u32, dy as u32, raw);
+                }
+            }
+        }
+    }
+
+    fn check_blit<COLOR: ColorType + PixelColor>(colors: [COLOR; 2]) {
+        // two 11x5 icons: a frame and a diagonal
+        let icon_bytes = IconAtlas::<COLOR>::icon_bytes(11, 5);
+        let mut data = [0u8; 60];
+        let (frame, diagonal) = data[..2 * icon_bytes].split_at_mut(icon_bytes);
+        let mut frame = VarDisplay::<COLOR>::new(11, 5, frame, false).unwrap();
+        let _ = frame.clear(colors[0]);
+        let _ = Rectangle::new(Point::zero(), Size::new(11, 5))
+            .into_styled(PrimitiveStyle::with_stroke(colors[1], 1))
+            .draw(&mut frame);
+        let mut diagonal = VarDisplay::<COLOR>::new(11, 5, diagonal, false).unwrap();
+        let _ = diagonal.clear(colors[1]);
+        let _ = Line::new(Point::zero(), Point::new(10, 4))
+            .into_styled(PrimitiveStyle::with_stroke(colors[0], 1))
+            .draw(&mut diagonal);
+
+        let atlas = IconAtlas::<COLOR>::new(&data[..2 * icon_bytes], 11, 5);
+        assert_eq!(atlas.len(), 2);
+        assert!(atlas.icon(2).is_none());
+
+        for rotation in ROTATIONS {
+            for point in [Point::new(8, 3), Point::new(2, 1), Point::new(-3, 9)] {
+                let mut buffer = [0x5au8; 2 * 20 * 24];
+                let mut expected_buffer = buffer;
+                let mut display = VarDisplay::<COLOR>::new(24, 20, &mut buffer, false).unwrap();
+                let mut expected =
+                    VarDisplay::<COLOR>::new(24, 20, &mut expected_buffer, false).unwrap();
+                display.set_rotation(rotation);
+                expected.set_rotation(rotation);
+
+                display.blit_icon(&atlas, 1, point);
+                draw_icon(
+                    &mut expected,
+                    atlas.icon(1).unwrap(),
+                    atlas.icon_size(),
+                    point,
+                );
+                assert_eq!(
+                    display.buffer(),
+                    expected.buffer(),
+                    "{:?} {:?}",
+                    rotation,
+                    point
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn graphics_blit_icon() {
+        check_blit([Color::White, Color::Black]);
+        check_blit([TriColor::Chromatic, TriColor::Black]);
+        check_blit([OctColor::Red, OctColor::Blue]);
+    }
+
+    #[test]
+    fn graphics_blit_icon_aligned() {
+        let icons = [0b1010_1010, 0b1100_0000, 0b0101_0101, 0b0000_0000];
+        let atlas = IconAtlas::<Color>::new(&icons, 10, 2);
+        let mut display = Display::<24, 2, false, 6, Color>::default();
+        display.blit_icon(&atlas, 0, Point::new(8, 0));
+        assert_eq!(
+            display.buffer(),
+            [0, 0b1010_1010, 0b1100_0000, 0, 0b0101_0101, 0]
+        );
+        // out of range icons are ignored
+        display.blit_icon(&atlas, 1, Point::new(0, 0));
+        assert_eq!(
+            display.buffer(),
+            [0, 0b1010_1010, 0b1100_0000, 0, 0b0101_0101, 0]
+        );
     }
 }
@@ -0,0 +1,113 @@
+//! Pimoroni Inky boards
+//!
+//! The Inky boards of Pimoroni carry the same panels and controllers as some Waveshare HATs,
+//! the drivers of those are used for them under the names of the boards. The SSD1608 pHAT and
+//! the wHAT have modules of their own, with a black and white and a tri-color driver:
+//!
+//! | Board | Controller | Resolution | Driver |
+//! | :--- | :--- | :---: | :--- |
+//! | Inky pHAT (Red, Yellow, Black) | UC8151 (IL0373) | 212x104 | [`phat`], the 2.13" (B/C) |
+//! | Inky pHAT (Red, Yellow, Black) | SSD1608 | 250x122 | [`phat_ssd1608`] |
+//! | Inky wHAT (Red, Yellow, Black) | SSD1683 | 400x300 | [`what`] |
+//! | Inky Impression 4" | ACeP 7 colors | 640x400 | [`impression_4in0`], the 4.01" (F) |
+//! | Inky Impression 5.7" | UC8159 (ACeP 7 colors) | 600x448 | [`impression_5in7`], the 5.65" (F) |
+//! | Inky Impression 7.3" | ACeP 7 colors | 800x480 | [`impression_7in3`], the 7.3" (F) |
+//!
+//! The panels of the pHATs are mounted in portrait orientation: the UC8151 pHAT is 104 pixels
+//! wide and 212 high, rotate the display (`DisplayRotation::Rotate90` or `Rotate270`) to draw in
+//! landscape.
+//!
+//! Like on the Waveshare HATs, BUSY is low while the UC81xx controllers are busy, high while the
+//! SSD16xx ones are, and RST is active low.
+//! The pins of the boards on the Raspberry Pi header are:
+//!
+//! | Pin | GPIO |
+//! | :--- | :---: |
+//! | CS | CE0 (8) |
+//! | DC | 22 |
+//! | RST | 27 |
+//! | BUSY | 17 |
+//!
+//! The Pico Inky Pack has its own driver, [`badger2040`](crate::badger2040).
+//!
+//!```rust, no_run
+//!# use embedded_hal_mock::eh1::*;
+//!# fn main() -> Result<(), epd_waveshare::error::EpdError<embedded_hal::spi::ErrorKind>> {
+//!use embedded_graphics::{prelude::*, primitives::{Line, PrimitiveStyle}};
+//!use epd_waveshare::{graphics::DisplayRotation, inky::phat::*, prelude::*};
+//!#
+//!# let expectations = [];
+//!# let mut spi = spi::Mock::new(&expectations);
+//!# let expectations = [];
+//!# let busy_in = digital::Mock::new(&expectations);
+//!# let dc = digital::Mock::new(&expectations);
+//!# let rst = digital::Mock::new(&expectations);
+//!# let mut delay = delay::NoopDelay::new();
+//!
+//!let mut epd = Epd2in13bc::new(&mut spi, busy_in, dc, rst, &mut delay, None)?;
+//!
+//!let mut display = Display2in13bc::default();
+//!display.set_rotation(DisplayRotation::Rotate90);
+//!let _ = Line::new(Point::new(0, 52), Point::new(211, 52))
+//!    .into_styled(PrimitiveStyle::with_stroke(TriColor::Chromatic, 1))
+//!    .draw(&mut display);
+//!
+//!epd.update_color_frame(&mut spi, &mut delay, display.bw_buffer(), display.chromatic_buffer())?;
+//!epd.display_frame(&mut spi, &mut delay)?;
+//!# Ok(())
+//!# }
+//!```
+
+/// Inky pHAT, 212x104 pixels in black, white and red or yellow
+#[cfg(feature = "epd2in13bc")]
+pub use crate::epd2in13bc as phat;
+
+#[cfg(feature = "inky_phat_ssd1608")]
+pub mod phat_ssd1608;
+
+#[cfg(feature = "inky_what")]
+pub mod what;
+
+/// Inky Impression 4", 640x400 pixels in 7 colors
+#[cfg(feature = "epd4in01f")]
+pub use crate::epd4in01f as impression_4in0;
+
+/// Inky Impression 5.7", 600x448 pixels in 7 colors
+#[cfg(feature = "epd5in65f")]
+pub use crate::epd5in65f as impression_5in7;
+
+/// Inky Impression 7.3", 800x480 pixels in 7 colors
+#[cfg(feature = "epd7in3f")]
+pub use crate::epd7in3f as impression_7in3;
+
+#[cfg(all(
+    test,
+    feature = "epd2in13bc",
+    feature = "epd4in01f",
+    feature = "epd5in65f",
+    feature = "epd7in3f",
+    feature = "inky_phat_ssd1608",
+    feature = "inky_what"
+))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn board_sizes() {
+        assert_eq!((phat::WIDTH, phat::HEIGHT), (104, 212));
+        assert_eq!((phat_ssd1608::WIDTH, phat_ssd1608::HEIGHT), (122, 250));
+        assert_eq!((what::WIDTH, what::HEIGHT), (400, 300));
+        assert_eq!(
+            (impression_4in0::WIDTH, impression_4in0::HEIGHT),
+            (640, 400)
+        );
+        assert_eq!(
+            (impression_5in7::WIDTH, impression_5in7::HEIGHT),
+            (600, 448)
+        );
+        assert_eq!(
+            (impression_7in3::WIDTH, impression_7in3::HEIGHT),
+            (800, 480)
+        );
+    }
+}
@@ -0,0 +1,154 @@
+//! Inky pHAT with an SSD1608 controller, 250x122 pixels in black and white or in black, white
+//! and red or yellow
+//!
+//! The pHAT is driven like the SSD1608 panels of Waveshare, see [`crate::controller::ssd16xx`].
+//! The black and white board is refreshed with the waveform of the 1.54" and 2.9" panels, with
+//! the VCOM and line timings of the Pimoroni library, and has a quick refresh. The tri-color
+//! boards are refreshed with the waveform of their OTP.
+//!
+//! The panel is 122 pixels wide and 250 high: rotate the display to draw in landscape.
+//!
+//!```rust, no_run
+//!# use embedded_hal_mock::eh1::*;
+//!# fn main() -> Result<(), epd_waveshare::error::EpdError<embedded_hal::spi::ErrorKind>> {
+//!use embedded_graphics::{prelude::*, primitives::{Line, PrimitiveStyle}};
+//!use epd_waveshare::{graphics::DisplayRotation, inky::phat_ssd1608::*, prelude::*};
+//!#
+//!# let expectations = [];
+//!# let mut spi = spi::Mock::new(&expectations);
+//!# let expectations = [];
+//!# let busy_in = digital::Mock::new(&expectations);
+//!# let dc = digital::Mock::new(&expectations);
+//!# let rst = digital::Mock::new(&expectations);
+//!# let mut delay = delay::NoopDelay::new();
+//!
+//!let mut epd = InkyPhatColor::new(&mut spi, busy_in, dc, rst, &mut delay, None)?;
+//!
+//!let mut display = DisplayInkyPhatColor::default();
+//!display.set_rotation(DisplayRotation::Rotate90);
+//!let _ = Line::new(Point::new(0, 61), Point::new(249, 61))
+//!    .into_styled(PrimitiveStyle::with_stroke(TriColor::Chromatic, 1))
+//!    .draw(&mut display);
+//!
+//!epd.update_color_frame(&mut spi, &mut delay, display.bw_buffer(), display.chromatic_buffer())?;
+//!epd.display_frame(&mut spi, &mut delay)?;
+//!# Ok(())
+//!# }
+//!```
+
+use crate::color::{Color, TriColor};
+use crate::controller::ssd16xx::{Luts, Panel, PanelConfig, Ssd16xx, Voltages};
+use crate::type_a::constants::{LUT_FULL_UPDATE, LUT_PARTIAL_UPDATE};
+
+/// Width of the display in pixels
+pub const WIDTH: u32 = 122;
+/// Height of the display in pixels
+pub const HEIGHT: u32 = 250;
+/// Default Background Color (white)
+pub const DEFAULT_BACKGROUND_COLOR: Color = Color::White;
+
+/// Display with Fullsize buffer for use with the black and white Inky pHAT
+#[cfg(feature = "graphics")]
+pub type DisplayInkyPhat = crate::graphics::Display<
+    WIDTH,
+    HEIGHT,
+    false,
+    { crate::graphics::display_len::<Color>(WIDTH, HEIGHT) },
+    Color,
+>;
+
+/// Display with Fullsize buffer for use with the red or yellow Inky pHAT
+#[cfg(feature = "graphics")]
+pub type DisplayInkyPhatColor = crate::graphics::Display<
+    WIDTH,
+    HEIGHT,
+    true,
+    { crate::graphics::display_len::<TriColor>(WIDTH, HEIGHT) },
+    TriColor,
+>;
+
+/// Settings of the black and white Inky pHAT
+pub struct InkyPhatPanel;
+
+impl Panel for InkyPhatPanel {
+    const CONFIG: PanelConfig = PanelConfig {
+        single_byte_write: true,
+        // the SSD1608 doesn't have a temperature sensor
+        temperature_sensor: None,
+        voltages: Voltages {
+            booster: &[0xD7, 0xD6, 0x9D],
+            vcom: Some(0x70),
+            ..Voltages::DEFAULT
+        },
+        luts: Some(Luts {
+            full: &LUT_FULL_UPDATE,
+            quick: &LUT_PARTIAL_UPDATE,
+            refresh: 0xC4,
+            dummy_line_period: 0x1B,
+            gate_line_width: 0x0B,
+        }),
+        ..PanelConfig::new(WIDTH, HEIGHT)
+    };
+}
+
+/// Settings of the red and yellow Inky pHAT
+pub struct InkyPhatColorPanel;
+
+impl Panel for InkyPhatColorPanel {
+    const CONFIG: PanelConfig = PanelConfig {
+        single_byte_write: true,
+        temperature_sensor: None,
+        voltages: Voltages {
+            vcom: Some(0x70),
+            ..Voltages::DEFAULT
+        },
+        ..PanelConfig::new(WIDTH, HEIGHT)
+    };
+}
+
+/// Black and white Inky pHAT driver
+pub type InkyPhat<SPI, BUSY, DC, RST, DELAY> = Ssd16xx<InkyPhatPanel, SPI, BUSY, DC, RST, DELAY>;
+
+/// Black and white Inky pHAT driver for the async SPI and delay traits
+#[cfg(feature = "async")]
+pub type InkyPhatAsync<SPI, BUSY, DC, RST, DELAY> =
+    crate::controller::ssd16xx::Ssd16xxAsync<InkyPhatPanel, SPI, BUSY, DC, RST, DELAY>;
+
+/// Red or yellow Inky pHAT driver
+pub type InkyPhatColor<SPI, BUSY, DC, RST, DELAY> =
+    Ssd16xx<InkyPhatColorPanel, SPI, BUSY, DC, RST, DELAY, TriColor>;
+
+/// Red or yellow Inky pHAT driver for the async SPI and delay traits
+#[cfg(feature = "async")]
+pub type InkyPhatColorAsync<SPI, BUSY, DC, RST, DELAY> = crate::controller::ssd16xx::Ssd16xxAsync<
+    InkyPhatColorPanel,
+    SPI,
+    BUSY,
+    DC,
+    RST,
+    DELAY,
+    TriColor,
+>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn epd_size() {
+        assert_eq!(WIDTH, 122);
+        assert_eq!(HEIGHT, 250);
+        assert_eq!(DEFAULT_BACKGROUND_COLOR, Color::White);
+    }
+
+    #[test]
+    fn panel_config() {
+        let config = InkyPhatPanel::CONFIG;
+        assert_eq!((config.width, config.height), (WIDTH, HEIGHT));
+        assert_eq!(config.temperature_sensor, None);
+        assert_eq!(config.luts.map(|luts| luts.full.len()), Some(30));
+        let config = InkyPhatColorPanel::CONFIG;
+        assert_eq!((config.width, config.height), (WIDTH, HEIGHT));
+        assert_eq!(config.luts, None);
+    }
+}
@@ -0,0 +1,127 @@
+//! Inky wHAT, 400x300 pixels in black and white or in black, white and red or yellow
+//!
+//! The wHAT has an SSD1683 controller, set up like the one of the WeAct Studio 4.2" module, see
+//! [`crate::controller::ssd16xx`]. The waveforms are the ones of the controller OTP, the black and
+//! white board also has a quick refresh.
+//!
+//!```rust, no_run
+//!# use embedded_hal_mock::eh1::*;
+//!# fn main() -> Result<(), epd_waveshare::error::EpdError<embedded_hal::spi::ErrorKind>> {
+//!use embedded_graphics::{prelude::*, primitives::{Line, PrimitiveStyle}};
+//!use epd_waveshare::{inky::what::*, prelude::*};
+//!#
+//!# let expectations = [];
+//!# let mut spi = spi::Mock::new(&expectations);
+//!# let expectations = [];
+//!# let busy_in = digital::Mock::new(&expectations);
+//!# let dc = digital::Mock::new(&expectations);
+//!# let rst = digital::Mock::new(&expectations);
+//!# let mut delay = delay::NoopDelay::new();
+//!
+//!let mut epd = InkyWhatColor::new(&mut spi, busy_in, dc, rst, &mut delay, None)?;
+//!
+//!let mut display = DisplayInkyWhatColor::default();
+//!let _ = Line::new(Point::new(0, 150), Point::new(399, 150))
+//!    .into_styled(PrimitiveStyle::with_stroke(TriColor::Chromatic, 1))
+//!    .draw(&mut display);
+//!
+//!epd.update_color_frame(&mut spi, &mut delay, display.bw_buffer(), display.chromatic_buffer())?;
+//!epd.display_frame(&mut spi, &mut delay)?;
+//!# Ok(())
+//!# }
+//!```
+
+use crate::color::{Color, TriColor};
+use crate::controller::ssd16xx::{Panel, PanelConfig, Ssd16xx};
+
+/// Width of the display in pixels
+pub const WIDTH: u32 = 400;
+/// Height of the display in pixels
+pub const HEIGHT: u32 = 300;
+/// Default Background Color (white)
+pub const DEFAULT_BACKGROUND_COLOR: Color = Color::White;
+
+/// Display with Fullsize buffer for use with the black and white Inky wHAT
+#[cfg(feature = "graphics")]
+pub type DisplayInkyWhat = crate::graphics::Display<
+    WIDTH,
+    HEIGHT,
+    false,
+    { crate::graphics::display_len::<Color>(WIDTH, HEIGHT) },
+    Color,
+>;
+
+/// Display with Fullsize buffer for use with the red or yellow Inky wHAT
+#[cfg(feature = "graphics")]
+pub type DisplayInkyWhatColor = crate::graphics::Display<
+    WIDTH,
+    HEIGHT,
+    true,
+    { crate::graphics::display_len::<TriColor>(WIDTH, HEIGHT) },
+    TriColor,
+>;
+
+/// Settings of the black and white Inky wHAT
+pub struct InkyWhatPanel;
+
+impl Panel for InkyWhatPanel {
+    const CONFIG: PanelConfig = PanelConfig {
+        // the old frame is ignored by full refreshes, avoiding the ghosts of the previous image
+        update_control_full: Some([0x40, 0x00]),
+        update_control_quick: Some([0x00, 0x00]),
+        ..PanelConfig::new(WIDTH, HEIGHT)
+    };
+}
+
+/// Settings of the red and yellow Inky wHAT, whose full refreshes use both RAMs
+pub struct InkyWhatColorPanel;
+
+impl Panel for InkyWhatColorPanel {
+    const CONFIG: PanelConfig = PanelConfig::new(WIDTH, HEIGHT);
+}
+
+/// Black and white Inky wHAT driver
+pub type InkyWhat<SPI, BUSY, DC, RST, DELAY> = Ssd16xx<InkyWhatPanel, SPI, BUSY, DC, RST, DELAY>;
+
+/// Black and white Inky wHAT driver for the async SPI and delay traits
+#[cfg(feature = "async")]
+pub type InkyWhatAsync<SPI, BUSY, DC, RST, DELAY> =
+    crate::controller::ssd16xx::Ssd16xxAsync<InkyWhatPanel, SPI, BUSY, DC, RST, DELAY>;
+
+/// Red or yellow Inky wHAT driver
+pub type InkyWhatColor<SPI, BUSY, DC, RST, DELAY> =
+    Ssd16xx<InkyWhatColorPanel, SPI, BUSY, DC, RST, DELAY, TriColor>;
+
+/// Red or yellow Inky wHAT driver for the async SPI and delay traits
+#[cfg(feature = "async")]
+pub type InkyWhatColorAsync<SPI, BUSY, DC, RST, DELAY> = crate::controller::ssd16xx::Ssd16xxAsync<
+    InkyWhatColorPanel,
+    SPI,
+    BUSY,
+    DC,
+    RST,
+    DELAY,
+    TriColor,
+>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn epd_size() {
+        assert_eq!(WIDTH, 400);
+        assert_eq!(HEIGHT, 300);
+        assert_eq!(DEFAULT_BACKGROUND_COLOR, Color::White);
+    }
+
+    #[test]
+    fn panel_config() {
+        let config = InkyWhatPanel::CONFIG;
+        assert_eq!((config.width, config.height), (WIDTH, HEIGHT));
+        assert_eq!(config.update_control_full, Some([0x40, 0x00]));
+        let config = InkyWhatColorPanel::CONFIG;
+        assert_eq!((config.width, config.height), (WIDTH, HEIGHT));
+        assert_eq!(config.update_control_full, Some([0x00, 0x00]));
+    }
+}
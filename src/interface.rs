@@ -1,9 +1,37 @@
-use crate::traits::Command;
+use crate::color::gray4_plane_byte;
+use crate::error::EpdError;
+use crate::pin::BusyPin;
+use crate::ram_addressing::{x_window, y_window, RamAddressing, RamCommand};
+use crate::rect::Rect;
+#[cfg(feature = "async")]
+use crate::traits::AsyncBusyPin;
+use crate::traits::{Command, InterfaceConfig, ResetTiming};
 use core::marker::PhantomData;
-use embedded_hal::{delay::*, digital::*, spi::SpiDevice};
+use embedded_hal::{
+    delay::*,
+    digital::*,
+    spi::{Operation, SpiDevice},
+};
+#[cfg(feature = "async")]
+use embedded_hal_async::{delay::DelayNs as AsyncDelayNs, spi::SpiDevice as AsyncSpiDevice};
 
+#[maybe_async_cfg::maybe(
+    sync(keep_self),
+    async(
+        feature = "async",
+        idents(
+            SpiDevice(async = "AsyncSpiDevice"),
+            BusyPin(async = "AsyncBusyPin"),
+            DelayNs(async = "AsyncDelayNs")
+        )
+    )
+)]
 /// The Connection Interface of all (?) Waveshare EPD-Devices
 ///
+/// The code is written once as async: the blocking `DisplayInterface` is generated by removing
+/// the `.await`s and, with the `async` feature, `DisplayInterfaceAsync` for the async SPI and
+/// delay traits of `embedded-hal-async`.
+///
 /// SINGLE_BYTE_WRITE defines if a data block is written bytewise
 /// or blockwise to the spi device
 pub(crate) struct DisplayInterface<SPI, BUSY, DC, RST, DELAY, const SINGLE_BYTE_WRITE: bool> {
@@ -17,15 +45,30 @@ pub(crate) struct DisplayInterface<SPI, BUSY, DC, RST, DELAY, const SINGLE_BYTE_
     dc: DC,
     /// Pin for Resetting
     rst: RST,
-    /// number of ms the idle loop should sleep on
-    delay_us: u32,
+    /// Options set through the drivers
+    config: InterfaceConfig,
+    /// Waits without BUSY, the ones of the controller family if `None`
+    busy_timings: Option<BusyTimings>,
+    /// Address of the last command sent since the last wait
+    last_command: Option<u8>,
 }
 
+#[maybe_async_cfg::maybe(
+    sync(keep_self),
+    async(
+        feature = "async",
+        idents(
+            SpiDevice(async = "AsyncSpiDevice"),
+            BusyPin(async = "AsyncBusyPin"),
+            DelayNs(async = "AsyncDelayNs")
+        )
+    )
+)]
 impl<SPI, BUSY, DC, RST, DELAY, const SINGLE_BYTE_WRITE: bool>
     DisplayInterface<SPI, BUSY, DC, RST, DELAY, SINGLE_BYTE_WRITE>
 where
     SPI: SpiDevice,
-    BUSY: InputPin,
+    BUSY: BusyPin,
     DC: OutputPin,
     RST: OutputPin,
     DELAY: DelayNs,
@@ -36,41 +79,122 @@ where
     pub fn new(busy: BUSY, dc: DC, rst: RST, delay_us: Option<u32>) -> Self {
         // default delay of 10ms
         let delay_us = delay_us.unwrap_or(10_000);
-        DisplayInterface {
+        Self {
             _spi: PhantomData,
             _delay: PhantomData,
             busy,
             dc,
             rst,
-            delay_us,
+            config: InterfaceConfig {
+                busy_poll_us: delay_us,
+                busy_timeout_us: None,
+                on_busy_wait: None,
+                reset_timing: None,
+                // Be careful!! Linux has a default limit of 4096 bytes per spi transfer
+                // see https://raspberrypi.stackexchange.com/questions/65595/spi-transfer-fails-with-buffer-size-greater-than-4096
+                max_transfer: if cfg!(target_os = "linux") {
+                    Some(4096)
+                } else {
+                    None
+                },
+                single_byte_write: SINGLE_BYTE_WRITE,
+                in_band_dc: false,
+            },
+            busy_timings: None,
+            last_command: None,
+        }
+    }
+
+    /// Waits `timings` after the commands when BUSY isn't connected, instead of the waits of
+    /// the controller family
+    #[cfg_attr(
+        not(any(
+            feature = "epd1in54b",
+            feature = "epd1in54c",
+            feature = "epd2in13b_v4",
+            feature = "epd2in13bc",
+            feature = "epd2in36g",
+            feature = "epd2in66b",
+            feature = "epd2in7b",
+            feature = "epd2in7b_v2",
+            feature = "epd2in9b_v4",
+            feature = "epd2in9bc",
+            feature = "epd2in9d",
+            feature = "epd3in0g",
+            feature = "epd4in01f",
+            feature = "epd5in65f",
+            feature = "epd5in83b_v2",
+            feature = "epd7in3f",
+            feature = "epd7in5b_v2",
+            feature = "epd13in3e"
+        )),
+        allow(dead_code)
+    )]
+    pub(crate) fn with_busy_timings(mut self, timings: BusyTimings) -> Self {
+        self.busy_timings = Some(timings);
+        self
+    }
+
+    /// Returns the BUSY, DC and RST pins
+    pub(crate) fn release(self) -> (BUSY, DC, RST) {
+        (self.busy, self.dc, self.rst)
+    }
+
+    /// Returns the options set through the drivers, see [`InterfaceConfig`]
+    pub(crate) fn config_mut(&mut self) -> &mut InterfaceConfig {
+        &mut self.config
+    }
+
+    /// Counts the time waited for the controller since the `waited_us` of the previous poll,
+    /// and calls the busy wait hook
+    ///
+    /// Without delay between the polls, each poll counts as 1us.
+    fn waited(&self, waited_us: u32) -> Result<u32, EpdError<SPI::Error>> {
+        match self.config.busy_timeout_us {
+            Some(timeout_us) if waited_us >= timeout_us => Err(EpdError::BusyTimeout),
+            _ => {
+                if let Some(hook) = self.config.on_busy_wait {
+                    hook(waited_us);
+                }
+                Ok(waited_us.saturating_add(self.config.busy_poll_us.max(1)))
+            }
         }
     }
 
     /// Basic function for sending [Commands](Command).
     ///
     /// Enables direct interaction with the device with the help of [data()](DisplayInterface::data())
-    pub(crate) fn cmd<T: Command>(&mut self, spi: &mut SPI, command: T) -> Result<(), SPI::Error> {
+    pub(crate) async fn cmd<T: Command>(
+        &mut self,
+        spi: &mut SPI,
+        command: T,
+    ) -> Result<(), EpdError<SPI::Error>> {
         // low for commands
-        let _ = self.dc.set_low();
+        self.dc.set_low().map_err(EpdError::pin)?;
+        self.last_command = Some(command.address());
 
         // Transfer the command over spi
-        self.write(spi, &[command.address()])
+        self.write(spi, &[command.address()]).await
     }
 
     /// Basic function for sending an array of u8-values of data over spi
     ///
     /// Enables direct interaction with the device with the help of [command()](Epd4in2::command())
-    pub(crate) fn data(&mut self, spi: &mut SPI, data: &[u8]) -> Result<(), SPI::Error> {
+    pub(crate) async fn data(
+        &mut self,
+        spi: &mut SPI,
+        data: &[u8],
+    ) -> Result<(), EpdError<SPI::Error>> {
         // high for data
-        let _ = self.dc.set_high();
+        self.dc.set_high().map_err(EpdError::pin)?;
 
-        if SINGLE_BYTE_WRITE {
+        if self.config.single_byte_write {
             for val in data.iter().copied() {
                 // Transfer data one u8 at a time over spi
-                self.write(spi, &[val])?;
+                self.write(spi, &[val]).await?;
             }
         } else {
-            self.write(spi, data)?;
+            self.write(spi, data).await?;
         }
 
         Ok(())
@@ -78,47 +202,156 @@ where
 
     /// Basic function for sending [Commands](Command) and the data belonging to it.
     ///
-    /// TODO: directly use ::write? cs wouldn't needed to be changed twice than
-    pub(crate) fn cmd_with_data<T: Command>(
+    /// `SpiDevice` can't toggle a DC pin between the operations of a transaction, so CS is
+    /// released after the command. When the SPI device sends the DC bit itself (see
+    /// [`InterfaceConfig::in_band_dc`]), the command and its data share a single transaction
+    /// instead, unless it would be longer than the longest write.
+    pub(crate) async fn cmd_with_data<T: Command>(
         &mut self,
         spi: &mut SPI,
         command: T,
         data: &[u8],
-    ) -> Result<(), SPI::Error> {
-        self.cmd(spi, command)?;
-        self.data(spi, data)
+    ) -> Result<(), EpdError<SPI::Error>> {
+        let fits = self
+            .config
+            .max_transfer
+            .map_or(true, |max_bytes| data.len() < max_bytes);
+        if self.config.in_band_dc && fits {
+            // low for the command, the SPI device switching to data after its write
+            self.dc.set_low().map_err(EpdError::pin)?;
+            self.last_command = Some(command.address());
+            return spi
+                .transaction(&mut [
+                    Operation::Write(&[command.address()]),
+                    Operation::Write(data),
+                ])
+                .await
+                .map_err(EpdError::Spi);
+        }
+        self.cmd(spi, command).await?;
+        self.data(spi, data).await
     }
 
     /// Basic function for sending the same byte of data (one u8) multiple times over spi
     ///
     /// Enables direct interaction with the device with the help of [command()](ConnectionInterface::command())
-    pub(crate) fn data_x_times(
+    ///
+    /// Unless the data is written bytewise, the byte is sent from a buffer filled with it, in
+    /// writes of up to 256 bytes instead of one write per byte.
+    pub(crate) async fn data_x_times(
         &mut self,
         spi: &mut SPI,
         val: u8,
         repetitions: u32,
-    ) -> Result<(), SPI::Error> {
+    ) -> Result<(), EpdError<SPI::Error>> {
         // high for data
-        let _ = self.dc.set_high();
-        // Transfer data (u8) over spi
-        for _ in 0..repetitions {
-            self.write(spi, &[val])?;
+        self.dc.set_high().map_err(EpdError::pin)?;
+        if self.config.single_byte_write {
+            // Transfer data (u8) over spi
+            for _ in 0..repetitions {
+                self.write(spi, &[val]).await?;
+            }
+            return Ok(());
+        }
+        let buffer = [val; 256];
+        let mut remaining = repetitions as usize;
+        while remaining > 0 {
+            let len = remaining.min(buffer.len());
+            self.write(spi, &buffer[..len]).await?;
+            remaining -= len;
         }
         Ok(())
     }
 
+    /// Sends bit `bit` of the gray levels of `buffer`, packed by 4 pixels in a byte, as one bit
+    /// per pixel
+    #[cfg_attr(
+        not(any(feature = "epd2in9_v2", feature = "epd4in26", feature = "magtag2in9")),
+        allow(dead_code)
+    )]
+    pub(crate) async fn data_gray4_plane(
+        &mut self,
+        spi: &mut SPI,
+        buffer: &[u8],
+        bit: u32,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        let mut plane = [0u8; 16];
+        for packed in buffer.chunks(plane.len() * 2) {
+            let len = packed.len() / 2;
+            for (byte, pixels) in plane.iter_mut().zip(packed.chunks(2)) {
+                *byte = gray4_plane_byte(pixels, bit);
+            }
+            self.data(spi, &plane[..len]).await?;
+        }
+        Ok(())
+    }
+
+    /// Sends the data of `area` band by band, `render` filling each band of `band` before it's
+    /// sent, with as many lines of `line_bytes` bytes as `band` holds
+    pub(crate) async fn data_banded<F>(
+        &mut self,
+        spi: &mut SPI,
+        area: Rect,
+        line_bytes: usize,
+        band: &mut [u8],
+        mut render: F,
+    ) -> Result<(), EpdError<SPI::Error>>
+    where
+        F: FnMut(Rect, &mut [u8]),
+    {
+        for part in bands(area, line_bytes, band.len())? {
+            let pixels = &mut band[..part.h as usize * line_bytes];
+            render(part, pixels);
+            self.data(spi, pixels).await?;
+        }
+        Ok(())
+    }
+
+    /// Sets the RAM window of an SSD16xx controller, from `start` to `end` (both included)
+    pub(crate) async fn set_ram_area<A: RamAddressing>(
+        &mut self,
+        spi: &mut SPI,
+        start_x: u32,
+        start_y: u32,
+        end_x: u32,
+        end_y: u32,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        let (x, len) = x_window::<A>(start_x, end_x);
+        self.cmd_with_data(spi, RamCommand::XAddressStartEndPosition, &x[..len])
+            .await?;
+        self.cmd_with_data(
+            spi,
+            RamCommand::YAddressStartEndPosition,
+            &y_window::<A>(start_y, end_y),
+        )
+        .await
+    }
+
+    /// Moves the RAM address counters of an SSD16xx controller to pixel (`x`, `y`)
+    pub(crate) async fn set_ram_counter<A: RamAddressing>(
+        &mut self,
+        spi: &mut SPI,
+        x: u32,
+        y: u32,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        let (x, len) = A::x_address(x);
+        self.cmd_with_data(spi, RamCommand::XAddressCounter, &x[..len])
+            .await?;
+        self.cmd_with_data(spi, RamCommand::YAddressCounter, &A::y_address(y))
+            .await
+    }
+
     // spi write helper/abstraction function
-    fn write(&mut self, spi: &mut SPI, data: &[u8]) -> Result<(), SPI::Error> {
-        // transfer spi data
-        // Be careful!! Linux has a default limit of 4096 bytes per spi transfer
-        // see https://raspberrypi.stackexchange.com/questions/65595/spi-transfer-fails-with-buffer-size-greater-than-4096
-        if cfg!(target_os = "linux") {
-            for data_chunk in data.chunks(4096) {
-                spi.write(data_chunk)?;
-            }
-            Ok(())
-        } else {
-            spi.write(data)
+    async fn write(&mut self, spi: &mut SPI, data: &[u8]) -> Result<(), EpdError<SPI::Error>> {
+        // transfer spi data, split in the longest writes allowed
+        match self.config.max_transfer {
+            Some(max_bytes) => {
+                for data_chunk in data.chunks(max_bytes.max(1)) {
+                    spi.write(data_chunk).await.map_err(EpdError::Spi)?;
+                }
+                Ok(())
+            }
+            None => spi.write(data).await.map_err(EpdError::Spi),
         }
     }
 
@@ -134,41 +367,86 @@ where
     ///  - FALSE for epd2in9, epd1in54 (for all Display Type A ones?)
     ///
     /// Most likely there was a mistake with the 2in9 busy connection
-    pub(crate) fn wait_until_idle(&mut self, delay: &mut DELAY, is_busy_low: bool) {
-        while self.is_busy(is_busy_low) {
-            // This has been removed and added many time :
-            // - it is faster to not have it
-            // - it is complicated to pass the delay everywhere all the time
-            // - busy waiting can consume more power that delaying
-            // - delay waiting enables task switching on realtime OS
-            // -> keep it and leave the decision to the user
-            if self.delay_us > 0 {
-                delay.delay_us(self.delay_us);
+    pub(crate) async fn wait_until_idle(
+        &mut self,
+        delay: &mut DELAY,
+        is_busy_low: bool,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        let command = self.last_command.take();
+        let mut waited_us = 0;
+        loop {
+            match self.read_busy(is_busy_low)? {
+                Some(true) => {}
+                Some(false) => return Ok(()),
+                None => {
+                    self.wait_without_busy(delay, is_busy_low, command).await;
+                    return Ok(());
+                }
             }
+            waited_us = self.sleep_while_busy(delay, is_busy_low, waited_us).await?;
         }
     }
 
     /// Same as `wait_until_idle` for device needing a command to probe Busy pin
-    pub(crate) fn wait_until_idle_with_cmd<T: Command>(
+    pub(crate) async fn wait_until_idle_with_cmd<T: Command>(
         &mut self,
         spi: &mut SPI,
         delay: &mut DELAY,
         is_busy_low: bool,
         status_command: T,
-    ) -> Result<(), SPI::Error> {
-        self.cmd(spi, status_command)?;
-        if self.delay_us > 0 {
-            delay.delay_us(self.delay_us);
+    ) -> Result<(), EpdError<SPI::Error>> {
+        let command = self.last_command.take();
+        self.cmd(spi, status_command).await?;
+        if self.config.busy_poll_us > 0 {
+            delay.delay_us(self.config.busy_poll_us).await;
         }
-        while self.is_busy(is_busy_low) {
-            self.cmd(spi, status_command)?;
-            if self.delay_us > 0 {
-                delay.delay_us(self.delay_us);
+        let mut waited_us = 0;
+        loop {
+            match self.read_busy(is_busy_low)? {
+                Some(true) => {}
+                Some(false) => break,
+                None => {
+                    self.wait_without_busy(delay, is_busy_low, command).await;
+                    break;
+                }
+            }
+            waited_us = self.waited(waited_us)?;
+            self.cmd(spi, status_command).await?;
+            if self.config.busy_poll_us > 0 {
+                delay.delay_us(self.config.busy_poll_us).await;
             }
         }
+        self.last_command = None;
         Ok(())
     }
 
+    /// Waits for the controller without BUSY, after `command`
+    ///
+    /// The refreshes take the longest, the waits being those of the slowest panels of the
+    /// controller family, or the ones the driver set with
+    /// [`with_busy_timings`](Self::with_busy_timings).
+    async fn wait_without_busy(
+        &mut self,
+        delay: &mut DELAY,
+        is_busy_low: bool,
+        command: Option<u8>,
+    ) {
+        let timings = self.busy_timings.unwrap_or(if is_busy_low {
+            BusyTimings::UC81XX
+        } else {
+            BusyTimings::SSD16XX
+        });
+        let wait_us = match command {
+            Some(command) if command == timings.refresh_command => timings.refresh_us,
+            Some(_) => timings.command_us,
+            None => 0,
+        };
+        if let Some(hook) = self.config.on_busy_wait {
+            hook(0);
+        }
+        delay.delay_us(wait_us).await;
+    }
+
     /// Checks if device is still busy
     ///
     /// This is normally handled by the more complicated commands themselves,
@@ -182,9 +460,26 @@ where
     ///
     /// Most likely there was a mistake with the 2in9 busy connection
     /// //TODO: use the #cfg feature to make this compile the right way for the certain types
-    pub(crate) fn is_busy(&mut self, is_busy_low: bool) -> bool {
-        (is_busy_low && self.busy.is_low().unwrap_or(false))
-            || (!is_busy_low && self.busy.is_high().unwrap_or(false))
+    ///
+    /// Returns the error of a BUSY pin which can't be read. Without BUSY, see
+    /// [`NoPin`](crate::pin::NoPin), the device always counts as idle.
+    #[cfg_attr(
+        not(any(feature = "epd1in54_v2", feature = "epd2in9_v2", feature = "epd4in2")),
+        allow(dead_code)
+    )]
+    pub(crate) fn is_busy(&mut self, is_busy_low: bool) -> Result<bool, EpdError<SPI::Error>> {
+        Ok(self.read_busy(is_busy_low)?.unwrap_or(false))
+    }
+
+    /// Reads the BUSY pin, `true` while the device is busy and `None` if BUSY isn't connected
+    ///
+    /// BUSY isn't connected when it's a [`NoPin`](crate::pin::NoPin).
+    fn read_busy(&mut self, is_busy_low: bool) -> Result<Option<bool>, EpdError<SPI::Error>> {
+        if !BUSY::CONNECTED {
+            return Ok(None);
+        }
+        let high = self.busy.read_high().map_err(EpdError::pin)?;
+        Ok(Some(high != is_busy_low))
     }
 
     /// Resets the device.
@@ -194,15 +489,600 @@ where
     /// The timing of keeping the reset pin low seems to be important and different per device.
     /// Most displays seem to require keeping it low for 10ms, but the 7in5_v2 only seems to reset
     /// properly with 2ms
-    pub(crate) fn reset(&mut self, delay: &mut DELAY, initial_delay: u32, duration: u32) {
-        let _ = self.rst.set_high();
-        delay.delay_us(initial_delay);
-
-        let _ = self.rst.set_low();
-        delay.delay_us(duration);
-        let _ = self.rst.set_high();
-        //TODO: the upstream libraries always sleep for 200ms here
-        // 10ms works fine with just for the 7in5_v2 but this needs to be validated for other devices
-        delay.delay_us(200_000);
+    ///
+    /// A timing set with [`set_reset_timing`](Self::set_reset_timing) replaces the one given here.
+    pub(crate) async fn reset(
+        &mut self,
+        delay: &mut DELAY,
+        initial_delay: u32,
+        duration: u32,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        let timing = self.config.reset_timing.unwrap_or(ResetTiming {
+            high_us: initial_delay,
+            low_us: duration,
+            //TODO: the upstream libraries always sleep for 200ms here
+            // 10ms works fine with just for the 7in5_v2 but this needs to be validated for other devices
+            settle_us: 200_000,
+        });
+        self.rst.set_high().map_err(EpdError::pin)?;
+        delay.delay_us(timing.high_us).await;
+
+        self.rst.set_low().map_err(EpdError::pin)?;
+        delay.delay_us(timing.low_us).await;
+        self.rst.set_high().map_err(EpdError::pin)?;
+        delay.delay_us(timing.settle_us).await;
+        Ok(())
+    }
+}
+
+impl<SPI, BUSY, DC, RST, DELAY, const SINGLE_BYTE_WRITE: bool>
+    DisplayInterface<SPI, BUSY, DC, RST, DELAY, SINGLE_BYTE_WRITE>
+where
+    SPI: SpiDevice,
+    BUSY: BusyPin,
+    DC: OutputPin,
+    RST: OutputPin,
+    DELAY: DelayNs,
+{
+    /// Sleeps until the next poll of BUSY, `waited_us` after the start of the wait
+    fn sleep_while_busy(
+        &mut self,
+        delay: &mut DELAY,
+        _is_busy_low: bool,
+        waited_us: u32,
+    ) -> Result<u32, EpdError<SPI::Error>> {
+        let waited_us = self.waited(waited_us)?;
+        // This has been removed and added many time :
+        // - it is faster to not have it
+        // - it is complicated to pass the delay everywhere all the time
+        // - busy waiting can consume more power that delaying
+        // - delay waiting enables task switching on realtime OS
+        // -> keep it and leave the decision to the user
+        if self.config.busy_poll_us > 0 {
+            delay.delay_us(self.config.busy_poll_us);
+        }
+        Ok(waited_us)
+    }
+}
+
+#[cfg(feature = "async")]
+impl<SPI, BUSY, DC, RST, DELAY, const SINGLE_BYTE_WRITE: bool>
+    DisplayInterfaceAsync<SPI, BUSY, DC, RST, DELAY, SINGLE_BYTE_WRITE>
+where
+    SPI: AsyncSpiDevice,
+    BUSY: AsyncBusyPin,
+    DC: OutputPin,
+    RST: OutputPin,
+    DELAY: AsyncDelayNs,
+{
+    /// Awaits BUSY until the device is idle, `waited_us` after the start of the wait, instead of
+    /// polling it
+    ///
+    /// The busy timeout is awaited at the same time, the delay between the polls isn't used.
+    async fn sleep_while_busy(
+        &mut self,
+        delay: &mut DELAY,
+        is_busy_low: bool,
+        waited_us: u32,
+    ) -> Result<u32, EpdError<SPI::Error>> {
+        if let Some(hook) = self.config.on_busy_wait {
+            hook(waited_us);
+        }
+        let timeout_us = self.config.busy_timeout_us;
+        let busy = &mut self.busy;
+        let idle = async {
+            if is_busy_low {
+                busy.wait_for_high().await
+            } else {
+                busy.wait_for_low().await
+            }
+        };
+        match timeout_us {
+            None => idle.await.map_err(EpdError::pin)?,
+            Some(timeout_us) => {
+                let timeout = delay.delay_us(timeout_us.saturating_sub(waited_us));
+                match first(idle, timeout).await {
+                    Some(idle) => idle.map_err(EpdError::pin)?,
+                    None => return Err(EpdError::BusyTimeout),
+                }
+            }
+        }
+        Ok(waited_us)
+    }
+}
+
+/// Splits `area` in the bands of lines of `line_bytes` bytes that a buffer of `band_len` bytes
+/// holds, for the drivers sending their frames band by band
+///
+/// Returns [`EpdError::BufferSize`] if the buffer doesn't hold a single line.
+pub(crate) fn bands<E>(
+    area: Rect,
+    line_bytes: usize,
+    band_len: usize,
+) -> Result<impl Iterator<Item = Rect> + Clone, EpdError<E>> {
+    let lines = band_len / line_bytes.max(1);
+    if lines == 0 {
+        return Err(EpdError::BufferSize {
+            expected: line_bytes,
+            actual: band_len,
+        });
+    }
+    let end = area.y + area.h;
+    Ok((area.y..end)
+        .step_by(lines)
+        .map(move |y| Rect::new(area.x, y, area.w, (lines as u32).min(end - y))))
+}
+
+/// Awaits `future` and `timeout` together, `None` if `timeout` completes first
+#[cfg(feature = "async")]
+async fn first<F: core::future::Future>(
+    future: F,
+    timeout: impl core::future::Future<Output = ()>,
+) -> Option<F::Output> {
+    use core::task::Poll;
+
+    let mut future = core::pin::pin!(future);
+    let mut timeout = core::pin::pin!(timeout);
+    core::future::poll_fn(|cx| {
+        if let Poll::Ready(output) = future.as_mut().poll(cx) {
+            Poll::Ready(Some(output))
+        } else if timeout.as_mut().poll(cx).is_ready() {
+            Poll::Ready(None)
+        } else {
+            Poll::Pending
+        }
+    })
+    .await
+}
+
+/// Waits for the controller after the commands when BUSY isn't connected, see
+/// [`NoPin`](crate::pin::NoPin)
+///
+/// The drivers take the waits of their controller family, picked from their BUSY polarity, and
+/// set the ones of their panel with [`DisplayInterface::with_busy_timings`] when its refreshes
+/// take longer.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct BusyTimings {
+    /// Command starting a refresh
+    refresh_command: u8,
+    /// Wait after a refresh in us
+    refresh_us: u32,
+    /// Wait after the other commands in us
+    command_us: u32,
+}
+
+impl BusyTimings {
+    /// Black and white panels of the Solomon Systech controllers (SSD16xx), busy when high
+    pub(crate) const SSD16XX: Self = BusyTimings {
+        refresh_command: 0x20,
+        refresh_us: 4_000_000,
+        command_us: 200_000,
+    };
+
+    /// Tri-color panels of the SSD16xx
+    #[cfg_attr(
+        not(any(
+            feature = "epd2in13b_v4",
+            feature = "epd2in66b",
+            feature = "epd2in7b_v2",
+            feature = "epd2in9b_v4"
+        )),
+        allow(dead_code)
+    )]
+    pub(crate) const SSD16XX_COLOR: Self = BusyTimings {
+        refresh_command: 0x20,
+        refresh_us: 20_000_000,
+        command_us: 200_000,
+    };
+
+    /// Black and white panels of the UltraChip controllers (UC81xx), busy when low
+    pub(crate) const UC81XX: Self = BusyTimings {
+        refresh_command: 0x12,
+        refresh_us: 6_000_000,
+        command_us: 200_000,
+    };
+
+    /// Tri-color and 4-color panels of the UC81xx
+    #[cfg_attr(
+        not(any(
+            feature = "epd1in54b",
+            feature = "epd1in54c",
+            feature = "epd2in13bc",
+            feature = "epd2in36g",
+            feature = "epd2in7b",
+            feature = "epd2in9bc",
+            feature = "epd3in0g",
+            feature = "epd5in83b_v2",
+            feature = "epd7in5b_v2"
+        )),
+        allow(dead_code)
+    )]
+    pub(crate) const UC81XX_COLOR: Self = BusyTimings {
+        refresh_command: 0x12,
+        refresh_us: 30_000_000,
+        command_us: 200_000,
+    };
+
+    /// 6-color and 7-color panels (ACeP and Spectra)
+    #[cfg_attr(
+        not(any(
+            feature = "epd4in01f",
+            feature = "epd5in65f",
+            feature = "epd7in3f",
+            feature = "epd13in3e"
+        )),
+        allow(dead_code)
+    )]
+    pub(crate) const ACEP: Self = BusyTimings {
+        refresh_command: 0x12,
+        refresh_us: 45_000_000,
+        command_us: 200_000,
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pin::NoPin;
+    use embedded_hal_mock::eh1::{
+        delay::NoopDelay,
+        digital::{Mock, State, Transaction},
+        spi::{Mock as SpiMock, Transaction as SpiTransaction},
+    };
+
+    #[derive(Clone, Copy)]
+    struct Refresh;
+
+    impl Command for Refresh {
+        fn address(self) -> u8 {
+            0x12
+        }
+    }
+
+    /// Sums the delays
+    #[derive(Default)]
+    struct Delayed(u64);
+
+    impl DelayNs for Delayed {
+        fn delay_ns(&mut self, ns: u32) {
+            self.0 += u64::from(ns);
+        }
+    }
+
+    #[cfg(feature = "async")]
+    impl AsyncDelayNs for Delayed {
+        async fn delay_ns(&mut self, ns: u32) {
+            self.0 += u64::from(ns);
+        }
+    }
+
+    #[test]
+    fn wait_without_busy() {
+        extern crate std;
+        let mut spi = SpiMock::new(&[
+            SpiTransaction::transaction_start(),
+            SpiTransaction::write_vec(std::vec![0x12]),
+            SpiTransaction::transaction_end(),
+        ]);
+        let mut interface: DisplayInterface<_, _, _, _, Delayed, false> =
+            DisplayInterface::new(NoPin, NoPin, NoPin, Some(10));
+        interface.config_mut().busy_timeout_us = Some(25);
+        interface.cmd(&mut spi, Refresh).unwrap();
+        let mut delay = Delayed::default();
+        interface.wait_until_idle(&mut delay, true).unwrap();
+        assert_eq!(delay.0, 6_000_000_000);
+        // nothing sent since the refresh
+        interface.wait_until_idle(&mut delay, true).unwrap();
+        assert_eq!(delay.0, 6_000_000_000);
+        assert_eq!(interface.is_busy(true), Ok(false));
+        spi.done();
+    }
+
+    #[cfg(feature = "async")]
+    #[test]
+    fn wait_on_busy() {
+        use crate::test_utils::block_on;
+        use core::convert::Infallible;
+        use embedded_hal::spi::Operation;
+        use embedded_hal_async::digital::Wait;
+
+        struct Spi;
+
+        impl embedded_hal::spi::ErrorType for Spi {
+            type Error = Infallible;
+        }
+
+        impl AsyncSpiDevice for Spi {
+            async fn transaction(&mut self, _: &mut [Operation<'_, u8>]) -> Result<(), Infallible> {
+                Ok(())
+            }
+        }
+
+        /// Busy (high) until awaited, forever if `stuck`
+        struct Busy {
+            high: bool,
+            stuck: bool,
+        }
+
+        impl ErrorType for Busy {
+            type Error = Infallible;
+        }
+
+        impl InputPin for Busy {
+            fn is_high(&mut self) -> Result<bool, Infallible> {
+                Ok(self.high)
+            }
+            fn is_low(&mut self) -> Result<bool, Infallible> {
+                Ok(!self.high)
+            }
+        }
+
+        impl Wait for Busy {
+            async fn wait_for_high(&mut self) -> Result<(), Infallible> {
+                unreachable!("busy when high")
+            }
+            async fn wait_for_low(&mut self) -> Result<(), Infallible> {
+                if self.stuck {
+                    core::future::pending::<()>().await;
+                }
+                self.high = false;
+                Ok(())
+            }
+            async fn wait_for_rising_edge(&mut self) -> Result<(), Infallible> {
+                unreachable!("waits on the levels")
+            }
+            async fn wait_for_falling_edge(&mut self) -> Result<(), Infallible> {
+                unreachable!("waits on the levels")
+            }
+            async fn wait_for_any_edge(&mut self) -> Result<(), Infallible> {
+                unreachable!("waits on the levels")
+            }
+        }
+
+        let busy = Busy {
+            high: true,
+            stuck: false,
+        };
+        let mut interface: DisplayInterfaceAsync<Spi, _, _, _, Delayed, false> =
+            DisplayInterfaceAsync::new(busy, NoPin, NoPin, Some(10));
+        let mut delay = Delayed::default();
+        block_on(interface.wait_until_idle(&mut delay, false)).unwrap();
+        // awaited, not polled
+        assert_eq!(delay.0, 0);
+
+        let busy = Busy {
+            high: true,
+            stuck: true,
+        };
+        let mut interface: DisplayInterfaceAsync<Spi, _, _, _, Delayed, false> =
+            DisplayInterfaceAsync::new(busy, NoPin, NoPin, Some(10));
+        interface.config_mut().busy_timeout_us = Some(25);
+        assert_eq!(
+            block_on(interface.wait_until_idle(&mut delay, false)),
+            Err(EpdError::BusyTimeout)
+        );
+        assert_eq!(delay.0, 25_000);
+    }
+
+    #[test]
+    fn busy_wait_hook() {
+        use core::sync::atomic::{AtomicU32, Ordering};
+
+        static WAITED_US: AtomicU32 = AtomicU32::new(0);
+        static CALLS: AtomicU32 = AtomicU32::new(0);
+
+        // busy at 0 and 5us, idle at 10us
+        let busy = Mock::new(&[
+            Transaction::get(State::High),
+            Transaction::get(State::High),
+            Transaction::get(State::Low),
+        ]);
+        let mut interface: DisplayInterface<SpiMock<u8>, _, _, _, Delayed, true> =
+            DisplayInterface::new(busy, Mock::new(&[]), Mock::new(&[]), None);
+        interface.config_mut().busy_poll_us = 5;
+        interface.config_mut().on_busy_wait = Some(|waited_us| {
+            WAITED_US.fetch_add(waited_us, Ordering::Relaxed);
+            CALLS.fetch_add(1, Ordering::Relaxed);
+        });
+        let mut delay = Delayed::default();
+        interface.wait_until_idle(&mut delay, false).unwrap();
+        assert_eq!(delay.0, 10_000);
+        assert_eq!(CALLS.load(Ordering::Relaxed), 2);
+        assert_eq!(WAITED_US.load(Ordering::Relaxed), 5);
+        interface.busy.done();
+        interface.dc.done();
+        interface.rst.done();
+    }
+
+    #[test]
+    fn max_transfer() {
+        extern crate std;
+        let mut spi = SpiMock::new(&[
+            SpiTransaction::transaction_start(),
+            SpiTransaction::write_vec(std::vec![1, 2]),
+            SpiTransaction::transaction_end(),
+            SpiTransaction::transaction_start(),
+            SpiTransaction::write_vec(std::vec![3]),
+            SpiTransaction::transaction_end(),
+            SpiTransaction::transaction_start(),
+            SpiTransaction::write_vec(std::vec![4, 5, 6]),
+            SpiTransaction::transaction_end(),
+        ]);
+        let mut interface: DisplayInterface<_, _, _, _, NoopDelay, false> =
+            DisplayInterface::new(NoPin, NoPin, NoPin, None);
+        interface.config_mut().max_transfer = Some(2);
+        interface.data(&mut spi, &[1, 2, 3]).unwrap();
+        interface.config_mut().max_transfer = None;
+        interface.data(&mut spi, &[4, 5, 6]).unwrap();
+        spi.done();
+    }
+
+    #[test]
+    fn single_byte_write() {
+        extern crate std;
+        let mut spi = SpiMock::new(&[
+            SpiTransaction::transaction_start(),
+            SpiTransaction::write_vec(std::vec![1]),
+            SpiTransaction::transaction_end(),
+            SpiTransaction::transaction_start(),
+            SpiTransaction::write_vec(std::vec![2]),
+            SpiTransaction::transaction_end(),
+            SpiTransaction::transaction_start(),
+            SpiTransaction::write_vec(std::vec![3, 4]),
+            SpiTransaction::transaction_end(),
+        ]);
+        let mut interface: DisplayInterface<_, _, _, _, NoopDelay, true> =
+            DisplayInterface::new(NoPin, NoPin, NoPin, None);
+        interface.data(&mut spi, &[1, 2]).unwrap();
+        interface.config_mut().single_byte_write = false;
+        interface.data(&mut spi, &[3, 4]).unwrap();
+        spi.done();
+    }
+
+    #[test]
+    fn in_band_dc() {
+        extern crate std;
+        let mut spi = SpiMock::new(&[
+            SpiTransaction::transaction_start(),
+            SpiTransaction::write_vec(std::vec![0x12]),
+            SpiTransaction::write_vec(std::vec![1, 2]),
+            SpiTransaction::transaction_end(),
+            // longer than the longest write
+            SpiTransaction::transaction_start(),
+            SpiTransaction::write_vec(std::vec![0x12]),
+            SpiTransaction::transaction_end(),
+            SpiTransaction::transaction_start(),
+            SpiTransaction::write_vec(std::vec![1]),
+            SpiTransaction::transaction_end(),
+            SpiTransaction::transaction_start(),
+            SpiTransaction::write_vec(std::vec![2]),
+            SpiTransaction::transaction_end(),
+        ]);
+        // bytewise, which doesn't matter when the command and its data share a transaction
+        let mut interface: DisplayInterface<_, _, _, _, NoopDelay, true> =
+            DisplayInterface::new(NoPin, NoPin, NoPin, None);
+        interface.config_mut().in_band_dc = true;
+        interface.config_mut().max_transfer = Some(4);
+        interface.cmd_with_data(&mut spi, Refresh, &[1, 2]).unwrap();
+        interface.config_mut().max_transfer = Some(2);
+        interface.cmd_with_data(&mut spi, Refresh, &[1, 2]).unwrap();
+        spi.done();
+    }
+
+    #[test]
+    fn data_x_times() {
+        extern crate std;
+        let mut spi = SpiMock::new(&[
+            SpiTransaction::transaction_start(),
+            SpiTransaction::write_vec(std::vec![0xff; 256]),
+            SpiTransaction::transaction_end(),
+            SpiTransaction::transaction_start(),
+            SpiTransaction::write_vec(std::vec![0xff; 44]),
+            SpiTransaction::transaction_end(),
+            SpiTransaction::transaction_start(),
+            SpiTransaction::write_vec(std::vec![0x00]),
+            SpiTransaction::transaction_end(),
+            SpiTransaction::transaction_start(),
+            SpiTransaction::write_vec(std::vec![0x00]),
+            SpiTransaction::transaction_end(),
+        ]);
+        let mut interface: DisplayInterface<_, _, _, _, NoopDelay, false> =
+            DisplayInterface::new(NoPin, NoPin, NoPin, None);
+        interface.data_x_times(&mut spi, 0xff, 300).unwrap();
+        interface.config_mut().single_byte_write = true;
+        interface.data_x_times(&mut spi, 0x00, 2).unwrap();
+        spi.done();
+    }
+
+    #[test]
+    fn banded() {
+        extern crate std;
+        use std::vec::Vec;
+        let parts: Vec<Rect> = bands::<()>(Rect::new(4, 2, 16, 5), 2, 5).unwrap().collect();
+        assert_eq!(
+            parts,
+            [
+                Rect::new(4, 2, 16, 2),
+                Rect::new(4, 4, 16, 2),
+                Rect::new(4, 6, 16, 1)
+            ]
+        );
+        assert!(matches!(
+            bands::<()>(Rect::new(0, 0, 16, 5), 2, 1),
+            Err(EpdError::BufferSize {
+                expected: 2,
+                actual: 1
+            })
+        ));
+
+        let mut spi = SpiMock::new(&[
+            SpiTransaction::transaction_start(),
+            SpiTransaction::write_vec(std::vec![0, 0, 1, 1]),
+            SpiTransaction::transaction_end(),
+            SpiTransaction::transaction_start(),
+            SpiTransaction::write_vec(std::vec![2, 2]),
+            SpiTransaction::transaction_end(),
+        ]);
+        let mut interface: DisplayInterface<_, _, _, _, NoopDelay, false> =
+            DisplayInterface::new(NoPin, NoPin, NoPin, None);
+        let mut band = [0; 5];
+        interface
+            .data_banded(
+                &mut spi,
+                Rect::new(0, 0, 16, 3),
+                2,
+                &mut band,
+                |area, pixels| {
+                    for (line, bytes) in pixels.chunks_mut(2).enumerate() {
+                        bytes.fill(area.y as u8 + line as u8);
+                    }
+                },
+            )
+            .unwrap();
+        spi.done();
+    }
+
+    #[test]
+    fn reset_timing() {
+        let mut interface: DisplayInterface<SpiMock<u8>, _, _, _, Delayed, false> =
+            DisplayInterface::new(NoPin, NoPin, NoPin, None);
+        let mut delay = Delayed::default();
+        interface.reset(&mut delay, 10_000, 2_000).unwrap();
+        assert_eq!(delay.0, 212_000_000);
+
+        interface.config_mut().reset_timing = Some(ResetTiming {
+            high_us: 1,
+            low_us: 2,
+            settle_us: 3,
+        });
+        let mut delay = Delayed::default();
+        interface.reset(&mut delay, 10_000, 2_000).unwrap();
+        assert_eq!(delay.0, 6_000);
+
+        interface.config_mut().reset_timing = Some(ResetTiming::NONE);
+        let mut delay = Delayed::default();
+        interface.reset(&mut delay, 10_000, 2_000).unwrap();
+        assert_eq!(delay.0, 0);
+    }
+
+    #[test]
+    fn busy_timeout() {
+        // polled at 0, 10, 20 and 30us
+        let busy = Mock::new(&[
+            Transaction::get(State::High),
+            Transaction::get(State::High),
+            Transaction::get(State::High),
+            Transaction::get(State::High),
+        ]);
+        let mut interface: DisplayInterface<SpiMock<u8>, _, _, _, NoopDelay, true> =
+            DisplayInterface::new(busy, Mock::new(&[]), Mock::new(&[]), Some(10));
+        interface.config_mut().busy_timeout_us = Some(25);
+        assert_eq!(
+            interface.wait_until_idle(&mut NoopDelay::new(), false),
+            Err(EpdError::BusyTimeout)
+        );
+        interface.busy.done();
+        interface.dc.done();
+        interface.rst.done();
     }
 }
@@ -0,0 +1,98 @@
+//! SPI Commands and registers of the IT8951 controller
+
+/// Preambles starting every SPI transfer
+#[derive(Copy, Clone)]
+pub(crate) enum Preamble {
+    /// A command code follows
+    Command = 0x6000,
+    /// Data words to write follow
+    WriteData = 0x0000,
+    /// A dummy word then the data words to read follow
+    ReadData = 0x1000,
+}
+
+impl Preamble {
+    /// Returns the preamble as sent on the wire
+    pub fn bytes(self) -> [u8; 2] {
+        (self as u16).to_be_bytes()
+    }
+}
+
+/// IT8951 commands
+///
+/// The commands without arguments are the ones of the I80 interface, the others are the user
+/// defined commands of the Waveshare firmware.
+#[allow(dead_code)]
+#[derive(Copy, Clone)]
+pub(crate) enum Command {
+    /// Leave sleep or standby mode
+    SysRun = 0x0001,
+    /// Enter standby mode, the panel power stays on
+    Standby = 0x0002,
+    /// Enter sleep mode, everything is powered off except the interface
+    Sleep = 0x0003,
+    /// Read a register: address, then read one word
+    RegRead = 0x0010,
+    /// Write a register: address and value
+    RegWrite = 0x0011,
+    /// Start loading an image in the full frame: format
+    LoadImage = 0x0020,
+    /// Start loading an image in an area: format, x, y, width and height
+    LoadImageArea = 0x0021,
+    /// End loading an image
+    LoadImageEnd = 0x0022,
+    /// Refresh an area of the panel: x, y, width, height and waveform mode
+    DisplayArea = 0x0034,
+    /// Read the device info (20 words)
+    GetDeviceInfo = 0x0302,
+    /// Refresh an area from a given image buffer: x, y, width, height, mode, address low and high
+    DisplayBufferArea = 0x0037,
+    /// Get (0) or set (1 and value) the VCOM voltage in mV
+    Vcom = 0x0039,
+}
+
+impl Command {
+    /// Returns the command code
+    pub fn address(self) -> u16 {
+        self as u16
+    }
+}
+
+/// IT8951 registers used by the driver
+#[allow(dead_code)]
+#[derive(Copy, Clone)]
+pub(crate) enum Register {
+    /// I80 packed mode, 1 to write 2 bytes per word
+    I80Cpcr = 0x0004,
+    /// Load image target address, low word
+    Lisar = 0x0208,
+    /// Load image target address, high word
+    LisarHigh = 0x020a,
+    /// Update parameter 1 setting (1 bit mode)
+    Up1sr = 0x1138,
+    /// LUT engines status, 0 once all of them are free
+    Lutafsr = 0x1224,
+    /// Bitmap (1 bit mode) color table
+    Bgvr = 0x1250,
+}
+
+impl Register {
+    /// Returns the register address
+    pub fn address(self) -> u16 {
+        self as u16
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn command_addr() {
+        assert_eq!(Command::SysRun.address(), 0x0001);
+        assert_eq!(Command::DisplayArea.address(), 0x0034);
+        assert_eq!(Command::GetDeviceInfo.address(), 0x0302);
+        assert_eq!(Register::Lisar.address(), 0x0208);
+        assert_eq!(Preamble::ReadData.bytes(), [0x10, 0x00]);
+    }
+}
@@ -0,0 +1,692 @@
+//! A driver for the IT8951 controller of the large Waveshare HD e-Paper HATs
+//!
+//! Contrary to the other displays of this crate, the panels of the HD HATs (6", 7.8", 9.7",
+//! 10.3", ...) aren't driven directly: the IT8951 holds the frame in its own memory and applies
+//! the waveforms itself. The host only loads (parts of) an image in the controller memory and
+//! asks for an area to be refreshed with a given [`WaveformMode`].
+//!
+//! The controller is accessed through SPI with a BUSY (`HRDY`) and a RST pin, it doesn't use a
+//! DC pin. Every transfer starts with a 16 bit preamble, all values are 16 bit big endian words.
+//!
+//! # References
+//!
+//! - [IT8951 I80/SPI/I2C programming guide](https://www.waveshare.com/w/upload/c/c9/IT8951_I80_Programming_Guide.pdf)
+//! - [Wiki](https://www.waveshare.com/wiki/6inch_HD_e-Paper_HAT)
+//! - [Waveshare C driver](https://github.com/waveshare/IT8951-ePaper)
+//!
+//!```rust, no_run
+//!# use embedded_hal_mock::eh1::*;
+//!# fn main() -> Result<(), epd_waveshare::error::EpdError<embedded_hal::spi::ErrorKind>> {
+//!use epd_waveshare::it8951::*;
+//!use epd_waveshare::rect::Rect;
+//!#
+//!# let expectations = [];
+//!# let mut spi = spi::Mock::new(&expectations);
+//!# let expectations = [];
+//!# let busy_in = digital::Mock::new(&expectations);
+//!# let rst = digital::Mock::new(&expectations);
+//!# let mut delay = delay::NoopDelay::new();
+//!
+//!let mut it8951 = It8951::new(&mut spi, busy_in, rst, &mut delay, None)?;
+//!// the VCOM voltage is printed on the ribbon cable of the panel
+//!it8951.set_vcom(&mut spi, &mut delay, 1500)?;
+//!
+//!// a black square in the top left corner
+//!let area = Rect::new(0, 0, 32, 32);
+//!it8951.load_image_area(&mut spi, &mut delay, PixelFormat::Bpp4, area, &[0; 32 * 32 / 2])?;
+//!it8951.display_area(&mut spi, &mut delay, area, WaveformMode::Gc16)?;
+//!# Ok(())
+//!# }
+//!```
+
+mod command;
+
+use self::command::{Command, Preamble, Register};
+use crate::error::EpdError;
+use crate::interface::bands;
+use crate::rect::Rect;
+use core::marker::PhantomData;
+use embedded_hal::{
+    delay::*,
+    digital::*,
+    spi::{Operation, SpiDevice},
+};
+#[cfg(feature = "async")]
+use embedded_hal_async::{delay::DelayNs as AsyncDelayNs, spi::SpiDevice as AsyncSpiDevice};
+
+/// Number of bytes written by a single SPI transfer when loading an image
+const CHUNK_SIZE: usize = 64;
+
+/// Highest SPI clock frequency accepted by the controller, in Hz
+pub const MAX_SPI_FREQUENCY: u32 = 24_000_000;
+
+/// Packs 8 bits gray levels into the 4 bits per pixel format of the controller
+///
+/// Only the upper 4 bits of every gray level are kept, two pixels per byte with the first one in
+/// the upper bits. `gray` should contain an even number of pixels, a missing last pixel is black.
+pub fn pack_4bpp(gray: &[u8], packed: &mut [u8]) {
+    for (packed, pair) in packed.iter_mut().zip(gray.chunks(2)) {
+        *packed = pair[0] & 0xf0 | pair.get(1).copied().unwrap_or(0) >> 4;
+    }
+}
+
+/// Information about the panel and the controller
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DeviceInfo {
+    /// Width of the panel in pixels
+    pub panel_width: u16,
+    /// Height of the panel in pixels
+    pub panel_height: u16,
+    /// Address of the image buffer in the controller memory
+    pub image_buffer_address: u32,
+    /// Firmware version, NUL padded ASCII
+    pub firmware_version: [u8; 16],
+    /// LUT (waveforms) version, NUL padded ASCII
+    pub lut_version: [u8; 16],
+}
+
+impl DeviceInfo {
+    /// Parses the 20 words returned by the controller
+    fn from_words(words: &[u16; 20]) -> Self {
+        let mut firmware_version = [0; 16];
+        let mut lut_version = [0; 16];
+        for i in 0..8 {
+            firmware_version[2 * i..2 * i + 2].copy_from_slice(&words[4 + i].to_be_bytes());
+            lut_version[2 * i..2 * i + 2].copy_from_slice(&words[12 + i].to_be_bytes());
+        }
+        DeviceInfo {
+            panel_width: words[0],
+            panel_height: words[1],
+            image_buffer_address: words[2] as u32 | (words[3] as u32) << 16,
+            firmware_version,
+            lut_version,
+        }
+    }
+}
+
+/// Waveform used to refresh an area
+///
+/// The values are the ones of most panels, the ones of the 6" panel (M641 LUT) differ and are
+/// translated by [`It8951::waveform`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum WaveformMode {
+    /// Clears the panel to white with a lot of flashing, used after power on
+    Init = 0,
+    /// Fast black and white refresh, from any gray level
+    Du = 1,
+    /// Full 16 gray levels refresh with flashing, the best quality
+    #[default]
+    Gc16 = 2,
+    /// 16 gray levels refresh without flashing, some ghosting
+    Gl16 = 3,
+    /// Like GL16 with ghosting reduction
+    Glr16 = 4,
+    /// Like GL16 with ghosting reduction
+    Gld16 = 5,
+    /// Fastest black and white refresh, only from black or white, for animations
+    A2 = 6,
+    /// Fast 4 gray levels refresh
+    Du4 = 7,
+}
+
+/// Pixel format of the image data loaded in the controller
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PixelFormat {
+    /// 2 bits per pixel, 4 gray levels
+    Bpp2 = 0,
+    /// 3 bits per pixel, 8 gray levels
+    Bpp3 = 1,
+    /// 4 bits per pixel, 16 gray levels
+    #[default]
+    Bpp4 = 2,
+    /// 8 bits per pixel, only the 4 upper bits are used
+    Bpp8 = 3,
+}
+
+impl PixelFormat {
+    /// Number of bits used by a pixel in the image data
+    pub fn bits_per_pixel(self) -> u32 {
+        match self {
+            PixelFormat::Bpp2 => 2,
+            PixelFormat::Bpp3 | PixelFormat::Bpp4 => 4,
+            PixelFormat::Bpp8 => 8,
+        }
+    }
+
+    /// Number of bytes of a line of `width` pixels
+    pub fn line_bytes(self, width: u32) -> usize {
+        (width * self.bits_per_pixel()) as usize / 8
+    }
+
+    /// Grows `area` horizontally so that its lines are whole 16 bit words, as needed to load it
+    pub fn aligned_area(self, area: Rect) -> Rect {
+        let pixels = 16 / self.bits_per_pixel();
+        let x = area.x / pixels * pixels;
+        let end = (area.x + area.w + pixels - 1) / pixels * pixels;
+        Rect::new(x, area.y, end - x, area.h)
+    }
+}
+
+/// Value of the load image argument: little endian words, format and no rotation
+fn load_image_argument(format: PixelFormat) -> u16 {
+    (format as u16) << 4
+}
+
+#[maybe_async_cfg::maybe(
+    sync(keep_self),
+    async(
+        feature = "async",
+        idents(
+            It8951,
+            SpiDevice(async = "AsyncSpiDevice"),
+            DelayNs(async = "AsyncDelayNs")
+        )
+    )
+)]
+/// IT8951 controller
+pub struct It8951<SPI, BUSY, RST, DELAY> {
+    busy: BUSY,
+    rst: RST,
+    info: DeviceInfo,
+    /// number of µs the idle loops should sleep on
+    delay_us: u32,
+    /// Longest wait for the controller in µs, forever if `None`
+    busy_timeout_us: Option<u32>,
+    /// Called before each poll of the controller with the time waited in µs
+    on_busy_wait: Option<fn(u32)>,
+    _spi: PhantomData<SPI>,
+    _delay: PhantomData<DELAY>,
+}
+
+#[maybe_async_cfg::maybe(
+    sync(keep_self),
+    async(
+        feature = "async",
+        idents(
+            It8951,
+            SpiDevice(async = "AsyncSpiDevice"),
+            DelayNs(async = "AsyncDelayNs")
+        )
+    )
+)]
+impl<SPI, BUSY, RST, DELAY> It8951<SPI, BUSY, RST, DELAY>
+where
+    SPI: SpiDevice,
+    BUSY: InputPin,
+    RST: OutputPin,
+    DELAY: DelayNs,
+{
+    /// Resets the controller, wakes it up and reads its device info
+    ///
+    /// `delay_us` is the time to sleep between two checks of the BUSY pin, 10µs by default.
+    pub async fn new(
+        spi: &mut SPI,
+        busy: BUSY,
+        rst: RST,
+        delay: &mut DELAY,
+        delay_us: Option<u32>,
+    ) -> Result<Self, EpdError<SPI::Error>> {
+        let mut it8951 = It8951 {
+            busy,
+            rst,
+            info: DeviceInfo::from_words(&[0; 20]),
+            delay_us: delay_us.unwrap_or(10),
+            busy_timeout_us: None,
+            on_busy_wait: None,
+            _spi: PhantomData,
+            _delay: PhantomData,
+        };
+        it8951.init(spi, delay).await?;
+        Ok(it8951)
+    }
+
+    async fn init(&mut self, spi: &mut SPI, delay: &mut DELAY) -> Result<(), EpdError<SPI::Error>> {
+        self.reset(delay).await?;
+        self.command(spi, delay, Command::SysRun).await?;
+        self.info = self.read_device_info(spi, delay).await?;
+        // 2 bytes per word when loading images
+        self.write_register(spi, delay, Register::I80Cpcr, 0x0001)
+            .await
+    }
+
+    /// Hardware reset of the controller
+    pub async fn reset(&mut self, delay: &mut DELAY) -> Result<(), EpdError<SPI::Error>> {
+        self.rst.set_low().map_err(EpdError::pin)?;
+        delay.delay_ms(10).await;
+        self.rst.set_high().map_err(EpdError::pin)?;
+        delay.delay_ms(10).await;
+        self.wait_until_ready(delay).await
+    }
+
+    /// Sets the longest wait for the controller, `None` waiting forever (the default)
+    ///
+    /// The waits for the controller return [`EpdError::BusyTimeout`] after `timeout_us`. The
+    /// waits for the end of the refreshes in
+    /// [`wait_display_ready`](It8951::wait_display_ready) are limited as well.
+    pub fn set_busy_timeout(&mut self, timeout_us: Option<u32>) {
+        self.busy_timeout_us = timeout_us;
+    }
+
+    /// Sets the delay between the polls of the controller, `0` polling without delay
+    pub fn set_busy_poll(&mut self, interval_us: u32) {
+        self.delay_us = interval_us;
+    }
+
+    /// Sets a function called before each poll of the controller with the µs waited so far,
+    /// `None` (the default) calling nothing, e.g. to feed a watchdog during the refreshes
+    pub fn set_on_busy_wait(&mut self, hook: Option<fn(u32)>) {
+        self.on_busy_wait = hook;
+    }
+
+    /// Device info read during initialization
+    pub fn device_info(&self) -> &DeviceInfo {
+        &self.info
+    }
+
+    /// Reads the device info from the controller
+    pub async fn read_device_info(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+    ) -> Result<DeviceInfo, EpdError<SPI::Error>> {
+        self.command(spi, delay, Command::GetDeviceInfo).await?;
+        let mut words = [0; 20];
+        self.read_words(spi, delay, &mut words).await?;
+        Ok(DeviceInfo::from_words(&words))
+    }
+
+    /// Leaves sleep or standby mode
+    pub async fn wake_up(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.command(spi, delay, Command::SysRun).await
+    }
+
+    /// Enters standby mode
+    pub async fn standby(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.command(spi, delay, Command::Standby).await
+    }
+
+    /// Enters sleep mode, the image memory is kept
+    pub async fn sleep(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.command(spi, delay, Command::Sleep).await
+    }
+
+    /// Reads the VCOM voltage, in mV (1500 for -1.50V)
+    pub async fn vcom(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+    ) -> Result<u16, EpdError<SPI::Error>> {
+        self.command_with_args(spi, delay, Command::Vcom, &[0])
+            .await?;
+        let mut vcom = [0];
+        self.read_words(spi, delay, &mut vcom).await?;
+        Ok(vcom[0])
+    }
+
+    /// Sets the VCOM voltage, in mV (1500 for -1.50V)
+    ///
+    /// The right value depends on the panel and is printed on its ribbon cable.
+    pub async fn set_vcom(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+        vcom: u16,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.command_with_args(spi, delay, Command::Vcom, &[1, vcom])
+            .await
+    }
+
+    /// Starts loading an image in `area` of the controller memory
+    ///
+    /// The image is then sent with one or more calls to
+    /// [`load_image_data`](It8951::load_image_data), and the loading must be terminated with
+    /// [`load_image_end`](It8951::load_image_end). This allows to load an image larger than the
+    /// memory of the host, band by band.
+    pub async fn load_image_start(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+        format: PixelFormat,
+        area: Rect,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        let address = self.info.image_buffer_address;
+        self.write_register(spi, delay, Register::LisarHigh, (address >> 16) as u16)
+            .await?;
+        self.write_register(spi, delay, Register::Lisar, address as u16)
+            .await?;
+        self.command_with_args(
+            spi,
+            delay,
+            Command::LoadImageArea,
+            &[
+                load_image_argument(format),
+                area.x as u16,
+                area.y as u16,
+                area.w as u16,
+                area.h as u16,
+            ],
+        )
+        .await
+    }
+
+    /// Sends image data after [`load_image_start`](It8951::load_image_start)
+    ///
+    /// Pixels are packed like in the other buffers of this crate: line after line, the first
+    /// pixel in the most significant bits of a byte. Every line must use a whole number of
+    /// 16 bit words, i.e. an even number of bytes.
+    pub async fn load_image_data(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+        data: &[u8],
+    ) -> Result<(), EpdError<SPI::Error>> {
+        let mut chunk = [0; CHUNK_SIZE];
+        for data in data.chunks(CHUNK_SIZE) {
+            // the controller reads little endian words
+            for (swapped, pair) in chunk.chunks_mut(2).zip(data.chunks(2)) {
+                swapped[0] = pair.get(1).copied().unwrap_or(0);
+                swapped[1] = pair[0];
+            }
+            let len = (data.len() + 1) / 2 * 2;
+            self.wait_until_ready(delay).await?;
+            spi.transaction(&mut [
+                Operation::Write(&Preamble::WriteData.bytes()),
+                Operation::Write(&chunk[..len]),
+            ])
+            .await
+            .map_err(EpdError::Spi)?;
+        }
+        Ok(())
+    }
+
+    /// Terminates the loading of an image
+    pub async fn load_image_end(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.command(spi, delay, Command::LoadImageEnd).await
+    }
+
+    /// Loads a whole image in `area` of the controller memory
+    ///
+    /// See [`load_image_data`](It8951::load_image_data) for the format of `data`.
+    pub async fn load_image_area(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+        format: PixelFormat,
+        area: Rect,
+        data: &[u8],
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.load_image_start(spi, delay, format, area).await?;
+        self.load_image_data(spi, delay, data).await?;
+        self.load_image_end(spi, delay).await
+    }
+
+    /// Loads an image in `area` band by band, rendered in `band` by `render`
+    ///
+    /// `band` holds as many lines of `area` as possible, at least one or [`EpdError::BufferSize`]
+    /// is returned. For each band, `render` is called with the part of `area` it covers and the
+    /// pixels to fill, in the format of [`load_image_data`](It8951::load_image_data).
+    pub async fn load_image_banded<F>(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+        format: PixelFormat,
+        area: Rect,
+        band: &mut [u8],
+        mut render: F,
+    ) -> Result<(), EpdError<SPI::Error>>
+    where
+        F: FnMut(Rect, &mut [u8]),
+    {
+        let line_bytes = format.line_bytes(area.w);
+        let parts = bands(area, line_bytes, band.len())?;
+
+        self.load_image_start(spi, delay, format, area).await?;
+        for part in parts {
+            let pixels = &mut band[..part.h as usize * line_bytes];
+            render(part, pixels);
+            self.load_image_data(spi, delay, pixels).await?;
+        }
+        self.load_image_end(spi, delay).await
+    }
+
+    /// Refreshes `area` of the panel from the controller memory with a waveform `mode`
+    ///
+    /// Waits for the previous refreshes to be done first.
+    pub async fn display_area(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+        area: Rect,
+        mode: WaveformMode,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.wait_display_ready(spi, delay).await?;
+        self.command_with_args(
+            spi,
+            delay,
+            Command::DisplayArea,
+            &[
+                area.x as u16,
+                area.y as u16,
+                area.w as u16,
+                area.h as u16,
+                self.waveform(mode),
+            ],
+        )
+        .await
+    }
+
+    /// Number of the waveform `mode` in the LUT of the panel
+    pub fn waveform(&self, mode: WaveformMode) -> u16 {
+        // the 6" panel has no GL16 variants, A2 comes right after them
+        if self.info.lut_version.starts_with(b"M641") && mode == WaveformMode::A2 {
+            return 4;
+        }
+        mode as u16
+    }
+
+    /// Waits until all the refreshes are done
+    pub async fn wait_display_ready(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        let mut waited_us = 0;
+        while self.read_register(spi, delay, Register::Lutafsr).await? != 0 {
+            waited_us = self.waited(waited_us)?;
+            if self.delay_us > 0 {
+                delay.delay_us(self.delay_us).await;
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether the controller is busy and can't receive a transfer
+    pub fn is_busy(&mut self) -> Result<bool, EpdError<SPI::Error>> {
+        // HRDY is low while busy
+        self.busy.is_low().map_err(EpdError::pin)
+    }
+
+    /// Waits until the controller can receive a transfer
+    pub async fn wait_until_ready(
+        &mut self,
+        delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        let mut waited_us = 0;
+        while self.busy.is_low().map_err(EpdError::pin)? {
+            waited_us = self.waited(waited_us)?;
+            if self.delay_us > 0 {
+                delay.delay_us(self.delay_us).await;
+            }
+        }
+        Ok(())
+    }
+
+    /// Counts the time waited for the controller since the `waited_us` of the previous poll,
+    /// and calls the busy wait hook
+    ///
+    /// Without delay between the polls, each poll counts as 1µs.
+    fn waited(&self, waited_us: u32) -> Result<u32, EpdError<SPI::Error>> {
+        match self.busy_timeout_us {
+            Some(timeout_us) if waited_us >= timeout_us => Err(EpdError::BusyTimeout),
+            _ => {
+                if let Some(hook) = self.on_busy_wait {
+                    hook(waited_us);
+                }
+                Ok(waited_us.saturating_add(self.delay_us.max(1)))
+            }
+        }
+    }
+
+    async fn command(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+        command: Command,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.wait_until_ready(delay).await?;
+        spi.transaction(&mut [
+            Operation::Write(&Preamble::Command.bytes()),
+            Operation::Write(&command.address().to_be_bytes()),
+        ])
+        .await
+        .map_err(EpdError::Spi)
+    }
+
+    async fn write_words(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+        words: &[u16],
+    ) -> Result<(), EpdError<SPI::Error>> {
+        for word in words {
+            self.wait_until_ready(delay).await?;
+            spi.transaction(&mut [
+                Operation::Write(&Preamble::WriteData.bytes()),
+                Operation::Write(&word.to_be_bytes()),
+            ])
+            .await
+            .map_err(EpdError::Spi)?;
+        }
+        Ok(())
+    }
+
+    async fn read_words(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+        words: &mut [u16],
+    ) -> Result<(), EpdError<SPI::Error>> {
+        // the longest read is the device info
+        let mut bytes = [0; 40];
+        let bytes = &mut bytes[..2 * words.len()];
+        self.wait_until_ready(delay).await?;
+        spi.transaction(&mut [
+            Operation::Write(&Preamble::ReadData.bytes()),
+            Operation::Read(&mut [0; 2]),
+            Operation::Read(bytes),
+        ])
+        .await
+        .map_err(EpdError::Spi)?;
+        for (word, bytes) in words.iter_mut().zip(bytes.chunks(2)) {
+            *word = u16::from_be_bytes([bytes[0], bytes[1]]);
+        }
+        Ok(())
+    }
+
+    async fn command_with_args(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+        command: Command,
+        args: &[u16],
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.command(spi, delay, command).await?;
+        self.write_words(spi, delay, args).await
+    }
+
+    async fn read_register(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+        register: Register,
+    ) -> Result<u16, EpdError<SPI::Error>> {
+        self.command_with_args(spi, delay, Command::RegRead, &[register.address()])
+            .await?;
+        let mut value = [0];
+        self.read_words(spi, delay, &mut value).await?;
+        Ok(value[0])
+    }
+
+    async fn write_register(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+        register: Register,
+        value: u16,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.command_with_args(spi, delay, Command::RegWrite, &[register.address(), value])
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn device_info() {
+        let mut words = [0; 20];
+        words[..4].copy_from_slice(&[1448, 1072, 0x36e0, 0x0012]);
+        words[4..7].copy_from_slice(&[
+            u16::from_be_bytes(*b"SW"),
+            u16::from_be_bytes(*b"v_"),
+            0x3000,
+        ]);
+        let info = DeviceInfo::from_words(&words);
+        assert_eq!((info.panel_width, info.panel_height), (1448, 1072));
+        assert_eq!(info.image_buffer_address, 0x0012_36e0);
+        assert_eq!(&info.firmware_version[..6], b"SWv_0\0");
+        assert_eq!(info.lut_version, [0; 16]);
+    }
+
+    #[test]
+    fn pixel_format() {
+        assert_eq!(load_image_argument(PixelFormat::Bpp4), 0x0020);
+        assert_eq!(load_image_argument(PixelFormat::Bpp8), 0x0030);
+        assert_eq!(PixelFormat::Bpp3.bits_per_pixel(), 4);
+        assert_eq!(PixelFormat::Bpp4.line_bytes(8), 4);
+    }
+
+    #[test]
+    fn pack() {
+        let mut packed = [0; 3];
+        pack_4bpp(&[0x00, 0xff, 0x8f, 0x70, 0xa0], &mut packed);
+        assert_eq!(packed, [0x0f, 0x87, 0xa0]);
+    }
+
+    #[test]
+    fn aligned_area() {
+        let area = Rect::new(5, 7, 10, 3);
+        assert_eq!(PixelFormat::Bpp4.aligned_area(area), Rect::new(4, 7, 12, 3));
+        assert_eq!(PixelFormat::Bpp2.aligned_area(area), Rect::new(0, 7, 16, 3));
+        assert_eq!(PixelFormat::Bpp8.aligned_area(area), Rect::new(4, 7, 12, 3));
+        let aligned = Rect::new(8, 0, 16, 1);
+        assert_eq!(PixelFormat::Bpp4.aligned_area(aligned), aligned);
+    }
+}
@@ -12,7 +12,7 @@
 //!
 //!```rust, no_run
 //!# use embedded_hal_mock::eh1::*;
-//!# fn main() -> Result<(), embedded_hal::spi::ErrorKind> {
+//!# fn main() -> Result<(), epd_waveshare::error::EpdError<embedded_hal::spi::ErrorKind>> {
 //!use embedded_graphics::{
 //!    pixelcolor::BinaryColor::On as Black, prelude::*, primitives::{Line, PrimitiveStyle},
 //!};
@@ -63,56 +63,213 @@
 #![no_std]
 #![deny(missing_docs)]
 
+#[cfg(feature = "std")]
+extern crate std;
+
 #[cfg(feature = "graphics")]
 pub mod graphics;
 
+#[cfg(feature = "graphics")]
+pub mod convert;
+
+#[cfg(feature = "graphics")]
+pub mod epd_display;
+
+pub mod any_epd;
+
+pub mod builder;
+
+pub mod owned;
+
+pub mod pin;
+
+pub mod three_wire;
+
+#[cfg(feature = "std")]
+pub mod preview;
+
+#[cfg(feature = "std")]
+pub mod export;
+
+#[cfg(feature = "text")]
+pub mod terminal;
+
+#[cfg(feature = "text")]
+pub mod digits;
+
+#[cfg(feature = "text")]
+pub mod antialias;
+
 mod traits;
 
+#[cfg(feature = "eh02")]
+pub mod eh02;
+
+#[cfg(feature = "display-interface")]
+pub mod di;
+
 pub mod color;
 
+pub mod error;
+
 pub mod rect;
 
+pub mod frame_store;
+
+pub mod frame_diff;
+
+pub mod diagnostics;
+
+pub mod strict;
+
+pub mod refresh_policy;
+
+pub mod waveform;
+
+pub mod recovery;
+
+pub mod parallel;
+
+pub mod scan;
+
 /// Interface for the physical connection between display and the controlling device
 mod interface;
 
+mod ram_addressing;
+
+#[cfg(test)]
+mod test_utils;
+
+#[cfg(feature = "epd1in02")]
 pub mod epd1in02;
+#[cfg(feature = "epd1in54")]
 pub mod epd1in54;
+#[cfg(feature = "epd1in54_v2")]
 pub mod epd1in54_v2;
+#[cfg(feature = "epd1in54_v3")]
 pub mod epd1in54_v3;
+#[cfg(feature = "epd1in54b")]
 pub mod epd1in54b;
+#[cfg(feature = "epd1in54c")]
 pub mod epd1in54c;
+#[cfg(any(feature = "epd2in13_v2", feature = "epd2in13_v3"))]
 pub mod epd2in13_v2;
+#[cfg(feature = "epd2in13_v4")]
+pub mod epd2in13_v4;
+#[cfg(feature = "epd2in13b_v4")]
 pub mod epd2in13b_v4;
+#[cfg(feature = "epd2in13bc")]
 pub mod epd2in13bc;
+#[cfg(feature = "epd2in13d")]
+pub mod epd2in13d;
+#[cfg(feature = "epd2in36g")]
+pub mod epd2in36g;
+#[cfg(feature = "epd2in66")]
+pub mod epd2in66;
+#[cfg(feature = "epd2in66b")]
 pub mod epd2in66b;
+#[cfg(feature = "epd2in7")]
 pub mod epd2in7;
+#[cfg(feature = "epd2in7_v2")]
 pub mod epd2in7_v2;
+#[cfg(feature = "epd2in7b")]
 pub mod epd2in7b;
+#[cfg(feature = "epd2in7b_v2")]
+pub mod epd2in7b_v2;
+#[cfg(feature = "epd2in9")]
 pub mod epd2in9;
+#[cfg(feature = "epd2in9_v2")]
 pub mod epd2in9_v2;
+#[cfg(feature = "epd2in9b_v4")]
 pub mod epd2in9b_v4;
+#[cfg(feature = "epd2in9bc")]
 pub mod epd2in9bc;
+#[cfg(feature = "epd2in9d")]
 pub mod epd2in9d;
+#[cfg(feature = "epd3in0g")]
+pub mod epd3in0g;
+#[cfg(feature = "epd3in52")]
+pub mod epd3in52;
+#[cfg(feature = "epd3in7")]
 pub mod epd3in7;
+#[cfg(feature = "epd4in01f")]
+pub mod epd4in01f;
+#[cfg(feature = "epd4in2")]
 pub mod epd4in2;
+#[cfg(feature = "epd4in26")]
+pub mod epd4in26;
+#[cfg(feature = "epd5in65f")]
 pub mod epd5in65f;
+#[cfg(feature = "epd5in79")]
+pub mod epd5in79;
+#[cfg(feature = "epd5in83_v2")]
 pub mod epd5in83_v2;
+#[cfg(feature = "epd5in83b_v2")]
 pub mod epd5in83b_v2;
+#[cfg(feature = "epd6in0")]
+pub mod epd6in0;
+#[cfg(feature = "epd7in3f")]
 pub mod epd7in3f;
+#[cfg(feature = "epd7in5")]
 pub mod epd7in5;
+#[cfg(feature = "epd7in5_hd")]
 pub mod epd7in5_hd;
+#[cfg(feature = "epd7in5_v2")]
 pub mod epd7in5_v2;
+#[cfg(feature = "epd7in5b_v2")]
 pub mod epd7in5b_v2;
+#[cfg(feature = "epd7in5b_v2")]
 pub use epd7in5b_v2 as epd7in5b_v3;
+#[cfg(feature = "epd10in2")]
+pub mod epd10in2;
+#[cfg(feature = "epd10in3")]
+pub mod epd10in3;
+#[cfg(feature = "epd12in48b_v2")]
 pub mod epd12in48b_v2;
+#[cfg(feature = "epd13in3e")]
+pub mod epd13in3e;
+#[cfg(feature = "epd13in3k")]
+pub mod epd13in3k;
+#[cfg(feature = "epd7in8")]
+pub mod epd7in8;
+#[cfg(feature = "epd9in7")]
+pub mod epd9in7;
+#[cfg(feature = "it8951")]
+pub mod it8951;
+#[cfg(feature = "weact2in9")]
+pub mod weact2in9;
+#[cfg(feature = "weact4in2")]
+pub mod weact4in2;
+
+#[cfg(feature = "magtag2in9")]
+pub mod magtag2in9;
+
+#[cfg(feature = "badger2040")]
+pub mod badger2040;
+
+#[cfg(feature = "lilygo4in7")]
+pub mod lilygo4in7;
+
+pub mod inky;
+
+pub mod controller;
 
 pub(crate) mod type_a;
 
 /// Includes everything important besides the chosen Display
 pub mod prelude {
-    pub use crate::color::{Color, OctColor, TriColor};
+    pub use crate::color::{Color, Gray16, Gray4, OctColor, QuadColor, SpectraColor, TriColor};
+    pub use crate::error::EpdError;
     pub use crate::traits::{
-        QuickRefresh, RefreshLut, WaveshareDisplay, WaveshareThreeColorDisplay,
+        AnimationRefresh, Gray4Refresh, InterfaceConfig, NonBlockingRefresh, PreloadFrame,
+        QuickRefresh, RefreshLut, ResetTiming, WaveshareDisplay, WaveshareThreeColorDisplay,
+    };
+
+    #[cfg(feature = "async")]
+    pub use crate::traits::{
+        AnimationRefreshAsync, AsyncBusyPin, Gray4RefreshAsync, NonBlockingRefreshAsync,
+        PreloadFrameAsync, QuickRefreshAsync, WaveshareDisplayAsync,
+        WaveshareThreeColorDisplayAsync,
     };
 
     pub use crate::SPI_MODE;
@@ -134,6 +291,51 @@ pub const fn buffer_len(width: usize, height: usize) -> usize {
     (width + 7) / 8 * height
 }
 
+/// Buffers whose length is checked at compile time
+///
+/// The drivers take their frames as slices and most of them only check their length at runtime,
+/// if at all: a frame of the wrong size shows a shifted image. When the frame is an array,
+/// [`checked`](BufferLen::checked) turns it into the slice to pass to the driver and fails to
+/// compile if its length isn't `LEN`.
+///
+///```rust, no_run
+///# use embedded_hal_mock::eh1::*;
+///# fn main() -> Result<(), epd_waveshare::error::EpdError<embedded_hal::spi::ErrorKind>> {
+///use epd_waveshare::{buffer_len, epd2in9_v2::*, prelude::*, BufferLen};
+///#
+///# let expectations = [];
+///# let mut spi = spi::Mock::new(&expectations);
+///# let expectations = [];
+///# let busy_in = digital::Mock::new(&expectations);
+///# let dc = digital::Mock::new(&expectations);
+///# let rst = digital::Mock::new(&expectations);
+///# let mut delay = delay::NoopDelay::new();
+///
+///type Frame = BufferLen<{ buffer_len(WIDTH as usize, HEIGHT as usize) }>;
+///
+///let mut epd = Epd2in9::new(&mut spi, busy_in, dc, rst, &mut delay, None)?;
+///let frame = [0xff; WIDTH as usize / 8 * HEIGHT as usize];
+///epd.update_frame(&mut spi, Frame::checked(&frame), &mut delay)?;
+///# Ok(())
+///# }
+///```
+pub struct BufferLen<const LEN: usize>;
+
+impl<const LEN: usize> BufferLen<LEN> {
+    /// Returns `buffer` as a slice, failing to compile if it doesn't have `LEN` bytes
+    pub fn checked<const N: usize>(buffer: &[u8; N]) -> &[u8] {
+        #[allow(clippy::let_unit_value)]
+        let () = LenCheck::<LEN, N>::EQUAL;
+        buffer
+    }
+}
+
+struct LenCheck<const LEN: usize, const N: usize>;
+
+impl<const LEN: usize, const N: usize> LenCheck<LEN, N> {
+    const EQUAL: () = assert!(N == LEN, "the buffer doesn't have the expected length");
+}
+
 use embedded_hal::spi::{Mode, Phase, Polarity};
 
 /// SPI mode -
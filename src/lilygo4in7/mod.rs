@@ -0,0 +1,250 @@
+//! A Driver for the 4.7" parallel E-Paper Display (ED047TC1) of the LilyGo T5 4.7"
+//!
+//! The panel has no controller: the MCU drives its rows over a [`ParallelBus`], one frame after
+//! the other, as done by EPDiy. 16 gray levels are drawn in 15 frames on a white panel, a pixel
+//! being darkened during as many frames as it is darker than white. The frames get longer as
+//! they go, the first ones moving the particles much more than the last ones.
+//!
+//! The image has to be drawn on a white panel: [`clear`](LilyGo4in7::clear) it first.
+//!
+//!```rust, no_run
+//!# fn main() -> Result<(), epd_waveshare::error::EpdError<()>> {
+//!use embedded_graphics::{prelude::*, primitives::{PrimitiveStyle, Rectangle}};
+//!use epd_waveshare::{lilygo4in7::*, parallel::ParallelBus, prelude::*};
+//!
+//!// The bus of the board, e.g. over the LCD peripheral of the ESP32-S3
+//!struct Bus;
+//!
+//!impl ParallelBus for Bus {
+//!    type Error = ();
+//!    // ...
+//!#    fn power_on(&mut self) -> Result<(), ()> { Ok(()) }
+//!#    fn power_off(&mut self) -> Result<(), ()> { Ok(()) }
+//!#    fn start_frame(&mut self) -> Result<(), ()> { Ok(()) }
+//!#    fn write_row(&mut self, _data: &[u8], _output_us: u32) -> Result<(), ()> { Ok(()) }
+//!#    fn skip_row(&mut self) -> Result<(), ()> { Ok(()) }
+//!#    fn end_frame(&mut self) -> Result<(), ()> { Ok(()) }
+//!}
+//!
+//!let mut epd = LilyGo4in7::new(Bus);
+//!# let mut display = DisplayLilyGo4in7::default();
+//!// let mut display = Box::new(DisplayLilyGo4in7::default());
+//!display.clear(Gray16::WHITE).ok();
+//!let _ = Rectangle::new(Point::new(100, 100), Size::new(200, 100))
+//!    .into_styled(PrimitiveStyle::with_fill(Gray16::new(8)))
+//!    .draw(&mut display);
+//!
+//!epd.power_on()?;
+//!epd.clear(4)?;
+//!epd.draw_frame(display.buffer())?;
+//!epd.power_off()?;
+//!# Ok(())
+//!# }
+//!```
+
+use crate::error::EpdError;
+use crate::parallel::{ParallelBus, DARKEN, LIGHTEN, NO_OP};
+use crate::rect::Rect;
+
+#[cfg(feature = "graphics")]
+use crate::color::Gray16;
+
+/// Width of the display in pixels
+pub const WIDTH: u32 = 960;
+/// Height of the display in pixels
+pub const HEIGHT: u32 = 540;
+
+/// Bytes of a row sent over the bus, 2 bits per pixel
+const ROW_BYTES: usize = WIDTH as usize / 4;
+
+/// Length of the frames drawing the 16 gray levels, in microseconds per row (from EPDiy)
+const GRAY_FRAMES_US: [u32; 15] = [
+    30, 30, 20, 20, 30, 30, 30, 40, 40, 50, 50, 50, 100, 200, 300,
+];
+
+/// Length of the frames of [`clear`](LilyGo4in7::clear), in microseconds per row
+const CLEAR_FRAME_US: u32 = 50;
+
+/// Display with Fullsize buffer of 16 gray levels, 253KB: allocate it on the heap
+#[cfg(feature = "graphics")]
+pub type DisplayLilyGo4in7 = crate::graphics::Display<
+    WIDTH,
+    HEIGHT,
+    false,
+    { crate::graphics::display_len::<Gray16>(WIDTH, HEIGHT) },
+    Gray16,
+>;
+
+/// LilyGo T5 4.7" driver
+///
+/// The errors of the bus are returned as [`EpdError::Spi`].
+pub struct LilyGo4in7<BUS> {
+    /// Row and frame signals of the panel
+    bus: BUS,
+}
+
+impl<BUS: ParallelBus> LilyGo4in7<BUS> {
+    /// Creates the driver, the panel stays powered down
+    pub fn new(bus: BUS) -> Self {
+        LilyGo4in7 { bus }
+    }
+
+    /// Powers the panel up, before drawing
+    pub fn power_on(&mut self) -> Result<(), EpdError<BUS::Error>> {
+        self.bus.power_on().map_err(EpdError::Spi)
+    }
+
+    /// Powers the panel down, the image stays
+    pub fn power_off(&mut self) -> Result<(), EpdError<BUS::Error>> {
+        self.bus.power_off().map_err(EpdError::Spi)
+    }
+
+    /// Clears the panel to white, flashing it `cycles` times in black and white
+    ///
+    /// A few cycles (about 4) remove the ghosts of the previous images.
+    pub fn clear(&mut self, cycles: u32) -> Result<(), EpdError<BUS::Error>> {
+        for _ in 0..cycles {
+            self.fill_frames(DARKEN, 10)?;
+            self.fill_frames(LIGHTEN, 10)?;
+        }
+        Ok(())
+    }
+
+    /// Draws a full frame of 16 gray levels, packed by 2 pixels in a byte as in a
+    /// `DisplayLilyGo4in7`, on the white panel
+    pub fn draw_frame(&mut self, buffer: &[u8]) -> Result<(), EpdError<BUS::Error>> {
+        self.draw_area(buffer, Rect::new(0, 0, WIDTH, HEIGHT))
+    }
+
+    /// Draws the pixels of `area` only, `buffer` being the full frame
+    ///
+    /// The rows above and below the area are skipped, which makes the frames much shorter.
+    pub fn draw_area(&mut self, buffer: &[u8], area: Rect) -> Result<(), EpdError<BUS::Error>> {
+        EpdError::check_buffer(buffer, WIDTH as usize * HEIGHT as usize / 2)?;
+        let area = area.intersect(Rect::new(0, 0, WIDTH, HEIGHT));
+        if area.is_empty() {
+            return Ok(());
+        }
+
+        let mut row = [0u8; ROW_BYTES];
+        for (frame, &output_us) in GRAY_FRAMES_US.iter().enumerate() {
+            self.bus.start_frame().map_err(EpdError::Spi)?;
+            for y in 0..HEIGHT {
+                if y < area.y || y >= area.y + area.h {
+                    self.bus.skip_row().map_err(EpdError::Spi)?;
+                    continue;
+                }
+                let start = y as usize * WIDTH as usize / 2;
+                gray_row(
+                    &buffer[start..start + WIDTH as usize / 2],
+                    &mut row,
+                    frame as u8,
+                    area.x,
+                    area.x + area.w,
+                );
+                self.bus.write_row(&row, output_us).map_err(EpdError::Spi)?;
+            }
+            self.bus.end_frame().map_err(EpdError::Spi)?;
+        }
+        Ok(())
+    }
+
+    /// Drives every pixel the same way during `frames` frames
+    fn fill_frames(&mut self, action: u8, frames: u32) -> Result<(), EpdError<BUS::Error>> {
+        let row = [action * 0b0101_0101; ROW_BYTES];
+        for _ in 0..frames {
+            self.bus.start_frame().map_err(EpdError::Spi)?;
+            for _ in 0..HEIGHT {
+                self.bus
+                    .write_row(&row, CLEAR_FRAME_US)
+                    .map_err(EpdError::Spi)?;
+            }
+            self.bus.end_frame().map_err(EpdError::Spi)?;
+        }
+        Ok(())
+    }
+}
+
+/// Fills `row` with the actions of frame `frame` for the pixels of `pixels` (2 per byte)
+///
+/// Only the columns from `start` to `end` (excluded) are driven. A pixel of level `l` is darkened
+/// during the frames `0..15 - l`.
+fn gray_row(pixels: &[u8], row: &mut [u8], frame: u8, start: u32, end: u32) {
+    for (i, byte) in row.iter_mut().enumerate() {
+        *byte = (0..4).fold(0, |byte, p| {
+            let x = i * 4 + p;
+            let level = (pixels[x / 2] >> if x % 2 == 0 { 4 } else { 0 }) & 0x0f;
+            let driven = (x as u32) >= start && (x as u32) < end;
+            let action = if driven && frame < 15 - level {
+                DARKEN
+            } else {
+                NO_OP
+            };
+            byte << 2 | action
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn epd_size() {
+        assert_eq!(WIDTH, 960);
+        assert_eq!(HEIGHT, 540);
+    }
+
+    #[test]
+    fn frame_size() {
+        struct Bus;
+
+        impl ParallelBus for Bus {
+            type Error = ();
+            fn power_on(&mut self) -> Result<(), ()> {
+                Ok(())
+            }
+            fn power_off(&mut self) -> Result<(), ()> {
+                Ok(())
+            }
+            fn start_frame(&mut self) -> Result<(), ()> {
+                Err(())
+            }
+            fn write_row(&mut self, _data: &[u8], _output_us: u32) -> Result<(), ()> {
+                Ok(())
+            }
+            fn skip_row(&mut self) -> Result<(), ()> {
+                Ok(())
+            }
+            fn end_frame(&mut self) -> Result<(), ()> {
+                Ok(())
+            }
+        }
+
+        let mut epd = LilyGo4in7::new(Bus);
+        assert_eq!(
+            epd.draw_frame(&[0; 16]),
+            Err(EpdError::BufferSize {
+                expected: 259_200,
+                actual: 16
+            })
+        );
+        assert_eq!(epd.clear(1), Err(EpdError::Spi(())));
+    }
+
+    #[test]
+    fn gray_frames() {
+        // black, level 13, white, black
+        let pixels = [0x0d, 0xf0];
+        let mut row = [0; 1];
+        gray_row(&pixels, &mut row, 0, 0, 4);
+        assert_eq!(row, [0b01_01_00_01]);
+        gray_row(&pixels, &mut row, 2, 0, 4);
+        assert_eq!(row, [0b01_00_00_01]);
+        gray_row(&pixels, &mut row, 14, 0, 4);
+        assert_eq!(row, [0b01_00_00_01]);
+        // outside of the area
+        gray_row(&pixels, &mut row, 0, 1, 3);
+        assert_eq!(row, [0b00_01_00_00]);
+    }
+}
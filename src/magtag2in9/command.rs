@@ -0,0 +1,52 @@
+//! SPI Commands for the IL0373 controller of the Adafruit 2.9" grayscale display
+use crate::traits;
+
+#[allow(dead_code)]
+#[derive(Copy, Clone)]
+pub(crate) enum Command {
+    /// Resolution, LUT source (OTP or registers), gate and source scan directions
+    PanelSetting = 0x00,
+    /// Internal or external power and the VDH/VDL levels
+    PowerSetting = 0x01,
+    /// Turns off the charge pump, the drivers and the temperature sensor, the registers are kept
+    PowerOff = 0x02,
+    /// Turns on the power, BUSY_N is low until it's done
+    PowerOn = 0x04,
+    /// Soft start of the booster phases A, B and C
+    BoosterSoftStart = 0x06,
+    /// Enters deep sleep, with the check code 0xA5. Only a hardware reset wakes the chip up
+    DeepSleep = 0x07,
+    /// "OLD" data in B/W mode, the high bit of the gray levels in grayscale mode
+    DataStartTransmission1 = 0x10,
+    /// Stops the data transmission
+    DataStop = 0x11,
+    /// Refreshes the display from the SRAM with the LUT, BUSY_N is low until it's done
+    DisplayRefresh = 0x12,
+    /// "NEW" data in B/W mode, the low bit of the gray levels in grayscale mode
+    DataStartTransmission2 = 0x13,
+    /// VCOM LUT, 7 groups of 6 bytes and 2 bytes of frame timing
+    LutForVcom = 0x20,
+    /// White-to-White LUT, 7 groups of 6 bytes
+    LutWhiteToWhite = 0x21,
+    /// Black-to-White LUT, 7 groups of 6 bytes
+    LutBlackToWhite = 0x22,
+    /// White-to-Black LUT, 7 groups of 6 bytes
+    LutWhiteToBlack = 0x23,
+    /// Black-to-Black LUT, 7 groups of 6 bytes
+    LutBlackToBlack = 0x24,
+    /// Frame rate
+    PllControl = 0x30,
+    /// Border output and the interval between VCOM and the data
+    VcomAndDataIntervalSetting = 0x50,
+    /// Horizontal and vertical resolution
+    ResolutionSetting = 0x61,
+    /// VCOM DC level
+    VcmDcSetting = 0x82,
+}
+
+impl traits::Command for Command {
+    /// Returns the address of the command
+    fn address(self) -> u8 {
+        self as u8
+    }
+}
@@ -0,0 +1,59 @@
+//! 4 gray levels waveform of the IL0373 2.9" panel, from the Adafruit EPD library
+//!
+//! Each LUT has 7 groups of 6 bytes: the voltage levels of 4 phases, their lengths in frames
+//! and the repeat count of the group. The VCOM LUT has 2 more bytes.
+
+pub(crate) const LUT_VCOM: [u8; 44] = [
+    0x00, 0x0A, 0x00, 0x00, 0x00, 0x01, //
+    0x60, 0x14, 0x14, 0x00, 0x00, 0x01, //
+    0x00, 0x14, 0x00, 0x00, 0x00, 0x01, //
+    0x00, 0x13, 0x0A, 0x01, 0x00, 0x01, //
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, //
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, //
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, //
+    0x00, 0x00,
+];
+
+/// White (both planes set)
+pub(crate) const LUT_WW: [u8; 42] = [
+    0x40, 0x0A, 0x00, 0x00, 0x00, 0x01, //
+    0x90, 0x14, 0x14, 0x00, 0x00, 0x01, //
+    0x10, 0x14, 0x0A, 0x00, 0x00, 0x01, //
+    0xA0, 0x13, 0x01, 0x00, 0x00, 0x01, //
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, //
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, //
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, //
+];
+
+/// Dark gray (only the low plane set)
+pub(crate) const LUT_BW: [u8; 42] = [
+    0x40, 0x0A, 0x00, 0x00, 0x00, 0x01, //
+    0x90, 0x14, 0x14, 0x00, 0x00, 0x01, //
+    0x00, 0x14, 0x0A, 0x00, 0x00, 0x01, //
+    0x99, 0x0C, 0x01, 0x03, 0x04, 0x01, //
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, //
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, //
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, //
+];
+
+/// Light gray (only the high plane set)
+pub(crate) const LUT_WB: [u8; 42] = [
+    0x40, 0x0A, 0x00, 0x00, 0x00, 0x01, //
+    0x90, 0x14, 0x14, 0x00, 0x00, 0x01, //
+    0x00, 0x14, 0x0A, 0x00, 0x00, 0x01, //
+    0x99, 0x0B, 0x04, 0x04, 0x01, 0x01, //
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, //
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, //
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, //
+];
+
+/// Black (no plane set)
+pub(crate) const LUT_BB: [u8; 42] = [
+    0x80, 0x0A, 0x00, 0x00, 0x00, 0x01, //
+    0x90, 0x14, 0x14, 0x00, 0x00, 0x01, //
+    0x20, 0x14, 0x0A, 0x00, 0x00, 0x01, //
+    0x50, 0x13, 0x01, 0x00, 0x00, 0x01, //
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, //
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, //
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, //
+];
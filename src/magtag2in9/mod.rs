@@ -0,0 +1,451 @@
+//! A simple Driver for the 2.9" grayscale E-Ink Display of the Adafruit MagTag via SPI
+//!
+//! The 296x128 panel (GDEW029T5, IL0373 controller) of the MagTag and of the Adafruit 2.9"
+//! grayscale breakouts is refreshed either in black and white with the waveform of the
+//! controller OTP, or in 4 gray levels with the LUTs of the Adafruit library.
+//!
+//! The panel is portrait, rotate the display to draw in the landscape orientation of the MagTag.
+//!
+//! # Example for the Adafruit MagTag
+//!
+//!```rust, no_run
+//!# use embedded_hal_mock::eh1::*;
+//!# fn main() -> Result<(), epd_waveshare::error::EpdError<embedded_hal::spi::ErrorKind>> {
+//!use embedded_graphics::{prelude::*, primitives::{PrimitiveStyle, Rectangle}};
+//!use epd_waveshare::{graphics::DisplayRotation, magtag2in9::*, prelude::*};
+//!#
+//!# let expectations = [];
+//!# let mut spi = spi::Mock::new(&expectations);
+//!# let expectations = [];
+//!# let busy_in = digital::Mock::new(&expectations);
+//!# let dc = digital::Mock::new(&expectations);
+//!# let rst = digital::Mock::new(&expectations);
+//!# let mut delay = delay::NoopDelay::new();
+//!
+//!let mut epd = MagTag2in9::new(&mut spi, busy_in, dc, rst, &mut delay, None)?;
+//!
+//!let mut display = DisplayMagTag2in9Gray4::default();
+//!display.set_rotation(DisplayRotation::Rotate270);
+//!display.clear(Gray4::WHITE).ok();
+//!for (i, level) in [Gray4::BLACK, Gray4::DARK_GRAY, Gray4::LIGHT_GRAY].into_iter().enumerate() {
+//!    let _ = Rectangle::new(Point::new(i as i32 * 40, 0), Size::new(40, 128))
+//!        .into_styled(PrimitiveStyle::with_fill(level))
+//!        .draw(&mut display);
+//!}
+//!
+//!epd.update_and_display_gray4_frame(&mut spi, display.buffer(), &mut delay)?;
+//!epd.sleep(&mut spi, &mut delay)?;
+//!# Ok(())
+//!# }
+//!```
+
+use crate::error::EpdError;
+use crate::pin::BusyPin;
+use embedded_hal::{delay::*, digital::*, spi::SpiDevice};
+
+use crate::buffer_len;
+use crate::color::Color;
+use crate::interface::DisplayInterface;
+use crate::traits::{
+    Gray4Refresh, InterfaceConfig, InternalWiAdditions, RefreshLut, WaveshareDisplay,
+};
+
+pub(crate) mod command;
+use self::command::Command;
+
+mod constants;
+use self::constants::*;
+
+#[cfg(feature = "graphics")]
+use crate::color::Gray4;
+#[cfg(feature = "async")]
+use crate::interface::DisplayInterfaceAsync;
+#[cfg(feature = "async")]
+use crate::traits::{
+    AsyncBusyPin, Gray4RefreshAsync, InternalWiAdditionsAsync, WaveshareDisplayAsync,
+};
+#[cfg(feature = "async")]
+use embedded_hal_async::{delay::DelayNs as AsyncDelayNs, spi::SpiDevice as AsyncSpiDevice};
+
+/// Width of the display in pixels
+pub const WIDTH: u32 = 128;
+/// Height of the display in pixels
+pub const HEIGHT: u32 = 296;
+/// Default Background Color (white)
+pub const DEFAULT_BACKGROUND_COLOR: Color = Color::White;
+const IS_BUSY_LOW: bool = true;
+const SINGLE_BYTE_WRITE: bool = false;
+
+/// Display with Fullsize buffer for black and white refreshes
+#[cfg(feature = "graphics")]
+pub type DisplayMagTag2in9 = crate::graphics::Display<
+    WIDTH,
+    HEIGHT,
+    false,
+    { crate::graphics::display_len::<Color>(WIDTH, HEIGHT) },
+    Color,
+>;
+
+/// Display with Fullsize buffer for 4 gray levels refreshes
+#[cfg(feature = "graphics")]
+pub type DisplayMagTag2in9Gray4 = crate::graphics::Display<
+    WIDTH,
+    HEIGHT,
+    false,
+    { crate::graphics::display_len::<Gray4>(WIDTH, HEIGHT) },
+    Gray4,
+>;
+
+/// Panel setting with the LUT of the OTP, black and white mode
+const PANEL_SETTING_OTP: u8 = 0x1F;
+/// Panel setting with the LUT of the registers, black and white mode
+const PANEL_SETTING_REGISTERS: u8 = 0x3F;
+
+#[maybe_async_cfg::maybe(
+    sync(keep_self),
+    async(
+        feature = "async",
+        idents(
+            MagTag2in9,
+            DisplayInterface,
+            WaveshareDisplay,
+            InternalWiAdditions,
+            Gray4Refresh,
+            SpiDevice(async = "AsyncSpiDevice"),
+            BusyPin(async = "AsyncBusyPin"),
+            DelayNs(async = "AsyncDelayNs")
+        )
+    )
+)]
+/// MagTag 2.9" driver
+pub struct MagTag2in9<SPI, BUSY, DC, RST, DELAY> {
+    /// Connection Interface
+    interface: DisplayInterface<SPI, BUSY, DC, RST, DELAY, SINGLE_BYTE_WRITE>,
+    /// Background Color
+    background_color: Color,
+}
+
+#[maybe_async_cfg::maybe(
+    sync(keep_self),
+    async(
+        feature = "async",
+        idents(
+            MagTag2in9,
+            DisplayInterface,
+            WaveshareDisplay,
+            InternalWiAdditions,
+            Gray4Refresh,
+            SpiDevice(async = "AsyncSpiDevice"),
+            BusyPin(async = "AsyncBusyPin"),
+            DelayNs(async = "AsyncDelayNs")
+        )
+    )
+)]
+impl<SPI, BUSY, DC, RST, DELAY> InternalWiAdditions<SPI, BUSY, DC, RST, DELAY>
+    for MagTag2in9<SPI, BUSY, DC, RST, DELAY>
+where
+    SPI: SpiDevice,
+    BUSY: BusyPin,
+    DC: OutputPin,
+    RST: OutputPin,
+    DELAY: DelayNs,
+{
+    async fn init(&mut self, spi: &mut SPI, delay: &mut DELAY) -> Result<(), EpdError<SPI::Error>> {
+        self.interface.reset(delay, 10_000, 10_000).await?;
+
+        // internal power, VDH/VDL at +-11V, VDHR at 4.2V
+        self.interface
+            .cmd_with_data(spi, Command::PowerSetting, &[0x03, 0x00, 0x2B, 0x2B, 0x13])
+            .await?;
+        self.interface
+            .cmd_with_data(spi, Command::BoosterSoftStart, &[0x17, 0x17, 0x17])
+            .await?;
+        self.interface.cmd(spi, Command::PowerOn).await?;
+        self.wait_until_idle(spi, delay).await?;
+
+        self.interface
+            .cmd_with_data(spi, Command::PanelSetting, &[PANEL_SETTING_OTP])
+            .await?;
+        // 50Hz
+        self.interface
+            .cmd_with_data(spi, Command::PllControl, &[0x3C])
+            .await?;
+        self.interface
+            .cmd_with_data(spi, Command::VcmDcSetting, &[0x12])
+            .await?;
+        self.interface
+            .cmd_with_data(spi, Command::VcomAndDataIntervalSetting, &[0x97])
+            .await?;
+        self.interface
+            .cmd_with_data(
+                spi,
+                Command::ResolutionSetting,
+                &[WIDTH as u8, (HEIGHT >> 8) as u8, HEIGHT as u8],
+            )
+            .await
+    }
+}
+
+#[maybe_async_cfg::maybe(
+    sync(keep_self),
+    async(
+        feature = "async",
+        idents(
+            MagTag2in9,
+            DisplayInterface,
+            WaveshareDisplay,
+            InternalWiAdditions,
+            Gray4Refresh,
+            SpiDevice(async = "AsyncSpiDevice"),
+            BusyPin(async = "AsyncBusyPin"),
+            DelayNs(async = "AsyncDelayNs")
+        )
+    )
+)]
+impl<SPI, BUSY, DC, RST, DELAY> WaveshareDisplay<SPI, BUSY, DC, RST, DELAY>
+    for MagTag2in9<SPI, BUSY, DC, RST, DELAY>
+where
+    SPI: SpiDevice,
+    BUSY: BusyPin,
+    DC: OutputPin,
+    RST: OutputPin,
+    DELAY: DelayNs,
+{
+    type DisplayColor = Color;
+    fn width(&self) -> u32 {
+        WIDTH
+    }
+
+    fn height(&self) -> u32 {
+        HEIGHT
+    }
+
+    async fn new(
+        spi: &mut SPI,
+        busy: BUSY,
+        dc: DC,
+        rst: RST,
+        delay: &mut DELAY,
+        delay_us: Option<u32>,
+    ) -> Result<Self, EpdError<SPI::Error>> {
+        let mut epd = Self::new_uninit(busy, dc, rst, delay_us);
+        epd.init(spi, delay).await?;
+        Ok(epd)
+    }
+
+    fn new_uninit(busy: BUSY, dc: DC, rst: RST, delay_us: Option<u32>) -> Self {
+        let interface = DisplayInterface::new(busy, dc, rst, delay_us);
+
+        MagTag2in9 {
+            interface,
+            background_color: DEFAULT_BACKGROUND_COLOR,
+        }
+    }
+
+    async fn sleep(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.interface.cmd(spi, Command::PowerOff).await?;
+        self.wait_until_idle(spi, delay).await?;
+        self.interface
+            .cmd_with_data(spi, Command::DeepSleep, &[0xA5])
+            .await
+    }
+
+    async fn wake_up(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.init(spi, delay).await
+    }
+
+    async fn update_frame(
+        &mut self,
+        spi: &mut SPI,
+        buffer: &[u8],
+        delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        EpdError::check_buffer(buffer, buffer_len(WIDTH as usize, HEIGHT as usize))?;
+        self.wait_until_idle(spi, delay).await?;
+        // same old and new frame, the OTP waveform refreshes every pixel anyway
+        self.interface
+            .cmd_with_data(spi, Command::DataStartTransmission1, buffer)
+            .await?;
+        self.interface
+            .cmd_with_data(spi, Command::DataStartTransmission2, buffer)
+            .await
+    }
+
+    /// Partial updates aren't supported: returns [`EpdError::Unsupported`]
+    async fn update_partial_frame(
+        &mut self,
+        _spi: &mut SPI,
+        _delay: &mut DELAY,
+        _buffer: &[u8],
+        _x: u32,
+        _y: u32,
+        _width: u32,
+        _height: u32,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        Err(EpdError::Unsupported)
+    }
+
+    async fn display_frame(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.interface.cmd(spi, Command::DisplayRefresh).await?;
+        self.wait_until_idle(spi, delay).await
+    }
+
+    async fn update_and_display_frame(
+        &mut self,
+        spi: &mut SPI,
+        buffer: &[u8],
+        delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.update_frame(spi, buffer, delay).await?;
+        self.display_frame(spi, delay).await
+    }
+
+    async fn clear_frame(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.wait_until_idle(spi, delay).await?;
+
+        let color = self.background_color.get_byte_value();
+        let len = buffer_len(WIDTH as usize, HEIGHT as usize) as u32;
+        self.interface
+            .cmd(spi, Command::DataStartTransmission1)
+            .await?;
+        self.interface.data_x_times(spi, color, len).await?;
+        self.interface
+            .cmd(spi, Command::DataStartTransmission2)
+            .await?;
+        self.interface.data_x_times(spi, color, len).await
+    }
+
+    fn set_background_color(&mut self, background_color: Color) {
+        self.background_color = background_color;
+    }
+
+    fn background_color(&self) -> &Color {
+        &self.background_color
+    }
+
+    async fn set_lut(
+        &mut self,
+        _spi: &mut SPI,
+        _delay: &mut DELAY,
+        _refresh_rate: Option<RefreshLut>,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        Ok(())
+    }
+
+    fn interface_config_mut(&mut self) -> &mut InterfaceConfig {
+        self.interface.config_mut()
+    }
+
+    fn release(self) -> (BUSY, DC, RST) {
+        self.interface.release()
+    }
+
+    async fn wait_until_idle(
+        &mut self,
+        _spi: &mut SPI,
+        delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        self.interface.wait_until_idle(delay, IS_BUSY_LOW).await
+    }
+}
+
+#[maybe_async_cfg::maybe(
+    sync(keep_self),
+    async(
+        feature = "async",
+        idents(
+            MagTag2in9,
+            DisplayInterface,
+            WaveshareDisplay,
+            InternalWiAdditions,
+            Gray4Refresh,
+            SpiDevice(async = "AsyncSpiDevice"),
+            BusyPin(async = "AsyncBusyPin"),
+            DelayNs(async = "AsyncDelayNs")
+        )
+    )
+)]
+impl<SPI, BUSY, DC, RST, DELAY> Gray4Refresh<SPI, BUSY, DC, RST, DELAY>
+    for MagTag2in9<SPI, BUSY, DC, RST, DELAY>
+where
+    SPI: SpiDevice,
+    BUSY: BusyPin,
+    DC: OutputPin,
+    RST: OutputPin,
+    DELAY: DelayNs,
+{
+    /// Sends a frame of 4 gray levels, packed by 4 pixels in a byte as in a
+    /// `DisplayMagTag2in9Gray4`, and refreshes it with the grayscale LUTs
+    ///
+    /// The following refreshes are in black and white again.
+    async fn update_and_display_gray4_frame(
+        &mut self,
+        spi: &mut SPI,
+        buffer: &[u8],
+        delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>> {
+        EpdError::check_buffer(buffer, WIDTH as usize * HEIGHT as usize / 4)?;
+        self.wait_until_idle(spi, delay).await?;
+
+        self.interface
+            .cmd_with_data(spi, Command::PanelSetting, &[PANEL_SETTING_REGISTERS])
+            .await?;
+        self.interface
+            .cmd_with_data(spi, Command::LutForVcom, &LUT_VCOM)
+            .await?;
+        self.interface
+            .cmd_with_data(spi, Command::LutWhiteToWhite, &LUT_WW)
+            .await?;
+        self.interface
+            .cmd_with_data(spi, Command::LutBlackToWhite, &LUT_BW)
+            .await?;
+        self.interface
+            .cmd_with_data(spi, Command::LutWhiteToBlack, &LUT_WB)
+            .await?;
+        self.interface
+            .cmd_with_data(spi, Command::LutBlackToBlack, &LUT_BB)
+            .await?;
+
+        self.interface
+            .cmd(spi, Command::DataStartTransmission1)
+            .await?;
+        self.interface.data_gray4_plane(spi, buffer, 1).await?;
+        self.interface
+            .cmd(spi, Command::DataStartTransmission2)
+            .await?;
+        self.interface.data_gray4_plane(spi, buffer, 0).await?;
+        self.display_frame(spi, delay).await?;
+
+        self.interface
+            .cmd_with_data(spi, Command::PanelSetting, &[PANEL_SETTING_OTP])
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn epd_size() {
+        assert_eq!(WIDTH, 128);
+        assert_eq!(HEIGHT, 296);
+        assert_eq!(DEFAULT_BACKGROUND_COLOR, Color::White);
+    }
+}
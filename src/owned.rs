@@ -0,0 +1,226 @@
+//! A driver owning its SPI device and delay
+//!
+//! The drivers borrow the SPI device and the delay in each call, so that they can be shared with
+//! other peripherals. An application where the panel is alone on its bus can give them to
+//! [`OwnedEpd`] instead, whose methods don't take them, and get them back with
+//! [`release`](OwnedEpd::release).
+//!
+//!```rust, no_run
+//!# use embedded_hal_mock::eh1::*;
+//!# fn main() -> Result<(), epd_waveshare::error::EpdError<embedded_hal::spi::ErrorKind>> {
+//!use epd_waveshare::{epd2in9_v2::*, owned::OwnedEpd, prelude::*};
+//!#
+//!# let expectations = [];
+//!# let spi = spi::Mock::new(&expectations);
+//!# let expectations = [];
+//!# let busy_in = digital::Mock::new(&expectations);
+//!# let dc = digital::Mock::new(&expectations);
+//!# let rst = digital::Mock::new(&expectations);
+//!# let delay = delay::NoopDelay::new();
+//!
+//!let mut epd: OwnedEpd<Epd2in9<_, _, _, _, _>, _, _> =
+//!    OwnedEpd::new(spi, busy_in, dc, rst, delay, None)?;
+//!
+//!let display = Display2in9::default();
+//!epd.update_and_display_frame(display.buffer())?;
+//!
+//!// the methods of the other traits take the peripherals from the closure
+//!epd.with(|epd, spi, delay| epd.update_and_display_new_frame(spi, display.buffer(), delay))?;
+//!
+//!epd.sleep()?;
+//!let (epd, spi, delay) = epd.release();
+//!# Ok(())
+//!# }
+//!```
+
+use crate::error::EpdError;
+use crate::pin::BusyPin;
+use crate::traits::{RefreshLut, WaveshareDisplay};
+use embedded_hal::{delay::*, digital::*, spi::SpiDevice};
+
+/// A driver with its SPI device and delay, see the [module](self)
+pub struct OwnedEpd<EPD, SPI, DELAY> {
+    epd: EPD,
+    spi: SPI,
+    delay: DELAY,
+}
+
+impl<EPD, SPI, DELAY> OwnedEpd<EPD, SPI, DELAY> {
+    /// Bundles an already created driver with its peripherals
+    pub fn from_parts(epd: EPD, spi: SPI, delay: DELAY) -> Self {
+        OwnedEpd { epd, spi, delay }
+    }
+
+    /// Returns the driver and its peripherals
+    pub fn release(self) -> (EPD, SPI, DELAY) {
+        (self.epd, self.spi, self.delay)
+    }
+
+    /// Returns the driver
+    pub fn epd(&self) -> &EPD {
+        &self.epd
+    }
+
+    /// Calls `f` with the driver and its peripherals, for the methods which aren't forwarded
+    pub fn with<R>(&mut self, f: impl FnOnce(&mut EPD, &mut SPI, &mut DELAY) -> R) -> R {
+        f(&mut self.epd, &mut self.spi, &mut self.delay)
+    }
+}
+
+impl<EPD, SPI, DELAY> OwnedEpd<EPD, SPI, DELAY>
+where
+    SPI: SpiDevice,
+    DELAY: DelayNs,
+{
+    /// Creates the driver, see [`WaveshareDisplay::new`]
+    pub fn new<BUSY, DC, RST>(
+        mut spi: SPI,
+        busy: BUSY,
+        dc: DC,
+        rst: RST,
+        mut delay: DELAY,
+        delay_us: Option<u32>,
+    ) -> Result<Self, EpdError<SPI::Error>>
+    where
+        EPD: WaveshareDisplay<SPI, BUSY, DC, RST, DELAY>,
+        BUSY: BusyPin,
+        DC: OutputPin,
+        RST: OutputPin,
+    {
+        let epd = EPD::new(&mut spi, busy, dc, rst, &mut delay, delay_us)?;
+        Ok(OwnedEpd { epd, spi, delay })
+    }
+
+    /// See [`WaveshareDisplay::sleep`]
+    pub fn sleep<BUSY, DC, RST>(&mut self) -> Result<(), EpdError<SPI::Error>>
+    where
+        EPD: WaveshareDisplay<SPI, BUSY, DC, RST, DELAY>,
+        BUSY: BusyPin,
+        DC: OutputPin,
+        RST: OutputPin,
+    {
+        self.epd.sleep(&mut self.spi, &mut self.delay)
+    }
+
+    /// See [`WaveshareDisplay::wake_up`]
+    pub fn wake_up<BUSY, DC, RST>(&mut self) -> Result<(), EpdError<SPI::Error>>
+    where
+        EPD: WaveshareDisplay<SPI, BUSY, DC, RST, DELAY>,
+        BUSY: BusyPin,
+        DC: OutputPin,
+        RST: OutputPin,
+    {
+        self.epd.wake_up(&mut self.spi, &mut self.delay)
+    }
+
+    /// See [`WaveshareDisplay::update_frame`]
+    pub fn update_frame<BUSY, DC, RST>(&mut self, buffer: &[u8]) -> Result<(), EpdError<SPI::Error>>
+    where
+        EPD: WaveshareDisplay<SPI, BUSY, DC, RST, DELAY>,
+        BUSY: BusyPin,
+        DC: OutputPin,
+        RST: OutputPin,
+    {
+        self.epd
+            .update_frame(&mut self.spi, buffer, &mut self.delay)
+    }
+
+    /// See [`WaveshareDisplay::update_partial_frame`]
+    pub fn update_partial_frame<BUSY, DC, RST>(
+        &mut self,
+        buffer: &[u8],
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+    ) -> Result<(), EpdError<SPI::Error>>
+    where
+        EPD: WaveshareDisplay<SPI, BUSY, DC, RST, DELAY>,
+        BUSY: BusyPin,
+        DC: OutputPin,
+        RST: OutputPin,
+    {
+        self.epd
+            .update_partial_frame(&mut self.spi, &mut self.delay, buffer, x, y, width, height)
+    }
+
+    /// See [`WaveshareDisplay::display_frame`]
+    pub fn display_frame<BUSY, DC, RST>(&mut self) -> Result<(), EpdError<SPI::Error>>
+    where
+        EPD: WaveshareDisplay<SPI, BUSY, DC, RST, DELAY>,
+        BUSY: BusyPin,
+        DC: OutputPin,
+        RST: OutputPin,
+    {
+        self.epd.display_frame(&mut self.spi, &mut self.delay)
+    }
+
+    /// See [`WaveshareDisplay::update_and_display_frame`]
+    pub fn update_and_display_frame<BUSY, DC, RST>(
+        &mut self,
+        buffer: &[u8],
+    ) -> Result<(), EpdError<SPI::Error>>
+    where
+        EPD: WaveshareDisplay<SPI, BUSY, DC, RST, DELAY>,
+        BUSY: BusyPin,
+        DC: OutputPin,
+        RST: OutputPin,
+    {
+        self.epd
+            .update_and_display_frame(&mut self.spi, buffer, &mut self.delay)
+    }
+
+    /// See [`WaveshareDisplay::clear_frame`]
+    pub fn clear_frame<BUSY, DC, RST>(&mut self) -> Result<(), EpdError<SPI::Error>>
+    where
+        EPD: WaveshareDisplay<SPI, BUSY, DC, RST, DELAY>,
+        BUSY: BusyPin,
+        DC: OutputPin,
+        RST: OutputPin,
+    {
+        self.epd.clear_frame(&mut self.spi, &mut self.delay)
+    }
+
+    /// See [`WaveshareDisplay::set_lut`]
+    pub fn set_lut<BUSY, DC, RST>(
+        &mut self,
+        refresh_rate: Option<RefreshLut>,
+    ) -> Result<(), EpdError<SPI::Error>>
+    where
+        EPD: WaveshareDisplay<SPI, BUSY, DC, RST, DELAY>,
+        BUSY: BusyPin,
+        DC: OutputPin,
+        RST: OutputPin,
+    {
+        self.epd
+            .set_lut(&mut self.spi, &mut self.delay, refresh_rate)
+    }
+
+    /// See [`WaveshareDisplay::wait_until_idle`]
+    pub fn wait_until_idle<BUSY, DC, RST>(&mut self) -> Result<(), EpdError<SPI::Error>>
+    where
+        EPD: WaveshareDisplay<SPI, BUSY, DC, RST, DELAY>,
+        BUSY: BusyPin,
+        DC: OutputPin,
+        RST: OutputPin,
+    {
+        self.epd.wait_until_idle(&mut self.spi, &mut self.delay)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn release_parts() {
+        let mut owned = OwnedEpd::from_parts(1u8, 2u16, 3u32);
+        owned.with(|epd, spi, delay| {
+            *epd += 1;
+            *spi += 1;
+            *delay += 1;
+        });
+        assert_eq!(*owned.epd(), 2);
+        assert_eq!(owned.release(), (2, 3, 4));
+    }
+}
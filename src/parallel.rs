@@ -0,0 +1,45 @@
+//! Interface of the panels driven directly by the MCU over a parallel bus
+//!
+//! Some boards, like the LilyGo T5 4.7", have no controller between the MCU and the panel: the
+//! MCU shifts the data of each row in the source drivers over an 8 bit bus (usually with the I2S
+//! or LCD peripheral of an ESP32) and clocks the gate driver itself, as done by EPDiy. The
+//! waveforms are then computed by the driver, one frame of the panel after the other.
+//!
+//! [`ParallelBus`] is what a board has to provide to such a driver: the timing of the row
+//! signals depends too much on the peripheral used to be written with `embedded-hal` alone.
+
+/// Pixel left as it is during a frame
+pub const NO_OP: u8 = 0b00;
+/// Pixel driven towards black during a frame
+pub const DARKEN: u8 = 0b01;
+/// Pixel driven towards white during a frame
+pub const LIGHTEN: u8 = 0b10;
+
+/// Row and frame signals of a panel without controller
+///
+/// The data of a row has 2 bits per pixel ([`NO_OP`], [`DARKEN`] or [`LIGHTEN`]), 4 pixels per
+/// byte with the leftmost one in the high bits. Implementations reorder it if their peripheral
+/// needs it.
+pub trait ParallelBus {
+    /// Error of the bus
+    type Error;
+
+    /// Powers the panel up, the high voltages included
+    fn power_on(&mut self) -> Result<(), Self::Error>;
+
+    /// Powers the panel down
+    fn power_off(&mut self) -> Result<(), Self::Error>;
+
+    /// Starts a frame, the gate driver selects the first row
+    fn start_frame(&mut self) -> Result<(), Self::Error>;
+
+    /// Shifts `data` in the source drivers, latches it and drives the row for `output_us`
+    /// microseconds, then selects the next row
+    fn write_row(&mut self, data: &[u8], output_us: u32) -> Result<(), Self::Error>;
+
+    /// Selects the next row without driving the current one
+    fn skip_row(&mut self) -> Result<(), Self::Error>;
+
+    /// Ends the frame
+    fn end_frame(&mut self) -> Result<(), Self::Error>;
+}
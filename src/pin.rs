@@ -0,0 +1,112 @@
+//! Pins which aren't connected
+//!
+//! Some boards don't route all the pins of the panel to the MCU: RST may be tied to the reset
+//! line of the MCU or to VCC, BUSY left unconnected. [`NoPin`] takes the place of such a pin in
+//! the drivers, its changes doing nothing.
+//!
+//! Without RST, the reset pulse sent by the initialisation of the drivers has no effect: the
+//! controllers start from the state they had, the drivers of the Solomon Systech controllers
+//! (SSD16xx) resetting them with their software reset command. The controllers only leave their
+//! deep sleep with a hardware reset: after [`sleep`](crate::traits::WaveshareDisplay::sleep), the
+//! panel must be powered off and on instead of being woken up. The delays of the pulse can be
+//! skipped with [`ResetTiming::NONE`](crate::traits::ResetTiming::NONE), see
+//! [`set_reset_timing`](crate::traits::WaveshareDisplay::set_reset_timing).
+//!
+//! The BUSY pin of the drivers is a [`BusyPin`], implemented by every [`InputPin`] and by
+//! [`NoPin`], which tells the drivers at compile time that BUSY isn't connected.
+//!
+//! Without BUSY, the drivers can't poll the controller: they wait a fixed time after each
+//! command instead, long enough for the slowest panels of the controller, the refreshes taking
+//! the longest (seconds, up to 45s for the 7-color panels), and the busy timeout isn't checked.
+//! The refreshes always count as complete for
+//! [`is_refresh_complete`](crate::traits::NonBlockingRefresh::is_refresh_complete):
+//! [`wait_until_idle`](crate::traits::WaveshareDisplay::wait_until_idle) waits for them instead.
+//!
+//!```rust, no_run
+//!# use embedded_hal_mock::eh1::*;
+//!# fn main() -> Result<(), epd_waveshare::error::EpdError<embedded_hal::spi::ErrorKind>> {
+//!use epd_waveshare::{epd2in9_v2::*, pin::NoPin, prelude::*};
+//!#
+//!# let expectations = [];
+//!# let mut spi = spi::Mock::new(&expectations);
+//!# let expectations = [];
+//!# let dc = digital::Mock::new(&expectations);
+//!# let mut delay = delay::NoopDelay::new();
+//!
+//!// neither BUSY nor RST connected
+//!let mut epd = Epd2in9::new(&mut spi, NoPin, dc, NoPin, &mut delay, None)?;
+//!# Ok(())
+//!# }
+//!```
+
+use core::convert::Infallible;
+use embedded_hal::digital::{ErrorType, InputPin, OutputPin};
+#[cfg(feature = "async")]
+use embedded_hal_async::digital::Wait;
+
+/// A pin which isn't connected, see the [module](self)
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct NoPin;
+
+impl ErrorType for NoPin {
+    type Error = Infallible;
+}
+
+impl OutputPin for NoPin {
+    fn set_low(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    fn set_high(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+/// BUSY pin of the drivers, see the [module](self)
+pub trait BusyPin: ErrorType {
+    /// `false` if BUSY isn't connected, the drivers then wait a fixed time instead of reading it
+    const CONNECTED: bool = true;
+
+    /// Reads the level of the pin, `true` when high
+    fn read_high(&mut self) -> Result<bool, Self::Error>;
+}
+
+impl<PIN: InputPin> BusyPin for PIN {
+    fn read_high(&mut self) -> Result<bool, Self::Error> {
+        self.is_high()
+    }
+}
+
+/// Never read, BUSY isn't connected
+impl BusyPin for NoPin {
+    const CONNECTED: bool = false;
+
+    fn read_high(&mut self) -> Result<bool, Self::Error> {
+        Ok(false)
+    }
+}
+
+/// Never waits, the drivers don't await a BUSY pin which isn't connected
+#[cfg(feature = "async")]
+impl Wait for NoPin {
+    async fn wait_for_high(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    async fn wait_for_low(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    async fn wait_for_rising_edge(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    async fn wait_for_falling_edge(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    async fn wait_for_any_edge(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
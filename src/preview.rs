@@ -0,0 +1,197 @@
+//! Preview of the display buffers as text, for host-side tests and log dumps
+//!
+//! Each character shows several pixels: 2 above each other with the half blocks (`▀`, `▄`, `█`),
+//! or 2 by 4 with the braille patterns. A dark pixel is drawn, a light one left blank. The
+//! pixels are read in the drawing coordinates, with the rotation of the display.
+//!
+//!```rust
+//!use embedded_graphics::{prelude::*, primitives::{Line, PrimitiveStyle}};
+//!use epd_waveshare::{color::Color, graphics::Display, preview::*};
+//!
+//!let mut display = Display::<8, 4, false, 4, Color>::default();
+//!display.clear(Color::White).ok();
+//!let _ = Line::new(Point::new(0, 0), Point::new(7, 3))
+//!    .into_styled(PrimitiveStyle::with_stroke(Color::Black, 1))
+//!    .draw(&mut display);
+//!
+//!assert_eq!(preview(&display, PreviewStyle::HalfBlocks), "▀▀▄▄    \n    ▀▀▄▄\n");
+//!assert_eq!(preview(&display, PreviewStyle::Braille), "⠉⠒⠤⣀\n");
+//!```
+
+use crate::color::{Color, Gray16, Gray4, OctColor, QuadColor, SpectraColor, TriColor};
+use core::fmt;
+use embedded_graphics_core::{
+    image::GetPixel,
+    pixelcolor::{BinaryColor, Rgb888, RgbColor},
+    prelude::*,
+};
+use std::string::String;
+
+/// Characters showing several pixels each
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PreviewStyle {
+    /// 2 pixels above each other per character
+    #[default]
+    HalfBlocks,
+    /// 2 by 4 pixels per character, for a more compact preview
+    Braille,
+}
+
+/// Colors of the display buffers, split into dark and light for the preview
+pub trait PreviewColor {
+    /// Whether the pixel is drawn in the preview
+    fn is_dark(&self) -> bool;
+}
+
+/// Luma of a color below the middle gray
+fn dark(rgb: Rgb888) -> bool {
+    (rgb.r() as u32 * 77 + rgb.g() as u32 * 150 + rgb.b() as u32 * 29) >> 8 < 128
+}
+
+impl PreviewColor for Color {
+    fn is_dark(&self) -> bool {
+        *self == Color::Black
+    }
+}
+
+impl PreviewColor for TriColor {
+    fn is_dark(&self) -> bool {
+        *self != TriColor::White
+    }
+}
+
+impl PreviewColor for OctColor {
+    fn is_dark(&self) -> bool {
+        dark((*self).into())
+    }
+}
+
+impl PreviewColor for QuadColor {
+    fn is_dark(&self) -> bool {
+        dark((*self).into())
+    }
+}
+
+impl PreviewColor for SpectraColor {
+    fn is_dark(&self) -> bool {
+        dark((*self).into())
+    }
+}
+
+impl PreviewColor for Gray16 {
+    fn is_dark(&self) -> bool {
+        dark((*self).into())
+    }
+}
+
+impl PreviewColor for Gray4 {
+    fn is_dark(&self) -> bool {
+        *self == Gray4::BLACK || *self == Gray4::DARK_GRAY
+    }
+}
+
+/// `On` being black, as on a [`BinaryDisplay`](crate::graphics::BinaryDisplay) by default
+impl PreviewColor for BinaryColor {
+    fn is_dark(&self) -> bool {
+        self.is_on()
+    }
+}
+
+/// Returns the preview of `display`, one line of characters per line of text
+pub fn preview<D>(display: &D, style: PreviewStyle) -> String
+where
+    D: GetPixel + Dimensions,
+    D::Color: PreviewColor,
+{
+    let mut text = String::new();
+    // writing in a String doesn't fail
+    let _ = write_preview(display, style, &mut text);
+    text
+}
+
+/// Writes the preview of `display` to `out`, e.g. a formatter
+pub fn write_preview<D, W>(display: &D, style: PreviewStyle, out: &mut W) -> fmt::Result
+where
+    D: GetPixel + Dimensions,
+    D::Color: PreviewColor,
+    W: fmt::Write,
+{
+    let area = display.bounding_box();
+    let (cell_width, cell_height) = match style {
+        PreviewStyle::HalfBlocks => (1, 2),
+        PreviewStyle::Braille => (2, 4),
+    };
+    let dark = |x: i32, y: i32| {
+        display
+            .pixel(area.top_left + Point::new(x, y))
+            .is_some_and(|color| color.is_dark())
+    };
+
+    for row in 0..(area.size.height as i32 + cell_height - 1) / cell_height {
+        for column in 0..(area.size.width as i32 + cell_width - 1) / cell_width {
+            let (x, y) = (column * cell_width, row * cell_height);
+            let c = match style {
+                PreviewStyle::HalfBlocks => match (dark(x, y), dark(x, y + 1)) {
+                    (false, false) => ' ',
+                    (true, false) => '▀',
+                    (false, true) => '▄',
+                    (true, true) => '█',
+                },
+                PreviewStyle::Braille => {
+                    // dots 1 to 8 of the pattern, column after column
+                    const DOTS: [(i32, i32); 8] = [
+                        (0, 0),
+                        (0, 1),
+                        (0, 2),
+                        (1, 0),
+                        (1, 1),
+                        (1, 2),
+                        (0, 3),
+                        (1, 3),
+                    ];
+                    let dots = DOTS
+                        .iter()
+                        .enumerate()
+                        .filter(|(_, &(dx, dy))| dark(x + dx, y + dy))
+                        .fold(0, |dots, (i, _)| dots | 1 << i);
+                    char::from_u32(0x2800 + dots).unwrap_or(' ')
+                }
+            };
+            out.write_char(c)?;
+        }
+        out.write_char('\n')?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graphics::{Display, DisplayRotation, VarDisplay};
+
+    #[test]
+    fn preview_rotation() {
+        let mut display = Display::<8, 2, false, 2, Color>::default();
+        display.clear(Color::White).unwrap();
+        display.set_pixel(Pixel(Point::new(0, 0), Color::Black));
+        display.set_pixel(Pixel(Point::new(7, 1), Color::Black));
+        assert_eq!(preview(&display, PreviewStyle::HalfBlocks), "▀      ▄\n");
+        assert_eq!(preview(&display, PreviewStyle::Braille), "⠁⠀⠀⠐\n");
+
+        display.set_rotation(DisplayRotation::Rotate90);
+        assert_eq!(
+            preview(&display, PreviewStyle::HalfBlocks),
+            " ▀\n  \n  \n▄ \n"
+        );
+    }
+
+    #[test]
+    fn preview_colors() {
+        let mut buffer = [0u8; 4];
+        let mut display = VarDisplay::<TriColor>::new(4, 2, &mut buffer, false).unwrap();
+        display.clear(TriColor::White).unwrap();
+        display.set_pixel(Pixel(Point::new(1, 0), TriColor::Chromatic));
+        display.set_pixel(Pixel(Point::new(2, 1), TriColor::Black));
+        assert_eq!(preview(&display, PreviewStyle::HalfBlocks), " ▀▄ \n");
+    }
+}
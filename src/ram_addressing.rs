@@ -0,0 +1,104 @@
+//! RAM window and address counters of the Solomon Systech controllers (SSD1608, SSD1675,
+//! SSD1680, SSD1677, ...)
+//!
+//! All of them select the RAM area written next with the same four commands, only the width of
+//! the addresses differs: small controllers address x in bytes of 8 pixels, the larger SSD1677
+//! in pixels. A [`RamAddressing`] type encodes the addresses of one controller family, the
+//! commands themselves are sent by the `set_ram_area` and `set_ram_counter` functions of the
+//! display interface.
+
+use crate::traits;
+
+/// Commands shared by the SSD16xx controllers to address their RAM
+#[derive(Copy, Clone)]
+pub(crate) enum RamCommand {
+    /// Start and end of the RAM window in x direction
+    XAddressStartEndPosition = 0x44,
+    /// Start and end of the RAM window in y direction
+    YAddressStartEndPosition = 0x45,
+    /// Initial x position of the address counter
+    XAddressCounter = 0x4E,
+    /// Initial y position of the address counter
+    YAddressCounter = 0x4F,
+}
+
+impl traits::Command for RamCommand {
+    /// Returns the address of the command
+    fn address(self) -> u8 {
+        self as u8
+    }
+}
+
+/// Encoding of the RAM addresses of a controller
+pub(crate) trait RamAddressing {
+    /// Encodes column `x` (in pixels), returns the bytes and how many of them are sent
+    fn x_address(x: u32) -> ([u8; 2], usize);
+
+    /// Encodes line `y`, low byte first
+    fn y_address(y: u32) -> [u8; 2];
+}
+
+/// SSD1608, SSD1675, SSD1680 and SSD1681: x in bytes of 8 pixels, 9 bits y
+pub(crate) struct Ssd1680;
+
+impl RamAddressing for Ssd1680 {
+    fn x_address(x: u32) -> ([u8; 2], usize) {
+        // x is positioned in bytes, so the last 3 bits which show the position inside a byte in
+        // the ram aren't relevant
+        ([(x >> 3) as u8, 0], 1)
+    }
+
+    fn y_address(y: u32) -> [u8; 2] {
+        [y as u8, ((y >> 8) & 0x01) as u8]
+    }
+}
+
+/// SSD1677: x in pixels and y on 10 bits
+pub(crate) struct Ssd1677;
+
+impl RamAddressing for Ssd1677 {
+    fn x_address(x: u32) -> ([u8; 2], usize) {
+        ([x as u8, ((x >> 8) & 0x03) as u8], 2)
+    }
+
+    fn y_address(y: u32) -> [u8; 2] {
+        [y as u8, ((y >> 8) & 0x03) as u8]
+    }
+}
+
+/// Data of the command setting the window from `start` to `end` (both included)
+pub(crate) fn x_window<A: RamAddressing>(start: u32, end: u32) -> ([u8; 4], usize) {
+    let (start, len) = A::x_address(start);
+    let (end, _) = A::x_address(end);
+    let mut data = [0; 4];
+    data[..len].copy_from_slice(&start[..len]);
+    data[len..2 * len].copy_from_slice(&end[..len]);
+    (data, 2 * len)
+}
+
+/// Data of the command setting the window from line `start` to `end` (both included)
+pub(crate) fn y_window<A: RamAddressing>(start: u32, end: u32) -> [u8; 4] {
+    let [start_low, start_high] = A::y_address(start);
+    let [end_low, end_high] = A::y_address(end);
+    [start_low, start_high, end_low, end_high]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ssd1680_addresses() {
+        assert_eq!(x_window::<Ssd1680>(0, 127), ([0x00, 0x0f, 0, 0], 2));
+        assert_eq!(x_window::<Ssd1680>(16, 39), ([0x02, 0x04, 0, 0], 2));
+        assert_eq!(y_window::<Ssd1680>(0, 295), [0x00, 0x00, 0x27, 0x01]);
+        assert_eq!(Ssd1680::x_address(200), ([25, 0], 1));
+    }
+
+    #[test]
+    fn ssd1677_addresses() {
+        assert_eq!(x_window::<Ssd1677>(0, 279), ([0x00, 0x00, 0x17, 0x01], 4));
+        assert_eq!(y_window::<Ssd1677>(0, 479), [0x00, 0x00, 0xdf, 0x01]);
+        assert_eq!(Ssd1677::y_address(0x3ff), [0xff, 0x03]);
+    }
+}
@@ -0,0 +1,369 @@
+//! Recovery of wedged controllers, for devices left alone in the field
+//!
+//! A controller hit by an ESD event or a brown-out may stop answering, its BUSY line staying up.
+//! [`Recovering`] wraps a driver and, when one of its calls returns
+//! [`EpdError::BusyTimeout`], pulses the reset line and replays the init sequence with
+//! [`wake_up`](WaveshareDisplay::wake_up), sends the last full frame again and retries the call,
+//! up to the number of times given by the [`RecoveryPolicy`]. The timeout is only returned once
+//! the retries are exhausted, the other errors (e.g. a frame of the wrong size) right away.
+//!
+//! A controller which stays busy is only noticed with a busy timeout, see
+//! [`set_busy_timeout`](WaveshareDisplay::set_busy_timeout).
+//!
+//!```rust, no_run
+//!# use embedded_hal_mock::eh1::*;
+//!# fn main() -> Result<(), epd_waveshare::error::EpdError<embedded_hal::spi::ErrorKind>> {
+//!use epd_waveshare::{epd2in9_v2::*, prelude::*, recovery::*};
+//!#
+//!# let expectations = [];
+//!# let mut spi = spi::Mock::new(&expectations);
+//!# let expectations = [];
+//!# let busy_in = digital::Mock::new(&expectations);
+//!# let dc = digital::Mock::new(&expectations);
+//!# let rst = digital::Mock::new(&expectations);
+//!# let mut delay = delay::NoopDelay::new();
+//!
+//!let epd = Epd2in9::new(&mut spi, busy_in, dc, rst, &mut delay, None)?;
+//!// copy of the last full frame, sent again after a recovery
+//!let mut cache = [0xff; WIDTH as usize / 8 * HEIGHT as usize];
+//!let mut epd = Recovering::new(epd, &mut cache).with_policy(RecoveryPolicy { retries: 3 });
+//!
+//!let frame = [0xff; WIDTH as usize / 8 * HEIGHT as usize];
+//!epd.update_and_display_frame(&mut spi, &frame, &mut delay)?;
+//!if epd.recoveries() > 0 {
+//!    // worth a line in the log of the device
+//!}
+//!# Ok(())
+//!# }
+//!```
+
+use crate::error::EpdError;
+use crate::pin::BusyPin;
+use crate::traits::WaveshareDisplay;
+use embedded_hal::{delay::*, digital::*, spi::SpiDevice};
+
+/// How hard [`Recovering`] tries before returning an error
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct RecoveryPolicy {
+    /// Number of recoveries and retries of a failed call (2)
+    pub retries: u8,
+}
+
+impl Default for RecoveryPolicy {
+    fn default() -> Self {
+        RecoveryPolicy { retries: 2 }
+    }
+}
+
+/// Driver wrapper re-initializing the controller and retrying the calls which timed out
+///
+/// It offers the methods of [`WaveshareDisplay`] which send something to the controller, the
+/// others being reached with [`epd`](Recovering::epd) and [`epd_mut`](Recovering::epd_mut).
+/// The last full frame sent successfully is copied in a cache of its size, given by the
+/// application.
+pub struct Recovering<'a, EPD> {
+    epd: EPD,
+    policy: RecoveryPolicy,
+    cache: &'a mut [u8],
+    /// Whether the cache holds the frame in the RAM of the controller
+    cached: bool,
+    recoveries: u32,
+}
+
+impl<'a, EPD> Recovering<'a, EPD> {
+    /// Wraps `epd` with the default policy, `cache` being as large as a full frame
+    pub fn new(epd: EPD, cache: &'a mut [u8]) -> Self {
+        Recovering {
+            epd,
+            policy: RecoveryPolicy::default(),
+            cache,
+            cached: false,
+            recoveries: 0,
+        }
+    }
+
+    /// Recovers following `policy` instead of the default one
+    pub fn with_policy(mut self, policy: RecoveryPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Returns the wrapped driver
+    pub fn epd(&self) -> &EPD {
+        &self.epd
+    }
+
+    /// Returns the wrapped driver, e.g. for the calls not part of the traits
+    ///
+    /// These aren't retried.
+    pub fn epd_mut(&mut self) -> &mut EPD {
+        &mut self.epd
+    }
+
+    /// Unwraps the driver
+    pub fn into_inner(self) -> EPD {
+        self.epd
+    }
+
+    /// Number of recoveries since the driver was wrapped
+    pub fn recoveries(&self) -> u32 {
+        self.recoveries
+    }
+
+    /// Keeps a copy of `buffer` if it is a full frame
+    fn cache(&mut self, buffer: &[u8]) {
+        self.cached = buffer.len() == self.cache.len();
+        if self.cached {
+            self.cache.copy_from_slice(buffer);
+        }
+    }
+
+    /// Runs `op`, recovering with `recover` and running it again as long as it times out
+    fn run<S, D, E>(
+        &mut self,
+        spi: &mut S,
+        delay: &mut D,
+        mut recover: impl FnMut(&mut EPD, &mut S, &mut D, Option<&[u8]>) -> Result<(), EpdError<E>>,
+        mut op: impl FnMut(&mut EPD, &mut S, &mut D) -> Result<(), EpdError<E>>,
+    ) -> Result<(), EpdError<E>> {
+        let mut result = op(&mut self.epd, spi, delay);
+        for _ in 0..self.policy.retries {
+            if !matches!(result, Err(EpdError::BusyTimeout)) {
+                break;
+            }
+            self.recoveries += 1;
+            let frame = match self.cached {
+                true => Some(&*self.cache),
+                false => None,
+            };
+            result = recover(&mut self.epd, spi, delay, frame)
+                .and_then(|()| op(&mut self.epd, spi, delay));
+        }
+        result
+    }
+
+    /// Sends a full frame, see [`WaveshareDisplay::update_frame`]
+    pub fn update_frame<SPI, BUSY, DC, RST, DELAY>(
+        &mut self,
+        spi: &mut SPI,
+        buffer: &[u8],
+        delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>>
+    where
+        EPD: WaveshareDisplay<SPI, BUSY, DC, RST, DELAY>,
+        SPI: SpiDevice,
+        BUSY: BusyPin,
+        DC: OutputPin,
+        RST: OutputPin,
+        DELAY: DelayNs,
+    {
+        self.run(spi, delay, reinit, |epd, spi, delay| {
+            epd.update_frame(spi, buffer, delay)
+        })?;
+        self.cache(buffer);
+        Ok(())
+    }
+
+    /// Sends a part of the frame, see [`WaveshareDisplay::update_partial_frame`]
+    ///
+    /// The cache isn't updated: a recovery sends the last full frame again, then the part.
+    #[allow(clippy::too_many_arguments)]
+    pub fn update_partial_frame<SPI, BUSY, DC, RST, DELAY>(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+        buffer: &[u8],
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+    ) -> Result<(), EpdError<SPI::Error>>
+    where
+        EPD: WaveshareDisplay<SPI, BUSY, DC, RST, DELAY>,
+        SPI: SpiDevice,
+        BUSY: BusyPin,
+        DC: OutputPin,
+        RST: OutputPin,
+        DELAY: DelayNs,
+    {
+        self.run(spi, delay, reinit, |epd, spi, delay| {
+            epd.update_partial_frame(spi, delay, buffer, x, y, width, height)
+        })
+    }
+
+    /// Refreshes the panel, see [`WaveshareDisplay::display_frame`]
+    pub fn display_frame<SPI, BUSY, DC, RST, DELAY>(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>>
+    where
+        EPD: WaveshareDisplay<SPI, BUSY, DC, RST, DELAY>,
+        SPI: SpiDevice,
+        BUSY: BusyPin,
+        DC: OutputPin,
+        RST: OutputPin,
+        DELAY: DelayNs,
+    {
+        self.run(spi, delay, reinit, |epd, spi, delay| {
+            epd.display_frame(spi, delay)
+        })
+    }
+
+    /// Sends a full frame and refreshes the panel, see
+    /// [`WaveshareDisplay::update_and_display_frame`]
+    pub fn update_and_display_frame<SPI, BUSY, DC, RST, DELAY>(
+        &mut self,
+        spi: &mut SPI,
+        buffer: &[u8],
+        delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>>
+    where
+        EPD: WaveshareDisplay<SPI, BUSY, DC, RST, DELAY>,
+        SPI: SpiDevice,
+        BUSY: BusyPin,
+        DC: OutputPin,
+        RST: OutputPin,
+        DELAY: DelayNs,
+    {
+        self.run(spi, delay, reinit, |epd, spi, delay| {
+            epd.update_and_display_frame(spi, buffer, delay)
+        })?;
+        self.cache(buffer);
+        Ok(())
+    }
+
+    /// Clears the frame, see [`WaveshareDisplay::clear_frame`]
+    pub fn clear_frame<SPI, BUSY, DC, RST, DELAY>(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>>
+    where
+        EPD: WaveshareDisplay<SPI, BUSY, DC, RST, DELAY>,
+        SPI: SpiDevice,
+        BUSY: BusyPin,
+        DC: OutputPin,
+        RST: OutputPin,
+        DELAY: DelayNs,
+    {
+        self.cached = false;
+        self.run(spi, delay, reinit, |epd, spi, delay| {
+            epd.clear_frame(spi, delay)
+        })
+    }
+}
+
+/// Resets and initializes the controller again, then sends `frame` back to its RAM
+fn reinit<EPD, SPI, BUSY, DC, RST, DELAY>(
+    epd: &mut EPD,
+    spi: &mut SPI,
+    delay: &mut DELAY,
+    frame: Option<&[u8]>,
+) -> Result<(), EpdError<SPI::Error>>
+where
+    EPD: WaveshareDisplay<SPI, BUSY, DC, RST, DELAY>,
+    SPI: SpiDevice,
+    BUSY: BusyPin,
+    DC: OutputPin,
+    RST: OutputPin,
+    DELAY: DelayNs,
+{
+    epd.wake_up(spi, delay)?;
+    match frame {
+        Some(frame) => epd.update_frame(spi, frame, delay),
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::cell::Cell;
+
+    #[test]
+    fn retries() {
+        let mut cache = [0; 2];
+        // the driver counts the calls timing out before it works again
+        let mut recovering = Recovering::new(2u32, &mut cache);
+        recovering.cache(&[1, 2]);
+        let restored = Cell::new(None);
+        let recover = |_: &mut u32, _: &mut (), _: &mut (), frame: Option<&[u8]>| {
+            restored.set(frame.map(|frame| frame[1]));
+            Ok(())
+        };
+        let op = |failures: &mut u32, _: &mut (), _: &mut ()| match *failures {
+            0 => Ok(()),
+            _ => {
+                *failures -= 1;
+                Err(EpdError::<()>::BusyTimeout)
+            }
+        };
+        assert_eq!(recovering.run(&mut (), &mut (), recover, op), Ok(()));
+        assert_eq!(recovering.recoveries(), 2);
+        assert_eq!(restored.get(), Some(2));
+
+        // the retries are exhausted
+        *recovering.epd_mut() = 3;
+        assert_eq!(
+            recovering.run(&mut (), &mut (), recover, op),
+            Err(EpdError::BusyTimeout)
+        );
+        assert_eq!(recovering.recoveries(), 4);
+
+        // a partial frame isn't cached
+        recovering.cache(&[1]);
+        restored.set(None);
+        *recovering.epd_mut() = 1;
+        assert_eq!(recovering.run(&mut (), &mut (), recover, op), Ok(()));
+        assert_eq!(restored.get(), None);
+    }
+
+    #[test]
+    fn other_errors_not_retried() {
+        let mut cache = [0; 2];
+        let mut recovering = Recovering::new((), &mut cache);
+        let recover = |_: &mut (), _: &mut (), _: &mut (), _: Option<&[u8]>| -> Result<(), _> {
+            panic!("recovered from an error which isn't a timeout")
+        };
+        let op = |_: &mut (), _: &mut (), _: &mut ()| Err(EpdError::<()>::Unsupported);
+        assert_eq!(
+            recovering.run(&mut (), &mut (), recover, op),
+            Err(EpdError::Unsupported)
+        );
+        assert_eq!(recovering.recoveries(), 0);
+    }
+
+    #[cfg(feature = "epd2in9")]
+    #[test]
+    fn buffer_size_not_retried() {
+        use crate::epd2in9::{Epd2in9, HEIGHT, WIDTH};
+        use embedded_hal_mock::eh1::{delay::NoopDelay, digital, spi};
+
+        // nothing is sent, the reset line isn't pulsed
+        let mut spi = spi::Mock::new(&[]);
+        let busy = digital::Mock::new(&[]);
+        let dc = digital::Mock::new(&[]);
+        let rst = digital::Mock::new(&[]);
+        let mut delay = NoopDelay::new();
+        let epd = Epd2in9::new_uninit(busy, dc, rst, None);
+
+        let mut cache = [0xff; WIDTH as usize / 8 * HEIGHT as usize];
+        let mut recovering = Recovering::new(epd, &mut cache);
+        assert_eq!(
+            recovering.update_frame(&mut spi, &[0; 10], &mut delay),
+            Err(EpdError::BufferSize {
+                expected: WIDTH as usize / 8 * HEIGHT as usize,
+                actual: 10,
+            })
+        );
+        assert_eq!(recovering.recoveries(), 0);
+
+        let (mut busy, mut dc, mut rst) = recovering.into_inner().release();
+        spi.done();
+        busy.done();
+        dc.done();
+        rst.done();
+    }
+}
@@ -3,6 +3,7 @@ use core::cmp;
 
 /// A rectangle
 #[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct Rect {
     /// Origin X
     pub x: u32,
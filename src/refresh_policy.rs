@@ -0,0 +1,326 @@
+//! Scheduling of the full refreshes cleaning the ghosts of the quick ones
+//!
+//! Quick refreshes leave a little of the previous image behind, and a display only refreshed
+//! quickly for hours ends up with every past image on it. [`Scheduled`] wraps a driver and picks
+//! the LUT of each refresh following a [`RefreshPolicy`]: quick refreshes, and a full one after
+//! a number of quick ones in a row or once the last full one gets too old.
+//!
+//!```rust, no_run
+//!# use embedded_hal_mock::eh1::*;
+//!# fn main() -> Result<(), epd_waveshare::error::EpdError<embedded_hal::spi::ErrorKind>> {
+//!use epd_waveshare::{epd2in9_v2::*, prelude::*, refresh_policy::*};
+//!#
+//!# let expectations = [];
+//!# let mut spi = spi::Mock::new(&expectations);
+//!# let expectations = [];
+//!# let busy_in = digital::Mock::new(&expectations);
+//!# let dc = digital::Mock::new(&expectations);
+//!# let rst = digital::Mock::new(&expectations);
+//!# let mut delay = delay::NoopDelay::new();
+//!# fn seconds() -> u32 { 0 }
+//!
+//!let epd = Epd2in9::new(&mut spi, busy_in, dc, rst, &mut delay, None)?;
+//!// `seconds` reads a clock of the MCU
+//!let policy = RefreshPolicy {
+//!    full_every: Some(10),
+//!    full_after_s: Some(3600),
+//!};
+//!let mut epd = Scheduled::new(epd).with_policy(policy).with_clock(seconds);
+//!
+//!let frame = [0xff; WIDTH as usize / 8 * HEIGHT as usize];
+//!// a full refresh, then quick ones until the tenth or until an hour has passed
+//!for _ in 0..12 {
+//!    epd.update_and_display_frame(&mut spi, &frame, &mut delay)?;
+//!}
+//!# Ok(())
+//!# }
+//!```
+
+use crate::error::EpdError;
+use crate::pin::BusyPin;
+use crate::traits::{RefreshLut, WaveshareDisplay};
+use embedded_hal::{delay::*, digital::*, spi::SpiDevice};
+
+/// When [`Scheduled`] does a full refresh instead of a quick one
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct RefreshPolicy {
+    /// Number of quick refreshes in a row after which the next one is full (5)
+    pub full_every: Option<u32>,
+    /// Age in seconds of the last full refresh after which the next one is full, only with a
+    /// clock (none)
+    pub full_after_s: Option<u32>,
+}
+
+impl Default for RefreshPolicy {
+    fn default() -> Self {
+        RefreshPolicy {
+            full_every: Some(5),
+            full_after_s: None,
+        }
+    }
+}
+
+/// Count of the quick refreshes since the last full one, telling when a [`RefreshPolicy`] wants
+/// a full refresh
+///
+/// [`Scheduled`] keeps one, as do [`Digits`](crate::digits::Digits) and
+/// [`Strict`](crate::strict::Strict).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct RefreshCounter {
+    policy: RefreshPolicy,
+    quick_refreshes: u32,
+    /// Time of the last full refresh, if there was one
+    last_full: Option<u32>,
+}
+
+impl RefreshCounter {
+    /// Creates a counter which doesn't know of any full refresh yet
+    pub(crate) fn new(policy: RefreshPolicy) -> Self {
+        RefreshCounter {
+            policy,
+            quick_refreshes: 0,
+            last_full: None,
+        }
+    }
+
+    pub(crate) fn set_policy(&mut self, policy: RefreshPolicy) {
+        self.policy = policy;
+    }
+
+    pub(crate) fn quick_refreshes(&self) -> u32 {
+        self.quick_refreshes
+    }
+
+    /// Makes the next refresh a full one
+    pub(crate) fn force_full(&mut self) {
+        self.last_full = None;
+    }
+
+    /// Whether the quick refreshes in a row reached [`full_every`](RefreshPolicy::full_every)
+    pub(crate) fn too_many_quick(&self) -> bool {
+        self.policy
+            .full_every
+            .is_some_and(|count| self.quick_refreshes >= count)
+    }
+
+    /// LUT of the next refresh, `now` being the time in seconds if there is a clock
+    pub(crate) fn next_lut(&self, now: Option<u32>) -> RefreshLut {
+        let too_old = match (self.last_full, self.policy.full_after_s, now) {
+            (None, _, _) => true,
+            (Some(last), Some(max_s), Some(now)) => now.wrapping_sub(last) >= max_s,
+            _ => false,
+        };
+        match self.too_many_quick() || too_old {
+            true => RefreshLut::Full,
+            false => RefreshLut::Quick,
+        }
+    }
+
+    /// Records a refresh done with `lut` at `now`
+    pub(crate) fn refreshed(&mut self, lut: RefreshLut, now: Option<u32>) {
+        match lut {
+            RefreshLut::Quick => self.quick_refreshes += 1,
+            _ => {
+                self.quick_refreshes = 0;
+                self.last_full = Some(now.unwrap_or(0));
+            }
+        }
+    }
+}
+
+/// Driver wrapper selecting the LUT of each refresh following a [`RefreshPolicy`]
+///
+/// It offers the refreshes of [`WaveshareDisplay`], the other methods being reached with
+/// [`epd`](Scheduled::epd) and [`epd_mut`](Scheduled::epd_mut). The LUT of the driver is set
+/// before the refreshes when it changes, the first refresh being a full one.
+pub struct Scheduled<EPD> {
+    epd: EPD,
+    counter: RefreshCounter,
+    clock: Option<fn() -> u32>,
+    /// LUT selected in the driver, if known
+    lut: Option<RefreshLut>,
+}
+
+impl<EPD> Scheduled<EPD> {
+    /// Wraps `epd` with the default policy, without clock
+    pub fn new(epd: EPD) -> Self {
+        Scheduled {
+            epd,
+            counter: RefreshCounter::new(RefreshPolicy::default()),
+            clock: None,
+            lut: None,
+        }
+    }
+
+    /// Follows `policy` instead of the default one
+    pub fn with_policy(mut self, policy: RefreshPolicy) -> Self {
+        self.counter.set_policy(policy);
+        self
+    }
+
+    /// Reads the time from `clock`, a counter of seconds which may wrap around, for
+    /// [`full_after_s`](RefreshPolicy::full_after_s)
+    pub fn with_clock(mut self, clock: fn() -> u32) -> Self {
+        self.clock = Some(clock);
+        self
+    }
+
+    /// Returns the wrapped driver
+    pub fn epd(&self) -> &EPD {
+        &self.epd
+    }
+
+    /// Returns the wrapped driver, e.g. to send partial frames before a
+    /// [`display_frame`](Scheduled::display_frame)
+    ///
+    /// The LUT must not be changed through it.
+    pub fn epd_mut(&mut self) -> &mut EPD {
+        &mut self.epd
+    }
+
+    /// Unwraps the driver
+    pub fn into_inner(self) -> EPD {
+        self.epd
+    }
+
+    /// Number of quick refreshes since the last full one
+    pub fn quick_refreshes(&self) -> u32 {
+        self.counter.quick_refreshes()
+    }
+
+    /// Makes the next refresh a full one, e.g. after a screen change
+    pub fn force_full(&mut self) {
+        self.counter.force_full();
+    }
+
+    fn now(&self) -> Option<u32> {
+        self.clock.map(|clock| clock())
+    }
+
+    /// LUT of the next refresh
+    pub fn next_lut(&self) -> RefreshLut {
+        self.counter.next_lut(self.now())
+    }
+
+    /// Records a refresh done with `lut`
+    fn refreshed(&mut self, lut: RefreshLut) {
+        let now = self.now();
+        self.counter.refreshed(lut, now);
+    }
+
+    /// Selects the LUT of the next refresh in the driver
+    fn select_lut<SPI, BUSY, DC, RST, DELAY>(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+    ) -> Result<RefreshLut, EpdError<SPI::Error>>
+    where
+        EPD: WaveshareDisplay<SPI, BUSY, DC, RST, DELAY>,
+        SPI: SpiDevice,
+        BUSY: BusyPin,
+        DC: OutputPin,
+        RST: OutputPin,
+        DELAY: DelayNs,
+    {
+        let lut = self.next_lut();
+        if self.lut != Some(lut) {
+            // the driver holds something unknown if this fails
+            self.lut = None;
+            self.epd.set_lut(spi, delay, Some(lut))?;
+            self.lut = Some(lut);
+        }
+        Ok(lut)
+    }
+
+    /// Refreshes the panel with the LUT picked by the policy, see
+    /// [`WaveshareDisplay::display_frame`]
+    pub fn display_frame<SPI, BUSY, DC, RST, DELAY>(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>>
+    where
+        EPD: WaveshareDisplay<SPI, BUSY, DC, RST, DELAY>,
+        SPI: SpiDevice,
+        BUSY: BusyPin,
+        DC: OutputPin,
+        RST: OutputPin,
+        DELAY: DelayNs,
+    {
+        let lut = self.select_lut(spi, delay)?;
+        self.epd.display_frame(spi, delay)?;
+        self.refreshed(lut);
+        Ok(())
+    }
+
+    /// Sends a full frame and refreshes the panel with the LUT picked by the policy, see
+    /// [`WaveshareDisplay::update_and_display_frame`]
+    pub fn update_and_display_frame<SPI, BUSY, DC, RST, DELAY>(
+        &mut self,
+        spi: &mut SPI,
+        buffer: &[u8],
+        delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>>
+    where
+        EPD: WaveshareDisplay<SPI, BUSY, DC, RST, DELAY>,
+        SPI: SpiDevice,
+        BUSY: BusyPin,
+        DC: OutputPin,
+        RST: OutputPin,
+        DELAY: DelayNs,
+    {
+        let lut = self.select_lut(spi, delay)?;
+        self.epd.update_and_display_frame(spi, buffer, delay)?;
+        self.refreshed(lut);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn full_every() {
+        let mut scheduled = Scheduled::new(()).with_policy(RefreshPolicy {
+            full_every: Some(2),
+            full_after_s: Some(10),
+        });
+        // no clock, the age is ignored
+        let luts: [RefreshLut; 6] = core::array::from_fn(|_| {
+            let lut = scheduled.next_lut();
+            scheduled.refreshed(lut);
+            lut
+        });
+        assert_eq!(
+            luts.map(|lut| lut == RefreshLut::Full),
+            [true, false, false, true, false, false]
+        );
+
+        scheduled.force_full();
+        assert_eq!(scheduled.next_lut(), RefreshLut::Full);
+    }
+
+    #[test]
+    fn full_after_s() {
+        use core::sync::atomic::{AtomicU32, Ordering};
+
+        static NOW: AtomicU32 = AtomicU32::new(u32::MAX - 5);
+        fn now() -> u32 {
+            NOW.load(Ordering::Relaxed)
+        }
+        let mut scheduled = Scheduled::new(())
+            .with_policy(RefreshPolicy {
+                full_every: None,
+                full_after_s: Some(10),
+            })
+            .with_clock(now);
+        scheduled.refreshed(RefreshLut::Full);
+        NOW.store(3, Ordering::Relaxed);
+        assert_eq!(scheduled.next_lut(), RefreshLut::Quick);
+        // the clock wrapped around
+        NOW.store(4, Ordering::Relaxed);
+        assert_eq!(scheduled.next_lut(), RefreshLut::Full);
+    }
+}
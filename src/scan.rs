@@ -0,0 +1,104 @@
+//! Scan directions of the SSD16xx panels
+//!
+//! Rebadged and clone glass (D67, T94, ...) is sometimes wired the other way round than the panel
+//! the driver was written for: the image then shows mirrored or upside down. A [`ScanConfig`]
+//! given to the drivers of these controllers fixes it without patching their init code, e.g. with
+//! [`Epd2in9::set_scan_config`](crate::epd2in9_v2::Epd2in9::set_scan_config) or
+//! [`Ssd16xx::set_scan_config`](crate::controller::ssd16xx::Ssd16xx::set_scan_config).
+//!
+//! Flipping the RAM columns keeps the 8 pixels of a byte in their order: draw in a display
+//! buffer with [`BitOrder::LsbFirst`](crate::graphics::BitOrder::LsbFirst) as well to mirror
+//! the image pixel by pixel.
+
+/// Direction of one of the scans of the controller
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum ScanDirection {
+    /// The direction of the reference panel
+    #[default]
+    Forward,
+    /// The other way round
+    Reverse,
+}
+
+/// Scan directions of a SSD16xx panel
+///
+/// The default is the configuration of the reference panel.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct ScanConfig {
+    /// Order in which the gates (rows) are scanned, the TB bit of the driver output control:
+    /// `Reverse` shows the image upside down
+    pub gate_scan: ScanDirection,
+    /// Order in which the RAM columns are shifted to the sources, the x direction of the data
+    /// entry mode: `Reverse` mirrors the image horizontally
+    pub source_shift: ScanDirection,
+    /// Order in which the rows are written in the RAM, the y direction of the data entry mode:
+    /// `Reverse` shows the image upside down
+    pub row_order: ScanDirection,
+}
+
+impl ScanConfig {
+    /// Last byte of the driver output control command (GD, SM and TB bits)
+    pub(crate) fn driver_output(self) -> u8 {
+        match self.gate_scan {
+            ScanDirection::Forward => 0x00,
+            ScanDirection::Reverse => 0x01,
+        }
+    }
+
+    /// Data of the data entry mode command, the counter being updated in x direction
+    pub(crate) fn data_entry_mode(self) -> u8 {
+        let x_incr = (self.source_shift == ScanDirection::Forward) as u8;
+        let y_incr = (self.row_order == ScanDirection::Forward) as u8;
+        x_incr | y_incr << 1
+    }
+
+    /// RAM column of pixel `x` on a panel `width` pixels wide
+    pub(crate) fn x(self, x: u32, width: u32) -> u32 {
+        match self.source_shift {
+            ScanDirection::Forward => x,
+            ScanDirection::Reverse => width - 1 - x.min(width - 1),
+        }
+    }
+
+    /// RAM row of line `y` on a panel `height` lines high
+    pub(crate) fn y(self, y: u32, height: u32) -> u32 {
+        match self.row_order {
+            ScanDirection::Forward => y,
+            ScanDirection::Reverse => height - 1 - y.min(height - 1),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scan_config_bytes() {
+        let config = ScanConfig::default();
+        assert_eq!(config.driver_output(), 0x00);
+        assert_eq!(config.data_entry_mode(), 0x03);
+        assert_eq!(config.x(8, 128), 8);
+
+        let config = ScanConfig {
+            gate_scan: ScanDirection::Reverse,
+            source_shift: ScanDirection::Reverse,
+            row_order: ScanDirection::Forward,
+        };
+        assert_eq!(config.driver_output(), 0x01);
+        assert_eq!(config.data_entry_mode(), 0x02);
+        // the first byte of the row is written at the last column, backwards
+        assert_eq!(config.x(0, 128) >> 3, 15);
+        assert_eq!(config.x(127, 128) >> 3, 0);
+        assert_eq!(config.y(0, 296), 0);
+
+        let config = ScanConfig {
+            row_order: ScanDirection::Reverse,
+            ..Default::default()
+        };
+        assert_eq!(config.data_entry_mode(), 0x01);
+        assert_eq!(config.y(0, 296), 295);
+    }
+}
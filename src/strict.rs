@@ -0,0 +1,456 @@
+//! Checks of the sequencing and timing rules of the panels, for development
+//!
+//! The controllers accept commands sent in the wrong order or too early without complaining, and
+//! the result is a panel which stays blank, a ghosted image, or one which wears out early. The
+//! drivers already wait for the controller where the datasheets require it; [`Strict`] wraps a
+//! driver to also catch the mistakes of the application using it:
+//!
+//! - sending frames or refreshing while the controller is in deep sleep,
+//! - waking the controller up too soon after it entered deep sleep,
+//! - refreshing more often than the panel is rated for,
+//! - chaining too many quick refreshes without a full one to clean the ghosts.
+//!
+//! A call breaking a rule isn't sent to the driver and returns a [`StrictError`] instead.
+//!
+//!```rust, no_run
+//!# use embedded_hal_mock::eh1::*;
+//!# fn main() -> Result<(), epd_waveshare::strict::StrictError<epd_waveshare::error::EpdError<embedded_hal::spi::ErrorKind>>> {
+//!use epd_waveshare::{epd2in9_v2::*, prelude::*, strict::*};
+//!#
+//!# let expectations = [];
+//!# let mut spi = spi::Mock::new(&expectations);
+//!# let expectations = [];
+//!# let busy_in = digital::Mock::new(&expectations);
+//!# let dc = digital::Mock::new(&expectations);
+//!# let rst = digital::Mock::new(&expectations);
+//!# let mut delay = delay::NoopDelay::new();
+//!# fn micros() -> u32 { 0 }
+//!
+//!let epd = Epd2in9::new(&mut spi, busy_in, dc, rst, &mut delay, None).map_err(StrictError::Spi)?;
+//!// `micros` reads a timer of the MCU
+//!let mut epd = Strict::new(epd, micros);
+//!
+//!let frame = [0xff; WIDTH as usize / 8 * HEIGHT as usize];
+//!epd.update_and_display_frame(&mut spi, &frame, &mut delay)?;
+//!epd.sleep(&mut spi, &mut delay)?;
+//!// bug: the controller sleeps
+//!assert_eq!(
+//!    epd.update_and_display_frame(&mut spi, &frame, &mut delay),
+//!    Err(StrictError::Asleep)
+//!);
+//!# Ok(())
+//!# }
+//!```
+
+use crate::error::EpdError;
+use crate::pin::BusyPin;
+use crate::refresh_policy::{RefreshCounter, RefreshPolicy};
+use crate::traits::{QuickRefresh, RefreshLut, WaveshareDisplay};
+use embedded_hal::{delay::*, digital::*, spi::SpiDevice};
+
+/// Rules checked by [`Strict`]
+///
+/// The default ones are the recommendations of the Waveshare manuals and examples.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct TimingRules {
+    /// Minimum time between two refreshes in microseconds (180s)
+    pub min_refresh_interval_us: u32,
+    /// Minimum time in deep sleep before waking up in microseconds (2s)
+    pub min_sleep_us: u32,
+    /// Maximum number of quick refreshes in a row, before a full one (5)
+    pub max_quick_refreshes: u32,
+}
+
+impl Default for TimingRules {
+    fn default() -> Self {
+        TimingRules {
+            min_refresh_interval_us: 180_000_000,
+            min_sleep_us: 2_000_000,
+            max_quick_refreshes: 5,
+        }
+    }
+}
+
+/// Policy whose full refreshes are the ones required by `rules`
+fn quick_policy(rules: TimingRules) -> RefreshPolicy {
+    RefreshPolicy {
+        full_every: Some(rules.max_quick_refreshes),
+        full_after_s: None,
+    }
+}
+
+/// Error of a [`Strict`] driver
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum StrictError<SpiError> {
+    /// Error of the driver
+    Spi(SpiError),
+    /// The controller is in deep sleep, [`wake_up`](Strict::wake_up) it first
+    Asleep,
+    /// The controller is woken up only `us` microseconds after entering deep sleep
+    SleepTooShort {
+        /// Time spent in deep sleep
+        us: u32,
+    },
+    /// The panel is refreshed only `us` microseconds after the previous refresh
+    RefreshTooSoon {
+        /// Time since the previous refresh
+        us: u32,
+    },
+    /// Too many quick refreshes in a row, a full refresh is needed
+    TooManyQuickRefreshes,
+}
+
+/// Driver wrapper returning an error when a [rule](TimingRules) is broken
+///
+/// It offers the methods of [`WaveshareDisplay`] and [`QuickRefresh`] which send something to
+/// the controller, the others being reached with [`epd`](Strict::epd) and
+/// [`epd_mut`](Strict::epd_mut).
+pub struct Strict<EPD> {
+    epd: EPD,
+    rules: TimingRules,
+    clock: fn() -> u32,
+    /// Time the controller entered deep sleep, if it sleeps
+    asleep_since: Option<u32>,
+    last_refresh: Option<u32>,
+    counter: RefreshCounter,
+    lut: RefreshLut,
+}
+
+impl<EPD> Strict<EPD> {
+    /// Wraps `epd`, an awake driver, checking the default rules
+    ///
+    /// `clock` is a free running counter of microseconds, which may wrap around.
+    pub fn new(epd: EPD, clock: fn() -> u32) -> Self {
+        Strict {
+            epd,
+            rules: TimingRules::default(),
+            clock,
+            asleep_since: None,
+            last_refresh: None,
+            counter: RefreshCounter::new(quick_policy(TimingRules::default())),
+            lut: RefreshLut::Full,
+        }
+    }
+
+    /// Checks `rules` instead of the default ones
+    pub fn with_rules(mut self, rules: TimingRules) -> Self {
+        self.rules = rules;
+        self.counter.set_policy(quick_policy(rules));
+        self
+    }
+
+    /// Returns the wrapped driver
+    pub fn epd(&self) -> &EPD {
+        &self.epd
+    }
+
+    /// Returns the wrapped driver, e.g. for the calls not part of the traits
+    ///
+    /// These aren't checked.
+    pub fn epd_mut(&mut self) -> &mut EPD {
+        &mut self.epd
+    }
+
+    /// Unwraps the driver
+    pub fn into_inner(self) -> EPD {
+        self.epd
+    }
+
+    fn check_awake<E>(&self) -> Result<(), StrictError<E>> {
+        match self.asleep_since {
+            Some(_) => Err(StrictError::Asleep),
+            None => Ok(()),
+        }
+    }
+
+    fn check_refresh<E>(&self, quick: bool) -> Result<(), StrictError<E>> {
+        self.check_awake()?;
+        if let Some(last) = self.last_refresh {
+            let us = (self.clock)().wrapping_sub(last);
+            if us < self.rules.min_refresh_interval_us {
+                return Err(StrictError::RefreshTooSoon { us });
+            }
+        }
+        if quick && self.counter.too_many_quick() {
+            return Err(StrictError::TooManyQuickRefreshes);
+        }
+        Ok(())
+    }
+
+    /// Records a refresh done, successfully or not
+    fn refreshed<E>(&mut self, quick: bool, result: Result<(), E>) -> Result<(), StrictError<E>> {
+        self.last_refresh = Some((self.clock)());
+        let lut = if quick {
+            RefreshLut::Quick
+        } else {
+            RefreshLut::Full
+        };
+        self.counter.refreshed(lut, None);
+        result.map_err(StrictError::Spi)
+    }
+
+    /// Lets the controller enter deep sleep, see [`WaveshareDisplay::sleep`]
+    pub fn sleep<SPI, BUSY, DC, RST, DELAY>(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+    ) -> Result<(), StrictError<EpdError<SPI::Error>>>
+    where
+        EPD: WaveshareDisplay<SPI, BUSY, DC, RST, DELAY>,
+        SPI: SpiDevice,
+        BUSY: BusyPin,
+        DC: OutputPin,
+        RST: OutputPin,
+        DELAY: DelayNs,
+    {
+        self.check_awake()?;
+        self.epd.sleep(spi, delay).map_err(StrictError::Spi)?;
+        self.asleep_since = Some((self.clock)());
+        Ok(())
+    }
+
+    /// Wakes the controller up, see [`WaveshareDisplay::wake_up`]
+    pub fn wake_up<SPI, BUSY, DC, RST, DELAY>(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+    ) -> Result<(), StrictError<EpdError<SPI::Error>>>
+    where
+        EPD: WaveshareDisplay<SPI, BUSY, DC, RST, DELAY>,
+        SPI: SpiDevice,
+        BUSY: BusyPin,
+        DC: OutputPin,
+        RST: OutputPin,
+        DELAY: DelayNs,
+    {
+        if let Some(since) = self.asleep_since {
+            let us = (self.clock)().wrapping_sub(since);
+            if us < self.rules.min_sleep_us {
+                return Err(StrictError::SleepTooShort { us });
+            }
+        }
+        self.epd.wake_up(spi, delay).map_err(StrictError::Spi)?;
+        self.asleep_since = None;
+        Ok(())
+    }
+
+    /// Sends a full frame, see [`WaveshareDisplay::update_frame`]
+    pub fn update_frame<SPI, BUSY, DC, RST, DELAY>(
+        &mut self,
+        spi: &mut SPI,
+        buffer: &[u8],
+        delay: &mut DELAY,
+    ) -> Result<(), StrictError<EpdError<SPI::Error>>>
+    where
+        EPD: WaveshareDisplay<SPI, BUSY, DC, RST, DELAY>,
+        SPI: SpiDevice,
+        BUSY: BusyPin,
+        DC: OutputPin,
+        RST: OutputPin,
+        DELAY: DelayNs,
+    {
+        self.check_awake()?;
+        self.epd
+            .update_frame(spi, buffer, delay)
+            .map_err(StrictError::Spi)
+    }
+
+    /// Sends a part of the frame, see [`WaveshareDisplay::update_partial_frame`]
+    #[allow(clippy::too_many_arguments)]
+    pub fn update_partial_frame<SPI, BUSY, DC, RST, DELAY>(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+        buffer: &[u8],
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+    ) -> Result<(), StrictError<EpdError<SPI::Error>>>
+    where
+        EPD: WaveshareDisplay<SPI, BUSY, DC, RST, DELAY>,
+        SPI: SpiDevice,
+        BUSY: BusyPin,
+        DC: OutputPin,
+        RST: OutputPin,
+        DELAY: DelayNs,
+    {
+        self.check_awake()?;
+        self.epd
+            .update_partial_frame(spi, delay, buffer, x, y, width, height)
+            .map_err(StrictError::Spi)
+    }
+
+    /// Refreshes the panel with the selected LUT, see [`WaveshareDisplay::display_frame`]
+    pub fn display_frame<SPI, BUSY, DC, RST, DELAY>(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+    ) -> Result<(), StrictError<EpdError<SPI::Error>>>
+    where
+        EPD: WaveshareDisplay<SPI, BUSY, DC, RST, DELAY>,
+        SPI: SpiDevice,
+        BUSY: BusyPin,
+        DC: OutputPin,
+        RST: OutputPin,
+        DELAY: DelayNs,
+    {
+        let quick = self.lut == RefreshLut::Quick;
+        self.check_refresh(quick)?;
+        let result = self.epd.display_frame(spi, delay);
+        self.refreshed(quick, result)
+    }
+
+    /// Sends a full frame and refreshes the panel with the selected LUT, see
+    /// [`WaveshareDisplay::update_and_display_frame`]
+    pub fn update_and_display_frame<SPI, BUSY, DC, RST, DELAY>(
+        &mut self,
+        spi: &mut SPI,
+        buffer: &[u8],
+        delay: &mut DELAY,
+    ) -> Result<(), StrictError<EpdError<SPI::Error>>>
+    where
+        EPD: WaveshareDisplay<SPI, BUSY, DC, RST, DELAY>,
+        SPI: SpiDevice,
+        BUSY: BusyPin,
+        DC: OutputPin,
+        RST: OutputPin,
+        DELAY: DelayNs,
+    {
+        let quick = self.lut == RefreshLut::Quick;
+        self.check_refresh(quick)?;
+        let result = self.epd.update_and_display_frame(spi, buffer, delay);
+        self.refreshed(quick, result)
+    }
+
+    /// Clears the frame, see [`WaveshareDisplay::clear_frame`]
+    pub fn clear_frame<SPI, BUSY, DC, RST, DELAY>(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+    ) -> Result<(), StrictError<EpdError<SPI::Error>>>
+    where
+        EPD: WaveshareDisplay<SPI, BUSY, DC, RST, DELAY>,
+        SPI: SpiDevice,
+        BUSY: BusyPin,
+        DC: OutputPin,
+        RST: OutputPin,
+        DELAY: DelayNs,
+    {
+        self.check_awake()?;
+        self.epd.clear_frame(spi, delay).map_err(StrictError::Spi)
+    }
+
+    /// Selects the LUT of the next refreshes, see [`WaveshareDisplay::set_lut`]
+    pub fn set_lut<SPI, BUSY, DC, RST, DELAY>(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+        refresh_rate: Option<RefreshLut>,
+    ) -> Result<(), StrictError<EpdError<SPI::Error>>>
+    where
+        EPD: WaveshareDisplay<SPI, BUSY, DC, RST, DELAY>,
+        SPI: SpiDevice,
+        BUSY: BusyPin,
+        DC: OutputPin,
+        RST: OutputPin,
+        DELAY: DelayNs,
+    {
+        self.check_awake()?;
+        self.epd
+            .set_lut(spi, delay, refresh_rate)
+            .map_err(StrictError::Spi)?;
+        if let Some(lut) = refresh_rate {
+            self.lut = lut;
+        }
+        Ok(())
+    }
+
+    /// Sends the new frame and refreshes the panel quickly, see
+    /// [`QuickRefresh::update_and_display_new_frame`]
+    pub fn update_and_display_new_frame<SPI, BUSY, DC, RST, DELAY>(
+        &mut self,
+        spi: &mut SPI,
+        buffer: &[u8],
+        delay: &mut DELAY,
+    ) -> Result<(), StrictError<EpdError<SPI::Error>>>
+    where
+        EPD: QuickRefresh<SPI, BUSY, DC, RST, DELAY>,
+        SPI: SpiDevice,
+        BUSY: BusyPin,
+        DC: OutputPin,
+        RST: OutputPin,
+        DELAY: DelayNs,
+    {
+        self.check_refresh(true)?;
+        let result = self.epd.update_and_display_new_frame(spi, buffer, delay);
+        self.refreshed(true, result)
+    }
+
+    /// Refreshes the panel quickly with the new frame, see [`QuickRefresh::display_new_frame`]
+    pub fn display_new_frame<SPI, BUSY, DC, RST, DELAY>(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+    ) -> Result<(), StrictError<EpdError<SPI::Error>>>
+    where
+        EPD: QuickRefresh<SPI, BUSY, DC, RST, DELAY>,
+        SPI: SpiDevice,
+        BUSY: BusyPin,
+        DC: OutputPin,
+        RST: OutputPin,
+        DELAY: DelayNs,
+    {
+        self.check_refresh(true)?;
+        let result = self.epd.display_new_frame(spi, delay);
+        self.refreshed(true, result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::sync::atomic::{AtomicU32, Ordering};
+
+    static NOW: AtomicU32 = AtomicU32::new(0);
+
+    fn clock() -> u32 {
+        NOW.load(Ordering::Relaxed)
+    }
+
+    #[test]
+    fn refresh_rules() {
+        let rules = TimingRules {
+            min_refresh_interval_us: 1000,
+            min_sleep_us: 0,
+            max_quick_refreshes: 2,
+        };
+        let mut strict = Strict::new((), clock).with_rules(rules);
+        NOW.store(u32::MAX - 10, Ordering::Relaxed);
+        assert_eq!(strict.check_refresh::<()>(false), Ok(()));
+        strict.refreshed(false, Ok::<_, ()>(())).unwrap();
+
+        // wraps around
+        NOW.store(500, Ordering::Relaxed);
+        assert_eq!(
+            strict.check_refresh::<()>(false),
+            Err(StrictError::RefreshTooSoon { us: 511 })
+        );
+        for _ in 0..2 {
+            NOW.fetch_add(1000, Ordering::Relaxed);
+            assert_eq!(strict.check_refresh::<()>(true), Ok(()));
+            strict.refreshed(true, Ok::<_, ()>(())).unwrap();
+        }
+        NOW.fetch_add(1000, Ordering::Relaxed);
+        assert_eq!(
+            strict.check_refresh::<()>(true),
+            Err(StrictError::TooManyQuickRefreshes)
+        );
+        assert_eq!(strict.check_refresh::<()>(false), Ok(()));
+
+        strict.asleep_since = Some(0);
+        assert_eq!(strict.check_refresh::<()>(false), Err(StrictError::Asleep));
+    }
+}
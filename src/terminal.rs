@@ -0,0 +1,352 @@
+//! Text console for EPDs
+//!
+//! [`Terminal`] turns a display buffer into a grid of monospaced characters that can be printed
+//! to with `write!`. Only the lines that changed are sent to the EPD and refreshed with the quick
+//! refresh LUT, a full refresh is done every few updates to clean up ghosting.
+//!
+//!```rust, no_run
+//!# use embedded_hal_mock::eh1::*;
+//!# fn main() -> Result<(), epd_waveshare::error::EpdError<embedded_hal::spi::ErrorKind>> {
+//!use core::fmt::Write;
+//!use embedded_graphics::mono_font::ascii::FONT_6X10;
+//!use epd_waveshare::{epd2in9_v2::*, prelude::*, terminal::Terminal};
+//!#
+//!# let expectations = [];
+//!# let mut spi = spi::Mock::new(&expectations);
+//!# let expectations = [];
+//!# let busy_in = digital::Mock::new(&expectations);
+//!# let dc = digital::Mock::new(&expectations);
+//!# let rst = digital::Mock::new(&expectations);
+//!# let mut delay = delay::NoopDelay::new();
+//!
+//!let mut epd = Epd2in9::new(&mut spi, busy_in, dc, rst, &mut delay, None)?;
+//!// 128 / 6 columns and 296 / 10 lines
+//!let mut text = [b' '; 21 * 29];
+//!let mut terminal = Terminal::new(Display2in9::default(), &FONT_6X10, &mut text).unwrap();
+//!
+//!let _ = writeln!(terminal, "Booting...");
+//!terminal.flush(&mut epd, &mut spi, &mut delay)?;
+//!# Ok(())
+//!# }
+//!```
+
+use crate::color::Color;
+use crate::error::EpdError;
+use crate::graphics::{buffer_rows, DisplayBuffer};
+use crate::pin::BusyPin;
+use crate::traits::{RefreshLut, WaveshareDisplay};
+use embedded_graphics::{
+    mono_font::{MonoFont, MonoTextStyleBuilder},
+    prelude::*,
+    primitives::Rectangle,
+    text::{Baseline, Text},
+};
+use embedded_hal::{delay::*, digital::*, spi::SpiDevice};
+
+/// Number of quick refreshes after which a full refresh is done by default
+const DEFAULT_FULL_REFRESH_INTERVAL: u32 = 20;
+
+/// Error of [`Terminal::new`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TerminalError {
+    /// The text buffer is smaller than [`columns()`](Terminal::columns) x
+    /// [`lines()`](Terminal::lines) bytes
+    BufferTooSmall,
+}
+
+/// A text console drawing on a display buffer
+///
+/// The characters are stored in a user provided buffer, which needs to hold at least
+/// [`columns()`](Terminal::columns) x [`lines()`](Terminal::lines) bytes, as given by the size of
+/// the display (with its rotation) and of the font. Only ASCII characters are supported, any other
+/// character is printed as `?`.
+///
+/// Once the last line is full, the text scrolls up by one line.
+pub struct Terminal<'a, D> {
+    display: D,
+    font: &'a MonoFont<'a>,
+    text: &'a mut [u8],
+    columns: usize,
+    lines: usize,
+    /// Cursor position (column, line)
+    cursor: (usize, usize),
+    /// Range of lines changed since the last flush
+    dirty: Option<(usize, usize)>,
+    /// Number of quick refreshes since the last full refresh
+    quick_refreshes: u32,
+    full_refresh_interval: u32,
+}
+
+impl<'a, D> Terminal<'a, D>
+where
+    D: DisplayBuffer<Color = Color>,
+{
+    /// Create a terminal drawing on `display` with `font`
+    ///
+    /// Fails if `text` is too small to hold all the characters of the display.
+    pub fn new(
+        display: D,
+        font: &'a MonoFont<'a>,
+        text: &'a mut [u8],
+    ) -> Result<Self, TerminalError> {
+        let size = display.bounding_box().size;
+        let columns = (size.width / (font.character_size.width + font.character_spacing)) as usize;
+        let lines = (size.height / font.character_size.height) as usize;
+        if text.len() < columns * lines {
+            return Err(TerminalError::BufferTooSmall);
+        }
+        text.fill(b' ');
+
+        let mut terminal = Terminal {
+            display,
+            font,
+            text,
+            columns,
+            lines,
+            cursor: (0, 0),
+            dirty: None,
+            // start with a full refresh to get a clean screen
+            quick_refreshes: DEFAULT_FULL_REFRESH_INTERVAL,
+            full_refresh_interval: DEFAULT_FULL_REFRESH_INTERVAL,
+        };
+        terminal.mark_dirty(0, lines.saturating_sub(1));
+        Ok(terminal)
+    }
+
+    /// Number of characters per line
+    pub fn columns(&self) -> usize {
+        self.columns
+    }
+
+    /// Number of lines
+    pub fn lines(&self) -> usize {
+        self.lines
+    }
+
+    /// Text of a line, padded with spaces
+    pub fn line(&self, line: usize) -> &str {
+        let text = &self.text[line * self.columns..(line + 1) * self.columns];
+        // only ASCII is ever stored
+        core::str::from_utf8(text).unwrap_or_default()
+    }
+
+    /// Set after how many quick refreshes a full refresh is done
+    ///
+    /// 0 means every flush uses a full refresh.
+    pub fn set_full_refresh_interval(&mut self, interval: u32) {
+        self.full_refresh_interval = interval;
+    }
+
+    /// Force the next flush to do a full refresh
+    pub fn request_full_refresh(&mut self) {
+        self.quick_refreshes = self.full_refresh_interval;
+        self.mark_dirty(0, self.lines.saturating_sub(1));
+    }
+
+    /// Clear all the text and put the cursor back in the top left corner
+    pub fn clear(&mut self) {
+        self.text.fill(b' ');
+        self.cursor = (0, 0);
+        self.mark_dirty(0, self.lines.saturating_sub(1));
+    }
+
+    /// Get the display buffer
+    pub fn display(&self) -> &D {
+        &self.display
+    }
+
+    /// Get back the display buffer
+    pub fn release(self) -> D {
+        self.display
+    }
+
+    fn mark_dirty(&mut self, first: usize, last: usize) {
+        self.dirty = Some(match self.dirty {
+            Some((start, end)) => (start.min(first), end.max(last)),
+            None => (first, last),
+        });
+    }
+
+    fn new_line(&mut self) {
+        self.cursor = (0, self.cursor.1 + 1);
+        if self.cursor.1 < self.lines {
+            return;
+        }
+        // scroll everything up by one line
+        self.cursor.1 = self.lines - 1;
+        let len = self.columns * self.lines;
+        self.text.copy_within(self.columns..len, 0);
+        self.text[len - self.columns..len].fill(b' ');
+        self.mark_dirty(0, self.lines - 1);
+    }
+
+    fn put_char(&mut self, c: char) {
+        match c {
+            '\n' => self.new_line(),
+            '\r' => self.cursor.0 = 0,
+            c => {
+                if self.cursor.0 == self.columns {
+                    self.new_line();
+                }
+                let (column, line) = self.cursor;
+                self.text[line * self.columns + column] = if c.is_ascii() && !c.is_ascii_control() {
+                    c as u8
+                } else {
+                    b'?'
+                };
+                self.cursor.0 += 1;
+                self.mark_dirty(line, line);
+            }
+        }
+    }
+
+    /// Draw the changed lines on the display buffer
+    ///
+    /// This is done by [`flush`](Terminal::flush), but can be used alone to draw the text on the
+    /// buffer without sending it to the EPD. Returns the range of lines drawn.
+    pub fn draw(&mut self) -> Option<(usize, usize)> {
+        let (first, last) = self.dirty.take()?;
+        let style = MonoTextStyleBuilder::new()
+            .font(self.font)
+            .text_color(Color::Black)
+            .background_color(Color::White)
+            .build();
+        let line_height = self.font.character_size.height;
+        let width = self.display.bounding_box().size.width;
+        for line in first..=last.min(self.lines - 1) {
+            let top = (line as u32 * line_height) as i32;
+            // the line may not cover the whole width of the display
+            let _ = self.display.fill_solid(
+                &Rectangle::new(Point::new(0, top), Size::new(width, line_height)),
+                Color::White,
+            );
+            let text = &self.text[line * self.columns..(line + 1) * self.columns];
+            let text = core::str::from_utf8(text).unwrap_or_default();
+            let _ = Text::with_baseline(text, Point::new(0, top), style, Baseline::Top)
+                .draw(&mut self.display);
+        }
+        Some((first, last))
+    }
+
+    /// Draw the changed lines and show them on the EPD
+    ///
+    /// The changed lines are sent with a partial update and shown with a quick refresh, unless a
+    /// full refresh is due. When the display is rotated by 90 or 270 degrees, lines are not
+    /// contiguous in the buffer and the whole buffer is sent instead.
+    pub fn flush<SPI, BUSY, DC, RST, DELAY, EPD>(
+        &mut self,
+        epd: &mut EPD,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>>
+    where
+        SPI: SpiDevice,
+        BUSY: BusyPin,
+        DC: OutputPin,
+        RST: OutputPin,
+        DELAY: DelayNs,
+        EPD: WaveshareDisplay<SPI, BUSY, DC, RST, DELAY>,
+    {
+        let (first, last) = match self.draw() {
+            Some(lines) => lines,
+            None => return Ok(()),
+        };
+
+        if self.quick_refreshes >= self.full_refresh_interval {
+            epd.set_lut(spi, delay, Some(RefreshLut::Full))?;
+            epd.update_and_display_frame(spi, self.display.buffer(), delay)?;
+            self.quick_refreshes = 0;
+            return Ok(());
+        }
+
+        epd.set_lut(spi, delay, Some(RefreshLut::Quick))?;
+        let width = self.display.buffer_width();
+        let height = self.display.buffer_height();
+        let line_height = self.font.character_size.height;
+        let (top, bottom) = (first as u32 * line_height, (last as u32 + 1) * line_height);
+        match buffer_rows(self.display.rotation(), height, top, bottom) {
+            Some((top, bottom)) => {
+                let line_bytes = (width as usize + 7) / 8;
+                let band =
+                    &self.display.buffer()[top as usize * line_bytes..bottom as usize * line_bytes];
+                epd.update_partial_frame(spi, delay, band, 0, top, width, bottom - top)?;
+            }
+            None => epd.update_frame(spi, self.display.buffer(), delay)?,
+        }
+        epd.display_frame(spi, delay)?;
+        self.quick_refreshes += 1;
+        Ok(())
+    }
+}
+
+impl<D> core::fmt::Write for Terminal<'_, D>
+where
+    D: DisplayBuffer<Color = Color>,
+{
+    fn write_str(&mut self, s: &str) -> core::fmt::Result {
+        for c in s.chars() {
+            self.put_char(c);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graphics::Display;
+    use core::fmt::Write;
+    use embedded_graphics::mono_font::ascii::FONT_6X10;
+
+    // 4 columns and 3 lines
+    type TestDisplay = Display<24, 30, false, { 24 * 30 / 8 }, Color>;
+
+    #[test]
+    fn terminal_write_and_scroll() {
+        let mut text = [0; 12];
+        let mut terminal = Terminal::new(TestDisplay::default(), &FONT_6X10, &mut text).unwrap();
+        assert_eq!((terminal.columns(), terminal.lines()), (4, 3));
+        assert_eq!(terminal.draw(), Some((0, 2)));
+        assert_eq!(terminal.draw(), None);
+
+        write!(terminal, "ab\ncdefg").unwrap();
+        assert_eq!(terminal.line(0), "ab  ");
+        assert_eq!(terminal.line(1), "cdef");
+        assert_eq!(terminal.line(2), "g   ");
+        assert_eq!(terminal.draw(), Some((0, 2)));
+
+        write!(terminal, "h").unwrap();
+        assert_eq!(terminal.draw(), Some((2, 2)));
+
+        writeln!(terminal, "\né").unwrap();
+        assert_eq!(terminal.line(0), "gh  ");
+        assert_eq!(terminal.line(1), "?   ");
+        assert_eq!(terminal.line(2), "    ");
+        assert_eq!(terminal.draw(), Some((0, 2)));
+    }
+
+    #[test]
+    fn terminal_draw() {
+        let mut text = [0; 12];
+        let mut terminal = Terminal::new(TestDisplay::default(), &FONT_6X10, &mut text).unwrap();
+        terminal.draw();
+        // white background everywhere
+        assert!(terminal.display().buffer().iter().all(|&byte| byte == 0xff));
+
+        write!(terminal, "\n\n|").unwrap();
+        terminal.draw();
+        let buffer = terminal.display().buffer();
+        // only the last line has some black pixels
+        assert!(buffer[..20 * 3].iter().all(|&byte| byte == 0xff));
+        assert!(buffer[20 * 3..].iter().any(|&byte| byte != 0xff));
+    }
+
+    #[test]
+    fn terminal_text_too_small() {
+        let mut text = [0; 11];
+        assert!(matches!(
+            Terminal::new(TestDisplay::default(), &FONT_6X10, &mut text),
+            Err(TerminalError::BufferTooSmall)
+        ));
+    }
+}
@@ -0,0 +1,97 @@
+//! Helpers shared by the tests of the drivers
+
+// only the drivers of the enabled features use them
+#![allow(dead_code)]
+
+extern crate std;
+
+#[cfg(feature = "async")]
+use core::future::Future;
+#[cfg(feature = "async")]
+use core::pin::pin;
+#[cfg(feature = "async")]
+use core::task::{Context, Poll, RawWaker, RawWakerVTable, Waker};
+
+use core::cell::RefCell;
+use core::convert::Infallible;
+use embedded_hal::digital::OutputPin;
+use embedded_hal::spi::{ErrorType, Operation, SpiDevice};
+use std::rc::Rc;
+use std::vec::Vec;
+
+/// Runs `future` to completion, which must not wait: a single poll completes it
+#[cfg(feature = "async")]
+pub(crate) fn block_on<F: Future>(future: F) -> F::Output {
+    const VTABLE: RawWakerVTable = RawWakerVTable::new(
+        |_| RawWaker::new(core::ptr::null(), &VTABLE),
+        |_| {},
+        |_| {},
+        |_| {},
+    );
+    let waker = unsafe { Waker::from_raw(RawWaker::new(core::ptr::null(), &VTABLE)) };
+    match pin!(future).poll(&mut Context::from_waker(&waker)) {
+        Poll::Ready(output) => output,
+        Poll::Pending => panic!("the future is pending"),
+    }
+}
+
+#[derive(Default)]
+struct Stream {
+    data: bool,
+    commands: Vec<(u8, Vec<u8>)>,
+}
+
+/// SPI device and DC pin recording the commands sent to a controller with their data
+///
+/// The clones share the recording: one is the SPI device, another one the DC pin.
+#[derive(Default, Clone)]
+pub(crate) struct Recorder(Rc<RefCell<Stream>>);
+
+impl Recorder {
+    /// Commands sent since the last call with their data
+    pub(crate) fn take(&self) -> Vec<(u8, Vec<u8>)> {
+        core::mem::take(&mut self.0.borrow_mut().commands)
+    }
+}
+
+/// Commands of a recorded stream, without their data
+pub(crate) fn commands(stream: &[(u8, Vec<u8>)]) -> Vec<u8> {
+    stream.iter().map(|(command, _)| *command).collect()
+}
+
+impl ErrorType for Recorder {
+    type Error = Infallible;
+}
+
+impl SpiDevice for Recorder {
+    fn transaction(&mut self, operations: &mut [Operation<'_, u8>]) -> Result<(), Infallible> {
+        let mut stream = self.0.borrow_mut();
+        for operation in operations {
+            if let Operation::Write(words) = operation {
+                for &word in words.iter() {
+                    match (stream.data, stream.commands.last_mut()) {
+                        (true, Some((_, data))) => data.push(word),
+                        _ => stream.commands.push((word, Vec::new())),
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl embedded_hal::digital::ErrorType for Recorder {
+    type Error = Infallible;
+}
+
+impl OutputPin for Recorder {
+    fn set_low(&mut self) -> Result<(), Infallible> {
+        self.0.borrow_mut().data = false;
+        Ok(())
+    }
+
+    fn set_high(&mut self) -> Result<(), Infallible> {
+        self.0.borrow_mut().data = true;
+        Ok(())
+    }
+}
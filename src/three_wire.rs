@@ -0,0 +1,238 @@
+//! 3-wire SPI, without DC pin
+//!
+//! Several panels and HATs can be strapped for 3-wire SPI (BS1 high on the SSD16xx): the DC pin
+//! isn't used, each byte being sent as a 9-bit word whose first bit is the DC bit. The drivers
+//! still send commands and data through an [`SpiDevice`] and a DC pin. [`ThreeWire::split`]
+//! returns both, the SPI device packing the bytes it's given into 9-bit words with the state of
+//! the DC pin.
+//!
+//! The words are packed in bytes, which any 8-bit SPI bus can send. The last byte of each write is
+//! padded with zeros, the controller dropping the incomplete word when the chip select is
+//! released.
+//!
+//! The writes following the first one of a transaction started with DC low are data, so a
+//! command and its data can share a transaction: with
+//! [`in_band_dc`](crate::traits::InterfaceConfig::in_band_dc) set, the drivers send them
+//! together, in the same 9-bit words.
+//!
+//!```rust, no_run
+//!# use embedded_hal_mock::eh1::*;
+//!# fn main() -> Result<(), epd_waveshare::error::EpdError<epd_waveshare::three_wire::ThreeWireError<embedded_hal::spi::ErrorKind>>> {
+//!use epd_waveshare::{epd2in9_v2::*, prelude::*, three_wire::ThreeWire};
+//!#
+//!# let expectations = [];
+//!# let spi = spi::Mock::new(&expectations);
+//!# let expectations = [];
+//!# let busy_in = digital::Mock::new(&expectations);
+//!# let rst = digital::Mock::new(&expectations);
+//!# let mut delay = delay::NoopDelay::new();
+//!
+//!let mut three_wire = ThreeWire::new(spi);
+//!let (mut spi, dc) = three_wire.split();
+//!let mut epd = Epd2in9::new_uninit(busy_in, dc, rst, None);
+//!epd.interface_config_mut().in_band_dc = true;
+//!epd.wake_up(&mut spi, &mut delay)?;
+//!# Ok(())
+//!# }
+//!```
+
+use core::cell::Cell;
+use core::convert::Infallible;
+use embedded_hal::{
+    digital::{self, OutputPin},
+    spi::{self, ErrorKind, Operation, SpiDevice},
+};
+
+/// Number of words packed in each write
+const WORDS_PER_WRITE: usize = 64;
+
+/// Error of a [`ThreeWireSpi`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ThreeWireError<E> {
+    /// Error of the wrapped SPI device
+    Spi(E),
+    /// The operation (a read) isn't supported in 3-wire mode
+    Unsupported,
+}
+
+impl<E: spi::Error> spi::Error for ThreeWireError<E> {
+    fn kind(&self) -> ErrorKind {
+        match self {
+            ThreeWireError::Spi(error) => error.kind(),
+            ThreeWireError::Unsupported => ErrorKind::Other,
+        }
+    }
+}
+
+/// Owner of an SPI device and of the DC state shared by [`ThreeWireSpi`] and [`ThreeWireDc`]
+pub struct ThreeWire<SPI> {
+    spi: SPI,
+    data: Cell<bool>,
+}
+
+impl<SPI: SpiDevice> ThreeWire<SPI> {
+    /// Wrap the SPI device of a panel strapped for 3-wire SPI
+    pub fn new(spi: SPI) -> Self {
+        ThreeWire {
+            spi,
+            data: Cell::new(false),
+        }
+    }
+
+    /// Get the SPI device and DC pin to give to a driver
+    pub fn split(&mut self) -> (ThreeWireSpi<'_, SPI>, ThreeWireDc<'_>) {
+        let ThreeWire { spi, data } = self;
+        (ThreeWireSpi { spi, data }, ThreeWireDc { data })
+    }
+
+    /// Get back the SPI device
+    pub fn release(self) -> SPI {
+        self.spi
+    }
+}
+
+/// SPI device sending 9-bit words, whose DC bit is set by [`ThreeWireDc`]
+///
+/// Writes are sent in transactions of up to 64 words, the chip select being released between
+/// them. The writes following the first one of a transaction are data. Delays inside
+/// transactions are skipped, none of the drivers use them.
+pub struct ThreeWireSpi<'a, SPI> {
+    spi: &'a mut SPI,
+    data: &'a Cell<bool>,
+}
+
+impl<SPI: SpiDevice> spi::ErrorType for ThreeWireSpi<'_, SPI> {
+    type Error = ThreeWireError<SPI::Error>;
+}
+
+impl<SPI: SpiDevice> SpiDevice for ThreeWireSpi<'_, SPI> {
+    fn transaction(&mut self, operations: &mut [Operation<'_, u8>]) -> Result<(), Self::Error> {
+        let mut words = [(false, 0); WORDS_PER_WRITE];
+        let mut count = 0;
+        let mut data = self.data.get();
+        for operation in operations {
+            let bytes = match operation {
+                Operation::Write(bytes) => bytes,
+                Operation::DelayNs(_) => continue,
+                _ => return Err(ThreeWireError::Unsupported),
+            };
+            for &byte in bytes.iter() {
+                words[count] = (data, byte);
+                count += 1;
+                if count == WORDS_PER_WRITE {
+                    self.send(&words)?;
+                    count = 0;
+                }
+            }
+            // the data of a command follows it
+            data = true;
+        }
+        if count > 0 {
+            self.send(&words[..count])?;
+        }
+        Ok(())
+    }
+}
+
+impl<SPI: SpiDevice> ThreeWireSpi<'_, SPI> {
+    /// Sends `words`, their DC bits and bytes, in a single write
+    fn send(&mut self, words: &[(bool, u8)]) -> Result<(), ThreeWireError<SPI::Error>> {
+        let mut packed = [0; WORDS_PER_WRITE * 9 / 8 + 1];
+        let len = pack(words, &mut packed);
+        self.spi.write(&packed[..len]).map_err(ThreeWireError::Spi)
+    }
+}
+
+/// DC pin selecting the DC bit of the words sent by [`ThreeWireSpi`], low for commands
+pub struct ThreeWireDc<'a> {
+    data: &'a Cell<bool>,
+}
+
+impl digital::ErrorType for ThreeWireDc<'_> {
+    type Error = Infallible;
+}
+
+impl OutputPin for ThreeWireDc<'_> {
+    fn set_low(&mut self) -> Result<(), Self::Error> {
+        self.data.set(false);
+        Ok(())
+    }
+
+    fn set_high(&mut self) -> Result<(), Self::Error> {
+        self.data.set(true);
+        Ok(())
+    }
+}
+
+/// Packs `words` in `packed` as 9-bit words starting with their DC bit, most significant bit
+/// first, and returns the number of bytes
+fn pack(words: &[(bool, u8)], packed: &mut [u8]) -> usize {
+    let mut bits = 0u32;
+    let mut bit_count = 0;
+    let mut len = 0;
+    for &(data, word) in words {
+        bits = bits << 9 | u32::from(data) << 8 | u32::from(word);
+        bit_count += 9;
+        while bit_count >= 8 {
+            bit_count -= 8;
+            packed[len] = (bits >> bit_count) as u8;
+            len += 1;
+        }
+    }
+    if bit_count > 0 {
+        // padding, dropped with the incomplete word
+        packed[len] = (bits << (8 - bit_count)) as u8;
+        len += 1;
+    }
+    len
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use embedded_hal_mock::eh1::spi::{Mock, Transaction};
+
+    #[test]
+    fn pack_words() {
+        let mut packed = [0; 10];
+        assert_eq!(pack(&[(false, 0x12)], &mut packed), 2);
+        assert_eq!(packed[..2], [0x09, 0x00]);
+        assert_eq!(pack(&[(true, 0xff)], &mut packed), 2);
+        assert_eq!(packed[..2], [0xff, 0x80]);
+        assert_eq!(pack(&[(false, 0x12), (true, 0x34)], &mut packed), 3);
+        assert_eq!(packed[..3], [0x09, 0x4d, 0x00]);
+        // 8 words fill 9 bytes
+        assert_eq!(pack(&[(true, 0); 8], &mut packed), 9);
+        assert_eq!(
+            packed[..9],
+            [0x80, 0x40, 0x20, 0x10, 0x08, 0x04, 0x02, 0x01, 0x00]
+        );
+    }
+
+    #[test]
+    fn three_wire() {
+        extern crate std;
+        let mut three_wire = ThreeWire::new(Mock::new(&[
+            Transaction::transaction_start(),
+            Transaction::write_vec(std::vec![0x09, 0x00]),
+            Transaction::transaction_end(),
+            Transaction::transaction_start(),
+            Transaction::write_vec(std::vec![0x9a, 0x55, 0x80]),
+            Transaction::transaction_end(),
+            Transaction::transaction_start(),
+            Transaction::write_vec(std::vec![0x09, 0x4d, 0x00]),
+            Transaction::transaction_end(),
+        ]));
+        let (mut spi, mut dc) = three_wire.split();
+        dc.set_low().unwrap();
+        spi.write(&[0x12]).unwrap();
+        dc.set_high().unwrap();
+        spi.write(&[0x34, 0x56]).unwrap();
+        // a command and its data in one transaction
+        dc.set_low().unwrap();
+        spi.transaction(&mut [Operation::Write(&[0x12]), Operation::Write(&[0x34])])
+            .unwrap();
+        assert_eq!(spi.read(&mut [0]), Err(ThreeWireError::Unsupported));
+        three_wire.release().done();
+    }
+}
@@ -1,5 +1,12 @@
+use crate::color::{fill_frame, Color, ColorType};
+use crate::error::EpdError;
+use crate::pin::BusyPin;
 use core::marker::Sized;
 use embedded_hal::{delay::*, digital::*, spi::SpiDevice};
+#[cfg(feature = "async")]
+use embedded_hal_async::{
+    delay::DelayNs as AsyncDelayNs, digital::Wait, spi::SpiDevice as AsyncSpiDevice,
+};
 
 /// All commands need to have this trait which gives the address of the command
 /// which needs to be send via SPI with activated CommandsPin (Data/Command Pin in CommandMode)
@@ -7,8 +14,75 @@ pub(crate) trait Command: Copy {
     fn address(self) -> u8;
 }
 
+/// BUSY pin of the async drivers
+///
+/// Besides being read like the BUSY pins of the blocking drivers, it's awaited until the
+/// controller is idle, so that the MCU sleeps until the BUSY line changes, e.g. on an edge
+/// interrupt, instead of polling it during the refreshes.
+#[cfg(feature = "async")]
+pub trait AsyncBusyPin: BusyPin + Wait {}
+
+#[cfg(feature = "async")]
+impl<PIN: BusyPin + Wait> AsyncBusyPin for PIN {}
+
+/// Timing of the hardware reset, see [`set_reset_timing`](WaveshareDisplay::set_reset_timing)
+///
+/// RST is held high for `high_us`, pulsed low for `low_us`, then the controller is given
+/// `settle_us` to start.
+#[derive(Debug, Clone, PartialEq, Eq, Copy)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct ResetTiming {
+    /// Time RST is high before the pulse in us
+    pub high_us: u32,
+    /// Length of the pulse in us
+    pub low_us: u32,
+    /// Time given to the controller after the pulse in us
+    pub settle_us: u32,
+}
+
+impl ResetTiming {
+    /// No delay, for the boards without RST (see [`NoPin`](crate::pin::NoPin))
+    pub const NONE: Self = ResetTiming {
+        high_us: 0,
+        low_us: 0,
+        settle_us: 0,
+    };
+}
+
+/// Options of the SPI interface of a driver and of its waits for the controller, see
+/// [`interface_config_mut`](WaveshareDisplay::interface_config_mut)
+///
+/// The setters of [`WaveshareDisplay`] describe each option.
+#[derive(Debug, Clone, Copy)]
+pub struct InterfaceConfig {
+    /// Delay between the polls of BUSY in us, see
+    /// [`set_busy_poll`](WaveshareDisplay::set_busy_poll)
+    pub busy_poll_us: u32,
+    /// Longest wait for the controller in us, see
+    /// [`set_busy_timeout`](WaveshareDisplay::set_busy_timeout)
+    pub busy_timeout_us: Option<u32>,
+    /// Called before each poll of BUSY with the time waited in us, see
+    /// [`set_on_busy_wait`](WaveshareDisplay::set_on_busy_wait)
+    pub on_busy_wait: Option<fn(u32)>,
+    /// Timing of the resets, see [`set_reset_timing`](WaveshareDisplay::set_reset_timing)
+    pub reset_timing: Option<ResetTiming>,
+    /// Longest SPI write in bytes, see [`set_max_transfer`](WaveshareDisplay::set_max_transfer)
+    pub max_transfer: Option<usize>,
+    /// Whether the data is written bytewise, see
+    /// [`set_single_byte_write`](WaveshareDisplay::set_single_byte_write)
+    pub single_byte_write: bool,
+    /// Whether the SPI device sends the DC bit itself, `false` by default
+    ///
+    /// The SPI devices of [`ThreeWire`](crate::three_wire::ThreeWire) and `di::DiAdapter` take
+    /// the writes following the first one of a transaction started with DC low as data. A command
+    /// and its data are then sent in a single transaction, bytewise or not. It mustn't be set with
+    /// a real DC pin, which can't change during a transaction.
+    pub in_band_dc: bool,
+}
+
 /// Seperates the different LUT for the Display Refresh process
 #[derive(Debug, Clone, PartialEq, Eq, Copy, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum RefreshLut {
     /// The "normal" full Lookuptable for the Refresh-Sequence
     #[default]
@@ -16,12 +90,39 @@ pub enum RefreshLut {
     /// The quick LUT where not the full refresh sequence is followed.
     /// This might lead to some
     Quick,
+    /// The fast full refresh of the vendor code, about half as long as the full one but leaving
+    /// a little more ghosting. Drivers without one use the full LUT.
+    Fast,
+    /// The LUT showing 4 gray levels, sent as the frames of a [`Gray4`](crate::color::Gray4)
+    /// display. Drivers without one use the full LUT.
+    Gray4,
 }
 
+impl RefreshLut {
+    /// The LUT itself if it is one of the `luts` of a driver, the full one otherwise
+    pub(crate) fn or_full(self, luts: &[RefreshLut]) -> Self {
+        match luts.contains(&self) {
+            true => self,
+            false => RefreshLut::Full,
+        }
+    }
+}
+
+#[maybe_async_cfg::maybe(
+    sync(keep_self),
+    async(
+        feature = "async",
+        idents(
+            SpiDevice(async = "AsyncSpiDevice"),
+            BusyPin(async = "AsyncBusyPin"),
+            DelayNs(async = "AsyncDelayNs")
+        )
+    )
+)]
 pub(crate) trait InternalWiAdditions<SPI, BUSY, DC, RST, DELAY>
 where
     SPI: SpiDevice,
-    BUSY: InputPin,
+    BUSY: BusyPin,
     DC: OutputPin,
     RST: OutputPin,
     DELAY: DelayNs,
@@ -36,15 +137,28 @@ where
     /// This function calls [reset](WaveshareDisplay::reset),
     /// so you don't need to call reset your self when trying to wake your device up
     /// after setting it to sleep.
-    fn init(&mut self, spi: &mut SPI, delay: &mut DELAY) -> Result<(), SPI::Error>;
+    async fn init(&mut self, spi: &mut SPI, delay: &mut DELAY) -> Result<(), EpdError<SPI::Error>>;
 }
 
+#[maybe_async_cfg::maybe(
+    sync(keep_self),
+    async(
+        feature = "async",
+        idents(
+            WaveshareDisplay,
+            SpiDevice(async = "AsyncSpiDevice"),
+            BusyPin(async = "AsyncBusyPin"),
+            DelayNs(async = "AsyncDelayNs")
+        )
+    )
+)]
 /// Functions to interact with three color panels
+#[cfg_attr(feature = "async", allow(async_fn_in_trait))]
 pub trait WaveshareThreeColorDisplay<SPI, BUSY, DC, RST, DELAY>:
     WaveshareDisplay<SPI, BUSY, DC, RST, DELAY>
 where
     SPI: SpiDevice,
-    BUSY: InputPin,
+    BUSY: BusyPin,
     DC: OutputPin,
     RST: OutputPin,
     DELAY: DelayNs,
@@ -52,45 +166,50 @@ where
     /// Transmit data to the SRAM of the EPD
     ///
     /// Updates both the black and the secondary color layers
-    fn update_color_frame(
+    async fn update_color_frame(
         &mut self,
         spi: &mut SPI,
         delay: &mut DELAY,
         black: &[u8],
         chromatic: &[u8],
-    ) -> Result<(), SPI::Error>;
+    ) -> Result<(), EpdError<SPI::Error>>;
 
     /// Update only the black/white data of the display.
     ///
     /// This must be finished by calling `update_chromatic_frame`.
-    fn update_achromatic_frame(
+    async fn update_achromatic_frame(
         &mut self,
         spi: &mut SPI,
         delay: &mut DELAY,
         black: &[u8],
-    ) -> Result<(), SPI::Error>;
+    ) -> Result<(), EpdError<SPI::Error>>;
 
     /// Update only the chromatic data of the display.
     ///
     /// This should be preceded by a call to `update_achromatic_frame`.
     /// This data takes precedence over the black/white data.
-    fn update_chromatic_frame(
+    async fn update_chromatic_frame(
         &mut self,
         spi: &mut SPI,
         delay: &mut DELAY,
         chromatic: &[u8],
-    ) -> Result<(), SPI::Error>;
+    ) -> Result<(), EpdError<SPI::Error>>;
 }
 
 /// All the functions to interact with the EPDs
 ///
 /// This trait includes all public functions to use the EPDs
 ///
+/// With the `async` feature, every driver has an async variant (`Epd1in54Async` for `Epd1in54`)
+/// implementing `WaveshareDisplayAsync`, the same functions for the `embedded-hal-async` SPI and
+/// delay traits.
+/// Their BUSY pin is an `AsyncBusyPin`, awaited instead of polled.
+///
 /// # Example
 ///
 ///```rust, no_run
 ///# use embedded_hal_mock::eh1::*;
-///# fn main() -> Result<(), embedded_hal::spi::ErrorKind> {
+///# fn main() -> Result<(), epd_waveshare::error::EpdError<embedded_hal::spi::ErrorKind>> {
 ///use embedded_graphics::{
 ///    pixelcolor::BinaryColor::On as Black, prelude::*, primitives::{Line, PrimitiveStyle},
 ///};
@@ -144,10 +263,22 @@ where
 ///     .into_styled(PrimitiveStyle::with_stroke(Color::Black, 1))
 ///     .draw(&mut *display);
 ///```
+#[maybe_async_cfg::maybe(
+    sync(keep_self),
+    async(
+        feature = "async",
+        idents(
+            SpiDevice(async = "AsyncSpiDevice"),
+            BusyPin(async = "AsyncBusyPin"),
+            DelayNs(async = "AsyncDelayNs")
+        )
+    )
+)]
+#[cfg_attr(feature = "async", allow(async_fn_in_trait))]
 pub trait WaveshareDisplay<SPI, BUSY, DC, RST, DELAY>
 where
     SPI: SpiDevice,
-    BUSY: InputPin,
+    BUSY: BusyPin,
     DC: OutputPin,
     RST: OutputPin,
     DELAY: DelayNs,
@@ -161,26 +292,41 @@ where
     /// Setting it to None means a default value is used.
     ///
     /// This already initialises the device.
-    fn new(
+    async fn new(
         spi: &mut SPI,
         busy: BUSY,
         dc: DC,
         rst: RST,
         delay: &mut DELAY,
         delay_us: Option<u32>,
-    ) -> Result<Self, SPI::Error>
+    ) -> Result<Self, EpdError<SPI::Error>>
+    where
+        Self: Sized;
+
+    /// Creates a new driver like [`new`](Self::new), without initialising the device
+    ///
+    /// [`wake_up`](Self::wake_up) initialises it once the options of the driver are set, e.g. the
+    /// timeout of [`set_busy_timeout`](Self::set_busy_timeout) which the initialisation done by
+    /// `new` waits without. It can be skipped when the device is still initialised, e.g. after a
+    /// reset of the MCU which kept the panel powered.
+    fn new_uninit(busy: BUSY, dc: DC, rst: RST, delay_us: Option<u32>) -> Self
     where
         Self: Sized;
 
     /// Let the device enter deep-sleep mode to save power.
     ///
     /// The deep sleep mode returns to standby with a hardware reset.
-    fn sleep(&mut self, spi: &mut SPI, delay: &mut DELAY) -> Result<(), SPI::Error>;
+    async fn sleep(&mut self, spi: &mut SPI, delay: &mut DELAY)
+        -> Result<(), EpdError<SPI::Error>>;
 
     /// Wakes the device up from sleep
     ///
     /// Also reintialises the device if necessary.
-    fn wake_up(&mut self, spi: &mut SPI, delay: &mut DELAY) -> Result<(), SPI::Error>;
+    async fn wake_up(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>>;
 
     /// Sets the backgroundcolor for various commands like [clear_frame](WaveshareDisplay::clear_frame)
     fn set_background_color(&mut self, color: Self::DisplayColor);
@@ -195,12 +341,12 @@ where
     fn height(&self) -> u32;
 
     /// Transmit a full frame to the SRAM of the EPD
-    fn update_frame(
+    async fn update_frame(
         &mut self,
         spi: &mut SPI,
         buffer: &[u8],
         delay: &mut DELAY,
-    ) -> Result<(), SPI::Error>;
+    ) -> Result<(), EpdError<SPI::Error>>;
 
     /// Transmits partial data to the SRAM of the EPD
     ///
@@ -208,7 +354,7 @@ where
     ///
     /// BUFFER needs to be of size: width / 8 * height !
     #[allow(clippy::too_many_arguments)]
-    fn update_partial_frame(
+    async fn update_partial_frame(
         &mut self,
         spi: &mut SPI,
         delay: &mut DELAY,
@@ -217,25 +363,33 @@ where
         y: u32,
         width: u32,
         height: u32,
-    ) -> Result<(), SPI::Error>;
+    ) -> Result<(), EpdError<SPI::Error>>;
 
     /// Displays the frame data from SRAM
     ///
     /// This function waits until the device isn`t busy anymore
-    fn display_frame(&mut self, spi: &mut SPI, delay: &mut DELAY) -> Result<(), SPI::Error>;
+    async fn display_frame(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>>;
 
     /// Provide a combined update&display and save some time (skipping a busy check in between)
-    fn update_and_display_frame(
+    async fn update_and_display_frame(
         &mut self,
         spi: &mut SPI,
         buffer: &[u8],
         delay: &mut DELAY,
-    ) -> Result<(), SPI::Error>;
+    ) -> Result<(), EpdError<SPI::Error>>;
 
     /// Clears the frame buffer on the EPD with the declared background color
     ///
     /// The background color can be changed with [`WaveshareDisplay::set_background_color`]
-    fn clear_frame(&mut self, spi: &mut SPI, delay: &mut DELAY) -> Result<(), SPI::Error>;
+    async fn clear_frame(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>>;
 
     /// Trait for using various Waveforms from different LUTs
     /// E.g. for partial refreshes
@@ -245,19 +399,125 @@ where
     /// WARNING: Quick Refresh might lead to ghosting-effects/problems with your display. Especially for the 4.2in Display!
     ///
     /// If None is used the old value will be loaded on the LUTs once more
-    fn set_lut(
+    async fn set_lut(
         &mut self,
         spi: &mut SPI,
         delay: &mut DELAY,
         refresh_rate: Option<RefreshLut>,
-    ) -> Result<(), SPI::Error>;
+    ) -> Result<(), EpdError<SPI::Error>>;
 
     /// Wait until the display has stopped processing data
     ///
     /// You can call this to make sure a frame is displayed before goin further
-    fn wait_until_idle(&mut self, spi: &mut SPI, delay: &mut DELAY) -> Result<(), SPI::Error>;
+    async fn wait_until_idle(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>>;
+
+    /// Returns the options of the SPI interface and of the waits for the controller, which the
+    /// setters below change
+    fn interface_config_mut(&mut self) -> &mut InterfaceConfig;
+
+    /// Sets the longest wait for the controller in microseconds, `None` by default
+    ///
+    /// A wait lasting longer, because the panel is disconnected or wedged, returns
+    /// [`EpdError::BusyTimeout`] instead of hanging forever. The time is counted in steps of the
+    /// `delay_us` given to [`new`](WaveshareDisplay::new), 1us each without delay: it's a lower
+    /// bound which must be well above the longest refresh of the panel.
+    fn set_busy_timeout(&mut self, timeout_us: Option<u32>) {
+        self.interface_config_mut().busy_timeout_us = timeout_us;
+    }
+
+    /// Sets the delay between the polls of BUSY in microseconds, replacing the `delay_us` given to
+    /// [`new`](WaveshareDisplay::new), `0` polling without delay
+    ///
+    /// A longer delay lets an RTOS run the other tasks during the refreshes, a shorter one notices
+    /// their end sooner.
+    fn set_busy_poll(&mut self, interval_us: u32) {
+        self.interface_config_mut().busy_poll_us = interval_us;
+    }
+
+    /// Sets a function called before each poll of BUSY with the microseconds waited so far,
+    /// `None` by default
+    ///
+    /// The waits for the controller last seconds, the hook can e.g. feed a watchdog or yield to
+    /// the other tasks. Without BUSY, it's called once before the fixed wait.
+    fn set_on_busy_wait(&mut self, hook: Option<fn(u32)>) {
+        self.interface_config_mut().on_busy_wait = hook;
+    }
+
+    /// Sets the timing of the hardware resets done by [`wake_up`](WaveshareDisplay::wake_up),
+    /// `None` for the one of the driver (the default)
+    ///
+    /// Some clone panels need a longer pulse, others start with much shorter ones. The reset
+    /// done by [`new`](WaveshareDisplay::new) uses the timing of the driver: call `wake_up`
+    /// afterwards to reset and initialise the panel again with this one.
+    fn set_reset_timing(&mut self, timing: Option<ResetTiming>) {
+        self.interface_config_mut().reset_timing = timing;
+    }
+
+    /// Sets the longest SPI write in bytes, `None` not splitting the writes
+    ///
+    /// The frames (48KB for the 7.5" panels) are sent in writes of `max_bytes` at most, for the
+    /// platforms limiting the size of the transfers, e.g. the DMA of some HALs. By default, the
+    /// writes are split in 4096 bytes on Linux, the limit of spidev, and not split elsewhere.
+    fn set_max_transfer(&mut self, max_bytes: Option<usize>) {
+        self.interface_config_mut().max_transfer = max_bytes;
+    }
+
+    /// Sets whether the data is written one byte per SPI transaction
+    ///
+    /// Like the vendor code, most drivers release CS after each byte of data, which some panels
+    /// need. The controllers accept the data of a command in a single transaction otherwise,
+    /// `false` cutting the time lost setting up each transfer on the MCUs where it dominates.
+    fn set_single_byte_write(&mut self, single_byte_write: bool) {
+        self.interface_config_mut().single_byte_write = single_byte_write;
+    }
+
+    /// Returns the BUSY, DC and RST pins, e.g. to use them for something else once the panel
+    /// sleeps
+    fn release(self) -> (BUSY, DC, RST)
+    where
+        Self: Sized;
+
+    /// Cleans the ghosts of the previous images by refreshing the whole panel in black then in
+    /// white, `passes` times, as the vendors recommend after many quick refreshes or a long
+    /// storage
+    ///
+    /// The frames are built in `frame`, as long as a full frame, which is left white. The
+    /// refreshes use the selected LUT, which should be the full one.
+    async fn deep_clean(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+        frame: &mut [u8],
+        passes: u8,
+    ) -> Result<(), EpdError<SPI::Error>>
+    where
+        Self::DisplayColor: ColorType + From<Color>,
+    {
+        for _ in 0..passes {
+            for color in [Color::Black, Color::White] {
+                fill_frame(frame, &Self::DisplayColor::from(color));
+                self.update_and_display_frame(spi, frame, delay).await?;
+            }
+        }
+        Ok(())
+    }
 }
 
+#[maybe_async_cfg::maybe(
+    sync(keep_self),
+    async(
+        feature = "async",
+        idents(
+            SpiDevice(async = "AsyncSpiDevice"),
+            BusyPin(async = "AsyncBusyPin"),
+            DelayNs(async = "AsyncDelayNs")
+        )
+    )
+)]
 /// Allows quick refresh support for displays that support it; lets you send both
 /// old and new frame data to support this.
 ///
@@ -269,7 +529,7 @@ where
 /// Example:
 ///```rust, no_run
 ///# use embedded_hal_mock::eh1::*;
-///# fn main() -> Result<(), embedded_hal::spi::ErrorKind> {
+///# fn main() -> Result<(), epd_waveshare::error::EpdError<embedded_hal::spi::ErrorKind>> {
 ///# use embedded_graphics::{
 ///#   pixelcolor::BinaryColor::On as Black, prelude::*, primitives::{Line, PrimitiveStyle},
 ///# };
@@ -303,44 +563,49 @@ where
 ///# Ok(())
 ///# }
 ///```
+#[cfg_attr(feature = "async", allow(async_fn_in_trait))]
 pub trait QuickRefresh<SPI, BUSY, DC, RST, DELAY>
 where
     SPI: SpiDevice,
-    BUSY: InputPin,
+    BUSY: BusyPin,
     DC: OutputPin,
     RST: OutputPin,
     DELAY: DelayNs,
 {
     /// Updates the old frame.
-    fn update_old_frame(
+    async fn update_old_frame(
         &mut self,
         spi: &mut SPI,
         buffer: &[u8],
         delay: &mut DELAY,
-    ) -> Result<(), SPI::Error>;
+    ) -> Result<(), EpdError<SPI::Error>>;
 
     /// Updates the new frame.
-    fn update_new_frame(
+    async fn update_new_frame(
         &mut self,
         spi: &mut SPI,
         buffer: &[u8],
         delay: &mut DELAY,
-    ) -> Result<(), SPI::Error>;
+    ) -> Result<(), EpdError<SPI::Error>>;
 
     /// Displays the new frame
-    fn display_new_frame(&mut self, spi: &mut SPI, _delay: &mut DELAY) -> Result<(), SPI::Error>;
+    async fn display_new_frame(
+        &mut self,
+        spi: &mut SPI,
+        _delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>>;
 
     /// Updates and displays the new frame.
-    fn update_and_display_new_frame(
+    async fn update_and_display_new_frame(
         &mut self,
         spi: &mut SPI,
         buffer: &[u8],
         delay: &mut DELAY,
-    ) -> Result<(), SPI::Error>;
+    ) -> Result<(), EpdError<SPI::Error>>;
 
     /// Updates the old frame for a portion of the display.
     #[allow(clippy::too_many_arguments)]
-    fn update_partial_old_frame(
+    async fn update_partial_old_frame(
         &mut self,
         spi: &mut SPI,
         delay: &mut DELAY,
@@ -349,11 +614,11 @@ where
         y: u32,
         width: u32,
         height: u32,
-    ) -> Result<(), SPI::Error>;
+    ) -> Result<(), EpdError<SPI::Error>>;
 
     /// Updates the new frame for a portion of the display.
     #[allow(clippy::too_many_arguments)]
-    fn update_partial_new_frame(
+    async fn update_partial_new_frame(
         &mut self,
         spi: &mut SPI,
         delay: &mut DELAY,
@@ -362,11 +627,11 @@ where
         y: u32,
         width: u32,
         height: u32,
-    ) -> Result<(), SPI::Error>;
+    ) -> Result<(), EpdError<SPI::Error>>;
 
     /// Clears the partial frame buffer on the EPD with the declared background color
     /// The background color can be changed with [`WaveshareDisplay::set_background_color`]
-    fn clear_partial_frame(
+    async fn clear_partial_frame(
         &mut self,
         spi: &mut SPI,
         delay: &mut DELAY,
@@ -374,5 +639,183 @@ where
         y: u32,
         width: u32,
         height: u32,
-    ) -> Result<(), SPI::Error>;
+    ) -> Result<(), EpdError<SPI::Error>>;
+}
+
+#[maybe_async_cfg::maybe(
+    sync(keep_self),
+    async(
+        feature = "async",
+        idents(
+            WaveshareDisplay,
+            SpiDevice(async = "AsyncSpiDevice"),
+            BusyPin(async = "AsyncBusyPin"),
+            DelayNs(async = "AsyncDelayNs")
+        )
+    )
+)]
+/// Ultra fast 1 bit refreshes for simple animations and scrolling menus
+///
+/// The A2 waveform only drives the pixels between black and white, without any flashing, so
+/// frames sent with [`update_and_display_frame`](WaveshareDisplay::update_and_display_frame)
+/// show up in a fraction of a second. It builds ghosting up quickly though: an animation must
+/// be ended with [`end_animation`](AnimationRefresh::end_animation), which cleans the panel up
+/// with a full refresh of the last frame.
+#[cfg_attr(feature = "async", allow(async_fn_in_trait))]
+pub trait AnimationRefresh<SPI, BUSY, DC, RST, DELAY>:
+    WaveshareDisplay<SPI, BUSY, DC, RST, DELAY>
+where
+    SPI: SpiDevice,
+    BUSY: BusyPin,
+    DC: OutputPin,
+    RST: OutputPin,
+    DELAY: DelayNs,
+{
+    /// Switches the refreshes to the A2 waveform
+    async fn start_animation(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>>;
+
+    /// Switches back to the full waveform and refreshes the current frame with it
+    async fn end_animation(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>>;
+}
+
+#[maybe_async_cfg::maybe(
+    sync(keep_self),
+    async(
+        feature = "async",
+        idents(
+            WaveshareDisplay,
+            SpiDevice(async = "AsyncSpiDevice"),
+            BusyPin(async = "AsyncBusyPin"),
+            DelayNs(async = "AsyncDelayNs")
+        )
+    )
+)]
+/// Refreshes in 4 gray levels
+///
+/// The frames are those of a [`Display`](crate::graphics::Display) of
+/// [`Gray4`](crate::color::Gray4), packed by 4 pixels in a byte. The controller receives them as
+/// two planes of one bit per pixel, in the RAMs of the old and new frames, and a grayscale LUT
+/// drives each pixel following the bits of both planes.
+#[cfg_attr(feature = "async", allow(async_fn_in_trait))]
+pub trait Gray4Refresh<SPI, BUSY, DC, RST, DELAY>:
+    WaveshareDisplay<SPI, BUSY, DC, RST, DELAY>
+where
+    SPI: SpiDevice,
+    BUSY: BusyPin,
+    DC: OutputPin,
+    RST: OutputPin,
+    DELAY: DelayNs,
+{
+    /// Sends a frame of 4 gray levels and refreshes it with the grayscale LUT
+    ///
+    /// The following refreshes use the LUT selected before.
+    async fn update_and_display_gray4_frame(
+        &mut self,
+        spi: &mut SPI,
+        buffer: &[u8],
+        delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>>;
+}
+
+#[maybe_async_cfg::maybe(
+    sync(keep_self),
+    async(
+        feature = "async",
+        idents(
+            WaveshareDisplay,
+            SpiDevice(async = "AsyncSpiDevice"),
+            BusyPin(async = "AsyncBusyPin"),
+            DelayNs(async = "AsyncDelayNs")
+        )
+    )
+)]
+/// Frames written to the controller while the panel is powered down
+///
+/// The controller keeps its RAM and receives frames with the booster and the high voltage rails
+/// off. A battery powered device can send the next frame with the usual
+/// [`update_frame`](WaveshareDisplay::update_frame) whenever it is ready and have the rails on
+/// for the refresh only:
+///
+/// 1. [`power_down`](PreloadFrame::power_down) once the panel was refreshed
+/// 2. `update_frame` or `update_partial_frame`, the panel staying powered down
+/// 3. [`display_preloaded_frame`](PreloadFrame::display_preloaded_frame) later on, which powers
+///    the panel down again afterwards
+#[cfg_attr(feature = "async", allow(async_fn_in_trait))]
+pub trait PreloadFrame<SPI, BUSY, DC, RST, DELAY>:
+    WaveshareDisplay<SPI, BUSY, DC, RST, DELAY>
+where
+    SPI: SpiDevice,
+    BUSY: BusyPin,
+    DC: OutputPin,
+    RST: OutputPin,
+    DELAY: DelayNs,
+{
+    /// Turns the booster and the high voltages off, the controller still accepts frames
+    async fn power_down(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>>;
+
+    /// Powers the panel up, refreshes it with the frame in the RAM and powers it down again
+    async fn display_preloaded_frame(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>>;
+}
+
+/// Refreshes started without waiting for their end
+///
+/// A refresh keeps the controller busy for seconds. Instead of spinning in
+/// [`wait_until_idle`](WaveshareDisplay::wait_until_idle) like
+/// [`display_frame`](WaveshareDisplay::display_frame), the MCU can start it, do other work or
+/// enter a low power mode (e.g. until the BUSY line changes), and poll
+/// [`is_refresh_complete`](NonBlockingRefresh::is_refresh_complete) before sending anything else.
+///
+/// With the `async` feature, the async variants of the drivers implement
+/// `NonBlockingRefreshAsync`, the same functions for the `embedded-hal-async` SPI and delay
+/// traits.
+#[maybe_async_cfg::maybe(
+    sync(keep_self),
+    async(
+        feature = "async",
+        idents(
+            WaveshareDisplay,
+            SpiDevice(async = "AsyncSpiDevice"),
+            BusyPin(async = "AsyncBusyPin"),
+            DelayNs(async = "AsyncDelayNs")
+        )
+    )
+)]
+#[cfg_attr(feature = "async", allow(async_fn_in_trait))]
+pub trait NonBlockingRefresh<SPI, BUSY, DC, RST, DELAY>:
+    WaveshareDisplay<SPI, BUSY, DC, RST, DELAY>
+where
+    SPI: SpiDevice,
+    BUSY: BusyPin,
+    DC: OutputPin,
+    RST: OutputPin,
+    DELAY: DelayNs,
+{
+    /// Starts refreshing the panel with the frame in the RAM and the selected LUT, without
+    /// waiting for the end of the refresh
+    async fn display_frame_nonblocking(
+        &mut self,
+        spi: &mut SPI,
+        delay: &mut DELAY,
+    ) -> Result<(), EpdError<SPI::Error>>;
+
+    /// Returns whether the controller is done with the refresh, reading its BUSY line
+    ///
+    /// Fails with [`EpdError::Pin`] if the BUSY line can't be read.
+    fn is_refresh_complete(&mut self) -> Result<bool, EpdError<SPI::Error>>;
 }
@@ -1,2 +1,8 @@
 pub(crate) mod command;
+// LUTs of the type A drivers without their own
+#[cfg(any(
+    feature = "epd1in54",
+    feature = "epd2in9",
+    feature = "inky_phat_ssd1608"
+))]
 pub(crate) mod constants;
@@ -0,0 +1,256 @@
+//! Custom waveforms of the SSD1680-class controllers
+//!
+//! The controllers of the 1.54" V2 and 2.9" V2 panels (SSD1681, SSD1680) refresh the pixels
+//! following a LUT of [`LUT_LENGTH`] bytes written by the host. Rather than copying the byte
+//! tables of the vendor code, a [`Waveform`] describes it: 12 groups of 4 phases, each phase
+//! driving the pixels with a [`Voltage`] for a number of frames, the groups and the pairs of
+//! phases being repeated. [`to_bytes`](Waveform::to_bytes) compiles it down to the register
+//! table, which [`from_bytes`](Waveform::from_bytes) reads back, e.g. to tune a vendor LUT.
+//!
+//! The drivers load it with `set_waveform`, see
+//! [`Epd1in54::set_waveform`](crate::epd1in54_v2::Epd1in54::set_waveform) and
+//! [`Epd2in9::set_waveform`](crate::epd2in9_v2::Epd2in9::set_waveform).
+//!
+//!```rust, no_run
+//!# use embedded_hal_mock::eh1::*;
+//!# fn main() -> Result<(), epd_waveshare::error::EpdError<embedded_hal::spi::ErrorKind>> {
+//!use epd_waveshare::{epd2in9_v2::*, prelude::*, waveform::*};
+//!use Voltage::*;
+//!#
+//!# let expectations = [];
+//!# let mut spi = spi::Mock::new(&expectations);
+//!# let expectations = [];
+//!# let busy_in = digital::Mock::new(&expectations);
+//!# let dc = digital::Mock::new(&expectations);
+//!# let rst = digital::Mock::new(&expectations);
+//!# let mut delay = delay::NoopDelay::new();
+//!
+//!let mut epd = Epd2in9::new(&mut spi, busy_in, dc, rst, &mut delay, None)?;
+//!// a single short push of the pixels changing color
+//!let waveform = Waveform::new().with_group(
+//!    0,
+//!    Group {
+//!        voltages: [
+//!            [Vss; 4],
+//!            [Vsl, Vss, Vss, Vss],
+//!            [Vsh1, Vss, Vss, Vss],
+//!            [Vss; 4],
+//!            [Vss; 4],
+//!        ],
+//!        frames: [10, 0, 0, 0],
+//!        frame_rate: 2,
+//!        ..Group::default()
+//!    },
+//!);
+//!epd.set_waveform(&mut spi, &mut delay, &waveform)?;
+//!
+//!let frame = [0xff; WIDTH as usize / 8 * HEIGHT as usize];
+//!epd.update_and_display_frame(&mut spi, &frame, &mut delay)?;
+//!# Ok(())
+//!# }
+//!```
+
+/// Length of a LUT: 153 bytes of waveform, then the LUT end option, the gate voltage, the 3
+/// source voltages and VCOM
+pub const LUT_LENGTH: usize = 159;
+
+/// Number of groups of phases of a waveform
+pub const GROUPS: usize = 12;
+
+/// Voltage driving the pixels during a phase
+///
+/// For VCOM, the same values select DCVCOM, DCVCOM + VSH1, DCVCOM + VSL and floating.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Voltage {
+    /// Ground
+    #[default]
+    Vss = 0b00,
+    /// First positive source voltage
+    Vsh1 = 0b01,
+    /// Negative source voltage
+    Vsl = 0b10,
+    /// Second positive source voltage
+    Vsh2 = 0b11,
+}
+
+impl Voltage {
+    fn from_bits(bits: u8) -> Self {
+        match bits & 0b11 {
+            0b00 => Voltage::Vss,
+            0b01 => Voltage::Vsh1,
+            0b10 => Voltage::Vsl,
+            _ => Voltage::Vsh2,
+        }
+    }
+}
+
+/// Group of 4 phases, A to D, of a [`Waveform`]
+///
+/// The counts are those of the registers: a repeat of 0 runs the phases once.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Group {
+    /// Voltages of the phases for the LUTs 0 to 3, picked by the old and new colors of the
+    /// pixels, and for VCOM
+    pub voltages: [[Voltage; 4]; 5],
+    /// Length of the phases in frames, 0 skipping the phase
+    pub frames: [u8; 4],
+    /// Repeats of the phases A and B
+    pub repeat_ab: u8,
+    /// Repeats of the phases C and D
+    pub repeat_cd: u8,
+    /// Repeats of the group
+    pub repeat: u8,
+    /// Frame rate of the group, from 0 to 15
+    pub frame_rate: u8,
+}
+
+/// Typed LUT of the SSD1680-class controllers
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Waveform {
+    /// Groups of phases, run in order (all idle)
+    pub groups: [Group; GROUPS],
+    /// Gate scan selection of the pairs of phases (XON) as in the register (0)
+    pub gate_scan: [u8; 3],
+    /// LUT end option (0x22)
+    pub end_option: u8,
+    /// Gate driving voltage (0x17, 20V)
+    pub gate_voltage: u8,
+    /// Source driving voltages VSH1, VSH2 and VSL (0x41, 0x00, 0x32: 15V, off, -15V)
+    pub source_voltages: [u8; 3],
+    /// VCOM register (0x36, -1.35V)
+    pub vcom: u8,
+}
+
+impl Default for Waveform {
+    fn default() -> Self {
+        Waveform::new()
+    }
+}
+
+impl Waveform {
+    /// Waveform of idle groups, with the voltages of the Waveshare code
+    pub const fn new() -> Self {
+        const IDLE: Group = Group {
+            voltages: [[Voltage::Vss; 4]; 5],
+            frames: [0; 4],
+            repeat_ab: 0,
+            repeat_cd: 0,
+            repeat: 0,
+            frame_rate: 0,
+        };
+        Waveform {
+            groups: [IDLE; GROUPS],
+            gate_scan: [0; 3],
+            end_option: 0x22,
+            gate_voltage: 0x17,
+            source_voltages: [0x41, 0x00, 0x32],
+            vcom: 0x36,
+        }
+    }
+
+    /// Sets the group at `index`, below [`GROUPS`]
+    pub const fn with_group(mut self, index: usize, group: Group) -> Self {
+        self.groups[index] = group;
+        self
+    }
+
+    /// Sets the gate, source and VCOM voltages, see the fields of the same names
+    pub const fn with_voltages(mut self, gate: u8, source: [u8; 3], vcom: u8) -> Self {
+        self.gate_voltage = gate;
+        self.source_voltages = source;
+        self.vcom = vcom;
+        self
+    }
+
+    /// Compiles the waveform to the table written to the controller
+    pub fn to_bytes(&self) -> [u8; LUT_LENGTH] {
+        let mut lut = [0; LUT_LENGTH];
+        for (n, group) in self.groups.iter().enumerate() {
+            for (l, voltages) in group.voltages.iter().enumerate() {
+                lut[l * GROUPS + n] = voltages
+                    .iter()
+                    .fold(0, |byte, voltage| byte << 2 | *voltage as u8);
+            }
+            let [a, b, c, d] = group.frames;
+            lut[60 + n * 7..67 + n * 7].copy_from_slice(&[
+                a,
+                b,
+                group.repeat_ab,
+                c,
+                d,
+                group.repeat_cd,
+                group.repeat,
+            ]);
+            let shift = if n % 2 == 0 { 4 } else { 0 };
+            lut[144 + n / 2] |= (group.frame_rate & 0x0f) << shift;
+        }
+        lut[150..153].copy_from_slice(&self.gate_scan);
+        lut[153] = self.end_option;
+        lut[154] = self.gate_voltage;
+        lut[155..158].copy_from_slice(&self.source_voltages);
+        lut[158] = self.vcom;
+        lut
+    }
+
+    /// Reads a table written to the controller, e.g. one of the vendor code
+    pub fn from_bytes(lut: &[u8; LUT_LENGTH]) -> Self {
+        let mut waveform = Waveform::new();
+        for (n, group) in waveform.groups.iter_mut().enumerate() {
+            for (l, voltages) in group.voltages.iter_mut().enumerate() {
+                let byte = lut[l * GROUPS + n];
+                for (phase, voltage) in voltages.iter_mut().enumerate() {
+                    *voltage = Voltage::from_bits(byte >> (6 - 2 * phase));
+                }
+            }
+            let timing = &lut[60 + n * 7..67 + n * 7];
+            group.frames = [timing[0], timing[1], timing[3], timing[4]];
+            group.repeat_ab = timing[2];
+            group.repeat_cd = timing[5];
+            group.repeat = timing[6];
+            let shift = if n % 2 == 0 { 4 } else { 0 };
+            group.frame_rate = (lut[144 + n / 2] >> shift) & 0x0f;
+        }
+        waveform.gate_scan.copy_from_slice(&lut[150..153]);
+        waveform.end_option = lut[153];
+        waveform.gate_voltage = lut[154];
+        waveform.source_voltages.copy_from_slice(&lut[155..158]);
+        waveform.vcom = lut[158];
+        waveform
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use Voltage::*;
+
+    #[test]
+    fn layout() {
+        let group = Group {
+            voltages: [
+                [Vsl, Vsh1, Vss, Vsh2],
+                [Vss; 4],
+                [Vss; 4],
+                [Vss; 4],
+                [Vsh1, Vss, Vss, Vss],
+            ],
+            frames: [1, 2, 3, 4],
+            repeat_ab: 5,
+            repeat_cd: 6,
+            repeat: 7,
+            frame_rate: 3,
+        };
+        let waveform = Waveform::new().with_group(1, group);
+        let lut = waveform.to_bytes();
+        assert_eq!(lut[1], 0b10_01_00_11);
+        assert_eq!(lut[4 * GROUPS + 1], 0b01_00_00_00);
+        assert_eq!(lut[67..74], [1, 2, 5, 3, 4, 6, 7]);
+        assert_eq!(lut[144], 0x03);
+        assert_eq!(lut[153..], [0x22, 0x17, 0x41, 0x00, 0x32, 0x36]);
+        assert_eq!(lut.iter().filter(|byte| **byte != 0).count(), 2 + 7 + 1 + 5);
+        assert_eq!(Waveform::from_bytes(&lut), waveform);
+    }
+}
@@ -0,0 +1,115 @@
+//! A simple Driver for the WeAct Studio 2.9" B/W E-Paper Module via SPI
+//!
+//! The module has the same SSD1680 controller and resolution as the Waveshare 2.9" V2, but its
+//! panel is wired to other source outputs: driven with the settings of the Waveshare panel the
+//! image is shifted by 8 pixels and mirrored. The waveforms are the ones of the controller OTP.
+//!
+//! The driver is the one of the SSD16xx controllers, see [`crate::controller::ssd16xx`].
+//!
+//! Repository: <https://github.com/WeActStudio/WeActStudio.EpaperModule>
+//!
+//! # Example for the WeAct Studio 2.9" E-Paper Module
+//!
+//!```rust, no_run
+//!# use embedded_hal_mock::eh1::*;
+//!# fn main() -> Result<(), epd_waveshare::error::EpdError<embedded_hal::spi::ErrorKind>> {
+//!use embedded_graphics::{
+//!    pixelcolor::BinaryColor::On as Black, prelude::*, primitives::{Line, PrimitiveStyle},
+//!};
+//!use epd_waveshare::{weact2in9::*, prelude::*};
+//!#
+//!# let expectations = [];
+//!# let mut spi = spi::Mock::new(&expectations);
+//!# let expectations = [];
+//!# let busy_in = digital::Mock::new(&expectations);
+//!# let dc = digital::Mock::new(&expectations);
+//!# let rst = digital::Mock::new(&expectations);
+//!# let mut delay = delay::NoopDelay::new();
+//!
+//!// Setup EPD
+//!let mut epd = WeAct2in9::new(&mut spi, busy_in, dc, rst, &mut delay, None)?;
+//!
+//!// Use display graphics from embedded-graphics
+//!let mut display = DisplayWeAct2in9::default();
+//!
+//!// Use embedded graphics for drawing a line
+//!let _ = Line::new(Point::new(0, 120), Point::new(0, 295))
+//!    .into_styled(PrimitiveStyle::with_stroke(Color::Black, 1))
+//!    .draw(&mut display);
+//!
+//!// Display the frame, also as the base image of quick refreshes
+//!epd.update_old_frame(&mut spi, display.buffer(), &mut delay)?;
+//!epd.display_frame(&mut spi, &mut delay)?;
+//!
+//!// Update image here
+//!
+//!// quick refresh of updated pixels
+//!epd.update_and_display_new_frame(&mut spi, display.buffer(), &mut delay)?;
+//!
+//!// Set the EPD to sleep
+//!epd.sleep(&mut spi, &mut delay)?;
+//!# Ok(())
+//!# }
+//!```
+
+use crate::color::Color;
+use crate::controller::ssd16xx::{Panel, PanelConfig, Ssd16xx};
+
+/// Width of the display in pixels
+pub const WIDTH: u32 = 128;
+/// Height of the display in pixels
+pub const HEIGHT: u32 = 296;
+/// Default Background Color (white)
+pub const DEFAULT_BACKGROUND_COLOR: Color = Color::White;
+
+/// Display with Fullsize buffer for use with the WeAct Studio 2.9" module
+#[cfg(feature = "graphics")]
+pub type DisplayWeAct2in9 = crate::graphics::Display<
+    WIDTH,
+    HEIGHT,
+    false,
+    { crate::graphics::display_len::<Color>(WIDTH, HEIGHT) },
+    Color,
+>;
+
+/// Settings of the WeAct Studio 2.9" panel
+pub struct WeAct2in9Panel;
+
+impl Panel for WeAct2in9Panel {
+    const CONFIG: PanelConfig = PanelConfig {
+        // the panel is connected to the sources S8 to S167
+        update_control_full: Some([0x00, 0x80]),
+        update_control_quick: Some([0x00, 0x80]),
+        ..PanelConfig::new(WIDTH, HEIGHT)
+    };
+}
+
+/// WeAct Studio 2.9" driver
+pub type WeAct2in9<SPI, BUSY, DC, RST, DELAY> = Ssd16xx<WeAct2in9Panel, SPI, BUSY, DC, RST, DELAY>;
+
+/// WeAct Studio 2.9" driver for the async SPI and delay traits
+#[cfg(feature = "async")]
+pub type WeAct2in9Async<SPI, BUSY, DC, RST, DELAY> =
+    crate::controller::ssd16xx::Ssd16xxAsync<WeAct2in9Panel, SPI, BUSY, DC, RST, DELAY>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::controller::ssd16xx::Addressing;
+
+    #[test]
+    fn epd_size() {
+        assert_eq!(WIDTH, 128);
+        assert_eq!(HEIGHT, 296);
+        assert_eq!(DEFAULT_BACKGROUND_COLOR, Color::White);
+    }
+
+    #[test]
+    fn panel_config() {
+        let config = WeAct2in9Panel::CONFIG;
+        assert_eq!((config.width, config.height), (WIDTH, HEIGHT));
+        assert_eq!(config.addressing, Addressing::Bytes);
+        assert_eq!(config.update_control_full, Some([0x00, 0x80]));
+        assert_eq!(config.update_control_quick, Some([0x00, 0x80]));
+    }
+}
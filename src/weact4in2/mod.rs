@@ -0,0 +1,115 @@
+//! A simple Driver for the WeAct Studio 4.2" B/W E-Paper Module via SPI
+//!
+//! The module has an SSD1683 controller, driven like the SSD1680 of the smaller panels, instead
+//! of the UC8176 of the Waveshare 4.2": the drivers of the Waveshare panel don't work with it.
+//! The waveforms are the ones of the controller OTP.
+//!
+//! The driver is the one of the SSD16xx controllers, see [`crate::controller::ssd16xx`].
+//!
+//! Repository: <https://github.com/WeActStudio/WeActStudio.EpaperModule>
+//!
+//! # Example for the WeAct Studio 4.2" E-Paper Module
+//!
+//!```rust, no_run
+//!# use embedded_hal_mock::eh1::*;
+//!# fn main() -> Result<(), epd_waveshare::error::EpdError<embedded_hal::spi::ErrorKind>> {
+//!use embedded_graphics::{
+//!    pixelcolor::BinaryColor::On as Black, prelude::*, primitives::{Line, PrimitiveStyle},
+//!};
+//!use epd_waveshare::{weact4in2::*, prelude::*};
+//!#
+//!# let expectations = [];
+//!# let mut spi = spi::Mock::new(&expectations);
+//!# let expectations = [];
+//!# let busy_in = digital::Mock::new(&expectations);
+//!# let dc = digital::Mock::new(&expectations);
+//!# let rst = digital::Mock::new(&expectations);
+//!# let mut delay = delay::NoopDelay::new();
+//!
+//!// Setup EPD
+//!let mut epd = WeAct4in2::new(&mut spi, busy_in, dc, rst, &mut delay, None)?;
+//!
+//!// Use display graphics from embedded-graphics
+//!let mut display = DisplayWeAct4in2::default();
+//!
+//!// Use embedded graphics for drawing a line
+//!let _ = Line::new(Point::new(0, 120), Point::new(0, 299))
+//!    .into_styled(PrimitiveStyle::with_stroke(Color::Black, 1))
+//!    .draw(&mut display);
+//!
+//!// Display the frame, also as the base image of quick refreshes
+//!epd.update_old_frame(&mut spi, display.buffer(), &mut delay)?;
+//!epd.display_frame(&mut spi, &mut delay)?;
+//!
+//!// Update image here
+//!
+//!// quick refresh of updated pixels
+//!epd.update_and_display_new_frame(&mut spi, display.buffer(), &mut delay)?;
+//!
+//!// Set the EPD to sleep
+//!epd.sleep(&mut spi, &mut delay)?;
+//!# Ok(())
+//!# }
+//!```
+
+use crate::color::Color;
+use crate::controller::ssd16xx::{Panel, PanelConfig, Ssd16xx};
+
+/// Width of the display in pixels
+pub const WIDTH: u32 = 400;
+/// Height of the display in pixels
+pub const HEIGHT: u32 = 300;
+/// Default Background Color (white)
+pub const DEFAULT_BACKGROUND_COLOR: Color = Color::White;
+
+/// Display with Fullsize buffer for use with the WeAct Studio 4.2" module
+#[cfg(feature = "graphics")]
+pub type DisplayWeAct4in2 = crate::graphics::Display<
+    WIDTH,
+    HEIGHT,
+    false,
+    { crate::graphics::display_len::<Color>(WIDTH, HEIGHT) },
+    Color,
+>;
+
+/// Settings of the WeAct Studio 4.2" panel
+pub struct WeAct4in2Panel;
+
+impl Panel for WeAct4in2Panel {
+    const CONFIG: PanelConfig = PanelConfig {
+        // the old frame is ignored by full refreshes, avoiding the ghosts of the previous image
+        update_control_full: Some([0x40, 0x00]),
+        update_control_quick: Some([0x00, 0x00]),
+        ..PanelConfig::new(WIDTH, HEIGHT)
+    };
+}
+
+/// WeAct Studio 4.2" driver
+pub type WeAct4in2<SPI, BUSY, DC, RST, DELAY> = Ssd16xx<WeAct4in2Panel, SPI, BUSY, DC, RST, DELAY>;
+
+/// WeAct Studio 4.2" driver for the async SPI and delay traits
+#[cfg(feature = "async")]
+pub type WeAct4in2Async<SPI, BUSY, DC, RST, DELAY> =
+    crate::controller::ssd16xx::Ssd16xxAsync<WeAct4in2Panel, SPI, BUSY, DC, RST, DELAY>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::controller::ssd16xx::Addressing;
+
+    #[test]
+    fn epd_size() {
+        assert_eq!(WIDTH, 400);
+        assert_eq!(HEIGHT, 300);
+        assert_eq!(DEFAULT_BACKGROUND_COLOR, Color::White);
+    }
+
+    #[test]
+    fn panel_config() {
+        let config = WeAct4in2Panel::CONFIG;
+        assert_eq!((config.width, config.height), (WIDTH, HEIGHT));
+        assert_eq!(config.addressing, Addressing::Bytes);
+        assert_eq!(config.update_control_full, Some([0x40, 0x00]));
+        assert_eq!(config.update_control_quick, Some([0x00, 0x00]));
+    }
+}